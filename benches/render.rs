@@ -0,0 +1,151 @@
+//! Render and interaction benchmarks over the fixture templates in `benches/fixtures`.
+//!
+//! Run with `cargo bench --bench render`; compare against a saved baseline with
+//! `cargo bench --bench render -- --save-baseline main` and `--baseline main`.
+
+use std::hint::black_box;
+
+use component_adaptive_card::{
+    AdaptiveCardInvocation, CardInteraction, CardInteractionType, CardSource, CardSpec,
+    InvocationMode, TelemetryLevel, handle_invocation,
+};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use serde_json::{Value, json};
+
+const SMALL: &str = include_str!("fixtures/small.json");
+const MEDIUM: &str = include_str!("fixtures/medium.json");
+const EXPRESSIONS: &str = include_str!("fixtures/expressions.json");
+
+/// Copies of the medium body stacked into the huge card.
+const HUGE_SECTIONS: usize = 40;
+/// Payload rows bound into every card's paginated container.
+const LINES: usize = 5_000;
+
+/// The medium card's body repeated [`HUGE_SECTIONS`] times, each copy in its own Container
+/// with its ids prefixed so inputs stay unique.
+fn huge_template() -> String {
+    let medium: Value = serde_json::from_str(MEDIUM).expect("medium fixture");
+    let body = serde_json::to_string(&medium["body"]).expect("body");
+    let sections: Vec<Value> = (0..HUGE_SECTIONS)
+        .map(|n| {
+            let items = body.replace("\"id\":\"", &format!("\"id\":\"s{n}-"));
+            json!({
+                "type": "Container",
+                "id": format!("section-{n}"),
+                "items": serde_json::from_str::<Value>(&items).expect("section")
+            })
+        })
+        .collect();
+    json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": sections,
+        "actions": medium["actions"]
+    })
+    .to_string()
+}
+
+fn payload() -> Value {
+    let lines: Vec<Value> = (0..LINES)
+        .map(|n| json!({ "description": format!("Line item {n}"), "quantity": n % 7 + 1 }))
+        .collect();
+    json!({
+        "id": "REQ 1042/7",
+        "title": "Laptop replacement",
+        "summary": "Battery no longer holds a charge.",
+        "amount": 1849.5,
+        "urgent": true,
+        "submitted": "2024-03-18T09:30:00Z",
+        "requester": { "name": "Ada Lovelace", "avatar": "https://example.com/ada.png" },
+        "decisions": [
+            { "title": "Approve", "value": "approve" },
+            { "title": "Reject", "value": "reject" },
+            { "title": "Escalate", "value": "escalate" }
+        ],
+        "notes": "Previous device is four years old.",
+        "lines": lines
+    })
+}
+
+fn invocation(template: &str) -> AdaptiveCardInvocation {
+    AdaptiveCardInvocation {
+        card_source: CardSource::Inline,
+        card_spec: CardSpec {
+            inline_json: Some(serde_json::from_str(template).expect("fixture")),
+            ..Default::default()
+        },
+        payload: payload(),
+        session: json!({ "user": { "name": "Grace Hopper" } }),
+        state: json!({}),
+        mode: InvocationMode::RenderAndValidate,
+        telemetry: TelemetryLevel::Off,
+        ..Default::default()
+    }
+}
+
+fn template_parsing(c: &mut Criterion) {
+    let huge = huge_template();
+    let mut group = c.benchmark_group("parse");
+    for (name, template) in [
+        ("small", SMALL),
+        ("medium", MEDIUM),
+        ("huge", huge.as_str()),
+    ] {
+        group.throughput(Throughput::Bytes(template.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), template, |b, text| {
+            b.iter(|| serde_json::from_str::<Value>(black_box(text)).expect("template"))
+        });
+    }
+    group.finish();
+}
+
+fn expression_evaluation(c: &mut Criterion) {
+    let invocation = invocation(EXPRESSIONS);
+    c.bench_function("expressions", |b| {
+        b.iter(|| handle_invocation(black_box(invocation.clone())).expect("render"))
+    });
+}
+
+fn render(c: &mut Criterion) {
+    let huge = huge_template();
+    let mut group = c.benchmark_group("render");
+    for (name, template) in [
+        ("small", SMALL),
+        ("medium", MEDIUM),
+        ("huge", huge.as_str()),
+    ] {
+        let invocation = invocation(template);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &invocation, |b, inv| {
+            b.iter(|| handle_invocation(black_box(inv.clone())).expect("render"))
+        });
+    }
+    group.finish();
+}
+
+fn interaction(c: &mut Criterion) {
+    let mut invocation = invocation(MEDIUM);
+    invocation.mode = InvocationMode::Simulate;
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "submit".to_string(),
+        verb: None,
+        raw_inputs: json!({ "decision": "approve", "comment": "Approved.", "notify": "true" }),
+        card_instance_id: "bench-card".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    c.bench_function("interaction/submit", |b| {
+        b.iter(|| handle_invocation(black_box(invocation.clone())).expect("interaction"))
+    });
+}
+
+criterion_group!(
+    benches,
+    template_parsing,
+    expression_evaluation,
+    render,
+    interaction
+);
+criterion_main!(benches);
@@ -16,6 +16,10 @@ cargo clippy --workspace --all-targets --target wasm32-wasip2 -- -D warnings
 echo "Running cargo test..."
 cargo test --workspace --all-targets
 
+echo "Running cargo clippy + test (testkit, preview-png)..."
+cargo clippy --workspace --all-targets --features testkit,preview-png -- -D warnings
+cargo test --workspace --all-targets --features testkit,preview-png
+
 echo "Building wasm32-wasip2 (release)..."
 cargo build --target wasm32-wasip2 --release
 
@@ -0,0 +1,367 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{Map, Value};
+
+use crate::expression::truthy;
+use crate::fallback::ELEMENT_KEYS;
+use crate::show_card::EXPANDED_ID_PREFIX;
+
+/// Action declared anywhere in a rendered card (card actions, ActionSets, selectActions,
+/// TextRun inline actions, ShowCard subcards).
+#[derive(Debug, Clone)]
+pub struct ActionDeclaration {
+    pub id: Option<String>,
+    pub kind: String,
+    pub action: Map<String, Value>,
+    /// Whether this is a TextRun's `selectAction`.
+    pub inline: bool,
+}
+
+impl ActionDeclaration {
+    pub fn verb(&self) -> Option<&str> {
+        self.action.get("verb").and_then(|v| v.as_str())
+    }
+
+    pub fn data(&self) -> Option<&Value> {
+        self.action.get("data")
+    }
+}
+
+/// Collects every `Action.*` object in document order.
+pub fn collect_actions(card: &Value) -> Vec<ActionDeclaration> {
+    fn walk(value: &Value, inline: bool, out: &mut Vec<ActionDeclaration>) {
+        match value {
+            Value::Object(map) => {
+                let kind = map.get("type").and_then(|v| v.as_str()).unwrap_or_default();
+                if kind.starts_with("Action.") {
+                    out.push(ActionDeclaration {
+                        id: map.get("id").and_then(|v| v.as_str()).map(str::to_string),
+                        kind: kind.to_string(),
+                        action: map.clone(),
+                        inline,
+                    });
+                }
+                for (key, child) in map {
+                    walk(child, kind == "TextRun" && key == "selectAction", out);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    walk(item, false, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(card, false, &mut out);
+    out
+}
+
+/// Finds the action with the given id.
+pub fn find_action(card: &Value, id: &str) -> Option<ActionDeclaration> {
+    collect_actions(card)
+        .into_iter()
+        .find(|action| action.id.as_deref() == Some(id))
+}
+
+/// Ids of the inputs in `card` that the action `id` does not submit. With `associatedInputs:
+/// "auto"` (the default) an action submits the inputs of the card it sits in and of every
+/// card enclosing it, but not those of ShowCard subcards nested below it; with `"none"` it
+/// submits none. Inline-expanded ShowCards scope like the subcard they render. Empty when
+/// the action is not declared.
+pub fn unassociated_inputs(card: &Value, id: &str) -> BTreeSet<String> {
+    struct Scan<'a> {
+        target: &'a str,
+        /// Input ids declared directly in each card scope; scope 0 is the card itself.
+        scopes: Vec<Vec<String>>,
+        /// Scopes enclosing the target action, and whether it opts out of inputs.
+        found: Option<(Vec<usize>, bool)>,
+    }
+
+    fn walk(value: &Value, chain: &mut Vec<usize>, scan: &mut Scan<'_>) {
+        match value {
+            Value::Object(map) => {
+                let kind = map.get("type").and_then(Value::as_str).unwrap_or_default();
+                let id = map.get("id").and_then(Value::as_str);
+                if kind.starts_with("Input.")
+                    && let Some(id) = id
+                    && let Some(&scope) = chain.last()
+                {
+                    scan.scopes[scope].push(id.to_string());
+                }
+                if kind.starts_with("Action.") && id == Some(scan.target) && scan.found.is_none() {
+                    let none = map
+                        .get("associatedInputs")
+                        .and_then(Value::as_str)
+                        .is_some_and(|mode| mode.eq_ignore_ascii_case("none"));
+                    scan.found = Some((chain.clone(), none));
+                }
+                let expanded =
+                    kind == "Container" && id.is_some_and(|id| id.starts_with(EXPANDED_ID_PREFIX));
+                for (key, child) in map {
+                    let subcard = (kind == "Action.ShowCard" && key == "card")
+                        || (expanded && key == "items");
+                    if subcard {
+                        scan.scopes.push(Vec::new());
+                        chain.push(scan.scopes.len() - 1);
+                        walk(child, chain, scan);
+                        chain.pop();
+                    } else if key != "data" {
+                        walk(child, chain, scan);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    walk(item, chain, scan);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut scan = Scan {
+        target: id,
+        scopes: vec![Vec::new()],
+        found: None,
+    };
+    walk(card, &mut vec![0], &mut scan);
+    let Some((chain, none)) = scan.found else {
+        return BTreeSet::new();
+    };
+    let associated: BTreeSet<&String> = if none {
+        BTreeSet::new()
+    } else {
+        chain
+            .iter()
+            .flat_map(|&scope| &scan.scopes[scope])
+            .collect()
+    };
+    scan.scopes
+        .iter()
+        .flatten()
+        .filter(|input| !associated.contains(input))
+        .cloned()
+        .collect()
+}
+
+/// `ui.disabled_actions` key that disables every action on the card.
+pub const ALL_ACTIONS: &str = "*";
+
+/// Whether `ui.disabled_actions` in `state` disables the action with the given id.
+pub fn is_disabled(state: &Value, id: &str) -> bool {
+    let Some(disabled) = state
+        .get("ui")
+        .and_then(|ui| ui.get("disabled_actions"))
+        .and_then(|v| v.as_object())
+    else {
+        return false;
+    };
+    [id, ALL_ACTIONS]
+        .iter()
+        .any(|key| disabled.get(*key).and_then(|v| v.as_bool()) == Some(true))
+}
+
+/// Applies persisted `ui.disabled_actions` flags and bound `isEnabled` expressions, returning
+/// the ids of the disabled actions. A bound `isEnabled` is enabled when truthy, with the
+/// strings `"false"` and `"0"` counting as false. Cards at version 1.5 or later get
+/// `isEnabled: false` (and a boolean `isEnabled` on every action that declares one); older
+/// cards have the disabled actions removed since hosts ignore `isEnabled`.
+pub fn apply_disabled_actions(card: &mut Value, state: &Value) -> BTreeSet<String> {
+    fn disabled_action(map: &Map<String, Value>, state: &Value) -> bool {
+        map.get("type")
+            .and_then(|v| v.as_str())
+            .is_some_and(|kind| kind.starts_with("Action."))
+            && (map
+                .get("isEnabled")
+                .is_some_and(|enabled| !enabled_value(enabled))
+                || is_disabled(
+                    state,
+                    map.get("id").and_then(|v| v.as_str()).unwrap_or_default(),
+                ))
+    }
+
+    fn disabled(value: &Value, state: &Value, ids: &mut BTreeSet<String>) -> bool {
+        let Some(map) = value.as_object() else {
+            return false;
+        };
+        let disabled = disabled_action(map, state);
+        if disabled && let Some(id) = map.get("id").and_then(Value::as_str) {
+            ids.insert(id.to_string());
+        }
+        disabled
+    }
+
+    fn walk(
+        value: &mut Value,
+        state: &Value,
+        supports_is_enabled: bool,
+        ids: &mut BTreeSet<String>,
+    ) {
+        match value {
+            Value::Object(map) => {
+                if supports_is_enabled {
+                    if disabled_action(map, state) {
+                        if let Some(id) = map.get("id").and_then(Value::as_str) {
+                            ids.insert(id.to_string());
+                        }
+                        map.insert("isEnabled".into(), Value::Bool(false));
+                    } else if map.contains_key("isEnabled") {
+                        map.insert("isEnabled".into(), Value::Bool(true));
+                    }
+                } else if map
+                    .get("selectAction")
+                    .is_some_and(|action| disabled(action, state, ids))
+                {
+                    map.remove("selectAction");
+                }
+                for child in map.values_mut() {
+                    walk(child, state, supports_is_enabled, ids);
+                }
+            }
+            Value::Array(items) => {
+                if !supports_is_enabled {
+                    items.retain(|item| !disabled(item, state, ids));
+                }
+                for item in items {
+                    walk(item, state, supports_is_enabled, ids);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let supports_is_enabled = card
+        .get("version")
+        .and_then(|v| v.as_str())
+        .and_then(|v| {
+            let (major, minor) = v.split_once('.')?;
+            Some((major.parse::<u32>().ok()?, minor.parse::<u32>().ok()?))
+        })
+        .is_some_and(|version| version >= (1, 5));
+    let mut ids = BTreeSet::new();
+    walk(card, state, supports_is_enabled, &mut ids);
+    ids
+}
+
+fn enabled_value(value: &Value) -> bool {
+    match value {
+        Value::String(text) => {
+            let text = text.trim();
+            !(text.eq_ignore_ascii_case("false") || text == "0") && truthy(value)
+        }
+        other => truthy(other),
+    }
+}
+
+/// Elements whose `selectAction` is honoured by hosts.
+pub const SELECT_ACTION_HOSTS: &[&str] = &[
+    "Container",
+    "ColumnSet",
+    "Column",
+    "Image",
+    "TableCell",
+    "TableRow",
+];
+
+/// Types that never get a generated id: inputs must be named by their author (their id is
+/// the submitted key), and TextRuns and nested cards take no id.
+const UNNAMED_TYPES: &[&str] = &["AdaptiveCard", "TextRun"];
+
+/// Actions that post back to the component, routed by id wherever they sit.
+const ROUTED_TYPES: &[&str] = &["Action.Submit", "Action.Execute"];
+
+/// Gives elements and actions authored without an `id` a deterministic one, `<type>-<hash>`
+/// (e.g. `action-submit-1f3a9c0e`), hashed from the template hash and the element's JSON
+/// pointer, so the same template always yields the same ids. Only actions that need an id
+/// to be routed get one (Submit and Execute actions, `selectAction`s, including TextRun
+/// inline actions, and ActionSet actions) unless `all` is set, in which case every element
+/// and action but inputs does.
+pub fn assign_generated_ids(card: &mut Value, template_hash: &str, all: bool) {
+    fn walk(value: &mut Value, pointer: &str, routed: bool, template_hash: &str, all: bool) {
+        match value {
+            Value::Object(map) => {
+                let kind = map
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+                if !kind.is_empty()
+                    && (routed || all || ROUTED_TYPES.contains(&kind.as_str()))
+                    && !kind.starts_with("Input.")
+                    && !UNNAMED_TYPES.contains(&kind.as_str())
+                    && !map.contains_key("id")
+                {
+                    map.insert(
+                        "id".into(),
+                        Value::String(generated_id(&kind, pointer, template_hash)),
+                    );
+                }
+                for key in ELEMENT_KEYS {
+                    let Some(child) = map.get_mut(*key) else {
+                        continue;
+                    };
+                    let pointer = format!("{pointer}/{key}");
+                    match (*key, child) {
+                        ("actions", Value::Array(items)) if kind == "ActionSet" => {
+                            for (idx, item) in items.iter_mut().enumerate() {
+                                walk(item, &format!("{pointer}/{idx}"), true, template_hash, all);
+                            }
+                        }
+                        (key, child) => {
+                            walk(child, &pointer, key == "selectAction", template_hash, all)
+                        }
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter_mut().enumerate() {
+                    walk(item, &format!("{pointer}/{idx}"), false, template_hash, all);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    walk(card, "", false, template_hash, all);
+}
+
+fn generated_id(kind: &str, pointer: &str, template_hash: &str) -> String {
+    let slug = kind.to_ascii_lowercase().replace('.', "-");
+    let hash = blake3::hash(format!("{template_hash}{pointer}").as_bytes());
+    format!("{slug}-{}", &hash.to_hex()[..8])
+}
+
+/// JSON pointer of every element and action id in the card, keyed by id. Unused `fallback`
+/// subtrees are not included.
+pub fn element_ids(card: &Value) -> BTreeMap<String, String> {
+    fn walk(value: &Value, pointer: &str, ids: &mut BTreeMap<String, String>) {
+        match value {
+            Value::Object(map) => {
+                if map.contains_key("type")
+                    && let Some(id) = map.get("id").and_then(Value::as_str)
+                {
+                    ids.entry(id.to_string())
+                        .or_insert_with(|| pointer.to_string());
+                }
+                for key in ELEMENT_KEYS {
+                    if let Some(child) = map.get(*key) {
+                        walk(child, &format!("{pointer}/{key}"), ids);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    walk(item, &format!("{pointer}/{idx}"), ids);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut ids = BTreeMap::new();
+    walk(card, "", &mut ids);
+    ids
+}
@@ -0,0 +1,34 @@
+use serde_json::Value;
+
+use crate::model::{BotActivity, BotActivityOptions, CardAttachment};
+
+/// Attachment content type of Adaptive Cards.
+pub const ADAPTIVE_CARD_CONTENT_TYPE: &str = "application/vnd.microsoft.card.adaptive";
+
+/// Wraps `card` in a message activity with the card as its only attachment. The card gets a
+/// `fallbackText` (the options', or plain `speech` text) unless it declares one.
+pub fn build_activity(
+    card: &Value,
+    options: &BotActivityOptions,
+    speech: Option<&str>,
+) -> BotActivity {
+    let mut content = card.clone();
+    let fallback = options.fallback_text.clone().or_else(|| {
+        speech
+            .filter(|text| !text.starts_with("<speak"))
+            .map(str::to_string)
+    });
+    if let (Some(root), Some(fallback)) = (content.as_object_mut(), fallback)
+        && !root.contains_key("fallbackText")
+    {
+        root.insert("fallbackText".into(), Value::String(fallback));
+    }
+    BotActivity {
+        activity_type: "message".into(),
+        summary: options.summary.clone(),
+        attachments: vec![CardAttachment {
+            content_type: ADAPTIVE_CARD_CONTENT_TYPE.into(),
+            content,
+        }],
+    }
+}
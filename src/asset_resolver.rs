@@ -2,6 +2,12 @@ use std::collections::BTreeMap;
 use std::sync::RwLock;
 
 use once_cell::sync::OnceCell;
+use serde_json::Value;
+
+use crate::model::{ColorScheme, ValidationIssue};
+
+/// URL prefix marking an image reference that should be resolved through the asset resolver.
+pub const ASSET_URL_PREFIX: &str = "asset:";
 
 #[derive(Debug)]
 pub struct AssetError {
@@ -65,11 +71,106 @@ pub fn register_host_asset_callback(cb: Box<HostResolverFn>) -> Result<(), &'sta
     register_host_asset_resolver(Box::new(CallbackResolver::new(cb)))
 }
 
+/// Resolves `name` through the registered host resolver. Wasm builds with `asset-store`, where
+/// the host cannot register one, ask the host's `asset-store` import instead.
 pub fn resolve_with_host(name: &str) -> Result<Option<String>, AssetError> {
     if let Ok(guard) = host_cell().read()
         && let Some(resolver) = guard.as_ref()
     {
         return resolver.resolve(name);
     }
+    resolve_through_import(name)
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "asset-store"))]
+mod bindings {
+    wit_bindgen::generate!({
+        path: "wit/asset-store.wit",
+        world: "host-assets",
+    });
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "asset-store"))]
+fn resolve_through_import(name: &str) -> Result<Option<String>, AssetError> {
+    bindings::greentic::assets::asset_store::resolve(name).map_err(|message| AssetError {
+        message: format!("asset-store resolve failed: {message}"),
+    })
+}
+
+#[cfg(not(all(target_arch = "wasm32", feature = "asset-store")))]
+fn resolve_through_import(_name: &str) -> Result<Option<String>, AssetError> {
     Ok(None)
 }
+
+/// Candidate asset names for a color scheme: `logo.png` → `logo.dark.png`, then `logo.png`.
+pub fn scheme_candidates(name: &str, scheme: Option<&ColorScheme>) -> Vec<String> {
+    let suffix = match scheme {
+        Some(ColorScheme::Dark) => "dark",
+        Some(ColorScheme::Light) => "light",
+        _ => return vec![name.to_string()],
+    };
+    let variant = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.contains('/') => {
+            format!("{stem}.{suffix}.{ext}")
+        }
+        _ => format!("{name}.{suffix}"),
+    };
+    vec![variant, name.to_string()]
+}
+
+/// Resolves `asset:<name>` URLs in the card through the asset registry and then the host
+/// resolver, preferring the color-scheme variant of each asset. Unresolved references are
+/// left in place and reported.
+pub fn resolve_asset_urls(
+    card: &mut Value,
+    scheme: Option<&ColorScheme>,
+    registry: Option<&BTreeMap<String, String>>,
+) -> Result<Vec<ValidationIssue>, AssetError> {
+    fn visit(
+        value: &mut Value,
+        path: &str,
+        scheme: Option<&ColorScheme>,
+        registry: Option<&BTreeMap<String, String>>,
+        issues: &mut Vec<ValidationIssue>,
+    ) -> Result<(), AssetError> {
+        match value {
+            Value::String(text) => {
+                let Some(name) = text.strip_prefix(ASSET_URL_PREFIX) else {
+                    return Ok(());
+                };
+                for candidate in scheme_candidates(name, scheme) {
+                    let resolved = match registry.and_then(|r| r.get(&candidate)) {
+                        Some(mapped) => Some(mapped.clone()),
+                        None => resolve_with_host(&candidate)?,
+                    };
+                    if let Some(resolved) = resolved {
+                        *text = resolved;
+                        return Ok(());
+                    }
+                }
+                issues.push(ValidationIssue {
+                    code: "unresolved-asset".into(),
+                    message: format!("No asset registered for '{name}'"),
+                    path: path.to_string(),
+                    severity: None,
+                });
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter_mut().enumerate() {
+                    visit(item, &format!("{path}/{idx}"), scheme, registry, issues)?;
+                }
+            }
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    visit(child, &format!("{path}/{key}"), scheme, registry, issues)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    let mut issues = Vec::new();
+    visit(card, "", scheme, registry, &mut issues)?;
+    Ok(issues)
+}
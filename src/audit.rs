@@ -0,0 +1,119 @@
+use serde_json::{Map, Value};
+
+use crate::model::{AuditCheck, IssueSeverity, ValidationIssue};
+
+/// Runs the requested audits over the rendered card and returns their findings.
+pub fn run_audits(card: &Value, checks: &[AuditCheck]) -> Vec<ValidationIssue> {
+    let mut findings = Vec::new();
+    for check in checks {
+        match check {
+            AuditCheck::Accessibility => audit_accessibility(card, &mut findings),
+        }
+    }
+    findings
+}
+
+fn finding(
+    findings: &mut Vec<ValidationIssue>,
+    severity: IssueSeverity,
+    code: &str,
+    message: &str,
+    path: &str,
+) {
+    findings.push(ValidationIssue {
+        code: code.to_string(),
+        message: message.to_string(),
+        path: path.to_string(),
+        severity: Some(severity),
+    });
+}
+
+fn audit_accessibility(card: &Value, findings: &mut Vec<ValidationIssue>) {
+    if card.get("type").and_then(|v| v.as_str()) == Some("AdaptiveCard")
+        && !has_text(card.as_object(), "speak")
+    {
+        finding(
+            findings,
+            IssueSeverity::Info,
+            "a11y-missing-speak",
+            "Card has no speak text for voice-first hosts",
+            "/speak",
+        );
+    }
+    visit(card, "", findings);
+}
+
+fn has_text(map: Option<&Map<String, Value>>, key: &str) -> bool {
+    map.and_then(|m| m.get(key))
+        .and_then(|v| v.as_str())
+        .map(|s| !s.trim().is_empty())
+        .unwrap_or(false)
+}
+
+fn visit(value: &Value, path: &str, findings: &mut Vec<ValidationIssue>) {
+    match value {
+        Value::Object(map) => {
+            let kind = map.get("type").and_then(|v| v.as_str()).unwrap_or_default();
+            match kind {
+                "Image" if !has_text(Some(map), "altText") => finding(
+                    findings,
+                    IssueSeverity::Error,
+                    "a11y-missing-alt-text",
+                    "Images must include altText",
+                    path,
+                ),
+                kind if kind.starts_with("Input.") && !has_text(Some(map), "label") => finding(
+                    findings,
+                    IssueSeverity::Error,
+                    "a11y-missing-label",
+                    "Inputs must include a label; placeholders are not announced reliably",
+                    path,
+                ),
+                "TextBlock" | "TextRun" => audit_text_contrast(map, path, findings),
+                _ => {}
+            }
+            for (key, child) in map {
+                visit(child, &format!("{path}/{key}"), findings);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                visit(item, &format!("{path}/{idx}"), findings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Flags style combinations that commonly fall below WCAG contrast on default host themes.
+fn audit_text_contrast(map: &Map<String, Value>, path: &str, findings: &mut Vec<ValidationIssue>) {
+    let style = |key: &str| {
+        map.get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_ascii_lowercase())
+    };
+    let subtle = map
+        .get("isSubtle")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let small = style("size").as_deref() == Some("small");
+    let lighter = style("weight").as_deref() == Some("lighter");
+    if subtle && (small || lighter) {
+        finding(
+            findings,
+            IssueSeverity::Warning,
+            "a11y-low-contrast",
+            "Subtle text combined with small size or lighter weight may be hard to read",
+            path,
+        );
+    }
+    if style("color").as_deref() == Some("light") {
+        finding(
+            findings,
+            IssueSeverity::Warning,
+            "a11y-low-contrast",
+            "Light text color has poor contrast on default backgrounds",
+            path,
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::model::{
+    AdaptiveCardInvocation, AuditLogConfig, AuditOutcome, CardInteraction, InteractionAudit,
+};
+use crate::routing::wildcard_match;
+use crate::trace;
+use crate::views;
+
+/// Value recorded for inputs matching `redact_inputs`.
+pub const REDACTED: &str = "redacted";
+
+/// Builds the audit record of a handled interaction. `inputs` are the normalized inputs the
+/// event carries (or would have carried), and `verb` the resolved verb.
+pub fn build_record(
+    config: &AuditLogConfig,
+    invocation: &AdaptiveCardInvocation,
+    interaction: &CardInteraction,
+    verb: Option<&str>,
+    inputs: &Value,
+    outcome: AuditOutcome,
+) -> InteractionAudit {
+    let hashed = inputs
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(id, value)| {
+            let redacted = config
+                .redact_inputs
+                .iter()
+                .any(|pattern| wildcard_match(pattern, id));
+            let recorded = if redacted {
+                REDACTED.to_string()
+            } else {
+                trace::hash_value(value).unwrap_or_default()
+            };
+            (id.clone(), recorded)
+        })
+        .collect::<BTreeMap<_, _>>();
+    InteractionAudit {
+        actor: views::viewer_value(invocation)
+            .get("id")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        card_id: interaction
+            .metadata
+            .get("cardId")
+            .and_then(Value::as_str)
+            .unwrap_or(&interaction.card_instance_id)
+            .to_string(),
+        card_instance_id: interaction.card_instance_id.clone(),
+        action_id: interaction.action_id.clone(),
+        interaction_type: interaction.interaction_type.clone(),
+        verb: verb.map(str::to_string),
+        inputs: hashed,
+        outcome,
+        timestamp_ms: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default(),
+    }
+}
@@ -0,0 +1,223 @@
+use serde_json::Value;
+
+use crate::model::{CardBudget, IssueSeverity, TruncationStrategy, ValidationIssue};
+
+/// Size and complexity measurements of a rendered card.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CardMetrics {
+    pub bytes: usize,
+    pub elements: usize,
+    pub depth: usize,
+    pub actions: usize,
+}
+
+pub fn measure(card: &Value) -> CardMetrics {
+    fn count(value: &Value, metrics: &mut CardMetrics) {
+        match value {
+            Value::Object(map) => {
+                if let Some(kind) = map.get("type").and_then(|v| v.as_str()) {
+                    if kind.starts_with("Action.") {
+                        metrics.actions += 1;
+                    } else if kind != "AdaptiveCard" {
+                        metrics.elements += 1;
+                    }
+                }
+                map.values().for_each(|child| count(child, metrics));
+            }
+            Value::Array(items) => items.iter().for_each(|item| count(item, metrics)),
+            _ => {}
+        }
+    }
+
+    let mut metrics = CardMetrics {
+        bytes: serde_json::to_vec(card).map(|b| b.len()).unwrap_or(0),
+        depth: height(card),
+        ..CardMetrics::default()
+    };
+    count(card, &mut metrics);
+    metrics
+}
+
+fn is_element(value: &Value) -> bool {
+    value
+        .get("type")
+        .and_then(|v| v.as_str())
+        .map(|kind| !kind.starts_with("Action.") && kind != "AdaptiveCard")
+        .unwrap_or(false)
+}
+
+/// Element nesting depth of a subtree; a top-level body element has depth 1.
+fn height(value: &Value) -> usize {
+    match value {
+        Value::Object(map) => {
+            usize::from(is_element(value)) + map.values().map(height).max().unwrap_or(0)
+        }
+        Value::Array(items) => items.iter().map(height).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Applies the configured truncation strategies in order, then reports any budget that is
+/// still exceeded.
+pub fn enforce_budget(card: &mut Value, budget: &CardBudget) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    for strategy in &budget.truncation {
+        match strategy {
+            TruncationStrategy::CollapseContainers => {
+                collapse_containers(card, budget, &mut issues)
+            }
+            TruncationStrategy::DropTrailing => drop_trailing(card, budget, &mut issues),
+        }
+    }
+
+    let metrics = measure(card);
+    let checks = [
+        (
+            "budget-bytes",
+            "serialized bytes",
+            metrics.bytes,
+            budget.max_bytes,
+            "",
+        ),
+        (
+            "budget-elements",
+            "elements",
+            metrics.elements,
+            budget.max_elements,
+            "/body",
+        ),
+        (
+            "budget-depth",
+            "nesting depth",
+            metrics.depth,
+            budget.max_depth,
+            "/body",
+        ),
+        (
+            "budget-actions",
+            "actions",
+            metrics.actions,
+            budget.max_actions,
+            "/actions",
+        ),
+    ];
+    for (code, label, actual, limit, path) in checks {
+        if let Some(limit) = limit
+            && actual > limit
+        {
+            issues.push(ValidationIssue {
+                code: code.to_string(),
+                message: format!("Card has {actual} {label}, exceeding the budget of {limit}"),
+                path: if path.is_empty() {
+                    "/".into()
+                } else {
+                    path.into()
+                },
+                severity: Some(IssueSeverity::Error),
+            });
+        }
+    }
+    issues
+}
+
+fn truncated(issues: &mut Vec<ValidationIssue>, message: String, path: &str) {
+    issues.push(ValidationIssue {
+        code: "budget-truncated".into(),
+        message,
+        path: path.into(),
+        severity: Some(IssueSeverity::Warning),
+    });
+}
+
+fn over_size(card: &Value, budget: &CardBudget) -> bool {
+    let metrics = measure(card);
+    budget.max_bytes.is_some_and(|max| metrics.bytes > max)
+        || budget
+            .max_elements
+            .is_some_and(|max| metrics.elements > max)
+}
+
+fn drop_trailing(card: &mut Value, budget: &CardBudget, issues: &mut Vec<ValidationIssue>) {
+    let mut dropped = 0;
+    while over_size(card, budget)
+        && let Some(Value::Array(body)) = card.get_mut("body")
+        && body.pop().is_some()
+    {
+        dropped += 1;
+    }
+    if dropped > 0 {
+        truncated(
+            issues,
+            format!("Dropped {dropped} trailing body element(s) to fit the budget"),
+            "/body",
+        );
+    }
+
+    let Some(max) = budget.max_actions else {
+        return;
+    };
+    let mut dropped = 0;
+    while measure(card).actions > max
+        && let Some(Value::Array(actions)) = card.get_mut("actions")
+        && actions.pop().is_some()
+    {
+        dropped += 1;
+    }
+    if dropped > 0 {
+        truncated(
+            issues,
+            format!("Dropped {dropped} trailing action(s) to fit the budget"),
+            "/actions",
+        );
+    }
+}
+
+fn collapse_containers(card: &mut Value, budget: &CardBudget, issues: &mut Vec<ValidationIssue>) {
+    let Some(max) = budget.max_depth else {
+        return;
+    };
+    let mut collapsed = 0;
+    while height(card) > max && collapse_one(card, 0, max) {
+        collapsed += 1;
+    }
+    if collapsed > 0 {
+        truncated(
+            issues,
+            format!("Collapsed {collapsed} container(s) to fit the depth budget"),
+            "/body",
+        );
+    }
+}
+
+/// Replaces the outermost Container on an over-deep path with its items.
+fn collapse_one(value: &mut Value, above: usize, max: usize) -> bool {
+    match value {
+        Value::Array(items) => {
+            let position = items.iter().position(|item| {
+                item.get("type").and_then(|v| v.as_str()) == Some("Container")
+                    && item.get("items").is_some_and(|v| v.is_array())
+                    && above + height(item) > max
+            });
+            if let Some(idx) = position {
+                let container = items.remove(idx);
+                let inner = container
+                    .get("items")
+                    .and_then(|v| v.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                items.splice(idx..idx, inner);
+                return true;
+            }
+            items.iter_mut().any(|item| collapse_one(item, above, max))
+        }
+        Value::Object(_) => {
+            let above = above + usize::from(is_element(value));
+            let Value::Object(map) = value else {
+                return false;
+            };
+            map.values_mut()
+                .any(|child| collapse_one(child, above, max))
+        }
+        _ => false,
+    }
+}
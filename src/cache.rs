@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use once_cell::sync::Lazy;
+use serde_json::Value;
+
+use crate::model::CacheStats;
+
+/// Independently locked shards per cache, so concurrent lookups of different keys rarely
+/// wait on each other and lookups of the same key only take a read lock.
+pub const SHARDS: usize = 16;
+
+/// Parsed card templates loaded from `asset_path` / `catalog_name` files.
+pub static TEMPLATES: Lazy<ShardedCache<CachedFile>> = Lazy::new(|| ShardedCache::new(512));
+/// Other parsed JSON assets: partials, layouts, string catalogs, and configuration.
+pub static ASSETS: Lazy<ShardedCache<CachedFile>> = Lazy::new(|| ShardedCache::new(512));
+
+/// A parsed JSON file, valid while the file keeps the same size and modification time.
+#[derive(Clone, Debug)]
+pub struct CachedFile {
+    pub stamp: FileStamp,
+    pub value: Value,
+    pub hash: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    /// The file's current stamp, or `None` when it cannot be read (it is then not cached).
+    pub fn of(path: &str) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        Some(Self {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+        })
+    }
+}
+
+struct Shard<V> {
+    entries: HashMap<String, V>,
+    /// Insertion order; the oldest entry is evicted when the shard is full.
+    order: VecDeque<String>,
+}
+
+/// Bounded map split into [`SHARDS`] `RwLock`ed shards with hit/miss/eviction counters.
+pub struct ShardedCache<V> {
+    shards: [RwLock<Shard<V>>; SHARDS],
+    shard_capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<V: Clone> ShardedCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            shards: std::array::from_fn(|_| {
+                RwLock::new(Shard {
+                    entries: HashMap::new(),
+                    order: VecDeque::new(),
+                })
+            }),
+            shard_capacity: capacity.div_ceil(SHARDS).max(1),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    fn shard(&self, key: &str) -> &RwLock<Shard<V>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % SHARDS]
+    }
+
+    /// The cached value for `key` when `fresh` accepts it; anything else counts as a miss.
+    pub fn get(&self, key: &str, fresh: impl FnOnce(&V) -> bool) -> Option<V> {
+        let found = self
+            .shard(key)
+            .read()
+            .ok()
+            .and_then(|shard| shard.entries.get(key).filter(|v| fresh(v)).cloned());
+        let counter = if found.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    pub fn insert(&self, key: &str, value: V) {
+        let Ok(mut shard) = self.shard(key).write() else {
+            return;
+        };
+        if shard.entries.insert(key.to_string(), value).is_some() {
+            return;
+        }
+        shard.order.push_back(key.to_string());
+        while shard.order.len() > self.shard_capacity {
+            if let Some(oldest) = shard.order.pop_front() {
+                shard.entries.remove(&oldest);
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self
+                .shards
+                .iter()
+                .filter_map(|shard| shard.read().ok().map(|shard| shard.entries.len()))
+                .sum(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counters of every internal cache, keyed by cache name.
+pub fn stats() -> BTreeMap<String, CacheStats> {
+    BTreeMap::from([
+        ("assets".to_string(), ASSETS.stats()),
+        ("templates".to_string(), TEMPLATES.stats()),
+    ])
+}
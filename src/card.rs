@@ -0,0 +1,956 @@
+//! Typed Adaptive Card model.
+//!
+//! Elements, actions, and inlines are enums keyed by `type`, so callers can pattern-match
+//! instead of comparing strings. Round-tripping is lossless: properties the structs do not
+//! model, and modelled properties that would not serialize back to exactly the same JSON (a
+//! template string where a number belongs, an explicit `null`), are kept verbatim in each
+//! struct's `extra` map. Objects with an unknown `type` are kept whole in the `Other`
+//! variant.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Number, Value};
+
+macro_rules! typed_union {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident { $($tag:literal => $variant:ident($ty:ty)),* $(,)? }
+    ) => {
+        $(#[$meta])*
+        // Unboxed so variants can be matched and destructured directly.
+        #[allow(clippy::large_enum_variant)]
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum $name {
+            $($variant($ty),)*
+            /// Unknown type, or not an object; kept verbatim.
+            Other(Value),
+        }
+
+        impl $name {
+            /// Parses `raw`, falling back to [`Self::Other`] for types it does not model.
+            pub fn from_value(raw: Value) -> Self {
+                let parsed = match raw.get("type").and_then(Value::as_str) {
+                    $(Some($tag) => typed::<$ty>(&raw).map(Self::$variant),)*
+                    _ => None,
+                };
+                parsed.unwrap_or(Self::Other(raw))
+            }
+
+            /// The `type` discriminator, when present.
+            pub fn type_name(&self) -> Option<&str> {
+                match self {
+                    $(Self::$variant(_) => Some($tag),)*
+                    Self::Other(raw) => raw.get("type").and_then(Value::as_str),
+                }
+            }
+
+            pub fn to_value(&self) -> Value {
+                match self {
+                    $(Self::$variant(inner) => tagged(inner, $tag),)*
+                    Self::Other(raw) => raw.clone(),
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.to_value().serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Value::deserialize(deserializer).map(Self::from_value)
+            }
+        }
+    };
+}
+
+/// Parses the object `raw` (minus its `type`) as `T`. Properties that do not serialize back
+/// unchanged on their own are moved into the struct's `extra` map as they are, so the result
+/// always reproduces `raw`.
+fn typed<T: DeserializeOwned + Serialize + Extra>(raw: &Value) -> Option<T> {
+    let mut body = raw.as_object()?.clone();
+    body.remove("type");
+    if let Some(parsed) = exact(&body) {
+        return Some(parsed);
+    }
+    let (fits, rest): (Map<String, Value>, Map<String, Value>) =
+        body.into_iter().partition(|(key, value)| {
+            exact::<T>(&Map::from_iter([(key.clone(), value.clone())])).is_some()
+        });
+    let mut parsed: T = exact(&fits)?;
+    parsed.extra_mut().extend(rest);
+    Some(parsed)
+}
+
+/// `body` as `T` when it serializes back to exactly `body`.
+fn exact<T: DeserializeOwned + Serialize>(body: &Map<String, Value>) -> Option<T> {
+    let body = Value::Object(body.clone());
+    let parsed: T = serde_json::from_value(body.clone()).ok()?;
+    (serde_json::to_value(&parsed).ok()? == body).then_some(parsed)
+}
+
+/// Structs whose unmodelled properties are kept in an `extra` map.
+trait Extra {
+    fn extra_mut(&mut self) -> &mut Map<String, Value>;
+}
+
+macro_rules! extra {
+    ($($ty:ty => $($field:ident).+;)*) => {
+        $(impl Extra for $ty {
+            fn extra_mut(&mut self) -> &mut Map<String, Value> {
+                &mut self.$($field).+
+            }
+        })*
+    };
+}
+
+extra! {
+    AdaptiveCard => extra;
+    TextBlock => common.extra;
+    RichTextBlock => common.extra;
+    TextRun => extra;
+    Image => common.extra;
+    Media => common.extra;
+    Container => common.extra;
+    ColumnSet => common.extra;
+    FactSet => common.extra;
+    ImageSet => common.extra;
+    ActionSet => common.extra;
+    Table => common.extra;
+    Icon => common.extra;
+    ProgressBar => common.extra;
+    Spinner => common.extra;
+    BarChart => common.extra;
+    SeriesChart => common.extra;
+    PieChart => common.extra;
+    GaugeChart => common.extra;
+    InputText => input.common.extra;
+    InputNumber => input.common.extra;
+    InputDate => input.common.extra;
+    InputTime => input.common.extra;
+    InputToggle => input.common.extra;
+    InputChoiceSet => input.common.extra;
+    OpenUrlAction => common.extra;
+    SubmitAction => common.extra;
+    ExecuteAction => common.extra;
+    ShowCardAction => common.extra;
+    ToggleVisibilityAction => common.extra;
+}
+
+fn tagged<T: Serialize>(inner: &T, tag: &str) -> Value {
+    let mut value = serde_json::to_value(inner).unwrap_or_else(|_| Value::Object(Map::new()));
+    if let Value::Object(map) = &mut value {
+        map.insert("type".to_string(), Value::String(tag.to_string()));
+    }
+    value
+}
+
+typed_union! {
+    /// A body element.
+    pub enum CardElement {
+        "TextBlock" => TextBlock(TextBlock),
+        "RichTextBlock" => RichTextBlock(RichTextBlock),
+        "Image" => Image(Image),
+        "Media" => Media(Media),
+        "Container" => Container(Container),
+        "ColumnSet" => ColumnSet(ColumnSet),
+        "FactSet" => FactSet(FactSet),
+        "ImageSet" => ImageSet(ImageSet),
+        "ActionSet" => ActionSet(ActionSet),
+        "Table" => Table(Table),
+        "Icon" => Icon(Icon),
+        "ProgressBar" => ProgressBar(ProgressBar),
+        "Spinner" => Spinner(Spinner),
+        "Chart.VerticalBar" => VerticalBarChart(BarChart),
+        "Chart.HorizontalBar" => HorizontalBarChart(BarChart),
+        "Chart.VerticalBar.Grouped" => GroupedBarChart(SeriesChart),
+        "Chart.HorizontalBar.Stacked" => StackedBarChart(SeriesChart),
+        "Chart.Line" => LineChart(SeriesChart),
+        "Chart.Pie" => PieChart(PieChart),
+        "Chart.Donut" => DonutChart(PieChart),
+        "Chart.Gauge" => GaugeChart(GaugeChart),
+        "Input.Text" => InputText(InputText),
+        "Input.Number" => InputNumber(InputNumber),
+        "Input.Date" => InputDate(InputDate),
+        "Input.Time" => InputTime(InputTime),
+        "Input.Toggle" => InputToggle(InputToggle),
+        "Input.ChoiceSet" => InputChoiceSet(InputChoiceSet),
+    }
+}
+
+typed_union! {
+    /// A card, ActionSet, or selectAction action.
+    pub enum CardAction {
+        "Action.OpenUrl" => OpenUrl(OpenUrlAction),
+        "Action.Submit" => Submit(SubmitAction),
+        "Action.Execute" => Execute(ExecuteAction),
+        "Action.ShowCard" => ShowCard(ShowCardAction),
+        "Action.ToggleVisibility" => ToggleVisibility(ToggleVisibilityAction),
+    }
+}
+
+typed_union! {
+    /// A RichTextBlock inline.
+    pub enum Inline {
+        "TextRun" => TextRun(TextRun),
+    }
+}
+
+/// Card root. `type` is always `AdaptiveCard`; use [`AdaptiveCard::from_value`] to parse
+/// losslessly.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AdaptiveCard {
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Vec<CardElement>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<CardAction>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select_action: Option<CardAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speak: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl AdaptiveCard {
+    /// Parses a card root; `None` unless `raw` is an `AdaptiveCard` object.
+    pub fn from_value(raw: &Value) -> Option<Self> {
+        if raw.get("type").and_then(Value::as_str) != Some("AdaptiveCard") {
+            return None;
+        }
+        typed(raw)
+    }
+
+    pub fn to_value(&self) -> Value {
+        tagged(self, "AdaptiveCard")
+    }
+
+    /// Every element in document order, including those nested in containers, columns,
+    /// table cells, and ShowCard subcards.
+    pub fn elements(&self) -> Vec<&CardElement> {
+        let mut out = Vec::new();
+        for element in self.body.iter().flatten() {
+            element.collect(&mut out);
+        }
+        for action in self.actions.iter().flatten() {
+            if let CardAction::ShowCard(show) = action
+                && let Some(card) = show.card.as_ref()
+            {
+                out.extend(card.elements());
+            }
+        }
+        out
+    }
+}
+
+impl CardElement {
+    fn collect<'a>(&'a self, out: &mut Vec<&'a CardElement>) {
+        out.push(self);
+        let children: Vec<&CardElement> = match self {
+            CardElement::Container(container) => container.items.iter().flatten().collect(),
+            CardElement::ColumnSet(set) => set
+                .columns
+                .iter()
+                .flatten()
+                .flat_map(|column| column.items.iter())
+                .collect(),
+            CardElement::Table(table) => table
+                .rows
+                .iter()
+                .flatten()
+                .flat_map(|row| row.cells.iter())
+                .flat_map(|cell| cell.items.iter())
+                .collect(),
+            _ => Vec::new(),
+        };
+        for child in children {
+            child.collect(out);
+        }
+    }
+
+    /// Properties shared by all typed elements; `None` for [`CardElement::Other`].
+    pub fn common(&self) -> Option<&ElementCommon> {
+        Some(match self {
+            CardElement::TextBlock(e) => &e.common,
+            CardElement::RichTextBlock(e) => &e.common,
+            CardElement::Image(e) => &e.common,
+            CardElement::Media(e) => &e.common,
+            CardElement::Container(e) => &e.common,
+            CardElement::ColumnSet(e) => &e.common,
+            CardElement::FactSet(e) => &e.common,
+            CardElement::ImageSet(e) => &e.common,
+            CardElement::ActionSet(e) => &e.common,
+            CardElement::Table(e) => &e.common,
+            CardElement::Icon(e) => &e.common,
+            CardElement::ProgressBar(e) => &e.common,
+            CardElement::Spinner(e) => &e.common,
+            CardElement::VerticalBarChart(e) | CardElement::HorizontalBarChart(e) => &e.common,
+            CardElement::GroupedBarChart(e)
+            | CardElement::StackedBarChart(e)
+            | CardElement::LineChart(e) => &e.common,
+            CardElement::PieChart(e) | CardElement::DonutChart(e) => &e.common,
+            CardElement::GaugeChart(e) => &e.common,
+            CardElement::InputText(e) => &e.input.common,
+            CardElement::InputNumber(e) => &e.input.common,
+            CardElement::InputDate(e) => &e.input.common,
+            CardElement::InputTime(e) => &e.input.common,
+            CardElement::InputToggle(e) => &e.input.common,
+            CardElement::InputChoiceSet(e) => &e.input.common,
+            CardElement::Other(_) => return None,
+        })
+    }
+
+    pub fn is_input(&self) -> bool {
+        self.type_name().is_some_and(|t| t.starts_with("Input."))
+    }
+
+    pub fn is_chart(&self) -> bool {
+        self.type_name().is_some_and(|t| t.starts_with("Chart."))
+    }
+}
+
+/// Properties shared by every element. Unmodelled properties are kept in `extra`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementCommon {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_visible: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spacing: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires: Option<Map<String, Value>>,
+    /// Fallback element or `"drop"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Properties shared by every action. Unmodelled properties are kept in `extra`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionCommon {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires: Option<Map<String, Value>>,
+    /// Fallback action or `"drop"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextBlock {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_subtle: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_lines: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub horizontal_alignment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RichTextBlock {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inlines: Option<Vec<InlineContent>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub horizontal_alignment: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+/// A RichTextBlock inline: plain string or typed inline.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum InlineContent {
+    Text(String),
+    Inline(Inline),
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextRun {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_subtle: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underline: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select_action: Option<CardAction>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub horizontal_alignment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select_action: Option<CardAction>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Media {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sources: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poster: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt_text: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Container {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<CardElement>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select_action: Option<CardAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vertical_content_alignment: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnSet {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Column>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select_action: Option<CardAction>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+/// ColumnSet column. Its optional `type: "Column"` is kept in `common.extra`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Column {
+    #[serde(default)]
+    pub items: Vec<CardElement>,
+    /// `"auto"`, `"stretch"`, a weight, or `"<n>px"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select_action: Option<CardAction>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FactSet {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facts: Option<Vec<Fact>>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Fact {
+    pub title: String,
+    pub value: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageSet {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<CardElement>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_size: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionSet {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<CardAction>>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Table {
+    /// Column definitions (`width`, alignment).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<Vec<TableRow>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_row_as_header: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show_grid_lines: Option<bool>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+/// Table row. Its `type: "TableRow"` is kept in `extra`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRow {
+    #[serde(default)]
+    pub cells: Vec<TableCell>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Table cell. Its `type: "TableCell"` is kept in `extra`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TableCell {
+    #[serde(default)]
+    pub items: Vec<CardElement>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select_action: Option<CardAction>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Icon {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    /// `Regular` or `Filled`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select_action: Option<CardAction>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+/// Determinate progress when `value` is set (out of `max`, default 100).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressBar {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Spinner {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+/// `Chart.VerticalBar` / `Chart.HorizontalBar`: one value per category.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BarChart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<ChartPoint>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_axis_title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y_axis_title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+/// `Chart.Line`, `Chart.VerticalBar.Grouped`, `Chart.HorizontalBar.Stacked`: one or more
+/// named series.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesChart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<ChartSeries>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_axis_title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y_axis_title: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+/// `Chart.Pie` / `Chart.Donut`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PieChart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<ChartSlice>>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GaugeChart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<GaugeSegment>>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+/// A bar or line point. `x` is a category label or a number.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartPoint {
+    pub x: Value,
+    pub y: Number,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartSeries {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legend: Option<String>,
+    #[serde(default)]
+    pub values: Vec<ChartPoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartSlice {
+    pub legend: String,
+    pub value: Number,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GaugeSegment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legend: Option<String>,
+    pub size: Number,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Properties shared by every input.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputCommon {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_required: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    #[serde(flatten)]
+    pub common: ElementCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputText {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_multiline: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(flatten)]
+    pub input: InputCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputNumber {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Number>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(flatten)]
+    pub input: InputCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputDate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(flatten)]
+    pub input: InputCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputTime {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(flatten)]
+    pub input: InputCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputToggle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_on: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_off: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap: Option<bool>,
+    #[serde(flatten)]
+    pub input: InputCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputChoiceSet {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<Choice>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_multi_select: Option<bool>,
+    /// `compact`, `expanded`, or `filtered`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    #[serde(flatten)]
+    pub input: InputCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Choice {
+    pub title: String,
+    pub value: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenUrlAction {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(flatten)]
+    pub common: ActionCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitAction {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub associated_inputs: Option<String>,
+    #[serde(flatten)]
+    pub common: ActionCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteAction {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verb: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub associated_inputs: Option<String>,
+    #[serde(flatten)]
+    pub common: ActionCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowCardAction {
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "subcard")]
+    pub card: Option<Box<AdaptiveCard>>,
+    #[serde(flatten)]
+    pub common: ActionCommon,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToggleVisibilityAction {
+    /// Element ids, or `{elementId, isVisible}` objects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_elements: Option<Vec<Value>>,
+    #[serde(flatten)]
+    pub common: ActionCommon,
+}
+
+/// ShowCard subcards carry their own `type: "AdaptiveCard"`; a `card` that is not an
+/// AdaptiveCard object is kept in the action's `extra` map instead.
+mod subcard {
+    use super::AdaptiveCard;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+    use serde_json::Value;
+
+    pub fn serialize<S: Serializer>(
+        card: &Option<Box<AdaptiveCard>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match card {
+            Some(card) => serde::Serialize::serialize(&card.to_value(), serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Box<AdaptiveCard>>, D::Error> {
+        let raw = Value::deserialize(deserializer)?;
+        AdaptiveCard::from_value(&raw)
+            .map(|card| Some(Box::new(card)))
+            .ok_or_else(|| D::Error::custom("subcard is not an AdaptiveCard"))
+    }
+}
@@ -0,0 +1,273 @@
+use serde_json::{Map, Number, Value, json};
+
+use crate::expression::stringify_value;
+use crate::model::{IssueSeverity, ValidationIssue};
+
+/// Chart extension elements and the shape of their `data`.
+const CHART_TYPES: &[(&str, ChartShape)] = &[
+    ("Chart.VerticalBar", ChartShape::Points),
+    ("Chart.HorizontalBar", ChartShape::Points),
+    ("Chart.VerticalBar.Grouped", ChartShape::Series),
+    ("Chart.HorizontalBar.Stacked", ChartShape::Series),
+    ("Chart.Line", ChartShape::Series),
+    ("Chart.Pie", ChartShape::Slices),
+    ("Chart.Donut", ChartShape::Slices),
+    ("Chart.Gauge", ChartShape::Gauge),
+];
+
+/// Data keys coerced from numeric strings, since bound payload values are often strings.
+const NUMERIC_KEYS: &[&str] = &["y", "value", "size", "min", "max"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum ChartShape {
+    /// `data: [{x, y}]`
+    Points,
+    /// `data: [{legend, values: [{x, y}]}]`
+    Series,
+    /// `data: [{legend, value}]`
+    Slices,
+    /// `value`, `min`, `max`, `segments: [{legend, size}]`
+    Gauge,
+}
+
+/// Prepares chart elements after binding. A chart's `fields` (`{"x": "month", "y": "total"}`)
+/// renames the keys of each bound `data` entry, and of each series' `values`, into the
+/// chart's schema; numeric strings become numbers. Charts without their own `fallback` get a
+/// generated summary (a FactSet, or a Table for multi-series charts) so hosts without chart
+/// support render the numbers instead of dropping the element.
+pub fn prepare_charts(card: &mut Value) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    walk(card, "", &mut issues);
+    issues
+}
+
+fn walk(value: &mut Value, path: &str, issues: &mut Vec<ValidationIssue>) {
+    match value {
+        Value::Object(map) => {
+            let shape = map.get("type").and_then(Value::as_str).and_then(|kind| {
+                CHART_TYPES
+                    .iter()
+                    .find(|(name, _)| *name == kind)
+                    .map(|(_, shape)| *shape)
+            });
+            if let Some(shape) = shape {
+                prepare(map, shape, path, issues);
+                return;
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    walk(child, &format!("{path}/{key}"), issues);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                walk(item, &format!("{path}/{idx}"), issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn prepare(
+    map: &mut Map<String, Value>,
+    shape: ChartShape,
+    path: &str,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    let fields = match map.remove("fields") {
+        Some(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+    if shape == ChartShape::Gauge {
+        coerce_numbers(map);
+        if let Some(Value::Array(segments)) = map.get_mut("segments") {
+            segments
+                .iter_mut()
+                .for_each(|segment| reshape(segment, &fields));
+        }
+        if !map.get("value").is_some_and(Value::is_number) {
+            issues.push(issue(
+                "invalid-chart-data",
+                "Chart.Gauge value must be a number".into(),
+                format!("{path}/value"),
+            ));
+        }
+    } else {
+        match map.get_mut("data") {
+            Some(Value::Array(entries)) => {
+                entries.iter_mut().for_each(|entry| reshape(entry, &fields));
+            }
+            other => {
+                issues.push(issue(
+                    "invalid-chart-data",
+                    format!(
+                        "Chart data must be an array, got {}",
+                        other.map_or("nothing".to_string(), |v| v.to_string())
+                    ),
+                    format!("{path}/data"),
+                ));
+                map.insert("data".into(), Value::Array(Vec::new()));
+            }
+        }
+    }
+    if !map.contains_key("fallback") {
+        let mut summary = summarize(map, shape);
+        if let Value::Object(summary) = &mut summary {
+            for key in ["id", "isVisible", "separator", "spacing"] {
+                if let Some(value) = map.get(key) {
+                    summary.insert(key.into(), value.clone());
+                }
+            }
+        }
+        map.insert("fallback".into(), summary);
+    }
+}
+
+/// Applies the `fields` renames to a data entry and its nested `values`.
+fn reshape(entry: &mut Value, fields: &Map<String, Value>) {
+    let Value::Object(entry) = entry else {
+        return;
+    };
+    for (target, source) in fields {
+        if let Some(value) = source.as_str().and_then(|source| entry.remove(source)) {
+            entry.insert(target.clone(), value);
+        }
+    }
+    coerce_numbers(entry);
+    if let Some(Value::Array(values)) = entry.get_mut("values") {
+        values.iter_mut().for_each(|value| reshape(value, fields));
+    }
+}
+
+fn coerce_numbers(map: &mut Map<String, Value>) {
+    for key in NUMERIC_KEYS {
+        if let Some(value) = map.get_mut(*key)
+            && let Some(raw) = value.as_str().map(str::trim)
+        {
+            if let Ok(int) = raw.parse::<i64>() {
+                *value = Value::from(int);
+            } else if let Some(float) = raw.parse::<f64>().ok().and_then(Number::from_f64) {
+                *value = Value::Number(float);
+            }
+        }
+    }
+}
+
+fn summarize(map: &Map<String, Value>, shape: ChartShape) -> Value {
+    let entries = map
+        .get("data")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let text = |entry: &Value, key: &str| entry.get(key).map(stringify_value).unwrap_or_default();
+    let summary = match shape {
+        ChartShape::Points => fact_set(entries.iter().map(|e| (text(e, "x"), text(e, "y")))),
+        ChartShape::Slices => fact_set(
+            entries
+                .iter()
+                .map(|e| (text(e, "legend"), text(e, "value"))),
+        ),
+        ChartShape::Gauge => {
+            let bound = |key: &str, default: &str| {
+                map.get(key)
+                    .map(stringify_value)
+                    .unwrap_or_else(|| default.to_string())
+            };
+            let value = format!(
+                "{} ({}–{})",
+                map.get("value").map(stringify_value).unwrap_or_default(),
+                bound("min", "0"),
+                bound("max", "100")
+            );
+            let segments = map
+                .get("segments")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(|s| (text(s, "legend"), text(s, "size")));
+            fact_set(std::iter::once(("Value".to_string(), value)).chain(segments))
+        }
+        ChartShape::Series => {
+            let points: Vec<(String, String, String)> = entries
+                .iter()
+                .flat_map(|series| {
+                    let legend = text(series, "legend");
+                    series
+                        .get("values")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .map(move |point| (legend.clone(), text(point, "x"), text(point, "y")))
+                })
+                .collect();
+            let axis = |key: &str, default: &str| {
+                map.get(key)
+                    .and_then(Value::as_str)
+                    .unwrap_or(default)
+                    .to_string()
+            };
+            let header = [
+                "Series".to_string(),
+                axis("xAxisTitle", "X"),
+                axis("yAxisTitle", "Value"),
+            ];
+            let rows: Vec<Value> = std::iter::once(table_row(&header))
+                .chain(
+                    points
+                        .iter()
+                        .map(|(legend, x, y)| table_row(&[legend.clone(), x.clone(), y.clone()])),
+                )
+                .collect();
+            json!({
+                "type": "Table",
+                "columns": [{ "width": 1 }, { "width": 1 }, { "width": 1 }],
+                "rows": rows,
+                "fallback": fact_set(
+                    points
+                        .into_iter()
+                        .map(|(legend, x, y)| (format!("{legend} · {x}"), y)),
+                )
+            })
+        }
+    };
+    match map.get("title").and_then(Value::as_str) {
+        Some(title) => json!({
+            "type": "Container",
+            "items": [
+                { "type": "TextBlock", "text": title, "weight": "Bolder", "wrap": true },
+                summary
+            ]
+        }),
+        None => summary,
+    }
+}
+
+fn fact_set(facts: impl Iterator<Item = (String, String)>) -> Value {
+    let facts: Vec<Value> = facts
+        .map(|(title, value)| json!({ "title": title, "value": value }))
+        .collect();
+    json!({ "type": "FactSet", "facts": facts })
+}
+
+fn table_row(cells: &[String]) -> Value {
+    let cells: Vec<Value> = cells
+        .iter()
+        .map(|text| {
+            json!({
+                "type": "TableCell",
+                "items": [{ "type": "TextBlock", "text": text, "wrap": true }]
+            })
+        })
+        .collect();
+    json!({ "type": "TableRow", "cells": cells })
+}
+
+fn issue(code: &str, message: String, path: String) -> ValidationIssue {
+    ValidationIssue {
+        code: code.to_string(),
+        message,
+        path,
+        severity: Some(IssueSeverity::Warning),
+    }
+}
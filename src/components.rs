@@ -0,0 +1,421 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::OnceCell;
+use serde_json::{Map, Value, json};
+
+use crate::model::{IssueSeverity, ValidationIssue};
+
+/// Expands one composite element into standard elements; errors become warnings.
+type ComponentMacroFn = dyn Fn(&Value) -> Result<Value, String> + Send + Sync;
+
+/// Element properties carried from a composite onto its expansion unless the expansion sets
+/// them itself.
+const CARRIED_KEYS: &[&str] = &[
+    "id",
+    "isVisible",
+    "separator",
+    "spacing",
+    "height",
+    "requires",
+    "fallback",
+    "visibleToRoles",
+];
+
+/// Expansions may produce further composites; deeper nesting is reported and left as is.
+const MAX_EXPANSION_DEPTH: usize = 8;
+
+/// Binding roots of the render a custom element renderer can read.
+#[derive(Clone, Copy, Debug)]
+pub struct ElementContext<'a> {
+    pub payload: &'a Value,
+    pub state: &'a Value,
+    pub session: &'a Value,
+    /// `card_spec.template_params`, `null` when unset.
+    pub params: &'a Value,
+    pub locale: Option<&'a str>,
+}
+
+/// Renders a custom element type into standard elements (one, or a list spliced in its
+/// place). The output is bound, processed, and validated like authored elements, so it may
+/// use `${...}` expressions.
+pub trait ElementRenderer: Send + Sync {
+    fn render(&self, element: &Value, ctx: &ElementContext<'_>) -> Result<Value, String>;
+}
+
+type ElementRendererFn = dyn Fn(&Value, &ElementContext<'_>) -> Result<Value, String> + Send + Sync;
+
+pub struct CallbackElementRenderer {
+    callback: Box<ElementRendererFn>,
+}
+
+impl CallbackElementRenderer {
+    pub fn new(callback: Box<ElementRendererFn>) -> Self {
+        Self { callback }
+    }
+}
+
+impl ElementRenderer for CallbackElementRenderer {
+    fn render(&self, element: &Value, ctx: &ElementContext<'_>) -> Result<Value, String> {
+        (self.callback)(element, ctx)
+    }
+}
+
+static HOST_RENDERERS: OnceCell<RwLock<BTreeMap<String, Arc<dyn ElementRenderer>>>> =
+    OnceCell::new();
+
+fn renderers_cell() -> &'static RwLock<BTreeMap<String, Arc<dyn ElementRenderer>>> {
+    HOST_RENDERERS.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Registers the renderer for custom elements of type `name` (e.g. `X.CustomerTimeline`),
+/// replacing any earlier one.
+pub fn register_element_renderer(
+    name: &str,
+    renderer: Box<dyn ElementRenderer>,
+) -> Result<(), &'static str> {
+    renderers_cell()
+        .write()
+        .map_err(|_| "element renderer registry poisoned")?
+        .insert(name.to_string(), Arc::from(renderer));
+    Ok(())
+}
+
+pub fn register_element_renderer_callback(
+    name: &str,
+    callback: Box<ElementRendererFn>,
+) -> Result<(), &'static str> {
+    register_element_renderer(name, Box::new(CallbackElementRenderer::new(callback)))
+}
+
+static HOST_MACROS: OnceCell<RwLock<BTreeMap<String, Arc<ComponentMacroFn>>>> = OnceCell::new();
+
+fn macros_cell() -> &'static RwLock<BTreeMap<String, Arc<ComponentMacroFn>>> {
+    HOST_MACROS.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Registers a component macro for elements of type `name`, applied on every render. Host
+/// macros take precedence over the built-in `CompoundButton` and `ListItem`.
+pub fn register_component_macro(
+    name: &str,
+    expand: Box<ComponentMacroFn>,
+) -> Result<(), &'static str> {
+    macros_cell()
+        .write()
+        .map_err(|_| "component macro registry poisoned")?
+        .insert(name.to_string(), Arc::from(expand));
+    Ok(())
+}
+
+/// Replaces composite elements (built-in or host-registered component macros) with the
+/// standard elements they expand to.
+pub fn expand_components(card: &mut Value) -> Vec<ValidationIssue> {
+    let macros = macros_cell()
+        .read()
+        .map(|registered| registered.clone())
+        .unwrap_or_default();
+    let mut issues = Vec::new();
+    walk(card, "", &macros, 0, &mut issues);
+    issues
+}
+
+fn walk(
+    value: &mut Value,
+    path: &str,
+    macros: &BTreeMap<String, Arc<ComponentMacroFn>>,
+    depth: usize,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    match value {
+        Value::Object(map) => {
+            let kind = map
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let expanded = match macros.get(&kind) {
+                Some(expand) => Some(expand(&Value::Object(map.clone()))),
+                None => builtin(&kind, map).map(Ok),
+            };
+            let Some(expanded) = expanded else {
+                for (key, child) in map.iter_mut() {
+                    if key != "data" {
+                        walk(child, &format!("{path}/{key}"), macros, depth, issues);
+                    }
+                }
+                return;
+            };
+            if depth >= MAX_EXPANSION_DEPTH {
+                issues.push(issue(
+                    "component-macro-depth",
+                    format!("{kind} expands more than {MAX_EXPANSION_DEPTH} levels deep"),
+                    path,
+                ));
+                return;
+            }
+            match expanded {
+                Ok(mut replacement) => {
+                    if let Value::Object(out) = &mut replacement {
+                        for key in CARRIED_KEYS {
+                            if let Some(carried) = map.get(*key)
+                                && !out.contains_key(*key)
+                            {
+                                out.insert(key.to_string(), carried.clone());
+                            }
+                        }
+                    }
+                    *value = replacement;
+                    walk(value, path, macros, depth + 1, issues);
+                }
+                Err(message) => issues.push(issue(
+                    "component-macro-failed",
+                    format!("{kind} could not be expanded: {message}"),
+                    path,
+                )),
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                walk(item, &format!("{path}/{idx}"), macros, depth, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces unbound custom elements with the output of their registered renderers, before
+/// bindings run. A list output is spliced into the enclosing list (or wrapped in a Container
+/// elsewhere). Renderer errors leave the element in place with a `custom-element-failed`
+/// warning.
+pub fn render_custom_elements(card: &mut Value, ctx: &ElementContext<'_>) -> Vec<ValidationIssue> {
+    let renderers = renderers_cell()
+        .read()
+        .map(|registered| registered.clone())
+        .unwrap_or_default();
+    let mut issues = Vec::new();
+    if !renderers.is_empty() {
+        render_walk(card, "", &renderers, ctx, 0, &mut issues);
+    }
+    issues
+}
+
+/// Whether the card contains an element of a type with a registered renderer.
+pub fn uses_custom_elements(card: &Value) -> bool {
+    fn find(value: &Value, renderers: &BTreeMap<String, Arc<dyn ElementRenderer>>) -> bool {
+        match value {
+            Value::Object(map) => {
+                map.get("type")
+                    .and_then(Value::as_str)
+                    .is_some_and(|kind| renderers.contains_key(kind))
+                    || map
+                        .iter()
+                        .any(|(key, child)| key != "data" && find(child, renderers))
+            }
+            Value::Array(items) => items.iter().any(|item| find(item, renderers)),
+            _ => false,
+        }
+    }
+    renderers_cell()
+        .read()
+        .is_ok_and(|renderers| !renderers.is_empty() && find(card, &renderers))
+}
+
+fn render_walk(
+    value: &mut Value,
+    path: &str,
+    renderers: &BTreeMap<String, Arc<dyn ElementRenderer>>,
+    ctx: &ElementContext<'_>,
+    depth: usize,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if key == "data" {
+                    continue;
+                }
+                let child_path = format!("{path}/{key}");
+                match render_one(child, &child_path, renderers, ctx, depth, issues) {
+                    Some(mut rendered) if rendered.len() == 1 => *child = rendered.remove(0),
+                    Some(rendered) => *child = json!({ "type": "Container", "items": rendered }),
+                    None => render_walk(child, &child_path, renderers, ctx, depth, issues),
+                }
+            }
+        }
+        Value::Array(items) => {
+            // Paths name template positions, which splicing shifts away from `idx`.
+            let mut idx = 0;
+            let mut source = 0;
+            while idx < items.len() {
+                let item_path = format!("{path}/{source}");
+                source += 1;
+                match render_one(&items[idx], &item_path, renderers, ctx, depth, issues) {
+                    Some(rendered) => {
+                        let count = rendered.len();
+                        items.splice(idx..=idx, rendered);
+                        idx += count;
+                    }
+                    None => {
+                        render_walk(&mut items[idx], &item_path, renderers, ctx, depth, issues);
+                        idx += 1;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Elements replacing `element` when it is a registered custom element, with any custom
+/// elements in them rendered too. A single rendered element keeps the custom element's
+/// common properties (see [`CARRIED_KEYS`]) unless it sets them itself.
+fn render_one(
+    element: &Value,
+    path: &str,
+    renderers: &BTreeMap<String, Arc<dyn ElementRenderer>>,
+    ctx: &ElementContext<'_>,
+    depth: usize,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<Vec<Value>> {
+    let original = element.as_object()?;
+    let kind = original.get("type").and_then(Value::as_str)?;
+    let renderer = renderers.get(kind)?;
+    if depth >= MAX_EXPANSION_DEPTH {
+        issues.push(issue(
+            "custom-element-depth",
+            format!("{kind} renders more than {MAX_EXPANSION_DEPTH} levels deep"),
+            path,
+        ));
+        return None;
+    }
+    let rendered = match renderer.render(element, ctx) {
+        Ok(Value::Array(items)) => items,
+        Ok(mut single) => {
+            if let Value::Object(out) = &mut single {
+                for key in CARRIED_KEYS {
+                    if let Some(carried) = original.get(*key)
+                        && !out.contains_key(*key)
+                    {
+                        out.insert(key.to_string(), carried.clone());
+                    }
+                }
+            }
+            vec![single]
+        }
+        Err(message) => {
+            issues.push(issue(
+                "custom-element-failed",
+                format!("{kind} could not be rendered: {message}"),
+                path,
+            ));
+            return None;
+        }
+    };
+    let mut list = Value::Array(rendered);
+    render_walk(&mut list, path, renderers, ctx, depth + 1, issues);
+    match list {
+        Value::Array(items) => Some(items),
+        _ => None,
+    }
+}
+
+fn builtin(kind: &str, map: &Map<String, Value>) -> Option<Value> {
+    match kind {
+        "ListItem" => Some(list_item(map)),
+        "CompoundButton" => Some(compound_button(map)),
+        _ => None,
+    }
+}
+
+/// `ListItem {icon, title, subtitle, selectAction}`: a clickable row with a leading icon.
+fn list_item(map: &Map<String, Value>) -> Value {
+    let mut text = vec![json!({
+        "type": "TextBlock",
+        "text": map.get("title").cloned().unwrap_or_default(),
+        "weight": "Bolder",
+        "wrap": true
+    })];
+    if let Some(subtitle) = map.get("subtitle") {
+        text.push(json!({
+            "type": "TextBlock",
+            "text": subtitle,
+            "isSubtle": true,
+            "spacing": "None",
+            "wrap": true
+        }));
+    }
+    row(map, text, None)
+}
+
+/// `CompoundButton {icon, title, description, badge, selectAction}`: an emphasised tile.
+fn compound_button(map: &Map<String, Value>) -> Value {
+    let mut text = vec![json!({
+        "type": "TextBlock",
+        "text": map.get("title").cloned().unwrap_or_default(),
+        "weight": "Bolder",
+        "size": "Medium",
+        "wrap": true
+    })];
+    if let Some(description) = map.get("description") {
+        text.push(json!({ "type": "TextBlock", "text": description, "wrap": true }));
+    }
+    if let Some(badge) = map.get("badge").and_then(Value::as_str) {
+        text.push(json!({
+            "type": "TextBlock",
+            "text": badge,
+            "color": "Accent",
+            "size": "Small",
+            "wrap": true
+        }));
+    }
+    row(map, text, Some("emphasis"))
+}
+
+fn row(map: &Map<String, Value>, text: Vec<Value>, style: Option<&str>) -> Value {
+    let mut columns = Vec::new();
+    if let Some(icon) = map.get("icon").and_then(icon_element) {
+        columns.push(json!({
+            "type": "Column",
+            "width": "auto",
+            "verticalContentAlignment": "Center",
+            "items": [icon]
+        }));
+    }
+    columns.push(json!({ "type": "Column", "width": "stretch", "items": text }));
+    let mut container = json!({
+        "type": "Container",
+        "items": [{ "type": "ColumnSet", "columns": columns }]
+    });
+    if let Some(style) = style {
+        container["style"] = json!(style);
+    }
+    if let Some(action) = map.get("selectAction") {
+        container["selectAction"] = action.clone();
+    }
+    container
+}
+
+/// An icon name becomes an `Icon`, a URL an `Image`, and an object its own `Icon` props.
+fn icon_element(icon: &Value) -> Option<Value> {
+    match icon {
+        Value::String(name) if name.contains(':') => {
+            Some(json!({ "type": "Image", "url": name, "size": "Small" }))
+        }
+        Value::String(name) if !name.is_empty() => Some(json!({ "type": "Icon", "name": name })),
+        Value::Object(props) => {
+            let mut props = props.clone();
+            props.insert("type".into(), json!("Icon"));
+            Some(Value::Object(props))
+        }
+        _ => None,
+    }
+}
+
+fn issue(code: &str, message: String, path: &str) -> ValidationIssue {
+    ValidationIssue {
+        code: code.to_string(),
+        message,
+        path: path.to_string(),
+        severity: Some(IssueSeverity::Warning),
+    }
+}
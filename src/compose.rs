@@ -0,0 +1,120 @@
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value, json};
+
+use crate::error::ComponentError;
+use crate::fallback::parse_version;
+use crate::model::{
+    AdaptiveCardInvocation, AdaptiveCardResult, CardSpec, IssueSeverity, ValidationIssue,
+};
+use crate::render::{analyze_features, render_card};
+
+/// `compose`: renders every `compose` part like a card of its own (its `card_source` and
+/// `card_spec`, plus its `payload` when set) and merges them into one card. Each part's body
+/// becomes a `compose-<n>` Container, separated from the previous one. The merged card takes
+/// the first `$schema` and the highest `version`. Root actions are concatenated, renaming ids
+/// already used by an earlier part, and cut to `budget.max_actions`.
+pub fn compose_cards(inv: &AdaptiveCardInvocation) -> Result<AdaptiveCardResult, ComponentError> {
+    if inv.compose.is_empty() {
+        return Err(ComponentError::InvalidInput(
+            "compose requires at least one card".into(),
+        ));
+    }
+    let mut schema = None;
+    let mut version: Option<(u32, u32)> = None;
+    let mut body = Vec::new();
+    let mut actions = Vec::new();
+    let mut action_ids = BTreeSet::new();
+    let mut issues = Vec::new();
+    for (idx, part) in inv.compose.iter().enumerate() {
+        let mut part_inv = inv.clone();
+        part_inv.compose = Vec::new();
+        part_inv.card_source = part.card_source.clone();
+        part_inv.card_spec = CardSpec {
+            asset_registry: part
+                .card_spec
+                .asset_registry
+                .clone()
+                .or_else(|| inv.card_spec.asset_registry.clone()),
+            ..part.card_spec.clone()
+        };
+        if let Some(payload) = &part.payload {
+            part_inv.payload = payload.clone();
+        }
+        // The budget applies to the merged card, not to each part.
+        part_inv.budget = None;
+        let outcome = render_card(&part_inv)?;
+        issues.extend(outcome.validation_issues.into_iter().map(|mut issue| {
+            issue.path = format!("/compose/{idx}{}", issue.path);
+            issue
+        }));
+        let Value::Object(mut card) = outcome.card else {
+            continue;
+        };
+        if schema.is_none() {
+            schema = card.remove("$schema");
+        }
+        let part_version = card
+            .get("version")
+            .and_then(Value::as_str)
+            .and_then(parse_version);
+        version = version.max(part_version);
+        let mut container = json!({
+            "type": "Container",
+            "id": format!("compose-{idx}"),
+            "items": card.remove("body").unwrap_or_else(|| json!([]))
+        });
+        if idx > 0 {
+            container["separator"] = json!(true);
+            container["spacing"] = json!("Medium");
+        }
+        body.push(container);
+        for mut action in card
+            .remove("actions")
+            .and_then(|a| a.as_array().cloned())
+            .unwrap_or_default()
+        {
+            if let Some(id) = action.get("id").and_then(Value::as_str).map(str::to_string)
+                && !action_ids.insert(id.clone())
+            {
+                let renamed = format!("{id}-{idx}");
+                action_ids.insert(renamed.clone());
+                action["id"] = json!(renamed);
+            }
+            actions.push(action);
+        }
+    }
+    if let Some(max) = inv.budget.as_ref().and_then(|budget| budget.max_actions)
+        && actions.len() > max
+    {
+        issues.push(ValidationIssue {
+            code: "compose-actions-truncated".into(),
+            message: format!(
+                "{} combined actions exceed the budget of {max}; the rest were dropped",
+                actions.len()
+            ),
+            path: "/actions".into(),
+            severity: Some(IssueSeverity::Warning),
+        });
+        actions.truncate(max);
+    }
+
+    let mut card = Map::new();
+    card.insert("type".into(), json!("AdaptiveCard"));
+    if let Some(schema) = schema {
+        card.insert("$schema".into(), schema);
+    }
+    let (major, minor) = version.unwrap_or((1, 0));
+    card.insert("version".into(), json!(format!("{major}.{minor}")));
+    card.insert("body".into(), Value::Array(body));
+    if !actions.is_empty() {
+        card.insert("actions".into(), Value::Array(actions));
+    }
+    let card = Value::Object(card);
+    Ok(AdaptiveCardResult {
+        card_features: analyze_features(&card),
+        rendered_card: Some(card),
+        validation_issues: issues,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,93 @@
+use std::sync::RwLock;
+
+use once_cell::sync::OnceCell;
+use serde_json::Value;
+
+use crate::error::ComponentError;
+use crate::model::{AdaptiveCardInvocation, ComponentConfig};
+use crate::render::load_json_asset;
+
+/// Asset read by `on_start` for the component configuration.
+pub const CONFIG_ASSET: &str = "adaptive-card.config.json";
+/// Environment variable naming a configuration file to read instead of [`CONFIG_ASSET`].
+pub const CONFIG_ENV: &str = "ADAPTIVE_CARD_CONFIG";
+
+static CONFIG: OnceCell<RwLock<ComponentConfig>> = OnceCell::new();
+
+fn config_cell() -> &'static RwLock<ComponentConfig> {
+    CONFIG.get_or_init(|| RwLock::new(ComponentConfig::default()))
+}
+
+/// Replaces the component configuration applied to later invocations.
+pub fn configure(config: ComponentConfig) -> Result<(), &'static str> {
+    *config_cell()
+        .write()
+        .map_err(|_| "component config poisoned")? = config;
+    Ok(())
+}
+
+/// The configuration currently applied to invocations.
+pub fn current_config() -> ComponentConfig {
+    config_cell()
+        .read()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+/// Startup configuration: reads the file named by `ADAPTIVE_CARD_CONFIG`, or the
+/// [`CONFIG_ASSET`] asset when present, and defaults `locale` to the host's `i18n_id`. A
+/// missing or unreadable asset leaves every default unset; a malformed one fails startup.
+pub fn load_startup_config(i18n_id: Option<&str>) -> Result<ComponentConfig, ComponentError> {
+    let asset = std::env::var(CONFIG_ENV).unwrap_or_else(|_| CONFIG_ASSET.to_string());
+    let mut config = match load_json_asset(&asset, None) {
+        Ok(value) => serde_json::from_value(value)
+            .map_err(|err| ComponentError::InvalidInput(format!("{asset}: {err}")))?,
+        Err(ComponentError::AssetNotFound(_) | ComponentError::Io(_)) => ComponentConfig::default(),
+        Err(err) => return Err(err),
+    };
+    if config.locale.is_none() {
+        config.locale = i18n_id.map(str::to_string);
+    }
+    configure(config.clone()).map_err(|err| ComponentError::InvalidInput(err.into()))?;
+    Ok(config)
+}
+
+/// Fills the invocation's unset `target_profile`, `theme`, `locale`, `budget`, `security`,
+/// `limits`, and `env` keys from the component configuration; values carried by the
+/// invocation always win.
+pub fn apply_config(inv: &mut AdaptiveCardInvocation) {
+    if let Ok(config) = config_cell().read() {
+        apply_defaults(inv, &config);
+    }
+}
+
+/// Fills the invocation's unset configurable fields from `config`.
+pub fn apply_defaults(inv: &mut AdaptiveCardInvocation, config: &ComponentConfig) {
+    if inv.target_profile.is_none() {
+        inv.target_profile = config.target_profile.clone();
+    }
+    if inv.theme.is_none() {
+        inv.theme = config.theme.clone();
+    }
+    if inv.locale.is_none() {
+        inv.locale = config.locale.clone();
+    }
+    if inv.budget.is_none() {
+        inv.budget = config.budget.clone();
+    }
+    if inv.security.is_none() {
+        inv.security = config.security.clone();
+    }
+    if inv.limits.is_none() {
+        inv.limits = config.limits.clone();
+    }
+    match (&mut inv.env, &config.env) {
+        (Some(Value::Object(env)), Some(Value::Object(defaults))) => {
+            for (key, value) in defaults {
+                env.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        (env @ None, defaults) => *env = defaults.clone(),
+        _ => {}
+    }
+}
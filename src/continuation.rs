@@ -0,0 +1,65 @@
+use serde_json::{Map, Value, json};
+
+use crate::model::{AdaptiveCardInvocation, CardContinuation, CardInteraction};
+
+/// Action `data` key declaring the operation the host should invoke after the action.
+pub const NEXT_KEY: &str = "__next";
+
+/// The continuation a declared action's `data.__next` describes: `op` (default `card`) and
+/// the rest of the object as invocation fields. The input carries over the card instance,
+/// node, locale, and target profile, so the next card shares the instance's state; a
+/// `card_spec` naming an `asset_path` or `catalog_name` selects that card source.
+pub fn continuation(
+    data: Option<&Value>,
+    inv: &AdaptiveCardInvocation,
+    interaction: &CardInteraction,
+) -> Option<CardContinuation> {
+    let Some(Value::Object(next)) = data.and_then(|data| data.get(NEXT_KEY)) else {
+        return None;
+    };
+    let mut input = Map::new();
+    input.insert(
+        "card_instance_id".into(),
+        json!(interaction.card_instance_id),
+    );
+    if let Some(node_id) = &inv.node_id {
+        input.insert("node_id".into(), json!(node_id));
+    }
+    if let Some(locale) = &inv.locale {
+        input.insert("locale".into(), json!(locale));
+    }
+    if let Some(profile) = &inv.target_profile {
+        input.insert(
+            "target_profile".into(),
+            serde_json::to_value(profile).unwrap_or(Value::Null),
+        );
+    }
+    let mut op = "card".to_string();
+    for (key, value) in next {
+        match (key.as_str(), value) {
+            ("op", Value::String(name)) => op = name.clone(),
+            ("op", _) => {}
+            _ => {
+                input.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    if !input.contains_key("card_source")
+        && let Some(spec) = input.get("card_spec")
+    {
+        let source = if spec.get("asset_path").is_some() {
+            Some("asset")
+        } else if spec.get("catalog_name").is_some() {
+            Some("catalog")
+        } else {
+            None
+        };
+        if let Some(source) = source {
+            input.insert("card_source".into(), json!(source));
+        }
+    }
+    Some(CardContinuation {
+        op,
+        input: Value::Object(input),
+    })
+}
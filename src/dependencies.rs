@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use crate::expression::{escaped_placeholder, placeholder_end};
+use crate::model::{ElementDependencies, TemplateAnalysis};
+use crate::render::{is_simple_expression, whole_placeholder};
+
+/// Binding roots understood by [`crate::render::BindingContext`].
+pub(crate) const ROOTS: &[&str] = &[
+    "payload", "session", "state", "params", "template", "inputs", "viewer", "item",
+];
+
+/// Data paths referenced by every bound string in `card`, keyed by JSON pointer. Unrooted
+/// paths (e.g. `name`) are kept as written; lookups fall back across the binding roots.
+pub fn collect_dependencies(card: &Value) -> BTreeMap<String, BTreeSet<String>> {
+    fn walk(value: &Value, pointer: &str, out: &mut BTreeMap<String, BTreeSet<String>>) {
+        match value {
+            Value::String(text) => {
+                let deps = string_dependencies(text);
+                if !deps.is_empty() {
+                    out.insert(pointer.to_string(), deps);
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    walk(item, &format!("{pointer}/{idx}"), out);
+                }
+            }
+            Value::Object(map) => {
+                for (key, child) in map {
+                    let escaped = key.replace('~', "~0").replace('/', "~1");
+                    walk(child, &format!("{pointer}/{escaped}"), out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    walk(card, "", &mut out);
+    out
+}
+
+/// Groups the template's dependencies by the nearest enclosing object with a `type`
+/// (element, action, or the card root).
+pub fn analyze_template(card: &Value) -> TemplateAnalysis {
+    let mut paths = BTreeSet::new();
+    let mut elements: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (pointer, deps) in collect_dependencies(card) {
+        let owner = owning_element(card, &pointer);
+        paths.extend(deps.iter().cloned());
+        elements.entry(owner).or_default().extend(deps);
+    }
+    TemplateAnalysis {
+        paths: paths.into_iter().collect(),
+        elements: elements
+            .into_iter()
+            .map(|(pointer, deps)| {
+                let element = card.pointer(&pointer);
+                let field = |key: &str| {
+                    element
+                        .and_then(|e| e.get(key))
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string)
+                };
+                ElementDependencies {
+                    id: field("id"),
+                    element_type: field("type"),
+                    pointer,
+                    paths: deps.into_iter().collect(),
+                }
+            })
+            .collect(),
+    }
+}
+
+fn owning_element(card: &Value, pointer: &str) -> String {
+    let mut current = pointer;
+    while let Some((parent, _)) = current.rsplit_once('/') {
+        if card
+            .pointer(parent)
+            .is_some_and(|v| v.get("type").is_some_and(Value::is_string))
+        {
+            return parent.to_string();
+        }
+        current = parent;
+    }
+    String::new()
+}
+
+/// Paths referenced by a single template string: a whole-string `${expr}`, or any
+/// `${path}` / `@{path}` placeholders (with `|| default` fallbacks ignored) and embedded
+/// `${expr}` expressions.
+pub fn string_dependencies(text: &str) -> BTreeSet<String> {
+    let mut deps = BTreeSet::new();
+    if let Some(expr) = whole_placeholder(text, "${") {
+        deps.extend(expression_dependencies(expr));
+        return deps;
+    }
+    deps.extend(handlebars_dependencies(text));
+    let mut rest = text;
+    while let Some(start) = [rest.find("${"), rest.find("@{")]
+        .into_iter()
+        .flatten()
+        .min()
+    {
+        let after = &rest[start + 2..];
+        if escaped_placeholder(rest, start) {
+            rest = after;
+            continue;
+        }
+        let Some(end) = placeholder_end(after) else {
+            break;
+        };
+        let body = &after[..end];
+        if rest[start..].starts_with('$') && !is_simple_expression(body) {
+            deps.extend(expression_dependencies(body));
+        } else if let Some(path) = normalize(body.split("||").next().unwrap_or_default()) {
+            deps.insert(path);
+        }
+        rest = &after[end + 1..];
+    }
+    deps
+}
+
+/// Paths referenced by `{{ ... }}` Handlebars tags; block helper names, `else`, and `this`
+/// are skipped.
+fn handlebars_dependencies(text: &str) -> BTreeSet<String> {
+    let mut deps = BTreeSet::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let tag = after[..end].trim_matches(['{', '}', '~', ' ']);
+        let mut words = tag.split_whitespace();
+        if tag.starts_with(['#', '^']) {
+            words.next();
+        }
+        if !tag.starts_with(['/', '!', '>']) {
+            for word in words {
+                if word.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && !matches!(word, "else" | "this" | "as")
+                    && let Some(path) = normalize(word)
+                {
+                    deps.insert(path);
+                }
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    deps
+}
+
+/// Paths referenced by an expression: every identifier that is not a literal, keyword, or
+/// function name.
+pub fn expression_dependencies(expr: &str) -> BTreeSet<String> {
+    let mut deps = BTreeSet::new();
+    let chars: Vec<char> = expr.chars().collect();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let ch = chars[idx];
+        if ch == '\'' || ch == '"' {
+            idx += 1;
+            while idx < chars.len() && chars[idx] != ch {
+                idx += 1;
+            }
+            idx += 1;
+            continue;
+        }
+        if ch.is_ascii_alphabetic() || matches!(ch, '_' | '@' | '$') {
+            let start = idx;
+            while idx < chars.len()
+                && (chars[idx].is_ascii_alphanumeric()
+                    || matches!(chars[idx], '_' | '.' | '[' | ']' | '@' | '$' | '{' | '}')
+                    || (chars[idx] == '?' && chars.get(idx + 1) == Some(&'.')))
+            {
+                idx += 1;
+            }
+            let token: String = chars[start..idx].iter().collect();
+            let is_call = chars[idx..].iter().find(|c| !c.is_whitespace()) == Some(&'(');
+            if !is_call && let Some(path) = normalize(&token) {
+                deps.insert(path);
+            }
+            continue;
+        }
+        idx += 1;
+    }
+    deps
+}
+
+/// Whether a change at `changed` can alter a value read from `dependency`: one path is a
+/// prefix of the other at segment boundaries. Unrooted dependencies match under any root.
+pub fn affected_by(dependency: &str, changed: &str) -> bool {
+    fn overlaps(a: &str, b: &str) -> bool {
+        let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+        long == short || long.starts_with(&format!("{short}."))
+    }
+    let changed = normalize(changed).unwrap_or_default();
+    if overlaps(dependency, &changed) {
+        return true;
+    }
+    let rooted = dependency
+        .split('.')
+        .next()
+        .is_some_and(|root| ROOTS.contains(&root));
+    !rooted
+        && changed
+            .split_once('.')
+            .is_some_and(|(root, rest)| ROOTS.contains(&root) && overlaps(dependency, rest))
+}
+
+/// Canonical dotted form of a referenced path (`${a[0].b}` -> `a.0.b`); `None` for literals.
+pub fn normalize(raw: &str) -> Option<String> {
+    let path = raw
+        .trim()
+        .trim_start_matches(['@', '$'])
+        .trim_start_matches('{')
+        .trim_end_matches('}')
+        .replace("?.", ".")
+        .replace('[', ".")
+        .replace(']', "")
+        .replace("..", ".");
+    let path = path.trim_matches('.');
+    let keyword = ["true", "false", "null"]
+        .iter()
+        .any(|k| path.eq_ignore_ascii_case(k));
+    (!path.is_empty() && !keyword).then(|| path.to_string())
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::expression::{self, ExpressionEngine, SimpleExpressionEngine};
+use crate::model::{IssueSeverity, StateUpdateOp, ValidationIssue};
+use crate::render::BindingContext;
+use crate::state_store;
+
+/// Card root key mapping state paths to the expressions that compute them.
+pub const DERIVED_KEY: &str = "$derived";
+
+/// Removes the `$derived` declarations from the card root so they are neither bound nor
+/// delivered, returning them keyed by state path (a leading `state.` is dropped). Expressions
+/// may be written bare or wrapped in `${...}`.
+pub fn take_derived(card: &mut Value) -> BTreeMap<String, String> {
+    let Some(Value::Object(declared)) = card
+        .as_object_mut()
+        .and_then(|root| root.remove(DERIVED_KEY))
+    else {
+        return BTreeMap::new();
+    };
+    declared
+        .into_iter()
+        .filter_map(|(path, expr)| {
+            let expr = expr.as_str()?.trim();
+            let expr = expr
+                .strip_prefix("${")
+                .and_then(|inner| inner.strip_suffix('}'))
+                .unwrap_or(expr);
+            let path = path.strip_prefix("state.").unwrap_or(&path);
+            Some((path.to_string(), expr.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Evaluates the derived fields against `state` (the post-interaction state) in path order,
+/// so later fields see earlier ones, and writes them into `state`. Returns a `Set` for each
+/// field whose value changed, and a warning for each expression that could not be evaluated.
+pub fn derive_state(
+    derived: &BTreeMap<String, String>,
+    ctx: &BindingContext<'_>,
+    state: &mut Value,
+) -> (Vec<StateUpdateOp>, Vec<ValidationIssue>) {
+    let engine = SimpleExpressionEngine;
+    let mut updates = Vec::new();
+    let mut issues = Vec::new();
+    for (path, expr) in derived {
+        expression::take_function_error();
+        let Some(value) = engine.eval(expr, &ctx.with_state(state)) else {
+            let reason = expression::take_function_error()
+                .map(|reason| format!(" ({reason})"))
+                .unwrap_or_default();
+            issues.push(ValidationIssue {
+                code: "derived-state-failed".into(),
+                message: format!("derived state {path} could not be evaluated: {expr}{reason}"),
+                path: format!("/{DERIVED_KEY}/{path}"),
+                severity: Some(IssueSeverity::Warning),
+            });
+            continue;
+        };
+        let current = path.split('.').try_fold(&*state, |node, key| node.get(key));
+        if current == Some(&value) {
+            continue;
+        }
+        let update = StateUpdateOp::Set {
+            path: path.clone(),
+            value,
+        };
+        state_store::apply_updates(state, std::slice::from_ref(&update));
+        updates.push(update);
+    }
+    (updates, issues)
+}
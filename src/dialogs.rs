@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value, json};
+
+use crate::actions::ActionDeclaration;
+use crate::error::ComponentError;
+use crate::model::{
+    CardDialog, CardInteraction, CardInteractionType, HostPlatform, IssueSeverity, TargetProfile,
+    ValidationIssue,
+};
+use crate::urls;
+
+/// Action property declaring the dialog the action opens.
+pub const OPEN_DIALOG_KEY: &str = "openDialog";
+/// Action `data` key naming the dialog a Teams `task/fetch` submit opens.
+pub const DIALOG_ID_KEY: &str = "dialogId";
+
+/// Turns every action carrying `openDialog: {id?, title?, width?, height?, card | url}` into
+/// the host's dialog opener and returns the declared dialogs keyed by opener action id. On
+/// Teams, card dialogs become an `Action.Submit` with `msteams.type: task/fetch` and
+/// `dialogId` in its data, and URL dialogs an `Action.OpenUrl` to the task module deep link.
+/// Other hosts have no modals: card dialogs become an `Action.ShowCard` and URL dialogs an
+/// `Action.OpenUrl` to the page.
+pub fn apply_dialogs(
+    card: &mut Value,
+    profile: Option<&TargetProfile>,
+) -> (BTreeMap<String, CardDialog>, Vec<ValidationIssue>) {
+    let mut dialogs = BTreeMap::new();
+    let mut issues = Vec::new();
+    walk(card, "", profile, &mut dialogs, &mut issues);
+    (dialogs, issues)
+}
+
+/// The dialog an interaction opens: an `OpenDialog` interaction names it through `dialog_id`
+/// or its opener's `action_id`, and a Teams `task/fetch` arrives as a Submit or Execute of an
+/// opener whose data carries `dialogId`. Interactions submitted from a dialog open nothing.
+pub fn opened_dialog(
+    interaction: &CardInteraction,
+    declared: Option<&ActionDeclaration>,
+    dialogs: &BTreeMap<String, CardDialog>,
+) -> Result<Option<CardDialog>, ComponentError> {
+    let by_id = |id: &str| dialogs.values().find(|dialog| dialog.id == id).cloned();
+    match interaction.interaction_type {
+        CardInteractionType::OpenDialog => interaction
+            .dialog_id
+            .as_deref()
+            .and_then(by_id)
+            .or_else(|| dialogs.get(&interaction.action_id).cloned())
+            .map(Some)
+            .ok_or_else(|| {
+                ComponentError::InteractionInvalid(format!(
+                    "action {} does not open a dialog",
+                    interaction.action_id
+                ))
+            }),
+        CardInteractionType::Submit | CardInteractionType::Execute
+            if interaction.dialog_id.is_none() =>
+        {
+            Ok(declared
+                .and_then(|action| action.data())
+                .and_then(|data| data.get(DIALOG_ID_KEY))
+                .and_then(Value::as_str)
+                .and_then(by_id))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The dialog a `dialog_id` interaction was submitted from.
+pub fn submitted_dialog(
+    interaction: &CardInteraction,
+    dialogs: &BTreeMap<String, CardDialog>,
+) -> Result<Option<CardDialog>, ComponentError> {
+    let Some(id) = interaction.dialog_id.as_deref() else {
+        return Ok(None);
+    };
+    if interaction.interaction_type == CardInteractionType::OpenDialog {
+        return Ok(None);
+    }
+    dialogs
+        .values()
+        .find(|dialog| dialog.id == id)
+        .cloned()
+        .map(Some)
+        .ok_or_else(|| ComponentError::InteractionInvalid(format!("unknown dialog {id}")))
+}
+
+fn walk(
+    value: &mut Value,
+    path: &str,
+    profile: Option<&TargetProfile>,
+    dialogs: &mut BTreeMap<String, CardDialog>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    match value {
+        Value::Object(map) => {
+            if let Some(spec) = map.remove(OPEN_DIALOG_KEY) {
+                match declare(map, spec, profile) {
+                    Ok((opener, mut dialog)) => {
+                        if let Some(card) = &mut dialog.card {
+                            walk(
+                                card,
+                                &format!("{path}/{OPEN_DIALOG_KEY}/card"),
+                                profile,
+                                dialogs,
+                                issues,
+                            );
+                        }
+                        dialogs.insert(opener, dialog);
+                    }
+                    Err(message) => issues.push(ValidationIssue {
+                        code: "invalid-dialog".into(),
+                        message,
+                        path: format!("{path}/{OPEN_DIALOG_KEY}"),
+                        severity: Some(IssueSeverity::Warning),
+                    }),
+                }
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    walk(child, &format!("{path}/{key}"), profile, dialogs, issues);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                walk(item, &format!("{path}/{idx}"), profile, dialogs, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Rewrites the opener action for the host and returns its id with the declared dialog.
+fn declare(
+    action: &mut Map<String, Value>,
+    spec: Value,
+    profile: Option<&TargetProfile>,
+) -> Result<(String, CardDialog), String> {
+    let Value::Object(spec) = spec else {
+        return Err("openDialog must be an object".into());
+    };
+    let opener = action
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or("actions opening a dialog need an id")?
+        .to_string();
+    let text = |key: &str| spec.get(key).and_then(Value::as_str).map(str::to_string);
+    let dialog = CardDialog {
+        id: text("id").unwrap_or_else(|| opener.clone()),
+        title: text("title").or_else(|| {
+            action
+                .get("title")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        }),
+        width: spec.get("width").cloned(),
+        height: spec.get("height").cloned(),
+        card: spec.get("card").filter(|card| card.is_object()).cloned(),
+        url: text("url"),
+    };
+    let teams = profile.and_then(|p| p.host) == Some(HostPlatform::Teams);
+    match (&dialog.card, &dialog.url) {
+        (Some(_), _) if teams => {
+            let mut data = match action.remove("data") {
+                Some(Value::Object(data)) => data,
+                _ => Map::new(),
+            };
+            data.insert("msteams".into(), json!({ "type": "task/fetch" }));
+            data.insert(DIALOG_ID_KEY.into(), json!(dialog.id));
+            action.insert("type".into(), json!("Action.Submit"));
+            action.insert("data".into(), Value::Object(data));
+            action.remove("verb");
+        }
+        (Some(card), _) => {
+            action.insert("type".into(), json!("Action.ShowCard"));
+            action.insert("card".into(), card.clone());
+            action.remove("data");
+            action.remove("verb");
+        }
+        (None, Some(url)) => {
+            let url = if teams {
+                let mut params = Map::new();
+                params.insert("url".into(), json!(url));
+                for (key, value) in [
+                    ("title", dialog.title.clone().map(Value::String)),
+                    ("width", dialog.width.clone()),
+                    ("height", dialog.height.clone()),
+                ] {
+                    if let Some(value) = value {
+                        params.insert(key.into(), value);
+                    }
+                }
+                urls::deep_link(profile, "dialog", &params)?
+            } else {
+                url.clone()
+            };
+            action.insert("type".into(), json!("Action.OpenUrl"));
+            action.insert("url".into(), json!(url));
+            action.remove("data");
+            action.remove("verb");
+        }
+        (None, None) => return Err("openDialog needs a card or a url".into()),
+    }
+    Ok((opener, dialog))
+}
@@ -0,0 +1,271 @@
+use serde_json::{Map, Value, json};
+
+use crate::asset_resolver::ASSET_URL_PREFIX;
+use crate::card::{CardElement, ElementCommon, Image};
+use crate::expression::stringify_value;
+use crate::model::{IssueSeverity, ValidationIssue};
+
+/// Asset folder that `Icon` elements are downgraded from: `asset:icons/<name>.png`, or
+/// `<name>-filled.png` for the `Filled` style.
+pub const ICON_ASSET_DIR: &str = "icons";
+
+/// Pixel edge of each `Icon.size`.
+const ICON_SIZES: &[(&str, u32)] = &[
+    ("xxSmall", 12),
+    ("xSmall", 16),
+    ("Small", 20),
+    ("Standard", 24),
+    ("Medium", 28),
+    ("Large", 32),
+    ("xLarge", 40),
+    ("xxLarge", 48),
+];
+
+/// Post-binding fixes for elements whose bound data rarely arrives in schema shape:
+/// FactSet facts become `{title, value}` strings (a bound object becomes one fact per key),
+/// ImageSet `images` accept bare URLs and inherit `imageSize`, and `Icon` elements are
+/// checked and, for cards below version 1.5, replaced by an Image pointing at an icon asset.
+/// `ProgressBar` and `Spinner` values are coerced and clamped; below version 1.5 they become
+/// a TextBlock (`Label: 45%`), otherwise that TextBlock is their generated `fallback`.
+pub fn normalize_elements(card: &mut Value) -> Vec<ValidationIssue> {
+    let supports_v1_5 = card
+        .get("version")
+        .and_then(Value::as_str)
+        .and_then(|v| {
+            let (major, minor) = v.split_once('.')?;
+            Some((major.parse::<u32>().ok()?, minor.parse::<u32>().ok()?))
+        })
+        .is_some_and(|version| version >= (1, 5));
+    let mut issues = Vec::new();
+    walk(card, "", supports_v1_5, &mut issues);
+    issues
+}
+
+fn walk(value: &mut Value, path: &str, supports_v1_5: bool, issues: &mut Vec<ValidationIssue>) {
+    match value {
+        Value::Object(map) => {
+            match map.get("type").and_then(Value::as_str) {
+                Some("FactSet") => normalize_facts(map, path, issues),
+                Some("ImageSet") => normalize_image_set(map),
+                Some("Icon") => {
+                    if let Some(image) = normalize_icon(map, path, supports_v1_5, issues) {
+                        *value = image;
+                        return;
+                    }
+                }
+                Some("ProgressBar" | "Spinner") => {
+                    let text = normalize_progress(map, path, issues);
+                    if supports_v1_5 {
+                        map.entry("fallback").or_insert(text);
+                    } else {
+                        *value = text;
+                    }
+                    return;
+                }
+                _ => {}
+            }
+            for (key, child) in map.iter_mut() {
+                walk(child, &format!("{path}/{key}"), supports_v1_5, issues);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                walk(item, &format!("{path}/{idx}"), supports_v1_5, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn normalize_facts(map: &mut Map<String, Value>, path: &str, issues: &mut Vec<ValidationIssue>) {
+    let facts = match map.remove("facts") {
+        Some(Value::Array(facts)) => facts,
+        Some(Value::Object(pairs)) => pairs
+            .into_iter()
+            .map(|(title, value)| json!({ "title": title, "value": value }))
+            .collect(),
+        Some(Value::Null) | None => Vec::new(),
+        Some(other) => {
+            issues.push(issue(
+                "invalid-facts",
+                format!("FactSet facts must be an array, got {other}"),
+                format!("{path}/facts"),
+            ));
+            Vec::new()
+        }
+    };
+    let mut normalized = Vec::with_capacity(facts.len());
+    for (idx, fact) in facts.into_iter().enumerate() {
+        let Value::Object(mut fact) = fact else {
+            issues.push(issue(
+                "invalid-fact",
+                "Fact must be an object with title and value".to_string(),
+                format!("{path}/facts/{idx}"),
+            ));
+            continue;
+        };
+        for key in ["title", "value"] {
+            let text = match fact.get(key) {
+                Some(Value::String(_)) => continue,
+                Some(Value::Null) | None => String::new(),
+                Some(other) => stringify_value(other),
+            };
+            fact.insert(key.to_string(), Value::String(text));
+        }
+        normalized.push(Value::Object(fact));
+    }
+    map.insert("facts".to_string(), Value::Array(normalized));
+}
+
+fn normalize_image_set(map: &mut Map<String, Value>) {
+    let image_size = map
+        .get("imageSize")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let Some(Value::Array(images)) = map.get_mut("images") else {
+        return;
+    };
+    for image in images.iter_mut() {
+        if let Value::String(url) = image {
+            *image = json!({ "type": "Image", "url": url });
+        }
+        if let (Some(size), Value::Object(image)) = (image_size.as_ref(), image) {
+            image
+                .entry("size")
+                .or_insert_with(|| Value::String(size.clone()));
+        }
+    }
+}
+
+/// Returns the replacement Image when the card predates `Icon` support.
+fn normalize_icon(
+    map: &Map<String, Value>,
+    path: &str,
+    supports_icon: bool,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<Value> {
+    let CardElement::Icon(mut icon) = CardElement::from_value(Value::Object(map.clone())) else {
+        return None;
+    };
+    let Some(name) = icon.name.take().filter(|n| !n.is_empty()) else {
+        issues.push(issue(
+            "icon-missing-name",
+            "Icon requires a name".to_string(),
+            format!("{path}/name"),
+        ));
+        return None;
+    };
+    if supports_icon {
+        return None;
+    }
+    let filled = icon.style.as_deref() == Some("Filled");
+    let pixels = icon
+        .size
+        .as_deref()
+        .and_then(|size| ICON_SIZES.iter().find(|(name, _)| *name == size))
+        .map_or(24, |(_, px)| *px);
+    // Off-spec values of the carried-over properties stay in `extra` and are kept as they are.
+    let mut extra = Map::new();
+    for key in [
+        "id",
+        "isVisible",
+        "spacing",
+        "separator",
+        "horizontalAlignment",
+    ] {
+        if let Some(value) = icon.common.extra.remove(key) {
+            extra.insert(key.to_string(), value);
+        }
+    }
+    let image = Image {
+        url: Some(format!(
+            "{ASSET_URL_PREFIX}{ICON_ASSET_DIR}/{name}{}.png",
+            if filled { "-filled" } else { "" }
+        )),
+        alt_text: Some(name),
+        width: Some(format!("{pixels}px")),
+        select_action: icon.select_action,
+        common: ElementCommon {
+            id: icon.common.id,
+            is_visible: icon.common.is_visible,
+            separator: icon.common.separator,
+            spacing: icon.common.spacing,
+            height: Some(format!("{pixels}px")),
+            extra,
+            ..ElementCommon::default()
+        },
+        ..Image::default()
+    };
+    Some(CardElement::Image(image).to_value())
+}
+
+/// Coerces `value`/`max` to numbers, clamping `value` into `0..=max` (default 100), and
+/// returns the TextBlock that stands in for the element on older targets: the percentage,
+/// or just the label (`Loading…` without one) for spinners and indeterminate bars.
+fn normalize_progress(
+    map: &mut Map<String, Value>,
+    path: &str,
+    issues: &mut Vec<ValidationIssue>,
+) -> Value {
+    let mut number = |key: &str| -> Option<f64> {
+        let parsed = match map.get(key)? {
+            Value::Number(n) => n.as_f64(),
+            Value::String(raw) => raw.trim().parse::<f64>().ok(),
+            _ => None,
+        };
+        if parsed.is_none_or(|n| !n.is_finite()) {
+            issues.push(issue(
+                "invalid-progress-value",
+                format!("{key} must be a number"),
+                format!("{path}/{key}"),
+            ));
+            map.remove(key);
+        }
+        parsed.filter(|n| n.is_finite())
+    };
+    let max = number("max").filter(|max| *max > 0.0);
+    let value = number("value").map(|value| value.clamp(0.0, max.unwrap_or(100.0)));
+    if let Some(max) = max {
+        map.insert("max".into(), json_number(max));
+    }
+    if let Some(value) = value {
+        map.insert("value".into(), json_number(value));
+    }
+    let label = map.get("label").and_then(Value::as_str).unwrap_or_default();
+    let text = match value {
+        Some(value) => {
+            let percent = format!("{:.0}%", value / max.unwrap_or(100.0) * 100.0);
+            if label.is_empty() {
+                percent
+            } else {
+                format!("{label}: {percent}")
+            }
+        }
+        None if label.is_empty() => "Loading…".to_string(),
+        None => label.to_string(),
+    };
+    let mut text = json!({ "type": "TextBlock", "text": text, "wrap": true });
+    for key in ["id", "color", "isVisible", "separator", "spacing"] {
+        if let Some(copied) = map.get(key) {
+            text[key] = copied.clone();
+        }
+    }
+    text
+}
+
+fn json_number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::from(n as i64)
+    } else {
+        Value::from(n)
+    }
+}
+
+fn issue(code: &str, message: String, path: String) -> ValidationIssue {
+    ValidationIssue {
+        code: code.to_string(),
+        message,
+        path,
+        severity: Some(IssueSeverity::Warning),
+    }
+}
@@ -0,0 +1,130 @@
+use std::future::Future;
+
+use serde_json::{Map, Value};
+
+use crate::config;
+use crate::error::ComponentError;
+use crate::migrations;
+use crate::model::{
+    AdaptiveCardInvocation, AdaptiveCardResult, CardInteraction, ComponentConfig, InvocationMode,
+};
+use crate::state_store;
+
+/// Card state kept by the host (a database, a distributed cache) instead of the component's
+/// built-in store. Keys are the component's state keys (`adaptive-card:card:<instance>`).
+pub trait StateBackend: Send + Sync {
+    fn load(&self, key: &str)
+    -> impl Future<Output = Result<Option<Value>, ComponentError>> + Send;
+    fn save(
+        &self,
+        key: &str,
+        state: Value,
+    ) -> impl Future<Output = Result<(), ComponentError>> + Send;
+}
+
+/// Typed entry point for Rust hosts embedding the crate directly, without the string-in /
+/// string-out `handle_message` interface. The engine's configuration fills the fields an
+/// invocation leaves unset, ahead of the process-wide configuration.
+#[derive(Clone, Debug, Default)]
+pub struct AdaptiveCardEngine {
+    config: ComponentConfig,
+}
+
+impl AdaptiveCardEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: ComponentConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &ComponentConfig {
+        &self.config
+    }
+
+    /// Renders the card, ignoring any interaction on the invocation.
+    pub fn render(
+        &self,
+        mut inv: AdaptiveCardInvocation,
+    ) -> Result<AdaptiveCardResult, ComponentError> {
+        inv.interaction = None;
+        self.run(inv)
+    }
+
+    /// Validates the card without returning it.
+    pub fn validate(
+        &self,
+        mut inv: AdaptiveCardInvocation,
+    ) -> Result<AdaptiveCardResult, ComponentError> {
+        inv.interaction = None;
+        inv.mode = InvocationMode::Validate;
+        self.run(inv)
+    }
+
+    /// Applies a user interaction and returns the event, state updates, and re-rendered card.
+    pub fn interact(
+        &self,
+        mut inv: AdaptiveCardInvocation,
+        interaction: CardInteraction,
+    ) -> Result<AdaptiveCardResult, ComponentError> {
+        inv.interaction = Some(interaction);
+        self.run(inv)
+    }
+
+    /// [`render`](Self::render) against state loaded from `backend` when the invocation
+    /// carries none. The component's own state store is not used.
+    pub async fn render_async<B: StateBackend>(
+        &self,
+        mut inv: AdaptiveCardInvocation,
+        backend: &B,
+    ) -> Result<AdaptiveCardResult, ComponentError> {
+        let key = state_store::state_key_for(&inv, None);
+        load_state(&mut inv, &key, backend).await?;
+        state_store::detached(|| self.render(inv)).0
+    }
+
+    /// [`interact`](Self::interact) against state loaded from `backend`, saving the
+    /// post-interaction state back to it (except for `simulate`). The component's own state
+    /// store is not used, so idempotency records and rate-limit windows are not kept.
+    pub async fn interact_async<B: StateBackend>(
+        &self,
+        mut inv: AdaptiveCardInvocation,
+        interaction: CardInteraction,
+        backend: &B,
+    ) -> Result<AdaptiveCardResult, ComponentError> {
+        let key = state_store::state_key_for(&inv, Some(&interaction));
+        load_state(&mut inv, &key, backend).await?;
+        let (result, state) = state_store::detached(|| self.interact(inv, interaction));
+        let result = result?;
+        if let Some(state) = state {
+            backend.save(&key, state).await?;
+        }
+        Ok(result)
+    }
+
+    fn run(&self, mut inv: AdaptiveCardInvocation) -> Result<AdaptiveCardResult, ComponentError> {
+        config::apply_defaults(&mut inv, &self.config);
+        crate::handle_invocation(inv)
+    }
+}
+
+/// Fills the invocation's state from `backend` when it carries none, migrated to the version
+/// its template declares.
+async fn load_state<B: StateBackend>(
+    inv: &mut AdaptiveCardInvocation,
+    key: &str,
+    backend: &B,
+) -> Result<(), ComponentError> {
+    if !inv.state.is_null() {
+        return Ok(());
+    }
+    inv.state = match backend.load(key).await? {
+        Some(mut state) => {
+            migrations::migrate_state(inv, &mut state)?;
+            state
+        }
+        None => Value::Object(Map::new()),
+    };
+    Ok(())
+}
@@ -1,6 +1,8 @@
 use thiserror::Error;
 
-use crate::model::ValidationIssue;
+use serde_json::{Value, json};
+
+use crate::model::{AdaptiveCardFailure, FailureCode, ValidationIssue};
 
 #[derive(Debug, Error)]
 pub enum ComponentError {
@@ -24,4 +26,142 @@ pub enum ComponentError {
     InteractionInvalid(String),
     #[error("state store error: {0}")]
     StateStore(String),
+    /// The card instance exceeded its interaction rate limit.
+    #[error("rate limited: retry after {retry_after_secs}s")]
+    RateLimited {
+        retry_after_secs: u64,
+        /// Re-rendered card with the configured notice.
+        card: Option<Box<Value>>,
+    },
+    /// The render ran past the invocation's `time_budget`.
+    #[error("render exceeded its time budget of {budget_ms} ms")]
+    Timeout { budget_ms: u64 },
+    /// The template exceeded one of the invocation's `limits`.
+    #[error("{limit} of {max} exceeded: {detail}")]
+    LimitExceeded {
+        /// Name of the exceeded `limits` field.
+        limit: &'static str,
+        max: usize,
+        detail: String,
+    },
+}
+
+impl ComponentError {
+    /// Maps the error onto the typed failure returned to hosts.
+    pub fn to_failure(&self) -> AdaptiveCardFailure {
+        let (code, message, path, retryable) = match self {
+            ComponentError::InvalidInput(_) => {
+                (FailureCode::SchemaInvalid, "invalid input", "/", false)
+            }
+            ComponentError::Serde(_) => (FailureCode::SchemaInvalid, "invalid input", "/", false),
+            ComponentError::Io(_) => (FailureCode::SchemaInvalid, "io error", "/", true),
+            ComponentError::AssetNotFound(_) => (
+                FailureCode::AssetNotFound,
+                "asset not found",
+                "/card_spec",
+                false,
+            ),
+            ComponentError::AssetParse(_) => (
+                FailureCode::AssetParseError,
+                "asset parse error",
+                "/card_spec",
+                false,
+            ),
+            ComponentError::Asset(_) => (
+                FailureCode::AssetNotFound,
+                "asset error",
+                "/card_spec",
+                true,
+            ),
+            ComponentError::Binding(_) => (
+                FailureCode::BindingEvalError,
+                "binding evaluation error",
+                "/card_spec/inline_json",
+                false,
+            ),
+            ComponentError::CardValidation(issues) => {
+                return AdaptiveCardFailure {
+                    code: FailureCode::CardValidationFailed,
+                    message: "card validation failed".into(),
+                    path: issues.first().map(|issue| issue.path.clone()),
+                    retryable: false,
+                    details: Some(json!({ "validation_issues": issues })),
+                };
+            }
+            ComponentError::InteractionInvalid(_) => (
+                FailureCode::InteractionInvalid,
+                "interaction invalid",
+                "/interaction",
+                false,
+            ),
+            ComponentError::StateStore(_) => (
+                FailureCode::StateStoreError,
+                "state store error",
+                "/state",
+                true,
+            ),
+            ComponentError::Timeout { budget_ms } => {
+                return AdaptiveCardFailure {
+                    code: FailureCode::Timeout,
+                    message: self.to_string(),
+                    path: Some("/timeBudget".into()),
+                    retryable: true,
+                    details: Some(json!({ "budget_ms": budget_ms })),
+                };
+            }
+            ComponentError::LimitExceeded { limit, max, detail } => {
+                return AdaptiveCardFailure {
+                    code: FailureCode::LimitExceeded,
+                    message: self.to_string(),
+                    path: Some(format!("/limits/{limit}")),
+                    retryable: false,
+                    details: Some(json!({ "limit": limit, "max": max, "detail": detail })),
+                };
+            }
+            ComponentError::RateLimited {
+                retry_after_secs,
+                card,
+            } => {
+                let mut details = json!({ "retry_after_seconds": retry_after_secs });
+                if let Some(card) = card {
+                    details["rendered_card"] = (**card).clone();
+                }
+                return AdaptiveCardFailure {
+                    code: FailureCode::RateLimited,
+                    message: self.to_string(),
+                    path: Some("/interaction".into()),
+                    retryable: true,
+                    details: Some(details),
+                };
+            }
+        };
+        let detail = match self {
+            ComponentError::Serde(inner) => inner.to_string(),
+            ComponentError::Io(inner) => inner.to_string(),
+            ComponentError::InvalidInput(message)
+            | ComponentError::AssetNotFound(message)
+            | ComponentError::AssetParse(message)
+            | ComponentError::Asset(message)
+            | ComponentError::Binding(message)
+            | ComponentError::InteractionInvalid(message)
+            | ComponentError::StateStore(message) => message.clone(),
+            ComponentError::CardValidation(_)
+            | ComponentError::RateLimited { .. }
+            | ComponentError::Timeout { .. }
+            | ComponentError::LimitExceeded { .. } => String::new(),
+        };
+        AdaptiveCardFailure {
+            code,
+            message: message.to_string(),
+            path: Some(path.to_string()),
+            retryable,
+            details: Some(json!({
+                "validation_issues": [{
+                    "code": code,
+                    "message": detail,
+                    "path": path
+                }]
+            })),
+        }
+    }
 }
@@ -1,27 +1,52 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::OnceCell;
 use serde_json::Value;
 
+use crate::error::ComponentError;
+use crate::format;
+use crate::inputs::{number_value, numeric};
+use crate::limits::Limits;
 use crate::render::BindingContext;
+use crate::urls;
 
 /// Minimal expression engine interface so richer engines can be plugged in later.
 pub trait ExpressionEngine: Send + Sync {
     /// Evaluate an expression string against the binding context.
     /// Returns None on invalid expressions or when no resolution is possible.
-    fn eval(&self, expr: &str, ctx: &BindingContext) -> Option<Value>;
+    fn eval(&self, expr: &str, ctx: &BindingContext<'_>) -> Option<Value>;
 }
 
 /// Default lightweight engine supporting:
 /// - Path lookups (payload/session/state/params) via dotted notation.
-/// - Equality on scalar values using `==`.
+/// - Comparisons on scalar values using `==` and `!=`, and `<`, `<=`, `>`, `>=` on numbers or
+///   strings (so ISO dates compare chronologically).
 /// - Simple ternary `cond ? a : b`.
-/// - Graceful failure: returns None for unknown expressions or missing paths.
+/// - Null-coalescing `a ?? b` (`b` when `a` is missing or null) and safe navigation
+///   `a?.b` (null instead of a missing path).
+/// - Function calls: `localize('key')`, `formatNumber(n, decimals?)`,
+///   `formatCurrency(n, 'EUR')`, `formatDate(date, 'short' | 'long')`, `urlEncode(value)`,
+///   `urlDecode(text)`, `deepLink('kind', params)` or `deepLink('kind', 'key', value, ...)`,
+///   `default(value, fallback)` (as `value ?? fallback`), `length(list | text | object)`,
+///   and the aggregates `sum(list)`, `count(list)`, `min(list)`, `max(list)` (typically over a
+///   projection such as `state.form_data.items[].price`).
+/// - Host functions registered with [`register_expression_function`], called with their
+///   evaluated arguments.
+/// - Graceful failure: returns None for unknown expressions or missing paths, and for
+///   expressions over the context's length or node limits.
 #[derive(Default)]
 pub struct SimpleExpressionEngine;
 
 impl ExpressionEngine for SimpleExpressionEngine {
-    fn eval(&self, expr: &str, ctx: &BindingContext) -> Option<Value> {
+    fn eval(&self, expr: &str, ctx: &BindingContext<'_>) -> Option<Value> {
+        check_limits(expr, &ctx.limits()).ok()?;
         let trimmed = expr.trim();
         // Ternary: cond ? a : b
-        if let Some((cond_raw, rest)) = split_top_level(trimmed, '?') {
+        if let Some((cond_raw, rest)) = split_question(trimmed, false) {
             let (then_raw, else_raw) = split_top_level(rest, ':')?;
             let cond_val = self.eval(cond_raw.trim(), ctx)?;
             let branch = if truthy(&cond_val) {
@@ -32,27 +57,36 @@ impl ExpressionEngine for SimpleExpressionEngine {
             return Some(branch);
         }
 
-        // Equality
-        if let Some((left, right)) = split_equality(trimmed) {
+        // Null-coalescing: a ?? b
+        if let Some((left, right)) = split_question(trimmed, true) {
+            return coalesce(self.eval(left.trim(), ctx), || self.eval(right.trim(), ctx));
+        }
+
+        // Comparison
+        if let Some((left, op, right)) = split_comparison(trimmed) {
             let l = eval_atom(left.trim(), ctx)?;
             let r = eval_atom(right.trim(), ctx)?;
-            return Some(Value::Bool(equals(&l, &r)));
+            return compare(&l, op, &r).map(Value::Bool);
         }
 
         eval_atom(trimmed, ctx)
     }
 }
 
-fn eval_atom(expr: &str, ctx: &BindingContext) -> Option<Value> {
+fn eval_atom(expr: &str, ctx: &BindingContext<'_>) -> Option<Value> {
     // Path forms: @{path} or ${path} or bare path
     if let Some(path) = expr.strip_prefix("@{").and_then(|s| s.strip_suffix('}')) {
-        return ctx.lookup(path.trim());
+        return lookup_path(path.trim(), ctx);
     }
     if let Some(path) = expr.strip_prefix("${").and_then(|s| s.strip_suffix('}')) {
-        return ctx.lookup(path.trim());
+        return lookup_path(path.trim(), ctx);
     }
     if expr.starts_with('@') || expr.starts_with('$') {
-        return ctx.lookup(expr.trim_start_matches(&['@', '$'][..]));
+        return lookup_path(expr.trim_start_matches(&['@', '$'][..]), ctx);
+    }
+
+    if let Some((name, args)) = parse_call(expr) {
+        return call_function(name, &args, ctx);
     }
 
     // Literals
@@ -68,12 +102,314 @@ fn eval_atom(expr: &str, ctx: &BindingContext) -> Option<Value> {
     if expr.eq_ignore_ascii_case("null") {
         return Some(Value::Null);
     }
-    if expr.starts_with('"') && expr.ends_with('"') && expr.len() >= 2 {
-        return Some(Value::String(expr.trim_matches('"').to_string()));
+    if expr.len() >= 2
+        && ((expr.starts_with('"') && expr.ends_with('"'))
+            || (expr.starts_with('\'') && expr.ends_with('\'')))
+    {
+        return Some(Value::String(expr[1..expr.len() - 1].to_string()));
     }
 
     // Bare path
-    ctx.lookup(expr.trim())
+    lookup_path(expr.trim(), ctx)
+}
+
+/// Looks `path` up; a path navigated with `?.` resolves to null instead of failing when any
+/// part of it is missing.
+fn lookup_path(path: &str, ctx: &BindingContext<'_>) -> Option<Value> {
+    if !path.contains("?.") {
+        return ctx.lookup(path).map(Cow::into_owned);
+    }
+    Some(
+        ctx.lookup(&path.replace("?.", "."))
+            .map_or(Value::Null, Cow::into_owned),
+    )
+}
+
+/// `value` unless it is missing or null, else `fallback()`.
+fn coalesce(value: Option<Value>, fallback: impl FnOnce() -> Option<Value>) -> Option<Value> {
+    match value {
+        Some(value) if !value.is_null() => Some(value),
+        _ => fallback(),
+    }
+}
+
+/// Fails when `expr` is longer than `max_expression_length` bytes or has more than
+/// `max_expression_nodes` nodes. Checked before evaluation, so deeply chained ternaries or
+/// nested calls never reach the recursive evaluator.
+pub fn check_limits(expr: &str, limits: &Limits) -> Result<(), ComponentError> {
+    let preview = || expr.chars().take(40).collect::<String>();
+    if expr.len() > limits.max_expression_length {
+        return Err(ComponentError::LimitExceeded {
+            limit: "maxExpressionLength",
+            max: limits.max_expression_length,
+            detail: format!("expression of {} bytes: {}...", expr.len(), preview()),
+        });
+    }
+    let nodes = expression_nodes(expr);
+    if nodes > limits.max_expression_nodes {
+        return Err(ComponentError::LimitExceeded {
+            limit: "maxExpressionNodes",
+            max: limits.max_expression_nodes,
+            detail: format!("expression with {nodes} nodes: {}...", preview()),
+        });
+    }
+    Ok(())
+}
+
+/// Operands, operators, and calls in an expression: one operand, plus an operator and another
+/// operand for every `?`, `:`, `,`, comparison, `||`, and `&&`, plus one node per call.
+/// Quoted strings are skipped.
+fn expression_nodes(expr: &str) -> usize {
+    let mut nodes = 1;
+    let mut quote = None;
+    let mut chars = expr.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, '?' | ':' | ',') => nodes += 2,
+            (None, '=' | '|' | '&') if chars.peek() == Some(&ch) => {
+                chars.next();
+                nodes += 2;
+            }
+            (None, '!' | '<' | '>') => {
+                chars.next_if_eq(&'=');
+                nodes += 2;
+            }
+            (None, '(') => nodes += 1,
+            _ => {}
+        }
+    }
+    nodes
+}
+
+/// Splits `name(arg, ...)` into the function name and its top-level arguments.
+fn parse_call(expr: &str) -> Option<(&str, Vec<&str>)> {
+    let open = expr.find('(')?;
+    let name = &expr[..open];
+    if name.is_empty()
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        || !expr.ends_with(')')
+    {
+        return None;
+    }
+    let mut inner = &expr[open + 1..expr.len() - 1];
+    let mut args = Vec::new();
+    while !inner.trim().is_empty() {
+        match split_top_level(inner, ',') {
+            Some((arg, rest)) => {
+                args.push(arg.trim());
+                inner = rest;
+            }
+            None => {
+                args.push(inner.trim());
+                break;
+            }
+        }
+    }
+    Some((name, args))
+}
+
+/// Functions callable from `${...}` expressions, as listed in the manifest.
+pub const FUNCTIONS: &[&str] = &[
+    "localize",
+    "formatNumber",
+    "formatCurrency",
+    "formatDate",
+    "urlEncode",
+    "urlDecode",
+    "deepLink",
+    "sum",
+    "count",
+    "min",
+    "max",
+    "default",
+    "length",
+];
+
+fn call_function(name: &str, args: &[&str], ctx: &BindingContext<'_>) -> Option<Value> {
+    let engine = SimpleExpressionEngine;
+    // Handled before the arguments are evaluated, so a missing value falls back instead of
+    // failing the call.
+    if let ("default", [value, fallback]) = (name, args) {
+        return coalesce(engine.eval(value, ctx), || engine.eval(fallback, ctx));
+    }
+    let values = args
+        .iter()
+        .map(|arg| engine.eval(arg, ctx))
+        .collect::<Option<Vec<_>>>()?;
+    match (name, values.as_slice()) {
+        ("localize", [Value::String(key)]) => Some(Value::String(
+            ctx.localize(key).unwrap_or(key.as_str()).to_string(),
+        )),
+        ("formatNumber", [value]) => Some(Value::String(format::format_number(
+            numeric(value)?,
+            None,
+            ctx.locale(),
+        ))),
+        ("formatNumber", [value, decimals]) => Some(Value::String(format::format_number(
+            numeric(value)?,
+            Some(numeric(decimals)?.max(0.0) as usize),
+            ctx.locale(),
+        ))),
+        ("formatCurrency", [value, Value::String(currency)]) => Some(Value::String(
+            format::format_currency(numeric(value)?, currency, ctx.locale()),
+        )),
+        ("formatDate", [value]) => {
+            format::format_date(value, "short", ctx.locale()).map(Value::String)
+        }
+        ("formatDate", [value, Value::String(style)]) => {
+            format::format_date(value, style, ctx.locale()).map(Value::String)
+        }
+        ("urlEncode", [value]) => Some(Value::String(urls::url_encode(&stringify_value(value)))),
+        ("urlDecode", [Value::String(text)]) => Some(Value::String(urls::url_decode(text))),
+        ("deepLink", [Value::String(kind), rest @ ..]) => {
+            let params = match rest {
+                [Value::Object(params)] => params.clone(),
+                pairs if pairs.len() % 2 == 0 => pairs
+                    .chunks(2)
+                    .map(|pair| (stringify_value(&pair[0]), pair[1].clone()))
+                    .collect(),
+                _ => return None,
+            };
+            urls::deep_link(ctx.target_profile(), kind, &params)
+                .ok()
+                .map(Value::String)
+        }
+        ("count", [Value::Array(items)]) => Some(Value::from(items.len())),
+        ("length", [Value::Array(items)]) => Some(Value::from(items.len())),
+        ("length", [Value::Object(map)]) => Some(Value::from(map.len())),
+        ("length", [Value::String(text)]) => Some(Value::from(text.chars().count())),
+        ("sum", [Value::Array(items)]) => {
+            Some(number_value(items.iter().filter_map(numeric).sum::<f64>()))
+        }
+        ("min", [Value::Array(items)]) => items
+            .iter()
+            .filter_map(numeric)
+            .reduce(f64::min)
+            .map(number_value),
+        ("max", [Value::Array(items)]) => items
+            .iter()
+            .filter_map(numeric)
+            .reduce(f64::max)
+            .map(number_value),
+        _ if FUNCTIONS.contains(&name) => None,
+        _ => call_host_function(name, &values),
+    }
+}
+
+/// Domain function hosts expose to templates, such as `riskScore(payload.account)`. It is
+/// called with its evaluated arguments once their count is within `arity`.
+pub trait ExpressionFunction: Send + Sync {
+    fn arity(&self) -> RangeInclusive<usize>;
+    fn call(&self, args: &[Value]) -> Result<Value, String>;
+}
+
+type ExpressionFn = dyn Fn(&[Value]) -> Result<Value, String> + Send + Sync;
+
+pub struct CallbackExpressionFunction {
+    arity: RangeInclusive<usize>,
+    callback: Box<ExpressionFn>,
+}
+
+impl CallbackExpressionFunction {
+    pub fn new(arity: RangeInclusive<usize>, callback: Box<ExpressionFn>) -> Self {
+        Self { arity, callback }
+    }
+}
+
+impl ExpressionFunction for CallbackExpressionFunction {
+    fn arity(&self) -> RangeInclusive<usize> {
+        self.arity.clone()
+    }
+
+    fn call(&self, args: &[Value]) -> Result<Value, String> {
+        (self.callback)(args)
+    }
+}
+
+static HOST_FUNCTIONS: OnceCell<RwLock<BTreeMap<String, Arc<dyn ExpressionFunction>>>> =
+    OnceCell::new();
+
+fn functions_cell() -> &'static RwLock<BTreeMap<String, Arc<dyn ExpressionFunction>>> {
+    HOST_FUNCTIONS.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Registers a function templates can call by `name`, replacing any earlier one. Built-in
+/// functions cannot be replaced.
+pub fn register_expression_function(
+    name: &str,
+    function: Box<dyn ExpressionFunction>,
+) -> Result<(), &'static str> {
+    if FUNCTIONS.contains(&name) {
+        return Err("built-in expression functions cannot be replaced");
+    }
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err("expression function names must be alphanumeric");
+    }
+    functions_cell()
+        .write()
+        .map_err(|_| "expression function registry poisoned")?
+        .insert(name.to_string(), Arc::from(function));
+    Ok(())
+}
+
+pub fn register_expression_callback(
+    name: &str,
+    arity: RangeInclusive<usize>,
+    callback: Box<ExpressionFn>,
+) -> Result<(), &'static str> {
+    register_expression_function(
+        name,
+        Box::new(CallbackExpressionFunction::new(arity, callback)),
+    )
+}
+
+/// Names of the registered host functions, in name order.
+pub(crate) fn host_functions() -> Vec<String> {
+    functions_cell()
+        .read()
+        .map(|registered| registered.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+thread_local! {
+    static FUNCTION_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Why the last host function call on this thread failed, if it did, clearing it. Renderers
+/// add it to the binding error of the expression that failed.
+pub(crate) fn take_function_error() -> Option<String> {
+    FUNCTION_ERROR.with(|error| error.borrow_mut().take())
+}
+
+fn call_host_function(name: &str, args: &[Value]) -> Option<Value> {
+    let function = functions_cell()
+        .read()
+        .ok()
+        .and_then(|registered| registered.get(name).cloned());
+    let result = match function {
+        None => Err(format!("unknown function '{name}'")),
+        Some(function) if !function.arity().contains(&args.len()) => {
+            let arity = function.arity();
+            let expected = match (arity.start(), arity.end()) {
+                (min, max) if min == max => min.to_string(),
+                (min, &usize::MAX) => format!("at least {min}"),
+                (min, max) => format!("{min} to {max}"),
+            };
+            Err(format!(
+                "{name} expects {expected} argument(s), got {}",
+                args.len()
+            ))
+        }
+        Some(function) => function
+            .call(args)
+            .map_err(|err| format!("{name} failed: {err}")),
+    };
+    result
+        .map_err(|err| FUNCTION_ERROR.with(|error| *error.borrow_mut() = Some(err)))
+        .ok()
 }
 
 fn split_top_level(expr: &str, separator: char) -> Option<(&str, &str)> {
@@ -91,17 +427,53 @@ fn split_top_level(expr: &str, separator: char) -> Option<(&str, &str)> {
     None
 }
 
-fn split_equality(expr: &str) -> Option<(&str, &str)> {
+/// Whether the placeholder opening at byte `at` of `text` is escaped as `\${` / `$${` (or
+/// `\@{` / `@@{`), so the sequence is emitted literally.
+pub(crate) fn escaped_placeholder(text: &str, at: usize) -> bool {
+    let bytes = text.as_bytes();
+    at > 0 && (bytes[at - 1] == b'\\' || bytes[at - 1] == bytes[at])
+}
+
+/// Byte offset of the `}` closing a placeholder whose body starts `text` (just after `${` or
+/// `@{`), skipping quoted text and nested braces.
+pub(crate) fn placeholder_end(text: &str) -> Option<usize> {
+    let mut depth: i32 = 0;
+    let mut quote = None;
+    for (idx, ch) in text.bytes().enumerate() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, b'\'' | b'"') => quote = Some(ch),
+            (None, b'{') => depth += 1,
+            (None, b'}') if depth == 0 => return Some(idx),
+            (None, b'}') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits at the first top-level `??` when `coalesce` is set, else at the first top-level
+/// ternary `?`. Quoted text, `?.` navigation, and (for ternaries) `??` are skipped.
+fn split_question(expr: &str, coalesce: bool) -> Option<(&str, &str)> {
     let mut depth: i32 = 0;
+    let mut quote = None;
     let bytes = expr.as_bytes();
     let mut idx = 0;
-    while idx + 1 < bytes.len() {
-        match bytes[idx] as char {
-            '(' | '{' => depth += 1,
-            ')' | '}' => depth = depth.saturating_sub(1),
-            '=' if depth == 0 && bytes[idx + 1] == b'=' => {
-                return Some((&expr[..idx], &expr[idx + 2..]));
-            }
+    while idx < bytes.len() {
+        let ch = bytes[idx];
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, b'\'' | b'"') => quote = Some(ch),
+            (None, b'(' | b'{') => depth += 1,
+            (None, b')' | b'}') => depth = depth.saturating_sub(1),
+            (None, b'?') if depth == 0 => match bytes.get(idx + 1) {
+                Some(b'?') if coalesce => return Some((&expr[..idx], &expr[idx + 2..])),
+                Some(b'?' | b'.') => idx += 1,
+                _ if !coalesce => return Some((&expr[..idx], &expr[idx + 1..])),
+                _ => {}
+            },
             _ => {}
         }
         idx += 1;
@@ -109,6 +481,67 @@ fn split_equality(expr: &str) -> Option<(&str, &str)> {
     None
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Splits `left <op> right` at the first top-level comparison operator outside quotes.
+fn split_comparison(expr: &str) -> Option<(&str, Comparison, &str)> {
+    let mut depth: i32 = 0;
+    let mut quote = None;
+    let bytes = expr.as_bytes();
+    for idx in 0..bytes.len() {
+        let ch = bytes[idx];
+        let next = bytes.get(idx + 1).copied();
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, b'\'' | b'"') => quote = Some(ch),
+            (None, b'(' | b'{') => depth += 1,
+            (None, b')' | b'}') => depth = depth.saturating_sub(1),
+            (None, _) if depth == 0 => {
+                let (op, len) = match (ch, next) {
+                    (b'=', Some(b'=')) => (Comparison::Eq, 2),
+                    (b'!', Some(b'=')) => (Comparison::Ne, 2),
+                    (b'<', Some(b'=')) => (Comparison::Le, 2),
+                    (b'>', Some(b'=')) => (Comparison::Ge, 2),
+                    (b'<', _) => (Comparison::Lt, 1),
+                    (b'>', _) => (Comparison::Gt, 1),
+                    _ => continue,
+                };
+                return Some((&expr[..idx], op, &expr[idx + len..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Orders numbers numerically and strings lexicographically; ordering any other pair fails.
+fn compare(left: &Value, op: Comparison, right: &Value) -> Option<bool> {
+    let ordering = match op {
+        Comparison::Eq => return Some(equals(left, right)),
+        Comparison::Ne => return Some(!equals(left, right)),
+        _ => match (left, right) {
+            (Value::Number(x), Value::Number(y)) => x.as_f64()?.partial_cmp(&y.as_f64()?)?,
+            (Value::String(x), Value::String(y)) => x.cmp(y),
+            _ => return None,
+        },
+    };
+    Some(match op {
+        Comparison::Lt => ordering.is_lt(),
+        Comparison::Le => ordering.is_le(),
+        Comparison::Gt => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    })
+}
+
 pub(crate) fn truthy(v: &Value) -> bool {
     match v {
         Value::Bool(b) => *b,
@@ -0,0 +1,322 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use once_cell::sync::OnceCell;
+use serde_json::Value;
+
+use crate::model::{FallbackReplacement, IssueSeverity, TargetProfile, ValidationIssue};
+use crate::outlook;
+
+/// Element and action types with the Adaptive Cards schema version that introduced them.
+/// Types outside this registry (and the profile's `custom_elements`) are unknown.
+pub const KNOWN_TYPES: &[(&str, (u32, u32))] = &[
+    ("AdaptiveCard", (1, 0)),
+    ("TextBlock", (1, 0)),
+    ("Image", (1, 0)),
+    ("Container", (1, 0)),
+    ("ColumnSet", (1, 0)),
+    ("Column", (1, 0)),
+    ("FactSet", (1, 0)),
+    ("ImageSet", (1, 0)),
+    ("Input.Text", (1, 0)),
+    ("Input.Number", (1, 0)),
+    ("Input.Date", (1, 0)),
+    ("Input.Time", (1, 0)),
+    ("Input.Toggle", (1, 0)),
+    ("Input.ChoiceSet", (1, 0)),
+    ("Action.OpenUrl", (1, 0)),
+    ("Action.Submit", (1, 0)),
+    ("Action.ShowCard", (1, 0)),
+    ("Media", (1, 1)),
+    ("ActionSet", (1, 2)),
+    ("RichTextBlock", (1, 2)),
+    ("TextRun", (1, 2)),
+    ("Action.ToggleVisibility", (1, 2)),
+    ("Action.Execute", (1, 4)),
+    ("Table", (1, 5)),
+    ("TableRow", (1, 5)),
+    ("TableCell", (1, 5)),
+    ("Icon", (1, 5)),
+    ("Badge", (1, 5)),
+    ("CodeBlock", (1, 5)),
+    ("ProgressBar", (1, 5)),
+    ("ProgressRing", (1, 5)),
+    ("Spinner", (1, 5)),
+    ("Rating", (1, 5)),
+    ("Input.Rating", (1, 5)),
+    ("Chart.VerticalBar", (1, 5)),
+    ("Chart.HorizontalBar", (1, 5)),
+    ("Chart.VerticalBar.Grouped", (1, 5)),
+    ("Chart.HorizontalBar.Stacked", (1, 5)),
+    ("Chart.Line", (1, 5)),
+    ("Chart.Pie", (1, 5)),
+    ("Chart.Donut", (1, 5)),
+    ("Chart.Gauge", (1, 5)),
+    ("CompoundButton", (1, 5)),
+    ("Carousel", (1, 6)),
+    ("CarouselPage", (1, 6)),
+    ("Input.Slider", (1, 6)),
+    ("Input.ColorPicker", (1, 6)),
+];
+
+/// Adaptive Cards schema versions the renderer understands, oldest first.
+pub const SCHEMA_VERSIONS: &[&str] = &["1.0", "1.1", "1.2", "1.3", "1.4", "1.5", "1.6"];
+
+/// Properties that hold child elements or actions. Other objects (e.g. `data`, `choices`)
+/// are payloads and are never treated as elements, even when they carry a `type` key.
+pub const ELEMENT_KEYS: &[&str] = &[
+    "body",
+    "items",
+    "columns",
+    "rows",
+    "cells",
+    "actions",
+    "card",
+    "selectAction",
+    "inlines",
+    "images",
+    "pages",
+];
+
+static HOST_FEATURES: OnceCell<RwLock<BTreeMap<String, String>>> = OnceCell::new();
+
+fn features_cell() -> &'static RwLock<BTreeMap<String, String>> {
+    HOST_FEATURES.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Registers a host feature for `requires` checks on every render. A `target_profile`'s
+/// `features` entry for the same name takes precedence.
+pub fn register_host_feature(name: &str, version: &str) -> Result<(), &'static str> {
+    features_cell()
+        .write()
+        .map_err(|_| "host feature registry poisoned")?
+        .insert(name.to_string(), version.to_string());
+    Ok(())
+}
+
+enum Outcome {
+    Keep,
+    Drop,
+    /// Unsupported with no fallback of its own; an ancestor's fallback must replace it.
+    Bubble,
+}
+
+struct Walk<'a> {
+    profile: Option<&'a TargetProfile>,
+    max_version: Option<(u32, u32)>,
+    /// Host features; `None` when neither a profile nor registered features describe the
+    /// host, in which case `requires` is not enforced.
+    features: Option<BTreeMap<String, String>>,
+    replacements: Vec<FallbackReplacement>,
+    issues: Vec<ValidationIssue>,
+}
+
+/// Applies `fallback` semantics: an element or action whose type is unknown, newer than the
+/// profile's `max_version`, or listed in `unsupported` is replaced by its `fallback` element
+/// (checked again in turn) or removed for `"drop"`. Without a fallback, the nearest ancestor
+/// with one is replaced instead; when no ancestor has one, the element is dropped with an
+/// `unsupported-element` warning. Elements whose `requires` names a feature the host lacks,
+/// or at a lower version, take the same path once the host is described by a profile or by
+/// registered features.
+pub fn apply_fallbacks(
+    card: &mut Value,
+    profile: Option<&TargetProfile>,
+) -> (Vec<FallbackReplacement>, Vec<ValidationIssue>) {
+    let mut features = features_cell()
+        .read()
+        .map(|registered| registered.clone())
+        .unwrap_or_default();
+    if let Some(profile) = profile {
+        features.extend(profile.features.clone());
+    }
+    let enforce = profile.is_some() || !features.is_empty();
+    let mut walk = Walk {
+        profile,
+        max_version: match profile
+            .and_then(|p| p.max_version.as_deref())
+            .and_then(parse_version)
+        {
+            // Outlook never renders past its schema cap, whatever the profile claims.
+            max if outlook::is_outlook(profile) => {
+                Some(max.map_or(outlook::MAX_VERSION, |max| max.min(outlook::MAX_VERSION)))
+            }
+            max => max,
+        },
+        features: enforce.then_some(features),
+        replacements: Vec::new(),
+        issues: Vec::new(),
+    };
+    walk.children(card, "", false);
+    (walk.replacements, walk.issues)
+}
+
+pub fn parse_version(raw: &str) -> Option<(u32, u32)> {
+    let (major, minor) = raw.trim().split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+impl Walk<'_> {
+    fn element(&mut self, value: &mut Value, pointer: &str, ancestor_fallback: bool) -> Outcome {
+        let mut reason = None;
+        loop {
+            let Some(kind) = value
+                .get("type")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+            else {
+                return Outcome::Keep;
+            };
+            let fallback = value.get("fallback").cloned();
+            let has_fallback = matches!(&fallback, Some(Value::Object(_)))
+                || fallback.as_ref().and_then(Value::as_str) == Some("drop");
+            if reason.is_none() {
+                reason = self.unsupported(&kind).or_else(|| self.unmet(value));
+            }
+            if reason.is_none() {
+                match self.children(value, pointer, ancestor_fallback || has_fallback) {
+                    Outcome::Bubble if has_fallback => reason = Some("descendant"),
+                    outcome => return outcome,
+                }
+            }
+            let why = reason.take().unwrap_or_default();
+            match fallback {
+                Some(Value::Object(replacement)) => {
+                    let replaced_with = replacement
+                        .get("type")
+                        .and_then(Value::as_str)
+                        .map(str::to_string);
+                    self.record(pointer, &kind, replaced_with, why);
+                    *value = Value::Object(replacement);
+                }
+                Some(Value::String(drop)) if drop == "drop" => {
+                    self.record(pointer, &kind, None, why);
+                    return Outcome::Drop;
+                }
+                _ if ancestor_fallback => return Outcome::Bubble,
+                _ => {
+                    self.record(pointer, &kind, None, why);
+                    self.issues.push(ValidationIssue {
+                        code: "unsupported-element".into(),
+                        message: format!(
+                            "{kind} is {} and declares no fallback; dropped",
+                            describe(why)
+                        ),
+                        path: pointer.to_string(),
+                        severity: Some(IssueSeverity::Warning),
+                    });
+                    return Outcome::Drop;
+                }
+            }
+        }
+    }
+
+    /// Walks the element-bearing properties of `value`, removing dropped children.
+    fn children(&mut self, value: &mut Value, pointer: &str, ancestor_fallback: bool) -> Outcome {
+        let Value::Object(map) = value else {
+            return Outcome::Keep;
+        };
+        for key in ELEMENT_KEYS {
+            let child_pointer = format!("{pointer}/{key}");
+            match map.get_mut(*key) {
+                Some(Value::Array(items)) => {
+                    let mut idx = 0;
+                    let mut original = 0;
+                    while idx < items.len() {
+                        let item_pointer = format!("{child_pointer}/{original}");
+                        original += 1;
+                        match self.element(&mut items[idx], &item_pointer, ancestor_fallback) {
+                            Outcome::Keep => idx += 1,
+                            Outcome::Drop => {
+                                items.remove(idx);
+                            }
+                            Outcome::Bubble => return Outcome::Bubble,
+                        }
+                    }
+                }
+                Some(child @ Value::Object(_)) => {
+                    match self.element(child, &child_pointer, ancestor_fallback) {
+                        Outcome::Keep => {}
+                        Outcome::Drop => {
+                            map.remove(*key);
+                        }
+                        Outcome::Bubble => return Outcome::Bubble,
+                    }
+                }
+                _ => {}
+            }
+        }
+        Outcome::Keep
+    }
+
+    fn unsupported(&self, kind: &str) -> Option<&'static str> {
+        let profile = self.profile;
+        if profile.is_some_and(|p| p.unsupported.iter().any(|t| t == kind)) {
+            return Some("unsupported");
+        }
+        if profile.is_some_and(|p| p.custom_elements.iter().any(|t| t == kind)) {
+            return None;
+        }
+        match KNOWN_TYPES.iter().find(|(name, _)| *name == kind) {
+            None => Some("unknown"),
+            Some((_, since)) if self.max_version.is_some_and(|max| *since > max) => Some("version"),
+            Some(_) => None,
+        }
+    }
+
+    fn unmet(&self, value: &Value) -> Option<&'static str> {
+        let features = self.features.as_ref()?;
+        let requires = value.get("requires")?.as_object()?;
+        requires
+            .iter()
+            .any(|(name, needed)| {
+                let needed = needed.as_str().unwrap_or("*");
+                !features
+                    .get(name)
+                    .is_some_and(|have| have == "*" || compare_versions(have, needed).is_ge())
+            })
+            .then_some("requires")
+    }
+
+    fn record(&mut self, pointer: &str, kind: &str, replaced_with: Option<String>, why: &str) {
+        self.replacements.push(FallbackReplacement {
+            pointer: pointer.to_string(),
+            element_type: kind.to_string(),
+            replaced_with,
+            reason: why.to_string(),
+        });
+    }
+}
+
+/// Compares dotted versions numerically, treating missing parts as 0; `*` matches anything.
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+    if left == "*" || right == "*" {
+        return Ordering::Equal;
+    }
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (left, right) = (parts(left), parts(right));
+    for idx in 0..left.len().max(right.len()) {
+        let ordering = left
+            .get(idx)
+            .unwrap_or(&0)
+            .cmp(right.get(idx).unwrap_or(&0));
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn describe(reason: &str) -> &'static str {
+    match reason {
+        "requires" => "missing a required host feature",
+        "unsupported" => "unsupported by the target profile",
+        "version" => "newer than the target profile version",
+        _ => "unknown",
+    }
+}
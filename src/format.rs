@@ -0,0 +1,373 @@
+use serde_json::Value;
+
+use crate::i18n::DEFAULT_LOCALE;
+use crate::inputs::numeric;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DateOrder {
+    MonthDayYear,
+    DayMonthYear,
+    YearMonthDay,
+}
+
+/// Formatting conventions for one locale; a small built-in table stands in for ICU data.
+#[derive(Debug, Clone, Copy)]
+struct LocaleFormat {
+    language: &'static str,
+    decimal: char,
+    group: &'static str,
+    date_order: DateOrder,
+    date_separator: char,
+    /// Currency symbol follows the amount (`1.234,50 €`).
+    symbol_after: bool,
+    /// Space between the symbol and the amount.
+    symbol_space: bool,
+}
+
+impl LocaleFormat {
+    const fn new(language: &'static str, decimal: char, group: &'static str) -> Self {
+        LocaleFormat {
+            language,
+            decimal,
+            group,
+            date_order: DateOrder::DayMonthYear,
+            date_separator: '/',
+            symbol_after: true,
+            symbol_space: true,
+        }
+    }
+}
+
+fn locale_format(locale: Option<&str>) -> LocaleFormat {
+    let tag = locale
+        .unwrap_or(DEFAULT_LOCALE)
+        .replace('_', "-")
+        .to_ascii_lowercase();
+    let language = tag.split('-').next().unwrap_or(DEFAULT_LOCALE);
+    let english = LocaleFormat {
+        date_order: DateOrder::MonthDayYear,
+        symbol_after: false,
+        symbol_space: false,
+        ..LocaleFormat::new("en", '.', ",")
+    };
+    match (language, tag.as_str()) {
+        ("en", "en-gb" | "en-au" | "en-nz" | "en-ie" | "en-in" | "en-za") => LocaleFormat {
+            date_order: DateOrder::DayMonthYear,
+            ..english
+        },
+        ("en", "en-ca") => LocaleFormat {
+            date_order: DateOrder::YearMonthDay,
+            date_separator: '-',
+            ..english
+        },
+        ("de", "de-ch") => LocaleFormat {
+            decimal: '.',
+            group: "\u{2019}",
+            date_separator: '.',
+            symbol_after: false,
+            ..LocaleFormat::new("de", ',', ".")
+        },
+        ("de", _) => LocaleFormat {
+            date_separator: '.',
+            ..LocaleFormat::new("de", ',', ".")
+        },
+        ("fr", "fr-ch") => LocaleFormat {
+            decimal: '.',
+            date_separator: '.',
+            ..LocaleFormat::new("fr", ',', "\u{202F}")
+        },
+        ("fr", "fr-ca") => LocaleFormat {
+            group: "\u{A0}",
+            date_order: DateOrder::YearMonthDay,
+            date_separator: '-',
+            ..LocaleFormat::new("fr", ',', "\u{202F}")
+        },
+        ("fr", _) => LocaleFormat::new("fr", ',', "\u{202F}"),
+        ("es", _) => LocaleFormat::new("es", ',', "."),
+        ("it", _) => LocaleFormat::new("it", ',', "."),
+        ("pt", "pt-br") => LocaleFormat {
+            symbol_after: false,
+            ..LocaleFormat::new("pt", ',', ".")
+        },
+        ("pt", _) => LocaleFormat::new("pt", ',', "\u{A0}"),
+        ("nl", _) => LocaleFormat {
+            date_separator: '-',
+            symbol_after: false,
+            ..LocaleFormat::new("nl", ',', ".")
+        },
+        ("ja" | "zh", _) => LocaleFormat {
+            language: "cjk",
+            date_order: DateOrder::YearMonthDay,
+            ..english
+        },
+        _ => english,
+    }
+}
+
+/// Formats a number with locale grouping and decimal separators. Without `decimals`, up to
+/// three fraction digits are kept and trailing zeros are trimmed.
+pub fn format_number(value: f64, decimals: Option<usize>, locale: Option<&str>) -> String {
+    let fmt = locale_format(locale);
+    let mut fixed = format!("{:.*}", decimals.unwrap_or(3), value.abs());
+    if decimals.is_none() && fixed.contains('.') {
+        fixed = fixed
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string();
+    }
+    let (int_part, frac_part) = match fixed.split_once('.') {
+        Some((int_part, frac_part)) => (int_part.to_string(), Some(frac_part.to_string())),
+        None => (fixed, None),
+    };
+
+    let mut grouped = String::new();
+    for (idx, ch) in int_part.chars().enumerate() {
+        if idx > 0 && (int_part.len() - idx) % 3 == 0 {
+            grouped.push_str(fmt.group);
+        }
+        grouped.push(ch);
+    }
+    let negative = value < 0.0 && fixed_is_nonzero(&grouped, frac_part.as_deref());
+    let mut out = String::new();
+    if negative {
+        out.push('-');
+    }
+    out.push_str(&grouped);
+    if let Some(frac) = frac_part {
+        out.push(fmt.decimal);
+        out.push_str(&frac);
+    }
+    out
+}
+
+fn fixed_is_nonzero(int_part: &str, frac_part: Option<&str>) -> bool {
+    int_part
+        .chars()
+        .chain(frac_part.unwrap_or_default().chars())
+        .any(|c| c.is_ascii_digit() && c != '0')
+}
+
+fn currency_symbol(code: &str) -> (&str, usize) {
+    match code {
+        "USD" => ("$", 2),
+        "EUR" => ("€", 2),
+        "GBP" => ("£", 2),
+        "JPY" => ("¥", 0),
+        "CNY" => ("CN¥", 2),
+        "INR" => ("₹", 2),
+        "BRL" => ("R$", 2),
+        "CAD" => ("CA$", 2),
+        "AUD" => ("A$", 2),
+        "KRW" => ("₩", 0),
+        other => (other, 2),
+    }
+}
+
+/// Formats an amount in an ISO 4217 currency using the locale's symbol placement.
+pub fn format_currency(value: f64, currency: &str, locale: Option<&str>) -> String {
+    let fmt = locale_format(locale);
+    let code = currency.trim().to_ascii_uppercase();
+    let (symbol, digits) = currency_symbol(&code);
+    let amount = format_number(value, Some(digits), locale);
+    let (sign, amount) = match amount.strip_prefix('-') {
+        Some(rest) => ("-", rest.to_string()),
+        None => ("", amount),
+    };
+    let space = if fmt.symbol_space || symbol == code {
+        "\u{A0}"
+    } else {
+        ""
+    };
+    if fmt.symbol_after {
+        format!("{sign}{amount}{space}{symbol}")
+    } else {
+        format!("{sign}{symbol}{space}{amount}")
+    }
+}
+
+/// Formats an ISO 8601 date (or date-time) or a Unix timestamp in seconds. `style` is
+/// `short` (numeric, locale date order) or `long` (month name).
+pub fn format_date(value: &Value, style: &str, locale: Option<&str>) -> Option<String> {
+    let (year, month, day) = parse_date(value)?;
+    let fmt = locale_format(locale);
+    if style.eq_ignore_ascii_case("long") {
+        let name = month_name(fmt.language, month);
+        return Some(match fmt.language {
+            "en" if fmt.date_order == DateOrder::MonthDayYear => {
+                format!("{name} {day}, {year}")
+            }
+            "de" => format!("{day}. {name} {year}"),
+            "es" | "pt" => format!("{day} de {name} de {year}"),
+            "cjk" => format!("{year}年{month}月{day}日"),
+            _ => format!("{day} {name} {year}"),
+        });
+    }
+    let sep = fmt.date_separator;
+    Some(match fmt.date_order {
+        DateOrder::MonthDayYear => format!("{month}{sep}{day}{sep}{year}"),
+        DateOrder::DayMonthYear => format!("{day:02}{sep}{month:02}{sep}{year}"),
+        DateOrder::YearMonthDay => format!("{year}{sep}{month:02}{sep}{day:02}"),
+    })
+}
+
+pub(crate) fn parse_date(value: &Value) -> Option<(i64, u32, u32)> {
+    if let Value::String(text) = value
+        && let Some(date) = text.trim().get(..10)
+    {
+        let mut parts = date.splitn(3, '-');
+        let year = parts.next()?.parse::<i64>().ok()?;
+        let month = parts.next()?.parse::<u32>().ok()?;
+        let day = parts.next()?.parse::<u32>().ok()?;
+        return ((1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day))
+            .then_some((year, month, day));
+    }
+    let seconds = numeric(value)?;
+    Some(civil_from_days((seconds / 86_400.0).floor() as i64))
+}
+
+/// Days in `month` of the proleptic Gregorian `year`.
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ` for a Unix timestamp in seconds.
+pub(crate) fn iso_timestamp(seconds: i64) -> String {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+/// Converts days since the Unix epoch to a proleptic Gregorian date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn month_name(language: &str, month: u32) -> &'static str {
+    const EN: [&str; 12] = [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ];
+    const FR: [&str; 12] = [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ];
+    const DE: [&str; 12] = [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ];
+    const ES: [&str; 12] = [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ];
+    const PT: [&str; 12] = [
+        "janeiro",
+        "fevereiro",
+        "março",
+        "abril",
+        "maio",
+        "junho",
+        "julho",
+        "agosto",
+        "setembro",
+        "outubro",
+        "novembro",
+        "dezembro",
+    ];
+    const IT: [&str; 12] = [
+        "gennaio",
+        "febbraio",
+        "marzo",
+        "aprile",
+        "maggio",
+        "giugno",
+        "luglio",
+        "agosto",
+        "settembre",
+        "ottobre",
+        "novembre",
+        "dicembre",
+    ];
+    const NL: [&str; 12] = [
+        "januari",
+        "februari",
+        "maart",
+        "april",
+        "mei",
+        "juni",
+        "juli",
+        "augustus",
+        "september",
+        "oktober",
+        "november",
+        "december",
+    ];
+    let names = match language {
+        "fr" => &FR,
+        "de" => &DE,
+        "es" => &ES,
+        "pt" => &PT,
+        "it" => &IT,
+        "nl" => &NL,
+        _ => &EN,
+    };
+    names[(month.clamp(1, 12) - 1) as usize]
+}
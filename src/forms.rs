@@ -0,0 +1,664 @@
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value, json};
+
+use crate::error::ComponentError;
+use crate::inputs::NEST_INPUTS_KEY;
+use crate::limits::Limits;
+use crate::model::{AdaptiveCardInvocation, AdaptiveCardResult, IssueSeverity, ValidationIssue};
+use crate::render::analyze_features;
+use crate::yaml;
+
+/// Version of generated cards: the first with input labels and `isRequired`.
+const GENERATED_VERSION: &str = "1.5";
+/// Id of the Submit action of generated cards.
+pub const SUBMIT_ID: &str = "submit";
+
+/// Collected form: a title, an intro, and the inputs in order, with headings for nested
+/// objects.
+#[derive(Debug, Default)]
+pub(crate) struct Form {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub items: Vec<FormItem>,
+    pub submit_title: Option<String>,
+    /// Makes the submit action an Action.Execute with this verb.
+    pub verb: Option<String>,
+    /// Actions replacing the default submit action.
+    pub actions: Vec<Value>,
+    /// Styles the card with `@theme.` tokens: a highlighted header, accented section
+    /// headings, and a positive primary action.
+    pub house_style: bool,
+}
+
+#[derive(Debug)]
+pub(crate) enum FormItem {
+    Heading(String),
+    /// An input element, complete except for `label` and `isRequired` (and `errorMessage`
+    /// unless it has a custom one).
+    Input {
+        label: String,
+        required: bool,
+        input: Map<String, Value>,
+    },
+}
+
+/// `generate-card-from-schema`: builds a card template collecting `source_schema`, either a
+/// JSON Schema (object properties become inputs) or a form description (`fields`). Input ids
+/// are the property paths, so the Submit event's inputs match the schema.
+pub fn generate_card_from_schema(
+    inv: &AdaptiveCardInvocation,
+) -> Result<AdaptiveCardResult, ComponentError> {
+    let Some(source) = inv.source_schema.as_ref() else {
+        return Err(ComponentError::InvalidInput(
+            "generate-card-from-schema requires source_schema".into(),
+        ));
+    };
+    let mut issues = Vec::new();
+    let form = if source.get("fields").is_some_and(Value::is_array) {
+        from_description(source, &mut issues)
+    } else {
+        from_json_schema(source, &mut issues)
+    };
+    let card = build_card(&form);
+    Ok(AdaptiveCardResult {
+        card_features: analyze_features(&card),
+        rendered_card: Some(card),
+        validation_issues: issues,
+        ..Default::default()
+    })
+}
+
+/// `compile-form`: expands the `form` DSL, a form description given as an object or as
+/// YAML or JSON text, into a card template with the house styling (see [`from_description`]).
+pub fn compile_form(inv: &AdaptiveCardInvocation) -> Result<AdaptiveCardResult, ComponentError> {
+    let source = match inv.form.as_ref() {
+        Some(Value::String(text)) if text.trim_start().starts_with('{') => {
+            serde_json::from_str(text)
+                .map_err(|err| ComponentError::InvalidInput(format!("form: {err}")))?
+        }
+        Some(Value::String(text)) => {
+            let max_depth = Limits::resolve(inv.limits.as_ref()).max_nesting;
+            yaml::parse(text, max_depth)
+                .map_err(|err| ComponentError::InvalidInput(format!("form: {err}")))?
+        }
+        Some(form) => form.clone(),
+        None => {
+            return Err(ComponentError::InvalidInput(
+                "compile-form requires form".into(),
+            ));
+        }
+    };
+    if !source.get("fields").is_some_and(Value::is_array) {
+        return Err(ComponentError::InvalidInput(
+            "form must be a mapping with a fields list".into(),
+        ));
+    }
+    let mut issues = Vec::new();
+    let mut form = from_description(&source, &mut issues);
+    form.house_style = true;
+    let card = build_card(&form);
+    Ok(AdaptiveCardResult {
+        card_features: analyze_features(&card),
+        rendered_card: Some(card),
+        validation_issues: issues,
+        ..Default::default()
+    })
+}
+
+/// Reads a JSON Schema object. Properties map to inputs by type and format: strings to
+/// Input.Text (`email`, `uri`, and phone formats set its style, `maxLength` and `pattern`
+/// carry over), `date` and `time` formats to Input.Date and Input.Time, enums to
+/// ChoiceSets, numbers and integers to Input.Number (with `minimum`/`maximum`), booleans to
+/// Input.Toggle, and arrays of enums to multi-select ChoiceSets. Nested objects add a heading
+/// and dotted ids (`address.city`), submitted as nested objects. Local `$ref`s are followed;
+/// anything else is skipped with a `schema-import-unsupported` warning.
+pub(crate) fn from_json_schema(schema: &Value, issues: &mut Vec<ValidationIssue>) -> Form {
+    let mut form = Form {
+        title: schema
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        description: schema
+            .get("description")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        ..Default::default()
+    };
+    collect_properties(schema, schema, "", &mut form.items, issues, 0);
+    form
+}
+
+pub(crate) fn collect_properties(
+    root: &Value,
+    object: &Value,
+    prefix: &str,
+    items: &mut Vec<FormItem>,
+    issues: &mut Vec<ValidationIssue>,
+    depth: usize,
+) {
+    let object = resolve_ref(root, object);
+    let required: BTreeSet<&str> = object
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let Some(properties) = object.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    for (name, property) in properties {
+        let property = resolve_ref(root, property);
+        let id = format!("{prefix}{name}");
+        let label = property
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| humanize(name));
+        if property.get("readOnly").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+        if schema_type(property) == Some("object") {
+            if depth >= 8 {
+                issues.push(unsupported(&id, "objects nested deeper than 8 levels"));
+                continue;
+            }
+            items.push(FormItem::Heading(label));
+            collect_properties(root, property, &format!("{id}."), items, issues, depth + 1);
+            continue;
+        }
+        match schema_input(root, property) {
+            Some(mut input) => {
+                input.insert("id".into(), json!(id));
+                if let Some(description) = property.get("description").and_then(Value::as_str) {
+                    input
+                        .entry("placeholder")
+                        .or_insert_with(|| json!(description));
+                }
+                if let Some(default) = property.get("default") {
+                    input.insert("value".into(), input_value(default));
+                }
+                items.push(FormItem::Input {
+                    label,
+                    required: required.contains(name.as_str()),
+                    input,
+                });
+            }
+            None => issues.push(unsupported(
+                &id,
+                &format!("{} properties", schema_type(property).unwrap_or("untyped")),
+            )),
+        }
+    }
+}
+
+/// Follows a local `#/...` `$ref`; other references resolve to the referencing schema.
+pub(crate) fn resolve_ref<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    let mut current = schema;
+    for _ in 0..16 {
+        let Some(pointer) = current
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+        else {
+            break;
+        };
+        match root.pointer(pointer) {
+            Some(target) => current = target,
+            None => break,
+        }
+    }
+    current
+}
+
+/// The property's type, ignoring `null` in type unions.
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(kind)) => Some(kind),
+        Some(Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|kind| *kind != "null"),
+        _ if schema.get("properties").is_some() => Some("object"),
+        _ if schema.get("enum").is_some() => Some("string"),
+        _ => None,
+    }
+}
+
+pub(crate) fn schema_input(root: &Value, schema: &Value) -> Option<Map<String, Value>> {
+    let mut input = Map::new();
+    let enumerated = |schema: &Value| -> Option<Vec<Value>> {
+        let values = schema.get("enum")?.as_array()?;
+        Some(
+            values
+                .iter()
+                .filter(|value| !value.is_null())
+                .map(|value| {
+                    let value = scalar_text(value);
+                    json!({ "title": value, "value": value })
+                })
+                .collect(),
+        )
+    };
+    if let Some(choices) = enumerated(schema) {
+        input.insert("type".into(), json!("Input.ChoiceSet"));
+        input.insert("choices".into(), json!(choices));
+        return Some(input);
+    }
+    match schema_type(schema)? {
+        "string" => match schema.get("format").and_then(Value::as_str) {
+            Some("date") => {
+                input.insert("type".into(), json!("Input.Date"));
+            }
+            Some("time") => {
+                input.insert("type".into(), json!("Input.Time"));
+            }
+            format => {
+                input.insert("type".into(), json!("Input.Text"));
+                let style = match format {
+                    Some("email") => Some("email"),
+                    Some("uri" | "url") => Some("url"),
+                    Some("phone" | "tel") => Some("tel"),
+                    _ => None,
+                };
+                if let Some(style) = style {
+                    input.insert("style".into(), json!(style));
+                }
+                if let Some(max) = schema.get("maxLength") {
+                    input.insert("maxLength".into(), max.clone());
+                    if max.as_u64().is_some_and(|max| max > 200) {
+                        input.insert("isMultiline".into(), json!(true));
+                    }
+                }
+                if let Some(pattern) = schema.get("pattern") {
+                    input.insert("regex".into(), pattern.clone());
+                }
+            }
+        },
+        "number" | "integer" => {
+            input.insert("type".into(), json!("Input.Number"));
+            for (from, to) in [("minimum", "min"), ("maximum", "max")] {
+                if let Some(bound) = schema.get(from) {
+                    input.insert(to.into(), bound.clone());
+                }
+            }
+        }
+        "boolean" => {
+            input.insert("type".into(), json!("Input.Toggle"));
+            input.insert("title".into(), json!(""));
+        }
+        "array" => {
+            let choices = enumerated(resolve_ref(root, schema.get("items")?))?;
+            input.insert("type".into(), json!("Input.ChoiceSet"));
+            input.insert("isMultiSelect".into(), json!(true));
+            input.insert("choices".into(), json!(choices));
+        }
+        _ => return None,
+    }
+    Some(input)
+}
+
+/// Reads a form description: `{title?, description?, submit?, fields: [...], actions?}`
+/// where each field has an `id` (or `name`), a `type` (`text`, `textarea`, `email`, `url`,
+/// `tel`, `number`, `integer`, `boolean`, `date`, `time`, `select`, `multiselect`), and
+/// optional `label`, `required`, `placeholder`, `default`, `options` (strings or
+/// `{title, value}`), `min`, `max`, `maxLength`, `pattern`, and `message` (the error shown
+/// when validation fails). A `{section: <title>}` entry adds a heading. `actions` replace the
+/// submit action (see [`form_action`]).
+pub(crate) fn from_description(description: &Value, issues: &mut Vec<ValidationIssue>) -> Form {
+    let text = |key: &str| {
+        description
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let mut form = Form {
+        title: text("title"),
+        description: text("description"),
+        submit_title: text("submit"),
+        ..Default::default()
+    };
+    let fields = description
+        .get("fields")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    for (idx, field) in fields.enumerate() {
+        if let Some(section) = field.get("section").and_then(Value::as_str) {
+            form.items.push(FormItem::Heading(section.to_string()));
+            continue;
+        }
+        let Some(id) = field
+            .get("id")
+            .or_else(|| field.get("name"))
+            .and_then(Value::as_str)
+        else {
+            issues.push(unsupported(
+                &format!("fields/{idx}"),
+                "fields without an id",
+            ));
+            continue;
+        };
+        let kind = field.get("type").and_then(Value::as_str).unwrap_or("text");
+        let Some(mut input) = field_input(kind, field) else {
+            issues.push(unsupported(id, &format!("{kind} fields")));
+            continue;
+        };
+        input.insert("id".into(), json!(id));
+        for key in ["placeholder", "min", "max", "maxLength"] {
+            if let Some(value) = field.get(key) {
+                input.insert(key.into(), value.clone());
+            }
+        }
+        if let Some(pattern) = field.get("pattern") {
+            input.insert("regex".into(), pattern.clone());
+        }
+        if let Some(default) = field.get("default") {
+            input.insert("value".into(), input_value(default));
+        }
+        if let Some(message) = field.get("message") {
+            input.insert("errorMessage".into(), message.clone());
+        }
+        form.items.push(FormItem::Input {
+            label: field
+                .get("label")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| humanize(id)),
+            required: field.get("required").and_then(Value::as_bool) == Some(true),
+            input,
+        });
+    }
+    let actions = description
+        .get("actions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    for (idx, action) in actions.enumerate() {
+        match form_action(action) {
+            Some(action) => form.actions.push(action),
+            None => issues.push(ValidationIssue {
+                code: "form-action-unsupported".into(),
+                message: "actions need a title or id and a type of submit, execute, or url; \
+                          skipped"
+                    .into(),
+                path: format!("/actions/{idx}"),
+                severity: Some(IssueSeverity::Warning),
+            }),
+        }
+    }
+    form
+}
+
+/// Card action for a form description action: `{title, id?, type?, verb?, url?, style?,
+/// data?, validate?}`. The type defaults to `url` with a `url`, `execute` with a `verb`,
+/// and `submit` otherwise; the id to the slugged title. `validate: false` submits without
+/// validating or sending the inputs.
+fn form_action(action: &Value) -> Option<Value> {
+    let text = |key: &str| action.get(key).and_then(Value::as_str);
+    let title = text("title");
+    let id = match text("id") {
+        Some(id) => id.to_string(),
+        None => slug(title?),
+    };
+    let kind = text("type").unwrap_or(if action.get("url").is_some() {
+        "url"
+    } else if action.get("verb").is_some() {
+        "execute"
+    } else {
+        "submit"
+    });
+    let mut card_action = json!({
+        "id": id,
+        "title": title.map_or_else(|| humanize(&id), str::to_string),
+    });
+    match kind {
+        "submit" | "execute" => {
+            card_action["type"] = json!(if kind == "submit" {
+                "Action.Submit"
+            } else {
+                "Action.Execute"
+            });
+            if let Some(verb) = action.get("verb") {
+                card_action["verb"] = verb.clone();
+            }
+            if let Some(data) = action.get("data") {
+                card_action["data"] = data.clone();
+            }
+            if action.get("validate").and_then(Value::as_bool) == Some(false) {
+                card_action["associatedInputs"] = json!("none");
+            }
+        }
+        "url" => {
+            card_action["type"] = json!("Action.OpenUrl");
+            card_action["url"] = action.get("url")?.clone();
+        }
+        _ => return None,
+    }
+    if let Some(style) = action.get("style") {
+        card_action["style"] = style.clone();
+    }
+    Some(card_action)
+}
+
+fn slug(title: &str) -> String {
+    let slug: String = title
+        .chars()
+        .map(|ch| {
+            if ch.is_alphanumeric() {
+                ch.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn field_input(kind: &str, field: &Value) -> Option<Map<String, Value>> {
+    let mut input = Map::new();
+    let element = match kind {
+        "text" | "string" => "Input.Text",
+        "textarea" => {
+            input.insert("isMultiline".into(), json!(true));
+            "Input.Text"
+        }
+        "email" | "url" | "tel" => {
+            input.insert("style".into(), json!(kind));
+            "Input.Text"
+        }
+        "number" | "integer" => "Input.Number",
+        "boolean" | "toggle" | "checkbox" => {
+            input.insert("title".into(), json!(""));
+            "Input.Toggle"
+        }
+        "date" => "Input.Date",
+        "time" => "Input.Time",
+        "select" | "multiselect" => {
+            let choices: Vec<Value> = field
+                .get("options")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(|option| match option {
+                    Value::Object(_) => {
+                        let value = option.get("value").map(scalar_text).unwrap_or_default();
+                        let title = option
+                            .get("title")
+                            .or_else(|| option.get("label"))
+                            .map(scalar_text)
+                            .unwrap_or_else(|| value.clone());
+                        json!({ "title": title, "value": value })
+                    }
+                    other => {
+                        let value = scalar_text(other);
+                        json!({ "title": value, "value": value })
+                    }
+                })
+                .collect();
+            input.insert("choices".into(), json!(choices));
+            if kind == "multiselect" {
+                input.insert("isMultiSelect".into(), json!(true));
+            }
+            "Input.ChoiceSet"
+        }
+        _ => return None,
+    };
+    input.insert("type".into(), json!(element));
+    Some(input)
+}
+
+/// Expands a form into a card: a heading and intro, then each input with its label,
+/// `isRequired`, and an error message, and the form's actions or a Submit (or, with a verb,
+/// Execute) action with id [`SUBMIT_ID`]. Cards with dotted input ids set `$nestInputs`.
+pub(crate) fn build_card(form: &Form) -> Value {
+    let mut header = Vec::new();
+    if let Some(title) = &form.title {
+        let mut title =
+            json!({ "type": "TextBlock", "text": title, "style": "heading", "wrap": true });
+        if form.house_style {
+            title["size"] = json!("@theme.fontSizes.title");
+            title["weight"] = json!("@theme.fontWeights.title");
+        }
+        header.push(title);
+    }
+    if let Some(description) = &form.description {
+        header.push(
+            json!({ "type": "TextBlock", "text": description, "isSubtle": true, "wrap": true }),
+        );
+    }
+    let mut body = if form.house_style && !header.is_empty() {
+        vec![json!({
+            "type": "Container",
+            "style": "@theme.containerStyles.highlight",
+            "bleed": true,
+            "items": header
+        })]
+    } else {
+        header
+    };
+    for item in &form.items {
+        match item {
+            FormItem::Heading(text) => {
+                let mut heading = json!({
+                    "type": "TextBlock",
+                    "text": text,
+                    "weight": "bolder",
+                    "separator": true,
+                    "wrap": true
+                });
+                if form.house_style {
+                    heading["color"] = json!("@theme.colors.accent");
+                    heading["spacing"] = json!("@theme.spacing.loose");
+                }
+                body.push(heading);
+            }
+            FormItem::Input {
+                label,
+                required,
+                input,
+            } => {
+                let mut input = input.clone();
+                input.insert("label".into(), json!(label));
+                if *required {
+                    input.insert("isRequired".into(), json!(true));
+                    input
+                        .entry("errorMessage")
+                        .or_insert_with(|| json!(format!("{label} is required")));
+                }
+                if form.house_style {
+                    input.insert("spacing".into(), json!("@theme.spacing.default"));
+                }
+                body.push(Value::Object(input));
+            }
+        }
+    }
+    let mut actions = form.actions.clone();
+    if actions.is_empty() {
+        let mut action = json!({
+            "type": "Action.Submit",
+            "id": SUBMIT_ID,
+            "title": form.submit_title.as_deref().unwrap_or("Submit")
+        });
+        if let Some(verb) = &form.verb {
+            action["type"] = json!("Action.Execute");
+            action["verb"] = json!(verb);
+        }
+        actions.push(action);
+    }
+    if form.house_style
+        && let Some(primary) = actions.first_mut()
+        && primary.get("type").and_then(Value::as_str) != Some("Action.OpenUrl")
+        && primary.get("style").is_none()
+    {
+        primary["style"] = json!("positive");
+    }
+    let mut card = json!({
+        "type": "AdaptiveCard",
+        "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+        "version": GENERATED_VERSION,
+        "body": body,
+        "actions": actions
+    });
+    let dotted = form.items.iter().any(|item| {
+        matches!(item, FormItem::Input { input, .. }
+            if input.get("id").and_then(Value::as_str).is_some_and(|id| id.contains('.')))
+    });
+    if dotted {
+        card[NEST_INPUTS_KEY] = json!(true);
+    }
+    card
+}
+
+/// `camelCase`, `snake_case`, or `kebab-case` name as a sentence-case label.
+pub(crate) fn humanize(name: &str) -> String {
+    let mut words = String::new();
+    let mut previous: Option<char> = None;
+    for ch in name.chars() {
+        if ch == '_' || ch == '-' || ch == '.' {
+            words.push(' ');
+        } else {
+            if ch.is_uppercase() && previous.is_some_and(|p| p.is_lowercase()) {
+                words.push(' ');
+            }
+            words.extend(ch.to_lowercase());
+        }
+        previous = Some(ch);
+    }
+    let words = words.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => words,
+    }
+}
+
+/// Input `value`s are strings: booleans and numbers are written as text, lists
+/// comma-joined.
+pub(crate) fn input_value(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => json!(items.iter().map(scalar_text).collect::<Vec<_>>().join(",")),
+        other => json!(scalar_text(other)),
+    }
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+pub(crate) fn unsupported(path: &str, what: &str) -> ValidationIssue {
+    ValidationIssue {
+        code: "schema-import-unsupported".into(),
+        message: format!("{what} cannot be collected by a card input; skipped"),
+        path: format!("/{}", path.replace('.', "/")),
+        severity: Some(IssueSeverity::Warning),
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+
+use crate::cache;
+use crate::model::{AdaptiveCardResponse, BuildInfo, FailureCode, HealthReport};
+use crate::state_store;
+
+/// Failure codes kept for `recent_errors`.
+pub const RECENT_ERROR_LIMIT: usize = 16;
+
+static STARTED: Lazy<Instant> = Lazy::new(Instant::now);
+static INVOCATIONS: AtomicU64 = AtomicU64::new(0);
+static FAILURES: AtomicU64 = AtomicU64::new(0);
+static RECENT_ERRORS: Lazy<Mutex<VecDeque<FailureCode>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(RECENT_ERROR_LIMIT)));
+
+/// Counts a handled message and remembers its failure code, if it failed.
+pub fn record(response: &AdaptiveCardResponse) {
+    Lazy::force(&STARTED);
+    INVOCATIONS.fetch_add(1, Ordering::Relaxed);
+    if let AdaptiveCardResponse::Failure { error } = response {
+        FAILURES.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut recent) = RECENT_ERRORS.lock() {
+            if recent.len() == RECENT_ERROR_LIMIT {
+                recent.pop_front();
+            }
+            recent.push_back(error.code);
+        }
+    }
+}
+
+/// `health`: counters since startup, state store connectivity, cache stats, and build info.
+pub fn report(component: &str, version: &str) -> HealthReport {
+    let (state_store, state_store_error) = match state_store::probe() {
+        Ok(true) => ("ok", None),
+        Ok(false) => ("disabled", None),
+        Err(err) => ("unavailable", Some(err.to_string())),
+    };
+    HealthReport {
+        uptime_ms: STARTED.elapsed().as_millis() as u64,
+        invocations: INVOCATIONS.load(Ordering::Relaxed),
+        failures: FAILURES.load(Ordering::Relaxed),
+        recent_errors: RECENT_ERRORS
+            .lock()
+            .map(|recent| recent.iter().copied().collect())
+            .unwrap_or_default(),
+        state_store: state_store.to_string(),
+        state_store_error,
+        caches: cache::stats(),
+        build: BuildInfo {
+            component: component.to_string(),
+            version: version.to_string(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            features: [
+                ("state-store", cfg!(feature = "state-store")),
+                ("asset-store", cfg!(feature = "asset-store")),
+                ("preview", cfg!(feature = "preview")),
+                ("preview-png", cfg!(feature = "preview-png")),
+                ("testkit", cfg!(feature = "testkit")),
+            ]
+            .into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        },
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::OnceCell;
+use serde_json::Value;
+
+use crate::model::{AdaptiveCardInvocation, IssueSeverity, ValidationIssue};
+
+/// Card root key naming the data transformers applied before binding: a name or a list of
+/// names, applied in order.
+pub const TRANSFORM_KEY: &str = "$transform";
+
+/// Rewrites a rendered card before it is returned: injecting tracking ids, enforcing a
+/// footer, stripping elements.
+pub trait PostRenderHook: Send + Sync {
+    fn transform(&self, card: &mut Value, inv: &AdaptiveCardInvocation) -> Result<(), String>;
+}
+
+type PostRenderFn = dyn Fn(&mut Value, &AdaptiveCardInvocation) -> Result<(), String> + Send + Sync;
+
+pub struct CallbackPostRenderHook {
+    callback: Box<PostRenderFn>,
+}
+
+impl CallbackPostRenderHook {
+    pub fn new(callback: Box<PostRenderFn>) -> Self {
+        Self { callback }
+    }
+}
+
+impl PostRenderHook for CallbackPostRenderHook {
+    fn transform(&self, card: &mut Value, inv: &AdaptiveCardInvocation) -> Result<(), String> {
+        (self.callback)(card, inv)
+    }
+}
+
+struct Registered<H: ?Sized> {
+    name: String,
+    order: i32,
+    hook: Arc<H>,
+}
+
+type PostRenderHooks = Vec<Registered<dyn PostRenderHook>>;
+
+static POST_RENDER_HOOKS: OnceCell<RwLock<PostRenderHooks>> = OnceCell::new();
+
+fn post_render_cell() -> &'static RwLock<PostRenderHooks> {
+    POST_RENDER_HOOKS.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Registers a post-render hook under `name`, replacing any hook of that name. Hooks run in
+/// ascending `order`, ties in name order.
+pub fn register_post_render_hook(
+    name: &str,
+    order: i32,
+    hook: Box<dyn PostRenderHook>,
+) -> Result<(), &'static str> {
+    let mut hooks = post_render_cell()
+        .write()
+        .map_err(|_| "post-render hooks poisoned")?;
+    hooks.retain(|registered| registered.name != name);
+    hooks.push(Registered {
+        name: name.to_string(),
+        order,
+        hook: Arc::from(hook),
+    });
+    hooks.sort_by(|a, b| a.order.cmp(&b.order).then_with(|| a.name.cmp(&b.name)));
+    Ok(())
+}
+
+pub fn register_post_render_callback(
+    name: &str,
+    order: i32,
+    callback: Box<PostRenderFn>,
+) -> Result<(), &'static str> {
+    register_post_render_hook(name, order, Box::new(CallbackPostRenderHook::new(callback)))
+}
+
+/// Removes the post-render hook registered under `name`; returns whether there was one.
+pub fn remove_post_render_hook(name: &str) -> Result<bool, &'static str> {
+    let mut hooks = post_render_cell()
+        .write()
+        .map_err(|_| "post-render hooks poisoned")?;
+    let before = hooks.len();
+    hooks.retain(|registered| registered.name != name);
+    Ok(hooks.len() != before)
+}
+
+/// Whether any post-render hook is registered.
+pub fn has_post_render_hooks() -> bool {
+    post_render_cell()
+        .read()
+        .map(|hooks| !hooks.is_empty())
+        .unwrap_or(true)
+}
+
+/// Runs the registered post-render hooks in order. A failing hook leaves the card as the
+/// previous hook left it and reports a `post-render-hook-failed` warning.
+pub fn apply_post_render_hooks(
+    card: &mut Value,
+    inv: &AdaptiveCardInvocation,
+) -> Vec<ValidationIssue> {
+    let hooks: Vec<(String, Arc<dyn PostRenderHook>)> = match post_render_cell().read() {
+        Ok(hooks) => hooks
+            .iter()
+            .map(|registered| (registered.name.clone(), registered.hook.clone()))
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+    let mut issues = Vec::new();
+    for (name, hook) in hooks {
+        let mut transformed = card.clone();
+        match hook.transform(&mut transformed, inv) {
+            Ok(()) => *card = transformed,
+            Err(err) => issues.push(ValidationIssue {
+                code: "post-render-hook-failed".into(),
+                message: format!("post-render hook '{name}' failed: {err}"),
+                path: "/".into(),
+                severity: Some(IssueSeverity::Warning),
+            }),
+        }
+    }
+    issues
+}
+
+/// Data roots a template's expressions read, as handed to data transformers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BindingData {
+    pub payload: Value,
+    pub state: Value,
+    pub session: Value,
+}
+
+/// Reshapes the data a template binds against: normalizing dates, flattening nested API
+/// responses. Templates opt in by name through `$transform`.
+pub trait DataTransformer: Send + Sync {
+    fn transform(&self, data: &mut BindingData) -> Result<(), String>;
+}
+
+type DataTransformerFn = dyn Fn(&mut BindingData) -> Result<(), String> + Send + Sync;
+
+pub struct CallbackDataTransformer {
+    callback: Box<DataTransformerFn>,
+}
+
+impl CallbackDataTransformer {
+    pub fn new(callback: Box<DataTransformerFn>) -> Self {
+        Self { callback }
+    }
+}
+
+impl DataTransformer for CallbackDataTransformer {
+    fn transform(&self, data: &mut BindingData) -> Result<(), String> {
+        (self.callback)(data)
+    }
+}
+
+static DATA_TRANSFORMERS: OnceCell<RwLock<BTreeMap<String, Arc<dyn DataTransformer>>>> =
+    OnceCell::new();
+
+fn transformers_cell() -> &'static RwLock<BTreeMap<String, Arc<dyn DataTransformer>>> {
+    DATA_TRANSFORMERS.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Registers the data transformer templates name in `$transform`, replacing any transformer
+/// of that name.
+pub fn register_data_transformer(
+    name: &str,
+    transformer: Box<dyn DataTransformer>,
+) -> Result<(), &'static str> {
+    transformers_cell()
+        .write()
+        .map_err(|_| "data transformers poisoned")?
+        .insert(name.to_string(), Arc::from(transformer));
+    Ok(())
+}
+
+pub fn register_data_transformer_callback(
+    name: &str,
+    callback: Box<DataTransformerFn>,
+) -> Result<(), &'static str> {
+    register_data_transformer(name, Box::new(CallbackDataTransformer::new(callback)))
+}
+
+/// Removes `$transform` from the card root, returning the transformer names it lists.
+pub fn take_transformers(card: &mut Value) -> Vec<String> {
+    match card
+        .as_object_mut()
+        .and_then(|root| root.remove(TRANSFORM_KEY))
+    {
+        Some(Value::String(name)) => vec![name],
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Copy of the invocation whose payload, state, and session went through the named
+/// transformers in order. Unknown names are reported as `unknown-data-transformer`
+/// warnings; a failing transformer leaves the data as it found it and reports a
+/// `data-transformer-failed` warning.
+pub fn apply_data_transformers(
+    inv: &AdaptiveCardInvocation,
+    names: &[String],
+) -> (AdaptiveCardInvocation, Vec<ValidationIssue>) {
+    let registered = transformers_cell()
+        .read()
+        .map(|registered| registered.clone())
+        .unwrap_or_default();
+    let mut data = BindingData {
+        payload: inv.payload.clone(),
+        state: inv.state.clone(),
+        session: inv.session.clone(),
+    };
+    let mut issues = Vec::new();
+    for name in names {
+        let Some(transformer) = registered.get(name) else {
+            issues.push(ValidationIssue {
+                code: "unknown-data-transformer".into(),
+                message: format!("no data transformer named '{name}' is registered"),
+                path: format!("/{TRANSFORM_KEY}"),
+                severity: Some(IssueSeverity::Warning),
+            });
+            continue;
+        };
+        let mut transformed = data.clone();
+        match transformer.transform(&mut transformed) {
+            Ok(()) => data = transformed,
+            Err(err) => issues.push(ValidationIssue {
+                code: "data-transformer-failed".into(),
+                message: format!("data transformer '{name}' failed: {err}"),
+                path: format!("/{TRANSFORM_KEY}"),
+                severity: Some(IssueSeverity::Warning),
+            }),
+        }
+    }
+    let mut transformed = inv.clone();
+    transformed.payload = data.payload;
+    transformed.state = data.state;
+    transformed.session = data.session;
+    (transformed, issues)
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::model::ValidationIssue;
+
+/// Locale used at the end of every fallback chain.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// String catalogs keyed by locale, then by string key.
+pub type StringCatalogs = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Builds the lookup chain for a locale: `fr-CA` → `fr-ca`, `fr`, `en`.
+pub fn fallback_chain(locale: Option<&str>) -> Vec<String> {
+    let mut chain = Vec::new();
+    if let Some(locale) = locale {
+        let mut tag = locale.trim().replace('_', "-").to_ascii_lowercase();
+        while !tag.is_empty() {
+            chain.push(tag.clone());
+            match tag.rfind('-') {
+                Some(idx) => tag.truncate(idx),
+                None => break,
+            }
+        }
+    }
+    if !chain.iter().any(|tag| tag == DEFAULT_LOCALE) {
+        chain.push(DEFAULT_LOCALE.to_string());
+    }
+    chain
+}
+
+/// Flattens the catalogs along the fallback chain; earlier locales win.
+pub fn resolve_strings(
+    locale: Option<&str>,
+    catalogs: &StringCatalogs,
+) -> BTreeMap<String, String> {
+    let mut strings = BTreeMap::new();
+    for tag in fallback_chain(locale) {
+        let catalog = catalogs
+            .iter()
+            .find(|(key, _)| key.replace('_', "-").eq_ignore_ascii_case(&tag));
+        if let Some((_, entries)) = catalog {
+            for (key, value) in entries {
+                strings.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+    strings
+}
+
+/// Replaces `@loc(key)` markers in every string of the card. Unknown keys are left as the
+/// bare key and reported so missing translations surface in validation output.
+pub fn apply_loc_markers(
+    value: &mut Value,
+    strings: &BTreeMap<String, String>,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    visit(value, "", strings, &mut issues);
+    issues
+}
+
+fn visit(
+    value: &mut Value,
+    path: &str,
+    strings: &BTreeMap<String, String>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    match value {
+        Value::String(text) if text.contains("@loc(") => {
+            *text = replace_markers(text, path, strings, issues);
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                visit(item, &format!("{path}/{idx}"), strings, issues);
+            }
+        }
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                visit(child, &format!("{path}/{key}"), strings, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn replace_markers(
+    text: &str,
+    path: &str,
+    strings: &BTreeMap<String, String>,
+    issues: &mut Vec<ValidationIssue>,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("@loc(") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 5..];
+        let Some(end) = after.find(')') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let key = after[..end].trim().trim_matches(['"', '\'']);
+        match strings.get(key) {
+            Some(localized) => out.push_str(localized),
+            None => {
+                issues.push(ValidationIssue {
+                    code: "missing-translation".into(),
+                    message: format!("No localized string for key '{key}'"),
+                    path: if path.is_empty() {
+                        "/".into()
+                    } else {
+                        path.into()
+                    },
+                    severity: None,
+                });
+                out.push_str(key);
+            }
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value};
+
+use crate::error::ComponentError;
+use crate::model::{IssueSeverity, ValidationIssue};
+
+/// Element key naming the partial to splice in place of the element.
+pub const INCLUDE_KEY: &str = "$include";
+/// Element key holding the include's local scope, read in the partial as `${scope.<key>}`.
+pub const WITH_KEY: &str = "$with";
+/// Includes nested deeper than this fail the render.
+pub const MAX_INCLUDE_DEPTH: usize = 8;
+/// Card root key naming the base layout the card fills.
+pub const LAYOUT_KEY: &str = "$layout";
+/// Card root key mapping slot names to the content that fills them.
+pub const SLOTS_KEY: &str = "$slots";
+/// Placeholder key in a layout marking where a named slot's content goes.
+pub const SLOT_KEY: &str = "$slot";
+
+/// Loads a partial template by name.
+pub type PartialLoader<'a> = dyn FnMut(&str) -> Result<Value, ComponentError> + 'a;
+
+/// Replaces every `{"$include": "<name>", "$with": {...}}` object with the named partial. The
+/// partial's `${scope.<key>}` placeholders are substituted from `$with` before the card is
+/// bound, so scope values may themselves be bindings (`"${payload.name}"`). A partial that is
+/// an array of elements is spliced into the surrounding array. Partials are expanded
+/// recursively; a cycle or nesting beyond [`MAX_INCLUDE_DEPTH`] fails the render.
+pub fn expand_includes(
+    card: &mut Value,
+    load: &mut PartialLoader<'_>,
+) -> Result<(), ComponentError> {
+    expand(card, load, &mut Vec::new())
+}
+
+/// Whether the card names a `$layout` or contains an `$include` outside `data`.
+pub fn uses_includes(card: &Value) -> bool {
+    fn find(value: &Value) -> bool {
+        match value {
+            Value::Object(map) => {
+                map.contains_key(INCLUDE_KEY)
+                    || map.iter().any(|(key, child)| key != "data" && find(child))
+            }
+            Value::Array(items) => items.iter().any(find),
+            _ => false,
+        }
+    }
+    card.get(LAYOUT_KEY).is_some() || find(card)
+}
+
+/// Merges a card that names a `$layout` into that layout: every `{"$slot": "<name>"}`
+/// placeholder in the layout is replaced by the card's `$slots.<name>` (an array is spliced
+/// into the surrounding array), the card's `body` and `actions` fill the `body` and
+/// `actions` slots, and its other root properties override the layout's. Unfilled slots
+/// keep the placeholder's `default` or are removed. A layout may name its own `$layout`; the
+/// chain is merged until the outermost layout, failing on cycles or beyond
+/// [`MAX_INCLUDE_DEPTH`] levels.
+pub fn apply_layout(
+    card: &mut Value,
+    load: &mut PartialLoader<'_>,
+) -> Result<Vec<ValidationIssue>, ComponentError> {
+    let mut issues = Vec::new();
+    let mut chain: Vec<String> = Vec::new();
+    while let Some(layout_ref) = card.get(LAYOUT_KEY) {
+        let name = layout_ref
+            .as_str()
+            .filter(|name| !name.trim().is_empty())
+            .ok_or_else(|| ComponentError::InvalidInput("$layout must name a template".into()))?
+            .to_string();
+        if chain.contains(&name) {
+            return Err(ComponentError::InvalidInput(format!(
+                "layout cycle: {} -> {name}",
+                chain.join(" -> ")
+            )));
+        }
+        if chain.len() >= MAX_INCLUDE_DEPTH {
+            return Err(ComponentError::InvalidInput(format!(
+                "layouts nested deeper than {MAX_INCLUDE_DEPTH} levels at {name}"
+            )));
+        }
+        let mut layout = load(&name)?;
+        let Value::Object(mut child) = std::mem::take(card) else {
+            unreachable!("only objects carry a $layout");
+        };
+        child.remove(LAYOUT_KEY);
+        let mut slots = match child.remove(SLOTS_KEY) {
+            Some(Value::Object(slots)) => slots,
+            _ => Map::new(),
+        };
+        for key in ["body", "actions"] {
+            if let Some(content) = child.remove(key) {
+                slots.entry(key).or_insert(content);
+            }
+        }
+        let mut filled = BTreeSet::new();
+        fill_slots(&mut layout, &slots, &mut filled);
+        for unknown in slots.keys().filter(|slot| !filled.contains(*slot)) {
+            issues.push(ValidationIssue {
+                code: "unknown-layout-slot".into(),
+                message: format!("layout {name} has no slot named {unknown}"),
+                path: format!("/{SLOTS_KEY}/{unknown}"),
+                severity: Some(IssueSeverity::Warning),
+            });
+        }
+        if let Value::Object(root) = &mut layout {
+            root.extend(child);
+        }
+        *card = layout;
+        chain.push(name);
+    }
+    Ok(issues)
+}
+
+fn fill_slots(value: &mut Value, slots: &Map<String, Value>, filled: &mut BTreeSet<String>) {
+    match value {
+        Value::Array(items) => {
+            let mut idx = 0;
+            while idx < items.len() {
+                match slot_content(&items[idx], slots, filled) {
+                    Some(Value::Array(fill)) => {
+                        let count = fill.len();
+                        items.splice(idx..=idx, fill);
+                        idx += count;
+                    }
+                    Some(Value::Null) => {
+                        items.remove(idx);
+                    }
+                    Some(fill) => {
+                        items[idx] = fill;
+                        idx += 1;
+                    }
+                    None => {
+                        fill_slots(&mut items[idx], slots, filled);
+                        idx += 1;
+                    }
+                }
+            }
+        }
+        Value::Object(map) => {
+            let keys: Vec<String> = map.keys().cloned().collect();
+            for key in keys {
+                match map
+                    .get(&key)
+                    .and_then(|value| slot_content(value, slots, filled))
+                {
+                    Some(Value::Null) => {
+                        map.remove(&key);
+                    }
+                    Some(fill) => {
+                        map.insert(key, fill);
+                    }
+                    None => {
+                        if let Some(child) = map.get_mut(&key) {
+                            fill_slots(child, slots, filled);
+                        }
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The content for a `$slot` placeholder (`Null` when unfilled without a default), or `None`
+/// when `placeholder` is not one.
+fn slot_content(
+    placeholder: &Value,
+    slots: &Map<String, Value>,
+    filled: &mut BTreeSet<String>,
+) -> Option<Value> {
+    let name = placeholder.get(SLOT_KEY)?.as_str()?;
+    match slots.get(name) {
+        Some(fill) => {
+            filled.insert(name.to_string());
+            Some(fill.clone())
+        }
+        None => Some(placeholder.get("default").cloned().unwrap_or(Value::Null)),
+    }
+}
+
+fn expand(
+    value: &mut Value,
+    load: &mut PartialLoader<'_>,
+    stack: &mut Vec<String>,
+) -> Result<(), ComponentError> {
+    match value {
+        Value::Object(map) => {
+            if let Some(partial) = include(map, load, stack)? {
+                *value = partial;
+                return Ok(());
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    expand(child, load, stack)?;
+                }
+            }
+        }
+        Value::Array(items) => {
+            let mut idx = 0;
+            while idx < items.len() {
+                let spliced = match &mut items[idx] {
+                    Value::Object(map) => include(map, load, stack)?,
+                    other => {
+                        expand(other, load, stack)?;
+                        None
+                    }
+                };
+                match spliced {
+                    Some(Value::Array(elements)) => {
+                        let count = elements.len();
+                        items.splice(idx..=idx, elements);
+                        idx += count;
+                    }
+                    Some(partial) => {
+                        items[idx] = partial;
+                        idx += 1;
+                    }
+                    None => {
+                        expand(&mut items[idx], load, stack)?;
+                        idx += 1;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The fully expanded partial for an include object, or `None` for any other object.
+fn include(
+    map: &Map<String, Value>,
+    load: &mut PartialLoader<'_>,
+    stack: &mut Vec<String>,
+) -> Result<Option<Value>, ComponentError> {
+    let Some(name) = map.get(INCLUDE_KEY) else {
+        return Ok(None);
+    };
+    let name = name
+        .as_str()
+        .filter(|name| !name.trim().is_empty())
+        .ok_or_else(|| ComponentError::InvalidInput("$include must name a partial".into()))?;
+    if stack.iter().any(|open| open == name) {
+        return Err(ComponentError::InvalidInput(format!(
+            "include cycle: {} -> {name}",
+            stack.join(" -> ")
+        )));
+    }
+    if stack.len() >= MAX_INCLUDE_DEPTH {
+        return Err(ComponentError::InvalidInput(format!(
+            "includes nested deeper than {MAX_INCLUDE_DEPTH} levels at {name}"
+        )));
+    }
+    let mut partial = load(name)?;
+    let scope = match map.get(WITH_KEY) {
+        Some(Value::Object(scope)) => scope.clone(),
+        _ => Map::new(),
+    };
+    substitute(&mut partial, &scope);
+    stack.push(name.to_string());
+    expand(&mut partial, load, stack)?;
+    stack.pop();
+    Ok(Some(partial))
+}
+
+/// Replaces `${scope.<key>}` placeholders: a string that is exactly one placeholder takes the
+/// scope value as is, otherwise the value is interpolated as text. Unknown keys become empty.
+fn substitute(value: &mut Value, scope: &Map<String, Value>) {
+    match value {
+        Value::String(text) if text.contains("${scope.") => {
+            if let Some(key) = text
+                .strip_prefix("${scope.")
+                .and_then(|rest| rest.strip_suffix('}'))
+                .filter(|key| !key.contains('}'))
+            {
+                *value = scope.get(key.trim()).cloned().unwrap_or(Value::Null);
+                return;
+            }
+            let mut out = String::with_capacity(text.len());
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("${scope.") {
+                let Some(len) = rest[start..].find('}') else {
+                    break;
+                };
+                out.push_str(&rest[..start]);
+                let key = rest[start + "${scope.".len()..start + len].trim();
+                match scope.get(key) {
+                    Some(Value::String(s)) => out.push_str(s),
+                    Some(Value::Null) | None => {}
+                    Some(other) => out.push_str(&other.to_string()),
+                }
+                rest = &rest[start + len + 1..];
+            }
+            out.push_str(rest);
+            *text = out;
+        }
+        Value::Object(map) => map.values_mut().for_each(|child| substitute(child, scope)),
+        Value::Array(items) => items.iter_mut().for_each(|item| substitute(item, scope)),
+        _ => {}
+    }
+}
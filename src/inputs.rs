@@ -0,0 +1,592 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+use crate::format;
+use crate::model::{DataClassification, IssueSeverity, ValidationIssue};
+
+/// Extension property classifying an input's data: `public`, `internal`, `pii`, or `secret`.
+pub const DATA_CLASSIFICATION_KEY: &str = "dataClassification";
+/// Card root flag submitting dotted input ids as nested objects.
+pub const NEST_INPUTS_KEY: &str = "$nestInputs";
+
+/// Input element declared somewhere in a rendered card.
+#[derive(Debug, Clone)]
+pub struct InputDeclaration {
+    pub id: String,
+    pub kind: String,
+    pub element: Map<String, Value>,
+}
+
+/// Collects every `Input.*` element that carries an id, in document order.
+pub fn collect_inputs(card: &Value) -> Vec<InputDeclaration> {
+    fn walk(value: &Value, out: &mut Vec<InputDeclaration>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(kind) = map.get("type").and_then(|v| v.as_str())
+                    && kind.starts_with("Input.")
+                    && let Some(id) = map.get("id").and_then(|v| v.as_str())
+                {
+                    out.push(InputDeclaration {
+                        id: id.to_string(),
+                        kind: kind.to_string(),
+                        element: map.clone(),
+                    });
+                }
+                for child in map.values() {
+                    walk(child, out);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    walk(item, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(card, &mut out);
+    out
+}
+
+/// Coerces submitted values to the types declared by the card's inputs: Number, Rating, and
+/// Slider to numbers, Toggle to booleans, Date/Time to ISO strings, multi-select ChoiceSets to
+/// arrays, and ColorPicker to `#rrggbb`. Empty values become `null`; values that cannot be
+/// coerced are left as-is and reported as `input-coercion-failed` issues.
+pub fn coerce_inputs(inputs: &mut Value, card: &Value) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let Value::Object(map) = inputs else {
+        return issues;
+    };
+    for decl in collect_inputs(card) {
+        let Some(value) = map.get_mut(&decl.id) else {
+            continue;
+        };
+        let expected = match decl.kind.as_str() {
+            "Input.Number" | "Input.Rating" | "Input.Slider" => "a number",
+            "Input.Toggle" => "a toggle value",
+            "Input.Date" => "an ISO date",
+            "Input.Time" => "a time",
+            "Input.ColorPicker" => "a hex color",
+            "Input.ChoiceSet" if is_multi_select(&decl.element) => "a list of choices",
+            _ => continue,
+        };
+        if value.is_null() || value.as_str().is_some_and(|s| s.trim().is_empty()) {
+            if decl.kind == "Input.ChoiceSet" {
+                *value = Value::Array(Vec::new());
+            } else if decl.kind != "Input.Toggle" {
+                *value = Value::Null;
+            }
+            continue;
+        }
+        let coerced = match decl.kind.as_str() {
+            "Input.Number" => numeric(value).map(number_value),
+            "Input.Rating" => coerce_rating(value, &decl.element),
+            "Input.Slider" => coerce_slider(value, &decl.element),
+            "Input.Toggle" => coerce_toggle(value, &decl.element),
+            "Input.Date" => format::parse_date(value)
+                .map(|(y, m, d)| Value::String(format!("{y:04}-{m:02}-{d:02}"))),
+            "Input.Time" => value.as_str().and_then(parse_time).map(Value::String),
+            "Input.ColorPicker" => value
+                .as_str()
+                .and_then(normalize_hex_color)
+                .map(Value::String),
+            _ => coerce_multi_select(value),
+        };
+        match coerced {
+            Some(coerced) => *value = coerced,
+            None => issues.push(ValidationIssue {
+                code: "input-coercion-failed".into(),
+                message: format!("input {} expects {expected}, got {value}", decl.id),
+                path: format!("/inputs/{}", decl.id),
+                severity: Some(IssueSeverity::Error),
+            }),
+        }
+    }
+    issues
+}
+
+/// Strips `dataClassification` from every input and returns the classifications keyed by
+/// input id. Unknown classifications are reported as warnings and ignored.
+pub fn take_classifications(
+    card: &mut Value,
+) -> (BTreeMap<String, DataClassification>, Vec<ValidationIssue>) {
+    fn walk(
+        value: &mut Value,
+        path: &str,
+        classes: &mut BTreeMap<String, DataClassification>,
+        issues: &mut Vec<ValidationIssue>,
+    ) {
+        match value {
+            Value::Object(map) => {
+                if let Some(declared) = map.remove(DATA_CLASSIFICATION_KEY)
+                    && let Some(id) = map.get("id").and_then(|v| v.as_str())
+                {
+                    match serde_json::from_value(declared.clone()) {
+                        Ok(class) => {
+                            classes.insert(id.to_string(), class);
+                        }
+                        Err(_) => issues.push(ValidationIssue {
+                            code: "invalid-data-classification".into(),
+                            message: format!(
+                                "input {id} has unknown dataClassification {declared}"
+                            ),
+                            path: format!("{path}/{DATA_CLASSIFICATION_KEY}"),
+                            severity: Some(IssueSeverity::Warning),
+                        }),
+                    }
+                }
+                for (key, child) in map.iter_mut() {
+                    if key != "data" {
+                        walk(child, &format!("{path}/{key}"), classes, issues);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter_mut().enumerate() {
+                    walk(item, &format!("{path}/{idx}"), classes, issues);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut classes = BTreeMap::new();
+    let mut issues = Vec::new();
+    walk(card, "", &mut classes, &mut issues);
+    (classes, issues)
+}
+
+/// The classifications of the submitted `inputs`, keyed by input id. Namespaced inputs
+/// (`rows[2].phone`) count as submitted when their regrouped namespace was.
+pub fn submitted_classifications(
+    inputs: &Value,
+    classes: &BTreeMap<String, DataClassification>,
+) -> Map<String, Value> {
+    classes
+        .iter()
+        .filter(|(id, _)| {
+            let key = split_namespaced(id).map_or(id.as_str(), |(namespace, _, _)| namespace);
+            inputs.get(key).is_some()
+        })
+        .filter_map(|(id, class)| Some((id.clone(), serde_json::to_value(class).ok()?)))
+        .collect()
+}
+
+/// Groups submitted values of namespaced inputs (`rows[2].qty`, rendered by paginated
+/// containers with an `inputNamespace`) back into arrays: `{"rows": [.., .., {"qty": ..}]}`.
+/// Entries are placed at their index, so rows not on the submitted page are `null`. Only ids
+/// of inputs declared in `card` are regrouped.
+pub fn regroup_namespaced_inputs(inputs: &mut Value, card: &Value) {
+    let Value::Object(map) = inputs else {
+        return;
+    };
+    for decl in collect_inputs(card) {
+        let Some((namespace, index, field)) = split_namespaced(&decl.id) else {
+            continue;
+        };
+        let Some(value) = map.remove(&decl.id) else {
+            continue;
+        };
+        let rows = map
+            .entry(namespace)
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if !rows.is_array() {
+            *rows = Value::Array(Vec::new());
+        }
+        let Value::Array(rows) = rows else {
+            continue;
+        };
+        if rows.len() <= index {
+            rows.resize(index + 1, Value::Null);
+        }
+        if !rows[index].is_object() {
+            rows[index] = Value::Object(Map::new());
+        }
+        if let Value::Object(row) = &mut rows[index] {
+            row.insert(field.to_string(), value);
+        }
+    }
+}
+
+/// Removes the `$nestInputs` flag from the card root, returning whether it was set.
+pub fn take_nest_inputs(card: &mut Value) -> bool {
+    card.as_object_mut()
+        .and_then(|root| root.remove(NEST_INPUTS_KEY))
+        .is_some_and(|flag| flag == Value::Bool(true))
+}
+
+/// Nests submitted values of dotted input ids into objects: `address.city` becomes
+/// `{"address": {"city": ..}}`. Only ids of inputs declared in `card` are nested; a segment
+/// already holding a non-object value is left as submitted.
+pub fn nest_dotted_inputs(inputs: &mut Value, card: &Value) {
+    let Value::Object(map) = inputs else {
+        return;
+    };
+    for decl in collect_inputs(card) {
+        if !decl.id.contains('.') || split_namespaced(&decl.id).is_some() {
+            continue;
+        }
+        let mut segments: Vec<&str> = decl.id.split('.').collect();
+        let Some(field) = segments.pop() else {
+            continue;
+        };
+        if field.is_empty() || segments.iter().any(|s| s.is_empty()) {
+            continue;
+        }
+        let fits = segments
+            .iter()
+            .try_fold(&*map, |node, key| match node.get(*key) {
+                None => Some(node),
+                Some(Value::Object(child)) => Some(child),
+                Some(_) => None,
+            })
+            .is_some();
+        if !fits {
+            continue;
+        }
+        let Some(value) = map.remove(&decl.id) else {
+            continue;
+        };
+        insert_nested(map, &segments, field, value);
+    }
+}
+
+fn insert_nested(map: &mut Map<String, Value>, segments: &[&str], field: &str, value: Value) {
+    match segments.split_first() {
+        None => {
+            map.insert(field.to_string(), value);
+        }
+        Some((key, rest)) => {
+            if let Value::Object(child) =
+                map.entry(*key).or_insert_with(|| Value::Object(Map::new()))
+            {
+                insert_nested(child, rest, field, value);
+            }
+        }
+    }
+}
+
+/// Splits `namespace[index].field`.
+fn split_namespaced(id: &str) -> Option<(&str, usize, &str)> {
+    let (namespace, rest) = id.split_once('[')?;
+    let (index, field) = rest.split_once("].")?;
+    if namespace.is_empty() || field.is_empty() {
+        return None;
+    }
+    Some((namespace, index.parse().ok()?, field))
+}
+
+/// Returns a copy of `inputs` with ChoiceSet values replaced by the matching choice titles.
+/// Values without a declared choice are kept as submitted.
+pub fn display_inputs(inputs: &Value, card: &Value) -> Value {
+    let mut display = inputs.clone();
+    let Value::Object(map) = &mut display else {
+        return display;
+    };
+    for decl in collect_inputs(card) {
+        if decl.kind != "Input.ChoiceSet" {
+            continue;
+        }
+        let Some(value) = map.get_mut(&decl.id) else {
+            continue;
+        };
+        let choices = decl
+            .element
+            .get("choices")
+            .and_then(|c| c.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let title = |raw: &Value| -> Value {
+            choices
+                .iter()
+                .find(|choice| choice.get("value").is_some_and(|v| v == raw))
+                .and_then(|choice| choice.get("title"))
+                .cloned()
+                .unwrap_or_else(|| raw.clone())
+        };
+        *value = match &*value {
+            Value::Array(items) => Value::Array(items.iter().map(&title).collect()),
+            other => title(other),
+        };
+    }
+    display
+}
+
+fn is_multi_select(element: &Map<String, Value>) -> bool {
+    element
+        .get("isMultiSelect")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+fn coerce_toggle(value: &Value, element: &Map<String, Value>) -> Option<Value> {
+    let value_on = element
+        .get("valueOn")
+        .and_then(|v| v.as_str())
+        .unwrap_or("true");
+    let value_off = element
+        .get("valueOff")
+        .and_then(|v| v.as_str())
+        .unwrap_or("false");
+    match value {
+        Value::Bool(b) => Some(Value::Bool(*b)),
+        Value::String(s) if s == value_on || s.eq_ignore_ascii_case("true") => {
+            Some(Value::Bool(true))
+        }
+        Value::String(s) if s == value_off || s.eq_ignore_ascii_case("false") => {
+            Some(Value::Bool(false))
+        }
+        _ => None,
+    }
+}
+
+/// Multi-select ChoiceSets submit a comma-separated string of values.
+fn coerce_multi_select(value: &Value) -> Option<Value> {
+    match value {
+        Value::Array(_) => Some(value.clone()),
+        Value::String(s) => Some(Value::Array(
+            s.split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(|v| Value::String(v.to_string()))
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+/// Parses `HH:MM`, `HH:MM:SS`, or 12-hour `h:MM AM` times into 24-hour `HH:MM`.
+fn parse_time(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let upper = raw.to_ascii_uppercase();
+    let (clock, meridiem) = match upper
+        .strip_suffix("AM")
+        .or_else(|| upper.strip_suffix("PM"))
+    {
+        Some(clock) => (clock.trim().to_string(), Some(upper.ends_with("PM"))),
+        None => (upper.clone(), None),
+    };
+    let mut parts = clock.split(':');
+    let mut hour = parts.next()?.parse::<u32>().ok()?;
+    let minute = parts.next()?.parse::<u32>().ok()?;
+    if let Some(seconds) = parts.next() {
+        seconds.parse::<u32>().ok().filter(|s| *s < 60)?;
+    }
+    if parts.next().is_some() || minute > 59 {
+        return None;
+    }
+    if let Some(pm) = meridiem {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = match (hour, pm) {
+            (12, false) => 0,
+            (12, true) => 12,
+            (h, true) => h + 12,
+            (h, false) => h,
+        };
+    }
+    (hour < 24).then(|| format!("{hour:02}:{minute:02}"))
+}
+
+/// Restores autosaved `drafts.<card_instance_id>` values into the matching inputs' `value`.
+pub fn restore_drafts(card: &mut Value, state: &Value, card_instance_id: Option<&str>) {
+    let Some(drafts) = card_instance_id
+        .and_then(|id| state.get("drafts")?.get(id))
+        .and_then(|d| d.as_object())
+    else {
+        return;
+    };
+    fn walk(value: &mut Value, drafts: &Map<String, Value>) {
+        match value {
+            Value::Object(map) => {
+                if map
+                    .get("type")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|kind| kind.starts_with("Input."))
+                    && let Some(draft) = map
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .and_then(|id| drafts.get(id))
+                {
+                    map.insert("value".into(), draft.clone());
+                }
+                for child in map.values_mut() {
+                    walk(child, drafts);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    walk(item, drafts);
+                }
+            }
+            _ => {}
+        }
+    }
+    walk(card, drafts);
+}
+
+/// Sets each input's `value` from the submitted (normalized) `inputs` so a re-rendered card
+/// keeps the user's entries. Values are written back in the form the host submits them:
+/// toggles as their `valueOn`/`valueOff`, multi-select choices comma-separated, and
+/// namespaced and dotted inputs read from their regrouped rows and nested objects. Typed
+/// inputs submitted empty (`null`) lose their `value`; inputs that were not submitted, and
+/// file inputs, are left alone.
+pub fn prefill_inputs(card: &mut Value, inputs: &Value) {
+    fn submitted<'a>(inputs: &'a Value, id: &str) -> Option<&'a Value> {
+        inputs
+            .get(id)
+            .or_else(|| {
+                let (namespace, index, field) = split_namespaced(id)?;
+                inputs.get(namespace)?.get(index)?.get(field)
+            })
+            .or_else(|| id.split('.').try_fold(inputs, |node, key| node.get(key)))
+    }
+
+    fn walk(value: &mut Value, inputs: &Value) {
+        match value {
+            Value::Object(map) => {
+                if let Some(kind) = map.get("type").and_then(|v| v.as_str())
+                    && kind.starts_with("Input.")
+                    && kind != "Input.File"
+                    && let Some(submitted) = map
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .and_then(|id| submitted(inputs, id))
+                {
+                    match prefill_value(submitted, map) {
+                        Some(prefill) => {
+                            map.insert("value".into(), prefill);
+                        }
+                        None if submitted.is_null() => {
+                            map.remove("value");
+                        }
+                        None => {}
+                    }
+                }
+                for child in map.values_mut() {
+                    walk(child, inputs);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    walk(item, inputs);
+                }
+            }
+            _ => {}
+        }
+    }
+    walk(card, inputs);
+}
+
+fn prefill_value(value: &Value, element: &Map<String, Value>) -> Option<Value> {
+    let text = |key: &str, default: &str| {
+        Value::String(
+            element
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or(default)
+                .to_string(),
+        )
+    };
+    match value {
+        Value::Bool(true) => Some(text("valueOn", "true")),
+        Value::Bool(false) => Some(text("valueOff", "false")),
+        Value::Array(items) => {
+            let joined: Option<Vec<String>> = items
+                .iter()
+                .map(|item| match item {
+                    Value::String(s) => Some(s.clone()),
+                    Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                })
+                .collect();
+            joined.map(|values| Value::String(values.join(",")))
+        }
+        Value::String(_) | Value::Number(_) => Some(value.clone()),
+        Value::Null | Value::Object(_) => None,
+    }
+}
+
+fn coerce_rating(value: &Value, element: &Map<String, Value>) -> Option<Value> {
+    let raw = numeric(value)?;
+    let max = element.get("max").and_then(|v| v.as_f64()).unwrap_or(5.0);
+    let allow_half = element
+        .get("allowHalf")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let rounded = if allow_half {
+        (raw * 2.0).round() / 2.0
+    } else {
+        raw.round()
+    };
+    Some(number_value(rounded.clamp(0.0, max.max(0.0))))
+}
+
+fn coerce_slider(value: &Value, element: &Map<String, Value>) -> Option<Value> {
+    let raw = numeric(value)?;
+    let min = element.get("min").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let max = element.get("max").and_then(|v| v.as_f64()).unwrap_or(100.0);
+    if min > max {
+        return None;
+    }
+    let mut snapped = raw;
+    if let Some(step) = element.get("step").and_then(|v| v.as_f64())
+        && step > 0.0
+    {
+        let scale = 10f64.powi(decimal_places(step).max(decimal_places(min)));
+        snapped = ((min + ((raw - min) / step).round() * step) * scale).round() / scale;
+    }
+    Some(number_value(snapped.clamp(min, max)))
+}
+
+/// Digits after the decimal point in the shortest representation of `value`, so snapping to
+/// a step of `0.1` yields `0.3` rather than `0.30000000000000004`.
+fn decimal_places(value: f64) -> i32 {
+    value
+        .to_string()
+        .split_once('.')
+        .map_or(0, |(_, digits)| digits.len().min(15) as i32)
+}
+
+/// Parses a JSON number or numeric string.
+pub fn numeric(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok().filter(|f| f.is_finite()),
+        _ => None,
+    }
+}
+
+/// Emits integral floats as JSON integers so `3.0` round-trips as `3`.
+pub fn number_value(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        Value::from(value as i64)
+    } else {
+        serde_json::Number::from_f64(value)
+            .map(Value::Number)
+            .unwrap_or(Value::Null)
+    }
+}
+
+/// Normalizes `#rgb` / `#rrggbb` colors to lowercase `#rrggbb`.
+pub fn normalize_hex_color(raw: &str) -> Option<String> {
+    let hex = raw.trim().strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        3 => Some(format!(
+            "#{}",
+            hex.chars()
+                .flat_map(|c| [c, c])
+                .collect::<String>()
+                .to_ascii_lowercase()
+        )),
+        6 => Some(format!("#{}", hex.to_ascii_lowercase())),
+        _ => None,
+    }
+}
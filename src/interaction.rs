@@ -1,13 +1,38 @@
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
 
+use crate::actions;
+use crate::audit_log;
+use crate::continuation;
+use crate::derived;
+use crate::dialogs;
 use crate::error::ComponentError;
+use crate::inputs;
+use crate::migrations;
 use crate::model::{
     AdaptiveActionEvent, AdaptiveActionType, AdaptiveCardInvocation, AdaptiveCardResult,
-    CardInteractionType, SessionUpdateOp, StateUpdateOp,
+    AuditOutcome, CardInteraction, CardInteractionType, CardSource, DisableActions, InvocationMode,
+    SessionUpdateOp, StateUpdateOp, ValidationMode,
 };
-use crate::render::render_card;
+use crate::pagination;
+use crate::policy;
+use crate::receipt;
+use crate::redaction;
+use crate::render::{BindingContext, render_card, resolve_template};
+use crate::routing;
+use crate::rules;
 use crate::state_store;
+use crate::tables;
+use crate::telemetry;
 use crate::trace;
+use crate::ui_state;
+use crate::uploads;
+use crate::verbs;
+use crate::visibility;
+
+/// Action `data` key whose entries become `SetVariable` (or `UnsetVariable` for `null`) ops.
+const SESSION_KEY: &str = "__session";
+/// Action `data` key whose object is emitted as a single `MergeVariables` op.
+const SESSION_MERGE_KEY: &str = "__sessionMerge";
 
 pub fn handle_interaction(
     inv: &AdaptiveCardInvocation,
@@ -27,76 +52,389 @@ pub fn handle_interaction(
         ));
     }
 
+    // Redelivered interactions return the recorded result instead of re-applying updates.
+    if let Some(cached) = state_store::cached_result(&interaction)? {
+        return Ok(serde_json::from_value(cached)?);
+    }
+
+    if let Some(limit) = inv.rate_limit.as_ref() {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let record = inv.mode != InvocationMode::Simulate;
+        if let Some(retry_after_secs) =
+            state_store::check_rate_limit(&interaction, limit, now_ms, record)?
+        {
+            let card = match limit.notice.as_deref() {
+                Some(notice) => Some(Box::new(slow_down_card(inv, &interaction, notice)?)),
+                None => None,
+            };
+            return Err(ComponentError::RateLimited {
+                retry_after_secs,
+                card,
+            });
+        }
+    }
+
+    let started = std::time::Instant::now();
     let mut invocation = inv.clone();
-    let state_loaded = state_store::load_state_if_missing(&mut invocation, Some(&interaction))?;
+    let persist = invocation.mode != InvocationMode::Simulate;
+    let state_loaded =
+        state_store::load_state_if_missing(&mut invocation, Some(&interaction), persist)?;
     let state_read_hash = state_loaded.as_ref().and_then(trace::hash_value);
-    let resolved = render_card(&invocation)?;
-    let normalized_inputs = normalize_inputs(&interaction.raw_inputs);
-    let mut state_updates = Vec::new();
+    let initial = render_card(&invocation)?;
+    if initial.blocked_actions.contains(&interaction.action_id)
+        || invocation
+            .security
+            .as_ref()
+            .is_some_and(|p| !policy::interaction_allowed(p, &interaction.interaction_type))
+    {
+        return Err(ComponentError::InteractionInvalid(format!(
+            "action {} is blocked by the security policy",
+            interaction.action_id
+        )));
+    }
+    if initial.restricted_actions.contains(&interaction.action_id) {
+        return Err(ComponentError::InteractionInvalid(format!(
+            "action {} is not visible to the viewer's roles",
+            interaction.action_id
+        )));
+    }
+    // Drafts and uploads name their input in `action_id`.
+    if matches!(
+        interaction.interaction_type,
+        CardInteractionType::InputChanged | CardInteractionType::UploadComplete
+    ) && initial.restricted_inputs.contains(&interaction.action_id)
+    {
+        return Err(ComponentError::InteractionInvalid(format!(
+            "input {} is not visible to the viewer's roles",
+            interaction.action_id
+        )));
+    }
+    if initial.disabled_actions.contains(&interaction.action_id)
+        || actions::is_disabled(&invocation.state, &interaction.action_id)
+    {
+        return Err(ComponentError::InteractionInvalid(format!(
+            "action {} is disabled",
+            interaction.action_id
+        )));
+    }
+    // Interactions submitted from a dialog are checked against the dialog's card.
+    let dialog = dialogs::submitted_dialog(&interaction, &initial.dialogs)?;
+    let source_card = dialog
+        .as_ref()
+        .map(|dialog| dialog.card.clone().unwrap_or(Value::Null))
+        .unwrap_or_else(|| initial.card.clone());
+    let mut normalized_inputs = normalize_inputs(&interaction.raw_inputs);
+    // Values for inputs the viewer's roles hide are dropped, as if never submitted.
+    if let Value::Object(map) = &mut normalized_inputs {
+        map.retain(|id, _| !initial.restricted_inputs.contains(id));
+    }
+    // Submit and Execute only carry (and validate) the inputs their `associatedInputs` scope.
+    if matches!(
+        interaction.interaction_type,
+        CardInteractionType::Submit | CardInteractionType::Execute
+    ) && let Value::Object(map) = &mut normalized_inputs
+    {
+        let excluded = actions::unassociated_inputs(&source_card, &interaction.action_id);
+        map.retain(|id, _| !excluded.contains(id));
+    }
+    let mut coercion_issues = inputs::coerce_inputs(&mut normalized_inputs, &source_card);
+    let mut uploaded = None;
+    if interaction.interaction_type == CardInteractionType::UploadComplete {
+        let (files, upload_issues) =
+            uploads::complete_upload(&interaction, &source_card, invocation.security.as_ref())?;
+        let mut upload_inputs = Map::new();
+        upload_inputs.insert(
+            interaction.action_id.clone(),
+            files.clone().unwrap_or(Value::Null),
+        );
+        normalized_inputs = Value::Object(upload_inputs);
+        coercion_issues = upload_issues;
+        uploaded = files;
+    }
+    inputs::regroup_namespaced_inputs(&mut normalized_inputs, &source_card);
+    if initial.nest_inputs {
+        inputs::nest_dotted_inputs(&mut normalized_inputs, &source_card);
+    }
+    if invocation.validation_mode == ValidationMode::Error && !coercion_issues.is_empty() {
+        return Err(ComponentError::CardValidation(coercion_issues));
+    }
+    let declared_action = actions::find_action(&source_card, &interaction.action_id);
+    let opened_dialog =
+        dialogs::opened_dialog(&interaction, declared_action.as_ref(), &initial.dialogs)?;
+    let upload_request = matches!(
+        interaction.interaction_type,
+        CardInteractionType::Submit | CardInteractionType::Execute
+    )
+    .then(|| {
+        uploads::upload_request(
+            declared_action.as_ref(),
+            invocation.target_profile.as_ref(),
+            &interaction.card_instance_id,
+        )
+    })
+    .flatten();
+    // A TextRun's selectAction has no inputs of its own to carry its intent, so its `data` is
+    // submitted with it; other actions' `data` stays out of the inputs.
+    if let Some(declared) = declared_action.as_ref()
+        && declared.inline
+        && matches!(
+            interaction.interaction_type,
+            CardInteractionType::Submit | CardInteractionType::Execute
+        )
+    {
+        merge_action_data(&mut normalized_inputs, declared.data());
+    }
+    // Session mutations are only honoured from the declared action, never from client inputs.
+    if let Value::Object(map) = &mut normalized_inputs {
+        map.remove(SESSION_KEY);
+        map.remove(SESSION_MERGE_KEY);
+        map.remove(continuation::NEXT_KEY);
+    }
+    // What reaches state and the event; rules, routing, and derived state see the inputs as
+    // submitted.
+    let stored_inputs = redaction::redact_inputs(&normalized_inputs, invocation.redaction.as_ref());
+    // UI state left behind by elements the current template no longer has is dropped.
+    let mut state_updates = match resolve_template(&invocation) {
+        Ok(template) => ui_state::orphaned_ui_state(
+            &invocation.state,
+            &ui_state::element_ids([&template, &initial.card]),
+        ),
+        Err(_) => Vec::new(),
+    };
     let mut session_updates = Vec::new();
 
-    if let Some(route) = interaction
+    let verb = interaction.verb.clone().or_else(|| {
+        declared_action
+            .as_ref()
+            .and_then(|a| a.verb())
+            .map(str::to_string)
+    });
+    let routed = routing::match_route(
+        &invocation.routing,
+        verb.as_deref(),
+        &interaction.action_id,
+        &BindingContext::for_interaction(&invocation, &normalized_inputs),
+    );
+    // An explicit `route` in the action metadata overrides the routing table.
+    let route = interaction
         .metadata
         .get("route")
         .and_then(|v| v.as_str())
         .map(|s| s.to_string())
-    {
+        .or_else(|| routed.and_then(|rule| rule.route.clone()));
+    if let Some(route) = route.clone() {
         session_updates.push(SessionUpdateOp::SetRoute { route });
     }
+    session_updates.extend(session_mutations(
+        declared_action.as_ref().and_then(|a| a.data()),
+    ));
 
+    let drafts_path = format!("drafts.{}", interaction.card_instance_id);
+    let has_drafts = invocation
+        .state
+        .get("drafts")
+        .and_then(|d| d.get(&interaction.card_instance_id))
+        .is_some();
+    // Generated pager and table actions only change UI state; they never reach the flow as
+    // events.
+    let ui_update = matches!(
+        interaction.interaction_type,
+        CardInteractionType::Submit | CardInteractionType::Execute
+    )
+    .then(|| {
+        let data = declared_action.as_ref().and_then(|a| a.data());
+        pagination::page_update(data)
+            .or_else(|| tables::table_update(data, &normalized_inputs, &invocation.state))
+    })
+    .flatten();
+    // Opening a dialog or an upload changes nothing until it completes.
+    let ui_only = ui_update.is_some() || opened_dialog.is_some() || upload_request.is_some();
+    state_updates.extend(ui_update);
+    // Broken `$rules` hold the submit back: the inputs are kept as drafts and the messages
+    // persisted for the re-render to show under the offending inputs.
+    let rules_checked = !ui_only
+        && dialog.is_none()
+        && invocation.validation_mode != ValidationMode::Off
+        && matches!(
+            interaction.interaction_type,
+            CardInteractionType::Submit | CardInteractionType::Execute
+        );
+    let (input_errors, rule_issues) = if rules_checked {
+        rules::check_rules(
+            &initial.rules,
+            &inputs::collect_inputs(&source_card),
+            &BindingContext::for_rules(&invocation, &normalized_inputs),
+        )
+    } else {
+        Default::default()
+    };
+    if invocation.validation_mode == ValidationMode::Error && !rule_issues.is_empty() {
+        return Err(ComponentError::CardValidation(rule_issues));
+    }
+    let rejected = !rule_issues.is_empty();
+    let errors_path = format!("ui.input_errors.{}", interaction.card_instance_id);
+    if rejected {
+        state_updates.push(StateUpdateOp::Set {
+            path: errors_path,
+            value: json!(input_errors),
+        });
+        state_updates.push(StateUpdateOp::Merge {
+            path: drafts_path.clone(),
+            value: redaction::omit_redacted(&normalized_inputs, invocation.redaction.as_ref()),
+        });
+    } else if rules_checked
+        && invocation
+            .state
+            .get("ui")
+            .and_then(|ui| ui.get("input_errors"))
+            .and_then(|errors| errors.get(&interaction.card_instance_id))
+            .is_some()
+    {
+        state_updates.push(StateUpdateOp::Delete { path: errors_path });
+    }
+    if let Some(dialog) = dialog.as_ref()
+        && !ui_only
+        && matches!(
+            interaction.interaction_type,
+            CardInteractionType::Submit | CardInteractionType::Execute
+        )
+    {
+        state_updates.push(StateUpdateOp::Set {
+            path: format!("dialogs.{}", dialog.id),
+            value: stored_inputs.clone(),
+        });
+    }
     let action_type = match interaction.interaction_type {
+        _ if ui_only || rejected => None,
         CardInteractionType::Submit => {
             state_updates.push(StateUpdateOp::Merge {
                 path: "form_data".into(),
-                value: normalized_inputs.clone(),
+                value: stored_inputs.clone(),
             });
-            AdaptiveActionType::Submit
+            if has_drafts {
+                state_updates.push(StateUpdateOp::Delete { path: drafts_path });
+            }
+            Some(AdaptiveActionType::Submit)
         }
         CardInteractionType::Execute => {
             state_updates.push(StateUpdateOp::Merge {
                 path: "form_data".into(),
-                value: normalized_inputs.clone(),
+                value: stored_inputs.clone(),
             });
-            AdaptiveActionType::Execute
+            if has_drafts {
+                state_updates.push(StateUpdateOp::Delete { path: drafts_path });
+            }
+            Some(AdaptiveActionType::Execute)
         }
-        CardInteractionType::OpenUrl => AdaptiveActionType::OpenUrl,
+        CardInteractionType::OpenUrl => Some(AdaptiveActionType::OpenUrl),
         CardInteractionType::ShowCard => {
             let subcard_id = interaction
                 .metadata
                 .get("subcardId")
                 .and_then(|v| v.as_str())
+                .or_else(|| {
+                    declared_action
+                        .as_ref()
+                        .and_then(|a| a.action.get("card"))
+                        .and_then(|c| c.get("id"))
+                        .and_then(|v| v.as_str())
+                })
                 .unwrap_or(&interaction.action_id)
                 .to_string();
             state_updates.push(StateUpdateOp::Set {
                 path: format!("ui.active_show_card.{}", interaction.card_instance_id),
                 value: Value::String(subcard_id.clone()),
             });
-            AdaptiveActionType::ShowCard
+            Some(AdaptiveActionType::ShowCard)
         }
         CardInteractionType::ToggleVisibility => {
-            let visible = interaction
-                .metadata
-                .get("visible")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(true);
-            state_updates.push(StateUpdateOp::Set {
-                path: format!("ui.visibility.{}", interaction.action_id),
-                value: Value::Bool(visible),
+            match declared_action
+                .as_ref()
+                .filter(|a| a.kind == "Action.ToggleVisibility")
+            {
+                Some(declared) => {
+                    for (element_id, visible) in
+                        visibility::resolve_toggle(&declared.action, &initial.card)
+                    {
+                        state_updates.push(StateUpdateOp::Set {
+                            path: format!("ui.visibility.{element_id}"),
+                            value: Value::Bool(visible),
+                        });
+                    }
+                }
+                None => {
+                    // Undeclared actions keep the legacy single flag keyed by action id.
+                    let visible = interaction
+                        .metadata
+                        .get("visible")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(true);
+                    state_updates.push(StateUpdateOp::Set {
+                        path: format!("ui.visibility.{}", interaction.action_id),
+                        value: Value::Bool(visible),
+                    });
+                }
+            }
+            Some(AdaptiveActionType::ToggleVisibility)
+        }
+        CardInteractionType::InputChanged => {
+            let input_id = interaction.action_id.clone();
+            let mut draft = Map::new();
+            draft.insert(
+                input_id.clone(),
+                draft_value(&interaction.raw_inputs, &input_id),
+            );
+            let mut draft = Value::Object(draft);
+            inputs::coerce_inputs(&mut draft, &source_card);
+            state_updates.push(StateUpdateOp::Merge {
+                path: drafts_path,
+                value: redaction::omit_redacted(&draft, invocation.redaction.as_ref()),
             });
-            AdaptiveActionType::ToggleVisibility
+            None
         }
+        CardInteractionType::OpenDialog => None,
+        CardInteractionType::UploadComplete => uploaded.map(|files| {
+            state_updates.push(StateUpdateOp::Set {
+                path: format!(
+                    "uploads.{}.{}",
+                    interaction.card_instance_id, interaction.action_id
+                ),
+                value: files,
+            });
+            state_updates.push(StateUpdateOp::Merge {
+                path: "form_data".into(),
+                value: stored_inputs.clone(),
+            });
+            AdaptiveActionType::UploadComplete
+        }),
     };
+    let continuation = matches!(
+        action_type,
+        Some(AdaptiveActionType::Submit | AdaptiveActionType::Execute)
+    )
+    .then(|| {
+        continuation::continuation(
+            declared_action.as_ref().and_then(|a| a.data()),
+            &invocation,
+            &interaction,
+        )
+    })
+    .flatten();
 
-    let event = AdaptiveActionEvent {
+    let event = action_type.map(|action_type| AdaptiveActionEvent {
         action_type,
         action_id: interaction.action_id.clone(),
-        verb: interaction.verb.clone(),
-        route: interaction
-            .metadata
-            .get("route")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string()),
-        inputs: normalized_inputs.clone(),
+        verb: verb.clone(),
+        route,
+        node: routed.and_then(|rule| rule.node.clone()),
+        op: routed.and_then(|rule| rule.op.clone()),
+        inputs: stored_inputs.clone(),
+        inputs_display: invocation
+            .choice_labels
+            .then(|| inputs::display_inputs(&stored_inputs, &source_card)),
         card_id: interaction
             .metadata
             .get("cardId")
@@ -109,8 +447,30 @@ pub fn handle_interaction(
             .get("subcardId")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string()),
-        metadata: interaction.metadata.clone(),
-    };
+        dialog_id: dialog.as_ref().map(|dialog| dialog.id.clone()),
+        metadata: event_metadata(
+            &interaction.metadata,
+            inputs::submitted_classifications(&normalized_inputs, &initial.classifications),
+        ),
+    });
+
+    if let Some(scope) = invocation.disable_after_click.as_ref()
+        && !ui_only
+        && !rejected
+        && matches!(
+            interaction.interaction_type,
+            CardInteractionType::Submit | CardInteractionType::Execute
+        )
+    {
+        let key = match scope {
+            DisableActions::Clicked => interaction.action_id.as_str(),
+            DisableActions::All => actions::ALL_ACTIONS,
+        };
+        state_updates.push(StateUpdateOp::Set {
+            path: format!("ui.disabled_actions.{key}"),
+            value: Value::Bool(true),
+        });
+    }
 
     let mut persisted_state = if invocation.state.is_null() {
         Value::Object(Map::new())
@@ -118,8 +478,63 @@ pub fn handle_interaction(
         invocation.state.clone()
     };
     state_store::apply_updates(&mut persisted_state, &state_updates);
+    let mut verb_issues = Vec::new();
+    if let Some(event) = event.as_ref() {
+        let (outcome, issues) = verbs::dispatch(event, &persisted_state);
+        state_store::apply_updates(&mut persisted_state, &outcome.state_updates);
+        state_updates.extend(outcome.state_updates);
+        session_updates.extend(outcome.session_updates);
+        verb_issues = issues;
+    }
+    let (derived_updates, derived_issues) = derived::derive_state(
+        &initial.derived,
+        &BindingContext::for_interaction(&invocation, &normalized_inputs),
+        &mut persisted_state,
+    );
+    state_updates.extend(derived_updates);
+    let simulate = invocation.mode == InvocationMode::Simulate;
     let state_write_hash = trace::hash_value(&persisted_state);
-    state_store::persist_state(&invocation, Some(&interaction), &persisted_state)?;
+    if !simulate {
+        migrations::stamp_version(&invocation, &mut persisted_state);
+        state_store::persist_state(&invocation, Some(&interaction), &persisted_state)?;
+    }
+    // Re-render against the post-interaction state so UI state (visibility flags, expanded
+    // ShowCards) is reflected in the returned card.
+    invocation.state = persisted_state;
+    let mut resolved = render_card(&invocation)?;
+    // Submits with input issues, and any submit under `preserve_inputs`, keep the entries.
+    if matches!(
+        interaction.interaction_type,
+        CardInteractionType::Submit | CardInteractionType::Execute
+    ) && (invocation.preserve_inputs || rejected || !coercion_issues.is_empty())
+    {
+        // The card is recorded for idempotent replays, so redacted values are left empty.
+        inputs::prefill_inputs(
+            &mut resolved.card,
+            &redaction::omit_redacted(&normalized_inputs, invocation.redaction.as_ref()),
+        );
+    }
+    if invocation.render_receipt
+        && !ui_only
+        && matches!(
+            interaction.interaction_type,
+            CardInteractionType::Submit | CardInteractionType::Execute
+        )
+    {
+        resolved.card = match resolved.post_submit_view.take() {
+            Some(view) => {
+                let mut receipt_inv = invocation.clone();
+                receipt_inv.card_source = CardSource::Inline;
+                receipt_inv.card_spec.inline_json = Some(view);
+                render_card(&receipt_inv)?.card
+            }
+            None => receipt::build_receipt(
+                &resolved.card,
+                &redaction::receipt_inputs(&normalized_inputs, invocation.redaction.as_ref()),
+            ),
+        };
+    }
+    let simulated_state = simulate.then(|| invocation.state.clone());
 
     let mut telemetry_events = Vec::new();
     if trace::trace_enabled() {
@@ -134,16 +549,70 @@ pub fn handle_interaction(
             state_write_hash,
         ));
     }
+    telemetry_events.extend(telemetry::build_events(
+        &invocation.telemetry,
+        &resolved,
+        Some(&interaction),
+        event.as_ref().and_then(|e| e.verb.as_deref()),
+        started.elapsed(),
+    ));
 
-    Ok(AdaptiveCardResult {
+    let audit = invocation.audit_log.as_ref().map(|config| {
+        let outcome = match (&event, rejected) {
+            (_, true) => AuditOutcome::Rejected,
+            (Some(_), false) => AuditOutcome::Accepted,
+            (None, false) => AuditOutcome::Handled,
+        };
+        audit_log::build_record(
+            config,
+            &invocation,
+            &interaction,
+            verb.as_deref(),
+            &normalized_inputs,
+            outcome,
+        )
+    });
+
+    let mut validation_issues = coercion_issues;
+    validation_issues.extend(rule_issues);
+    validation_issues.extend(verb_issues);
+    validation_issues.extend(derived_issues);
+    validation_issues.extend(resolved.validation_issues);
+    let result = AdaptiveCardResult {
         rendered_card: Some(resolved.card),
-        event: Some(event),
+        event,
         state_updates,
         session_updates,
         card_features: resolved.features,
-        validation_issues: resolved.validation_issues,
+        validation_issues,
         telemetry_events,
-    })
+        subcard: resolved.subcard,
+        template_analysis: None,
+        sample_data: None,
+        card_instance_id: None,
+        simulated_state,
+        variants: resolved.variants,
+        preview: None,
+        speak: None,
+        dialog: opened_dialog,
+        upload_request,
+        rendered_card_json: None,
+        card_hash: None,
+        fingerprint: resolved.fingerprint,
+        schemas: Default::default(),
+        health: None,
+        audit,
+        activity: None,
+        transcoded: None,
+        introspection: None,
+        schedule: resolved.schedule,
+        continuation,
+        state_snapshot: None,
+    };
+    if !simulate {
+        state_store::record_result(&interaction, &serde_json::to_value(&result)?)?;
+    }
+    Ok(result)
 }
 
 fn normalize_inputs(raw: &Value) -> Value {
@@ -162,3 +631,81 @@ fn normalize_inputs(raw: &Value) -> Value {
         }
     }
 }
+
+/// The interaction metadata with the submitted inputs' classifications added under
+/// `dataClassification`.
+fn event_metadata(metadata: &Value, classifications: Map<String, Value>) -> Value {
+    if classifications.is_empty() {
+        return metadata.clone();
+    }
+    let mut metadata = match metadata {
+        Value::Object(map) => map.clone(),
+        _ => Map::new(),
+    };
+    metadata.insert(
+        inputs::DATA_CLASSIFICATION_KEY.into(),
+        Value::Object(classifications),
+    );
+    Value::Object(metadata)
+}
+
+/// Translates the declared action's `__session` / `__sessionMerge` data into session ops.
+fn session_mutations(data: Option<&Value>) -> Vec<SessionUpdateOp> {
+    let mut ops = Vec::new();
+    if let Some(Value::Object(vars)) = data.and_then(|d| d.get(SESSION_KEY)) {
+        for (name, value) in vars {
+            ops.push(match value {
+                Value::Null => SessionUpdateOp::UnsetVariable { name: name.clone() },
+                value => SessionUpdateOp::SetVariable {
+                    name: name.clone(),
+                    value: value.clone(),
+                },
+            });
+        }
+    }
+    if let Some(values @ Value::Object(_)) = data.and_then(|d| d.get(SESSION_MERGE_KEY)) {
+        ops.push(SessionUpdateOp::MergeVariables {
+            values: values.clone(),
+        });
+    }
+    ops
+}
+
+/// Reads the drafted value from `raw_inputs`, which is either the bare value or an inputs
+/// object keyed by the input id.
+fn draft_value(raw: &Value, input_id: &str) -> Value {
+    match raw {
+        Value::Object(map) if map.contains_key(input_id) => map[input_id].clone(),
+        other => other.clone(),
+    }
+}
+
+/// Merges the action's static `data` object under the submitted inputs, matching the
+/// Adaptive Cards rule that input values win over declared data on key collisions.
+fn merge_action_data(inputs: &mut Value, data: Option<&Value>) {
+    let (Value::Object(inputs), Some(Value::Object(data))) = (inputs, data) else {
+        return;
+    };
+    for (key, value) in data {
+        inputs.entry(key.clone()).or_insert_with(|| value.clone());
+    }
+}
+
+/// Re-renders the card without applying the interaction and puts `notice` above its body.
+fn slow_down_card(
+    inv: &AdaptiveCardInvocation,
+    interaction: &CardInteraction,
+    notice: &str,
+) -> Result<Value, ComponentError> {
+    let mut invocation = inv.clone();
+    invocation.interaction = None;
+    state_store::load_state_if_missing(&mut invocation, Some(interaction), false)?;
+    let mut card = render_card(&invocation)?.card;
+    if let Some(body) = card.get_mut("body").and_then(Value::as_array_mut) {
+        body.insert(
+            0,
+            json!({ "type": "TextBlock", "text": notice, "color": "Warning", "wrap": true }),
+        );
+    }
+    Ok(card)
+}
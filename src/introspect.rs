@@ -0,0 +1,110 @@
+use serde_json::{Map, Value};
+
+use crate::actions::{assign_generated_ids, collect_actions, element_ids, unassociated_inputs};
+use crate::inputs::{self, collect_inputs};
+use crate::model::{
+    ActionDescription, CardIntrospection, ChoiceDescription, InputConstraints, InputDescription,
+};
+use crate::render;
+
+/// Describes the inputs and actions of `card`: each input's type, label, requirement,
+/// constraints, and default (coerced like a submission would be), and each action's
+/// verb, data, and, for Submit and Execute with an id, the inputs it sends. ActionSet
+/// actions and selectActions authored without an id get the id rendering generates.
+pub fn introspect(card: &Value) -> CardIntrospection {
+    let mut card = card.clone();
+    let template_hash = render::hash_json(&card).unwrap_or_default();
+    assign_generated_ids(&mut card, &template_hash, false);
+    let card = &card;
+    let pointers = element_ids(card);
+    let declared = collect_inputs(card);
+    let mut typed = Value::Object(
+        declared
+            .iter()
+            .filter_map(|decl| Some((decl.id.clone(), decl.element.get("value")?.clone())))
+            .collect::<Map<String, Value>>(),
+    );
+    inputs::coerce_inputs(&mut typed, card);
+
+    let inputs = declared
+        .iter()
+        .map(|decl| {
+            let element = &decl.element;
+            let text = |key: &str| element.get(key).and_then(Value::as_str).map(str::to_string);
+            InputDescription {
+                id: decl.id.clone(),
+                input_type: decl.kind.clone(),
+                pointer: pointers.get(&decl.id).cloned().unwrap_or_default(),
+                label: text("label").or_else(|| {
+                    (decl.kind == "Input.Toggle")
+                        .then(|| text("title"))
+                        .flatten()
+                }),
+                required: element.get("isRequired").and_then(Value::as_bool) == Some(true),
+                default: typed
+                    .get(&decl.id)
+                    .filter(|value| !value.is_null())
+                    .cloned(),
+                constraints: InputConstraints {
+                    min: element.get("min").cloned(),
+                    max: element.get("max").cloned(),
+                    max_length: element.get("maxLength").and_then(Value::as_u64),
+                    regex: text("regex"),
+                    style: text("style").filter(|_| decl.kind == "Input.Text"),
+                    choices: element
+                        .get("choices")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|choice| {
+                            let value = choice.get("value")?.as_str()?.to_string();
+                            let title = choice
+                                .get("title")
+                                .and_then(Value::as_str)
+                                .map_or_else(|| value.clone(), str::to_string);
+                            Some(ChoiceDescription { title, value })
+                        })
+                        .collect(),
+                    multi_select: element.get("isMultiSelect").and_then(Value::as_bool)
+                        == Some(true),
+                    error_message: text("errorMessage"),
+                },
+            }
+        })
+        .collect();
+
+    let input_ids: Vec<&str> = declared.iter().map(|decl| decl.id.as_str()).collect();
+    let actions = collect_actions(card)
+        .into_iter()
+        .map(|decl| {
+            let text = |key: &str| {
+                decl.action
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            };
+            let posts = matches!(decl.kind.as_str(), "Action.Submit" | "Action.Execute");
+            let submits = match &decl.id {
+                Some(id) if posts => {
+                    let excluded = unassociated_inputs(card, id);
+                    input_ids
+                        .iter()
+                        .filter(|input| !excluded.contains(**input))
+                        .map(|input| input.to_string())
+                        .collect()
+                }
+                _ => Vec::new(),
+            };
+            ActionDescription {
+                id: decl.id.clone(),
+                title: text("title"),
+                verb: decl.verb().map(str::to_string),
+                data: decl.data().cloned(),
+                url: text("url"),
+                submits,
+                action_type: decl.kind,
+            }
+        })
+        .collect();
+    CardIntrospection { inputs, actions }
+}
@@ -1,14 +1,76 @@
 #![cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
 
+mod actions;
+mod activity;
 mod asset_resolver;
+mod audit;
+mod audit_log;
+mod budget;
+mod cache;
+pub mod card;
+mod charts;
+mod components;
+mod compose;
+mod config;
+mod continuation;
+mod dependencies;
+mod derived;
+mod dialogs;
+mod elements;
+mod engine;
 mod error;
 mod expression;
+mod fallback;
+mod format;
+mod forms;
+mod health;
+mod hooks;
+mod i18n;
+mod includes;
+mod inputs;
 mod interaction;
+mod introspect;
+mod lifecycle;
+mod limits;
+mod markdown;
+mod migrations;
 mod model;
+mod openapi;
+mod outlook;
+mod output;
+mod pagination;
+mod policy;
+mod preview;
+mod receipt;
+mod redaction;
 mod render;
+mod rich_text;
+mod roles;
+mod routing;
+mod rules;
+mod sample_data;
+mod sanitize;
+mod schedule;
+mod schema;
+mod show_card;
+mod speech;
 mod state_store;
+mod stream;
+mod tables;
+mod telemetry;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+mod theme;
 mod trace;
+mod transcode;
+mod ui_state;
+mod uploads;
+mod urls;
 mod validation;
+mod verbs;
+mod views;
+mod visibility;
+mod yaml;
 
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -27,16 +89,88 @@ use once_cell::sync::Lazy;
 pub use asset_resolver::{
     register_host_asset_callback, register_host_asset_map, register_host_asset_resolver,
 };
+pub use components::{
+    ElementContext, ElementRenderer, register_component_macro, register_element_renderer,
+    register_element_renderer_callback,
+};
+pub use config::{apply_config, configure, current_config, load_startup_config};
+pub use engine::{AdaptiveCardEngine, StateBackend};
 pub use error::ComponentError;
+pub use expression::{
+    CallbackExpressionFunction, ExpressionFunction, register_expression_callback,
+    register_expression_function,
+};
+pub use fallback::register_host_feature;
+pub use hooks::{
+    BindingData, DataTransformer, PostRenderHook, register_data_transformer,
+    register_data_transformer_callback, register_post_render_callback, register_post_render_hook,
+    remove_post_render_hook,
+};
 pub use interaction::handle_interaction;
+pub use migrations::{
+    CallbackStateMigration, StateMigration, register_state_migration,
+    register_state_migration_callback,
+};
 pub use model::*;
 pub use render::render_card;
+pub use schema::{SCHEMA_NAMES, model_schema};
+pub use stream::{StreamFrame, invoke_stream};
+pub use transcode::{reply_interaction, select_interaction};
+pub use verbs::{VerbOutcome, register_verb_handler};
 
 const COMPONENT_NAME: &str = "component-adaptive-card";
 const COMPONENT_ORG: &str = "ai.greentic";
 const COMPONENT_VERSION: &str = "0.1.12";
 const COMPONENT_ID: &str = "ai.greentic.component-adaptive-card";
+/// Operations exposed by the component: the default render/interaction op and its streaming
+/// continuation, the card
+/// instance lifecycle ops, template tooling, card generation and composition, the model's
+/// JSON Schemas, and diagnostics.
+const OPERATIONS: [(&str, &str); 18] = [
+    ("card", "Render adaptive card"),
+    (
+        stream::CONTINUE_OPERATION,
+        "Deliver the final frame of a streamed interaction",
+    ),
+    ("validate", "Validate adaptive card"),
+    ("init-card", "Create card instance"),
+    ("dismiss-card", "Dismiss card instance"),
+    ("expire-card", "Expire card instance"),
+    ("export-state", "Export card instance state"),
+    ("import-state", "Import card instance state"),
+    ("compact-state", "Remove orphaned card instance UI state"),
+    ("analyze-template", "Analyze template dependencies"),
+    ("generate-sample-data", "Generate sample template data"),
+    ("introspect-card", "Describe a card's inputs and actions"),
+    (
+        "generate-card-from-schema",
+        "Generate a card collecting a JSON Schema",
+    ),
+    (
+        "generate-card-from-openapi",
+        "Generate a card collecting an OpenAPI operation's input",
+    ),
+    ("compile-form", "Compile the form DSL into a styled card"),
+    ("compose", "Merge several cards into one"),
+    ("get-schema", "JSON Schemas of the invocation and result"),
+    ("health", "Component diagnostics"),
+];
 const COMPONENT_ROLE: &str = "tool";
+/// Capabilities built into every build, reported as enabled in the manifest.
+const BUILTIN_FEATURES: &[&str] = &[
+    "charts",
+    "deep_links",
+    "dialogs",
+    "expressions",
+    "handlebars",
+    "i18n",
+    "markdown",
+    "pagination",
+    "speech",
+    "tables",
+    "theming",
+    "uploads",
+];
 
 static COMPONENT_SCHEMA_JSON: Lazy<serde_json::Value> = Lazy::new(|| {
     serde_json::from_str(include_str!("../schemas/component.schema.json"))
@@ -127,7 +261,7 @@ mod legacy_component_v0_5 {
         self, ExecCtx, InvokeResult, LifecycleStatus, StreamEvent,
     };
 
-    use super::{describe_payload, handle_message};
+    use super::{StreamFrame, config, describe_payload, handle_message};
 
     pub(super) struct ComponentV05;
 
@@ -136,8 +270,10 @@ mod legacy_component_v0_5 {
             describe_payload()
         }
 
-        fn on_start(_ctx: ExecCtx) -> Result<LifecycleStatus, String> {
-            Ok(LifecycleStatus::Ok)
+        fn on_start(ctx: ExecCtx) -> Result<LifecycleStatus, String> {
+            config::load_startup_config(ctx.i18n_id.as_deref())
+                .map(|_| LifecycleStatus::Ok)
+                .map_err(|err| err.to_string())
         }
 
         fn on_stop(_ctx: ExecCtx, _reason: String) -> Result<LifecycleStatus, String> {
@@ -149,11 +285,15 @@ mod legacy_component_v0_5 {
         }
 
         fn invoke_stream(_ctx: ExecCtx, op: String, input: String) -> Vec<StreamEvent> {
-            vec![
-                StreamEvent::Progress(0),
-                StreamEvent::Data(handle_message(&op, &input)),
-                StreamEvent::Done,
-            ]
+            let mut events = Vec::new();
+            super::invoke_stream(&op, &input, &mut |frame| {
+                events.push(match frame {
+                    StreamFrame::Progress(percent) => StreamEvent::Progress(percent),
+                    StreamFrame::Data(json) => StreamEvent::Data(json),
+                    StreamFrame::Done => StreamEvent::Done,
+                })
+            });
+            events
         }
     }
 }
@@ -327,13 +467,39 @@ mod legacy_exports_v0_5 {
     }
 }
 
+/// Manifest returned by `get_manifest`: the component identity and schemas, plus what this
+/// build supports so hosts can feature-detect at startup: its operations, the Adaptive Cards
+/// schema versions it renders, its optional features (enabled or not), and the expression
+/// functions templates may call, built-in and host-registered.
 pub fn describe_payload() -> String {
+    let operations: Vec<_> = OPERATIONS
+        .iter()
+        .map(|(id, label)| serde_json::json!({ "id": id, "description": label }))
+        .collect();
+    let mut features: BTreeMap<&str, bool> =
+        BUILTIN_FEATURES.iter().map(|name| (*name, true)).collect();
+    features.insert("state_store", cfg!(feature = "state-store"));
+    features.insert(
+        "asset_store",
+        cfg!(all(target_arch = "wasm32", feature = "asset-store")),
+    );
+    features.insert("preview_svg", cfg!(feature = "preview"));
+    features.insert("preview_png", cfg!(feature = "preview-png"));
+    let mut functions: Vec<String> = expression::FUNCTIONS
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    functions.extend(expression::host_functions());
     serde_json::json!({
         "component": {
             "name": COMPONENT_NAME,
             "org": COMPONENT_ORG,
             "version": COMPONENT_VERSION,
             "world": "greentic:component/component@0.6.0",
+            "operations": operations,
+            "schema_versions": fallback::SCHEMA_VERSIONS,
+            "features": features,
+            "functions": functions,
             "schemas": {
                 "component": COMPONENT_SCHEMA_JSON.clone(),
                 "input": INPUT_SCHEMA_JSON.clone(),
@@ -489,19 +655,26 @@ fn component_describe() -> ComponentDescribe {
         provided_capabilities: Vec::new(),
         required_capabilities: Vec::new(),
         metadata: BTreeMap::new(),
-        operations: vec![ComponentOperation {
-            id: "card".to_string(),
-            display_name: Some(I18nText::new(
-                "adaptive_card.operation.card",
-                Some("Render adaptive card".to_string()),
-            )),
-            input: ComponentRunInput { schema: input },
-            output: ComponentRunOutput { schema: output },
-            defaults: BTreeMap::new(),
-            redactions: Vec::new(),
-            constraints: BTreeMap::new(),
-            schema_hash: hash,
-        }],
+        operations: OPERATIONS
+            .iter()
+            .map(|(id, label)| ComponentOperation {
+                id: id.to_string(),
+                display_name: Some(I18nText::new(
+                    format!("adaptive_card.operation.{}", id.replace('-', "_")),
+                    Some(label.to_string()),
+                )),
+                input: ComponentRunInput {
+                    schema: input.clone(),
+                },
+                output: ComponentRunOutput {
+                    schema: output.clone(),
+                },
+                defaults: BTreeMap::new(),
+                redactions: Vec::new(),
+                constraints: BTreeMap::new(),
+                schema_hash: hash.clone(),
+            })
+            .collect(),
         config_schema: config,
     }
 }
@@ -600,11 +773,11 @@ fn run_component_cbor(input: Vec<u8>, _state: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
             let raw = serde_json::to_string(&value).unwrap_or_else(|_| "{}".to_string());
             handle_message(op, &raw)
         }
-        Err(err) => error_payload(
-            "AC_SCHEMA_INVALID",
+        Err(err) => serialize_response(&error_response(failure(
+            FailureCode::SchemaInvalid,
             "invalid cbor invocation",
             Some(serde_json::Value::String(err.to_string())),
-        ),
+        ))),
     };
     let parsed: serde_json::Value =
         serde_json::from_str(&output_json).unwrap_or_else(|_| serde_json::json!({}));
@@ -612,71 +785,236 @@ fn run_component_cbor(input: Vec<u8>, _state: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
 }
 
 pub fn handle_message(operation: &str, input: &str) -> String {
-    let value: serde_json::Value = match serde_json::from_str(input) {
-        Ok(value) => value,
-        Err(err) => {
-            return error_payload(
-                "AC_SCHEMA_INVALID",
-                "invalid JSON",
-                Some(serde_json::Value::String(err.to_string())),
-            );
-        }
+    serialize_response(&process_message(operation, input))
+}
+
+/// Typed counterpart of [`handle_message`]; every failure path yields
+/// [`AdaptiveCardResponse::Failure`].
+pub fn process_message(operation: &str, input: &str) -> AdaptiveCardResponse {
+    if operation == "health" {
+        return AdaptiveCardResponse::Success(Box::new(AdaptiveCardResult {
+            health: Some(health::report(COMPONENT_ID, COMPONENT_VERSION)),
+            ..Default::default()
+        }));
+    }
+    let response = respond(operation, input);
+    health::record(&response);
+    response
+}
+
+/// [`handle_message`] for hosts that already hold the message as a `serde_json::Value`: the
+/// value is consumed without a string round trip, and the response is returned as a value.
+pub fn handle_message_value(operation: &str, input: serde_json::Value) -> serde_json::Value {
+    let response = if operation == "health" {
+        process_message(operation, "")
+    } else {
+        let response = respond_value(operation, input);
+        health::record(&response);
+        response
     };
-    let invocation_value =
-        validation::locate_invocation_candidate(&value).unwrap_or_else(|| value.clone());
-    let validation_mode = read_validation_mode(&value, &invocation_value);
+    serde_json::to_value(&response)
+        .unwrap_or_else(|err| serde_json::to_value(serialization_failure(err)).unwrap_or_default())
+}
+
+/// [`handle_message_value`] for the default `card` operation.
+pub fn handle_invocation_value(input: serde_json::Value) -> serde_json::Value {
+    handle_message_value("card", input)
+}
+
+/// [`handle_message`] for a message borrowed from a larger document the host parsed lazily
+/// (e.g. a field of its own request), parsed once straight from the raw JSON text.
+pub fn handle_message_raw(
+    operation: &str,
+    input: &serde_json::value::RawValue,
+) -> Box<serde_json::value::RawValue> {
+    let response = process_message(operation, input.get());
+    serde_json::value::to_raw_value(&response)
+        .or_else(|err| serde_json::value::to_raw_value(&serialization_failure(err)))
+        .unwrap_or_else(|_| {
+            serde_json::value::RawValue::from_string("{}".to_string()).expect("valid JSON")
+        })
+}
+
+fn respond(operation: &str, input: &str) -> AdaptiveCardResponse {
+    match serde_json::from_str(input) {
+        Ok(value) => respond_value(operation, value),
+        Err(err) => error_response(failure(
+            FailureCode::SchemaInvalid,
+            "invalid JSON",
+            Some(serde_json::Value::String(err.to_string())),
+        )),
+    }
+}
+
+fn respond_value(operation: &str, value: serde_json::Value) -> AdaptiveCardResponse {
+    if operation == "get-schema" {
+        return match schema::requested_schemas(&value) {
+            Ok(schemas) => AdaptiveCardResponse::Success(Box::new(AdaptiveCardResult {
+                schemas,
+                ..Default::default()
+            })),
+            Err(err) => error_response(err.to_failure()),
+        };
+    }
+    let invocation_value = validation::locate_invocation_candidate(&value).unwrap_or(&value);
+    let validation_mode = read_validation_mode(&value, invocation_value);
     let mut validation_issues = if validation_mode == ValidationMode::Off {
         Vec::new()
     } else {
-        validation::validate_invocation_schema(&invocation_value)
+        validation::validate_invocation_schema(invocation_value)
     };
     if validation_mode == ValidationMode::Error && !validation_issues.is_empty() {
-        return validation_error_payload(&validation_issues, None);
+        return error_response(validation_failure(&validation_issues, None));
     }
 
-    let mut invocation = match parse_invocation_value(&value) {
+    let parsed = if validation::is_bare_invocation(&value) {
+        // The common shape: move the value into the invocation instead of copying it.
+        serde_json::from_value(value).map_err(ComponentError::Serde)
+    } else {
+        parse_invocation_value(&value)
+    };
+    let mut invocation = match parsed {
         Ok(invocation) => invocation,
         Err(err) => {
             if !validation_issues.is_empty() {
-                return validation_error_payload(&validation_issues, Some(&err.to_string()));
+                return error_response(validation_failure(
+                    &validation_issues,
+                    Some(&err.to_string()),
+                ));
             }
-            return error_payload(
-                "AC_SCHEMA_INVALID",
+            return error_response(failure(
+                FailureCode::SchemaInvalid,
                 "invalid invocation",
                 Some(serde_json::Value::String(err.to_string())),
-            );
+            ));
         }
     };
+    config::apply_config(&mut invocation);
     // Allow the operation name to steer mode selection if the host provides it.
     if operation.eq_ignore_ascii_case("validate") {
         invocation.mode = InvocationMode::Validate;
     }
-    match handle_invocation(invocation) {
+    let outcome = match operation {
+        "init-card" => lifecycle::init_card(&invocation),
+        "dismiss-card" => lifecycle::end_card(&invocation, lifecycle::CardEnd::Dismissed),
+        "expire-card" => lifecycle::end_card(&invocation, lifecycle::CardEnd::Expired),
+        "export-state" => lifecycle::export_state(&invocation),
+        "import-state" => lifecycle::import_state(&invocation),
+        "compact-state" => lifecycle::compact_state(&invocation),
+        "analyze-template" => analyze_template(&invocation),
+        "generate-sample-data" => generate_sample_data(&invocation),
+        "introspect-card" => introspect_card(&invocation),
+        "generate-card-from-schema" => forms::generate_card_from_schema(&invocation),
+        "generate-card-from-openapi" => openapi::generate_card_from_openapi(&invocation),
+        "compile-form" => forms::compile_form(&invocation),
+        "compose" => compose::compose_cards(&invocation),
+        _ => handle_invocation(invocation),
+    };
+    match outcome {
         Ok(mut result) => {
             if validation_mode != ValidationMode::Off {
                 result.validation_issues.append(&mut validation_issues);
             }
-            serde_json::to_string(&result).unwrap_or_else(|err| {
-                error_payload(
-                    "AC_INTERNAL_ERROR",
-                    "serialization error",
-                    Some(serde_json::Value::String(err.to_string())),
-                )
-            })
+            AdaptiveCardResponse::Success(Box::new(result))
         }
         Err(err) => {
             if !validation_issues.is_empty() {
-                return validation_error_payload(&validation_issues, Some(&err.to_string()));
+                return error_response(validation_failure(
+                    &validation_issues,
+                    Some(&err.to_string()),
+                ));
             }
-            error_payload_from_error(&err)
+            error_response(err.to_failure())
         }
     }
 }
 
+/// `analyze-template`: reports the data paths the template's expressions read, per element.
+fn analyze_template(
+    invocation: &AdaptiveCardInvocation,
+) -> Result<AdaptiveCardResult, ComponentError> {
+    let template = render::resolve_template(invocation)?;
+    Ok(AdaptiveCardResult {
+        card_features: render::analyze_features(&template),
+        template_analysis: Some(dependencies::analyze_template(&template)),
+        ..Default::default()
+    })
+}
+
+/// `generate-sample-data`: emits placeholder data for every path the template reads.
+fn generate_sample_data(
+    invocation: &AdaptiveCardInvocation,
+) -> Result<AdaptiveCardResult, ComponentError> {
+    let template = render::resolve_template(invocation)?;
+    Ok(AdaptiveCardResult {
+        card_features: render::analyze_features(&template),
+        sample_data: Some(sample_data::generate(&template)),
+        ..Default::default()
+    })
+}
+
+/// `introspect-card`: describes what the template collects and how its actions submit it.
+fn introspect_card(
+    invocation: &AdaptiveCardInvocation,
+) -> Result<AdaptiveCardResult, ComponentError> {
+    let template = render::resolve_template(invocation)?;
+    Ok(AdaptiveCardResult {
+        card_features: render::analyze_features(&template),
+        introspection: Some(introspect::introspect(&template)),
+        ..Default::default()
+    })
+}
+
 pub fn handle_invocation(
     mut invocation: AdaptiveCardInvocation,
 ) -> Result<AdaptiveCardResult, ComponentError> {
-    let state_loaded = state_store::load_state_if_missing(&mut invocation, None)?;
+    config::apply_config(&mut invocation);
+    let preview = invocation.preview.clone();
+    let bot_activity = invocation.bot_activity.clone();
+    let target = invocation.target.map(|target| {
+        (
+            target,
+            transcode::TranscodeContext::from_invocation(&invocation),
+        )
+    });
+    let dark = invocation.color_scheme == Some(ColorScheme::Dark);
+    let output_options = invocation.output.clone();
+    let mut result = render_or_interact(invocation)?;
+    if let Some(options) = output_options.as_ref() {
+        output::apply_output_options(&mut result, options);
+    }
+    result.card_hash = result.rendered_card.as_ref().and_then(trace::hash_value);
+    result.speak = result
+        .rendered_card
+        .as_ref()
+        .and_then(speech::extract_speech);
+    if let Some(options) = bot_activity {
+        result.activity = result
+            .rendered_card
+            .as_ref()
+            .map(|card| activity::build_activity(card, &options, result.speak.as_deref()));
+    }
+    if let Some((target, context)) = target
+        && let Some(card) = result.rendered_card.as_ref()
+    {
+        let (transcoded, issues) = transcode::transcode(card, target, &context);
+        result.transcoded = Some(transcoded);
+        result.validation_issues.extend(issues);
+    }
+    if let Some(format) = preview {
+        preview::attach_preview(&mut result, &format, dark);
+    }
+    Ok(result)
+}
+
+fn render_or_interact(
+    mut invocation: AdaptiveCardInvocation,
+) -> Result<AdaptiveCardResult, ComponentError> {
+    let persist = !matches!(
+        invocation.mode,
+        InvocationMode::Simulate | InvocationMode::Validate
+    );
+    let state_loaded = state_store::load_state_if_missing(&mut invocation, None, persist)?;
     let state_read_hash = state_loaded.as_ref().and_then(trace::hash_value);
     if let Some(interaction) = invocation.interaction.as_ref()
         && interaction.enabled == Some(false)
@@ -687,16 +1025,28 @@ pub fn handle_invocation(
         return handle_interaction(&invocation);
     }
 
+    let started = std::time::Instant::now();
+    if let Some(hydration) = invocation.hydrate.as_ref()
+        && let Some((card, rebound)) = render::hydrate_card(&invocation, hydration)?
+    {
+        return Ok(AdaptiveCardResult {
+            card_features: render::analyze_features(&card),
+            rendered_card: (invocation.mode != InvocationMode::Validate).then_some(card),
+            telemetry_events: telemetry::hydrate_event(
+                &invocation.telemetry,
+                rebound,
+                started.elapsed(),
+            )
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        });
+    }
     let rendered = render_card(&invocation)?;
     if invocation.validation_mode == ValidationMode::Error && !rendered.validation_issues.is_empty()
     {
         return Err(ComponentError::CardValidation(rendered.validation_issues));
     }
-    let rendered_card = match invocation.mode {
-        InvocationMode::Validate => None,
-        InvocationMode::Render | InvocationMode::RenderAndValidate => Some(rendered.card),
-    };
-
     let mut telemetry_events = Vec::new();
     if trace::trace_enabled() {
         let state_key = Some(state_store::state_key_for(&invocation, None));
@@ -710,6 +1060,19 @@ pub fn handle_invocation(
             None,
         ));
     }
+    telemetry_events.extend(telemetry::build_events(
+        &invocation.telemetry,
+        &rendered,
+        None,
+        None,
+        started.elapsed(),
+    ));
+    let rendered_card = match invocation.mode {
+        InvocationMode::Validate => None,
+        InvocationMode::Render | InvocationMode::RenderAndValidate | InvocationMode::Simulate => {
+            Some(rendered.card)
+        }
+    };
 
     Ok(AdaptiveCardResult {
         rendered_card,
@@ -719,6 +1082,28 @@ pub fn handle_invocation(
         card_features: rendered.features,
         validation_issues: rendered.validation_issues,
         telemetry_events,
+        subcard: rendered.subcard,
+        template_analysis: None,
+        sample_data: None,
+        card_instance_id: None,
+        simulated_state: None,
+        variants: rendered.variants,
+        preview: None,
+        speak: None,
+        dialog: None,
+        upload_request: None,
+        rendered_card_json: None,
+        card_hash: None,
+        fingerprint: rendered.fingerprint,
+        schemas: BTreeMap::new(),
+        health: None,
+        audit: None,
+        activity: None,
+        transcoded: None,
+        introspection: None,
+        schedule: rendered.schedule,
+        continuation: None,
+        state_snapshot: None,
     })
 }
 
@@ -749,8 +1134,7 @@ fn parse_invocation_value(
     value: &serde_json::Value,
 ) -> Result<AdaptiveCardInvocation, ComponentError> {
     if let Some(invocation_value) = validation::locate_invocation_candidate(value) {
-        return serde_json::from_value::<AdaptiveCardInvocation>(invocation_value)
-            .map_err(ComponentError::Serde);
+        return serde::Deserialize::deserialize(invocation_value).map_err(ComponentError::Serde);
     }
 
     if let Some(inner) = value.get("config") {
@@ -860,120 +1244,49 @@ fn merge_envelope_struct(
     inv
 }
 
-fn error_payload(code: &str, message: &str, details: Option<serde_json::Value>) -> String {
-    let mut payload = serde_json::Map::new();
-    payload.insert(
-        "code".to_string(),
-        serde_json::Value::String(code.to_string()),
-    );
-    payload.insert(
-        "message".to_string(),
-        serde_json::Value::String(message.to_string()),
-    );
-    if let Some(details) = details {
-        payload.insert("details".to_string(), details);
+fn failure(
+    code: FailureCode,
+    message: &str,
+    details: Option<serde_json::Value>,
+) -> AdaptiveCardFailure {
+    AdaptiveCardFailure {
+        code,
+        message: message.to_string(),
+        path: None,
+        retryable: false,
+        details,
     }
-    serde_json::json!({ "error": payload }).to_string()
 }
 
-fn validation_error_payload(issues: &[ValidationIssue], detail: Option<&str>) -> String {
+fn validation_failure(issues: &[ValidationIssue], detail: Option<&str>) -> AdaptiveCardFailure {
     let mut message = "invocation schema validation failed".to_string();
     if let Some(detail) = detail {
         message = format!("{message}: {detail}");
     }
     let details = serde_json::json!({ "validation_issues": issues });
-    error_payload("AC_SCHEMA_INVALID", &message, Some(details))
+    AdaptiveCardFailure {
+        path: issues.first().map(|issue| issue.path.clone()),
+        ..failure(FailureCode::SchemaInvalid, &message, Some(details))
+    }
 }
 
-fn error_payload_from_error(err: &ComponentError) -> String {
-    let issue_details = |code: &str, message: String, path: &str| {
-        serde_json::json!({
-            "validation_issues": [{
-                "code": code,
-                "message": message,
-                "path": path
-            }]
-        })
-    };
-    match err {
-        ComponentError::InvalidInput(message) => error_payload(
-            "AC_SCHEMA_INVALID",
-            "invalid input",
-            Some(issue_details("AC_SCHEMA_INVALID", message.clone(), "/")),
-        ),
-        ComponentError::Serde(inner) => error_payload(
-            "AC_SCHEMA_INVALID",
-            "invalid input",
-            Some(issue_details("AC_SCHEMA_INVALID", inner.to_string(), "/")),
-        ),
-        ComponentError::Io(inner) => error_payload(
-            "AC_SCHEMA_INVALID",
-            "io error",
-            Some(issue_details("AC_SCHEMA_INVALID", inner.to_string(), "/")),
-        ),
-        ComponentError::AssetNotFound(path) => error_payload(
-            "AC_ASSET_NOT_FOUND",
-            "asset not found",
-            Some(issue_details(
-                "AC_ASSET_NOT_FOUND",
-                path.clone(),
-                "/card_spec",
-            )),
-        ),
-        ComponentError::AssetParse(message) => error_payload(
-            "AC_ASSET_PARSE_ERROR",
-            "asset parse error",
-            Some(issue_details(
-                "AC_ASSET_PARSE_ERROR",
-                message.clone(),
-                "/card_spec",
-            )),
-        ),
-        ComponentError::Asset(message) => error_payload(
-            "AC_ASSET_NOT_FOUND",
-            "asset error",
-            Some(issue_details(
-                "AC_ASSET_NOT_FOUND",
-                message.clone(),
-                "/card_spec",
-            )),
-        ),
-        ComponentError::Binding(message) => error_payload(
-            "AC_BINDING_EVAL_ERROR",
-            "binding evaluation error",
-            Some(issue_details(
-                "AC_BINDING_EVAL_ERROR",
-                message.clone(),
-                "/card_spec/inline_json",
-            )),
-        ),
-        ComponentError::CardValidation(issues) => {
-            let details = serde_json::json!({ "validation_issues": issues });
-            error_payload(
-                "AC_CARD_VALIDATION_FAILED",
-                "card validation failed",
-                Some(details),
-            )
-        }
-        ComponentError::InteractionInvalid(message) => error_payload(
-            "AC_INTERACTION_INVALID",
-            "interaction invalid",
-            Some(issue_details(
-                "AC_INTERACTION_INVALID",
-                message.clone(),
-                "/interaction",
-            )),
-        ),
-        ComponentError::StateStore(message) => error_payload(
-            "AC_SCHEMA_INVALID",
-            "state store error",
-            Some(issue_details(
-                "AC_SCHEMA_INVALID",
-                message.clone(),
-                "/state",
-            )),
-        ),
-    }
+fn error_response(failure: AdaptiveCardFailure) -> AdaptiveCardResponse {
+    AdaptiveCardResponse::Failure { error: failure }
+}
+
+fn serialize_response(response: &AdaptiveCardResponse) -> String {
+    serde_json::to_string(response).unwrap_or_else(|err| {
+        serde_json::to_string(&serialization_failure(err)).unwrap_or_else(|_| "{}".to_string())
+    })
+}
+
+/// The response sent in place of one that failed to serialize.
+fn serialization_failure(err: serde_json::Error) -> AdaptiveCardResponse {
+    error_response(failure(
+        FailureCode::Internal,
+        "serialization error",
+        Some(serde_json::Value::String(err.to_string())),
+    ))
 }
 
 fn read_validation_mode(
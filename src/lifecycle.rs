@@ -0,0 +1,265 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde_json::{Map, Value, json};
+
+use crate::error::ComponentError;
+use crate::migrations;
+use crate::model::{
+    AdaptiveCardInvocation, AdaptiveCardResult, CardInteraction, CardSource, CardStateSnapshot,
+    StateSnapshotMetadata,
+};
+use crate::render::{analyze_features, render_card, resolve_template};
+use crate::schedule;
+use crate::state_store;
+use crate::ui_state;
+
+static INSTANCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Why a card instance is being retired.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardEnd {
+    Dismissed,
+    Expired,
+}
+
+impl CardEnd {
+    fn default_text(self) -> &'static str {
+        match self {
+            CardEnd::Dismissed => "This card has been dismissed.",
+            CardEnd::Expired => "This form has expired.",
+        }
+    }
+}
+
+/// `init-card`: assigns a card instance id (unless one is supplied), seeds its state from
+/// `state`, and renders the card for that instance.
+pub fn init_card(inv: &AdaptiveCardInvocation) -> Result<AdaptiveCardResult, ComponentError> {
+    let instance_id = instance_id(inv).unwrap_or_else(|| generate_instance_id(inv));
+    let mut seed = match &inv.state {
+        Value::Object(_) => inv.state.clone(),
+        _ => Value::Object(Map::new()),
+    };
+    migrations::stamp_version(inv, &mut seed);
+    let mut invocation = inv.clone();
+    invocation.card_instance_id = Some(instance_id.clone());
+    invocation.interaction = None;
+    state_store::persist_state(&invocation, Some(&owner(&instance_id)), &seed)?;
+    invocation.state = seed;
+
+    let rendered = render_card(&invocation)?;
+    Ok(AdaptiveCardResult {
+        rendered_card: Some(rendered.card),
+        card_features: rendered.features,
+        validation_issues: rendered.validation_issues,
+        subcard: rendered.subcard,
+        variants: rendered.variants,
+        preview: None,
+        speak: None,
+        dialog: None,
+        upload_request: None,
+        fingerprint: rendered.fingerprint,
+        card_instance_id: Some(instance_id),
+        schedule: rendered.schedule,
+        ..Default::default()
+    })
+}
+
+/// `dismiss-card` / `expire-card`: clears the instance's state and idempotency records and
+/// returns a tombstone card: `tombstone` from the invocation, for `expire-card` the
+/// template's `$schedule.onExpire` card bound against the instance's last state, or a short
+/// notice.
+pub fn end_card(
+    inv: &AdaptiveCardInvocation,
+    reason: CardEnd,
+) -> Result<AdaptiveCardResult, ComponentError> {
+    let instance_id = instance_id(inv)
+        .ok_or_else(|| ComponentError::InvalidInput("card_instance_id is required".into()))?;
+    let owner = owner(&instance_id);
+    let expired_view = match (&inv.tombstone, reason) {
+        (None, CardEnd::Expired) => render_on_expire(inv, &owner)?,
+        _ => None,
+    };
+    state_store::persist_state(inv, Some(&owner), &Value::Null)?;
+    state_store::clear_recorded_results(&owner)?;
+
+    let tombstone = inv.tombstone.clone().or(expired_view).unwrap_or_else(|| {
+        json!({
+            "type": "AdaptiveCard",
+            "version": "1.6",
+            "body": [
+                { "type": "TextBlock", "text": reason.default_text(), "wrap": true, "isSubtle": true }
+            ]
+        })
+    });
+    Ok(AdaptiveCardResult {
+        card_features: analyze_features(&tombstone),
+        rendered_card: Some(tombstone),
+        card_instance_id: Some(instance_id),
+        ..Default::default()
+    })
+}
+
+/// `export-state`: the instance's persisted state as stored (not migrated), with the state
+/// version it records and metadata for `import-state`.
+pub fn export_state(inv: &AdaptiveCardInvocation) -> Result<AdaptiveCardResult, ComponentError> {
+    let instance_id = instance_id(inv)
+        .ok_or_else(|| ComponentError::InvalidInput("card_instance_id is required".into()))?;
+    let state =
+        state_store::read_persisted_state(inv, Some(&owner(&instance_id)))?.ok_or_else(|| {
+            ComponentError::InvalidInput(format!("card instance '{instance_id}' has no state"))
+        })?;
+    let version = state
+        .get(migrations::STATE_VERSION_KEY)
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+    let snapshot = CardStateSnapshot {
+        card_instance_id: instance_id.clone(),
+        version,
+        metadata: StateSnapshotMetadata {
+            node_id: inv.node_id.clone(),
+            exported_at_ms: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            component_version: crate::COMPONENT_VERSION.to_string(),
+            hash: state_hash(&state),
+        },
+        state,
+    };
+    Ok(AdaptiveCardResult {
+        card_instance_id: Some(instance_id),
+        state_snapshot: Some(snapshot),
+        ..Default::default()
+    })
+}
+
+/// `import-state`: replaces the state of the invocation's card instance (the snapshot's
+/// instance when none is given) with the snapshot's state, recording the snapshot's version,
+/// and clears the instance's idempotency records. Snapshots whose state does not match their
+/// `hash` are rejected; state recording an older version migrates on its next load.
+pub fn import_state(inv: &AdaptiveCardInvocation) -> Result<AdaptiveCardResult, ComponentError> {
+    let mut snapshot = inv
+        .state_snapshot
+        .clone()
+        .ok_or_else(|| ComponentError::InvalidInput("state_snapshot is required".into()))?;
+    if !snapshot.metadata.hash.is_empty() && snapshot.metadata.hash != state_hash(&snapshot.state) {
+        return Err(ComponentError::InvalidInput(
+            "state_snapshot state does not match its hash".into(),
+        ));
+    }
+    let Value::Object(map) = &mut snapshot.state else {
+        return Err(ComponentError::InvalidInput(
+            "state_snapshot state must be an object".into(),
+        ));
+    };
+    map.insert(
+        migrations::STATE_VERSION_KEY.into(),
+        Value::from(snapshot.version),
+    );
+    let instance_id = instance_id(inv)
+        .or_else(|| Some(snapshot.card_instance_id.clone()))
+        .filter(|id| !id.trim().is_empty())
+        .ok_or_else(|| ComponentError::InvalidInput("card_instance_id is required".into()))?;
+    let owner = owner(&instance_id);
+    state_store::persist_state(inv, Some(&owner), &snapshot.state)?;
+    state_store::clear_recorded_results(&owner)?;
+    snapshot.card_instance_id = instance_id.clone();
+    snapshot.metadata.hash = state_hash(&snapshot.state);
+    Ok(AdaptiveCardResult {
+        card_instance_id: Some(instance_id),
+        state_snapshot: Some(snapshot),
+        ..Default::default()
+    })
+}
+
+/// `compact-state`: removes UI state referencing elements the invocation's template no longer
+/// has from the instance's persisted state, returning the removals as `state_updates`.
+pub fn compact_state(inv: &AdaptiveCardInvocation) -> Result<AdaptiveCardResult, ComponentError> {
+    let instance_id = instance_id(inv)
+        .ok_or_else(|| ComponentError::InvalidInput("card_instance_id is required".into()))?;
+    let owner = owner(&instance_id);
+    let mut invocation = inv.clone();
+    invocation.interaction = None;
+    invocation.card_instance_id = Some(instance_id.clone());
+    invocation.state = Value::Null;
+    state_store::load_state_if_missing(&mut invocation, Some(&owner), true)?;
+    if invocation.state.is_null() {
+        return Err(ComponentError::InvalidInput(format!(
+            "card instance '{instance_id}' has no state"
+        )));
+    }
+    let template = resolve_template(&invocation)?;
+    // Bound ids only exist in the rendered card; templates that cannot render without their
+    // payload are checked against their literal ids.
+    let rendered = render_card(&invocation).map(|rendered| rendered.card).ok();
+    let ids = ui_state::element_ids([Some(&template), rendered.as_ref()].into_iter().flatten());
+    let state_updates = ui_state::orphaned_ui_state(&invocation.state, &ids);
+    if !state_updates.is_empty() {
+        let mut state = invocation.state.clone();
+        state_store::apply_updates(&mut state, &state_updates);
+        state_store::persist_state(&invocation, Some(&owner), &state)?;
+    }
+    Ok(AdaptiveCardResult {
+        card_instance_id: Some(instance_id),
+        state_updates,
+        ..Default::default()
+    })
+}
+
+fn state_hash(state: &Value) -> String {
+    blake3::hash(&serde_json::to_vec(state).unwrap_or_default())
+        .to_hex()
+        .to_string()
+}
+
+/// The template's `onExpire` card rendered for the instance, when the invocation's card
+/// source resolves and declares one.
+fn render_on_expire(
+    inv: &AdaptiveCardInvocation,
+    owner: &CardInteraction,
+) -> Result<Option<Value>, ComponentError> {
+    let Some(view) = resolve_template(inv)
+        .ok()
+        .and_then(|template| schedule::on_expire_view(&template))
+    else {
+        return Ok(None);
+    };
+    let mut invocation = inv.clone();
+    invocation.interaction = None;
+    state_store::load_state_if_missing(&mut invocation, Some(owner), false)?;
+    invocation.card_source = CardSource::Inline;
+    invocation.card_spec.inline_json = Some(view);
+    Ok(Some(render_card(&invocation)?.card))
+}
+
+fn instance_id(inv: &AdaptiveCardInvocation) -> Option<String> {
+    inv.card_instance_id
+        .clone()
+        .or_else(|| inv.interaction.as_ref().map(|i| i.card_instance_id.clone()))
+        .filter(|id| !id.trim().is_empty())
+}
+
+/// Stand-in interaction so lifecycle ops address the same state key as interactions do.
+fn owner(instance_id: &str) -> CardInteraction {
+    CardInteraction {
+        card_instance_id: instance_id.to_string(),
+        ..Default::default()
+    }
+}
+
+fn generate_instance_id(inv: &AdaptiveCardInvocation) -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(inv.node_id.as_deref().unwrap_or_default().as_bytes());
+    hasher.update(&serde_json::to_vec(&inv.card_spec).unwrap_or_default());
+    hasher.update(&nanos.to_le_bytes());
+    hasher.update(
+        &INSTANCE_COUNTER
+            .fetch_add(1, Ordering::Relaxed)
+            .to_le_bytes(),
+    );
+    format!("card-{}", &hasher.finalize().to_hex()[..16])
+}
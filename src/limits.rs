@@ -0,0 +1,134 @@
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::error::ComponentError;
+use crate::model::{RenderLimits, TimeBudget, TimeBudgetPolicy};
+
+pub const DEFAULT_MAX_NESTING: usize = 32;
+pub const DEFAULT_MAX_EXPRESSION_LENGTH: usize = 1024;
+pub const DEFAULT_MAX_EXPRESSION_NODES: usize = 128;
+pub const DEFAULT_MAX_REPEAT: usize = 1000;
+
+/// [`RenderLimits`] with the defaults filled in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    pub max_nesting: usize,
+    pub max_expression_length: usize,
+    pub max_expression_nodes: usize,
+    pub max_repeat: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_nesting: DEFAULT_MAX_NESTING,
+            max_expression_length: DEFAULT_MAX_EXPRESSION_LENGTH,
+            max_expression_nodes: DEFAULT_MAX_EXPRESSION_NODES,
+            max_repeat: DEFAULT_MAX_REPEAT,
+        }
+    }
+}
+
+impl Limits {
+    pub fn resolve(limits: Option<&RenderLimits>) -> Self {
+        let defaults = Limits::default();
+        let Some(limits) = limits else {
+            return defaults;
+        };
+        Limits {
+            max_nesting: limits.max_nesting.unwrap_or(defaults.max_nesting),
+            max_expression_length: limits
+                .max_expression_length
+                .unwrap_or(defaults.max_expression_length),
+            max_expression_nodes: limits
+                .max_expression_nodes
+                .unwrap_or(defaults.max_expression_nodes),
+            max_repeat: limits.max_repeat.unwrap_or(defaults.max_repeat),
+        }
+    }
+}
+
+/// A started `time_budget`, checked by the renderer at element boundaries.
+#[derive(Clone, Copy, Debug)]
+pub struct Deadline {
+    started: Instant,
+    budget: Duration,
+    pub policy: TimeBudgetPolicy,
+}
+
+impl Deadline {
+    pub fn start(budget: Option<&TimeBudget>) -> Option<Self> {
+        budget.map(|budget| Deadline {
+            started: Instant::now(),
+            budget: Duration::from_millis(budget.max_ms),
+            policy: budget.on_exceeded,
+        })
+    }
+
+    pub fn expired(&self) -> bool {
+        self.started.elapsed() >= self.budget
+    }
+
+    pub fn error(&self) -> ComponentError {
+        ComponentError::Timeout {
+            budget_ms: self.budget.as_millis() as u64,
+        }
+    }
+}
+
+/// Fails when elements nest deeper than `max` (a top-level body element has depth 1). The
+/// walk uses an explicit stack, so it is safe to run on cards that would overflow the
+/// recursive passes it guards.
+pub fn check_nesting(card: &Value, max: usize) -> Result<(), ComponentError> {
+    if too_deep(card, max, false).is_none() {
+        return Ok(());
+    }
+    // Pointers are only built once the card is known to be too deep.
+    let (depth, path) = too_deep(card, max, true).unwrap_or_default();
+    Err(ComponentError::LimitExceeded {
+        limit: "maxNesting",
+        max,
+        detail: format!("elements nest {depth} deep at {path}"),
+    })
+}
+
+fn too_deep(card: &Value, max: usize, track: bool) -> Option<(usize, String)> {
+    let child_path = |path: &str, key: &dyn std::fmt::Display| {
+        if track {
+            format!("{path}/{key}")
+        } else {
+            String::new()
+        }
+    };
+    let mut stack = vec![(card, String::new(), 0usize)];
+    while let Some((value, path, depth)) = stack.pop() {
+        match value {
+            Value::Object(map) => {
+                let depth = depth + usize::from(is_element(value));
+                if depth > max {
+                    return Some((depth, path));
+                }
+                for (key, child) in map {
+                    if key != "data" {
+                        stack.push((child, child_path(&path, key), depth));
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    stack.push((item, child_path(&path, &idx), depth));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_element(value: &Value) -> bool {
+    value
+        .get("type")
+        .and_then(Value::as_str)
+        .is_some_and(|kind| !kind.starts_with("Action.") && kind != "AdaptiveCard")
+}
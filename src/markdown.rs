@@ -0,0 +1,500 @@
+use serde_json::{Map, Value, json};
+
+use crate::model::MarkdownMode;
+
+/// Inline span produced by the Adaptive Cards Markdown subset parser.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub link: Option<String>,
+}
+
+/// Applies the configured Markdown processing to TextBlock text.
+pub fn apply_markdown(card: &mut Value, mode: &MarkdownMode) {
+    if *mode == MarkdownMode::Off {
+        return;
+    }
+    visit(card, mode);
+}
+
+fn visit(value: &mut Value, mode: &MarkdownMode) {
+    match value {
+        Value::Object(map) => {
+            match map.get("type").and_then(|v| v.as_str()) {
+                Some("TextBlock") => {
+                    if let Some(text) = map.get("text").and_then(|v| v.as_str()) {
+                        let lines = parse_blocks(text);
+                        match mode {
+                            MarkdownMode::TextRuns => {
+                                *map = text_block_to_rich(map, &lines);
+                                return;
+                            }
+                            _ => {
+                                map.insert("text".into(), Value::String(render_markdown(&lines)));
+                            }
+                        }
+                    }
+                }
+                // TextRun text is never read as Markdown, so it is left as authored.
+                Some("RichTextBlock") => return,
+                _ => {}
+            }
+            for child in map.values_mut() {
+                visit(child, mode);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                visit(item, mode);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LineKind {
+    Paragraph,
+    Bullet,
+    Numbered(u64),
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    kind: LineKind,
+    spans: Vec<Span>,
+}
+
+fn parse_blocks(text: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    for raw in strip_html(text).lines() {
+        let mut line = raw.trim();
+        if line.starts_with("```") || is_rule(line) {
+            continue;
+        }
+        // Headings and blockquotes are not part of the supported subset.
+        line = strip_heading(line);
+        while let Some(rest) = line.strip_prefix('>') {
+            line = rest.trim_start();
+        }
+        let (kind, content) = if let Some(rest) = line
+            .strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .or_else(|| line.strip_prefix("+ "))
+        {
+            (LineKind::Bullet, rest)
+        } else if let Some((num, rest)) = line.split_once(". ")
+            && let Ok(n) = num.parse::<u64>()
+        {
+            (LineKind::Numbered(n), rest)
+        } else {
+            (LineKind::Paragraph, line)
+        };
+        lines.push(Line {
+            kind,
+            spans: parse_inline(content),
+        });
+    }
+    while lines
+        .last()
+        .map(|l| l.kind == LineKind::Paragraph && l.spans.is_empty())
+        .unwrap_or(false)
+    {
+        lines.pop();
+    }
+    lines
+}
+
+/// Drops an ATX heading marker: one to six `#` followed by whitespace or the end of the line,
+/// so `#tag` stays as written.
+fn strip_heading(line: &str) -> &str {
+    let rest = line.trim_start_matches('#');
+    let level = line.len() - rest.len();
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        rest.trim_start()
+    } else {
+        line
+    }
+}
+
+fn is_rule(line: &str) -> bool {
+    line.len() >= 3 && (line.chars().all(|c| c == '-') || line.chars().all(|c| c == '*'))
+}
+
+/// Parses bold, italics, and links; images collapse to their alt text and inline code,
+/// strikethrough, and unsafe link targets are reduced to plain text. A backslash escapes ASCII
+/// punctuation, and an emphasis marker without a matching closer stays literal text.
+pub fn parse_inline(text: &str) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut current = Span::default();
+    let chars: Vec<char> = text.chars().collect();
+    // The marker character that opened the current bold or italic run.
+    let mut bold: Option<char> = None;
+    let mut italic: Option<char> = None;
+    let mut idx = 0;
+
+    let flush = |current: &mut Span, spans: &mut Vec<Span>, bold: bool, italic: bool| {
+        if !current.text.is_empty() {
+            spans.push(std::mem::take(current));
+        }
+        current.bold = bold;
+        current.italic = italic;
+    };
+
+    while idx < chars.len() {
+        let ch = chars[idx];
+        if ch == '\\'
+            && let Some(next) = chars.get(idx + 1).filter(|c| c.is_ascii_punctuation())
+        {
+            current.text.push(*next);
+            idx += 2;
+            continue;
+        }
+        if ch == '*' || ch == '_' {
+            let len = if chars.get(idx + 1) == Some(&ch) {
+                2
+            } else {
+                1
+            };
+            let open = if len == 2 { &mut bold } else { &mut italic };
+            let toggles = match *open {
+                Some(marker) => marker == ch && can_close(&chars, idx, len),
+                None => can_open(&chars, idx, len) && has_closer(&chars, idx + len, ch, len),
+            };
+            if toggles {
+                *open = if open.is_some() { None } else { Some(ch) };
+                flush(&mut current, &mut spans, bold.is_some(), italic.is_some());
+                idx += len;
+                continue;
+            }
+            current.text.extend(&chars[idx..idx + len]);
+            idx += len;
+            continue;
+        }
+        if ch == '~' && chars.get(idx + 1) == Some(&'~') {
+            idx += 2;
+            continue;
+        }
+        if ch == '`' {
+            idx += 1;
+            continue;
+        }
+        let is_image = ch == '!' && chars.get(idx + 1) == Some(&'[');
+        if (ch == '[' || is_image)
+            && let Some((label, url, next)) =
+                parse_link(&chars, if is_image { idx + 1 } else { idx })
+        {
+            flush(&mut current, &mut spans, bold.is_some(), italic.is_some());
+            let link = (!is_image && is_safe_link(&url)).then_some(url);
+            spans.push(Span {
+                text: label,
+                bold: bold.is_some(),
+                italic: italic.is_some(),
+                link,
+            });
+            idx = next;
+            continue;
+        }
+        current.text.push(ch);
+        idx += 1;
+    }
+    flush(&mut current, &mut spans, bold.is_some(), italic.is_some());
+    spans
+}
+
+fn is_space(chars: &[char], idx: Option<usize>) -> bool {
+    idx.and_then(|i| chars.get(i))
+        .is_none_or(|c| c.is_whitespace())
+}
+
+fn is_alphanumeric(chars: &[char], idx: Option<usize>) -> bool {
+    idx.and_then(|i| chars.get(i))
+        .is_some_and(|c| c.is_alphanumeric())
+}
+
+/// A marker run opens emphasis when text follows it directly; `_` must not follow a word.
+fn can_open(chars: &[char], idx: usize, len: usize) -> bool {
+    let after_word = chars[idx] == '_' && is_alphanumeric(chars, idx.checked_sub(1));
+    !is_space(chars, Some(idx + len)) && !after_word
+}
+
+/// A marker run closes emphasis when it directly follows text; `_` must not precede a word.
+fn can_close(chars: &[char], idx: usize, len: usize) -> bool {
+    let before_word = chars[idx] == '_' && is_alphanumeric(chars, Some(idx + len));
+    !is_space(chars, idx.checked_sub(1)) && !before_word
+}
+
+/// Looks ahead for an unescaped run of exactly `len` markers that can close emphasis.
+fn has_closer(chars: &[char], from: usize, marker: char, len: usize) -> bool {
+    let mut idx = from;
+    while idx < chars.len() {
+        if chars[idx] == '\\' {
+            idx += 2;
+            continue;
+        }
+        if chars[idx] != marker {
+            idx += 1;
+            continue;
+        }
+        let run = chars[idx..].iter().take_while(|c| **c == marker).count();
+        if run == len && idx > from && can_close(chars, idx, len) {
+            return true;
+        }
+        idx += run;
+    }
+    false
+}
+
+fn parse_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let close = (start + 1..chars.len()).find(|&i| chars[i] == ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let mut depth = 0usize;
+    let end = (close + 2..chars.len()).find(|&i| match chars[i] {
+        '(' => {
+            depth += 1;
+            false
+        }
+        ')' if depth > 0 => {
+            depth -= 1;
+            false
+        }
+        ')' => true,
+        _ => false,
+    })?;
+    let mut label = String::new();
+    let mut escaped = false;
+    for ch in &chars[start + 1..close] {
+        escaped = !escaped && *ch == '\\';
+        if !escaped {
+            label.push(*ch);
+        }
+    }
+    let url: String = chars[close + 2..end].iter().collect();
+    Some((label, url.trim().to_string(), end + 1))
+}
+
+fn is_safe_link(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    lower.starts_with("https://") || lower.starts_with("http://") || lower.starts_with("mailto:")
+}
+
+fn strip_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let opens_tag = ch == '<'
+            && chars
+                .peek()
+                .map(|c| c.is_ascii_alphabetic() || *c == '/' || *c == '!')
+                .unwrap_or(false);
+        match ch {
+            '<' if opens_tag => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(ch),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Backslash-escapes the characters in literal text that would otherwise be read back as
+/// Markdown syntax. Emphasis markers between spaces, or `_` inside a word, cannot open or close
+/// emphasis and are kept as written.
+fn escape_markdown(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for (idx, ch) in chars.iter().enumerate() {
+        let before = idx.checked_sub(1);
+        let after = Some(idx + 1);
+        let escape = match ch {
+            '\\' | '[' | ']' | '`' => true,
+            '~' => chars.get(idx + 1) == Some(&'~') || before.is_some_and(|i| chars[i] == '~'),
+            '*' | '_' => {
+                let spaced = is_space(&chars, before) && is_space(&chars, after);
+                let intra_word =
+                    *ch == '_' && is_alphanumeric(&chars, before) && is_alphanumeric(&chars, after);
+                !spaced && !intra_word
+            }
+            _ => false,
+        };
+        if escape {
+            out.push('\\');
+        }
+        out.push(*ch);
+    }
+    out
+}
+
+/// Escapes a paragraph that would otherwise start a heading, quote, or list item.
+fn escape_line_start(body: String) -> String {
+    let numbered = body
+        .split_once(". ")
+        .is_some_and(|(num, _)| num.parse::<u64>().is_ok());
+    if numbered {
+        return body.replacen(". ", "\\. ", 1);
+    }
+    let block = strip_heading(&body) != body
+        || body.starts_with('>')
+        || ["- ", "* ", "+ "]
+            .iter()
+            .any(|prefix| body.starts_with(prefix));
+    if block { format!("\\{body}") } else { body }
+}
+
+fn render_spans(spans: &[Span]) -> String {
+    let mut out = String::new();
+    for span in spans {
+        let mut text = escape_markdown(&span.text);
+        if let Some(link) = &span.link {
+            text = format!("[{text}]({link})");
+        }
+        if span.italic && !span.text.trim().is_empty() {
+            text = format!("_{text}_");
+        }
+        if span.bold && !span.text.trim().is_empty() {
+            text = format!("**{text}**");
+        }
+        out.push_str(&text);
+    }
+    out
+}
+
+fn render_markdown(lines: &[Line]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            let body = render_spans(&line.spans);
+            match line.kind {
+                LineKind::Paragraph => escape_line_start(body),
+                LineKind::Bullet => format!("- {body}"),
+                LineKind::Numbered(n) => format!("{n}. {body}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Removes all Markdown syntax, keeping only the visible text.
+pub fn plain_text(text: &str) -> String {
+    parse_blocks(text)
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|s| s.text.as_str())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the Markdown subset as inline HTML for email bodies: emphasis as `<strong>` and
+/// `<em>`, safe links as `<a>`, and lines (list items keep a bullet or number) separated by
+/// `<br>`. All text is escaped.
+pub fn to_html(text: &str) -> String {
+    parse_blocks(text)
+        .iter()
+        .map(|line| {
+            let mut out = match line.kind {
+                LineKind::Paragraph => String::new(),
+                LineKind::Bullet => "&bull; ".to_string(),
+                LineKind::Numbered(n) => format!("{n}. "),
+            };
+            for span in &line.spans {
+                let mut html = escape_html(&span.text);
+                if span.italic && !span.text.trim().is_empty() {
+                    html = format!("<em>{html}</em>");
+                }
+                if span.bold && !span.text.trim().is_empty() {
+                    html = format!("<strong>{html}</strong>");
+                }
+                if let Some(link) = &span.link {
+                    html = format!("<a href=\"{}\">{html}</a>", escape_html(link));
+                }
+                out.push_str(&html);
+            }
+            out
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+/// Escapes text for HTML content and double-quoted attribute values.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn text_block_to_rich(block: &Map<String, Value>, lines: &[Line]) -> Map<String, Value> {
+    let mut inlines = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let prefix = match line.kind {
+            LineKind::Paragraph => String::new(),
+            LineKind::Bullet => "• ".to_string(),
+            LineKind::Numbered(n) => format!("{n}. "),
+        };
+        if !prefix.is_empty() {
+            inlines.push(json!({ "type": "TextRun", "text": prefix }));
+        }
+        for span in &line.spans {
+            let mut run = Map::new();
+            run.insert("type".into(), Value::String("TextRun".into()));
+            run.insert("text".into(), Value::String(span.text.clone()));
+            if span.bold {
+                run.insert("weight".into(), Value::String("Bolder".into()));
+            }
+            if span.italic {
+                run.insert("italic".into(), Value::Bool(true));
+            }
+            if let Some(url) = &span.link {
+                run.insert(
+                    "selectAction".into(),
+                    json!({ "type": "Action.OpenUrl", "url": url }),
+                );
+            }
+            inlines.push(Value::Object(run));
+        }
+        if idx + 1 < lines.len() {
+            inlines.push(json!({ "type": "TextRun", "text": "\n" }));
+        }
+    }
+
+    let mut rich = Map::new();
+    rich.insert("type".into(), Value::String("RichTextBlock".into()));
+    for key in [
+        "id",
+        "spacing",
+        "separator",
+        "horizontalAlignment",
+        "isVisible",
+        "height",
+        "fallback",
+        "requires",
+    ] {
+        if let Some(value) = block.get(key) {
+            rich.insert(key.into(), value.clone());
+        }
+    }
+    // Text styling on the block is pushed down onto each run.
+    for run in inlines.iter_mut() {
+        if let Value::Object(run) = run {
+            for key in ["size", "color", "isSubtle", "fontType"] {
+                if let Some(value) = block.get(key) {
+                    run.entry(key.to_string()).or_insert(value.clone());
+                }
+            }
+            if let Some(weight) = block.get("weight") {
+                run.entry("weight".to_string()).or_insert(weight.clone());
+            }
+        }
+    }
+    rich.insert("inlines".into(), Value::Array(inlines));
+    rich
+}
@@ -0,0 +1,212 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::ComponentError;
+use crate::model::{AdaptiveCardInvocation, StateUpdateOp};
+use crate::render;
+use crate::state_store;
+
+/// Card root key declaring the state version the template expects: `{name?, version,
+/// migrations?}`.
+pub const STATE_SPEC_KEY: &str = "$state";
+/// State key recording the version of the template that last wrote the state.
+pub const STATE_VERSION_KEY: &str = "$stateVersion";
+
+/// Upgrades persisted state by one version, e.g. renaming a field the new template reads
+/// under another path.
+pub trait StateMigration: Send + Sync {
+    fn migrate(&self, state: &mut Value) -> Result<(), String>;
+}
+
+type StateMigrationFn = dyn Fn(&mut Value) -> Result<(), String> + Send + Sync;
+
+pub struct CallbackStateMigration {
+    callback: Box<StateMigrationFn>,
+}
+
+impl CallbackStateMigration {
+    pub fn new(callback: Box<StateMigrationFn>) -> Self {
+        Self { callback }
+    }
+}
+
+impl StateMigration for CallbackStateMigration {
+    fn migrate(&self, state: &mut Value) -> Result<(), String> {
+        (self.callback)(state)
+    }
+}
+
+type Migrations = BTreeMap<(String, u64), Arc<dyn StateMigration>>;
+
+static STATE_MIGRATIONS: OnceCell<RwLock<Migrations>> = OnceCell::new();
+
+fn migrations_cell() -> &'static RwLock<Migrations> {
+    STATE_MIGRATIONS.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Registers the migration from `from_version` to the next version of the state of
+/// templates whose `$state.name` is `name`, replacing any earlier one. It takes precedence
+/// over the template's own `migrations` for that version.
+pub fn register_state_migration(
+    name: &str,
+    from_version: u64,
+    migration: Box<dyn StateMigration>,
+) -> Result<(), &'static str> {
+    migrations_cell()
+        .write()
+        .map_err(|_| "state migration registry poisoned")?
+        .insert((name.to_string(), from_version), Arc::from(migration));
+    Ok(())
+}
+
+pub fn register_state_migration_callback(
+    name: &str,
+    from_version: u64,
+    callback: Box<StateMigrationFn>,
+) -> Result<(), &'static str> {
+    register_state_migration(
+        name,
+        from_version,
+        Box::new(CallbackStateMigration::new(callback)),
+    )
+}
+
+/// One step of a template's declarative migration: a `StateUpdateOp` or a `Rename`.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+enum MigrationStep {
+    Rename {
+        from: String,
+        to: String,
+    },
+    #[serde(untagged)]
+    Update(StateUpdateOp),
+}
+
+#[derive(Deserialize)]
+struct StateSpec {
+    #[serde(default)]
+    name: Option<String>,
+    version: u64,
+    #[serde(default)]
+    migrations: BTreeMap<u64, Vec<MigrationStep>>,
+}
+
+/// Removes `$state` from the card root so it is not delivered with the card.
+pub fn take_state_spec(card: &mut Value) {
+    if let Some(root) = card.as_object_mut() {
+        root.remove(STATE_SPEC_KEY);
+    }
+}
+
+/// The `$state` the invocation's template declares, if it resolves and declares one.
+fn state_spec(inv: &AdaptiveCardInvocation) -> Option<StateSpec> {
+    let template = render::resolve_template(inv).ok()?;
+    serde_json::from_value(template.get(STATE_SPEC_KEY)?.clone()).ok()
+}
+
+/// Brings state loaded for `inv` up to the version its template declares, one version at a
+/// time: through the host migration registered for the template's name and version, else the
+/// template's `migrations` entry for that version. Versions without either are skipped.
+/// State without a recorded version counts as version 1. Returns whether the state changed;
+/// the state always ends up recording the template's version.
+pub fn migrate_state(
+    inv: &AdaptiveCardInvocation,
+    state: &mut Value,
+) -> Result<bool, ComponentError> {
+    let Some(spec) = state_spec(inv) else {
+        return Ok(false);
+    };
+    let Value::Object(map) = state else {
+        return Ok(false);
+    };
+    let stored = map
+        .get(STATE_VERSION_KEY)
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+    if stored >= spec.version {
+        map.entry(STATE_VERSION_KEY)
+            .or_insert_with(|| Value::from(spec.version));
+        return Ok(false);
+    }
+    // Only versions with a migration are visited, so a large version jump costs nothing.
+    let registered: BTreeMap<u64, Arc<dyn StateMigration>> =
+        match (&spec.name, migrations_cell().read()) {
+            (Some(name), Ok(registered)) => registered
+                .range((name.clone(), stored)..(name.clone(), spec.version))
+                .map(|((_, version), migration)| (*version, Arc::clone(migration)))
+                .collect(),
+            _ => BTreeMap::new(),
+        };
+    let versions: BTreeSet<u64> = registered
+        .keys()
+        .chain(
+            spec.migrations
+                .range(stored..spec.version)
+                .map(|(version, _)| version),
+        )
+        .copied()
+        .collect();
+    for version in versions {
+        if let Some(migration) = registered.get(&version) {
+            migration.migrate(state).map_err(|err| {
+                ComponentError::StateStore(format!(
+                    "state migration from version {version} failed: {err}"
+                ))
+            })?;
+        } else if let Some(steps) = spec.migrations.get(&version) {
+            for step in steps {
+                apply_step(state, step);
+            }
+        }
+    }
+    state_store::apply_updates(
+        state,
+        &[StateUpdateOp::Set {
+            path: STATE_VERSION_KEY.into(),
+            value: Value::from(spec.version),
+        }],
+    );
+    Ok(true)
+}
+
+/// Records the template's state version in state written without one, such as the first
+/// state of a card instance.
+pub fn stamp_version(inv: &AdaptiveCardInvocation, state: &mut Value) {
+    if let Some(spec) = state_spec(inv)
+        && let Value::Object(map) = state
+    {
+        map.entry(STATE_VERSION_KEY)
+            .or_insert_with(|| Value::from(spec.version));
+    }
+}
+
+fn apply_step(state: &mut Value, step: &MigrationStep) {
+    match step {
+        MigrationStep::Rename { from, to } => {
+            let moved = from
+                .split('.')
+                .try_fold(&*state, |node, key| node.get(key))
+                .cloned();
+            if let Some(value) = moved {
+                state_store::apply_updates(
+                    state,
+                    &[
+                        StateUpdateOp::Delete { path: from.clone() },
+                        StateUpdateOp::Set {
+                            path: to.clone(),
+                            value,
+                        },
+                    ],
+                );
+            }
+        }
+        MigrationStep::Update(update) => {
+            state_store::apply_updates(state, std::slice::from_ref(update))
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use greentic_types::InvocationEnvelope;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum CardSource {
     #[default]
@@ -11,7 +12,7 @@ pub enum CardSource {
     Catalog,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct CardSpec {
     pub inline_json: Option<Value>,
     pub asset_path: Option<String>,
@@ -20,16 +21,19 @@ pub struct CardSpec {
     pub asset_registry: Option<std::collections::BTreeMap<String, String>>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
 #[serde(rename_all = "camelCase")]
 pub enum InvocationMode {
     Render,
     Validate,
     #[default]
     RenderAndValidate,
+    /// Dry run: interactions compute the event, state updates, and re-rendered card but
+    /// nothing is persisted.
+    Simulate,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ValidationMode {
     Off,
@@ -38,7 +42,188 @@ pub enum ValidationMode {
     Error,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+/// Opt-in Markdown processing for TextBlock/RichTextBlock text.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum MarkdownMode {
+    /// Text passes through untouched.
+    #[default]
+    Off,
+    /// Parse the Adaptive Cards subset and re-emit sanitized Markdown.
+    Normalize,
+    /// Convert TextBlocks into RichTextBlocks made of TextRun inlines.
+    #[serde(alias = "text_runs")]
+    TextRuns,
+}
+
+/// Verbosity of `telemetry_events`. Trace events (`GREENTIC_TRACE`) are independent of this.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryLevel {
+    Off,
+    /// Render and interaction summaries.
+    #[default]
+    Summary,
+    /// Adds binding counters and per-code validation counts.
+    Verbose,
+}
+
+/// Client color scheme a card is rendered for.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScheme {
+    Light,
+    Dark,
+    /// Render a light and a dark variant; the light one is the primary card.
+    Both,
+}
+
+/// How an expanded Action.ShowCard is returned on re-render.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ShowCardMode {
+    /// Expand the active subcard inline, after the action that owns it.
+    #[default]
+    Inline,
+    /// Leave the card untouched and return the subcard in `AdaptiveCardResult::subcard`.
+    Separate,
+}
+
+/// Capabilities of the host a card is rendered for; drives `fallback` handling.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetProfile {
+    /// Highest Adaptive Cards schema version the host renders (e.g. `"1.3"`).
+    #[serde(default, alias = "max_version")]
+    pub max_version: Option<String>,
+    /// Types the host cannot render even though its version covers them.
+    #[serde(default)]
+    pub unsupported: Vec<String>,
+    /// Host-specific types, beyond the built-in registry, that the host renders.
+    #[serde(default, alias = "custom_elements")]
+    pub custom_elements: Vec<String>,
+    /// Host features and their versions, checked against element `requires`. Merged over
+    /// features registered with `register_host_feature`.
+    #[serde(default)]
+    pub features: std::collections::BTreeMap<String, String>,
+    /// Platform whose URL formats `deepLink` builds; `web` when unset.
+    #[serde(default)]
+    pub host: Option<HostPlatform>,
+    /// App id used in Teams dialog and tab deep links.
+    #[serde(default, alias = "app_id")]
+    pub app_id: Option<String>,
+    /// Web app origin that `route` deep links are resolved against.
+    #[serde(default, alias = "base_url")]
+    pub base_url: Option<String>,
+    /// Outlook Actionable Messages provider id, set as the card's `originator`.
+    #[serde(default)]
+    pub originator: Option<String>,
+    /// HTTPS endpoint that Outlook `Action.Http` POSTs (mapped from Submit and Execute) go to.
+    #[serde(default, alias = "action_url")]
+    pub action_url: Option<String>,
+}
+
+/// Host platform a card is rendered for, and whose URL formats its deep links use.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HostPlatform {
+    /// Microsoft Teams `https://teams.microsoft.com/l/...` links.
+    Teams,
+    /// Routes under the profile's `base_url`.
+    #[default]
+    Web,
+    /// Outlook Actionable Messages: schema 1.4, `originator`, and `Action.Http` posts.
+    Outlook,
+}
+
+/// Which actions are disabled after a Submit/Execute click.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DisableActions {
+    /// Only the clicked action.
+    Clicked,
+    /// Every action on the card.
+    All,
+}
+
+/// Image format of the server-side card preview.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewFormat {
+    /// SVG markup (`preview` feature).
+    Svg,
+    /// Base64-encoded PNG rasterized from the SVG (`preview-png` feature).
+    Png,
+}
+
+/// Incremental re-render: only strings whose expressions read one of `changed_paths` are
+/// re-bound and patched into `previous_card`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HydrationRequest {
+    /// Card returned by the previous full render of the same template.
+    #[serde(alias = "previous_card")]
+    pub previous_card: Value,
+    /// Changed data paths, e.g. `payload.metrics.cpu`.
+    #[serde(default)]
+    #[serde(alias = "changed_paths")]
+    pub changed_paths: Vec<String>,
+}
+
+/// Declarative interaction routing entry. `verb` and `action_id` accept `*`/`?` wildcards and
+/// `when` is an expression over payload/session/state/params and the submitted `inputs`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteRule {
+    #[serde(default)]
+    pub verb: Option<String>,
+    #[serde(default)]
+    #[serde(alias = "action_id")]
+    pub action_id: Option<String>,
+    #[serde(default)]
+    pub when: Option<String>,
+    /// Route emitted as `SessionUpdateOp::SetRoute` and on the event.
+    #[serde(default)]
+    pub route: Option<String>,
+    /// Flow node the event should be delivered to.
+    #[serde(default)]
+    pub node: Option<String>,
+    /// Operation to invoke on the target node.
+    #[serde(default)]
+    pub op: Option<String>,
+}
+
+/// Guardrails for partner-authored cards. Empty lists place no restriction.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityPolicy {
+    /// Action types (e.g. `Action.Submit`) that may appear on the card.
+    #[serde(default)]
+    #[serde(alias = "allowed_action_types")]
+    pub allowed_action_types: Vec<String>,
+    /// URL schemes accepted for `Action.OpenUrl` targets.
+    #[serde(default)]
+    #[serde(alias = "allowed_url_schemes")]
+    pub allowed_url_schemes: Vec<String>,
+    /// Domains (and their subdomains) accepted for `Action.OpenUrl` targets.
+    #[serde(default)]
+    #[serde(alias = "allowed_url_domains")]
+    pub allowed_url_domains: Vec<String>,
+    /// Maximum number of actions kept per card, counted in document order.
+    #[serde(default)]
+    #[serde(alias = "max_actions")]
+    pub max_actions: Option<usize>,
+    /// Largest accepted file upload in bytes, on top of each `Input.File`'s `maxSize`.
+    #[serde(default)]
+    #[serde(alias = "max_upload_bytes")]
+    pub max_upload_bytes: Option<u64>,
+    /// Content types (`image/*`) or extensions (`.pdf`) accepted for file uploads.
+    #[serde(default)]
+    #[serde(alias = "allowed_upload_types")]
+    pub allowed_upload_types: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AdaptiveCardInvocation {
     #[serde(default)]
@@ -71,10 +256,375 @@ pub struct AdaptiveCardInvocation {
 
     /// Optional shared invocation envelope metadata from the host.
     #[serde(default)]
+    #[schemars(with = "Option<Value>")]
     pub envelope: Option<InvocationEnvelope>,
+
+    #[serde(default)]
+    pub markdown: MarkdownMode,
+
+    /// Strip script-capable markup, executable URLs, and control characters after binding.
+    #[serde(default)]
+    pub sanitize: bool,
+
+    /// Give every element and action authored without an `id` a generated one, not only the
+    /// actions that need one to be routed.
+    #[serde(default)]
+    #[serde(alias = "generate_ids")]
+    pub generate_ids: bool,
+
+    /// Card instance being rendered; defaults to the interaction's instance id.
+    #[serde(default)]
+    #[serde(alias = "card_instance_id")]
+    pub card_instance_id: Option<String>,
+
+    #[serde(default)]
+    #[serde(alias = "show_card_mode")]
+    pub show_card_mode: ShowCardMode,
+
+    #[serde(default)]
+    pub security: Option<SecurityPolicy>,
+
+    #[serde(default)]
+    pub audit: Vec<AuditCheck>,
+
+    #[serde(default)]
+    pub budget: Option<CardBudget>,
+
+    /// Hard limits on template nesting, expression size, and repeater expansion. Exceeding one
+    /// fails the invocation with `AC_LIMIT_EXCEEDED`; unset limits use the defaults.
+    #[serde(default)]
+    pub limits: Option<RenderLimits>,
+
+    /// Wall-clock budget for the render, checked at element boundaries.
+    #[serde(default, alias = "time_budget")]
+    pub time_budget: Option<TimeBudget>,
+
+    /// BCP 47 locale for `@loc(key)` / `localize()` lookups (falls back to `en`).
+    #[serde(default)]
+    pub locale: Option<String>,
+
+    /// Inline string catalogs keyed by locale, then by string key.
+    #[serde(default)]
+    #[serde(alias = "string_catalogs")]
+    pub string_catalogs:
+        std::collections::BTreeMap<String, std::collections::BTreeMap<String, String>>,
+
+    /// Asset path of a JSON file shaped like `string_catalogs`; inline catalogs take precedence.
+    #[serde(default)]
+    #[serde(alias = "strings_asset")]
+    pub strings_asset: Option<String>,
+
+    /// Theme overrides merged over the built-in theme for `@theme.<path>` tokens.
+    #[serde(default)]
+    pub theme: Option<Value>,
+
+    /// Per-tenant theme overrides, merged last.
+    #[serde(default)]
+    #[serde(alias = "tenant_themes")]
+    pub tenant_themes: std::collections::BTreeMap<String, Value>,
+
+    /// Tenant used to select `tenant_themes`; defaults to the envelope tenant.
+    #[serde(default)]
+    pub tenant: Option<String>,
+
+    #[serde(default)]
+    #[serde(alias = "color_scheme")]
+    pub color_scheme: Option<ColorScheme>,
+
+    #[serde(default)]
+    pub telemetry: TelemetryLevel,
+
+    /// Add ChoiceSet display titles to submit events under `inputs_display`.
+    #[serde(default, alias = "choice_labels")]
+    pub choice_labels: bool,
+
+    /// Routing table evaluated in order by `handle_interaction`; the first match wins.
+    #[serde(default)]
+    pub routing: Vec<RouteRule>,
+
+    /// After Submit/Execute, return a read-only receipt (the template's `post_submit_view`,
+    /// or the form with inputs shown as facts and actions removed) as `rendered_card`.
+    #[serde(default, alias = "render_receipt")]
+    pub render_receipt: bool,
+
+    /// After Submit/Execute, re-render the card with each input's `value` set from the
+    /// submitted inputs. Submits with input issues always keep their entries.
+    #[serde(default, alias = "preserve_inputs")]
+    pub preserve_inputs: bool,
+
+    /// Disable the clicked (or every) action after Submit/Execute, persisted in
+    /// `ui.disabled_actions` so later renders keep them disabled.
+    #[serde(default, alias = "disable_after_click")]
+    pub disable_after_click: Option<DisableActions>,
+
+    /// Partial re-render against a previously bound card; falls back to a full render when
+    /// the template cannot be patched in place.
+    #[serde(default)]
+    pub hydrate: Option<HydrationRequest>,
+
+    /// Card returned by `dismiss-card` / `expire-card` in place of the default notice.
+    #[serde(default)]
+    pub tombstone: Option<Value>,
+
+    /// Card `invoke-stream` sends while an Execute interaction is handled, in place of the
+    /// default notice.
+    #[serde(default)]
+    pub processing: Option<Value>,
+
+    /// Card instance state `import-state` writes, as returned by `export-state`.
+    #[serde(default)]
+    #[serde(alias = "state_snapshot")]
+    pub state_snapshot: Option<CardStateSnapshot>,
+
+    /// Attach an approximate image preview of the rendered card.
+    #[serde(default)]
+    pub preview: Option<PreviewFormat>,
+
+    /// Host capabilities used to resolve element `fallback`s.
+    #[serde(default, alias = "target_profile")]
+    pub target_profile: Option<TargetProfile>,
+
+    /// User viewing the card, exposed to templates as the `viewer` root; defaults to the
+    /// envelope user.
+    #[serde(default)]
+    pub viewer: Option<CardViewer>,
+
+    /// Host environment and configuration values, exposed to templates as the `env` root.
+    #[serde(default)]
+    pub env: Option<Value>,
+
+    /// Render time (ISO 8601) exposed to templates as `now` and `today`; defaults to the
+    /// current UTC time.
+    #[serde(default)]
+    pub now: Option<String>,
+
+    /// Users whose views the host refreshes; replaces the card's `refresh.userIds`.
+    #[serde(default, alias = "refresh_user_ids")]
+    pub refresh_user_ids: Option<Vec<String>>,
+
+    /// Interaction rate limit per card instance, tracked in the state store.
+    #[serde(default, alias = "rate_limit")]
+    pub rate_limit: Option<RateLimit>,
+
+    /// Return an `audit` record of every handled interaction.
+    #[serde(default, alias = "audit_log")]
+    pub audit_log: Option<AuditLogConfig>,
+
+    /// Redacts submitted inputs before they are written to state, emitted in events, or
+    /// captured in traces.
+    #[serde(default)]
+    pub redaction: Option<RedactionPolicy>,
+
+    /// Also return the rendered card wrapped in a Bot Framework message activity.
+    #[serde(default, alias = "bot_activity")]
+    pub bot_activity: Option<BotActivityOptions>,
+
+    /// How the rendered card is serialized: pretty or compact text, sorted keys, and
+    /// stripped empty properties.
+    #[serde(default)]
+    pub output: Option<CardOutputOptions>,
+
+    /// Also transcode the rendered card into a messaging channel's native format.
+    #[serde(default)]
+    pub target: Option<OutputTarget>,
+
+    /// Links and subject of the `email` target.
+    #[serde(default)]
+    pub email: Option<EmailOptions>,
+
+    /// Cards merged, in order, by the `compose` operation.
+    #[serde(default)]
+    pub compose: Vec<ComposePart>,
+
+    /// JSON Schema, or form description with `fields`, that the `generate-card-from-schema`
+    /// operation builds a card from.
+    #[serde(default, alias = "source_schema")]
+    pub source_schema: Option<Value>,
+
+    /// OpenAPI operation the `generate-card-from-openapi` operation builds a card for.
+    #[serde(default)]
+    pub openapi: Option<OpenApiSource>,
+
+    /// Form DSL the `compile-form` operation expands: a form description object, or its YAML
+    /// or JSON text.
+    #[serde(default)]
+    pub form: Option<Value>,
+}
+
+/// Input of `generate-card-from-openapi`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenApiSource {
+    /// OpenAPI 3 operation object (`parameters`, `requestBody`, `operationId`, ...).
+    pub operation: Value,
+    /// Document the operation's local `$ref`s (`#/components/...`) resolve against.
+    #[serde(default)]
+    pub document: Option<Value>,
+}
+
+/// Component-wide defaults, loaded at startup and applied to every invocation that leaves
+/// the field unset.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentConfig {
+    #[serde(default, alias = "target_profile")]
+    pub target_profile: Option<TargetProfile>,
+    #[serde(default)]
+    pub theme: Option<Value>,
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub budget: Option<CardBudget>,
+    #[serde(default)]
+    pub security: Option<SecurityPolicy>,
+    #[serde(default)]
+    pub limits: Option<RenderLimits>,
+    /// Environment values under the invocation's own `env`.
+    #[serde(default)]
+    pub env: Option<Value>,
+}
+
+/// One card of a `compose` operation, rendered as if it were the invocation's own card.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposePart {
+    #[serde(default, alias = "card_source")]
+    pub card_source: CardSource,
+    #[serde(default, alias = "card_spec")]
+    pub card_spec: CardSpec,
+    /// Replaces the invocation's payload for this card.
+    #[serde(default)]
+    pub payload: Option<Value>,
+}
+
+/// Sliding-window interaction limit for one card instance.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    /// Interactions accepted per window.
+    #[serde(alias = "max_interactions")]
+    pub max_interactions: u32,
+    #[serde(alias = "window_seconds")]
+    pub window_seconds: u64,
+    /// Notice shown at the top of a re-rendered card returned with the `AC_RATE_LIMITED`
+    /// failure; no card is rendered when omitted.
+    #[serde(default)]
+    pub notice: Option<String>,
+}
+
+/// Interaction audit records returned as `audit`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogConfig {
+    /// Input id patterns (`*` and `?` wildcards) whose values are not even hashed.
+    #[serde(default, alias = "redact_inputs")]
+    pub redact_inputs: Vec<String>,
+}
+
+/// Options for the Bot Framework activity returned as `activity`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BotActivityOptions {
+    /// Activity summary shown in notifications and chat lists.
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Card `fallbackText` for clients that cannot render Adaptive Cards; derived from the
+    /// card's text when omitted.
+    #[serde(default, alias = "fallback_text")]
+    pub fallback_text: Option<String>,
+}
+
+/// Serialization of the rendered card, for diff-based pipelines (compact, sorted, minimal)
+/// and debugging (pretty).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardOutputOptions {
+    /// Indent `rendered_card_json` instead of emitting it compactly.
+    #[serde(default)]
+    pub pretty: bool,
+    /// Sort object keys, even in builds where serde_json preserves insertion order.
+    #[serde(default, alias = "sort_keys")]
+    pub sort_keys: bool,
+    /// Drop properties whose value is null, an empty array, or an empty object.
+    #[serde(default, alias = "strip_empty")]
+    pub strip_empty: bool,
+}
+
+/// Channel formats the rendered card can be transcoded into, returned as `transcoded`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputTarget {
+    /// WhatsApp Cloud API message (`text` or `interactive`).
+    WhatsApp,
+    /// Discord message with embeds and message components.
+    Discord,
+    /// Static, table-based HTML email (plus a plain-text part), configured by `email`.
+    Email,
+}
+
+/// Settings of the `email` target. Email clients cannot run actions, so Submit and Execute
+/// actions become links to `action_url`, and OpenUrl links can be routed through
+/// `tracking_url` to record clicks.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailOptions {
+    /// Endpoint receiving Submit and Execute clicks as `?reply=<reply id>&card=<instance>`.
+    #[serde(default, alias = "action_url")]
+    pub action_url: Option<String>,
+    /// Redirect endpoint wrapping OpenUrl links as `?url=<target>&action=<id>&card=<instance>`.
+    #[serde(default, alias = "tracking_url")]
+    pub tracking_url: Option<String>,
+    /// Email subject; defaults to the card's leading heading.
+    #[serde(default)]
+    pub subject: Option<String>,
+}
+
+/// Submitted input values to redact, by input id or by path into the inputs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionPolicy {
+    /// Input id patterns (`*` and `?` wildcards), such as `email` or `*phone*`.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// Dotted paths into the inputs, such as `contact.email` or `rows.*.phone`; every segment
+    /// is a pattern matching an object key or array index.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub mode: RedactionMode,
+    /// Host secret keying `hash` mode. Without one, hashed values are written as `redacted`:
+    /// an unkeyed hash of an email or phone number is recovered by hashing guesses.
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionMode {
+    /// Replace values with their `blake3:` hash keyed by the policy's `key`, so equal values
+    /// still correlate.
+    #[default]
+    Hash,
+    /// Replace values with `*`, keeping the last four characters of strings longer than eight.
+    Mask,
+}
+
+/// Identity of the user a card view is rendered for. Extra attributes (e.g. `role`,
+/// `email`) are available to templates as `viewer.<name>`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardViewer {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Roles checked against element and action `visibleToRoles`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    #[serde(flatten)]
+    pub attributes: serde_json::Map<String, Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
 #[serde(rename_all = "PascalCase")]
 pub enum CardInteractionType {
     #[default]
@@ -83,9 +633,16 @@ pub enum CardInteractionType {
     OpenUrl,
     ShowCard,
     ToggleVisibility,
+    /// Draft autosave for a single input; `action_id` names the input.
+    InputChanged,
+    /// Opens the dialog declared by the action's `openDialog` (Teams `task/fetch`).
+    OpenDialog,
+    /// The host finished an upload for the `Input.File` named by `action_id`; `raw_inputs`
+    /// describes the file (or files).
+    UploadComplete,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CardInteraction {
     #[serde(default)]
@@ -103,9 +660,15 @@ pub struct CardInteraction {
     pub card_instance_id: String,
     #[serde(default)]
     pub metadata: Value,
+    /// Client-supplied key used to suppress redelivered interactions for the same card instance.
+    #[serde(default, alias = "idempotency_key")]
+    pub idempotency_key: Option<String>,
+    /// Dialog the interaction was submitted from; its inputs update the parent card.
+    #[serde(default, alias = "dialog_id")]
+    pub dialog_id: Option<String>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
 #[serde(rename_all = "PascalCase")]
 pub enum AdaptiveActionType {
     #[default]
@@ -114,9 +677,10 @@ pub enum AdaptiveActionType {
     OpenUrl,
     ShowCard,
     ToggleVisibility,
+    UploadComplete,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AdaptiveActionEvent {
     pub action_type: AdaptiveActionType,
@@ -125,19 +689,31 @@ pub struct AdaptiveActionEvent {
     pub verb: Option<String>,
     #[serde(default)]
     pub route: Option<String>,
+    /// Flow node resolved from the routing table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    /// Node operation resolved from the routing table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub op: Option<String>,
     #[serde(default)]
     pub inputs: Value,
+    /// `inputs` with ChoiceSet values replaced by their display titles, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs_display: Option<Value>,
 
     pub card_id: String,
     pub card_instance_id: String,
     #[serde(default)]
     pub subcard_id: Option<String>,
+    /// Dialog whose submit produced the event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialog_id: Option<String>,
 
     #[serde(default)]
     pub metadata: Value,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub enum StateUpdateOp {
     Set { path: String, value: Value },
@@ -145,7 +721,7 @@ pub enum StateUpdateOp {
     Delete { path: String },
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub enum SessionUpdateOp {
     SetRoute { route: String },
@@ -153,9 +729,12 @@ pub enum SessionUpdateOp {
     DeleteAttribute { key: String },
     PushCardStack { card_id: String },
     PopCardStack,
+    SetVariable { name: String, value: Value },
+    UnsetVariable { name: String },
+    MergeVariables { values: Value },
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CardFeatureSummary {
     pub version: Option<String>,
@@ -165,19 +744,147 @@ pub struct CardFeatureSummary {
     pub uses_toggle_visibility: bool,
     pub uses_media: bool,
     pub uses_auth: bool,
+    /// Highest version of each `requires` feature across the elements the final card renders.
     #[serde(default)]
     pub requires_features: Value,
+    /// Resolved visibility of toggle targets and state-tracked elements, keyed by element id.
+    #[serde(default)]
+    pub visibility: std::collections::BTreeMap<String, bool>,
+    /// Elements and actions replaced or dropped through `fallback` handling.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<FallbackReplacement>,
+    /// JSON pointer of every element and action, keyed by its (authored or generated) id.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub ids: std::collections::BTreeMap<String, String>,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+/// One `fallback` substitution made while rendering.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FallbackReplacement {
+    /// JSON pointer of the replaced element in the bound template.
+    pub pointer: String,
+    pub element_type: String,
+    /// Type of the fallback element; `None` when the element was dropped.
+    #[serde(default)]
+    pub replaced_with: Option<String>,
+    /// `unknown`, `unsupported` (listed by the profile), `version` (newer than the profile),
+    /// `requires` (an unmet `requires` entry), or `descendant` (a child had no fallback of its
+    /// own).
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ValidationIssue {
     pub code: String,
     pub message: String,
     pub path: String,
+    /// Set for audit and budget findings; structural validation issues leave it empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<IssueSeverity>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Size and complexity limits checked after render. Unset limits are not enforced.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardBudget {
+    #[serde(default)]
+    #[serde(alias = "max_bytes")]
+    pub max_bytes: Option<usize>,
+    #[serde(default)]
+    #[serde(alias = "max_elements")]
+    pub max_elements: Option<usize>,
+    #[serde(default)]
+    #[serde(alias = "max_depth")]
+    pub max_depth: Option<usize>,
+    #[serde(default)]
+    #[serde(alias = "max_actions")]
+    pub max_actions: Option<usize>,
+    /// Strategies applied in order before the budget is checked.
+    #[serde(default)]
+    pub truncation: Vec<TruncationStrategy>,
+}
+
+/// Wall-clock budget bounding the latency of untrusted templates.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeBudget {
+    /// Milliseconds the render may take.
+    #[serde(alias = "max_ms")]
+    pub max_ms: u64,
+    #[serde(default, alias = "on_exceeded")]
+    pub on_exceeded: TimeBudgetPolicy,
+}
+
+/// Sensitivity an input declares through its `dataClassification` extension property.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DataClassification {
+    Public,
+    Internal,
+    Pii,
+    Secret,
+}
+
+/// What a render that runs out of time returns.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeBudgetPolicy {
+    /// Fail with `AC_TIMEOUT`.
+    #[default]
+    Error,
+    /// Return the card bound so far: elements and actions not yet bound are dropped and a
+    /// `time-budget-exceeded` warning is reported.
+    Partial,
+}
+
+/// Limits guarding the renderer and expression engine against pathological templates. Unlike
+/// [`CardBudget`], they are checked while rendering and fail the invocation.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderLimits {
+    /// Element nesting depth of the template and of the bound card (default 32).
+    #[serde(default, alias = "max_nesting")]
+    pub max_nesting: Option<usize>,
+    /// Length in bytes of one `${...}` expression (default 1024).
+    #[serde(default, alias = "max_expression_length")]
+    pub max_expression_length: Option<usize>,
+    /// Operands, operators, and calls in one expression (default 128).
+    #[serde(default, alias = "max_expression_nodes")]
+    pub max_expression_nodes: Option<usize>,
+    /// Entries rendered through `itemTemplate` in one render (default 1000).
+    #[serde(default, alias = "max_repeat")]
+    pub max_repeat: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TruncationStrategy {
+    /// Drop trailing body elements (and root actions) until the card fits.
+    #[serde(alias = "drop_trailing")]
+    DropTrailing,
+    /// Unwrap Containers on over-deep paths, outermost first.
+    #[serde(alias = "collapse_containers")]
+    CollapseContainers,
+}
+
+/// Optional audits run over the rendered card.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditCheck {
+    Accessibility,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TelemetryEvent {
     pub name: String,
@@ -185,7 +892,7 @@ pub struct TelemetryEvent {
     pub properties: Value,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AdaptiveCardResult {
     pub rendered_card: Option<Value>,
@@ -199,4 +906,458 @@ pub struct AdaptiveCardResult {
     pub validation_issues: Vec<ValidationIssue>,
     #[serde(default)]
     pub telemetry_events: Vec<TelemetryEvent>,
+    /// Active ShowCard subcard when `show_card_mode` is `separate`.
+    #[serde(default)]
+    pub subcard: Option<Value>,
+    /// Template dependencies returned by the `analyze-template` operation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_analysis: Option<TemplateAnalysis>,
+    /// Placeholder payload/state/session/params returned by the `generate-sample-data` operation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_data: Option<Value>,
+    /// Card instance addressed by lifecycle operations (`init-card` assigns it).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card_instance_id: Option<String>,
+    /// State that would have been persisted, returned for `simulate` interactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulated_state: Option<Value>,
+    /// Rendered variants keyed by color scheme when `color_scheme` is `both`.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub variants: std::collections::BTreeMap<String, Value>,
+    /// Image preview of `rendered_card` when `preview` was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<CardPreview>,
+    /// Text (or an SSML `<speak>` document) for voice channels to announce the rendered card.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speak: Option<String>,
+    /// Dialog to show, returned by `OpenDialog` interactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialog: Option<CardDialog>,
+    /// Upload the host should start, returned when an `Input.File` upload button is clicked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_request: Option<UploadRequest>,
+    /// `rendered_card` serialized as the invocation's `output` options ask.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendered_card_json: Option<String>,
+    /// Content hash of `rendered_card`; identical cards hash identically.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card_hash: Option<String>,
+    /// Hash of the template and the data bound into it, for cache keys.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    /// JSON Schemas of the model, keyed by name, returned by the `get-schema` operation.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub schemas: std::collections::BTreeMap<String, Value>,
+    /// Component diagnostics returned by the `health` operation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthReport>,
+    /// Audit record of the handled interaction when `audit_log` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit: Option<InteractionAudit>,
+    /// `rendered_card` wrapped in a Bot Framework activity when `bot_activity` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<BotActivity>,
+    /// `rendered_card` in the channel format requested by `target`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcoded: Option<TranscodedMessage>,
+    /// Inputs and actions of the card, returned by the `introspect-card` operation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub introspection: Option<CardIntrospection>,
+    /// When the host scheduler should remind about and expire the card, from `$schedule`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<CardSchedule>,
+    /// Operation the host should invoke next, declared by the action through `data.__next`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continuation: Option<CardContinuation>,
+    /// Card instance state read by `export-state` or written by `import-state`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_snapshot: Option<CardStateSnapshot>,
+}
+
+/// Bot Framework message activity, ready to post to a connector conversation.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BotActivity {
+    /// Always `message`.
+    #[serde(rename = "type")]
+    pub activity_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub attachments: Vec<CardAttachment>,
+}
+
+/// Rendered card converted for a channel without Adaptive Card support. Card features the
+/// channel cannot express are dropped and reported as `transcode-unsupported` warnings.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscodedMessage {
+    pub target: OutputTarget,
+    /// Message payload in the channel's own API format.
+    pub content: Value,
+}
+
+/// Next step of a card-driven flow: the operation to invoke and its input.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct CardContinuation {
+    pub op: String,
+    pub input: Value,
+}
+
+/// A card instance's persisted state, as moved between shards, backups, and test
+/// environments by `export-state` / `import-state`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardStateSnapshot {
+    #[serde(alias = "card_instance_id")]
+    pub card_instance_id: String,
+    /// State version the state records (`$stateVersion`, 1 when absent).
+    #[serde(default = "default_state_version")]
+    pub version: u64,
+    pub state: Value,
+    #[serde(default)]
+    pub metadata: StateSnapshotMetadata,
+}
+
+fn default_state_version() -> u64 {
+    1
+}
+
+/// Where and when a state snapshot was taken.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StateSnapshotMetadata {
+    /// Node whose state key the snapshot was read from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "node_id")]
+    pub node_id: Option<String>,
+    #[serde(default)]
+    #[serde(alias = "exported_at_ms")]
+    pub exported_at_ms: u64,
+    /// Component version that exported the snapshot.
+    #[serde(default)]
+    #[serde(alias = "component_version")]
+    pub component_version: String,
+    /// Hex blake3 hash of the serialized state; `import-state` rejects a snapshot whose
+    /// state no longer matches it.
+    #[serde(default)]
+    pub hash: String,
+}
+
+/// Scheduling a template declares through `$schedule`, in seconds after the render.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardSchedule {
+    /// When the host should invoke `expire-card`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expire_after_seconds: Option<u64>,
+    /// When the host should remind the card's audience, ascending.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remind_after_seconds: Vec<u64>,
+    /// Whether `expire-card` renders the template's `onExpire` card.
+    #[serde(default)]
+    pub on_expire: bool,
+}
+
+/// What a card collects and how it submits it, in document order.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardIntrospection {
+    pub inputs: Vec<InputDescription>,
+    pub actions: Vec<ActionDescription>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputDescription {
+    pub id: String,
+    /// Element type (`Input.Text`, `Input.Number`, ...).
+    #[serde(rename = "type")]
+    pub input_type: String,
+    /// JSON pointer of the element.
+    pub pointer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    /// Initial `value`, typed the way its submission is coerced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    /// Validation and shape of the accepted value.
+    #[serde(default)]
+    pub constraints: InputConstraints,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputConstraints {
+    /// Lower bound of a number, date, or time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    /// Text style (`email`, `tel`, `url`, `password`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    /// Accepted values of a ChoiceSet.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<ChoiceDescription>,
+    #[serde(default)]
+    pub multi_select: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChoiceDescription {
+    pub title: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Action type (`Action.Submit`, `Action.Execute`, ...).
+    #[serde(rename = "type")]
+    pub action_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verb: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    /// Target of an OpenUrl action.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Ids of the inputs a Submit or Execute action sends with it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub submits: Vec<String>,
+}
+
+/// Activity attachment holding an Adaptive Card.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardAttachment {
+    /// `application/vnd.microsoft.card.adaptive`.
+    pub content_type: String,
+    pub content: Value,
+}
+
+/// Who submitted what through which action, and what came of it. Input values are only
+/// recorded as hashes.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InteractionAudit {
+    /// Viewer id, defaulting to the envelope user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    pub card_id: String,
+    pub card_instance_id: String,
+    pub action_id: String,
+    pub interaction_type: CardInteractionType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verb: Option<String>,
+    /// `blake3:` hash of each submitted input's value keyed by input id, or `redacted` for
+    /// ids matching `audit_log.redact_inputs`.
+    #[serde(default)]
+    pub inputs: std::collections::BTreeMap<String, String>,
+    pub outcome: AuditOutcome,
+    /// Milliseconds since the Unix epoch when the interaction was handled.
+    pub timestamp_ms: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// An event was emitted to the flow.
+    #[default]
+    Accepted,
+    /// Input issues held the submit back.
+    Rejected,
+    /// Handled without an event, such as an input draft, a page change, or opening a dialog.
+    Handled,
+}
+
+/// Diagnostics returned by the `health` operation; probing renders nothing.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct HealthReport {
+    /// Milliseconds since the component first handled a message.
+    pub uptime_ms: u64,
+    /// Messages handled, not counting `health` probes.
+    pub invocations: u64,
+    /// Messages that returned a failure.
+    pub failures: u64,
+    /// Failure codes of the most recent failures, oldest first.
+    #[serde(default)]
+    pub recent_errors: Vec<FailureCode>,
+    /// `ok`, `unavailable` (see `state_store_error`), or `disabled` when built without it.
+    pub state_store: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_store_error: Option<String>,
+    /// Entry and hit/miss/eviction counters per cache, keyed by cache name.
+    #[serde(default)]
+    pub caches: std::collections::BTreeMap<String, CacheStats>,
+    pub build: BuildInfo,
+}
+
+/// Counters of one in-memory cache.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+/// Identity of the running build.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct BuildInfo {
+    pub component: String,
+    pub version: String,
+    pub crate_version: String,
+    /// Cargo features compiled in.
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+/// Upload requested through an `Input.File`; the host collects the file and reports it back
+/// with an `UploadComplete` interaction.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadRequest {
+    pub input_id: String,
+    /// Accepted content types and extensions; empty accepts any file.
+    #[serde(default)]
+    pub accept: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+    #[serde(default)]
+    pub multiple: bool,
+    /// Teams file consent card attachment to send before uploading.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consent_card: Option<Value>,
+}
+
+/// File reported by an `UploadComplete` interaction, as stored in inputs and state.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadedFile {
+    pub name: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Host reference to the stored content (URL, blob id, Teams unique id).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_ref: Option<String>,
+}
+
+/// Modal dialog declared by an action's `openDialog`, shown by the host as a Teams task
+/// module or its own modal.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardDialog {
+    pub id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// `small`, `medium`, `large`, or pixels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<Value>,
+    /// Bound card shown in the dialog.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card: Option<Value>,
+    /// Web page shown in the dialog instead of a card.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Approximate rendering of a card for authoring tools and image-only channels.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CardPreview {
+    pub format: PreviewFormat,
+    pub media_type: String,
+    pub width: u32,
+    pub height: u32,
+    /// SVG markup, or base64 PNG bytes.
+    pub data: String,
+}
+
+/// Data paths referenced by a template, returned by the `analyze-template` operation.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateAnalysis {
+    /// Every referenced path, sorted and deduplicated.
+    pub paths: Vec<String>,
+    /// Referenced paths grouped by the nearest enclosing element or action.
+    pub elements: Vec<ElementDependencies>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementDependencies {
+    /// JSON pointer of the element (`""` for the card root).
+    pub pointer: String,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default, rename = "type")]
+    pub element_type: Option<String>,
+    pub paths: Vec<String>,
+}
+
+/// Stable failure codes returned in `AdaptiveCardFailure::code`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum FailureCode {
+    #[serde(rename = "AC_SCHEMA_INVALID")]
+    SchemaInvalid,
+    #[serde(rename = "AC_ASSET_NOT_FOUND")]
+    AssetNotFound,
+    #[serde(rename = "AC_ASSET_PARSE_ERROR")]
+    AssetParseError,
+    #[serde(rename = "AC_BINDING_EVAL_ERROR")]
+    BindingEvalError,
+    #[serde(rename = "AC_CARD_VALIDATION_FAILED")]
+    CardValidationFailed,
+    #[serde(rename = "AC_INTERACTION_INVALID")]
+    InteractionInvalid,
+    #[serde(rename = "AC_STATE_STORE_ERROR")]
+    StateStoreError,
+    #[serde(rename = "AC_RATE_LIMITED")]
+    RateLimited,
+    #[serde(rename = "AC_LIMIT_EXCEEDED")]
+    LimitExceeded,
+    #[serde(rename = "AC_TIMEOUT")]
+    Timeout,
+    #[serde(rename = "AC_INTERNAL_ERROR")]
+    Internal,
+}
+
+/// Typed failure returned instead of an `AdaptiveCardResult`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct AdaptiveCardFailure {
+    pub code: FailureCode,
+    pub message: String,
+    /// JSON pointer into the invocation (or card) the failure relates to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Whether retrying the same invocation may succeed (e.g. transient state store errors).
+    #[serde(default)]
+    pub retryable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+/// Output of `handle_message`: a result, or a failure serialized as `{"error": {...}}`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(untagged)]
+pub enum AdaptiveCardResponse {
+    Failure { error: AdaptiveCardFailure },
+    Success(Box<AdaptiveCardResult>),
 }
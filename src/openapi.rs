@@ -0,0 +1,158 @@
+use serde_json::{Value, json};
+
+use crate::error::ComponentError;
+use crate::forms::{
+    self, Form, FormItem, collect_properties, humanize, input_value, resolve_ref, schema_input,
+};
+use crate::model::{AdaptiveCardInvocation, AdaptiveCardResult, ValidationIssue};
+use crate::render::analyze_features;
+
+/// Request body media types read, in order of preference.
+const BODY_MEDIA_TYPES: [&str; 3] = [
+    "application/json",
+    "application/x-www-form-urlencoded",
+    "multipart/form-data",
+];
+
+/// `generate-card-from-openapi`: builds a card collecting the parameters and request body of
+/// `openapi.operation`. Parameters get `<in>.<name>` ids and body properties `body.<name>`
+/// ones; the card sets `$nestInputs`, so the event's inputs hold `path`, `query`, and
+/// `header` objects and a `body` shaped like the request body the operation expects. The
+/// action is an Execute whose verb is the `operationId`.
+pub fn generate_card_from_openapi(
+    inv: &AdaptiveCardInvocation,
+) -> Result<AdaptiveCardResult, ComponentError> {
+    let Some(source) = inv.openapi.as_ref() else {
+        return Err(ComponentError::InvalidInput(
+            "generate-card-from-openapi requires openapi.operation".into(),
+        ));
+    };
+    let operation = &source.operation;
+    if !operation.is_object() {
+        return Err(ComponentError::InvalidInput(
+            "openapi.operation must be an OpenAPI operation object".into(),
+        ));
+    }
+    let root = source.document.as_ref().unwrap_or(operation);
+    let text = |key: &str| {
+        operation
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let operation_id = text("operationId");
+    let mut form = Form {
+        title: text("summary").or_else(|| operation_id.as_deref().map(humanize)),
+        description: text("description"),
+        submit_title: Some("Send".into()),
+        verb: operation_id,
+        ..Default::default()
+    };
+    let mut issues = Vec::new();
+    let parameters = operation
+        .get("parameters")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    for parameter in parameters {
+        if let Some(item) = parameter_input(root, resolve_ref(root, parameter), &mut issues) {
+            form.items.push(item);
+        }
+    }
+    if let Some(body) = operation.get("requestBody") {
+        request_body(root, resolve_ref(root, body), &mut form, &mut issues);
+    }
+    let card = forms::build_card(&form);
+    Ok(AdaptiveCardResult {
+        card_features: analyze_features(&card),
+        rendered_card: Some(card),
+        validation_issues: issues,
+        ..Default::default()
+    })
+}
+
+fn parameter_input(
+    root: &Value,
+    parameter: &Value,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<FormItem> {
+    let name = parameter.get("name")?.as_str()?;
+    let location = parameter.get("in")?.as_str()?;
+    let id = format!("{location}.{name}");
+    if !matches!(location, "path" | "query" | "header") {
+        issues.push(forms::unsupported(&id, &format!("{location} parameters")));
+        return None;
+    }
+    let schema = resolve_ref(root, parameter.get("schema").unwrap_or(&Value::Null));
+    let Some(mut input) = schema_input(root, schema) else {
+        issues.push(forms::unsupported(&id, "Parameters of this schema"));
+        return None;
+    };
+    input.insert("id".into(), json!(id));
+    if let Some(description) = parameter.get("description").and_then(Value::as_str) {
+        input
+            .entry("placeholder")
+            .or_insert_with(|| json!(description));
+    }
+    if let Some(default) = schema.get("default") {
+        input.insert("value".into(), input_value(default));
+    }
+    Some(FormItem::Input {
+        label: humanize(name),
+        required: location == "path"
+            || parameter.get("required").and_then(Value::as_bool) == Some(true),
+        input,
+    })
+}
+
+/// Adds the body's inputs: an object schema's properties under `body.`, or a single `body`
+/// input for a scalar body.
+fn request_body(root: &Value, body: &Value, form: &mut Form, issues: &mut Vec<ValidationIssue>) {
+    let content = body.get("content").and_then(Value::as_object);
+    let schema = content.and_then(|content| {
+        BODY_MEDIA_TYPES
+            .iter()
+            .find_map(|media| content.get(*media))
+            .or_else(|| {
+                content
+                    .iter()
+                    .find(|(media, _)| media.ends_with("+json"))
+                    .map(|(_, entry)| entry)
+            })?
+            .get("schema")
+    });
+    let Some(schema) = schema.map(|schema| resolve_ref(root, schema)) else {
+        issues.push(forms::unsupported(
+            "body",
+            "Request bodies without a JSON or form schema",
+        ));
+        return;
+    };
+    if !form.items.is_empty() {
+        form.items.push(FormItem::Heading(
+            body.get("description")
+                .and_then(Value::as_str)
+                .unwrap_or("Request body")
+                .to_string(),
+        ));
+    }
+    if schema.get("properties").is_some() {
+        collect_properties(root, schema, "body.", &mut form.items, issues, 0);
+        return;
+    }
+    match schema_input(root, schema) {
+        Some(mut input) => {
+            input.insert("id".into(), json!("body"));
+            form.items.push(FormItem::Input {
+                label: schema
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Body")
+                    .to_string(),
+                required: body.get("required").and_then(Value::as_bool) == Some(true),
+                input,
+            });
+        }
+        None => issues.push(forms::unsupported("body", "Request bodies of this schema")),
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value, json};
+
+use crate::actions;
+use crate::fallback::parse_version;
+use crate::inputs;
+use crate::model::{HostPlatform, IssueSeverity, TargetProfile, ValidationIssue};
+
+/// Highest schema version Outlook Actionable Messages render.
+pub const MAX_VERSION: (u32, u32) = (1, 4);
+
+/// Whether the profile targets Outlook Actionable Messages.
+pub fn is_outlook(profile: Option<&TargetProfile>) -> bool {
+    profile.and_then(|p| p.host) == Some(HostPlatform::Outlook)
+}
+
+/// Turns the card into an Actionable Message: the root gets the profile's `originator` and a
+/// version capped at 1.4, and Submit and Execute actions, which Outlook does not run, become
+/// `Action.Http` POSTs to the profile's `action_url`. The JSON body carries the action id,
+/// verb, data, and the action's inputs as `{{id.value}}` substitutions, so the host receiving
+/// the POST can build the `CardInteraction`. Missing or non-HTTPS settings are reported as
+/// warnings.
+pub fn to_actionable_message(
+    card: &mut Value,
+    profile: Option<&TargetProfile>,
+) -> Vec<ValidationIssue> {
+    let Some(profile) = profile.filter(|p| is_outlook(Some(p))) else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+    let Some(root) = card.as_object_mut() else {
+        return issues;
+    };
+    match profile.originator.as_deref() {
+        Some(originator) => {
+            root.insert("originator".into(), json!(originator));
+        }
+        None => issues.push(warning(
+            "outlook-originator-missing",
+            "Outlook Actionable Messages need the provider id as target_profile.originator",
+            "/originator",
+        )),
+    }
+    let capped = root
+        .get("version")
+        .and_then(Value::as_str)
+        .and_then(parse_version)
+        .is_none_or(|version| version > MAX_VERSION);
+    if capped {
+        let (major, minor) = MAX_VERSION;
+        root.insert("version".into(), json!(format!("{major}.{minor}")));
+    }
+
+    // Inputs each posting action sends, resolved before the card is rewritten.
+    let all_inputs: Vec<String> = inputs::collect_inputs(card)
+        .into_iter()
+        .map(|decl| decl.id)
+        .collect();
+    let posted: BTreeMap<String, Vec<String>> = actions::collect_actions(card)
+        .into_iter()
+        .filter(|action| posts(&action.kind))
+        .filter_map(|action| {
+            let id = action.id?;
+            let excluded = actions::unassociated_inputs(card, &id);
+            let sent = all_inputs
+                .iter()
+                .filter(|input| !excluded.contains(*input))
+                .cloned()
+                .collect();
+            Some((id, sent))
+        })
+        .collect();
+    if posted.is_empty() {
+        return issues;
+    }
+    let Some(url) = profile.action_url.as_deref() else {
+        issues.push(warning(
+            "outlook-action-url-missing",
+            "Submit and Execute actions need target_profile.action_url to become Action.Http",
+            "/actions",
+        ));
+        return issues;
+    };
+    if !url.starts_with("https://") {
+        issues.push(warning(
+            "outlook-action-url-insecure",
+            "Outlook only posts Action.Http to https URLs",
+            "/actions",
+        ));
+    }
+    rewrite(card, url, &posted);
+    issues
+}
+
+fn posts(kind: &str) -> bool {
+    matches!(kind, "Action.Submit" | "Action.Execute")
+}
+
+fn rewrite(value: &mut Value, url: &str, posted: &BTreeMap<String, Vec<String>>) {
+    match value {
+        Value::Object(map) => {
+            if map.get("type").and_then(Value::as_str).is_some_and(posts)
+                && let Some(sent) = map
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .and_then(|id| posted.get(id))
+            {
+                to_http(map, url, sent);
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    rewrite(child, url, posted);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                rewrite(item, url, posted);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn to_http(action: &mut Map<String, Value>, url: &str, sent: &[String]) {
+    let inputs: Map<String, Value> = sent
+        .iter()
+        .map(|id| (id.clone(), json!(format!("{{{{{id}.value}}}}"))))
+        .collect();
+    let body = json!({
+        "actionId": action.get("id").cloned().unwrap_or(Value::Null),
+        "verb": action.remove("verb").unwrap_or(Value::Null),
+        "data": action.remove("data").unwrap_or(Value::Null),
+        "inputs": inputs,
+    });
+    action.remove("associatedInputs");
+    action.insert("type".into(), json!("Action.Http"));
+    action.insert("method".into(), json!("POST"));
+    action.insert("url".into(), json!(url));
+    action.insert("body".into(), json!(body.to_string()));
+    action.insert(
+        "headers".into(),
+        json!([{ "name": "Content-Type", "value": "application/json" }]),
+    );
+}
+
+fn warning(code: &str, message: &str, path: &str) -> ValidationIssue {
+    ValidationIssue {
+        code: code.into(),
+        message: message.into(),
+        path: path.into(),
+        severity: Some(IssueSeverity::Warning),
+    }
+}
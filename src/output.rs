@@ -0,0 +1,68 @@
+use serde_json::{Map, Value};
+
+use crate::model::{AdaptiveCardResult, CardOutputOptions};
+
+/// Applies `options` to the rendered card, its separate subcard, and its color-scheme
+/// variants, and serializes the rendered card into `rendered_card_json`.
+pub fn apply_output_options(result: &mut AdaptiveCardResult, options: &CardOutputOptions) {
+    let cards = result
+        .rendered_card
+        .iter_mut()
+        .chain(result.subcard.iter_mut())
+        .chain(result.variants.values_mut());
+    for card in cards {
+        if options.strip_empty {
+            strip_empty(card);
+        }
+        if options.sort_keys {
+            sort_keys(card);
+        }
+    }
+    result.rendered_card_json = result.rendered_card.as_ref().and_then(|card| {
+        if options.pretty {
+            serde_json::to_string_pretty(card).ok()
+        } else {
+            serde_json::to_string(card).ok()
+        }
+    });
+}
+
+/// Removes null, `[]`, and `{}` properties, innermost first, so an object left empty by the
+/// removal goes too. Array entries and empty strings (such as a blank `text`) are kept.
+fn strip_empty(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.values_mut().for_each(strip_empty);
+            map.retain(|_, child| !is_empty(child));
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_empty),
+        _ => {}
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+fn sort_keys(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = std::mem::take(map).into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            *map = entries
+                .into_iter()
+                .map(|(key, mut child)| {
+                    sort_keys(&mut child);
+                    (key, child)
+                })
+                .collect::<Map<_, _>>();
+        }
+        Value::Array(items) => items.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
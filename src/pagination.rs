@@ -0,0 +1,196 @@
+use serde_json::{Map, Value, json};
+
+use crate::error::ComponentError;
+use crate::model::{IssueSeverity, StateUpdateOp, ValidationIssue};
+
+/// Action `data` key carrying `{container, page}` on generated Previous/Next actions.
+pub const PAGE_KEY: &str = "__page";
+/// Container key naming the namespace that `itemTemplate` inputs are rendered into.
+pub const INPUT_NAMESPACE_KEY: &str = "inputNamespace";
+
+/// Binds `itemTemplate` against one array entry (the `item` root); `None` skips the entry.
+pub type ItemBinder<'a> = dyn FnMut(&Value, &Value) -> Result<Option<Value>, ComponentError> + 'a;
+
+/// Paginates every Container that declares `pageSize`: only the page stored under
+/// `ui.pages.<containerId>` is kept in `items` (clamped to the last page), and an ActionSet
+/// with Previous/Next actions is appended when there is more than one page. Entries that are
+/// data rather than elements are rendered through the container's `itemTemplate`; with an
+/// `inputNamespace`, the inputs of entry `n` get the id `<namespace>[n].<id>`.
+pub fn apply_pagination(
+    card: &mut Value,
+    state: &Value,
+    bind_item: &mut ItemBinder<'_>,
+) -> Result<Vec<ValidationIssue>, ComponentError> {
+    let mut issues = Vec::new();
+    visit(card, "", state, bind_item, &mut issues)?;
+    Ok(issues)
+}
+
+/// Whether any element outside `data` declares `pageSize`.
+pub fn is_paginated(card: &Value) -> bool {
+    match card {
+        Value::Object(map) => {
+            map.contains_key("pageSize")
+                || map
+                    .iter()
+                    .any(|(key, child)| key != "data" && is_paginated(child))
+        }
+        Value::Array(items) => items.iter().any(is_paginated),
+        _ => false,
+    }
+}
+
+/// `ui.pages.<containerId>` update requested by a generated pager action's `data`.
+pub fn page_update(data: Option<&Value>) -> Option<StateUpdateOp> {
+    let request = data?.get(PAGE_KEY)?;
+    Some(StateUpdateOp::Set {
+        path: format!("ui.pages.{}", request.get("container")?.as_str()?),
+        value: Value::from(request.get("page")?.as_u64()?),
+    })
+}
+
+fn visit(
+    value: &mut Value,
+    path: &str,
+    state: &Value,
+    bind_item: &mut ItemBinder<'_>,
+    issues: &mut Vec<ValidationIssue>,
+) -> Result<(), ComponentError> {
+    match value {
+        Value::Object(map) => {
+            if map.contains_key("pageSize") {
+                paginate(map, path, state, bind_item, issues)?;
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    visit(child, &format!("{path}/{key}"), state, bind_item, issues)?;
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                visit(item, &format!("{path}/{idx}"), state, bind_item, issues)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn paginate(
+    map: &mut Map<String, Value>,
+    path: &str,
+    state: &Value,
+    bind_item: &mut ItemBinder<'_>,
+    issues: &mut Vec<ValidationIssue>,
+) -> Result<(), ComponentError> {
+    let page_size = map.remove("pageSize").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+    let template = map.remove("itemTemplate");
+    let namespace = map
+        .remove(INPUT_NAMESPACE_KEY)
+        .and_then(|v| v.as_str().map(str::to_string));
+    let Some(id) = map.get("id").and_then(Value::as_str).map(str::to_string) else {
+        issues.push(warning(
+            "pagination-missing-id",
+            "Paginated containers need an id to remember the current page".into(),
+            format!("{path}/id"),
+        ));
+        return Ok(());
+    };
+    if page_size == 0 {
+        issues.push(warning(
+            "invalid-page-size",
+            "pageSize must be a positive integer".into(),
+            format!("{path}/pageSize"),
+        ));
+        return Ok(());
+    }
+    let entries = match map.remove("items") {
+        Some(Value::Array(entries)) => entries,
+        _ => Vec::new(),
+    };
+    let pages = entries.len().div_ceil(page_size).max(1);
+    let page = state
+        .get("ui")
+        .and_then(|ui| ui.get("pages"))
+        .and_then(|pages| pages.get(&id))
+        .and_then(Value::as_u64)
+        .map_or(0, |page| (page as usize).min(pages - 1));
+    let start = page * page_size;
+    let mut items = Vec::with_capacity(page_size + 1);
+    for (index, entry) in entries.iter().enumerate().skip(start).take(page_size) {
+        let is_element = entry.get("type").and_then(Value::as_str).is_some();
+        let item = match template.as_ref() {
+            Some(template) if !is_element => bind_item(template, entry)?.map(|mut element| {
+                if let Some(namespace) = namespace.as_deref() {
+                    namespace_inputs(&mut element, &format!("{namespace}[{index}]."));
+                }
+                element
+            }),
+            _ => Some(entry.clone()),
+        };
+        items.extend(item);
+    }
+    if pages > 1 {
+        let mut actions = Vec::new();
+        if page > 0 {
+            actions.push(pager_action(&id, "prev", "Previous", page - 1));
+        }
+        if page + 1 < pages {
+            actions.push(pager_action(&id, "next", "Next", page + 1));
+        }
+        items.push(json!({
+            "type": "TextBlock",
+            "id": format!("{id}-page"),
+            "text": format!("Page {} of {pages}", page + 1),
+            "isSubtle": true,
+            "size": "Small"
+        }));
+        items.push(json!({ "type": "ActionSet", "id": format!("{id}-pager"), "actions": actions }));
+    }
+    map.insert("items".into(), Value::Array(items));
+    Ok(())
+}
+
+/// Prefixes the id of every input in `element` with `prefix`.
+fn namespace_inputs(element: &mut Value, prefix: &str) {
+    match element {
+        Value::Object(map) => {
+            if map
+                .get("type")
+                .and_then(Value::as_str)
+                .is_some_and(|kind| kind.starts_with("Input."))
+                && let Some(Value::String(id)) = map.get_mut("id")
+            {
+                id.insert_str(0, prefix);
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    namespace_inputs(child, prefix);
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| namespace_inputs(item, prefix)),
+        _ => {}
+    }
+}
+
+fn pager_action(container: &str, suffix: &str, title: &str, page: usize) -> Value {
+    json!({
+        "type": "Action.Submit",
+        "id": format!("{container}-{suffix}"),
+        "title": title,
+        "data": { PAGE_KEY: { "container": container, "page": page } }
+    })
+}
+
+fn warning(code: &str, message: String, path: String) -> ValidationIssue {
+    ValidationIssue {
+        code: code.to_string(),
+        message,
+        path,
+        severity: Some(IssueSeverity::Warning),
+    }
+}
@@ -0,0 +1,169 @@
+use serde_json::Value;
+
+use crate::model::{CardInteractionType, SecurityPolicy, ValidationIssue};
+use crate::urls;
+
+/// Result of enforcing a [`SecurityPolicy`] against a rendered card.
+#[derive(Debug, Default, Clone)]
+pub struct PolicyOutcome {
+    pub issues: Vec<ValidationIssue>,
+    /// Ids of actions that were removed from the card.
+    pub blocked_actions: Vec<String>,
+}
+
+/// Removes actions that the policy does not allow (by type, OpenUrl target, or the per-card
+/// action budget). Each removal is reported as a validation issue.
+pub fn enforce_policy(card: &mut Value, policy: &SecurityPolicy) -> PolicyOutcome {
+    let mut outcome = PolicyOutcome::default();
+    let mut seen = 0usize;
+    visit(card, "", policy, &mut seen, &mut outcome);
+    outcome
+}
+
+/// Rejects interactions whose action type is disallowed by the policy.
+pub fn interaction_allowed(policy: &SecurityPolicy, kind: &CardInteractionType) -> bool {
+    let action_type = match kind {
+        CardInteractionType::Submit => "Action.Submit",
+        CardInteractionType::Execute => "Action.Execute",
+        CardInteractionType::OpenUrl => "Action.OpenUrl",
+        CardInteractionType::ShowCard => "Action.ShowCard",
+        CardInteractionType::ToggleVisibility => "Action.ToggleVisibility",
+        // Draft autosaves and upload reports are not actions, and dialog openers were checked
+        // at render.
+        CardInteractionType::InputChanged
+        | CardInteractionType::OpenDialog
+        | CardInteractionType::UploadComplete => return true,
+    };
+    type_allowed(policy, action_type)
+}
+
+fn type_allowed(policy: &SecurityPolicy, action_type: &str) -> bool {
+    policy.allowed_action_types.is_empty()
+        || policy
+            .allowed_action_types
+            .iter()
+            .any(|allowed| allowed == action_type)
+}
+
+/// Checks an OpenUrl target against the allowed schemes and domains. Subdomains of an
+/// allowed domain are accepted; URLs whose host browsers would reject are not.
+pub fn url_allowed(policy: &SecurityPolicy, url: &str) -> bool {
+    let Some((scheme, _)) = url.trim().split_once(':') else {
+        return false;
+    };
+    let scheme = scheme.to_ascii_lowercase();
+    if !policy.allowed_url_schemes.is_empty()
+        && !policy
+            .allowed_url_schemes
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&scheme))
+    {
+        return false;
+    }
+    if policy.allowed_url_domains.is_empty() {
+        return true;
+    }
+    // The host is read as the browser will read it, so `https://evil.test\@example.com`
+    // counts as evil.test.
+    let Ok(host) = urls::url_host(url) else {
+        return false;
+    };
+    if host.is_empty() {
+        return false;
+    }
+    policy.allowed_url_domains.iter().any(|domain| {
+        let domain = domain.trim_start_matches("*.").to_ascii_lowercase();
+        host == domain || host.ends_with(&format!(".{domain}"))
+    })
+}
+
+fn block_reason(
+    action: &Value,
+    policy: &SecurityPolicy,
+    seen: &mut usize,
+) -> Option<(&'static str, String)> {
+    let kind = action
+        .get("type")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    if !type_allowed(policy, kind) {
+        return Some((
+            "policy-blocked-action",
+            format!("{kind} is not allowed by the security policy"),
+        ));
+    }
+    if kind == "Action.OpenUrl" {
+        let url = action
+            .get("url")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        if !url_allowed(policy, url) {
+            return Some((
+                "policy-blocked-url",
+                format!("OpenUrl target '{url}' is not allowed by the security policy"),
+            ));
+        }
+    }
+    *seen += 1;
+    if let Some(max) = policy.max_actions
+        && *seen > max
+    {
+        return Some((
+            "policy-max-actions",
+            format!("Card exceeds the maximum of {max} actions"),
+        ));
+    }
+    None
+}
+
+fn record(outcome: &mut PolicyOutcome, action: &Value, path: String, reason: (&str, String)) {
+    if let Some(id) = action.get("id").and_then(|v| v.as_str()) {
+        outcome.blocked_actions.push(id.to_string());
+    }
+    outcome.issues.push(ValidationIssue {
+        code: reason.0.to_string(),
+        message: reason.1,
+        path,
+        severity: None,
+    });
+}
+
+fn visit(
+    value: &mut Value,
+    path: &str,
+    policy: &SecurityPolicy,
+    seen: &mut usize,
+    outcome: &mut PolicyOutcome,
+) {
+    match value {
+        Value::Object(map) => {
+            if let Some(action) = map.get("selectAction")
+                && let Some(reason) = block_reason(action, policy, seen)
+            {
+                let action = map.remove("selectAction").unwrap_or_default();
+                record(outcome, &action, format!("{path}/selectAction"), reason);
+            }
+            if let Some(Value::Array(actions)) = map.get_mut("actions") {
+                let mut kept = Vec::with_capacity(actions.len());
+                for (idx, action) in std::mem::take(actions).into_iter().enumerate() {
+                    match block_reason(&action, policy, seen) {
+                        Some(reason) => {
+                            record(outcome, &action, format!("{path}/actions/{idx}"), reason)
+                        }
+                        None => kept.push(action),
+                    }
+                }
+                *actions = kept;
+            }
+            for (key, child) in map.iter_mut() {
+                visit(child, &format!("{path}/{key}"), policy, seen, outcome);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                visit(item, &format!("{path}/{idx}"), policy, seen, outcome);
+            }
+        }
+        _ => {}
+    }
+}
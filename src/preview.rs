@@ -0,0 +1,104 @@
+use crate::model::{
+    AdaptiveCardResult, CardPreview, IssueSeverity, PreviewFormat, ValidationIssue,
+};
+
+#[cfg(feature = "preview")]
+mod svg;
+
+/// Attaches an image preview of `result.rendered_card` (validate-only results have none).
+/// Formats that were not compiled in (`preview` / `preview-png` features) produce a
+/// `preview-unavailable` warning instead.
+pub fn attach_preview(result: &mut AdaptiveCardResult, format: &PreviewFormat, dark: bool) {
+    let Some(card) = result.rendered_card.as_ref() else {
+        return;
+    };
+    match build_preview(card, format, dark) {
+        Ok(preview) => result.preview = Some(preview),
+        Err(message) => result.validation_issues.push(ValidationIssue {
+            code: "preview-unavailable".into(),
+            message,
+            path: "/preview".into(),
+            severity: Some(IssueSeverity::Warning),
+        }),
+    }
+}
+
+#[cfg(feature = "preview")]
+fn build_preview(
+    card: &serde_json::Value,
+    format: &PreviewFormat,
+    dark: bool,
+) -> Result<CardPreview, String> {
+    let (markup, width, height) = svg::render_svg(card, dark);
+    match format {
+        PreviewFormat::Svg => Ok(CardPreview {
+            format: PreviewFormat::Svg,
+            media_type: "image/svg+xml".into(),
+            width,
+            height,
+            data: markup,
+        }),
+        PreviewFormat::Png => Ok(CardPreview {
+            format: PreviewFormat::Png,
+            media_type: "image/png".into(),
+            width,
+            height,
+            data: rasterize(&markup)?,
+        }),
+    }
+}
+
+#[cfg(not(feature = "preview"))]
+fn build_preview(
+    _card: &serde_json::Value,
+    _format: &PreviewFormat,
+    _dark: bool,
+) -> Result<CardPreview, String> {
+    Err("card previews require the `preview` feature".into())
+}
+
+/// Rasterizes the SVG with resvg and returns the PNG bytes base64-encoded. Text needs system
+/// fonts; without them the PNG still shows the layout boxes.
+#[cfg(feature = "preview-png")]
+fn rasterize(markup: &str) -> Result<String, String> {
+    use base64::Engine;
+    use resvg::{tiny_skia, usvg};
+
+    let mut options = usvg::Options::default();
+    let fonts = options.fontdb_mut();
+    fonts.load_system_fonts();
+    // The generic `sans-serif` defaults to Arial; fall back to whatever sans face is installed.
+    let fallback = fonts
+        .query(&ARIAL)
+        .is_none()
+        .then(|| {
+            fonts
+                .faces()
+                .flat_map(|face| face.families.iter().map(|(name, _)| name.clone()))
+                .find(|name| name.contains("Sans"))
+        })
+        .flatten();
+    if let Some(family) = fallback {
+        fonts.set_sans_serif_family(family);
+    }
+    let tree = usvg::Tree::from_str(markup, &options).map_err(|err| err.to_string())?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| "preview has zero size".to_string())?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    let png = pixmap.encode_png().map_err(|err| err.to_string())?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(png))
+}
+
+#[cfg(feature = "preview-png")]
+const ARIAL: resvg::usvg::fontdb::Query<'static> = resvg::usvg::fontdb::Query {
+    families: &[resvg::usvg::fontdb::Family::Name("Arial")],
+    weight: resvg::usvg::fontdb::Weight::NORMAL,
+    stretch: resvg::usvg::fontdb::Stretch::Normal,
+    style: resvg::usvg::fontdb::Style::Normal,
+};
+
+#[cfg(all(feature = "preview", not(feature = "preview-png")))]
+fn rasterize(_markup: &str) -> Result<String, String> {
+    Err("PNG previews require the `preview-png` feature".into())
+}
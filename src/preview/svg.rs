@@ -0,0 +1,697 @@
+//! Approximate Adaptive Card layout drawn as SVG. Text is measured with an average glyph
+//! width, images are placeholders, and only the host-config basics (spacing, font sizes,
+//! container styles, light/dark palette) are modelled.
+
+use serde_json::Value;
+
+pub const CARD_WIDTH: f32 = 400.0;
+const PADDING: f32 = 12.0;
+const FONT_FAMILY: &str = "Segoe UI, Helvetica Neue, Arial, sans-serif";
+/// Average glyph width as a fraction of the font size.
+const GLYPH_RATIO: f32 = 0.55;
+
+struct Palette {
+    background: &'static str,
+    emphasis: &'static str,
+    accent_background: &'static str,
+    border: &'static str,
+    text: &'static str,
+    subtle: &'static str,
+    accent: &'static str,
+    good: &'static str,
+    warning: &'static str,
+    attention: &'static str,
+    placeholder: &'static str,
+}
+
+const LIGHT: Palette = Palette {
+    background: "#ffffff",
+    emphasis: "#f2f2f2",
+    accent_background: "#dce5f7",
+    border: "#d0d0d0",
+    text: "#323130",
+    subtle: "#767676",
+    accent: "#0063b1",
+    good: "#107c10",
+    warning: "#a55200",
+    attention: "#c50f1f",
+    placeholder: "#e1e1e1",
+};
+
+const DARK: Palette = Palette {
+    background: "#1f1f1f",
+    emphasis: "#2b2b2b",
+    accent_background: "#1e3a5f",
+    border: "#4a4a4a",
+    text: "#f3f2f1",
+    subtle: "#a19f9d",
+    accent: "#4fa3e0",
+    good: "#54b054",
+    warning: "#f2b541",
+    attention: "#f1707b",
+    placeholder: "#3b3b3b",
+};
+
+/// Renders `card` and returns the SVG markup with its pixel width and height.
+pub fn render_svg(card: &Value, dark: bool) -> (String, u32, u32) {
+    let mut canvas = Canvas {
+        nodes: Vec::new(),
+        palette: if dark { &DARK } else { &LIGHT },
+    };
+    let inner = CARD_WIDTH - 2.0 * PADDING;
+    let mut y = canvas.items(body(card), PADDING, inner, PADDING);
+    if let Some(actions) = card.get("actions").and_then(Value::as_array)
+        && !actions.is_empty()
+    {
+        y = canvas.actions(actions, PADDING, inner, y + 12.0);
+    }
+    let height = (y + PADDING).ceil();
+    let palette = canvas.palette;
+    let svg = format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            r#"<rect width="{w}" height="{h}" rx="6" fill="{bg}" stroke="{border}"/>{nodes}</svg>"#
+        ),
+        w = CARD_WIDTH,
+        h = height,
+        bg = palette.background,
+        border = palette.border,
+        nodes = canvas.nodes.concat(),
+    );
+    (svg, CARD_WIDTH as u32, height as u32)
+}
+
+struct Canvas {
+    nodes: Vec<String>,
+    palette: &'static Palette,
+}
+
+impl Canvas {
+    /// Lays out `items` top to bottom inside the column starting at `x`; returns the next `y`.
+    fn items(&mut self, items: &[Value], x: f32, width: f32, mut y: f32) -> f32 {
+        let mut first = true;
+        for item in items {
+            if item.get("isVisible").and_then(Value::as_bool) == Some(false) {
+                continue;
+            }
+            if !first {
+                let gap = spacing(item.get("spacing").and_then(Value::as_str));
+                if item.get("separator").and_then(Value::as_bool) == Some(true) {
+                    let line_y = y + gap / 2.0;
+                    self.rect(x, line_y, width, 1.0, self.palette.border, None);
+                }
+                y += gap;
+            }
+            first = false;
+            y = self.element(item, x, width, y);
+        }
+        y
+    }
+
+    fn element(&mut self, item: &Value, x: f32, width: f32, y: f32) -> f32 {
+        let kind = item.get("type").and_then(Value::as_str).unwrap_or_default();
+        match kind {
+            "TextBlock" => self.text_block(item, x, width, y),
+            "RichTextBlock" => {
+                let text: String = item
+                    .get("inlines")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|inline| {
+                        inline
+                            .as_str()
+                            .or_else(|| inline.get("text").and_then(Value::as_str))
+                    })
+                    .collect();
+                let mut block = item.clone();
+                block["text"] = Value::String(text);
+                block["wrap"] = Value::Bool(true);
+                self.text_block(&block, x, width, y)
+            }
+            "Image" => {
+                let height = image_height(item);
+                let img_width = match item.get("width").and_then(Value::as_str) {
+                    Some(px) if px.ends_with("px") => px
+                        .trim_end_matches("px")
+                        .parse::<f32>()
+                        .unwrap_or(height)
+                        .min(width),
+                    _ if item.get("size").and_then(Value::as_str) == Some("Stretch") => width,
+                    _ => height.min(width),
+                };
+                let left = align(x, width, img_width, item);
+                self.image(left, y, img_width, height);
+                y + height
+            }
+            "ImageSet" => {
+                let images = children(item, "images");
+                let size = match item.get("imageSize").and_then(Value::as_str) {
+                    Some("Small") => 40.0,
+                    Some("Large") => 120.0,
+                    _ => 80.0,
+                };
+                let per_row = ((width + 8.0) / (size + 8.0)).floor().max(1.0) as usize;
+                let rows = images.len().div_ceil(per_row);
+                for idx in 0..images.len() {
+                    let col = (idx % per_row) as f32;
+                    let row = (idx / per_row) as f32;
+                    self.image(x + col * (size + 8.0), y + row * (size + 8.0), size, size);
+                }
+                y + rows as f32 * (size + 8.0) - if rows > 0 { 8.0 } else { 0.0 }
+            }
+            "Icon" => {
+                let left = align(x, width, 24.0, item);
+                let fill = self.color(item.get("color").and_then(Value::as_str));
+                self.nodes.push(format!(
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="10" fill="{fill}"/>"#,
+                    left + 12.0,
+                    y + 12.0
+                ));
+                y + 24.0
+            }
+            "Container" => self.container(item, children(item, "items"), x, width, y),
+            "ColumnSet" => self.columns(item, x, width, y),
+            "FactSet" => self.facts(item, x, width, y),
+            "ActionSet" => self.actions(children(item, "actions"), x, width, y),
+            "Table" => self.table(item, x, width, y),
+            kind if kind.starts_with("Input.") => self.input(item, kind, x, width, y),
+            other => {
+                let label = if other.is_empty() { "Unknown" } else { other };
+                self.rect(
+                    x,
+                    y,
+                    width,
+                    24.0,
+                    self.palette.emphasis,
+                    Some(self.palette.border),
+                );
+                self.text(
+                    x + 6.0,
+                    y + 16.0,
+                    12.0,
+                    "normal",
+                    self.palette.subtle,
+                    label,
+                );
+                y + 24.0
+            }
+        }
+    }
+
+    fn text_block(&mut self, item: &Value, x: f32, width: f32, y: f32) -> f32 {
+        let text = item.get("text").map(display_text).unwrap_or_default();
+        let size = font_size(item.get("size").and_then(Value::as_str));
+        let weight = match item.get("weight").and_then(Value::as_str) {
+            Some("Bolder") => "bold",
+            Some("Lighter") => "300",
+            _ => "normal",
+        };
+        let color = if item.get("isSubtle").and_then(Value::as_bool) == Some(true) {
+            self.palette.subtle
+        } else {
+            self.color(item.get("color").and_then(Value::as_str))
+        };
+        let wrap = item.get("wrap").and_then(Value::as_bool).unwrap_or(false);
+        let max_lines = item
+            .get("maxLines")
+            .and_then(Value::as_u64)
+            .filter(|n| *n > 0)
+            .map(|n| n as usize);
+        let lines = wrap_text(&text, width, size, if wrap { max_lines } else { Some(1) });
+        let line_height = (size * 1.35).round();
+        for (idx, line) in lines.iter().enumerate() {
+            let line_width = measure(line, size);
+            let left = align(x, width, line_width, item);
+            let baseline = y + idx as f32 * line_height + size;
+            self.text(left, baseline, size, weight, color, line);
+        }
+        y + lines.len().max(1) as f32 * line_height
+    }
+
+    fn container(&mut self, item: &Value, items: &[Value], x: f32, width: f32, y: f32) -> f32 {
+        let fill = match item.get("style").and_then(Value::as_str) {
+            Some("emphasis") => Some(self.palette.emphasis),
+            Some("accent") => Some(self.palette.accent_background),
+            Some("good") => Some("#dff6dd"),
+            Some("warning") => Some("#fff4ce"),
+            Some("attention") => Some("#fde7e9"),
+            _ => None,
+        };
+        let Some(fill) = fill else {
+            return self.items(items, x, width, y);
+        };
+        let index = self.nodes.len();
+        let bottom = self.items(items, x + 8.0, width - 16.0, y + 8.0) + 8.0;
+        let rect = rect_node(x, y, width, bottom - y, fill, None);
+        self.nodes.insert(index, rect);
+        bottom
+    }
+
+    fn columns(&mut self, item: &Value, x: f32, width: f32, y: f32) -> f32 {
+        let columns: Vec<&Value> = children(item, "columns")
+            .iter()
+            .filter(|c| c.get("isVisible").and_then(Value::as_bool) != Some(false))
+            .collect();
+        if columns.is_empty() {
+            return y;
+        }
+        let gap = 8.0;
+        let available = width - gap * (columns.len() - 1) as f32;
+        let fixed: Vec<Option<f32>> = columns
+            .iter()
+            .map(|c| match c.get("width") {
+                Some(Value::String(px)) if px.ends_with("px") => {
+                    px.trim_end_matches("px").parse::<f32>().ok()
+                }
+                Some(Value::String(auto)) if auto == "auto" => {
+                    Some(natural_width(children(c, "items")).min(available / 2.0))
+                }
+                _ => None,
+            })
+            .collect();
+        let weights: Vec<f32> = columns
+            .iter()
+            .map(|c| match c.get("width") {
+                Some(Value::Number(n)) => n.as_f64().unwrap_or(1.0) as f32,
+                Some(Value::String(s)) => s.parse::<f32>().unwrap_or(1.0),
+                _ => 1.0,
+            })
+            .collect();
+        let fixed_total: f32 = fixed.iter().flatten().sum();
+        let weight_total: f32 = weights
+            .iter()
+            .zip(&fixed)
+            .filter(|(_, f)| f.is_none())
+            .map(|(w, _)| w)
+            .sum::<f32>()
+            .max(f32::EPSILON);
+        let flexible = (available - fixed_total).max(0.0);
+        let mut left = x;
+        let mut bottom = y;
+        for ((column, fixed), weight) in columns.iter().zip(fixed).zip(weights) {
+            let col_width = fixed.unwrap_or(flexible * weight / weight_total);
+            let col_bottom = self.container(column, children(column, "items"), left, col_width, y);
+            bottom = bottom.max(col_bottom);
+            left += col_width + gap;
+        }
+        bottom
+    }
+
+    fn facts(&mut self, item: &Value, x: f32, width: f32, mut y: f32) -> f32 {
+        let title_width = (width * 0.35).round();
+        for fact in children(item, "facts") {
+            let title = fact.get("title").map(display_text).unwrap_or_default();
+            let value = fact.get("value").map(display_text).unwrap_or_default();
+            let value_lines = wrap_text(&value, width - title_width - 8.0, 14.0, None);
+            self.text(x, y + 14.0, 14.0, "bold", self.palette.text, &title);
+            for (idx, line) in value_lines.iter().enumerate() {
+                let baseline = y + 14.0 + idx as f32 * 19.0;
+                self.text(
+                    x + title_width + 8.0,
+                    baseline,
+                    14.0,
+                    "normal",
+                    self.palette.text,
+                    line,
+                );
+            }
+            y += value_lines.len().max(1) as f32 * 19.0 + 4.0;
+        }
+        y
+    }
+
+    fn table(&mut self, item: &Value, x: f32, width: f32, mut y: f32) -> f32 {
+        let rows = children(item, "rows");
+        let columns = rows
+            .iter()
+            .map(|row| children(row, "cells").len())
+            .max()
+            .unwrap_or(0)
+            .max(children(item, "columns").len())
+            .max(1);
+        let cell_width = width / columns as f32;
+        let grid = item.get("showGridLines").and_then(Value::as_bool) != Some(false);
+        for row in rows {
+            let mut bottom = y + 24.0;
+            for (idx, cell) in children(row, "cells").iter().enumerate() {
+                let left = x + idx as f32 * cell_width + 4.0;
+                let cell_bottom =
+                    self.items(children(cell, "items"), left, cell_width - 8.0, y + 4.0) + 4.0;
+                bottom = bottom.max(cell_bottom);
+            }
+            if grid {
+                self.rect(x, bottom, width, 1.0, self.palette.border, None);
+            }
+            y = bottom + 1.0;
+        }
+        y
+    }
+
+    fn input(&mut self, item: &Value, kind: &str, x: f32, width: f32, mut y: f32) -> f32 {
+        if let Some(label) = item.get("label").and_then(Value::as_str) {
+            let required = item.get("isRequired").and_then(Value::as_bool) == Some(true);
+            let label = if required {
+                format!("{label} *")
+            } else {
+                label.to_string()
+            };
+            self.text(x, y + 14.0, 14.0, "600", self.palette.text, &label);
+            y += 22.0;
+        }
+        let value = item
+            .get("value")
+            .map(display_text)
+            .filter(|v| !v.is_empty());
+        let placeholder = item.get("placeholder").map(display_text);
+        match kind {
+            "Input.Toggle" => {
+                let on = item
+                    .get("valueOn")
+                    .map(display_text)
+                    .unwrap_or("true".into());
+                let checked = value.as_deref() == Some(on.as_str());
+                self.rect(
+                    x,
+                    y + 2.0,
+                    16.0,
+                    16.0,
+                    self.palette.background,
+                    Some(self.palette.subtle),
+                );
+                if checked {
+                    self.rect(x + 4.0, y + 6.0, 8.0, 8.0, self.palette.accent, None);
+                }
+                let title = item.get("title").map(display_text).unwrap_or_default();
+                self.text(
+                    x + 24.0,
+                    y + 15.0,
+                    14.0,
+                    "normal",
+                    self.palette.text,
+                    &title,
+                );
+                y + 20.0
+            }
+            "Input.ChoiceSet" if item.get("style").and_then(Value::as_str) == Some("expanded") => {
+                for choice in children(item, "choices") {
+                    let title = choice.get("title").map(display_text).unwrap_or_default();
+                    let selected = value.as_deref().is_some_and(|v| {
+                        choice
+                            .get("value")
+                            .map(display_text)
+                            .is_some_and(|c| v.split(',').any(|s| s == c))
+                    });
+                    self.circle(x + 8.0, y + 10.0, 7.0, selected);
+                    self.text(
+                        x + 24.0,
+                        y + 15.0,
+                        14.0,
+                        "normal",
+                        self.palette.text,
+                        &title,
+                    );
+                    y += 22.0;
+                }
+                y
+            }
+            _ => {
+                let multiline = item.get("isMultiline").and_then(Value::as_bool) == Some(true);
+                let height = if multiline { 64.0 } else { 32.0 };
+                self.rect(
+                    x,
+                    y,
+                    width,
+                    height,
+                    self.palette.background,
+                    Some(self.palette.border),
+                );
+                let shown = if kind == "Input.ChoiceSet" {
+                    value.as_deref().map(|v| choice_title(item, v))
+                } else {
+                    value.clone()
+                };
+                let (text, color) = match (shown, placeholder) {
+                    (Some(text), _) => (text, self.palette.text),
+                    (None, Some(placeholder)) => (placeholder, self.palette.subtle),
+                    (None, None) => (String::new(), self.palette.subtle),
+                };
+                let line = wrap_text(&text, width - 36.0, 14.0, Some(1))
+                    .pop()
+                    .unwrap_or_default();
+                self.text(x + 8.0, y + 21.0, 14.0, "normal", color, &line);
+                if matches!(kind, "Input.ChoiceSet" | "Input.Date" | "Input.Time") {
+                    let glyph = if kind == "Input.ChoiceSet" {
+                        "▾"
+                    } else {
+                        "…"
+                    };
+                    self.text(
+                        x + width - 20.0,
+                        y + 21.0,
+                        14.0,
+                        "normal",
+                        self.palette.subtle,
+                        glyph,
+                    );
+                }
+                y + height
+            }
+        }
+    }
+
+    fn actions(&mut self, actions: &[Value], x: f32, width: f32, mut y: f32) -> f32 {
+        let mut left = x;
+        for action in actions {
+            if action.get("isVisible").and_then(Value::as_bool) == Some(false) {
+                continue;
+            }
+            let title = action.get("title").map(display_text).unwrap_or_default();
+            let button = (measure(&title, 14.0) + 32.0).clamp(64.0, width);
+            if left > x && left + button > x + width {
+                left = x;
+                y += 40.0;
+            }
+            let enabled = action.get("isEnabled").and_then(Value::as_bool) != Some(false);
+            let positive = action.get("style").and_then(Value::as_str) == Some("positive");
+            let destructive = action.get("style").and_then(Value::as_str) == Some("destructive");
+            let (fill, color) = match (enabled, positive, destructive) {
+                (false, _, _) => (self.palette.emphasis, self.palette.subtle),
+                (true, true, _) => (self.palette.accent, "#ffffff"),
+                (true, _, true) => (self.palette.attention, "#ffffff"),
+                _ => (self.palette.background, self.palette.accent),
+            };
+            self.rect(left, y, button, 32.0, fill, Some(self.palette.border));
+            let label = wrap_text(&title, button - 16.0, 14.0, Some(1))
+                .pop()
+                .unwrap_or_default();
+            let text_left = left + (button - measure(&label, 14.0)) / 2.0;
+            self.text(text_left, y + 21.0, 14.0, "600", color, &label);
+            left += button + 8.0;
+        }
+        y + 32.0
+    }
+
+    fn image(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.rect(x, y, width, height, self.palette.placeholder, None);
+        let (cx, cy) = (x + width / 2.0, y + height / 2.0);
+        let r = (width.min(height) / 6.0).max(3.0);
+        self.nodes.push(format!(
+            r#"<circle cx="{cx:.1}" cy="{cy:.1}" r="{r:.1}" fill="{}"/>"#,
+            self.palette.border
+        ));
+    }
+
+    fn circle(&mut self, cx: f32, cy: f32, r: f32, filled: bool) {
+        self.nodes.push(format!(
+            r#"<circle cx="{cx:.1}" cy="{cy:.1}" r="{r:.1}" fill="{}" stroke="{}"/>"#,
+            if filled {
+                self.palette.accent
+            } else {
+                self.palette.background
+            },
+            self.palette.subtle
+        ));
+    }
+
+    fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, fill: &str, stroke: Option<&str>) {
+        self.nodes.push(rect_node(x, y, w, h, fill, stroke));
+    }
+
+    fn text(&mut self, x: f32, y: f32, size: f32, weight: &str, fill: &str, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.nodes.push(format!(
+            r#"<text x="{x:.1}" y="{y:.1}" font-family="{FONT_FAMILY}" font-size="{size}" font-weight="{weight}" fill="{fill}">{}</text>"#,
+            escape(text)
+        ));
+    }
+
+    fn color(&self, name: Option<&str>) -> &'static str {
+        match name {
+            Some("Accent") => self.palette.accent,
+            Some("Good") => self.palette.good,
+            Some("Warning") => self.palette.warning,
+            Some("Attention") => self.palette.attention,
+            _ => self.palette.text,
+        }
+    }
+}
+
+fn rect_node(x: f32, y: f32, w: f32, h: f32, fill: &str, stroke: Option<&str>) -> String {
+    let stroke = stroke
+        .map(|s| format!(r#" stroke="{s}""#))
+        .unwrap_or_default();
+    format!(
+        r#"<rect x="{x:.1}" y="{y:.1}" width="{w:.1}" height="{h:.1}" rx="2" fill="{fill}"{stroke}/>"#
+    )
+}
+
+fn body(card: &Value) -> &[Value] {
+    children(card, "body")
+}
+
+fn children<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn display_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.replace("**", "").replace("__", ""),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn choice_title(item: &Value, value: &str) -> String {
+    value
+        .split(',')
+        .map(|v| {
+            children(item, "choices")
+                .iter()
+                .find(|c| c.get("value").map(display_text).as_deref() == Some(v))
+                .and_then(|c| c.get("title"))
+                .map(display_text)
+                .unwrap_or_else(|| v.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn spacing(name: Option<&str>) -> f32 {
+    match name {
+        Some("None") => 0.0,
+        Some("Small") => 4.0,
+        Some("Medium") => 12.0,
+        Some("Large") => 16.0,
+        Some("ExtraLarge") => 24.0,
+        Some("Padding") => 12.0,
+        _ => 8.0,
+    }
+}
+
+fn font_size(name: Option<&str>) -> f32 {
+    match name {
+        Some("Small") => 12.0,
+        Some("Medium") => 17.0,
+        Some("Large") => 21.0,
+        Some("ExtraLarge") => 26.0,
+        _ => 14.0,
+    }
+}
+
+fn image_height(item: &Value) -> f32 {
+    if let Some(px) = item.get("height").and_then(Value::as_str)
+        && let Some(px) = px.strip_suffix("px")
+        && let Ok(px) = px.parse::<f32>()
+    {
+        return px;
+    }
+    match item.get("size").and_then(Value::as_str) {
+        Some("Small") => 40.0,
+        Some("Medium") => 80.0,
+        Some("Large") => 160.0,
+        Some("Stretch") => 180.0,
+        _ => 100.0,
+    }
+}
+
+fn align(x: f32, width: f32, content: f32, item: &Value) -> f32 {
+    match item.get("horizontalAlignment").and_then(Value::as_str) {
+        Some("Center") | Some("center") => x + (width - content).max(0.0) / 2.0,
+        Some("Right") | Some("right") => x + (width - content).max(0.0),
+        _ => x,
+    }
+}
+
+/// Width an `auto` column needs: its widest image or single-line text.
+fn natural_width(items: &[Value]) -> f32 {
+    items
+        .iter()
+        .map(|item| match item.get("type").and_then(Value::as_str) {
+            Some("Image") => image_height(item),
+            Some("TextBlock") => measure(
+                &item.get("text").map(display_text).unwrap_or_default(),
+                font_size(item.get("size").and_then(Value::as_str)),
+            ),
+            _ => 100.0,
+        })
+        .fold(0.0, f32::max)
+}
+
+fn measure(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * GLYPH_RATIO
+}
+
+/// Greedy word wrap by estimated width. When `max_lines` cuts the text, the last kept line
+/// ends with an ellipsis.
+fn wrap_text(text: &str, width: f32, size: f32, max_lines: Option<usize>) -> Vec<String> {
+    let max_chars = ((width / (size * GLYPH_RATIO)).floor() as usize).max(1);
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word = word.to_string();
+            while word.chars().count() > max_chars {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                let head: String = word.chars().take(max_chars).collect();
+                word = word.chars().skip(max_chars).collect();
+                lines.push(head);
+            }
+            let needed =
+                line.chars().count() + usize::from(!line.is_empty()) + word.chars().count();
+            if needed > max_chars && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+    if let Some(max) = max_lines
+        && lines.len() > max
+    {
+        lines.truncate(max);
+        if let Some(last) = lines.last_mut() {
+            let kept: String = last.chars().take(max_chars.saturating_sub(1)).collect();
+            *last = format!("{}…", kept.trim_end());
+        }
+    }
+    lines
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,92 @@
+use serde_json::{Map, Value, json};
+
+use crate::expression::stringify_value;
+use crate::inputs;
+
+/// Template key holding an optional card shown in place of the form after a submit.
+pub const POST_SUBMIT_VIEW_KEY: &str = "post_submit_view";
+
+/// Removes the `post_submit_view` section from the card root so it is neither bound nor
+/// delivered with the interactive card.
+pub fn take_post_submit_view(card: &mut Value) -> Option<Value> {
+    card.as_object_mut()?.remove(POST_SUBMIT_VIEW_KEY)
+}
+
+/// Derives a read-only receipt from the rendered form: every input becomes a FactSet row
+/// showing the submitted value (ChoiceSet values as their titles) and all actions are removed.
+pub fn build_receipt(card: &Value, submitted: &Value) -> Value {
+    let display = inputs::display_inputs(submitted, card);
+    let mut receipt = card.clone();
+    if let Value::Object(root) = &mut receipt {
+        root.remove("actions");
+    }
+    strip_interactive(&mut receipt, &display);
+    receipt
+}
+
+fn strip_interactive(value: &mut Value, display: &Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove("selectAction");
+            for child in map.values_mut() {
+                strip_interactive(child, display);
+            }
+        }
+        Value::Array(items) => {
+            // Consecutive inputs collapse into a single FactSet.
+            let mut out: Vec<Value> = Vec::with_capacity(items.len());
+            let mut facts_open = false;
+            for mut item in std::mem::take(items) {
+                if item.get("type").and_then(|t| t.as_str()) == Some("ActionSet") {
+                    continue;
+                }
+                match input_fact(&item, display) {
+                    Some(fact) => {
+                        if facts_open
+                            && let Some(Value::Array(facts)) =
+                                out.last_mut().and_then(|last| last.get_mut("facts"))
+                        {
+                            facts.push(fact);
+                        } else {
+                            out.push(json!({ "type": "FactSet", "facts": [fact] }));
+                        }
+                        facts_open = true;
+                    }
+                    None => {
+                        strip_interactive(&mut item, display);
+                        out.push(item);
+                        facts_open = false;
+                    }
+                }
+            }
+            *items = out;
+        }
+        _ => {}
+    }
+}
+
+fn input_fact(element: &Value, display: &Value) -> Option<Value> {
+    let map = element.as_object()?;
+    map.get("type")?.as_str()?.strip_prefix("Input.")?;
+    let id = map.get("id")?.as_str()?;
+    let title = label(map).unwrap_or(id);
+    let value = match display.get(id) {
+        None | Some(Value::Null) => "-".to_string(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(stringify_value)
+            .collect::<Vec<_>>()
+            .join(", "),
+        Some(Value::Bool(true)) => "Yes".to_string(),
+        Some(Value::Bool(false)) => "No".to_string(),
+        Some(other) => stringify_value(other),
+    };
+    Some(json!({ "title": title, "value": value }))
+}
+
+fn label(map: &Map<String, Value>) -> Option<&str> {
+    ["label", "title", "placeholder"]
+        .iter()
+        .find_map(|key| map.get(*key).and_then(|v| v.as_str()))
+        .filter(|s| !s.is_empty())
+}
@@ -0,0 +1,135 @@
+use serde_json::Value;
+
+use crate::audit_log::REDACTED;
+use crate::model::{RedactionMode, RedactionPolicy};
+use crate::routing::wildcard_match;
+
+/// Characters left visible at the end of masked strings.
+const MASK_VISIBLE: usize = 4;
+/// blake3 key derivation context for the policy's `key`.
+const KEY_CONTEXT: &str = "greentic component-adaptive-card 2024 input redaction";
+
+/// Returns `inputs` with every value selected by the policy's input id patterns or paths
+/// hashed or masked. Without a policy the inputs are returned unchanged.
+pub fn redact_inputs(inputs: &Value, policy: Option<&RedactionPolicy>) -> Value {
+    apply(inputs, policy, Treatment::Redact)
+}
+
+/// Returns `inputs` without the values the policy selects (`null` inside arrays), for
+/// drafts that are restored into the card: a hash or mask would be shown as the input's
+/// value.
+pub fn omit_redacted(inputs: &Value, policy: Option<&RedactionPolicy>) -> Value {
+    apply(inputs, policy, Treatment::Omit)
+}
+
+/// Returns `inputs` as shown back in a receipt: masked values under `mask`, else without the
+/// values the policy selects (a hash means nothing to the reader).
+pub fn receipt_inputs(inputs: &Value, policy: Option<&RedactionPolicy>) -> Value {
+    match policy.map(|policy| policy.mode) {
+        Some(RedactionMode::Mask) => redact_inputs(inputs, policy),
+        _ => omit_redacted(inputs, policy),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Treatment {
+    Redact,
+    Omit,
+}
+
+fn apply(inputs: &Value, policy: Option<&RedactionPolicy>, treatment: Treatment) -> Value {
+    let mut redacted = inputs.clone();
+    if let Some(policy) = policy
+        && (!policy.inputs.is_empty() || !policy.paths.is_empty())
+    {
+        walk(&mut redacted, &mut Vec::new(), policy, treatment);
+    }
+    redacted
+}
+
+fn walk(value: &mut Value, path: &mut Vec<String>, policy: &RedactionPolicy, treatment: Treatment) {
+    let replace = |value: &Value| match treatment {
+        Treatment::Redact => Some(redact_value(value, policy)),
+        Treatment::Omit => None,
+    };
+    match value {
+        Value::Object(map) => {
+            let keys: Vec<String> = map.keys().cloned().collect();
+            for key in keys {
+                path.push(key.clone());
+                if selected(path, policy) {
+                    match map.get(&key).and_then(replace) {
+                        Some(replacement) => {
+                            map.insert(key, replacement);
+                        }
+                        None => {
+                            map.remove(&key);
+                        }
+                    }
+                } else if let Some(child) = map.get_mut(&key) {
+                    walk(child, path, policy, treatment);
+                }
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                path.push(idx.to_string());
+                if selected(path, policy) {
+                    *item = replace(item).unwrap_or(Value::Null);
+                } else {
+                    walk(item, path, policy, treatment);
+                }
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn selected(path: &[String], policy: &RedactionPolicy) -> bool {
+    let by_id = |pattern: &String| path.len() == 1 && wildcard_match(pattern, &path[0]);
+    let by_path = |pattern: &String| {
+        let segments: Vec<&str> = pattern.split('.').collect();
+        segments.len() == path.len()
+            && segments
+                .iter()
+                .zip(path)
+                .all(|(pattern, segment)| wildcard_match(pattern, segment))
+    };
+    policy.inputs.iter().any(by_id) || policy.paths.iter().any(by_path)
+}
+
+fn redact_value(value: &Value, policy: &RedactionPolicy) -> Value {
+    if value.is_null() {
+        return Value::Null;
+    }
+    match policy.mode {
+        RedactionMode::Hash => Value::String(match policy.key.as_deref() {
+            Some(key) => keyed_hash(value, key),
+            None => REDACTED.to_string(),
+        }),
+        RedactionMode::Mask => {
+            let text = match value {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            let chars: Vec<char> = text.chars().collect();
+            let visible = if chars.len() > 2 * MASK_VISIBLE {
+                MASK_VISIBLE
+            } else {
+                0
+            };
+            let masked: String = std::iter::repeat_n('*', chars.len() - visible)
+                .chain(chars[chars.len() - visible..].iter().copied())
+                .collect();
+            Value::String(masked)
+        }
+    }
+}
+
+fn keyed_hash(value: &Value, key: &str) -> String {
+    let key = blake3::derive_key(KEY_CONTEXT, key.as_bytes());
+    let bytes = serde_json::to_vec(value).unwrap_or_default();
+    format!("blake3:{}", blake3::keyed_hash(&key, &bytes).to_hex())
+}
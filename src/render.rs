@@ -1,15 +1,57 @@
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use handlebars::Handlebars;
-use serde_json::{Map, Value};
-
-use crate::asset_resolver::resolve_with_host;
+use serde_json::{Map, Value, json};
+
+use crate::actions;
+use crate::asset_resolver::{self, resolve_with_host};
+use crate::audit;
+use crate::budget;
+use crate::cache::{self, CachedFile, FileStamp, ShardedCache};
+use crate::charts;
+use crate::components;
+use crate::dependencies;
+use crate::derived;
+use crate::dialogs;
+use crate::elements;
 use crate::error::ComponentError;
-use crate::expression::{ExpressionEngine, SimpleExpressionEngine, stringify_value};
+use crate::expression::{
+    self, ExpressionEngine, SimpleExpressionEngine, check_limits, escaped_placeholder,
+    placeholder_end, stringify_value,
+};
+use crate::fallback;
+use crate::format;
+use crate::hooks;
+use crate::i18n::{self, StringCatalogs};
+use crate::includes;
+use crate::inputs;
+use crate::limits::{self, Deadline, Limits};
+use crate::markdown;
+use crate::migrations;
 use crate::model::{
-    AdaptiveCardInvocation, CardFeatureSummary, CardSource, CardSpec, ValidationIssue,
+    AdaptiveCardInvocation, CardDialog, CardFeatureSummary, CardSchedule, CardSource, CardSpec,
+    ColorScheme, DataClassification, HydrationRequest, IssueSeverity, TargetProfile,
+    TimeBudgetPolicy, ValidationIssue,
 };
+use crate::outlook;
+use crate::pagination;
+use crate::policy;
+use crate::receipt;
+use crate::rich_text;
+use crate::roles;
+use crate::rules::{self, InputRule};
+use crate::sanitize;
+use crate::schedule;
+use crate::show_card;
+use crate::tables;
+use crate::theme;
+use crate::uploads;
+use crate::urls;
+use crate::views;
+use crate::visibility;
 
 #[derive(Debug, Default, Clone)]
 pub struct BindingSummary {
@@ -17,6 +59,8 @@ pub struct BindingSummary {
     pub placeholder_replacements: u64,
     pub expression_evaluations: u64,
     pub missing_paths: u64,
+    /// Elements and actions dropped unbound because the time budget ran out.
+    pub elements_dropped: usize,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -33,18 +77,479 @@ pub struct RenderOutcome {
     pub validation_issues: Vec<ValidationIssue>,
     pub asset_resolution: AssetResolution,
     pub binding_summary: BindingSummary,
+    pub subcard: Option<Value>,
+    /// Action ids removed by the security policy.
+    pub blocked_actions: Vec<String>,
+    /// Action ids removed because the viewer lacks the roles in their `visibleToRoles`.
+    pub restricted_actions: Vec<String>,
+    /// Input ids removed because the viewer lacks the roles in their `visibleToRoles`.
+    pub restricted_inputs: BTreeSet<String>,
+    /// Action ids disabled through `ui.disabled_actions` or a false `isEnabled`.
+    pub disabled_actions: BTreeSet<String>,
+    /// Per-scheme cards when rendering both color schemes.
+    pub variants: BTreeMap<String, Value>,
+    /// Elements and actions removed by the security policy or budget truncation.
+    pub dropped_elements: usize,
+    /// Unbound `post_submit_view` section taken from the card template.
+    pub post_submit_view: Option<Value>,
+    /// Dialogs declared through `openDialog`, keyed by opener action id.
+    pub dialogs: BTreeMap<String, CardDialog>,
+    /// Hash of the expanded template together with the data bound into it.
+    pub fingerprint: Option<String>,
+    /// `$derived` state expressions taken from the template, keyed by state path.
+    pub derived: BTreeMap<String, String>,
+    /// `$rules` cross-input validation rules taken from the template.
+    pub rules: Vec<InputRule>,
+    /// `dataClassification` of the card's inputs, keyed by input id.
+    pub classifications: BTreeMap<String, DataClassification>,
+    /// Whether the template set `$nestInputs`.
+    pub nest_inputs: bool,
+    /// `$schedule` taken from the template.
+    pub schedule: Option<CardSchedule>,
 }
 
 pub fn render_card(inv: &AdaptiveCardInvocation) -> Result<RenderOutcome, ComponentError> {
+    let deadline = Deadline::start(inv.time_budget.as_ref());
+    match inv.color_scheme {
+        Some(ColorScheme::Both) => {
+            let mut light = render_variant(inv, Some(&ColorScheme::Light), deadline)?;
+            let dark = render_variant(inv, Some(&ColorScheme::Dark), deadline)?;
+            light.variants.insert("light".into(), light.card.clone());
+            light.variants.insert("dark".into(), dark.card);
+            Ok(light)
+        }
+        ref scheme => render_variant(inv, scheme.as_ref(), deadline),
+    }
+}
+
+/// Re-binds only the template strings that depend on `hydration.changed_paths` and patches
+/// them into the previously bound card, returning the patched card and the number of
+/// re-bound values. Each re-bound value goes through the post-binding passes before it is
+/// patched in (see [`secure_rebound_value`]). Returns `None` when a full render is required:
+/// a template with Handlebars, includes or a layout, paginated containers or interactive
+/// tables, custom elements with a registered renderer, registered post-render hooks,
+/// `color_scheme: both`, a re-bound `visibleToRoles`, a pointer whose arrays in the previous
+/// card no longer line up with the template (elements dropped by the policy, role pruning,
+/// fallbacks, or the budget, or inserted by ShowCard expansion), a re-bound value that a
+/// post-binding pass reports or reshapes beyond the value, or a patched card over its
+/// budget.
+pub fn hydrate_card(
+    inv: &AdaptiveCardInvocation,
+    hydration: &HydrationRequest,
+) -> Result<Option<(Value, usize)>, ComponentError> {
+    let scheme = match inv.color_scheme {
+        Some(ColorScheme::Both) => return Ok(None),
+        ref scheme => scheme.as_ref(),
+    };
+    let (mut template, _) = resolve_card(inv)?;
+    receipt::take_post_submit_view(&mut template);
+    derived::take_derived(&mut template);
+    rules::take_rules(&mut template);
+    inputs::take_nest_inputs(&mut template);
+    migrations::take_state_spec(&mut template);
+    schedule::take_schedule(&mut template);
+    let transformers = hooks::take_transformers(&mut template);
+    let transformed;
+    let inv = if transformers.is_empty() {
+        inv
+    } else {
+        transformed = hooks::apply_data_transformers(inv, &transformers).0;
+        &transformed
+    };
+    if has_handlebars(&template)
+        || includes::uses_includes(&template)
+        || pagination::is_paginated(&template)
+        || tables::has_interactive_tables(&template)
+        || components::uses_custom_elements(&template)
+        || hooks::has_post_render_hooks()
+    {
+        return Ok(None);
+    }
+    let strings = i18n::resolve_strings(inv.locale.as_deref(), &load_string_catalogs(inv)?);
+    i18n::apply_loc_markers(&mut template, &strings);
+    theme::apply_theme_tokens(&mut template, &theme::resolve_theme(inv, scheme));
+    let ctx = BindingContext::from_invocation(inv, strings);
+    let engine = SimpleExpressionEngine;
+    let mut summary = BindingSummary::default();
+    let mut card = hydration.previous_card.clone();
+    let mut rebound = 0;
+    for (pointer, deps) in dependencies::collect_dependencies(&template) {
+        let changed = deps.iter().any(|dep| {
+            hydration
+                .changed_paths
+                .iter()
+                .any(|path| dependencies::affected_by(dep, path))
+        });
+        if !changed {
+            continue;
+        }
+        let Some(mut value) = template.pointer(&pointer).cloned() else {
+            continue;
+        };
+        if pointer
+            .split('/')
+            .any(|segment| segment == "visibleToRoles")
+            || !same_layout(&template, &card, &pointer)
+        {
+            return Ok(None);
+        }
+        apply_bindings(&mut value, &ctx, &engine, &mut summary)?;
+        let Some(value) = secure_rebound_value(inv, scheme, &card, &pointer, value)? else {
+            return Ok(None);
+        };
+        let Some(target) = card.pointer_mut(&pointer) else {
+            return Ok(None);
+        };
+        *target = value;
+        rebound += 1;
+    }
+    elements::normalize_elements(&mut card);
+    views::normalize_refresh(&mut card, inv);
+    if let Some(card_budget) = inv.budget.as_ref()
+        && !budget::enforce_budget(&mut card.clone(), card_budget).is_empty()
+    {
+        return Ok(None);
+    }
+    Ok(Some((card, rebound)))
+}
+
+/// Whether every array on the way to `pointer` has as many entries in `card` as in
+/// `template`, each of the same `type`, so the pointer addresses the same element in both.
+fn same_layout(template: &Value, card: &Value, pointer: &str) -> bool {
+    let (mut template, mut card) = (template, card);
+    for segment in pointer.split('/').skip(1) {
+        if let (Value::Array(expected), Value::Array(actual)) = (template, card)
+            && (expected.len() != actual.len()
+                || expected
+                    .iter()
+                    .zip(actual)
+                    .any(|(expected, actual)| expected.get("type") != actual.get("type")))
+        {
+            return false;
+        }
+        let key = segment.replace("~1", "/").replace("~0", "~");
+        let next = match (template, card) {
+            (Value::Array(expected), Value::Array(actual)) => key
+                .parse::<usize>()
+                .ok()
+                .and_then(|idx| Some((expected.get(idx)?, actual.get(idx)?))),
+            (Value::Object(expected), Value::Object(actual)) => {
+                expected.get(&key).zip(actual.get(&key))
+            }
+            _ => None,
+        };
+        let Some(next) = next else {
+            return false;
+        };
+        (template, card) = next;
+    }
+    true
+}
+
+/// Runs a re-bound value through the passes a full render applies after binding: it is
+/// placed alone in a copy of the `type` of its enclosing object in `card`, which then goes
+/// through chart preparation, fallbacks, asset URL resolution, deep links, sanitization,
+/// Markdown, RichTextBlock normalization, dialogs, the Outlook conversion, and the security
+/// policy. `None` when any pass reports an issue, drops the value, or changes anything beyond
+/// the value itself (a fallback, a generated chart summary, a converted action), since only a
+/// full render can apply that to the rest of the card.
+fn secure_rebound_value(
+    inv: &AdaptiveCardInvocation,
+    scheme: Option<&ColorScheme>,
+    card: &Value,
+    pointer: &str,
+    value: Value,
+) -> Result<Option<Value>, ComponentError> {
+    let segments: Vec<&str> = pointer.split('/').skip(1).collect();
+    let owner = (0..segments.len())
+        .rev()
+        .find(|&len| {
+            card.pointer(&pointer_prefix(&segments[..len]))
+                .and_then(|node| node.get("type"))
+                .is_some()
+        })
+        .unwrap_or_default();
+    let mut isolated = json!({});
+    if let Some(kind) = card
+        .pointer(&pointer_prefix(&segments[..owner]))
+        .and_then(|node| node.get("type"))
+    {
+        isolated["type"] = kind.clone();
+    }
+    let relative = pointer_prefix(&segments[owner..]);
+    let mut slot = &mut isolated;
+    for (idx, segment) in segments[owner..].iter().enumerate() {
+        let key = segment.replace("~1", "/").replace("~0", "~");
+        let next_is_index = segments[owner..]
+            .get(idx + 1)
+            .is_some_and(|next| next.parse::<usize>().is_ok());
+        let empty = if next_is_index { json!([]) } else { json!({}) };
+        slot = match slot {
+            Value::Array(items) => {
+                let Ok(index) = key.parse::<usize>() else {
+                    return Ok(None);
+                };
+                if items.len() <= index {
+                    items.resize(index + 1, Value::Null);
+                }
+                items[index] = empty;
+                &mut items[index]
+            }
+            Value::Object(map) => map.entry(key).or_insert(empty),
+            _ => return Ok(None),
+        };
+    }
+    *slot = value;
+    let before = isolated.clone();
+    let profile = inv.target_profile.as_ref();
+    let mut issues = charts::prepare_charts(&mut isolated);
+    let (fallbacks, fallback_issues) = fallback::apply_fallbacks(&mut isolated, profile);
+    if !fallbacks.is_empty() {
+        return Ok(None);
+    }
+    issues.extend(fallback_issues);
+    issues.extend(
+        asset_resolver::resolve_asset_urls(
+            &mut isolated,
+            scheme,
+            inv.card_spec.asset_registry.as_ref(),
+        )
+        .map_err(|e| ComponentError::Asset(e.message))?,
+    );
+    issues.extend(urls::apply_deep_links(&mut isolated, profile));
+    if inv.sanitize {
+        issues.extend(sanitize::sanitize_card(&mut isolated));
+    }
+    markdown::apply_markdown(&mut isolated, &inv.markdown);
+    rich_text::normalize_rich_text(&mut isolated);
+    let (dialogs, dialog_issues) = dialogs::apply_dialogs(&mut isolated, profile);
+    if !dialogs.is_empty() {
+        return Ok(None);
+    }
+    issues.extend(dialog_issues);
+    issues.extend(outlook::to_actionable_message(&mut isolated, profile));
+    if without(&isolated, &relative) != without(&before, &relative) {
+        return Ok(None);
+    }
+    if let Some(policy) = inv.security.as_ref() {
+        let is_action = isolated
+            .get("type")
+            .and_then(Value::as_str)
+            .is_some_and(|kind| kind.starts_with("Action."));
+        let mut scope = if is_action {
+            json!({ "actions": [isolated.clone()] })
+        } else {
+            isolated.clone()
+        };
+        issues.extend(policy::enforce_policy(&mut scope, policy).issues);
+    }
+    issues.extend(urls::check_open_urls(&isolated, inv.security.as_ref()));
+    if !issues.is_empty() {
+        return Ok(None);
+    }
+    Ok(isolated.pointer(&relative).cloned())
+}
+
+/// `value` with the node at `pointer` blanked out.
+fn without(value: &Value, pointer: &str) -> Value {
+    let mut value = value.clone();
+    if let Some(node) = value.pointer_mut(pointer) {
+        *node = Value::Null;
+    }
+    value
+}
+
+fn pointer_prefix(segments: &[&str]) -> String {
+    segments
+        .iter()
+        .map(|segment| format!("/{segment}"))
+        .collect()
+}
+
+fn render_variant(
+    inv: &AdaptiveCardInvocation,
+    scheme: Option<&ColorScheme>,
+    deadline: Option<Deadline>,
+) -> Result<RenderOutcome, ComponentError> {
     let mut summary = BindingSummary::default();
     let (mut card, asset_resolution) = resolve_card(inv)?;
+    let mut load_partial = |name: &str| {
+        load_with_candidates(
+            name,
+            candidate_catalog_paths(name, &inv.card_spec)?,
+            &cache::ASSETS,
+        )
+        .map(|(partial, _)| partial)
+    };
+    let layout_issues = includes::apply_layout(&mut card, &mut load_partial)?;
+    includes::expand_includes(&mut card, &mut load_partial)?;
+    let limits = Limits::resolve(inv.limits.as_ref());
+    limits::check_nesting(&card, limits.max_nesting)?;
+    let template_hash = hash_json(&card).unwrap_or_default();
+    let fingerprint = fingerprint(&template_hash, inv);
+    let post_submit_view = receipt::take_post_submit_view(&mut card);
+    let derived = derived::take_derived(&mut card);
+    let rules = rules::take_rules(&mut card);
+    let nest_inputs = inputs::take_nest_inputs(&mut card);
+    let mut token_issues = layout_issues;
+    migrations::take_state_spec(&mut card);
+    let (schedule, schedule_issues) = schedule::take_schedule(&mut card);
+    token_issues.extend(schedule_issues);
+    let transformers = hooks::take_transformers(&mut card);
+    let transformed;
+    let inv = if transformers.is_empty() {
+        inv
+    } else {
+        let (data_inv, issues) = hooks::apply_data_transformers(inv, &transformers);
+        token_issues.extend(issues);
+        transformed = data_inv;
+        &transformed
+    };
     apply_handlebars(&mut card, inv, &mut summary)?;
-    let ctx = BindingContext::from_invocation(inv);
+    let strings = i18n::resolve_strings(inv.locale.as_deref(), &load_string_catalogs(inv)?);
+    token_issues.extend(i18n::apply_loc_markers(&mut card, &strings));
+    token_issues.extend(theme::apply_theme_tokens(
+        &mut card,
+        &theme::resolve_theme(inv, scheme),
+    ));
+    let no_params = Value::Null;
+    token_issues.extend(components::render_custom_elements(
+        &mut card,
+        &components::ElementContext {
+            payload: &inv.payload,
+            state: &inv.state,
+            session: &inv.session,
+            params: inv.card_spec.template_params.as_ref().unwrap_or(&no_params),
+            locale: inv.locale.as_deref(),
+        },
+    ));
+    let mut ctx = BindingContext::from_invocation(inv, strings);
+    ctx.deadline = deadline;
     let engine = SimpleExpressionEngine;
     apply_bindings(&mut card, &ctx, &engine, &mut summary)?;
-
-    let features = analyze_features(&card);
-    let validation_issues = validate_card(&card);
+    let mut repeated = 0;
+    token_issues.extend(pagination::apply_pagination(
+        &mut card,
+        &inv.state,
+        &mut |template, item| {
+            repeated += 1;
+            if repeated > limits.max_repeat {
+                return Err(ComponentError::LimitExceeded {
+                    limit: "maxRepeat",
+                    max: limits.max_repeat,
+                    detail: "too many entries rendered through itemTemplate".into(),
+                });
+            }
+            if out_of_time(template, &ctx)? {
+                summary.elements_dropped += 1;
+                return Ok(None);
+            }
+            let mut element = template.clone();
+            apply_bindings(&mut element, &ctx.with_item(item), &engine, &mut summary)?;
+            Ok(Some(element))
+        },
+    )?);
+    if summary.elements_dropped > 0 {
+        token_issues.push(ValidationIssue {
+            code: "time-budget-exceeded".into(),
+            message: format!(
+                "The time budget ran out; {} unbound element(s) and action(s) were dropped",
+                summary.elements_dropped
+            ),
+            path: "/".into(),
+            severity: Some(IssueSeverity::Warning),
+        });
+    }
+    token_issues.extend(components::expand_components(&mut card));
+    // Bindings and repeaters may have spliced in deeper subtrees from the data.
+    limits::check_nesting(&card, limits.max_nesting)?;
+    tables::apply_table_state(&mut card, &inv.state);
+    token_issues.extend(elements::normalize_elements(&mut card));
+    let instance_id = inv.card_instance_id.as_deref().or(inv
+        .interaction
+        .as_ref()
+        .map(|i| i.card_instance_id.as_str()));
+    uploads::render_file_inputs(
+        &mut card,
+        inv.target_profile.as_ref(),
+        &inv.state,
+        instance_id,
+    );
+    token_issues.extend(charts::prepare_charts(&mut card));
+    token_issues.extend(views::normalize_refresh(&mut card, inv));
+    let (fallbacks, fallback_issues) =
+        fallback::apply_fallbacks(&mut card, inv.target_profile.as_ref());
+    token_issues.extend(fallback_issues);
+    token_issues.extend(
+        asset_resolver::resolve_asset_urls(
+            &mut card,
+            scheme,
+            inv.card_spec.asset_registry.as_ref(),
+        )
+        .map_err(|e| ComponentError::Asset(e.message))?,
+    );
+    token_issues.extend(urls::apply_deep_links(
+        &mut card,
+        inv.target_profile.as_ref(),
+    ));
+    let sanitize_issues = if inv.sanitize {
+        sanitize::sanitize_card(&mut card)
+    } else {
+        Vec::new()
+    };
+    markdown::apply_markdown(&mut card, &inv.markdown);
+    rich_text::normalize_rich_text(&mut card);
+    actions::assign_generated_ids(&mut card, &template_hash, inv.generate_ids);
+    let (classifications, classification_issues) = inputs::take_classifications(&mut card);
+    token_issues.extend(classification_issues);
+    let (dialogs, dialog_issues) = dialogs::apply_dialogs(&mut card, inv.target_profile.as_ref());
+    token_issues.extend(dialog_issues);
+    let restricted = roles::apply_role_visibility(
+        &mut card,
+        inv.viewer
+            .as_ref()
+            .map(|viewer| viewer.roles.as_slice())
+            .unwrap_or_default(),
+    );
+    let disabled_actions = actions::apply_disabled_actions(&mut card, &inv.state);
+    let policy_outcome = inv
+        .security
+        .as_ref()
+        .map(|policy| policy::enforce_policy(&mut card, policy))
+        .unwrap_or_default();
+    let subcard =
+        show_card::apply_show_card_state(&mut card, &inv.state, instance_id, &inv.show_card_mode);
+    token_issues.extend(outlook::to_actionable_message(
+        &mut card,
+        inv.target_profile.as_ref(),
+    ));
+    inputs::restore_drafts(&mut card, &inv.state, instance_id);
+    rules::apply_input_errors(&mut card, &inv.state, instance_id);
+    let visibility = visibility::apply_visibility(&mut card, &inv.state);
+    token_issues.extend(hooks::apply_post_render_hooks(&mut card, inv));
+    let (budget_issues, truncated) = match inv.budget.as_ref() {
+        Some(b) => {
+            let before = budget::measure(&card);
+            let issues = budget::enforce_budget(&mut card, b);
+            let after = budget::measure(&card);
+            let removed =
+                (before.elements + before.actions).saturating_sub(after.elements + after.actions);
+            (issues, removed)
+        }
+        None => (Vec::new(), 0),
+    };
+    let dropped_elements = policy_outcome.issues.len() + truncated + summary.elements_dropped;
+
+    let mut features = analyze_features(&card);
+    features.visibility = visibility;
+    features.fallbacks = fallbacks;
+    let mut validation_issues = token_issues;
+    validation_issues.extend(sanitize_issues);
+    validation_issues.extend(policy_outcome.issues);
+    validation_issues.extend(validate_card(&card));
+    validation_issues.extend(urls::check_open_urls(&card, inv.security.as_ref()));
+    validation_issues.extend(budget_issues);
+    validation_issues.extend(audit::run_audits(&card, &inv.audit));
 
     Ok(RenderOutcome {
         card,
@@ -52,9 +557,29 @@ pub fn render_card(inv: &AdaptiveCardInvocation) -> Result<RenderOutcome, Compon
         validation_issues,
         asset_resolution,
         binding_summary: summary,
+        subcard,
+        blocked_actions: policy_outcome.blocked_actions,
+        restricted_actions: restricted.actions,
+        restricted_inputs: restricted.inputs,
+        disabled_actions,
+        variants: BTreeMap::new(),
+        dropped_elements,
+        post_submit_view,
+        dialogs,
+        fingerprint,
+        derived,
+        rules,
+        classifications,
+        nest_inputs,
+        schedule,
     })
 }
 
+/// Resolves the unbound template from the invocation's card source.
+pub fn resolve_template(inv: &AdaptiveCardInvocation) -> Result<Value, ComponentError> {
+    resolve_card(inv).map(|(card, _)| card)
+}
+
 fn resolve_card(inv: &AdaptiveCardInvocation) -> Result<(Value, AssetResolution), ComponentError> {
     match inv.card_source {
         CardSource::Inline => {
@@ -79,7 +604,7 @@ fn resolve_card(inv: &AdaptiveCardInvocation) -> Result<(Value, AssetResolution)
                 .as_ref()
                 .ok_or_else(|| ComponentError::InvalidInput("asset_path is required".into()))?;
             let candidates = candidate_asset_paths(path, inv.card_spec.asset_registry.as_ref())?;
-            load_with_candidates(path, candidates)
+            load_with_candidates(path, candidates, &cache::TEMPLATES)
         }
         CardSource::Catalog => {
             let catalog =
@@ -88,7 +613,7 @@ fn resolve_card(inv: &AdaptiveCardInvocation) -> Result<(Value, AssetResolution)
                 })?;
             let normalized = catalog.trim_start_matches('/');
             let candidates = candidate_catalog_paths(normalized, &inv.card_spec)?;
-            load_with_candidates(normalized, candidates)
+            load_with_candidates(normalized, candidates, &cache::TEMPLATES)
         }
     }
 }
@@ -207,7 +732,33 @@ fn asset_base_path() -> String {
     std::env::var("ADAPTIVE_CARD_ASSET_BASE").unwrap_or_else(|_| "assets".to_string())
 }
 
-fn load_card_from_path(path: &str) -> Result<(Value, String), ComponentError> {
+/// Reads and parses a JSON file through `cache`, re-reading it once its size or
+/// modification time changes.
+fn load_card_from_path(
+    path: &str,
+    cache: &ShardedCache<CachedFile>,
+) -> Result<(Value, String), ComponentError> {
+    let stamp = FileStamp::of(path);
+    if let Some(stamp) = stamp
+        && let Some(cached) = cache.get(path, |cached| cached.stamp == stamp)
+    {
+        return Ok((cached.value, cached.hash));
+    }
+    let (json, hash) = read_json_file(path)?;
+    if let Some(stamp) = stamp {
+        cache.insert(
+            path,
+            CachedFile {
+                stamp,
+                value: json.clone(),
+                hash: hash.clone(),
+            },
+        );
+    }
+    Ok((json, hash))
+}
+
+fn read_json_file(path: &str) -> Result<(Value, String), ComponentError> {
     let content = std::fs::read_to_string(path).map_err(|err| {
         if err.kind() == std::io::ErrorKind::NotFound {
             ComponentError::AssetNotFound(path.to_string())
@@ -224,10 +775,11 @@ fn load_card_from_path(path: &str) -> Result<(Value, String), ComponentError> {
 fn load_with_candidates(
     lookup_key: &str,
     candidates: Vec<String>,
+    cache: &ShardedCache<CachedFile>,
 ) -> Result<(Value, AssetResolution), ComponentError> {
     let mut last_err: Option<ComponentError> = None;
     for candidate in candidates {
-        match load_card_from_path(&candidate) {
+        match load_card_from_path(&candidate, cache) {
             Ok((card, hash)) => {
                 return Ok((
                     card,
@@ -245,7 +797,7 @@ fn load_with_candidates(
     if let Some(host) =
         resolve_with_host(lookup_key).map_err(|e| ComponentError::Asset(e.message))?
     {
-        match load_card_from_path(&host) {
+        match load_card_from_path(&host, cache) {
             Ok((card, hash)) => {
                 return Ok((
                     card,
@@ -265,41 +817,151 @@ fn load_with_candidates(
     }))
 }
 
-#[derive(Debug)]
-pub struct BindingContext {
-    payload: Value,
-    session: Value,
-    state: Value,
-    template_params: Value,
+/// Loads a JSON asset the way `asset_path` cards are loaded.
+pub fn load_json_asset(
+    name: &str,
+    registry: Option<&BTreeMap<String, String>>,
+) -> Result<Value, ComponentError> {
+    let candidates = candidate_asset_paths(name, registry)?;
+    load_with_candidates(name, candidates, &cache::ASSETS).map(|(value, _)| value)
 }
 
-impl BindingContext {
-    fn from_invocation(inv: &AdaptiveCardInvocation) -> Self {
+fn load_string_catalogs(inv: &AdaptiveCardInvocation) -> Result<StringCatalogs, ComponentError> {
+    let mut catalogs = StringCatalogs::new();
+    if let Some(asset) = inv.strings_asset.as_deref() {
+        let candidates = candidate_asset_paths(asset, inv.card_spec.asset_registry.as_ref())?;
+        let (value, _) = load_with_candidates(asset, candidates, &cache::ASSETS)?;
+        catalogs = serde_json::from_value(value)
+            .map_err(|err| ComponentError::AssetParse(format!("{asset}: {err}")))?;
+    }
+    for (locale, entries) in &inv.string_catalogs {
+        catalogs
+            .entry(locale.clone())
+            .or_default()
+            .extend(entries.clone());
+    }
+    Ok(catalogs)
+}
+
+/// Roots visible to bindings. Payload, session, state, and template params are borrowed from
+/// the invocation, so building a context (and the per-item contexts of paginated templates)
+/// never copies the invocation's data; only values a binding inserts into the card are cloned.
+#[derive(Clone, Debug)]
+pub struct BindingContext<'a> {
+    payload: &'a Value,
+    session: &'a Value,
+    state: &'a Value,
+    template_params: Cow<'a, Value>,
+    strings: Arc<BTreeMap<String, String>>,
+    locale: Option<&'a str>,
+    inputs: Cow<'a, Value>,
+    viewer: Arc<Value>,
+    runtime: Arc<Value>,
+    item: Cow<'a, Value>,
+    target: Option<&'a TargetProfile>,
+    limits: Limits,
+    deadline: Option<Deadline>,
+    /// Bare identifiers name inputs rather than data paths.
+    bare_inputs: bool,
+}
+
+impl<'a> BindingContext<'a> {
+    fn from_invocation(inv: &'a AdaptiveCardInvocation, strings: BTreeMap<String, String>) -> Self {
         BindingContext {
-            payload: inv.payload.clone(),
-            session: inv.session.clone(),
-            state: inv.state.clone(),
-            template_params: inv
-                .card_spec
-                .template_params
-                .clone()
-                .unwrap_or(Value::Object(Map::new())),
+            strings: Arc::new(strings),
+            locale: inv.locale.as_deref(),
+            inputs: Cow::Owned(Value::Null),
+            viewer: Arc::new(views::viewer_value(inv)),
+            runtime: Arc::new(runtime_roots(inv)),
+            item: Cow::Owned(Value::Null),
+            target: inv.target_profile.as_ref(),
+            limits: Limits::resolve(inv.limits.as_ref()),
+            deadline: None,
+            bare_inputs: false,
+            payload: &inv.payload,
+            session: &inv.session,
+            state: &inv.state,
+            template_params: match &inv.card_spec.template_params {
+                Some(params) => Cow::Borrowed(params),
+                None => Cow::Owned(Value::Object(Map::new())),
+            },
+        }
+    }
+
+    /// Context for evaluating interaction-time expressions, with the submitted inputs exposed
+    /// under the `inputs` root.
+    pub fn for_interaction(inv: &'a AdaptiveCardInvocation, inputs: &'a Value) -> Self {
+        BindingContext {
+            inputs: Cow::Borrowed(inputs),
+            ..Self::from_invocation(inv, BTreeMap::new())
+        }
+    }
+
+    /// Context for `$rules`: [`Self::for_interaction`] with bare identifiers (`endDate`) read
+    /// from the inputs only.
+    pub fn for_rules(inv: &'a AdaptiveCardInvocation, inputs: &'a Value) -> Self {
+        BindingContext {
+            bare_inputs: true,
+            ..Self::for_interaction(inv, inputs)
+        }
+    }
+
+    /// The same context reading `state` from `state`, such as the post-interaction state.
+    pub fn with_state<'b>(&'b self, state: &'b Value) -> BindingContext<'b> {
+        BindingContext {
+            state,
+            item: Cow::Borrowed(self.item.as_ref()),
+            template_params: Cow::Borrowed(self.template_params.as_ref()),
+            inputs: Cow::Borrowed(self.inputs.as_ref()),
+            strings: Arc::clone(&self.strings),
+            viewer: Arc::clone(&self.viewer),
+            runtime: Arc::clone(&self.runtime),
+            ..*self
+        }
+    }
+
+    /// Context for one paginated entry, exposed under the `item` root.
+    fn with_item<'b>(&'b self, item: &'b Value) -> BindingContext<'b> {
+        BindingContext {
+            item: Cow::Borrowed(item),
+            template_params: Cow::Borrowed(self.template_params.as_ref()),
+            inputs: Cow::Borrowed(self.inputs.as_ref()),
+            strings: Arc::clone(&self.strings),
+            viewer: Arc::clone(&self.viewer),
+            runtime: Arc::clone(&self.runtime),
+            ..*self
         }
     }
 
-    pub fn lookup(&self, raw: &str) -> Option<Value> {
+    pub fn locale(&self) -> Option<&str> {
+        self.locale
+    }
+
+    /// Host profile the card is rendered for, used to build deep links.
+    pub fn target_profile(&self) -> Option<&TargetProfile> {
+        self.target
+    }
+
+    /// Limits the invocation's expressions and repeaters are checked against.
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Localized string for `key` in the invocation locale's fallback chain.
+    pub fn localize(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(|s| s.as_str())
+    }
+
+    /// Resolves a binding path. The value is borrowed from the context's roots; only `[]`
+    /// projections and `??` defaults are built as new values.
+    pub fn lookup(&self, raw: &str) -> Option<Cow<'_, Value>> {
         let (path, default) = parse_binding_path(raw);
         let mut segments = path.split('.');
         let first = segments.next()?;
-        let attempt_root = |root: &Value, rest: std::str::Split<'_, char>| lookup_in(root, rest);
 
-        let found = match first {
-            "payload" => attempt_root(&self.payload, segments),
-            "session" => attempt_root(&self.session, segments),
-            "state" => attempt_root(&self.state, segments),
-            "params" | "template" => attempt_root(&self.template_params, segments),
-            _ => lookup_in(
-                &self.payload,
+        let unrooted = || {
+            lookup_in(
+                self.payload,
                 normalize_path(&path)
                     .split('.')
                     .collect::<Vec<_>>()
@@ -307,7 +969,7 @@ impl BindingContext {
             )
             .or_else(|| {
                 lookup_in(
-                    &self.session,
+                    self.session,
                     normalize_path(&path)
                         .split('.')
                         .collect::<Vec<_>>()
@@ -316,7 +978,7 @@ impl BindingContext {
             })
             .or_else(|| {
                 lookup_in(
-                    &self.state,
+                    self.state,
                     normalize_path(&path)
                         .split('.')
                         .collect::<Vec<_>>()
@@ -331,38 +993,125 @@ impl BindingContext {
                         .collect::<Vec<_>>()
                         .into_iter(),
                 )
+            })
+        };
+        let found = match first {
+            "payload" => lookup_in(self.payload, segments),
+            "session" => lookup_in(self.session, segments),
+            "state" => lookup_in(self.state, segments),
+            "params" | "template" => lookup_in(&self.template_params, segments),
+            "inputs" => lookup_in(&self.inputs, segments),
+            "viewer" => lookup_in(&self.viewer, segments),
+            "item" => lookup_in(&self.item, segments),
+            // Runtime roots only answer paths the data does not define, so templates that
+            // already bind `user` or `card` out of their payload render as before.
+            "user" => unrooted().or_else(|| lookup_in(&self.viewer, segments)),
+            "env" | "now" | "today" | "card" => unrooted().or_else(|| {
+                lookup_in(
+                    &self.runtime,
+                    normalize_path(&path)
+                        .split('.')
+                        .collect::<Vec<_>>()
+                        .into_iter(),
+                )
             }),
+            _ if self.bare_inputs => lookup_in(
+                &self.inputs,
+                normalize_path(&path)
+                    .split('.')
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            ),
+            _ => unrooted(),
         };
 
         match (found, default) {
             (Some(value), _) if !value.is_null() => Some(value),
-            (None, Some(fallback)) | (Some(Value::Null), Some(fallback)) => Some(fallback),
-            (other, _) => other,
+            (_, Some(fallback)) => Some(Cow::Owned(fallback)),
+            (other, None) => other,
         }
     }
 }
 
-fn lookup_in<'a, I>(value: &Value, mut parts: I) -> Option<Value>
+/// The runtime binding roots: `env` (host environment and configuration values), `now` and
+/// `today` (the invocation's `now`, else the current UTC time), and `card.instanceId`.
+fn runtime_roots(inv: &AdaptiveCardInvocation) -> Value {
+    let now = inv.now.clone().unwrap_or_else(|| {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        format::iso_timestamp(seconds)
+    });
+    let today = format::parse_date(&Value::String(now.clone()))
+        .map(|(year, month, day)| format!("{year:04}-{month:02}-{day:02}"));
+    let instance_id = inv
+        .card_instance_id
+        .clone()
+        .or_else(|| inv.interaction.as_ref().map(|i| i.card_instance_id.clone()))
+        .filter(|id| !id.trim().is_empty());
+    json!({
+        "env": inv.env.clone().unwrap_or(Value::Null),
+        "now": now,
+        "today": today,
+        "card": { "instanceId": instance_id },
+    })
+}
+
+/// Resolves dotted path segments. A segment may index arrays with brackets (`items[2]`), and
+/// `items[]` projects the rest of the path over every entry, collecting the entries where it
+/// resolves into an array (`items[].price`). Plain paths borrow the value they select.
+fn lookup_in<'a, 'v, I>(value: &'v Value, mut parts: I) -> Option<Cow<'v, Value>>
 where
-    I: Iterator<Item = &'a str>,
+    I: Iterator<Item = &'a str> + Clone,
 {
-    let mut current = value;
-    for part in parts.by_ref() {
+    fn step<'v>(current: &'v Value, key: &str) -> Option<&'v Value> {
         match current {
-            Value::Object(map) => current = map.get(part)?,
-            Value::Array(items) => {
-                let idx: usize = part.parse().ok()?;
-                current = items.get(idx)?;
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => None,
+        }
+    }
+
+    let mut current = value;
+    while let Some(part) = parts.next() {
+        let Some(open) = part.find('[') else {
+            current = step(current, part)?;
+            continue;
+        };
+        if open > 0 {
+            current = step(current, &part[..open])?;
+        }
+        let mut brackets = &part[open..];
+        while let Some(index) = brackets.strip_prefix('[') {
+            let (index, rest) = index.split_once(']')?;
+            brackets = rest;
+            if index.is_empty() {
+                if !brackets.is_empty() {
+                    return None;
+                }
+                let Value::Array(items) = current else {
+                    return None;
+                };
+                return Some(Cow::Owned(Value::Array(
+                    items
+                        .iter()
+                        .filter_map(|item| lookup_in(item, parts.clone()).map(Cow::into_owned))
+                        .collect(),
+                )));
             }
-            _ => return None,
+            current = step(current, index)?;
+        }
+        if !brackets.is_empty() {
+            return None;
         }
     }
-    Some(current.clone())
+    Some(Cow::Borrowed(current))
 }
 
 fn apply_bindings(
     value: &mut Value,
-    ctx: &BindingContext,
+    ctx: &BindingContext<'_>,
     engine: &dyn ExpressionEngine,
     summary: &mut BindingSummary,
 ) -> Result<(), ComponentError> {
@@ -371,7 +1120,8 @@ fn apply_bindings(
             if let Some(expr) = extract_expression(text) {
                 if is_simple_expression(expr) {
                     if let Some(resolved) = ctx.lookup(expr) {
-                        *value = resolved;
+                        // The card owns its tree, so this is the one copy of the subtree.
+                        *value = resolved.into_owned();
                         summary.placeholder_replacements += 1;
                         return Ok(());
                     }
@@ -380,22 +1130,18 @@ fn apply_bindings(
                         "missing binding path: {expr}"
                     )));
                 }
-                if let Some(resolved) = engine.eval(expr, ctx) {
-                    *value = match resolved {
-                        Value::String(_) => resolved,
-                        other => Value::String(stringify_value(&other)),
-                    };
-                    summary.expression_evaluations += 1;
-                    return Ok(());
-                }
-                summary.missing_paths += 1;
-                return Err(ComponentError::Binding(format!(
-                    "invalid expression: {expr}"
-                )));
+                check_limits(expr, &ctx.limits())?;
+                let resolved = eval_expression(engine, expr, ctx, summary)?;
+                *value = match resolved {
+                    Value::String(_) => resolved,
+                    other => Value::String(stringify_value(&other)),
+                };
+                summary.expression_evaluations += 1;
+                return Ok(());
             }
             if let Some(path) = extract_single_placeholder(text) {
                 if let Some(resolved) = ctx.lookup(path) {
-                    *value = resolved;
+                    *value = resolved.into_owned();
                     summary.placeholder_replacements += 1;
                     return Ok(());
                 }
@@ -404,18 +1150,28 @@ fn apply_bindings(
                     "missing binding path: {path}"
                 )));
             }
-            let replaced = replace_placeholders(text, ctx, summary)?;
+            let replaced = replace_placeholders(text, ctx, engine, summary)?;
             *value = Value::String(replaced);
             Ok(())
         }
         Value::Array(items) => {
-            for item in items {
-                apply_bindings(item, ctx, engine, summary)?;
+            for idx in 0..items.len() {
+                if out_of_time(&items[idx], ctx)? {
+                    summary.elements_dropped += items.len() - idx;
+                    items.truncate(idx);
+                    break;
+                }
+                apply_bindings(&mut items[idx], ctx, engine, summary)?;
             }
             Ok(())
         }
         Value::Object(map) => {
-            for entry in map.values_mut() {
+            for (key, entry) in map.iter_mut() {
+                // Bound per entry by the paginator, against the `item` root. A continuation's
+                // `card_spec.inline_json` is bound when the next card renders.
+                if key == "itemTemplate" || key == "inline_json" {
+                    continue;
+                }
                 apply_bindings(entry, ctx, engine, summary)?;
             }
             Ok(())
@@ -424,17 +1180,45 @@ fn apply_bindings(
     }
 }
 
+/// Whether the time budget ran out before `value`, an element or action, could be bound. With
+/// the `error` policy an exhausted budget fails the render instead.
+fn out_of_time(value: &Value, ctx: &BindingContext<'_>) -> Result<bool, ComponentError> {
+    let Some(deadline) = ctx.deadline else {
+        return Ok(false);
+    };
+    if value.get("type").is_none() || !deadline.expired() {
+        return Ok(false);
+    }
+    match deadline.policy {
+        TimeBudgetPolicy::Error => Err(deadline.error()),
+        TimeBudgetPolicy::Partial => Ok(true),
+    }
+}
+
 fn apply_handlebars(
     value: &mut Value,
     inv: &AdaptiveCardInvocation,
     summary: &mut BindingSummary,
 ) -> Result<(), ComponentError> {
+    // Only build the context (a copy of payload and state) when there is something to expand.
+    if !has_handlebars(value) {
+        return Ok(());
+    }
     let mut engine = Handlebars::new();
     engine.set_strict_mode(false);
     let context = build_handlebars_context(inv);
     render_handlebars_value(value, &engine, &context, summary)
 }
 
+fn has_handlebars(value: &Value) -> bool {
+    match value {
+        Value::String(text) => text.contains("{{"),
+        Value::Array(items) => items.iter().any(has_handlebars),
+        Value::Object(map) => map.values().any(has_handlebars),
+        _ => false,
+    }
+}
+
 fn render_handlebars_value(
     value: &mut Value,
     engine: &Handlebars<'_>,
@@ -442,7 +1226,7 @@ fn render_handlebars_value(
     summary: &mut BindingSummary,
 ) -> Result<(), ComponentError> {
     match value {
-        Value::String(text) => {
+        Value::String(text) if text.contains("{{") => {
             let rendered = engine
                 .render_template(text, context)
                 .map_err(|err| ComponentError::Binding(format!("handlebars: {err}")))?;
@@ -470,6 +1254,7 @@ fn build_handlebars_context(inv: &AdaptiveCardInvocation) -> Value {
     let mut root = Map::new();
     root.insert("payload".to_owned(), inv.payload.clone());
     root.insert("state".to_owned(), inv.state.clone());
+    root.insert("viewer".to_owned(), views::viewer_value(inv));
 
     if let Some(node_id) = inv.node_id.as_deref() {
         root.insert("node_id".to_owned(), Value::String(node_id.to_owned()));
@@ -506,13 +1291,14 @@ fn resolve_state_input(state: &Value) -> Option<Map<String, Value>> {
 fn is_reserved_handlebars_key(key: &str) -> bool {
     matches!(
         key,
-        "payload" | "state" | "node" | "node_id" | "node_payload"
+        "payload" | "state" | "viewer" | "node" | "node_id" | "node_payload"
     )
 }
 
 fn replace_placeholders(
     input: &str,
-    ctx: &BindingContext,
+    ctx: &BindingContext<'_>,
+    engine: &dyn ExpressionEngine,
     summary: &mut BindingSummary,
 ) -> Result<String, ComponentError> {
     let mut output = String::new();
@@ -535,27 +1321,42 @@ fn replace_placeholders(
         };
 
         let absolute = cursor + pos;
+        let marker = input.as_bytes()[absolute];
+        // `\${` / `$${` (and `\@{` / `@@{`) emit the sequence itself, unevaluated.
+        if pos > 0 && escaped_placeholder(input, absolute) {
+            output.push_str(&input[cursor..absolute - 1]);
+            output.push_str(&input[absolute..absolute + 2]);
+            cursor = absolute + 2;
+            continue;
+        }
         output.push_str(&input[cursor..absolute]);
 
-        let marker = input.as_bytes()[absolute];
         if absolute + 2 > input.len() || bytes[absolute + 1] != b'{' {
             output.push_str(&input[absolute..]);
             break;
         }
         let rest = &input[absolute + 2..];
-        if let Some(end) = rest.find('}') {
+        if let Some(end) = placeholder_end(rest) {
             let path = &rest[..end];
+            // Embedded calls such as `${urlEncode(payload.q)}` go through the engine.
+            if marker == b'$' && !is_simple_expression(path) {
+                check_limits(path.trim(), &ctx.limits())?;
+                let resolved = eval_expression(engine, path.trim(), ctx, summary)?;
+                output.push_str(&stringify_value(&resolved));
+                summary.expression_evaluations += 1;
+                cursor = absolute + 2 + end + 1;
+                continue;
+            }
             let Some(replacement) = ctx.lookup(path.trim()) else {
                 summary.missing_paths += 1;
                 return Err(ComponentError::Binding(format!(
                     "missing binding path: {path}"
                 )));
             };
-            let replacement = match replacement {
-                Value::String(s) => s,
-                other => other.to_string(),
-            };
-            output.push_str(&replacement);
+            match replacement.as_ref() {
+                Value::String(s) => output.push_str(s),
+                other => output.push_str(&other.to_string()),
+            }
             summary.placeholder_replacements += 1;
             cursor = absolute + 2 + end + 1;
         } else {
@@ -571,17 +1372,31 @@ fn hash_bytes(bytes: &[u8]) -> String {
     format!("blake3:{}", blake3::hash(bytes).to_hex())
 }
 
-fn hash_json(value: &Value) -> Option<String> {
+pub(crate) fn hash_json(value: &Value) -> Option<String> {
     let bytes = serde_json::to_vec(value).ok()?;
     Some(hash_bytes(&bytes))
 }
 
+/// Combines the hash of the template (after layouts and includes) with the hash of every
+/// binding root, so equal fingerprints mean the same template rendered from the same data.
+fn fingerprint(template_hash: &str, inv: &AdaptiveCardInvocation) -> Option<String> {
+    let data_hash = hash_json(&json!({
+        "payload": inv.payload,
+        "session": inv.session,
+        "state": inv.state,
+        "params": inv.card_spec.template_params,
+        "viewer": views::viewer_value(inv),
+        "locale": inv.locale,
+        "env": inv.env,
+        "now": inv.now,
+    }))?;
+    Some(hash_bytes(
+        format!("{template_hash}\n{data_hash}").as_bytes(),
+    ))
+}
+
 fn extract_single_placeholder(input: &str) -> Option<&str> {
-    let trimmed = input.trim();
-    if let Some(stripped) = trimmed.strip_prefix("@{").and_then(|s| s.strip_suffix('}')) {
-        return Some(stripped.trim());
-    }
-    None
+    whole_placeholder(input, "@{")
 }
 
 fn parse_binding_path(raw: &str) -> (String, Option<Value>) {
@@ -600,19 +1415,43 @@ fn parse_binding_path(raw: &str) -> (String, Option<Value>) {
 }
 
 fn extract_expression(input: &str) -> Option<&str> {
-    let trimmed = input.trim();
-    if let Some(stripped) = trimmed.strip_prefix("${").and_then(|s| s.strip_suffix('}')) {
-        return Some(stripped.trim());
-    }
-    None
+    whole_placeholder(input, "${")
+}
+
+/// The body of a placeholder opened by `marker` that spans all of `input`. Text such as
+/// `${a} and ${b}` is interpolated placeholder by placeholder instead.
+pub(crate) fn whole_placeholder<'s>(input: &'s str, marker: &str) -> Option<&'s str> {
+    let body = input.trim().strip_prefix(marker)?;
+    let end = placeholder_end(body)?;
+    (end + 1 == body.len()).then(|| body[..end].trim())
+}
+
+/// Evaluates `expr`, failing with the reason a host function gave when one was the cause.
+fn eval_expression(
+    engine: &dyn ExpressionEngine,
+    expr: &str,
+    ctx: &BindingContext<'_>,
+    summary: &mut BindingSummary,
+) -> Result<Value, ComponentError> {
+    expression::take_function_error();
+    engine.eval(expr, ctx).ok_or_else(|| {
+        summary.missing_paths += 1;
+        ComponentError::Binding(match expression::take_function_error() {
+            Some(reason) => format!("invalid expression: {expr} ({reason})"),
+            None => format!("invalid expression: {expr}"),
+        })
+    })
 }
 
-fn is_simple_expression(expr: &str) -> bool {
+pub(crate) fn is_simple_expression(expr: &str) -> bool {
     let trimmed = expr.trim();
     if trimmed.chars().any(|c| c.is_whitespace()) {
         return false;
     }
-    !trimmed.contains('?') && !trimmed.contains("==") && !trimmed.contains(':')
+    !trimmed.contains('?')
+        && !trimmed.contains("==")
+        && !trimmed.contains(':')
+        && !trimmed.contains('(')
 }
 
 fn normalize_path(path: &str) -> String {
@@ -632,17 +1471,26 @@ pub fn analyze_features(card: &Value) -> CardFeatureSummary {
         ..CardFeatureSummary::default()
     };
 
+    /// Keeps the highest version requested for each feature.
     fn merge_requires(target: &mut Value, new_value: &Value) {
-        match target {
-            Value::Object(dst) => {
-                if let Value::Object(src) = new_value {
-                    for (k, v) in src {
-                        dst.entry(k.clone()).or_insert(v.clone());
-                    }
-                }
+        let Value::Object(src) = new_value else {
+            return;
+        };
+        if target.is_null() {
+            *target = Value::Object(Map::new());
+        }
+        let Value::Object(dst) = target else {
+            return;
+        };
+        for (k, v) in src {
+            let newer = match (dst.get(k).and_then(Value::as_str), v.as_str()) {
+                (Some(have), Some(want)) => fallback::compare_versions(want, have).is_gt(),
+                (None, _) => true,
+                _ => false,
+            };
+            if newer {
+                dst.insert(k.clone(), v.clone());
             }
-            Value::Null => *target = new_value.clone(),
-            _ => {}
         }
     }
 
@@ -676,7 +1524,11 @@ pub fn analyze_features(card: &Value) -> CardFeatureSummary {
                 if let Some(req) = map.get("requires") {
                     merge_requires(&mut summary.requires_features, req);
                 }
-                for value in map.values() {
+                for (key, value) in map {
+                    // An element that rendered never shows its fallback.
+                    if key == "fallback" && map.contains_key("type") {
+                        continue;
+                    }
                     walk(value, used_elements, used_actions, summary);
                 }
             }
@@ -690,6 +1542,7 @@ pub fn analyze_features(card: &Value) -> CardFeatureSummary {
     }
 
     walk(card, &mut used_elements, &mut used_actions, &mut summary);
+    summary.ids = actions::element_ids(card);
     summary.used_elements = used_elements.into_iter().collect();
     summary.used_actions = used_actions.into_iter().collect();
     summary
@@ -702,6 +1555,7 @@ pub fn validate_card(card: &Value) -> Vec<ValidationIssue> {
             code: "invalid-root".into(),
             message: "Card must be a JSON object".into(),
             path: "/".into(),
+            severity: None,
         });
         return issues;
     }
@@ -712,6 +1566,7 @@ pub fn validate_card(card: &Value) -> Vec<ValidationIssue> {
             code: "invalid-type".into(),
             message: "Root type must be AdaptiveCard".into(),
             path: "/type".into(),
+            severity: None,
         });
     }
     if card.get("version").is_none() {
@@ -719,16 +1574,18 @@ pub fn validate_card(card: &Value) -> Vec<ValidationIssue> {
             code: "missing-version".into(),
             message: "AdaptiveCard must include a version".into(),
             path: "/version".into(),
+            severity: None,
         });
     }
 
-    let mut input_ids = HashSet::new();
+    let mut input_ids = HashMap::new();
 
     fn push_issue(path: &str, code: &str, message: &str, issues: &mut Vec<ValidationIssue>) {
         issues.push(ValidationIssue {
             code: code.to_string(),
             message: message.to_string(),
             path: path.to_string(),
+            severity: None,
         });
     }
 
@@ -736,7 +1593,7 @@ pub fn validate_card(card: &Value) -> Vec<ValidationIssue> {
         value: &Value,
         path: &str,
         issues: &mut Vec<ValidationIssue>,
-        input_ids: &mut HashSet<String>,
+        input_ids: &mut HashMap<String, String>,
         action_ids: &mut HashSet<String>,
     ) {
         match value {
@@ -748,14 +1605,17 @@ pub fn validate_card(card: &Value) -> Vec<ValidationIssue> {
                 if kind.starts_with("Input.")
                     && let Some(id) = map.get("id").and_then(|v| v.as_str())
                 {
-                    let inserted = input_ids.insert(id.to_string());
-                    if !inserted {
+                    if let Some(first) = input_ids.get(id) {
                         push_issue(
                             path,
                             "duplicate-id",
-                            "Input ids should be unique within the card",
+                            &format!(
+                                "Input id {id} is already used at {first}; namespace repeated inputs with inputNamespace"
+                            ),
                             issues,
                         );
+                    } else {
+                        input_ids.insert(id.to_string(), path.to_string());
                     }
                 }
                 if kind.starts_with("Action.") {
@@ -771,7 +1631,31 @@ pub fn validate_card(card: &Value) -> Vec<ValidationIssue> {
                     }
                     validate_action(map, path, issues);
                 }
+                if map
+                    .get("selectAction")
+                    .map(|a| a.is_object())
+                    .unwrap_or(false)
+                    && kind != "TextRun"
+                    && !actions::SELECT_ACTION_HOSTS.contains(&kind)
+                {
+                    push_issue(
+                        path,
+                        "unsupported-select-action",
+                        "selectAction is only supported on Container, ColumnSet, Column, Image, TableRow and TableCell",
+                        issues,
+                    );
+                }
                 match kind {
+                    "ActionSet" => {
+                        if !map.get("actions").map(|a| a.is_array()).unwrap_or(false) {
+                            push_issue(
+                                path,
+                                "invalid-actions",
+                                "ActionSet must include an actions array",
+                                issues,
+                            );
+                        }
+                    }
                     "Input.ChoiceSet" => {
                         if let Some(choices) = map.get("choices") {
                             if let Some(arr) = choices.as_array() {
@@ -846,6 +1730,109 @@ pub fn validate_card(card: &Value) -> Vec<ValidationIssue> {
                             );
                         }
                     }
+                    "Input.Rating" => {
+                        let max = map.get("max").and_then(|v| v.as_f64());
+                        if max.map(|m| m <= 0.0).unwrap_or(false) {
+                            push_issue(
+                                path,
+                                "invalid-max",
+                                "Input.Rating max must be greater than 0",
+                                issues,
+                            );
+                        } else if let Some(value) = map.get("value").and_then(|v| v.as_f64())
+                            && (value < 0.0 || value > max.unwrap_or(5.0))
+                        {
+                            push_issue(
+                                path,
+                                "invalid-value",
+                                "Input.Rating value must be between 0 and max",
+                                issues,
+                            );
+                        }
+                    }
+                    "Input.Slider" => {
+                        let min = map.get("min").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                        let max = map.get("max").and_then(|v| v.as_f64()).unwrap_or(100.0);
+                        if min > max {
+                            push_issue(
+                                path,
+                                "invalid-range",
+                                "Input.Slider min must be <= max",
+                                issues,
+                            );
+                        } else if let Some(value) = map.get("value").and_then(|v| v.as_f64())
+                            && (value < min || value > max)
+                        {
+                            push_issue(
+                                path,
+                                "invalid-value",
+                                "Input.Slider value must be within min and max",
+                                issues,
+                            );
+                        }
+                        if map
+                            .get("step")
+                            .and_then(|v| v.as_f64())
+                            .map(|step| step <= 0.0)
+                            .unwrap_or(false)
+                        {
+                            push_issue(
+                                path,
+                                "invalid-step",
+                                "Input.Slider step must be greater than 0",
+                                issues,
+                            );
+                        }
+                    }
+                    "Input.ColorPicker" => {
+                        if let Some(value) = map.get("value").and_then(|v| v.as_str())
+                            && crate::inputs::normalize_hex_color(value).is_none()
+                        {
+                            push_issue(
+                                path,
+                                "invalid-color",
+                                "Input.ColorPicker value must be a #rgb or #rrggbb color",
+                                issues,
+                            );
+                        }
+                    }
+                    "RichTextBlock" => match map.get("inlines") {
+                        Some(Value::Array(inlines)) => {
+                            for inline in inlines {
+                                if inline.is_string() {
+                                    continue;
+                                }
+                                if inline.get("type").and_then(|v| v.as_str()) != Some("TextRun")
+                                    || !inline.get("text").map(|t| t.is_string()).unwrap_or(false)
+                                {
+                                    push_issue(
+                                        path,
+                                        "invalid-inline",
+                                        "RichTextBlock inlines must be TextRuns with text",
+                                        issues,
+                                    );
+                                } else if inline
+                                    .get("selectAction")
+                                    .and_then(|a| a.get("type"))
+                                    .and_then(|v| v.as_str())
+                                    == Some("Action.ShowCard")
+                                {
+                                    push_issue(
+                                        path,
+                                        "invalid-select-action",
+                                        "TextRun selectAction cannot be Action.ShowCard",
+                                        issues,
+                                    );
+                                }
+                            }
+                        }
+                        _ => push_issue(
+                            path,
+                            "invalid-inlines",
+                            "RichTextBlock must include an inlines array",
+                            issues,
+                        ),
+                    },
                     "ColumnSet" => {
                         if let Some(columns) = map.get("columns") {
                             if !columns.is_array() {
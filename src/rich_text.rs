@@ -0,0 +1,36 @@
+use serde_json::{Map, Value};
+
+/// Normalizes RichTextBlock inlines: bare strings become TextRun objects. Inline
+/// `selectAction`s get their ids with every other routed action.
+pub fn normalize_rich_text(card: &mut Value) {
+    match card {
+        Value::Object(map) => {
+            if map.get("type").and_then(|v| v.as_str()) == Some("RichTextBlock") {
+                normalize_block(map);
+            }
+            for child in map.values_mut() {
+                normalize_rich_text(child);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                normalize_rich_text(item);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn normalize_block(map: &mut Map<String, Value>) {
+    let Some(Value::Array(inlines)) = map.get_mut("inlines") else {
+        return;
+    };
+    for inline in inlines.iter_mut() {
+        if let Value::String(text) = inline {
+            let mut run = Map::new();
+            run.insert("type".into(), Value::String("TextRun".into()));
+            run.insert("text".into(), Value::String(std::mem::take(text)));
+            *inline = Value::Object(run);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::actions;
+use crate::inputs;
+
+/// Extension property listing the roles allowed to see an element or action: a role name or
+/// an array of them.
+pub const VISIBLE_TO_ROLES: &str = "visibleToRoles";
+
+/// Actions and inputs [`apply_role_visibility`] removed from a card.
+#[derive(Debug, Default, Clone)]
+pub struct RestrictedContent {
+    pub actions: Vec<String>,
+    /// Ids of removed inputs that no remaining input shares.
+    pub inputs: BTreeSet<String>,
+}
+
+/// Removes elements and actions whose `visibleToRoles` shares no role with `roles` (an
+/// unknown viewer has none) and strips the property from the rest. Returns the ids of every
+/// action and input removed, including those nested in removed elements, so interactions
+/// naming them, or submitting values for them, can be rejected.
+pub fn apply_role_visibility(card: &mut Value, roles: &[String]) -> RestrictedContent {
+    let mut removed = Vec::new();
+    prune(card, roles, &mut removed);
+    let remaining: BTreeSet<String> = inputs::collect_inputs(card)
+        .into_iter()
+        .map(|input| input.id)
+        .collect();
+    RestrictedContent {
+        actions: removed
+            .iter()
+            .flat_map(actions::collect_actions)
+            .filter_map(|action| action.id)
+            .collect(),
+        inputs: removed
+            .iter()
+            .flat_map(inputs::collect_inputs)
+            .map(|input| input.id)
+            .filter(|id| !remaining.contains(id))
+            .collect(),
+    }
+}
+
+fn prune(value: &mut Value, roles: &[String], removed: &mut Vec<Value>) {
+    match value {
+        Value::Object(map) => {
+            map.remove(VISIBLE_TO_ROLES);
+            let hidden: Vec<String> = map
+                .iter()
+                .filter(|(key, child)| *key != "data" && !allowed(child, roles))
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in hidden {
+                removed.extend(map.remove(&key));
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    prune(child, roles, removed);
+                }
+            }
+        }
+        Value::Array(items) => {
+            let mut idx = 0;
+            while idx < items.len() {
+                if allowed(&items[idx], roles) {
+                    prune(&mut items[idx], roles, removed);
+                    idx += 1;
+                } else {
+                    removed.push(items.remove(idx));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn allowed(value: &Value, roles: &[String]) -> bool {
+    let has = |role: &Value| {
+        role.as_str()
+            .is_some_and(|r| roles.iter().any(|own| own == r))
+    };
+    match value.get(VISIBLE_TO_ROLES) {
+        None => true,
+        Some(Value::Array(required)) => required.iter().any(has),
+        Some(role) => has(role),
+    }
+}
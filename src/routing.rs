@@ -0,0 +1,51 @@
+use crate::expression::{ExpressionEngine, SimpleExpressionEngine, truthy};
+use crate::model::RouteRule;
+use crate::render::BindingContext;
+
+/// Returns the first rule whose `verb` and `action_id` patterns and `when` expression all
+/// match the interaction. Omitted criteria match anything.
+pub fn match_route<'a>(
+    rules: &'a [RouteRule],
+    verb: Option<&str>,
+    action_id: &str,
+    ctx: &BindingContext<'_>,
+) -> Option<&'a RouteRule> {
+    let engine = SimpleExpressionEngine;
+    rules.iter().find(|rule| {
+        rule.verb
+            .as_deref()
+            .is_none_or(|pattern| verb.is_some_and(|verb| wildcard_match(pattern, verb)))
+            && rule
+                .action_id
+                .as_deref()
+                .is_none_or(|pattern| wildcard_match(pattern, action_id))
+            && rule
+                .when
+                .as_deref()
+                .is_none_or(|expr| engine.eval(expr, ctx).is_some_and(|v| truthy(&v)))
+    })
+}
+
+/// Glob-style match where `*` matches any run of characters and `?` a single character.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
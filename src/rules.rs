@@ -0,0 +1,288 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{Map, Value, json};
+
+use crate::dependencies::{ROOTS, expression_dependencies, normalize};
+use crate::expression::{ExpressionEngine, SimpleExpressionEngine, truthy};
+use crate::inputs::InputDeclaration;
+use crate::model::{IssueSeverity, ValidationIssue};
+use crate::render::BindingContext;
+
+/// Card root key listing cross-input validation rules.
+pub const RULES_KEY: &str = "$rules";
+/// Suffix of the ids given to inline error TextBlocks (`<input id>-error`).
+pub const ERROR_ID_SUFFIX: &str = "-error";
+
+/// One `$rules` entry: `rule` must hold for a submit to go through, otherwise `message` is
+/// reported against each of `inputs`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputRule {
+    pub rule: String,
+    pub message: String,
+    pub inputs: Vec<String>,
+}
+
+/// Removes the `$rules` declarations from the card root so they are neither bound nor
+/// delivered. Rules may be written bare or wrapped in `${...}`; entries without a `rule` or
+/// `message` are dropped.
+pub fn take_rules(card: &mut Value) -> Vec<InputRule> {
+    let Some(Value::Array(declared)) = card.as_object_mut().and_then(|root| root.remove(RULES_KEY))
+    else {
+        return Vec::new();
+    };
+    declared
+        .iter()
+        .filter_map(|entry| {
+            let rule = entry.get("rule")?.as_str()?.trim();
+            let rule = rule
+                .strip_prefix("${")
+                .and_then(|inner| inner.strip_suffix('}'))
+                .unwrap_or(rule);
+            Some(InputRule {
+                rule: rule.trim().to_string(),
+                message: entry.get("message")?.as_str()?.to_string(),
+                inputs: entry
+                    .get("inputs")
+                    .and_then(Value::as_array)
+                    .map(|ids| {
+                        ids.iter()
+                            .filter_map(Value::as_str)
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// How a rule, or one of its `&&` / `||` clauses, came out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Verdict {
+    Held,
+    Broken,
+    /// Could not be evaluated, though every input it reads has a value.
+    Invalid,
+}
+
+/// Evaluates every rule against the submitted inputs (exposed under `inputs`, and as bare
+/// identifiers) and returns the messages of the broken ones keyed by input id, plus an
+/// error issue per broken rule. Clauses joined by `&&` and `||` are evaluated separately. A
+/// clause that cannot be evaluated because one of the card's `inputs` it reads was left
+/// empty holds: required inputs are enforced with `isRequired`. Any other clause that cannot
+/// be evaluated breaks its rule and is reported as `input-rule-invalid`, so a mistyped rule
+/// never lets a submit through.
+pub fn check_rules(
+    rules: &[InputRule],
+    inputs: &[InputDeclaration],
+    ctx: &BindingContext<'_>,
+) -> (BTreeMap<String, String>, Vec<ValidationIssue>) {
+    let declared: BTreeSet<String> = inputs
+        .iter()
+        .filter_map(|input| normalize(&input.id))
+        .collect();
+    let mut errors = BTreeMap::new();
+    let mut issues = Vec::new();
+    for (idx, rule) in rules.iter().enumerate() {
+        let (code, message) = match evaluate(&rule.rule, &declared, ctx) {
+            Verdict::Held => continue,
+            Verdict::Broken => ("input-rule-failed", rule.message.clone()),
+            Verdict::Invalid => (
+                "input-rule-invalid",
+                format!("rule `{}` cannot be evaluated: {}", rule.rule, rule.message),
+            ),
+        };
+        for input in &rule.inputs {
+            errors
+                .entry(input.clone())
+                .or_insert_with(|| rule.message.clone());
+        }
+        issues.push(ValidationIssue {
+            code: code.into(),
+            message,
+            path: match rule.inputs.first() {
+                Some(input) => format!("/inputs/{input}"),
+                None => format!("/{RULES_KEY}/{idx}"),
+            },
+            severity: Some(IssueSeverity::Error),
+        });
+    }
+    (errors, issues)
+}
+
+fn evaluate(rule: &str, declared: &BTreeSet<String>, ctx: &BindingContext<'_>) -> Verdict {
+    let rule = rule.trim();
+    // Parentheses around the whole rule only group it.
+    if let Some(inner) = rule.strip_prefix('(').and_then(|r| r.strip_suffix(')'))
+        && balanced(inner)
+    {
+        return evaluate(inner, declared, ctx);
+    }
+    let any = split_logical(rule, "||");
+    if any.len() > 1 {
+        let verdicts: Vec<Verdict> = any
+            .iter()
+            .map(|clause| evaluate(clause, declared, ctx))
+            .collect();
+        return if verdicts.contains(&Verdict::Held) {
+            Verdict::Held
+        } else if verdicts.contains(&Verdict::Invalid) {
+            Verdict::Invalid
+        } else {
+            Verdict::Broken
+        };
+    }
+    let all = split_logical(rule, "&&");
+    if all.len() > 1 {
+        let verdicts: Vec<Verdict> = all
+            .iter()
+            .map(|clause| evaluate(clause, declared, ctx))
+            .collect();
+        return if verdicts.contains(&Verdict::Broken) {
+            Verdict::Broken
+        } else if verdicts.contains(&Verdict::Invalid) {
+            Verdict::Invalid
+        } else {
+            Verdict::Held
+        };
+    }
+    match SimpleExpressionEngine.eval(rule, ctx) {
+        Some(held) if truthy(&held) => Verdict::Held,
+        Some(_) => Verdict::Broken,
+        None if reads_empty_input(rule, declared, ctx) => Verdict::Held,
+        None => Verdict::Invalid,
+    }
+}
+
+/// Whether some declared input the clause reads, as `inputs.<id>` or a bare `<id>`, was
+/// left empty.
+fn reads_empty_input(clause: &str, declared: &BTreeSet<String>, ctx: &BindingContext<'_>) -> bool {
+    expression_dependencies(clause).iter().any(|path| {
+        let input = match path.split_once('.') {
+            Some(("inputs", rest)) => rest,
+            Some((root, _)) if ROOTS.contains(&root) => return false,
+            _ if ROOTS.contains(&path.as_str()) => return false,
+            _ => path,
+        };
+        if !declared.contains(input) {
+            return false;
+        }
+        match ctx.lookup(&format!("inputs.{input}")).as_deref() {
+            None | Some(Value::Null) => true,
+            Some(Value::String(text)) => text.trim().is_empty(),
+            Some(Value::Array(items)) => items.is_empty(),
+            Some(_) => false,
+        }
+    })
+}
+
+/// Splits `rule` at each `op` outside quotes and parentheses.
+fn split_logical<'r>(rule: &'r str, op: &str) -> Vec<&'r str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    let bytes = rule.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let byte = bytes[idx];
+        match (quote, byte) {
+            (Some(open), _) if byte == open => quote = None,
+            (Some(_), _) => {}
+            (None, b'\'' | b'"') => quote = Some(byte),
+            (None, b'(') => depth += 1,
+            (None, b')') => depth = depth.saturating_sub(1),
+            (None, _) if depth == 0 && rule[idx..].starts_with(op) => {
+                parts.push(&rule[start..idx]);
+                idx += op.len();
+                start = idx;
+                continue;
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+    parts.push(&rule[start..]);
+    parts
+}
+
+/// Whether every `(` in `text` outside quotes is closed within it, never dipping below zero.
+fn balanced(text: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote = None;
+    for byte in text.bytes() {
+        match (quote, byte) {
+            (Some(open), _) if byte == open => quote = None,
+            (Some(_), _) => {}
+            (None, b'\'' | b'"') => quote = Some(byte),
+            (None, b'(') => depth += 1,
+            (None, b')') => {
+                depth -= 1;
+                if depth < 0 {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+/// Inserts an attention-colored TextBlock after each input with a persisted
+/// `ui.input_errors.<card_instance_id>.<input id>` message.
+pub fn apply_input_errors(card: &mut Value, state: &Value, card_instance_id: Option<&str>) {
+    let Some(errors) = card_instance_id
+        .and_then(|id| state.get("ui")?.get("input_errors")?.get(id))
+        .and_then(Value::as_object)
+        .filter(|errors| !errors.is_empty())
+    else {
+        return;
+    };
+    fn walk(value: &mut Value, errors: &Map<String, Value>) {
+        match value {
+            Value::Object(map) => {
+                for child in map.values_mut() {
+                    walk(child, errors);
+                }
+            }
+            Value::Array(items) => {
+                for item in items.iter_mut() {
+                    walk(item, errors);
+                }
+                let mut idx = 0;
+                while idx < items.len() {
+                    idx += 1;
+                    let Some(block) = input_id(&items[idx - 1])
+                        .and_then(|id| Some(error_block(id, errors.get(id)?.as_str()?)))
+                    else {
+                        continue;
+                    };
+                    items.insert(idx, block);
+                    idx += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    walk(card, errors);
+}
+
+fn input_id(element: &Value) -> Option<&str> {
+    element
+        .get("type")
+        .and_then(Value::as_str)
+        .filter(|kind| kind.starts_with("Input."))?;
+    element.get("id").and_then(Value::as_str)
+}
+
+fn error_block(input: &str, message: &str) -> Value {
+    json!({
+        "type": "TextBlock",
+        "id": format!("{input}{ERROR_ID_SUFFIX}"),
+        "text": message,
+        "color": "attention",
+        "size": "small",
+        "wrap": true,
+        "spacing": "none"
+    })
+}
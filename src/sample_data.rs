@@ -0,0 +1,202 @@
+use serde_json::{Map, Value};
+
+use crate::dependencies::{collect_dependencies, normalize};
+use crate::limits::DEFAULT_MAX_REPEAT;
+
+/// Formatting functions whose first argument hints at the value's type.
+const NUMBER_FUNCTIONS: &[&str] = &["formatNumber", "formatCurrency"];
+const DATE_FUNCTIONS: &[&str] = &["formatDate"];
+
+/// Shape inferred for a referenced path from how the template uses it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Hint {
+    Number,
+    Date,
+    Bool,
+    List,
+    Url,
+}
+
+/// Builds `{ "payload": …, "state": …, "session": …, "params": …, "viewer": … }` with a
+/// placeholder for every path the template reads. Unrooted paths land in `payload` (the first
+/// lookup root); `inputs` and `item` paths are skipped because they come from submissions and
+/// paginated lists. Sections nobody references are omitted, except `payload`, which is always
+/// present.
+pub fn generate(template: &Value) -> Value {
+    let mut sections = Map::new();
+    sections.insert("payload".to_string(), Value::Object(Map::new()));
+    for (pointer, paths) in collect_dependencies(template) {
+        let text = template
+            .pointer(&pointer)
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let key = pointer.rsplit('/').next().unwrap_or_default();
+        for path in paths {
+            let (root, rest) = match path.split_once('.') {
+                Some((root @ ("payload" | "state" | "session" | "viewer"), rest)) => (root, rest),
+                Some(("params" | "template", rest)) => ("params", rest),
+                None if matches!(
+                    path.as_str(),
+                    "payload"
+                        | "state"
+                        | "session"
+                        | "params"
+                        | "template"
+                        | "inputs"
+                        | "viewer"
+                        | "item"
+                ) =>
+                {
+                    continue;
+                }
+                Some(("inputs" | "item", _)) => continue,
+                _ => ("payload", path.as_str()),
+            };
+            let hint = usage_hint(text, key, &path);
+            let section = sections
+                .entry(root.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            insert_placeholder(section, rest, hint);
+        }
+    }
+    Value::Object(sections)
+}
+
+fn usage_hint(text: &str, key: &str, path: &str) -> Option<Hint> {
+    let first_arg_of = |functions: &[&str]| {
+        functions.iter().any(|name| {
+            text.match_indices(&format!("{name}(")).any(|(idx, call)| {
+                let args = &text[idx + call.len()..];
+                let end = args.find([',', ')']).unwrap_or(args.len());
+                normalize(&args[..end]).is_some_and(|arg| arg == path)
+            })
+        })
+    };
+    let block_arg = |helper: &str| {
+        text.match_indices(&format!("{{{{#{helper} "))
+            .any(|(idx, tag)| {
+                let rest = &text[idx + tag.len()..];
+                let end = rest.find("}}").unwrap_or(rest.len());
+                rest[..end]
+                    .split_whitespace()
+                    .next()
+                    .and_then(normalize)
+                    .is_some_and(|arg| arg == path)
+            })
+    };
+    if first_arg_of(NUMBER_FUNCTIONS) {
+        Some(Hint::Number)
+    } else if first_arg_of(DATE_FUNCTIONS) {
+        Some(Hint::Date)
+    } else if block_arg("each") {
+        Some(Hint::List)
+    } else if block_arg("if") || block_arg("unless") || matches!(key, "isVisible" | "isEnabled") {
+        Some(Hint::Bool)
+    } else if key.to_ascii_lowercase().ends_with("url") {
+        Some(Hint::Url)
+    } else {
+        None
+    }
+}
+
+/// Writes a placeholder at dotted `path` inside `root`, creating objects (or arrays for
+/// numeric segments) along the way. A scalar already sitting where a container is needed is
+/// replaced, since a deeper reference proves the value is structured. Paths indexing past
+/// the repeater limit get no placeholder: the array would have to be padded up to the index.
+fn insert_placeholder(root: &mut Value, path: &str, hint: Option<Hint>) {
+    let segments: Vec<&str> = path.split('.').collect();
+    let mut current = root;
+    for (idx, segment) in segments.iter().enumerate() {
+        let last = idx + 1 == segments.len();
+        let next_is_index = segments
+            .get(idx + 1)
+            .is_some_and(|next| next.parse::<usize>().is_ok());
+        let container = || {
+            if next_is_index {
+                Value::Array(Vec::new())
+            } else {
+                Value::Object(Map::new())
+            }
+        };
+        let slot = match segment.parse::<usize>() {
+            Ok(index) if current.is_array() => {
+                if index >= DEFAULT_MAX_REPEAT {
+                    return;
+                }
+                let items = current.as_array_mut().expect("checked array");
+                if items.len() <= index {
+                    items.resize(index + 1, Value::Null);
+                }
+                &mut items[index]
+            }
+            _ => {
+                if !current.is_object() {
+                    *current = Value::Object(Map::new());
+                }
+                current
+                    .as_object_mut()
+                    .expect("checked object")
+                    .entry(segment.to_string())
+                    .or_insert(Value::Null)
+            }
+        };
+        if last {
+            if slot.is_null() {
+                *slot = placeholder(segment, hint);
+            }
+            return;
+        }
+        if !(slot.is_object() || slot.is_array()) {
+            *slot = container();
+        }
+        current = slot;
+    }
+}
+
+/// Plausible value for a leaf, from the usage hint first and the field name second.
+fn placeholder(name: &str, hint: Option<Hint>) -> Value {
+    let lower = name.to_ascii_lowercase();
+    let has = |needles: &[&str]| needles.iter().any(|n| lower.contains(n));
+    match hint {
+        Some(Hint::Number) => return serde_json::json!(42.5),
+        Some(Hint::Date) => return Value::from("2024-01-15T09:30:00Z"),
+        Some(Hint::Bool) => return Value::Bool(true),
+        Some(Hint::List) => return serde_json::json!(["Sample item 1", "Sample item 2"]),
+        Some(Hint::Url) => return Value::from("https://example.com"),
+        None => {}
+    }
+    if let Ok(index) = name.parse::<usize>() {
+        Value::from(format!("Sample item {}", index + 1))
+    } else if lower.starts_with("is") && name.chars().nth(2).is_some_and(char::is_uppercase)
+        || lower.starts_with("has") && name.chars().nth(3).is_some_and(char::is_uppercase)
+        || has(&["enabled", "visible", "active", "approved"])
+    {
+        Value::Bool(true)
+    } else if has(&["date", "time"]) || name.ends_with("At") {
+        Value::from("2024-01-15T09:30:00Z")
+    } else if has(&["email"]) {
+        Value::from("jane.doe@example.com")
+    } else if has(&["image", "avatar", "logo", "icon", "photo"]) {
+        Value::from("https://example.com/image.png")
+    } else if has(&["url", "link", "href"]) {
+        Value::from("https://example.com")
+    } else if has(&["phone"]) {
+        Value::from("+1 555 0100")
+    } else if has(&["currency"]) {
+        Value::from("EUR")
+    } else if has(&[
+        "amount", "price", "total", "cost", "balance", "subtotal", "tax",
+    ]) {
+        serde_json::json!(42.5)
+    } else if lower == "age" || has(&["count", "qty", "quantity", "number", "size", "score"]) {
+        Value::from(3)
+    } else if lower == "id" || name.ends_with("Id") || lower.ends_with("_id") {
+        Value::from(format!("{name}-001"))
+    } else if has(&["name", "user", "author", "owner"]) {
+        Value::from("Jane Doe")
+    } else if has(&["status", "state"]) {
+        Value::from("active")
+    } else {
+        Value::from(format!("Sample {name}"))
+    }
+}
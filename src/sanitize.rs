@@ -0,0 +1,127 @@
+use serde_json::{Map, Value};
+
+use crate::model::ValidationIssue;
+
+/// Keys whose string values are treated as navigable or loadable URLs.
+const URL_KEYS: [&str; 5] = [
+    "url",
+    "iconUrl",
+    "backgroundImage",
+    "poster",
+    "thumbnailUrl",
+];
+
+/// Elements whose content is dropped entirely rather than unwrapped.
+const SCRIPT_TAGS: [&str; 4] = ["script", "style", "iframe", "object"];
+
+/// Strips script-capable markup and control characters from every string in the card and
+/// removes URLs with executable schemes, reporting each removal as a validation issue.
+pub fn sanitize_card(card: &mut Value) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    visit(card, "", &mut issues);
+    issues
+}
+
+fn visit(value: &mut Value, path: &str, issues: &mut Vec<ValidationIssue>) {
+    match value {
+        Value::String(text) => *text = sanitize_text(text),
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                visit(item, &format!("{path}/{idx}"), issues);
+            }
+        }
+        Value::Object(map) => {
+            strip_unsafe_urls(map, path, issues);
+            for (key, child) in map.iter_mut() {
+                visit(child, &format!("{path}/{key}"), issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn strip_unsafe_urls(map: &mut Map<String, Value>, path: &str, issues: &mut Vec<ValidationIssue>) {
+    for key in URL_KEYS {
+        if let Some(url) = map.get(key).and_then(|v| v.as_str())
+            && !is_safe_url(url)
+        {
+            map.remove(key);
+            issues.push(ValidationIssue {
+                code: "unsafe-url".into(),
+                message: format!("Removed {key} with an executable URL scheme"),
+                path: format!("{path}/{key}"),
+                severity: None,
+            });
+        }
+    }
+}
+
+/// Rejects `javascript:`, `vbscript:` and non-image `data:` URLs. Browsers ignore embedded
+/// whitespace and control characters in schemes, so those are dropped before comparing.
+pub fn is_safe_url(url: &str) -> bool {
+    let compact: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if compact.starts_with("javascript:") || compact.starts_with("vbscript:") {
+        return false;
+    }
+    if let Some(rest) = compact.strip_prefix("data:") {
+        return rest.starts_with("image/") && !rest.starts_with("image/svg");
+    }
+    true
+}
+
+/// Removes control characters (except newlines and tabs), script-capable elements with their
+/// content, and any remaining HTML tags.
+pub fn sanitize_text(text: &str) -> String {
+    let cleaned: String = text
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .collect();
+    if !cleaned.contains('<') {
+        return cleaned;
+    }
+
+    let mut out = String::with_capacity(cleaned.len());
+    let mut rest = cleaned.as_str();
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let opens_tag = tail[1..]
+            .chars()
+            .next()
+            .map(|c| c.is_ascii_alphabetic() || c == '/' || c == '!')
+            .unwrap_or(false);
+        if !opens_tag {
+            out.push('<');
+            rest = &tail[1..];
+            continue;
+        }
+        let name: String = tail[1..]
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        let Some(end) = tail.find('>') else {
+            // Unterminated tag: drop the remainder rather than leak a partial element.
+            rest = "";
+            break;
+        };
+        rest = &tail[end + 1..];
+        if SCRIPT_TAGS.contains(&name.as_str()) {
+            let close = format!("</{name}");
+            let lower = rest.to_ascii_lowercase();
+            rest = match lower.find(&close) {
+                Some(pos) => {
+                    let after = &rest[pos..];
+                    after.find('>').map(|gt| &after[gt + 1..]).unwrap_or("")
+                }
+                None => "",
+            };
+        }
+    }
+    out.push_str(rest);
+    out
+}
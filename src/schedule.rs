@@ -0,0 +1,102 @@
+use serde_json::Value;
+
+use crate::model::{CardSchedule, IssueSeverity, ValidationIssue};
+
+/// Card root key declaring when the host should remind about and expire the card.
+pub const SCHEDULE_KEY: &str = "$schedule";
+
+/// Removes `$schedule` from the card root and reads it: `expireAfter` and `remindAfter` (one
+/// duration or a list) as seconds or strings such as `"90s"`, `"30m"`, `"24h"`, `"3d"`, or
+/// `"1w"`, and whether an `onExpire` card is declared. Unreadable durations and reminders
+/// due at or after the expiry are dropped with `invalid-schedule` warnings.
+pub fn take_schedule(card: &mut Value) -> (Option<CardSchedule>, Vec<ValidationIssue>) {
+    let Some(raw) = card
+        .as_object_mut()
+        .and_then(|root| root.remove(SCHEDULE_KEY))
+    else {
+        return (None, Vec::new());
+    };
+    let mut issues = Vec::new();
+    let mut read = |key: &str, value: &Value, path: String| {
+        let seconds = parse_duration(value);
+        if seconds.is_none() {
+            issues.push(invalid(path, format!("{key} is not a duration: {value}")));
+        }
+        seconds
+    };
+    let expire_after_seconds = raw
+        .get("expireAfter")
+        .and_then(|value| read("expireAfter", value, format!("/{SCHEDULE_KEY}/expireAfter")));
+    let reminders = match raw.get("remindAfter") {
+        Some(Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, value)| {
+                read(
+                    "remindAfter",
+                    value,
+                    format!("/{SCHEDULE_KEY}/remindAfter/{idx}"),
+                )
+            })
+            .collect(),
+        Some(value) => read("remindAfter", value, format!("/{SCHEDULE_KEY}/remindAfter"))
+            .into_iter()
+            .collect(),
+        None => Vec::new(),
+    };
+    let mut remind_after_seconds = Vec::new();
+    for seconds in reminders {
+        if expire_after_seconds.is_some_and(|expiry| seconds >= expiry) {
+            issues.push(invalid(
+                format!("/{SCHEDULE_KEY}/remindAfter"),
+                format!("reminder after {seconds}s is not before the card expires"),
+            ));
+            continue;
+        }
+        remind_after_seconds.push(seconds);
+    }
+    remind_after_seconds.sort_unstable();
+    remind_after_seconds.dedup();
+    let schedule = CardSchedule {
+        expire_after_seconds,
+        remind_after_seconds,
+        on_expire: raw.get("onExpire").is_some_and(Value::is_object),
+    };
+    (Some(schedule), issues)
+}
+
+/// The `onExpire` card the template declares, unbound.
+pub fn on_expire_view(template: &Value) -> Option<Value> {
+    template
+        .get(SCHEDULE_KEY)?
+        .get("onExpire")
+        .filter(|view| view.is_object())
+        .cloned()
+}
+
+fn parse_duration(value: &Value) -> Option<u64> {
+    if let Some(seconds) = value.as_u64() {
+        return Some(seconds);
+    }
+    let text = value.as_str()?.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let amount: u64 = text[..split].parse().ok()?;
+    let unit = match text[split..].trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return None,
+    };
+    amount.checked_mul(unit)
+}
+
+fn invalid(path: String, message: String) -> ValidationIssue {
+    ValidationIssue {
+        code: "invalid-schedule".into(),
+        message,
+        path,
+        severity: Some(IssueSeverity::Warning),
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use schemars::schema_for;
+use serde_json::Value;
+
+use crate::error::ComponentError;
+use crate::model::{
+    AdaptiveActionEvent, AdaptiveCardInvocation, AdaptiveCardResponse, AdaptiveCardResult,
+    CardInteraction, ComponentConfig,
+};
+
+/// Schemas `get-schema` can return, generated from the model types they are named after.
+pub const SCHEMA_NAMES: [&str; 6] = [
+    "invocation",
+    "result",
+    "interaction",
+    "event",
+    "response",
+    "config",
+];
+
+/// JSON Schema (draft 2020-12) of a model type, derived from its serde attributes so it always
+/// matches what the component accepts and returns.
+pub fn model_schema(name: &str) -> Option<Value> {
+    let schema = match name {
+        "invocation" => schema_for!(AdaptiveCardInvocation),
+        "result" => schema_for!(AdaptiveCardResult),
+        "interaction" => schema_for!(CardInteraction),
+        "event" => schema_for!(AdaptiveActionEvent),
+        "response" => schema_for!(AdaptiveCardResponse),
+        "config" => schema_for!(ComponentConfig),
+        _ => return None,
+    };
+    Some(schema.to_value())
+}
+
+/// `get-schema`: the schemas named by `schema` (a name or a list of names), or all of them.
+pub fn requested_schemas(input: &Value) -> Result<BTreeMap<String, Value>, ComponentError> {
+    let names: Vec<&str> = match input.get("schema") {
+        None | Some(Value::Null) => SCHEMA_NAMES.to_vec(),
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        Some(_) => {
+            return Err(ComponentError::InvalidInput(
+                "schema must be a schema name or a list of names".into(),
+            ));
+        }
+    };
+    names
+        .into_iter()
+        .map(|name| {
+            model_schema(name)
+                .map(|schema| (name.to_string(), schema))
+                .ok_or_else(|| {
+                    ComponentError::InvalidInput(format!(
+                        "unknown schema {name}; expected one of {}",
+                        SCHEMA_NAMES.join(", ")
+                    ))
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,134 @@
+use serde_json::{Map, Value, json};
+
+use crate::model::ShowCardMode;
+
+/// Id prefix of the Container an inline-expanded ShowCard subcard is rendered into.
+pub const EXPANDED_ID_PREFIX: &str = "showcard-";
+
+/// Applies the persisted `ui.active_show_card.<card_instance_id>` selection. In inline mode
+/// the chosen subcard is expanded into the parent card right after the action that owns it;
+/// in separate mode the card is left untouched and the subcard is returned for the host.
+pub fn apply_show_card_state(
+    card: &mut Value,
+    state: &Value,
+    card_instance_id: Option<&str>,
+    mode: &ShowCardMode,
+) -> Option<Value> {
+    let instance_id = card_instance_id?;
+    let active = state
+        .get("ui")
+        .and_then(|ui| ui.get("active_show_card"))
+        .and_then(|v| v.get(instance_id))
+        .and_then(|v| v.as_str())?
+        .to_string();
+
+    let subcard = find_subcard(card, &active)?;
+    match mode {
+        ShowCardMode::Separate => Some(subcard),
+        ShowCardMode::Inline => {
+            let expanded = expanded_container(&active, &subcard);
+            if card_actions_contain(card, &active) {
+                if let Value::Object(root) = card {
+                    let body = root
+                        .entry("body")
+                        .or_insert_with(|| Value::Array(Vec::new()));
+                    if let Value::Array(items) = body {
+                        items.push(expanded);
+                    }
+                }
+            } else {
+                insert_after_action_set(card, &active, expanded);
+            }
+            None
+        }
+    }
+}
+
+fn matches_show_card(action: &Value, active: &str) -> bool {
+    action.get("type").and_then(|v| v.as_str()) == Some("Action.ShowCard")
+        && (action.get("id").and_then(|v| v.as_str()) == Some(active)
+            || action
+                .get("card")
+                .and_then(|c| c.get("id"))
+                .and_then(|v| v.as_str())
+                == Some(active))
+}
+
+fn find_subcard(card: &Value, active: &str) -> Option<Value> {
+    match card {
+        Value::Object(map) => {
+            if matches_show_card(card, active) {
+                return map.get("card").cloned();
+            }
+            map.values().find_map(|child| find_subcard(child, active))
+        }
+        Value::Array(items) => items.iter().find_map(|item| find_subcard(item, active)),
+        _ => None,
+    }
+}
+
+fn card_actions_contain(card: &Value, active: &str) -> bool {
+    card.get("actions")
+        .and_then(|v| v.as_array())
+        .map(|actions| actions.iter().any(|a| matches_show_card(a, active)))
+        .unwrap_or(false)
+}
+
+fn insert_after_action_set(value: &mut Value, active: &str, expanded: Value) -> bool {
+    match value {
+        Value::Array(items) => {
+            let position = items.iter().position(|item| {
+                item.get("type").and_then(|v| v.as_str()) == Some("ActionSet")
+                    && item
+                        .get("actions")
+                        .and_then(|v| v.as_array())
+                        .map(|actions| actions.iter().any(|a| matches_show_card(a, active)))
+                        .unwrap_or(false)
+            });
+            if let Some(idx) = position {
+                items.insert(idx + 1, expanded);
+                return true;
+            }
+            for item in items.iter_mut() {
+                if insert_after_action_set(item, active, expanded.clone()) {
+                    return true;
+                }
+            }
+            false
+        }
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                // Do not expand into another ShowCard's subcard.
+                if key == "card" {
+                    continue;
+                }
+                if insert_after_action_set(child, active, expanded.clone()) {
+                    return true;
+                }
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
+fn expanded_container(active: &str, subcard: &Value) -> Value {
+    let mut items = subcard
+        .get("body")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    if let Some(actions) = subcard.get("actions").and_then(|v| v.as_array())
+        && !actions.is_empty()
+    {
+        items.push(json!({ "type": "ActionSet", "actions": actions }));
+    }
+    let mut container = Map::new();
+    container.insert("type".into(), Value::String("Container".into()));
+    container.insert(
+        "id".into(),
+        Value::String(format!("{EXPANDED_ID_PREFIX}{active}")),
+    );
+    container.insert("items".into(), Value::Array(items));
+    Value::Object(container)
+}
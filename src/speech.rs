@@ -0,0 +1,139 @@
+use serde_json::Value;
+
+use crate::markdown::plain_text;
+
+/// Builds the text a voice channel should announce for a rendered card. The card's own
+/// `speak` wins; otherwise visible elements contribute their `speak`, or text derived from
+/// TextBlocks, RichTextBlocks, and FactSets, in document order. When any element `speak` is
+/// SSML the result is a single `<speak>` document with the derived text escaped.
+pub fn extract_speech(card: &Value) -> Option<String> {
+    if let Some(speak) = card.get("speak").and_then(Value::as_str)
+        && !speak.trim().is_empty()
+    {
+        return Some(speak.to_string());
+    }
+    let mut pieces = Vec::new();
+    if let Some(body) = card.get("body").and_then(Value::as_array) {
+        for element in body {
+            collect(element, &mut pieces);
+        }
+    }
+    if pieces.is_empty() {
+        return None;
+    }
+    if pieces.iter().any(|piece| matches!(piece, Piece::Ssml(_))) {
+        let inner = pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Ssml(markup) => unwrap_speak(markup).to_string(),
+                Piece::Text(text) => escape_xml(text),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        return Some(format!("<speak>{inner}</speak>"));
+    }
+    Some(
+        pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Ssml(text) | Piece::Text(text) => text.as_str(),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+enum Piece {
+    Text(String),
+    Ssml(String),
+}
+
+fn collect(element: &Value, pieces: &mut Vec<Piece>) {
+    let Value::Object(map) = element else {
+        return;
+    };
+    if map.get("isVisible").and_then(Value::as_bool) == Some(false) {
+        return;
+    }
+    if let Some(speak) = map.get("speak").and_then(Value::as_str) {
+        let speak = speak.trim();
+        if speak.contains('<') {
+            pieces.push(Piece::Ssml(speak.to_string()));
+        } else if !speak.is_empty() {
+            pieces.push(Piece::Text(sentence(speak)));
+        }
+        return;
+    }
+    match map.get("type").and_then(Value::as_str) {
+        Some("TextBlock") => {
+            if let Some(text) = map.get("text").and_then(Value::as_str) {
+                push_text(pieces, &plain_text(text));
+            }
+        }
+        Some("RichTextBlock") => {
+            let text = map
+                .get("inlines")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|inline| match inline {
+                    Value::String(text) => Some(text.as_str()),
+                    other => other.get("text").and_then(Value::as_str),
+                })
+                .collect::<String>();
+            push_text(pieces, &text);
+        }
+        Some("FactSet") => {
+            for fact in map
+                .get("facts")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let title = fact.get("title").and_then(Value::as_str).unwrap_or("");
+                let value = fact.get("value").and_then(Value::as_str).unwrap_or("");
+                push_text(pieces, &format!("{title}: {value}"));
+            }
+        }
+        _ => {}
+    }
+    for key in ["items", "columns", "rows", "cells"] {
+        if let Some(children) = map.get(key).and_then(Value::as_array) {
+            for child in children {
+                collect(child, pieces);
+            }
+        }
+    }
+}
+
+fn push_text(pieces: &mut Vec<Piece>, text: &str) {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !text.is_empty() && text != ":" {
+        pieces.push(Piece::Text(sentence(&text)));
+    }
+}
+
+/// Ends the text with punctuation so consecutive pieces are announced as separate sentences.
+fn sentence(text: &str) -> String {
+    if text.ends_with(['.', '!', '?', ':', ';']) {
+        text.to_string()
+    } else {
+        format!("{text}.")
+    }
+}
+
+fn unwrap_speak(markup: &str) -> &str {
+    let Some(rest) = markup.strip_prefix("<speak") else {
+        return markup;
+    };
+    match (rest.find('>'), rest.strip_suffix("</speak>")) {
+        (Some(open), Some(body)) if open < body.len() => body[open + 1..].trim(),
+        _ => markup,
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
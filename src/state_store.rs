@@ -1,7 +1,10 @@
+use std::cell::RefCell;
+
 use serde_json::{Map, Value};
 
 use crate::error::ComponentError;
-use crate::model::{AdaptiveCardInvocation, CardInteraction, StateUpdateOp};
+use crate::migrations;
+use crate::model::{AdaptiveCardInvocation, CardInteraction, RateLimit, StateUpdateOp};
 
 #[cfg(all(target_arch = "wasm32", feature = "state-store"))]
 use greentic_interfaces_guest::state_store;
@@ -17,27 +20,72 @@ use std::sync::Mutex;
 static STATE_STORE: Lazy<Mutex<HashMap<String, Vec<u8>>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+thread_local! {
+    /// Set while [`detached`] runs, holding the card state last persisted in the meantime.
+    static DETACHED: RefCell<Option<Option<Value>>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` without the component's state store: reads find nothing and writes (state,
+/// idempotency records, rate-limit windows, migrated state) are dropped. Returns the card
+/// state `f` persisted last, if any, for hosts that keep state elsewhere to save.
+pub fn detached<T>(f: impl FnOnce() -> T) -> (T, Option<Value>) {
+    struct Reset(Option<Option<Value>>);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            DETACHED.with(|slot| slot.replace(self.0.take()));
+        }
+    }
+    let _reset = Reset(DETACHED.with(|slot| slot.replace(Some(None))));
+    let out = f();
+    let persisted = DETACHED.with(|slot| slot.borrow_mut().as_mut().and_then(Option::take));
+    (out, persisted)
+}
+
+fn is_detached() -> bool {
+    DETACHED.with(|slot| slot.borrow().is_some())
+}
+
+/// Loads the instance's persisted state into `inv` when it carries none, migrated to the
+/// version its template declares. Returns the state as stored. The migrated state is written
+/// back only when `persist` is set, so dry runs and read-only calls leave the store untouched.
 pub fn load_state_if_missing(
     inv: &mut AdaptiveCardInvocation,
     interaction: Option<&CardInteraction>,
+    persist: bool,
 ) -> Result<Option<Value>, ComponentError> {
     if !inv.state.is_null() {
         return Ok(None);
     }
     let key = state_key(inv, interaction);
     let loaded = read_state(&key)?;
-    if let Some(state) = loaded.clone() {
+    if let Some(mut state) = loaded.clone() {
+        // Migrated state is written back so the upgrade runs once per instance.
+        if migrations::migrate_state(inv, &mut state)? && persist {
+            write_state(&key, serde_json::to_vec(&state)?)?;
+        }
         inv.state = state;
     }
     Ok(loaded)
 }
 
+/// The state persisted for `inv`, as stored: without migrations.
+pub fn read_persisted_state(
+    inv: &AdaptiveCardInvocation,
+    interaction: Option<&CardInteraction>,
+) -> Result<Option<Value>, ComponentError> {
+    read_state(&state_key(inv, interaction))
+}
+
 pub fn persist_state(
     inv: &AdaptiveCardInvocation,
     interaction: Option<&CardInteraction>,
     state: &Value,
 ) -> Result<(), ComponentError> {
     let key = state_key(inv, interaction);
+    if is_detached() {
+        DETACHED.with(|slot| *slot.borrow_mut() = Some(Some(state.clone())));
+        return Ok(());
+    }
     if state.is_null() {
         delete_state(&key)?;
         return Ok(());
@@ -46,6 +94,78 @@ pub fn persist_state(
     write_state(&key, bytes)
 }
 
+/// Number of idempotency keys remembered per card instance.
+pub const IDEMPOTENCY_WINDOW: usize = 32;
+
+/// Returns the cached result recorded for the interaction's idempotency key, if any.
+pub fn cached_result(interaction: &CardInteraction) -> Result<Option<Value>, ComponentError> {
+    let Some(key) = idempotency_key(interaction) else {
+        return Ok(None);
+    };
+    let entries = read_state(&idempotency_store_key(interaction))?;
+    Ok(entries
+        .as_ref()
+        .and_then(Value::as_array)
+        .and_then(|entries| {
+            entries
+                .iter()
+                .find(|entry| entry.get("key").and_then(Value::as_str) == Some(key))
+        })
+        .and_then(|entry| entry.get("result").cloned()))
+}
+
+/// Records the result for the interaction's idempotency key, keeping the most recent
+/// [`IDEMPOTENCY_WINDOW`] keys for the card instance.
+pub fn record_result(interaction: &CardInteraction, result: &Value) -> Result<(), ComponentError> {
+    let Some(key) = idempotency_key(interaction) else {
+        return Ok(());
+    };
+    let store_key = idempotency_store_key(interaction);
+    let mut entries = match read_state(&store_key)? {
+        Some(Value::Array(entries)) => entries,
+        _ => Vec::new(),
+    };
+    entries.retain(|entry| entry.get("key").and_then(Value::as_str) != Some(key));
+    entries.push(serde_json::json!({ "key": key, "result": result }));
+    if entries.len() > IDEMPOTENCY_WINDOW {
+        entries.drain(..entries.len() - IDEMPOTENCY_WINDOW);
+    }
+    write_state(&store_key, serde_json::to_vec(&entries)?)
+}
+
+/// Forgets every recorded idempotency key for the interaction's card instance.
+pub fn clear_recorded_results(interaction: &CardInteraction) -> Result<(), ComponentError> {
+    delete_state(&idempotency_store_key(interaction))
+}
+
+/// Counts an interaction against the card instance's sliding-window rate limit. Returns the
+/// seconds to wait when the window is already full; rejected interactions are not counted,
+/// and neither is any interaction when `record` is false (simulations).
+pub fn check_rate_limit(
+    interaction: &CardInteraction,
+    limit: &RateLimit,
+    now_ms: u64,
+    record: bool,
+) -> Result<Option<u64>, ComponentError> {
+    let window_ms = limit.window_seconds.saturating_mul(1000);
+    let store_key = format!("adaptive-card:rate:{}", interaction.card_instance_id);
+    let mut stamps: Vec<u64> = match read_state(&store_key)? {
+        Some(Value::Array(stamps)) => stamps.iter().filter_map(Value::as_u64).collect(),
+        _ => Vec::new(),
+    };
+    stamps.retain(|stamp| now_ms.saturating_sub(*stamp) < window_ms);
+    if stamps.len() >= limit.max_interactions as usize {
+        let oldest = stamps.iter().min().copied().unwrap_or(now_ms);
+        let wait_ms = (oldest + window_ms).saturating_sub(now_ms);
+        return Ok(Some(wait_ms.div_ceil(1000).max(1)));
+    }
+    if record {
+        stamps.push(now_ms);
+        write_state(&store_key, serde_json::to_vec(&stamps)?)?;
+    }
+    Ok(None)
+}
+
 pub fn state_key_for(
     inv: &AdaptiveCardInvocation,
     interaction: Option<&CardInteraction>,
@@ -70,9 +190,24 @@ fn state_key(inv: &AdaptiveCardInvocation, interaction: Option<&CardInteraction>
     if let Some(interaction) = interaction {
         return format!("adaptive-card:card:{}", interaction.card_instance_id);
     }
+    if let Some(instance_id) = inv.card_instance_id.as_deref() {
+        return format!("adaptive-card:card:{instance_id}");
+    }
     "adaptive-card:default".to_string()
 }
 
+fn idempotency_key(interaction: &CardInteraction) -> Option<&str> {
+    interaction
+        .idempotency_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+fn idempotency_store_key(interaction: &CardInteraction) -> String {
+    format!("adaptive-card:idempotency:{}", interaction.card_instance_id)
+}
+
 fn set_path(state: &mut Value, path: &str, value: Value) {
     let parts: Vec<&str> = path.split('.').collect();
     if parts.is_empty() {
@@ -158,7 +293,21 @@ fn ensure_object(value: &mut Value) {
     }
 }
 
+/// Key read by [`probe`]; never written.
+const PROBE_KEY: &str = "__health_probe";
+
+/// Checks that the state store answers a read: `Ok(false)` when the build has no state store.
+pub fn probe() -> Result<bool, ComponentError> {
+    if cfg!(all(target_arch = "wasm32", not(feature = "state-store"))) {
+        return Ok(false);
+    }
+    read_bytes(PROBE_KEY).map(|_| true)
+}
+
 fn read_state(key: &str) -> Result<Option<Value>, ComponentError> {
+    if is_detached() {
+        return Ok(None);
+    }
     let bytes = read_bytes(key)?;
     let Some(bytes) = bytes else {
         return Ok(None);
@@ -170,6 +319,20 @@ fn read_state(key: &str) -> Result<Option<Value>, ComponentError> {
     Ok(Some(value))
 }
 
+fn write_state(key: &str, bytes: Vec<u8>) -> Result<(), ComponentError> {
+    if is_detached() {
+        return Ok(());
+    }
+    write_bytes(key, bytes)
+}
+
+fn delete_state(key: &str) -> Result<(), ComponentError> {
+    if is_detached() {
+        return Ok(());
+    }
+    delete_bytes(key)
+}
+
 #[cfg(all(target_arch = "wasm32", feature = "state-store"))]
 fn read_bytes(key: &str) -> Result<Option<Vec<u8>>, ComponentError> {
     match state_store::read(key, None) {
@@ -196,7 +359,7 @@ fn read_bytes(key: &str) -> Result<Option<Vec<u8>>, ComponentError> {
 }
 
 #[cfg(all(target_arch = "wasm32", feature = "state-store"))]
-fn write_state(key: &str, bytes: Vec<u8>) -> Result<(), ComponentError> {
+fn write_bytes(key: &str, bytes: Vec<u8>) -> Result<(), ComponentError> {
     match state_store::write(key, &bytes, None) {
         Ok(state_store::OpAck::Ok) => Ok(()),
         Err(err) => Err(ComponentError::StateStore(format!(
@@ -207,12 +370,12 @@ fn write_state(key: &str, bytes: Vec<u8>) -> Result<(), ComponentError> {
 }
 
 #[cfg(all(target_arch = "wasm32", not(feature = "state-store")))]
-fn write_state(_key: &str, _bytes: Vec<u8>) -> Result<(), ComponentError> {
+fn write_bytes(_key: &str, _bytes: Vec<u8>) -> Result<(), ComponentError> {
     Ok(())
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn write_state(key: &str, bytes: Vec<u8>) -> Result<(), ComponentError> {
+fn write_bytes(key: &str, bytes: Vec<u8>) -> Result<(), ComponentError> {
     let mut store = STATE_STORE
         .lock()
         .map_err(|_| ComponentError::StateStore("state store poisoned".into()))?;
@@ -221,7 +384,7 @@ fn write_state(key: &str, bytes: Vec<u8>) -> Result<(), ComponentError> {
 }
 
 #[cfg(all(target_arch = "wasm32", feature = "state-store"))]
-fn delete_state(key: &str) -> Result<(), ComponentError> {
+fn delete_bytes(key: &str) -> Result<(), ComponentError> {
     match state_store::delete(key, None) {
         Ok(state_store::OpAck::Ok) => Ok(()),
         Err(err) => Err(ComponentError::StateStore(format!(
@@ -232,12 +395,12 @@ fn delete_state(key: &str) -> Result<(), ComponentError> {
 }
 
 #[cfg(all(target_arch = "wasm32", not(feature = "state-store")))]
-fn delete_state(_key: &str) -> Result<(), ComponentError> {
+fn delete_bytes(_key: &str) -> Result<(), ComponentError> {
     Ok(())
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn delete_state(key: &str) -> Result<(), ComponentError> {
+fn delete_bytes(key: &str) -> Result<(), ComponentError> {
     let mut store = STATE_STORE
         .lock()
         .map_err(|_| ComponentError::StateStore("state store poisoned".into()))?;
@@ -278,6 +441,7 @@ mod tests {
             mode: InvocationMode::RenderAndValidate,
             validation_mode: ValidationMode::Warn,
             envelope: None,
+            ..Default::default()
         }
     }
 
@@ -308,7 +472,8 @@ mod tests {
         let state = json!({"ui": {"visibility": {"card": true}}});
         persist_state(&invocation, None, &state).expect("persist should succeed");
 
-        let loaded = load_state_if_missing(&mut invocation, None).expect("load should succeed");
+        let loaded =
+            load_state_if_missing(&mut invocation, None, true).expect("load should succeed");
         assert_eq!(loaded, Some(state));
         assert_eq!(invocation.state["ui"]["visibility"]["card"], true);
     }
@@ -0,0 +1,88 @@
+use serde_json::{Value, json};
+
+use crate::model::{
+    AdaptiveCardInvocation, AdaptiveCardResponse, AdaptiveCardResult, CardInteractionType,
+};
+use crate::render::analyze_features;
+use crate::{handle_message, parse_invocation_value, serialize_response, validation};
+
+/// One `invoke-stream` event, independent of the WIT bindings the wasm build maps it to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamFrame {
+    Progress(u8),
+    Data(String),
+    Done,
+}
+
+/// Operation the host invokes once the long-running work behind a streamed Execute
+/// interaction is done, to receive the stream's `final` frame.
+pub const CONTINUE_OPERATION: &str = "continue-stream";
+
+/// Streams `operation`. A `card` invocation carrying an Execute interaction returns at once
+/// with a `Data` frame `{sequence: 0, phase: "processing", result, continuation}` holding the
+/// invocation's `processing` card (or a short notice); the interaction is not handled yet.
+/// When its long-running work is done, the host streams [`CONTINUE_OPERATION`] with the
+/// invocation (carrying whatever the work produced), which handles the interaction and yields
+/// `{sequence: 1, phase: "final", result}`. Any other operation yields its response as a
+/// single, plain `Data` frame.
+pub fn invoke_stream(operation: &str, input: &str, emit: &mut dyn FnMut(StreamFrame)) {
+    emit(StreamFrame::Progress(0));
+    if operation == CONTINUE_OPERATION {
+        let data = frame(1, "final", handle_message("card", input));
+        emit(StreamFrame::Data(data.to_string()));
+        emit(StreamFrame::Progress(100));
+        emit(StreamFrame::Done);
+        return;
+    }
+    let Some(processing) = processing_result(operation, input) else {
+        emit(StreamFrame::Data(handle_message(operation, input)));
+        emit(StreamFrame::Done);
+        return;
+    };
+    let mut data = frame(0, "processing", processing);
+    data["continuation"] = json!(CONTINUE_OPERATION);
+    emit(StreamFrame::Data(data.to_string()));
+    emit(StreamFrame::Progress(50));
+    emit(StreamFrame::Done);
+}
+
+/// The serialized result streamed while the Execute interaction in `input` is handled.
+fn processing_result(operation: &str, input: &str) -> Option<String> {
+    if operation != "card" {
+        return None;
+    }
+    let value: Value = serde_json::from_str(input).ok()?;
+    let inv: AdaptiveCardInvocation = if validation::is_bare_invocation(&value) {
+        serde_json::from_value(value).ok()?
+    } else {
+        parse_invocation_value(&value).ok()?
+    };
+    let interaction = inv.interaction.as_ref()?;
+    if interaction.interaction_type != CardInteractionType::Execute {
+        return None;
+    }
+    let card = inv.processing.clone().unwrap_or_else(|| {
+        json!({
+            "type": "AdaptiveCard",
+            "version": "1.6",
+            "body": [
+                { "type": "TextBlock", "text": "Working on it…", "wrap": true, "isSubtle": true }
+            ]
+        })
+    });
+    let result = AdaptiveCardResult {
+        card_features: analyze_features(&card),
+        rendered_card: Some(card),
+        card_instance_id: Some(interaction.card_instance_id.clone())
+            .filter(|id| !id.trim().is_empty()),
+        ..Default::default()
+    };
+    Some(serialize_response(&AdaptiveCardResponse::Success(
+        Box::new(result),
+    )))
+}
+
+fn frame(sequence: u32, phase: &str, result: String) -> Value {
+    let result: Value = serde_json::from_str(&result).unwrap_or(Value::Null);
+    json!({ "sequence": sequence, "phase": phase, "result": result })
+}
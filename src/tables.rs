@@ -0,0 +1,276 @@
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value, json};
+
+use crate::model::StateUpdateOp;
+
+/// Action `data` key carrying `{table, action, column?}` on generated sort and filter actions.
+pub const TABLE_KEY: &str = "__table";
+
+/// Applies the per-instance table state in `ui.tables.<tableId>` to every Table with an `id`
+/// that declares `sortable` and/or `filterable`. Sortable tables get a header-cell
+/// `selectAction` per column and are sorted by `sort: {column, direction}`; filterable tables
+/// get a filter input with Filter/Clear actions placed before them and keep only rows with a
+/// cell containing `filter` (case-insensitive). The header row is never sorted or filtered.
+pub fn apply_table_state(card: &mut Value, state: &Value) {
+    match card {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    apply_table_state(child, state);
+                }
+            }
+        }
+        Value::Array(items) => {
+            let mut idx = 0;
+            while idx < items.len() {
+                apply_table_state(&mut items[idx], state);
+                let controls = match &mut items[idx] {
+                    Value::Object(map) if is_interactive_table(map) => prepare(map, state),
+                    _ => Vec::new(),
+                };
+                let inserted = controls.len();
+                for (offset, control) in controls.into_iter().enumerate() {
+                    items.insert(idx + offset, control);
+                }
+                idx += inserted + 1;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether the card contains a Table that [`apply_table_state`] makes interactive.
+pub fn has_interactive_tables(card: &Value) -> bool {
+    match card {
+        Value::Object(map) => {
+            is_interactive_table(map)
+                || map
+                    .iter()
+                    .any(|(key, child)| key != "data" && has_interactive_tables(child))
+        }
+        Value::Array(items) => items.iter().any(has_interactive_tables),
+        _ => false,
+    }
+}
+
+/// State update requested by a generated table action, or `None` for other actions.
+pub fn table_update(data: Option<&Value>, inputs: &Value, state: &Value) -> Option<StateUpdateOp> {
+    let request = data?.get(TABLE_KEY)?;
+    let table = request.get("table")?.as_str()?;
+    match request.get("action")?.as_str()? {
+        "sort" => {
+            let column = request.get("column")?.as_u64()?;
+            let current = table_state(state, table).get("sort");
+            let same_column = current
+                .and_then(|sort| sort.get("column"))
+                .and_then(Value::as_u64)
+                == Some(column);
+            let ascending = current
+                .and_then(|sort| sort.get("direction"))
+                .and_then(Value::as_str)
+                == Some("asc");
+            let direction = if same_column && ascending {
+                "desc"
+            } else {
+                "asc"
+            };
+            Some(StateUpdateOp::Set {
+                path: format!("ui.tables.{table}.sort"),
+                value: json!({ "column": column, "direction": direction }),
+            })
+        }
+        "filter" => {
+            let text = inputs
+                .get(format!("{table}-filter"))
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .unwrap_or_default();
+            let path = format!("ui.tables.{table}.filter");
+            Some(if text.is_empty() {
+                StateUpdateOp::Delete { path }
+            } else {
+                StateUpdateOp::Set {
+                    path,
+                    value: Value::String(text.to_string()),
+                }
+            })
+        }
+        "clear" => Some(StateUpdateOp::Delete {
+            path: format!("ui.tables.{table}.filter"),
+        }),
+        _ => None,
+    }
+}
+
+fn is_interactive_table(map: &Map<String, Value>) -> bool {
+    map.get("type").and_then(Value::as_str) == Some("Table")
+        && map.get("id").and_then(Value::as_str).is_some()
+        && (map.contains_key("sortable") || map.contains_key("filterable"))
+}
+
+fn table_state<'a>(state: &'a Value, table: &str) -> &'a Value {
+    state
+        .get("ui")
+        .and_then(|ui| ui.get("tables"))
+        .and_then(|tables| tables.get(table))
+        .unwrap_or(&Value::Null)
+}
+
+/// Sorts, filters, and decorates the table; returns the controls to insert before it.
+fn prepare(map: &mut Map<String, Value>, state: &Value) -> Vec<Value> {
+    let id = map
+        .get("id")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let sortable = map.remove("sortable").and_then(|v| v.as_bool()) == Some(true);
+    let filterable = map.remove("filterable").and_then(|v| v.as_bool()) == Some(true);
+    let ui = table_state(state, &id).clone();
+    let has_header = map.get("firstRowAsHeader").and_then(Value::as_bool) != Some(false);
+    let Some(Value::Array(rows)) = map.get_mut("rows") else {
+        return Vec::new();
+    };
+    let mut body = rows.split_off(usize::from(has_header).min(rows.len()));
+
+    let filter = ui.get("filter").and_then(Value::as_str).unwrap_or_default();
+    if filterable && !filter.is_empty() {
+        let needle = filter.to_lowercase();
+        body.retain(|row| {
+            row_cells(row)
+                .iter()
+                .any(|cell| cell_text(cell).to_lowercase().contains(&needle))
+        });
+    }
+
+    let sort = ui.get("sort");
+    let sort_column = sort
+        .and_then(|s| s.get("column"))
+        .and_then(Value::as_u64)
+        .map(|c| c as usize);
+    let descending = sort
+        .and_then(|s| s.get("direction"))
+        .and_then(Value::as_str)
+        == Some("desc");
+    if sortable && let Some(column) = sort_column {
+        body.sort_by(|a, b| {
+            let ordering = compare_cells(row_cells(a).get(column), row_cells(b).get(column));
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    if sortable
+        && has_header
+        && let Some(header) = rows.first_mut()
+    {
+        decorate_header(header, &id, sort_column, descending);
+    }
+    rows.extend(body);
+
+    if !filterable {
+        return Vec::new();
+    }
+    let mut actions = vec![table_action(&id, "filter", "Filter")];
+    if !filter.is_empty() {
+        actions.push(table_action(&id, "clear", "Clear"));
+    }
+    vec![
+        json!({
+            "type": "Input.Text",
+            "id": format!("{id}-filter"),
+            "placeholder": "Filter",
+            "value": filter
+        }),
+        json!({ "type": "ActionSet", "id": format!("{id}-filter-actions"), "actions": actions }),
+    ]
+}
+
+fn decorate_header(header: &mut Value, id: &str, sort_column: Option<usize>, descending: bool) {
+    let Some(Value::Array(cells)) = header.get_mut("cells") else {
+        return;
+    };
+    for (column, cell) in cells.iter_mut().enumerate() {
+        let Value::Object(cell) = cell else {
+            continue;
+        };
+        cell.insert(
+            "selectAction".into(),
+            json!({
+                "type": "Action.Submit",
+                "id": format!("{id}-sort-{column}"),
+                "title": "Sort",
+                "data": { TABLE_KEY: { "table": id, "action": "sort", "column": column } }
+            }),
+        );
+        if sort_column == Some(column)
+            && let Some(text) = cell
+                .get_mut("items")
+                .and_then(|items| items.get_mut(0))
+                .and_then(|first| first.get_mut("text"))
+            && let Value::String(label) = text
+        {
+            label.push_str(if descending { " ▼" } else { " ▲" });
+        }
+    }
+}
+
+fn table_action(id: &str, action: &str, title: &str) -> Value {
+    json!({
+        "type": "Action.Submit",
+        "id": format!("{id}-{action}"),
+        "title": title,
+        "data": { TABLE_KEY: { "table": id, "action": action } }
+    })
+}
+
+fn row_cells(row: &Value) -> &[Value] {
+    row.get("cells")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Visible text of a cell: its TextBlock texts and RichTextBlock inlines, space-joined.
+fn cell_text(cell: &Value) -> String {
+    fn collect(value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    match (key.as_str(), child) {
+                        ("text", Value::String(text)) => out.push(text.clone()),
+                        ("data" | "selectAction", _) => {}
+                        (_, Value::Object(_) | Value::Array(_)) => collect(child, out),
+                        _ => {}
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| collect(item, out)),
+            Value::String(text) => out.push(text.clone()),
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    if let Some(items) = cell.get("items") {
+        collect(items, &mut out);
+    }
+    out.join(" ")
+}
+
+/// Numbers compare numerically, everything else case-insensitively; empty cells sort last.
+fn compare_cells(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    let (a, b) = (a.map(cell_text), b.map(cell_text));
+    let (a, b) = (a.unwrap_or_default(), b.unwrap_or_default());
+    match (a.is_empty(), b.is_empty()) {
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        _ => {}
+    }
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::cache;
+use crate::model::{CardInteraction, IssueSeverity, TelemetryEvent, TelemetryLevel};
+use crate::render::RenderOutcome;
+
+/// Stable telemetry event names.
+pub const RENDER_EVENT: &str = "adaptive_card.render";
+pub const INTERACTION_EVENT: &str = "adaptive_card.interaction";
+pub const BINDINGS_EVENT: &str = "adaptive_card.bindings";
+pub const VALIDATION_EVENT: &str = "adaptive_card.validation";
+pub const HYDRATE_EVENT: &str = "adaptive_card.hydrate";
+pub const CACHE_EVENT: &str = "adaptive_card.cache";
+
+/// Builds the event for a partial (hydrated) re-render.
+pub fn hydrate_event(
+    level: &TelemetryLevel,
+    rebound: usize,
+    elapsed: Duration,
+) -> Option<TelemetryEvent> {
+    (*level != TelemetryLevel::Off).then(|| TelemetryEvent {
+        name: HYDRATE_EVENT.to_string(),
+        properties: json!({
+            "duration_ms": elapsed.as_secs_f64() * 1000.0,
+            "rebound_values": rebound,
+        }),
+    })
+}
+
+/// Builds the telemetry events for one invocation at the requested verbosity.
+pub fn build_events(
+    level: &TelemetryLevel,
+    rendered: &RenderOutcome,
+    interaction: Option<&CardInteraction>,
+    verb: Option<&str>,
+    elapsed: Duration,
+) -> Vec<TelemetryEvent> {
+    if *level == TelemetryLevel::Off {
+        return Vec::new();
+    }
+    let summary = &rendered.binding_summary;
+    let issues = &rendered.validation_issues;
+    let errors = issues
+        .iter()
+        .filter(|i| matches!(i.severity, None | Some(IssueSeverity::Error)))
+        .count();
+
+    let mut events = vec![TelemetryEvent {
+        name: RENDER_EVENT.to_string(),
+        properties: json!({
+            "duration_ms": elapsed.as_secs_f64() * 1000.0,
+            "expression_evaluations": summary.expression_evaluations
+                + summary.placeholder_replacements,
+            "expression_failures": summary.missing_paths,
+            "validation_issues": issues.len(),
+            "validation_errors": errors,
+            "dropped_elements": rendered.dropped_elements,
+            "variants": rendered.variants.len(),
+        }),
+    }];
+    if let Some(interaction) = interaction {
+        events.push(TelemetryEvent {
+            name: INTERACTION_EVENT.to_string(),
+            properties: json!({
+                "type": interaction.interaction_type,
+                "action_id": interaction.action_id,
+                "verb": verb,
+            }),
+        });
+    }
+    if *level == TelemetryLevel::Verbose {
+        events.push(TelemetryEvent {
+            name: BINDINGS_EVENT.to_string(),
+            properties: json!({
+                "handlebars_expansions": summary.handlebars_expansions,
+                "placeholder_replacements": summary.placeholder_replacements,
+                "expression_evaluations": summary.expression_evaluations,
+                "missing_paths": summary.missing_paths,
+            }),
+        });
+        let mut by_code: BTreeMap<&str, usize> = BTreeMap::new();
+        for issue in issues {
+            *by_code.entry(issue.code.as_str()).or_default() += 1;
+        }
+        events.push(TelemetryEvent {
+            name: VALIDATION_EVENT.to_string(),
+            properties: json!({ "by_code": by_code }),
+        });
+        events.push(TelemetryEvent {
+            name: CACHE_EVENT.to_string(),
+            properties: json!(cache::stats()),
+        });
+    }
+    events
+}
@@ -0,0 +1,194 @@
+//! Golden-file test helpers for crates that render cards with this component.
+//!
+//! Load a template and its sample data, render, and compare the result against a stored
+//! golden card. Set `UPDATE_GOLDEN=1` to (re)write golden files instead of comparing.
+//! [`strategies`] adds proptest generators for property-testing interaction handling.
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::expression::equals;
+use crate::handle_invocation;
+use crate::model::{AdaptiveCardInvocation, CardSource, CardSpec};
+
+pub mod strategies;
+
+/// Environment variable that switches [`assert_golden`] to rewriting golden files.
+pub const UPDATE_GOLDEN_ENV: &str = "UPDATE_GOLDEN";
+
+/// Controls how [`semantic_diff`] compares cards. Object keys are always order-insensitive;
+/// arrays stored under a key listed in `unordered_arrays` are compared as multisets.
+#[derive(Clone, Debug)]
+pub struct DiffOptions {
+    pub unordered_arrays: Vec<String>,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            unordered_arrays: vec!["targetElements".to_string()],
+        }
+    }
+}
+
+/// Reads a JSON file, panicking with the path on failure.
+pub fn load_json(path: impl AsRef<Path>) -> Value {
+    let path = path.as_ref();
+    let raw = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("read fixture {}: {err}", path.display()));
+    serde_json::from_str(&raw)
+        .unwrap_or_else(|err| panic!("parse fixture {}: {err}", path.display()))
+}
+
+/// Builds an inline invocation for `template`. Sample data with a `payload`, `state`,
+/// `session`, or `params` key fills those fields; any other object is used as the payload.
+pub fn fixture_invocation(template: Value, data: Option<Value>) -> AdaptiveCardInvocation {
+    let mut invocation = AdaptiveCardInvocation {
+        card_source: CardSource::Inline,
+        card_spec: CardSpec {
+            inline_json: Some(template),
+            ..Default::default()
+        },
+        payload: Value::Object(Default::default()),
+        session: Value::Object(Default::default()),
+        state: Value::Object(Default::default()),
+        ..Default::default()
+    };
+    let Some(data) = data else {
+        return invocation;
+    };
+    let sections = ["payload", "state", "session", "params"];
+    if sections.iter().any(|key| data.get(key).is_some()) {
+        for (key, target) in [
+            ("payload", &mut invocation.payload),
+            ("state", &mut invocation.state),
+            ("session", &mut invocation.session),
+        ] {
+            if let Some(value) = data.get(key) {
+                *target = value.clone();
+            }
+        }
+        invocation.card_spec.template_params = data.get("params").cloned();
+    } else {
+        invocation.payload = data;
+    }
+    invocation
+}
+
+/// Loads a template and optional sample data from disk and renders the card.
+pub fn render_fixture(template: impl AsRef<Path>, data: Option<&Path>) -> Value {
+    let invocation = fixture_invocation(load_json(template), data.map(load_json));
+    render(invocation)
+}
+
+/// Renders an invocation, panicking with the component error on failure.
+pub fn render(invocation: AdaptiveCardInvocation) -> Value {
+    handle_invocation(invocation)
+        .unwrap_or_else(|err| panic!("render failed: {err}"))
+        .rendered_card
+        .unwrap_or_else(|| panic!("render returned no card (validate-only mode?)"))
+}
+
+/// Compares `actual` with the golden card at `golden` using [`DiffOptions::default`].
+pub fn assert_golden(actual: &Value, golden: impl AsRef<Path>) {
+    assert_golden_with(actual, golden, &DiffOptions::default());
+}
+
+/// Compares `actual` with the golden card at `golden`, panicking with one line per
+/// difference. With `UPDATE_GOLDEN=1` (or a missing golden file) the file is written instead.
+pub fn assert_golden_with(actual: &Value, golden: impl AsRef<Path>, options: &DiffOptions) {
+    let golden = golden.as_ref();
+    let update = std::env::var(UPDATE_GOLDEN_ENV).is_ok_and(|v| v == "1");
+    if update || !golden.exists() {
+        let pretty = serde_json::to_string_pretty(actual).expect("serialize golden");
+        if let Some(parent) = golden.parent() {
+            std::fs::create_dir_all(parent)
+                .unwrap_or_else(|err| panic!("create {}: {err}", parent.display()));
+        }
+        std::fs::write(golden, pretty + "\n")
+            .unwrap_or_else(|err| panic!("write golden {}: {err}", golden.display()));
+        return;
+    }
+    let diffs = semantic_diff(&load_json(golden), actual, options);
+    if !diffs.is_empty() {
+        panic!(
+            "rendered card differs from {} ({} difference(s); rerun with {UPDATE_GOLDEN_ENV}=1 to accept):\n  {}",
+            golden.display(),
+            diffs.len(),
+            diffs.join("\n  ")
+        );
+    }
+}
+
+/// Lists the differences between two cards as `pointer: message` lines. Numbers compare by
+/// value (`1` equals `1.0`).
+pub fn semantic_diff(expected: &Value, actual: &Value, options: &DiffOptions) -> Vec<String> {
+    let mut diffs = Vec::new();
+    diff_at(expected, actual, "", None, options, &mut diffs);
+    diffs
+}
+
+fn diff_at(
+    expected: &Value,
+    actual: &Value,
+    pointer: &str,
+    key: Option<&str>,
+    options: &DiffOptions,
+    diffs: &mut Vec<String>,
+) {
+    match (expected, actual) {
+        (Value::Object(exp), Value::Object(act)) => {
+            for (k, exp_value) in exp {
+                let child = format!("{pointer}/{k}");
+                match act.get(k) {
+                    Some(act_value) => {
+                        diff_at(exp_value, act_value, &child, Some(k), options, diffs)
+                    }
+                    None => diffs.push(format!("{child}: missing (expected {exp_value})")),
+                }
+            }
+            for (k, act_value) in act {
+                if !exp.contains_key(k) {
+                    diffs.push(format!("{pointer}/{k}: unexpected {act_value}"));
+                }
+            }
+        }
+        (Value::Array(exp), Value::Array(act))
+            if key.is_some_and(|k| options.unordered_arrays.iter().any(|u| u == k)) =>
+        {
+            let mut unmatched: Vec<&Value> = act.iter().collect();
+            for item in exp {
+                match unmatched
+                    .iter()
+                    .position(|candidate| semantic_diff(item, candidate, options).is_empty())
+                {
+                    Some(idx) => {
+                        unmatched.remove(idx);
+                    }
+                    None => diffs.push(format!("{pointer}: missing item {item}")),
+                }
+            }
+            for item in unmatched {
+                diffs.push(format!("{pointer}: unexpected item {item}"));
+            }
+        }
+        (Value::Array(exp), Value::Array(act)) => {
+            for (idx, (e, a)) in exp.iter().zip(act).enumerate() {
+                diff_at(e, a, &format!("{pointer}/{idx}"), None, options, diffs);
+            }
+            if exp.len() != act.len() {
+                diffs.push(format!(
+                    "{pointer}: expected {} item(s), got {}",
+                    exp.len(),
+                    act.len()
+                ));
+            }
+        }
+        (e, a) if equals(e, a) && std::mem::discriminant(e) == std::mem::discriminant(a) => {}
+        (e, a) => diffs.push(format!(
+            "{}: expected {e}, got {a}",
+            if pointer.is_empty() { "/" } else { pointer }
+        )),
+    }
+}
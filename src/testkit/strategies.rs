@@ -0,0 +1,317 @@
+//! Proptest strategies for the model types and generators for interactions that a given card
+//! could actually receive: a declared action (or input, for drafts) plus raw inputs whose
+//! values match each declared input type.
+
+use once_cell::sync::Lazy;
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+use proptest::sample::select;
+use serde_json::{Map, Value};
+
+use crate::actions::collect_actions;
+use crate::handle_invocation;
+use crate::inputs::{InputDeclaration, collect_inputs};
+use crate::model::{
+    AdaptiveCardInvocation, AdaptiveCardResult, CardInteraction, CardInteractionType, ColorScheme,
+    InvocationMode, SessionUpdateOp, ShowCardMode, StateUpdateOp, TelemetryLevel, ValidationMode,
+};
+
+use super::fixture_invocation;
+
+static OUTPUT_SCHEMA: Lazy<jsonschema::Validator> = Lazy::new(|| {
+    let schema: Value = serde_json::from_str(include_str!("../../schemas/io/output.schema.json"))
+        .expect("output schema JSON must be valid");
+    jsonschema::validator_for(&schema).expect("output schema must compile")
+});
+
+pub fn arb_interaction_type() -> impl Strategy<Value = CardInteractionType> {
+    prop_oneof![
+        Just(CardInteractionType::Submit),
+        Just(CardInteractionType::Execute),
+        Just(CardInteractionType::OpenUrl),
+        Just(CardInteractionType::ShowCard),
+        Just(CardInteractionType::ToggleVisibility),
+        Just(CardInteractionType::InputChanged),
+    ]
+}
+
+pub fn arb_invocation_mode() -> impl Strategy<Value = InvocationMode> {
+    prop_oneof![
+        Just(InvocationMode::Render),
+        Just(InvocationMode::Validate),
+        Just(InvocationMode::RenderAndValidate),
+        Just(InvocationMode::Simulate),
+    ]
+}
+
+pub fn arb_validation_mode() -> impl Strategy<Value = ValidationMode> {
+    prop_oneof![
+        Just(ValidationMode::Off),
+        Just(ValidationMode::Warn),
+        Just(ValidationMode::Error),
+    ]
+}
+
+pub fn arb_telemetry_level() -> impl Strategy<Value = TelemetryLevel> {
+    prop_oneof![
+        Just(TelemetryLevel::Off),
+        Just(TelemetryLevel::Summary),
+        Just(TelemetryLevel::Verbose),
+    ]
+}
+
+pub fn arb_color_scheme() -> impl Strategy<Value = ColorScheme> {
+    prop_oneof![
+        Just(ColorScheme::Light),
+        Just(ColorScheme::Dark),
+        Just(ColorScheme::Both),
+    ]
+}
+
+pub fn arb_show_card_mode() -> impl Strategy<Value = ShowCardMode> {
+    prop_oneof![Just(ShowCardMode::Inline), Just(ShowCardMode::Separate)]
+}
+
+/// Arbitrary JSON up to three levels deep with short lowercase keys.
+pub fn arb_json() -> BoxedStrategy<Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i32>().prop_map(Value::from),
+        "[ -~]{0,12}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..4).prop_map(Value::Array),
+            btree_map("[a-z]{1,6}", inner, 0..4)
+                .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+    .boxed()
+}
+
+/// Dotted state/session path such as `form.step`.
+pub fn arb_path() -> impl Strategy<Value = String> {
+    "[a-z]{1,6}(\\.[a-z]{1,6}){0,2}"
+}
+
+pub fn arb_state_update_op() -> impl Strategy<Value = StateUpdateOp> {
+    prop_oneof![
+        (arb_path(), arb_json()).prop_map(|(path, value)| StateUpdateOp::Set { path, value }),
+        (arb_path(), arb_json()).prop_map(|(path, value)| StateUpdateOp::Merge { path, value }),
+        arb_path().prop_map(|path| StateUpdateOp::Delete { path }),
+    ]
+}
+
+pub fn arb_session_update_op() -> impl Strategy<Value = SessionUpdateOp> {
+    prop_oneof![
+        arb_path().prop_map(|route| SessionUpdateOp::SetRoute { route }),
+        ("[a-z]{1,8}", arb_json())
+            .prop_map(|(key, value)| SessionUpdateOp::SetAttribute { key, value }),
+        "[a-z]{1,8}".prop_map(|key| SessionUpdateOp::DeleteAttribute { key }),
+        "[a-z-]{1,12}".prop_map(|card_id| SessionUpdateOp::PushCardStack { card_id }),
+        Just(SessionUpdateOp::PopCardStack),
+        ("[a-z]{1,8}", arb_json())
+            .prop_map(|(name, value)| SessionUpdateOp::SetVariable { name, value }),
+        "[a-z]{1,8}".prop_map(|name| SessionUpdateOp::UnsetVariable { name }),
+        btree_map("[a-z]{1,6}", arb_json(), 0..3).prop_map(|values| {
+            SessionUpdateOp::MergeVariables {
+                values: Value::Object(values.into_iter().collect()),
+            }
+        }),
+    ]
+}
+
+/// Raw inputs for a random subset of the card's declared inputs, each value shaped like what
+/// a client would submit for that input type.
+pub fn arb_inputs(card: &Value) -> BoxedStrategy<Value> {
+    let fields = collect_inputs(card)
+        .iter()
+        .map(|decl| {
+            let id = decl.id.clone();
+            proptest::option::of(input_value(decl))
+                .prop_map(move |value| value.map(|value| (id.clone(), value)))
+                .boxed()
+        })
+        .collect::<Vec<_>>();
+    fields
+        .prop_map(|fields| Value::Object(fields.into_iter().flatten().collect::<Map<_, _>>()))
+        .boxed()
+}
+
+/// Interactions the card can receive: one per declared action with an id (typed after the
+/// action) plus an `InputChanged` draft per declared input. Cards without either fall back to
+/// a Submit for an undeclared `submit` action.
+pub fn arb_interaction(card: &Value) -> BoxedStrategy<CardInteraction> {
+    let mut targets: Vec<(CardInteractionType, String, Option<String>)> = collect_actions(card)
+        .into_iter()
+        .filter_map(|action| {
+            let kind = match action.kind.as_str() {
+                "Action.Submit" => CardInteractionType::Submit,
+                "Action.Execute" => CardInteractionType::Execute,
+                "Action.OpenUrl" => CardInteractionType::OpenUrl,
+                "Action.ShowCard" => CardInteractionType::ShowCard,
+                "Action.ToggleVisibility" => CardInteractionType::ToggleVisibility,
+                _ => return None,
+            };
+            let verb = action.verb().map(str::to_string);
+            Some((kind, action.id?, verb))
+        })
+        .collect();
+    let declared = collect_inputs(card);
+    targets.extend(
+        declared
+            .iter()
+            .map(|decl| (CardInteractionType::InputChanged, decl.id.clone(), None)),
+    );
+    if targets.is_empty() {
+        targets.push((CardInteractionType::Submit, "submit".to_string(), None));
+    }
+
+    let inputs = arb_inputs(card);
+    let drafts: Vec<(String, BoxedStrategy<Value>)> = declared
+        .iter()
+        .map(|decl| (decl.id.clone(), input_value(decl)))
+        .collect();
+    let meta = ("[a-z0-9]{4,12}", proptest::option::of("[a-z0-9]{8}"));
+    (select(targets), meta)
+        .prop_flat_map(
+            move |((kind, action_id, verb), (instance, idempotency_key))| {
+                let raw_inputs = if kind == CardInteractionType::InputChanged {
+                    let (id, value) = drafts
+                        .iter()
+                        .find(|(id, _)| *id == action_id)
+                        .cloned()
+                        .expect("draft target is a declared input");
+                    value
+                        .prop_map(move |value| {
+                            let mut map = Map::new();
+                            map.insert(id.clone(), value);
+                            Value::Object(map)
+                        })
+                        .boxed()
+                } else {
+                    inputs.clone()
+                };
+                raw_inputs.prop_map(move |raw_inputs| CardInteraction {
+                    enabled: Some(true),
+                    interaction_type: kind.clone(),
+                    action_id: action_id.clone(),
+                    verb: verb.clone(),
+                    raw_inputs,
+                    card_instance_id: format!("card-{instance}"),
+                    metadata: Value::Null,
+                    idempotency_key: idempotency_key.clone(),
+                    dialog_id: None,
+                })
+            },
+        )
+        .boxed()
+}
+
+/// Interaction invocations against an inline `card` with varied mode, validation mode, and
+/// telemetry level. State and session start empty so runs never touch the state store.
+pub fn arb_interaction_invocation(card: Value) -> BoxedStrategy<AdaptiveCardInvocation> {
+    (
+        arb_interaction(&card),
+        arb_invocation_mode(),
+        arb_validation_mode(),
+        arb_telemetry_level(),
+    )
+        .prop_map(
+            move |(interaction, mode, validation_mode, telemetry)| AdaptiveCardInvocation {
+                interaction: Some(interaction),
+                mode,
+                validation_mode,
+                telemetry,
+                ..fixture_invocation(card.clone(), None)
+            },
+        )
+        .boxed()
+}
+
+/// Checks a result against `schemas/io/output.schema.json`, returning one
+/// `pointer: message` line per violation.
+pub fn output_schema_errors(result: &AdaptiveCardResult) -> Vec<String> {
+    let value = serde_json::to_value(result).expect("serialize result");
+    OUTPUT_SCHEMA
+        .iter_errors(&value)
+        .map(|error| format!("{}: {error}", error.instance_path()))
+        .collect()
+}
+
+/// Runs `invocation` and asserts the component contract: no panic, and any successful result
+/// is schema-valid. Errors are allowed (e.g. strict validation rejecting the inputs) and are
+/// returned so callers can assert on them.
+pub fn check_invocation(invocation: AdaptiveCardInvocation) -> Result<(), String> {
+    match handle_invocation(invocation) {
+        Ok(result) => {
+            let errors = output_schema_errors(&result);
+            assert!(
+                errors.is_empty(),
+                "result violates output schema:\n  {}",
+                errors.join("\n  ")
+            );
+            Ok(())
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn input_value(decl: &InputDeclaration) -> BoxedStrategy<Value> {
+    let element = &decl.element;
+    let text = |key: &str| element.get(key).and_then(Value::as_str).map(str::to_string);
+    let number = |key: &str| element.get(key).and_then(Value::as_f64);
+    match decl.kind.as_str() {
+        "Input.Number" | "Input.Rating" | "Input.Slider" => {
+            let min = number("min").unwrap_or(-1000.0);
+            let max = number("max").unwrap_or(1000.0).max(min);
+            prop_oneof![
+                (min..=max).prop_map(Value::from),
+                (min..=max).prop_map(|n| Value::String(n.to_string())),
+                Just(Value::String(String::new())),
+            ]
+            .boxed()
+        }
+        "Input.Toggle" => {
+            let on = text("valueOn").unwrap_or_else(|| "true".to_string());
+            let off = text("valueOff").unwrap_or_else(|| "false".to_string());
+            prop_oneof![
+                select(vec![on, off]).prop_map(Value::String),
+                any::<bool>().prop_map(Value::Bool),
+            ]
+            .boxed()
+        }
+        "Input.Date" => "(19|20)[0-9]{2}-(0[1-9]|1[0-2])-(0[1-9]|1[0-9]|2[0-8])"
+            .prop_map(Value::String)
+            .boxed(),
+        "Input.Time" => "([01][0-9]|2[0-3]):[0-5][0-9]"
+            .prop_map(Value::String)
+            .boxed(),
+        "Input.ColorPicker" => "#[0-9a-f]{6}".prop_map(Value::String).boxed(),
+        "Input.ChoiceSet" => {
+            let choices: Vec<String> = element
+                .get("choices")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|choice| choice.get("value").and_then(Value::as_str))
+                .map(str::to_string)
+                .collect();
+            let multi = element
+                .get("isMultiSelect")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            if choices.is_empty() {
+                "[a-z]{0,8}".prop_map(Value::String).boxed()
+            } else if multi {
+                proptest::sample::subsequence(choices.clone(), 0..=choices.len())
+                    .prop_map(|picked| Value::String(picked.join(",")))
+                    .boxed()
+            } else {
+                select(choices).prop_map(Value::String).boxed()
+            }
+        }
+        _ => "[ -~]{0,24}".prop_map(Value::String).boxed(),
+    }
+}
@@ -0,0 +1,130 @@
+use serde_json::{Value, json};
+
+use crate::model::{AdaptiveCardInvocation, ColorScheme, ValidationIssue};
+
+const TOKEN_PREFIX: &str = "@theme.";
+
+/// Built-in theme. Values are Adaptive Card enum names so cards stay valid on any host; tenant
+/// themes may swap them for other enum values or literal URLs/colors where the schema allows.
+pub fn default_theme() -> Value {
+    json!({
+        "colors": {
+            "text": "Default",
+            "accent": "Accent",
+            "good": "Good",
+            "warning": "Warning",
+            "attention": "Attention",
+            "subtle": "Default"
+        },
+        "spacing": {
+            "tight": "Small",
+            "default": "Default",
+            "loose": "Large"
+        },
+        "fontSizes": {
+            "caption": "Small",
+            "body": "Default",
+            "title": "Large",
+            "hero": "ExtraLarge"
+        },
+        "fontWeights": {
+            "body": "Default",
+            "title": "Bolder"
+        },
+        "containerStyles": {
+            "card": "default",
+            "highlight": "emphasis",
+            "brand": "accent",
+            "success": "good",
+            "error": "attention"
+        }
+    })
+}
+
+/// Resolves the effective theme: defaults, then `theme`, then the tenant's override, then the
+/// `schemes.<light|dark>` section of the merged theme for the requested color scheme.
+pub fn resolve_theme(inv: &AdaptiveCardInvocation, scheme: Option<&ColorScheme>) -> Value {
+    let mut theme = default_theme();
+    if let Some(overrides) = inv.theme.as_ref() {
+        deep_merge(&mut theme, overrides);
+    }
+    let tenant = inv.tenant.clone().or_else(|| {
+        inv.envelope
+            .as_ref()
+            .map(|env| env.ctx.tenant.as_str().to_string())
+    });
+    if let Some(tenant) = tenant
+        && let Some(overrides) = inv.tenant_themes.get(&tenant)
+    {
+        deep_merge(&mut theme, overrides);
+    }
+    let scheme_key = match scheme {
+        Some(ColorScheme::Light) => Some("light"),
+        Some(ColorScheme::Dark) => Some("dark"),
+        _ => None,
+    };
+    if let Some(key) = scheme_key
+        && let Some(overrides) = theme.get("schemes").and_then(|s| s.get(key)).cloned()
+    {
+        deep_merge(&mut theme, &overrides);
+    }
+    theme
+}
+
+/// Recursively merges objects; any other overlay value replaces the target.
+pub fn deep_merge(target: &mut Value, overlay: &Value) {
+    match (target, overlay) {
+        (Value::Object(dst), Value::Object(src)) => {
+            for (key, value) in src {
+                match dst.get_mut(key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        dst.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, overlay) => *target = overlay.clone(),
+    }
+}
+
+/// Replaces string values of the form `@theme.<path>` with the theme value at that path.
+/// Unknown tokens are left in place and reported.
+pub fn apply_theme_tokens(card: &mut Value, theme: &Value) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    visit(card, "", theme, &mut issues);
+    issues
+}
+
+fn visit(value: &mut Value, path: &str, theme: &Value, issues: &mut Vec<ValidationIssue>) {
+    match value {
+        Value::String(text) => {
+            let Some(token) = text.trim().strip_prefix(TOKEN_PREFIX) else {
+                return;
+            };
+            let resolved = token
+                .split('.')
+                .try_fold(theme, |node, segment| node.get(segment));
+            match resolved {
+                Some(found) => *value = found.clone(),
+                None => issues.push(ValidationIssue {
+                    code: "unknown-theme-token".into(),
+                    message: format!("Theme has no value for '{token}'"),
+                    path: path.to_string(),
+                    severity: None,
+                }),
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                visit(item, &format!("{path}/{idx}"), theme, issues);
+            }
+        }
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                visit(child, &format!("{path}/{key}"), theme, issues);
+            }
+        }
+        _ => {}
+    }
+}
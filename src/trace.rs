@@ -1,6 +1,7 @@
 use serde_json::{Map, Value};
 
 use crate::model::{AdaptiveCardInvocation, CardInteraction, TelemetryEvent};
+use crate::redaction::redact_inputs;
 use crate::render::{AssetResolution, BindingSummary};
 
 pub fn trace_enabled() -> bool {
@@ -79,7 +80,8 @@ pub fn build_trace_event(
                 "payload": invocation.payload,
                 "session": invocation.session,
                 "state": invocation.state,
-                "interaction_raw_inputs": interaction.map(|i| i.raw_inputs.clone())
+                "interaction_raw_inputs": interaction
+                    .map(|i| redact_inputs(&i.raw_inputs, invocation.redaction.as_ref()))
             }),
         );
     }
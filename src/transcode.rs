@@ -0,0 +1,342 @@
+use serde_json::{Map, Value, json};
+
+use crate::markdown::plain_text;
+use crate::model::{
+    AdaptiveCardInvocation, CardInteraction, CardInteractionType, EmailOptions, IssueSeverity,
+    OutputTarget, TranscodedMessage, ValidationIssue,
+};
+use crate::theme;
+use crate::urls::{url_decode, url_encode};
+
+mod discord;
+mod email;
+mod whatsapp;
+
+/// Invocation settings the transcoders read besides the card.
+#[derive(Clone, Debug, Default)]
+pub struct TranscodeContext {
+    /// Resolved theme, for the branding of email.
+    pub theme: Value,
+    pub email: EmailOptions,
+    /// Instance carried by email links.
+    pub card_instance_id: Option<String>,
+}
+
+impl TranscodeContext {
+    pub fn from_invocation(invocation: &AdaptiveCardInvocation) -> Self {
+        TranscodeContext {
+            theme: theme::resolve_theme(invocation, invocation.color_scheme.as_ref()),
+            email: invocation.email.clone().unwrap_or_default(),
+            card_instance_id: invocation.card_instance_id.clone().or_else(|| {
+                invocation
+                    .interaction
+                    .as_ref()
+                    .map(|interaction| interaction.card_instance_id.clone())
+            }),
+        }
+    }
+}
+
+/// Transcodes the rendered card into `target`'s message format. Features the channel
+/// cannot express are reported as `transcode-unsupported` warnings, text cut to the
+/// channel's limits as `transcode-truncated`.
+pub fn transcode(
+    card: &Value,
+    target: OutputTarget,
+    context: &TranscodeContext,
+) -> (TranscodedMessage, Vec<ValidationIssue>) {
+    let (content, issues) = match target {
+        OutputTarget::WhatsApp => whatsapp::transcode(card),
+        OutputTarget::Discord => discord::transcode(card),
+        OutputTarget::Email => email::transcode(card, context),
+    };
+    (TranscodedMessage { target, content }, issues)
+}
+
+/// Card element or action reached by [`collect`], with its JSON pointer in the card.
+pub(crate) struct Node<'a> {
+    pub path: String,
+    pub kind: &'a str,
+    pub map: &'a Map<String, Value>,
+}
+
+/// Visible content of a card in document order: leaf elements (layout containers are
+/// flattened away) and actions, both the card's and those of ActionSets.
+#[derive(Default)]
+pub(crate) struct Collected<'a> {
+    pub elements: Vec<Node<'a>>,
+    pub actions: Vec<Node<'a>>,
+}
+
+const LAYOUT: [&str; 6] = [
+    "Container",
+    "ColumnSet",
+    "Column",
+    "Table",
+    "TableRow",
+    "TableCell",
+];
+
+pub(crate) fn collect(card: &Value) -> Collected<'_> {
+    fn visit<'a>(value: &'a Value, path: String, out: &mut Collected<'a>) {
+        let Value::Object(map) = value else {
+            return;
+        };
+        if map.get("isVisible").and_then(Value::as_bool) == Some(false) {
+            return;
+        }
+        let kind = map.get("type").and_then(Value::as_str).unwrap_or("");
+        if kind == "ActionSet" {
+            visit_actions(map, &path, out);
+        } else if !LAYOUT.contains(&kind) {
+            out.elements.push(Node { path, kind, map });
+            return;
+        }
+        for key in ["items", "columns", "rows", "cells"] {
+            for (idx, child) in map
+                .get(key)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                visit(child, format!("{path}/{key}/{idx}"), out);
+            }
+        }
+    }
+    fn visit_actions<'a>(map: &'a Map<String, Value>, path: &str, out: &mut Collected<'a>) {
+        let actions = map
+            .get("actions")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for (idx, action) in actions.enumerate() {
+            if let Value::Object(action) = action
+                && action.get("isVisible").and_then(Value::as_bool) != Some(false)
+            {
+                out.actions.push(Node {
+                    path: format!("{path}/actions/{idx}"),
+                    kind: action.get("type").and_then(Value::as_str).unwrap_or(""),
+                    map: action,
+                });
+            }
+        }
+    }
+
+    let mut out = Collected::default();
+    let body = card
+        .get("body")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    for (idx, element) in body.enumerate() {
+        visit(element, format!("/body/{idx}"), &mut out);
+    }
+    if let Some(root) = card.as_object() {
+        visit_actions(root, "", &mut out);
+    }
+    out
+}
+
+/// Plain-text lines of a text element (TextBlock, RichTextBlock, or FactSet), or `None`
+/// for any other element.
+pub(crate) fn text_lines(node: &Node<'_>) -> Option<Vec<String>> {
+    let lines = match node.kind {
+        "TextBlock" => vec![plain_text(node.map.get("text")?.as_str()?)],
+        "RichTextBlock" => vec![
+            node.map
+                .get("inlines")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|inline| match inline {
+                    Value::String(text) => Some(text.as_str()),
+                    other => other.get("text").and_then(Value::as_str),
+                })
+                .collect(),
+        ],
+        "FactSet" => node
+            .map
+            .get("facts")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(|fact| {
+                let title = fact.get("title").and_then(Value::as_str).unwrap_or("");
+                let value = fact.get("value").and_then(Value::as_str).unwrap_or("");
+                format!("{title}: {value}")
+            })
+            .collect(),
+        _ => return None,
+    };
+    Some(
+        lines
+            .into_iter()
+            .filter(|line| !line.trim().is_empty())
+            .collect(),
+    )
+}
+
+/// Whether a TextBlock reads as a title: `style: heading`, or a large or extra-large size.
+pub(crate) fn is_heading(node: &Node<'_>) -> bool {
+    node.kind == "TextBlock"
+        && (enum_value(node.map, "style").as_deref() == Some("heading")
+            || matches!(
+                enum_value(node.map, "size").as_deref(),
+                Some("large" | "extralarge")
+            ))
+}
+
+/// The enum property `key` lowercased: hosts read `Large` and `large` alike.
+pub(crate) fn enum_value(map: &Map<String, Value>, key: &str) -> Option<String> {
+    map.get(key)
+        .and_then(Value::as_str)
+        .map(str::to_ascii_lowercase)
+}
+
+/// Whether the action posts back to the component (Submit or Execute).
+pub(crate) fn posts(node: &Node<'_>) -> bool {
+    matches!(node.kind, "Action.Submit" | "Action.Execute")
+}
+
+pub(crate) fn title(node: &Node<'_>) -> String {
+    node.map
+        .get("title")
+        .and_then(Value::as_str)
+        .or_else(|| node.map.get("id").and_then(Value::as_str))
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Reply id carried by a transcoded button or choice: `submit:<action id>` or
+/// `execute:<action id>`, plus `?<input id>=<value>` for a choice, each part
+/// percent-encoded. [`reply_interaction`] turns it back into a `CardInteraction`. `None` for
+/// an action without an id, whose replies could not be routed.
+pub(crate) fn reply_id(action: &Node<'_>, choice: Option<(&str, &str)>) -> Option<String> {
+    let prefix = if action.kind == "Action.Execute" {
+        "execute"
+    } else {
+        "submit"
+    };
+    let id = action
+        .map
+        .get("id")
+        .and_then(Value::as_str)
+        .filter(|id| !id.is_empty())?;
+    let mut reply = format!("{prefix}:{}", url_encode(id));
+    if let Some((input, value)) = choice {
+        reply.push_str(&format!("?{}={}", url_encode(input), url_encode(value)));
+    }
+    Some(reply)
+}
+
+/// Builds the interaction for a reply id produced by a transcoded card (a WhatsApp button
+/// or list reply id, or a Discord button `custom_id`), to pass to `handle_interaction`.
+/// Returns `None` for ids this component did not produce.
+pub fn reply_interaction(reply_id: &str, card_instance_id: &str) -> Option<CardInteraction> {
+    let (prefix, rest) = reply_id.split_once(':')?;
+    let interaction_type = match prefix {
+        "submit" => CardInteractionType::Submit,
+        "execute" => CardInteractionType::Execute,
+        _ => return None,
+    };
+    let (action_id, choice) = match rest.split_once('?') {
+        Some((action_id, choice)) => (action_id, Some(choice)),
+        None => (rest, None),
+    };
+    let mut raw_inputs = Map::new();
+    if let Some(choice) = choice {
+        let (input, value) = choice.split_once('=')?;
+        raw_inputs.insert(url_decode(input), json!(url_decode(value)));
+    }
+    Some(CardInteraction {
+        interaction_type,
+        action_id: url_decode(action_id),
+        raw_inputs: Value::Object(raw_inputs),
+        card_instance_id: card_instance_id.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Builds the interaction for a Discord select menu: `custom_id` names the action and the
+/// input (`submit:<action id>?<input id>=`), and the selected `values` become the input's
+/// value, comma-joined like a multi-select ChoiceSet submission.
+pub fn select_interaction(
+    custom_id: &str,
+    values: &[String],
+    card_instance_id: &str,
+) -> Option<CardInteraction> {
+    let mut interaction = reply_interaction(custom_id, card_instance_id)?;
+    let input = interaction.raw_inputs.as_object()?.keys().next()?.clone();
+    interaction.raw_inputs[input] = json!(values.join(","));
+    Some(interaction)
+}
+
+/// `(title, value)` of each ChoiceSet choice; the value doubles as a missing title.
+pub(crate) fn choices<'a>(node: &Node<'a>) -> Vec<(&'a str, &'a str)> {
+    node.map
+        .get("choices")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|choice| {
+            let value = choice.get("value").and_then(Value::as_str).unwrap_or("");
+            let title = choice.get("title").and_then(Value::as_str).unwrap_or(value);
+            (title, value)
+        })
+        .collect()
+}
+
+/// Cuts `text` to `max` characters, ending it with an ellipsis when anything was dropped.
+pub(crate) fn truncate(text: &str, max: usize) -> (String, bool) {
+    if text.chars().count() <= max {
+        return (text.to_string(), false);
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    (cut, true)
+}
+
+/// [`truncate`]s `text`, reporting a `transcode-truncated` warning at `path` when it was cut.
+pub(crate) fn fit(
+    target: OutputTarget,
+    text: &str,
+    max: usize,
+    path: &str,
+    issues: &mut Vec<ValidationIssue>,
+) -> String {
+    let (text, cut) = truncate(text, max);
+    if cut {
+        issues.push(truncated(target, path, max));
+    }
+    text
+}
+
+pub(crate) fn unsupported(target: OutputTarget, path: &str, what: &str) -> ValidationIssue {
+    ValidationIssue {
+        code: "transcode-unsupported".into(),
+        message: format!("{what} is not supported in {} messages", channel(target)),
+        path: path.into(),
+        severity: Some(IssueSeverity::Warning),
+    }
+}
+
+pub(crate) fn truncated(target: OutputTarget, path: &str, max: usize) -> ValidationIssue {
+    ValidationIssue {
+        code: "transcode-truncated".into(),
+        message: format!(
+            "text cut to the {max} characters {} allows",
+            channel(target)
+        ),
+        path: path.into(),
+        severity: Some(IssueSeverity::Warning),
+    }
+}
+
+fn channel(target: OutputTarget) -> &'static str {
+    match target {
+        OutputTarget::WhatsApp => "WhatsApp",
+        OutputTarget::Discord => "Discord",
+        OutputTarget::Email => "email",
+    }
+}
@@ -0,0 +1,274 @@
+use serde_json::{Map, Value, json};
+
+use super::{
+    Node, choices, collect, fit, is_heading, posts, reply_id, text_lines, title, unsupported,
+};
+use crate::model::{OutputTarget, ValidationIssue};
+
+// Discord API limits.
+const MAX_TITLE: usize = 256;
+const MAX_DESCRIPTION: usize = 4096;
+const MAX_FIELDS: usize = 25;
+const MAX_FIELD_NAME: usize = 256;
+const MAX_FIELD_VALUE: usize = 1024;
+const MAX_ROWS: usize = 5;
+const MAX_BUTTONS_PER_ROW: usize = 5;
+const MAX_LABEL: usize = 80;
+const MAX_OPTIONS: usize = 25;
+const MAX_OPTION_LABEL: usize = 100;
+const MAX_PLACEHOLDER: usize = 150;
+const MAX_CUSTOM_ID: usize = 100;
+const TARGET: OutputTarget = OutputTarget::Discord;
+
+// Message component types and button styles.
+const ACTION_ROW: u8 = 1;
+const BUTTON: u8 = 2;
+const STRING_SELECT: u8 = 3;
+const PRIMARY: u8 = 1;
+const SUCCESS: u8 = 3;
+const DANGER: u8 = 4;
+const LINK: u8 = 5;
+
+/// Maps the card onto a Discord message with one embed and message components:
+///
+/// - a leading heading becomes the embed title, TextBlocks and RichTextBlocks its
+///   description, FactSet facts inline fields, and the first Image its image;
+/// - each ChoiceSet becomes a string select menu in its own row, submitted through the
+///   card's first Submit or Execute action as soon as a choice is made;
+/// - the remaining Submit and Execute actions become buttons (`positive` and `destructive`
+///   styles map to success and danger), and OpenUrl actions link buttons, five per row.
+///
+/// Button `custom_id`s are [`super::reply_interaction`] reply ids; select menu ones end in
+/// `?<input id>=` and go through [`super::select_interaction`] with the selected values.
+pub fn transcode(card: &Value) -> (Value, Vec<ValidationIssue>) {
+    let collected = collect(card);
+    let mut issues = Vec::new();
+    let mut embed = Map::new();
+    let mut lines: Vec<String> = Vec::new();
+    let mut fields: Vec<Value> = Vec::new();
+    let mut selects: Vec<&Node<'_>> = Vec::new();
+    for node in &collected.elements {
+        match node.kind {
+            "TextBlock" if !embed.contains_key("title") && lines.is_empty() && is_heading(node) => {
+                let text = text_lines(node).unwrap_or_default().join(" ");
+                embed.insert(
+                    "title".into(),
+                    json!(fit(TARGET, &text, MAX_TITLE, &node.path, &mut issues)),
+                );
+            }
+            "FactSet" => fields.extend(facts(node, &mut issues)),
+            "Image" if !embed.contains_key("image") => {
+                if let Some(url) = node.map.get("url") {
+                    embed.insert("image".into(), json!({ "url": url }));
+                }
+            }
+            "Input.ChoiceSet" => selects.push(node),
+            _ => match text_lines(node) {
+                Some(text) => lines.extend(text),
+                None => issues.push(unsupported(TARGET, &node.path, node.kind)),
+            },
+        }
+    }
+    if !lines.is_empty() {
+        let description = fit(
+            TARGET,
+            &lines.join("\n"),
+            MAX_DESCRIPTION,
+            "/body",
+            &mut issues,
+        );
+        embed.insert("description".into(), json!(description));
+    }
+    if fields.len() > MAX_FIELDS {
+        issues.push(unsupported(TARGET, "/body", "More than 25 facts"));
+        fields.truncate(MAX_FIELDS);
+    }
+    if !fields.is_empty() {
+        embed.insert("fields".into(), json!(fields));
+    }
+
+    let mut posting = collected.actions.iter().filter(|a| posts(a));
+    let mut rows: Vec<Value> = Vec::new();
+    if !selects.is_empty() {
+        match posting.next() {
+            Some(action) => {
+                for select in &selects {
+                    if let Some(menu) = select_menu(select, action, &mut issues) {
+                        rows.push(json!({ "type": ACTION_ROW, "components": [menu] }));
+                    }
+                }
+            }
+            None => {
+                for select in &selects {
+                    issues.push(unsupported(
+                        TARGET,
+                        &select.path,
+                        "A ChoiceSet without a Submit action",
+                    ));
+                }
+            }
+        }
+    }
+    let posting: Vec<&Node<'_>> = posting.collect();
+    let buttons: Vec<Value> = collected
+        .actions
+        .iter()
+        .filter_map(|action| {
+            if action.kind == "Action.OpenUrl" {
+                return Some(json!({
+                    "type": BUTTON,
+                    "style": LINK,
+                    "label": fit(TARGET, &title(action), MAX_LABEL, &action.path, &mut issues),
+                    "url": action.map.get("url").cloned().unwrap_or(Value::Null),
+                }));
+            }
+            if !posting.iter().any(|p| p.path == action.path) {
+                if !posts(action) {
+                    issues.push(unsupported(TARGET, &action.path, action.kind));
+                }
+                return None;
+            }
+            let custom_id = custom_id(action, None, &mut issues)?;
+            let style = match action.map.get("style").and_then(Value::as_str) {
+                Some("positive") => SUCCESS,
+                Some("destructive") => DANGER,
+                _ => PRIMARY,
+            };
+            let mut button = json!({
+                "type": BUTTON,
+                "style": style,
+                "label": fit(TARGET, &title(action), MAX_LABEL, &action.path, &mut issues),
+                "custom_id": custom_id,
+            });
+            if action.map.get("isEnabled").and_then(Value::as_bool) == Some(false) {
+                button["disabled"] = json!(true);
+            }
+            Some(button)
+        })
+        .collect();
+    for chunk in buttons.chunks(MAX_BUTTONS_PER_ROW) {
+        rows.push(json!({ "type": ACTION_ROW, "components": chunk }));
+    }
+    if rows.len() > MAX_ROWS {
+        issues.push(unsupported(
+            TARGET,
+            "/actions",
+            "More than 5 component rows",
+        ));
+        rows.truncate(MAX_ROWS);
+    }
+
+    let mut message = Map::new();
+    if !embed.is_empty() {
+        message.insert("embeds".into(), json!([embed]));
+    }
+    message.insert("components".into(), json!(rows));
+    (Value::Object(message), issues)
+}
+
+/// Inline embed fields for the facts of a FactSet.
+fn facts(node: &Node<'_>, issues: &mut Vec<ValidationIssue>) -> Vec<Value> {
+    node.map
+        .get("facts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(idx, fact)| {
+            let path = format!("{}/facts/{idx}", node.path);
+            let name = fact.get("title").and_then(Value::as_str).unwrap_or("");
+            let value = fact.get("value").and_then(Value::as_str).unwrap_or("");
+            json!({
+                "name": fit(TARGET, name, MAX_FIELD_NAME, &path, issues),
+                "value": fit(TARGET, value, MAX_FIELD_VALUE, &path, issues),
+                "inline": true,
+            })
+        })
+        .collect()
+}
+
+fn select_menu(
+    select: &Node<'_>,
+    action: &Node<'_>,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<Value> {
+    let input = select.map.get("id").and_then(Value::as_str).unwrap_or("");
+    let custom_id = custom_id(action, Some((input, select)), issues)?;
+    let selected: Vec<&str> = select
+        .map
+        .get("value")
+        .and_then(Value::as_str)
+        .map(|value| value.split(',').map(str::trim).collect())
+        .unwrap_or_default();
+    let choices = choices(select);
+    if choices.len() > MAX_OPTIONS {
+        issues.push(unsupported(
+            TARGET,
+            &format!("{}/choices/{MAX_OPTIONS}", select.path),
+            "More than 25 choices",
+        ));
+    }
+    let options: Vec<Value> = choices
+        .iter()
+        .take(MAX_OPTIONS)
+        .enumerate()
+        .map(|(idx, (title, value))| {
+            let path = format!("{}/choices/{idx}", select.path);
+            let mut option = json!({
+                "label": fit(TARGET, title, MAX_OPTION_LABEL, &path, issues),
+                "value": value,
+            });
+            if selected.contains(value) {
+                option["default"] = json!(true);
+            }
+            option
+        })
+        .collect();
+    let multi = select.map.get("isMultiSelect").and_then(Value::as_bool) == Some(true);
+    let mut menu = json!({
+        "type": STRING_SELECT,
+        "custom_id": custom_id,
+        "min_values": 1,
+        "max_values": if multi { options.len().max(1) } else { 1 },
+        "options": options,
+    });
+    if let Some(placeholder) = select
+        .map
+        .get("placeholder")
+        .or_else(|| select.map.get("label"))
+        .and_then(Value::as_str)
+    {
+        menu["placeholder"] = json!(fit(
+            TARGET,
+            placeholder,
+            MAX_PLACEHOLDER,
+            &select.path,
+            issues
+        ));
+    }
+    Some(menu)
+}
+
+/// Reply id of `action` (with an empty value for a select menu's input), or `None` with a
+/// warning when the action has no id or the reply id exceeds Discord's 100-character
+/// `custom_id` limit.
+fn custom_id(
+    action: &Node<'_>,
+    select: Option<(&str, &Node<'_>)>,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<String> {
+    let path = select.map_or(action.path.as_str(), |(_, node)| node.path.as_str());
+    let Some(id) = reply_id(action, select.map(|(input, _)| (input, ""))) else {
+        issues.push(unsupported(TARGET, path, "An action without an id"));
+        return None;
+    };
+    if id.len() <= MAX_CUSTOM_ID {
+        return Some(id);
+    }
+    issues.push(unsupported(
+        TARGET,
+        path,
+        "An id longer than a 100-character custom_id",
+    ));
+    None
+}
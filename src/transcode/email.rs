@@ -0,0 +1,587 @@
+use serde_json::{Map, Value, json};
+
+use super::{
+    Node, TranscodeContext, choices, enum_value, is_heading, reply_id, text_lines, unsupported,
+};
+use crate::markdown::{escape_html, plain_text, to_html};
+use crate::model::{IssueSeverity, OutputTarget, ValidationIssue};
+use crate::urls::url_encode;
+
+const TARGET: OutputTarget = OutputTarget::Email;
+const WIDTH: u32 = 600;
+const TABLE: &str =
+    r#"<table role="presentation" width="100%" cellpadding="0" cellspacing="0" border="0""#;
+
+/// Email colors and fonts, from the theme's `email` section, then literal `#` colors in
+/// the theme's `colors`, then built-in light values.
+struct Palette {
+    text: String,
+    subtle: String,
+    accent: String,
+    good: String,
+    warning: String,
+    attention: String,
+    background: String,
+    page: String,
+    emphasis: String,
+    button_text: String,
+    font_family: String,
+    logo_url: Option<String>,
+}
+
+impl Palette {
+    fn from_theme(theme: &Value) -> Self {
+        let email = theme.get("email");
+        let setting = |key: &str| {
+            email
+                .and_then(|e| e.get(key))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        let color = |name: &str, default: &str| {
+            email
+                .and_then(|e| e.get("colors")?.get(name)?.as_str())
+                .or_else(|| {
+                    theme
+                        .get("colors")?
+                        .get(name)?
+                        .as_str()
+                        .filter(|c| c.starts_with('#'))
+                })
+                .unwrap_or(default)
+                .to_string()
+        };
+        Palette {
+            text: color("text", "#323130"),
+            subtle: color("subtle", "#767676"),
+            accent: color("accent", "#0063b1"),
+            good: color("good", "#107c10"),
+            warning: color("warning", "#a55200"),
+            attention: color("attention", "#c50f1f"),
+            background: setting("background").unwrap_or_else(|| "#ffffff".into()),
+            page: setting("pageBackground").unwrap_or_else(|| "#f4f4f4".into()),
+            emphasis: setting("emphasis").unwrap_or_else(|| "#f2f2f2".into()),
+            button_text: setting("buttonText").unwrap_or_else(|| "#ffffff".into()),
+            font_family: setting("fontFamily")
+                .unwrap_or_else(|| "'Segoe UI', Helvetica, Arial, sans-serif".into()),
+            logo_url: setting("logoUrl"),
+        }
+    }
+
+    fn named(&self, color: Option<&str>) -> &str {
+        match color.map(str::to_ascii_lowercase).as_deref() {
+            Some("accent") => &self.accent,
+            Some("good") => &self.good,
+            Some("warning") => &self.warning,
+            Some("attention") => &self.attention,
+            _ => &self.text,
+        }
+    }
+}
+
+struct Renderer<'a> {
+    palette: Palette,
+    context: &'a TranscodeContext,
+    /// Action ChoiceSet links submit through: the card's first Submit or Execute action.
+    choice_action: Option<Node<'a>>,
+    text: Vec<String>,
+    issues: Vec<ValidationIssue>,
+    missing_action_url: bool,
+}
+
+/// Renders the card as an email-safe HTML document (nested presentation tables, inline
+/// styles, 600px wide) with a plain-text alternative. Themed colors, font, and logo come
+/// from the resolved theme. Submit and Execute actions become buttons linking to
+/// `email.action_url` with their reply id, a single-select ChoiceSet a link per choice
+/// submitted through the card's first posting action, and OpenUrl actions links (through
+/// `email.tracking_url` when set). Inputs and other interactive features are dropped with
+/// warnings.
+pub fn transcode(card: &Value, context: &TranscodeContext) -> (Value, Vec<ValidationIssue>) {
+    let mut renderer = Renderer {
+        palette: Palette::from_theme(&context.theme),
+        context,
+        choice_action: first_posting_action(card),
+        text: Vec::new(),
+        issues: Vec::new(),
+        missing_action_url: false,
+    };
+    let body = card.get("body").and_then(Value::as_array);
+    let mut rows = renderer.items(body.map(Vec::as_slice).unwrap_or_default(), "/body");
+    if let Some(actions) = card.get("actions").and_then(Value::as_array) {
+        rows.push_str(&row(&renderer.actions(actions, "")));
+    }
+    let subject = context.email.subject.clone().or_else(|| heading(card));
+    let palette = &renderer.palette;
+    let logo = palette
+        .logo_url
+        .as_ref()
+        .map(|url| {
+            format!(
+                r#"<tr><td style="padding:24px 24px 0"><img src="{}" alt="" height="32" style="display:block;border:0;height:32px"></td></tr>"#,
+                escape_html(url)
+            )
+        })
+        .unwrap_or_default();
+    let html = format!(
+        concat!(
+            r#"<!DOCTYPE html><html><head><meta charset="utf-8">"#,
+            r#"<meta name="viewport" content="width=device-width, initial-scale=1">"#,
+            r#"<title>{title}</title></head><body style="margin:0;padding:0;background:{page}">"#,
+            r#"{table} style="background:{page}"><tr><td align="center" style="padding:24px 12px">"#,
+            r#"<table role="presentation" width="{width}" cellpadding="0" cellspacing="0" border="0" "#,
+            r#"style="width:100%;max-width:{width}px;background:{background};font-family:{font};color:{text};font-size:14px;line-height:1.4">"#,
+            r#"{logo}<tr><td style="padding:24px">{table}>{rows}</table></td></tr>"#,
+            r#"</table></td></tr></table></body></html>"#
+        ),
+        title = escape_html(subject.as_deref().unwrap_or("")),
+        page = palette.page,
+        table = TABLE,
+        width = WIDTH,
+        background = palette.background,
+        font = escape_html(&palette.font_family),
+        text = palette.text,
+        logo = logo,
+        rows = rows,
+    );
+    if renderer.missing_action_url {
+        renderer.issues.push(ValidationIssue {
+            code: "email-action-url-missing".into(),
+            message: "Submit and Execute actions need email.action_url to become links".into(),
+            path: "/actions".into(),
+            severity: Some(IssueSeverity::Warning),
+        });
+    }
+    let mut content = json!({ "html": html, "text": renderer.text.join("\n\n") });
+    if let Some(subject) = subject {
+        content["subject"] = json!(subject);
+    }
+    (content, renderer.issues)
+}
+
+fn heading(card: &Value) -> Option<String> {
+    let collected = super::collect(card);
+    let first = collected.elements.first()?;
+    is_heading(first).then(|| text_lines(first).unwrap_or_default().join(" "))
+}
+
+fn first_posting_action(card: &Value) -> Option<Node<'_>> {
+    super::collect(card)
+        .actions
+        .into_iter()
+        .find(|action| super::posts(action))
+}
+
+fn row(html: &str) -> String {
+    if html.is_empty() {
+        return String::new();
+    }
+    format!(r#"<tr><td style="padding:6px 0">{html}</td></tr>"#)
+}
+
+impl Renderer<'_> {
+    /// Rows for the visible elements of `items`.
+    fn items(&mut self, items: &[Value], path: &str) -> String {
+        let mut rows = String::new();
+        for (idx, item) in items.iter().enumerate() {
+            let Value::Object(map) = item else {
+                continue;
+            };
+            if map.get("isVisible").and_then(Value::as_bool) == Some(false) {
+                continue;
+            }
+            let html = self.element(map, &format!("{path}/{idx}"));
+            rows.push_str(&row(&html));
+        }
+        rows
+    }
+
+    fn element(&mut self, map: &Map<String, Value>, path: &str) -> String {
+        let kind = map.get("type").and_then(Value::as_str).unwrap_or("");
+        match kind {
+            "TextBlock" => self.text_block(map),
+            "RichTextBlock" => self.rich_text(map),
+            "FactSet" => self.fact_set(map),
+            "Image" => image(map, None),
+            "ImageSet" => map
+                .get("images")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_object)
+                .map(|img| image(img, Some("display:inline-block;margin:0 8px 8px 0")))
+                .collect(),
+            "Container" => {
+                let items = self.items(children(map, "items"), &format!("{path}/items"));
+                let style = self.container_style(map);
+                format!(r#"{TABLE}{style}>{items}</table>"#)
+            }
+            "ColumnSet" => self.column_set(map, path),
+            "Table" => self.table(map, path),
+            "ActionSet" => {
+                let actions = children(map, "actions");
+                self.actions(actions, path)
+            }
+            "Input.ChoiceSet"
+                if map.get("isMultiSelect").and_then(Value::as_bool) != Some(true) =>
+            {
+                self.choice_links(map, path)
+            }
+            _ => {
+                self.issues.push(unsupported(TARGET, path, kind));
+                String::new()
+            }
+        }
+    }
+
+    fn text_block(&mut self, map: &Map<String, Value>) -> String {
+        let text = map.get("text").and_then(Value::as_str).unwrap_or("");
+        self.text.push(plain_text(text));
+        let heading = enum_value(map, "style").as_deref() == Some("heading");
+        let size = match enum_value(map, "size").as_deref() {
+            Some("small") => 12,
+            Some("medium") => 17,
+            Some("large") => 21,
+            Some("extralarge") => 26,
+            _ if heading => 21,
+            _ => 14,
+        };
+        let weight = match enum_value(map, "weight").as_deref() {
+            Some("bolder") => "bold",
+            Some("lighter") => "300",
+            _ if heading => "bold",
+            _ => "normal",
+        };
+        let color = if map.get("isSubtle").and_then(Value::as_bool) == Some(true) {
+            &self.palette.subtle
+        } else {
+            self.palette.named(map.get("color").and_then(Value::as_str))
+        };
+        format!(
+            r#"<div style="font-size:{size}px;font-weight:{weight};color:{color};text-align:{align}">{html}</div>"#,
+            align = alignment(map),
+            html = to_html(text)
+        )
+    }
+
+    fn rich_text(&mut self, map: &Map<String, Value>) -> String {
+        let mut plain = String::new();
+        let mut html = String::new();
+        for inline in children(map, "inlines") {
+            let (text, run) = match inline {
+                Value::String(text) => (text.as_str(), None),
+                Value::Object(run) => (
+                    run.get("text").and_then(Value::as_str).unwrap_or(""),
+                    Some(run),
+                ),
+                _ => continue,
+            };
+            plain.push_str(text);
+            let mut piece = escape_html(text);
+            if let Some(run) = run {
+                if run.get("italic").and_then(Value::as_bool) == Some(true) {
+                    piece = format!("<em>{piece}</em>");
+                }
+                if enum_value(run, "weight").as_deref() == Some("bolder") {
+                    piece = format!("<strong>{piece}</strong>");
+                }
+                if let Some(color) = run.get("color").and_then(Value::as_str) {
+                    piece = format!(
+                        r#"<span style="color:{}">{piece}</span>"#,
+                        self.palette.named(Some(color))
+                    );
+                }
+            }
+            html.push_str(&piece);
+        }
+        self.text.push(plain);
+        format!(r#"<div style="text-align:{}">{html}</div>"#, alignment(map))
+    }
+
+    fn fact_set(&mut self, map: &Map<String, Value>) -> String {
+        let mut rows = String::new();
+        for fact in children(map, "facts") {
+            let title = fact.get("title").and_then(Value::as_str).unwrap_or("");
+            let value = fact.get("value").and_then(Value::as_str).unwrap_or("");
+            self.text.push(format!("{title}: {}", plain_text(value)));
+            rows.push_str(&format!(
+                r#"<tr><td style="padding:2px 16px 2px 0;font-weight:bold;vertical-align:top;white-space:nowrap">{}</td><td style="padding:2px 0">{}</td></tr>"#,
+                escape_html(title),
+                to_html(value)
+            ));
+        }
+        format!("{TABLE}>{rows}</table>")
+    }
+
+    fn column_set(&mut self, map: &Map<String, Value>, path: &str) -> String {
+        let columns: Vec<&Map<String, Value>> = children(map, "columns")
+            .iter()
+            .filter_map(Value::as_object)
+            .filter(|column| column.get("isVisible").and_then(Value::as_bool) != Some(false))
+            .collect();
+        let weight = |column: &Map<String, Value>| match column.get("width") {
+            Some(Value::Number(n)) => n.as_f64(),
+            Some(Value::String(w)) if w.eq_ignore_ascii_case("stretch") => Some(1.0),
+            Some(Value::String(w)) => w.parse::<f64>().ok(),
+            None => Some(1.0),
+            _ => None,
+        };
+        let total: f64 = columns.iter().filter_map(|c| weight(c)).sum();
+        let mut cells = String::new();
+        for (idx, column) in columns.iter().enumerate() {
+            let width = match column.get("width").and_then(Value::as_str) {
+                Some(px) if px.ends_with("px") => px
+                    .trim_end_matches("px")
+                    .parse::<u32>()
+                    .map(|px| format!(r#" width="{px}""#))
+                    .unwrap_or_default(),
+                _ => weight(column)
+                    .filter(|_| total > 0.0)
+                    .map(|w| format!(r#" width="{}%""#, (w / total * 100.0).round()))
+                    .unwrap_or_default(),
+            };
+            let items = self.items(
+                children(column, "items"),
+                &format!("{path}/columns/{idx}/items"),
+            );
+            cells.push_str(&format!(
+                r#"<td{width} valign="top" style="padding-right:12px">{TABLE}>{items}</table></td>"#
+            ));
+        }
+        format!("{TABLE}><tr>{cells}</tr></table>")
+    }
+
+    fn table(&mut self, map: &Map<String, Value>, path: &str) -> String {
+        let header = map.get("firstRowAsHeader").and_then(Value::as_bool) != Some(false);
+        let border = format!("1px solid {}", self.palette.emphasis);
+        let mut rows = String::new();
+        for (row_idx, row) in children(map, "rows").iter().enumerate() {
+            let mut cells = String::new();
+            for (cell_idx, cell) in children_of(row, "cells").iter().enumerate() {
+                let items = self.items(
+                    children_of(cell, "items"),
+                    &format!("{path}/rows/{row_idx}/cells/{cell_idx}/items"),
+                );
+                let weight = if header && row_idx == 0 {
+                    "bold"
+                } else {
+                    "normal"
+                };
+                cells.push_str(&format!(
+                    r#"<td valign="top" style="padding:4px 8px;border:{border};font-weight:{weight}">{TABLE}>{items}</table></td>"#
+                ));
+            }
+            rows.push_str(&format!("<tr>{cells}</tr>"));
+        }
+        format!(r#"{TABLE} style="border-collapse:collapse">{rows}</table>"#)
+    }
+
+    fn container_style(&self, map: &Map<String, Value>) -> String {
+        let background = match enum_value(map, "style").as_deref() {
+            Some("emphasis") => self.palette.emphasis.clone(),
+            Some("accent") => tint(&self.palette.accent),
+            Some("good") => tint(&self.palette.good),
+            Some("warning") => tint(&self.palette.warning),
+            Some("attention") => tint(&self.palette.attention),
+            _ => return String::new(),
+        };
+        format!(r#" style="background:{background};padding:8px""#)
+    }
+
+    /// A link per choice, each submitting the choice through the card's first posting action.
+    fn choice_links(&mut self, map: &Map<String, Value>, path: &str) -> String {
+        let Some(action) = self.choice_action.as_ref() else {
+            self.issues.push(unsupported(
+                TARGET,
+                path,
+                "A ChoiceSet without a Submit action",
+            ));
+            return String::new();
+        };
+        let input = map.get("id").and_then(Value::as_str).unwrap_or("");
+        let node = Node {
+            path: path.to_string(),
+            kind: "Input.ChoiceSet",
+            map,
+        };
+        let replies: Option<Vec<(&str, String)>> = choices(&node)
+            .into_iter()
+            .map(|(title, value)| Some((title, reply_id(action, Some((input, value)))?)))
+            .collect();
+        let Some(replies) = replies else {
+            self.issues
+                .push(unsupported(TARGET, &action.path, "An action without an id"));
+            return String::new();
+        };
+        let links: Vec<(String, Option<String>)> = replies
+            .into_iter()
+            .map(|(title, reply)| (title.to_string(), self.reply_link(&reply)))
+            .collect();
+        let mut html = String::new();
+        if let Some(label) = map.get("label").and_then(Value::as_str) {
+            self.text.push(label.to_string());
+            html.push_str(&format!(
+                r#"<div style="font-weight:bold;padding-bottom:4px">{}</div>"#,
+                escape_html(label)
+            ));
+        }
+        for (title, href) in links {
+            let Some(href) = href else {
+                continue;
+            };
+            self.text.push(format!("{title}: {href}"));
+            html.push_str(&self.button(&title, &href, false));
+        }
+        html
+    }
+
+    fn actions(&mut self, actions: &[Value], path: &str) -> String {
+        let mut buttons = String::new();
+        for (idx, action) in actions.iter().enumerate() {
+            let Value::Object(map) = action else {
+                continue;
+            };
+            if map.get("isVisible").and_then(Value::as_bool) == Some(false) {
+                continue;
+            }
+            let node = Node {
+                path: format!("{path}/actions/{idx}"),
+                kind: map.get("type").and_then(Value::as_str).unwrap_or(""),
+                map,
+            };
+            let title = super::title(&node);
+            let href = match node.kind {
+                "Action.Submit" | "Action.Execute" => match reply_id(&node, None) {
+                    Some(reply) => self.reply_link(&reply),
+                    None => {
+                        self.issues.push(unsupported(
+                            TARGET,
+                            &node.path,
+                            "An action without an id",
+                        ));
+                        None
+                    }
+                },
+                "Action.OpenUrl" => map
+                    .get("url")
+                    .and_then(Value::as_str)
+                    .map(|url| self.tracked_link(url, &node)),
+                kind => {
+                    self.issues.push(unsupported(TARGET, &node.path, kind));
+                    None
+                }
+            };
+            if let Some(href) = href {
+                self.text.push(format!("{title}: {href}"));
+                buttons.push_str(&self.button(&title, &href, true));
+            }
+        }
+        buttons
+    }
+
+    fn button(&self, title: &str, href: &str, primary: bool) -> String {
+        let (background, color) = if primary {
+            (
+                self.palette.accent.as_str(),
+                self.palette.button_text.as_str(),
+            )
+        } else {
+            (self.palette.emphasis.as_str(), self.palette.accent.as_str())
+        };
+        format!(
+            r#"<a href="{}" style="display:inline-block;margin:0 8px 8px 0;padding:10px 16px;border-radius:4px;background:{background};color:{color};font-weight:bold;text-decoration:none">{}</a>"#,
+            escape_html(href),
+            escape_html(title)
+        )
+    }
+
+    fn reply_link(&mut self, reply: &str) -> Option<String> {
+        let Some(base) = self.context.email.action_url.as_deref() else {
+            self.missing_action_url = true;
+            return None;
+        };
+        let mut link = format!("{base}{}reply={}", separator(base), url_encode(reply));
+        if let Some(card) = self.context.card_instance_id.as_deref() {
+            link.push_str(&format!("&card={}", url_encode(card)));
+        }
+        Some(link)
+    }
+
+    fn tracked_link(&self, url: &str, action: &Node<'_>) -> String {
+        let Some(base) = self.context.email.tracking_url.as_deref() else {
+            return url.to_string();
+        };
+        let mut link = format!("{base}{}url={}", separator(base), url_encode(url));
+        if let Some(id) = action.map.get("id").and_then(Value::as_str) {
+            link.push_str(&format!("&action={}", url_encode(id)));
+        }
+        if let Some(card) = self.context.card_instance_id.as_deref() {
+            link.push_str(&format!("&card={}", url_encode(card)));
+        }
+        link
+    }
+}
+
+fn image(map: &Map<String, Value>, style: Option<&str>) -> String {
+    let Some(url) = map.get("url").and_then(Value::as_str) else {
+        return String::new();
+    };
+    let width = match map.get("width").and_then(Value::as_str) {
+        Some(px) if px.ends_with("px") => px.trim_end_matches("px").parse().ok(),
+        _ => match enum_value(map, "size").as_deref() {
+            Some("small") => Some(80),
+            Some("medium") => Some(160),
+            Some("large") => Some(240),
+            _ => None,
+        },
+    };
+    let width: u32 = width.unwrap_or(WIDTH - 48);
+    let alt = map.get("altText").and_then(Value::as_str).unwrap_or("");
+    format!(
+        r#"<div style="text-align:{}"><img src="{}" alt="{}" width="{width}" style="{};max-width:100%;height:auto;border:0"></div>"#,
+        alignment(map),
+        escape_html(url),
+        escape_html(alt),
+        style.unwrap_or("display:inline-block")
+    )
+}
+
+fn alignment(map: &Map<String, Value>) -> &str {
+    match enum_value(map, "horizontalAlignment").as_deref() {
+        Some("center") => "center",
+        Some("right") => "right",
+        _ => "left",
+    }
+}
+
+/// Light background for a container style: the color mixed 15% over white.
+fn tint(color: &str) -> String {
+    let hex = color.trim_start_matches('#');
+    let channel = |idx: usize| {
+        hex.get(idx..idx + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .map(|c| (255.0 - (255.0 - f64::from(c)) * 0.15).round() as u8)
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => format!("#{r:02x}{g:02x}{b:02x}"),
+        _ => color.to_string(),
+    }
+}
+
+fn separator(base: &str) -> char {
+    if base.contains('?') { '&' } else { '?' }
+}
+
+fn children<'a>(map: &'a Map<String, Value>, key: &str) -> &'a [Value] {
+    map.get(key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn children_of<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
@@ -0,0 +1,298 @@
+use serde_json::{Value, json};
+
+use super::{
+    Node, collect, fit, is_heading, posts, reply_id, text_lines, title, truncate, truncated,
+    unsupported,
+};
+use crate::model::{OutputTarget, ValidationIssue};
+
+// WhatsApp Cloud API limits, in characters.
+const MAX_BUTTONS: usize = 3;
+const MAX_ROWS: usize = 10;
+const MAX_HEADER: usize = 60;
+const MAX_BODY: usize = 1024;
+const MAX_TEXT_BODY: usize = 4096;
+const MAX_BUTTON_TITLE: usize = 20;
+const MAX_ROW_TITLE: usize = 24;
+const MAX_ROW_DESCRIPTION: usize = 72;
+const TARGET: OutputTarget = OutputTarget::WhatsApp;
+
+/// Maps the card onto a WhatsApp Cloud API message (without the recipient `to`):
+///
+/// - TextBlocks, RichTextBlocks, and FactSets become the body text; a leading heading
+///   becomes the header, and the first Image the header of a button message;
+/// - a single-select ChoiceSet becomes a list message whose rows reply with the choice,
+///   submitted through the card's first Submit or Execute action;
+/// - otherwise up to three Submit or Execute actions become reply buttons, and more become
+///   list rows;
+/// - a lone OpenUrl becomes a call-to-action URL button, other OpenUrl actions links in
+///   the text;
+/// - a card without any of these is sent as a plain text message.
+///
+/// Button and row ids are [`super::reply_interaction`] reply ids.
+pub fn transcode(card: &Value) -> (Value, Vec<ValidationIssue>) {
+    let collected = collect(card);
+    let mut issues = Vec::new();
+    let mut header: Option<Value> = None;
+    let mut image: Option<Value> = None;
+    let mut lines: Vec<String> = Vec::new();
+    let mut choice_set: Option<&Node<'_>> = None;
+    for node in &collected.elements {
+        match node.kind {
+            "TextBlock" if header.is_none() && lines.is_empty() && is_heading(node) => {
+                let text = text_lines(node).unwrap_or_default().join(" ");
+                let (text, cut) = truncate(&text, MAX_HEADER);
+                if cut {
+                    issues.push(truncated(TARGET, &node.path, MAX_HEADER));
+                }
+                header = Some(json!({ "type": "text", "text": text }));
+            }
+            "Image" if image.is_none() => {
+                image = node
+                    .map
+                    .get("url")
+                    .and_then(Value::as_str)
+                    .map(|url| json!({ "type": "image", "image": { "link": url } }));
+            }
+            "Input.ChoiceSet"
+                if choice_set.is_none()
+                    && node.map.get("isMultiSelect").and_then(Value::as_bool) != Some(true) =>
+            {
+                choice_set = Some(node);
+            }
+            _ => match text_lines(node) {
+                Some(text) => lines.extend(text),
+                None => issues.push(unsupported(TARGET, &node.path, node.kind)),
+            },
+        }
+    }
+
+    // A posting action without an id could not be routed back, so it is dropped here and
+    // every reply id built below exists.
+    let posting: Vec<&Node<'_>> = collected
+        .actions
+        .iter()
+        .filter(|a| posts(a))
+        .filter(|a| {
+            let routable = reply_id(a, None).is_some();
+            if !routable {
+                issues.push(unsupported(TARGET, &a.path, "An action without an id"));
+            }
+            routable
+        })
+        .collect();
+    let links: Vec<&Node<'_>> = collected
+        .actions
+        .iter()
+        .filter(|a| a.kind == "Action.OpenUrl")
+        .collect();
+    for action in &collected.actions {
+        if !posts(action) && action.kind != "Action.OpenUrl" {
+            issues.push(unsupported(TARGET, &action.path, action.kind));
+        }
+    }
+    let cta = posting.is_empty() && choice_set.is_none() && links.len() == 1;
+    if !cta {
+        for link in &links {
+            if let Some(url) = link.map.get("url").and_then(Value::as_str) {
+                lines.push(format!("{}: {url}", title(link)));
+            }
+        }
+    }
+
+    let interactive = if let Some(choice_set) = choice_set {
+        match posting.first() {
+            Some(action) => {
+                for extra in &posting[1..] {
+                    issues.push(unsupported(TARGET, &extra.path, "A second posting action"));
+                }
+                Some(choice_list(choice_set, action, &mut issues))
+            }
+            None => {
+                issues.push(unsupported(
+                    TARGET,
+                    &choice_set.path,
+                    "A ChoiceSet without a Submit action",
+                ));
+                None
+            }
+        }
+    } else if posting.len() > MAX_BUTTONS {
+        Some(action_list(&posting, &mut issues))
+    } else if !posting.is_empty() {
+        let buttons: Vec<Value> = posting
+            .iter()
+            .map(|action| {
+                json!({
+                    "type": "reply",
+                    "reply": {
+                        "id": reply_id(action, None).unwrap_or_default(),
+                        "title": fitted(&title(action), MAX_BUTTON_TITLE, &action.path, &mut issues),
+                    }
+                })
+            })
+            .collect();
+        Some(json!({ "type": "button", "action": { "buttons": buttons } }))
+    } else if cta {
+        let link = links[0];
+        Some(json!({
+            "type": "cta_url",
+            "action": {
+                "name": "cta_url",
+                "parameters": {
+                    "display_text": fitted(&title(link), MAX_BUTTON_TITLE, &link.path, &mut issues),
+                    "url": link.map.get("url").cloned().unwrap_or(Value::Null),
+                }
+            }
+        }))
+    } else {
+        None
+    };
+
+    let Some(mut interactive) = interactive else {
+        if let Some(heading) = header.as_ref().and_then(|h| h.get("text")?.as_str()) {
+            lines.insert(0, heading.to_string());
+        }
+        if image.is_some() {
+            issues.push(unsupported(TARGET, "/body", "An Image in a text message"));
+        }
+        let body = fitted(&lines.join("\n"), MAX_TEXT_BODY, "/body", &mut issues);
+        return (
+            json!({
+                "messaging_product": "whatsapp",
+                "recipient_type": "individual",
+                "type": "text",
+                "text": { "body": body }
+            }),
+            issues,
+        );
+    };
+
+    let is_button = interactive["type"] == "button";
+    let header = match (image, header) {
+        (Some(image), header) if is_button => {
+            if let Some(heading) = header.as_ref().and_then(|h| h.get("text")?.as_str()) {
+                lines.insert(0, heading.to_string());
+            }
+            Some(image)
+        }
+        (Some(_), header) => {
+            issues.push(unsupported(TARGET, "/body", "An Image in a list message"));
+            header
+        }
+        (None, header) => header,
+    };
+    let header = match header {
+        Some(heading) if lines.is_empty() && heading["type"] == "text" => {
+            lines.extend(heading["text"].as_str().map(str::to_string));
+            None
+        }
+        header => header,
+    };
+    if lines.is_empty() {
+        issues.push(unsupported(
+            TARGET,
+            "/body",
+            "An interactive message without text",
+        ));
+    }
+    let body = fitted(&lines.join("\n"), MAX_BODY, "/body", &mut issues);
+    interactive["body"] = json!({ "text": body });
+    if let Some(header) = header {
+        interactive["header"] = header;
+    }
+    (
+        json!({
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "type": "interactive",
+            "interactive": interactive
+        }),
+        issues,
+    )
+}
+
+/// List message with one row per choice, each replying through `action`.
+fn choice_list(
+    choice_set: &Node<'_>,
+    action: &Node<'_>,
+    issues: &mut Vec<ValidationIssue>,
+) -> Value {
+    let input = choice_set
+        .map
+        .get("id")
+        .and_then(Value::as_str)
+        .unwrap_or("");
+    let choices: Vec<&Value> = choice_set
+        .map
+        .get("choices")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .collect();
+    if choices.len() > MAX_ROWS {
+        issues.push(unsupported(
+            TARGET,
+            &format!("{}/choices/{MAX_ROWS}", choice_set.path),
+            "More than 10 choices",
+        ));
+    }
+    let rows: Vec<Value> = choices
+        .iter()
+        .take(MAX_ROWS)
+        .enumerate()
+        .map(|(idx, choice)| {
+            let value = choice.get("value").and_then(Value::as_str).unwrap_or("");
+            let title = choice.get("title").and_then(Value::as_str).unwrap_or(value);
+            let path = format!("{}/choices/{idx}", choice_set.path);
+            json!({
+                "id": reply_id(action, Some((input, value))).unwrap_or_default(),
+                "title": fitted(title, MAX_ROW_TITLE, &path, issues),
+            })
+        })
+        .collect();
+    let label = choice_set
+        .map
+        .get("label")
+        .or_else(|| choice_set.map.get("placeholder"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| title(action));
+    json!({
+        "type": "list",
+        "action": {
+            "button": fitted(&label, MAX_BUTTON_TITLE, &choice_set.path, issues),
+            "sections": [{ "title": fitted(&label, MAX_ROW_TITLE, &choice_set.path, issues), "rows": rows }]
+        }
+    })
+}
+
+/// List message with one row per action, for cards with more actions than reply buttons.
+fn action_list(posting: &[&Node<'_>], issues: &mut Vec<ValidationIssue>) -> Value {
+    for extra in posting.iter().skip(MAX_ROWS) {
+        issues.push(unsupported(TARGET, &extra.path, "More than 10 actions"));
+    }
+    let rows: Vec<Value> = posting
+        .iter()
+        .take(MAX_ROWS)
+        .map(|action| {
+            let mut row = json!({
+                "id": reply_id(action, None).unwrap_or_default(),
+                "title": fitted(&title(action), MAX_ROW_TITLE, &action.path, issues),
+            });
+            if let Some(tooltip) = action.map.get("tooltip").and_then(Value::as_str) {
+                row["description"] =
+                    json!(fitted(tooltip, MAX_ROW_DESCRIPTION, &action.path, issues));
+            }
+            row
+        })
+        .collect();
+    json!({
+        "type": "list",
+        "action": { "button": "Options", "sections": [{ "title": "Options", "rows": rows }] }
+    })
+}
+
+fn fitted(text: &str, max: usize, path: &str, issues: &mut Vec<ValidationIssue>) -> String {
+    fit(TARGET, text, max, path, issues)
+}
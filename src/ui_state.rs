@@ -0,0 +1,64 @@
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::actions::ALL_ACTIONS;
+use crate::model::StateUpdateOp;
+
+/// `ui` state maps keyed by the id of the element or action they track.
+const ELEMENT_KEYED: [&str; 4] = ["visibility", "pages", "tables", "disabled_actions"];
+
+/// Ids of the elements, actions, and ShowCard subcards in `cards`.
+pub fn element_ids<'a>(cards: impl IntoIterator<Item = &'a Value>) -> BTreeSet<String> {
+    fn collect(value: &Value, ids: &mut BTreeSet<String>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(id)) = map.get("id") {
+                    ids.insert(id.clone());
+                }
+                map.values().for_each(|child| collect(child, ids));
+            }
+            Value::Array(items) => items.iter().for_each(|item| collect(item, ids)),
+            _ => {}
+        }
+    }
+    let mut ids = BTreeSet::new();
+    for card in cards {
+        collect(card, &mut ids);
+    }
+    ids
+}
+
+/// Deletes for UI state referencing ids not in `ids`: `ui.visibility`, `ui.pages`,
+/// `ui.tables`, and `ui.disabled_actions` entries (except the all-actions key), and
+/// `ui.active_show_card` selections naming a ShowCard that is gone. Entries whose id
+/// contains a `.` are left alone since no state path can address them.
+pub fn orphaned_ui_state(state: &Value, ids: &BTreeSet<String>) -> Vec<StateUpdateOp> {
+    let Some(ui) = state.get("ui") else {
+        return Vec::new();
+    };
+    let mut deletes = Vec::new();
+    for map in ELEMENT_KEYED {
+        let Some(entries) = ui.get(map).and_then(Value::as_object) else {
+            continue;
+        };
+        for id in entries.keys() {
+            if !ids.contains(id) && id != ALL_ACTIONS && !id.contains('.') {
+                deletes.push(StateUpdateOp::Delete {
+                    path: format!("ui.{map}.{id}"),
+                });
+            }
+        }
+    }
+    if let Some(selections) = ui.get("active_show_card").and_then(Value::as_object) {
+        for (instance_id, active) in selections {
+            let present = active.as_str().is_some_and(|active| ids.contains(active));
+            if !present && !instance_id.contains('.') {
+                deletes.push(StateUpdateOp::Delete {
+                    path: format!("ui.active_show_card.{instance_id}"),
+                });
+            }
+        }
+    }
+    deletes
+}
@@ -0,0 +1,361 @@
+use serde_json::{Map, Value, json};
+
+use crate::actions::{self, ActionDeclaration};
+use crate::error::ComponentError;
+use crate::model::{
+    CardInteraction, HostPlatform, SecurityPolicy, TargetProfile, UploadRequest, UploadedFile,
+    ValidationIssue,
+};
+
+/// Element type of the file-upload input extension.
+pub const FILE_INPUT_TYPE: &str = "Input.File";
+/// Upload button `data` key carrying the input's [`UploadRequest`].
+pub const UPLOAD_KEY: &str = "__upload";
+
+/// Replaces each `Input.File {id, label?, accept?, maxSize?, multiple?, uploadTitle?}` with an
+/// upload control: a Container holding the label, the files already uploaded for the card
+/// instance (`uploads.<card_instance_id>.<id>` in state), an accepted types/size hint, and an
+/// `Action.Submit` upload button whose data carries the upload request. Hosts that render
+/// `Input.File` natively list it in `target_profile.custom_elements` and keep the element.
+pub fn render_file_inputs(
+    card: &mut Value,
+    profile: Option<&TargetProfile>,
+    state: &Value,
+    card_instance_id: Option<&str>,
+) {
+    if profile.is_some_and(|p| p.custom_elements.iter().any(|e| e == FILE_INPUT_TYPE)) {
+        return;
+    }
+    let uploaded = card_instance_id
+        .and_then(|id| state.get("uploads")?.get(id))
+        .cloned()
+        .unwrap_or(Value::Null);
+    walk(card, &uploaded);
+}
+
+/// The upload a click on a generated upload button asks the host to start. On Teams it
+/// carries a file consent card for the bot to send.
+pub fn upload_request(
+    declared: Option<&ActionDeclaration>,
+    profile: Option<&TargetProfile>,
+    card_instance_id: &str,
+) -> Option<UploadRequest> {
+    let mut request: UploadRequest = declared
+        .and_then(|action| action.data())
+        .and_then(|data| data.get(UPLOAD_KEY))
+        .and_then(|request| serde_json::from_value(request.clone()).ok())?;
+    if profile.and_then(|p| p.host) == Some(HostPlatform::Teams) {
+        let context = json!({ "inputId": request.input_id, "cardInstanceId": card_instance_id });
+        request.consent_card = Some(json!({
+            "contentType": "application/vnd.microsoft.teams.card.file.consent",
+            "name": request.input_id,
+            "content": {
+                "description": hint(&request).unwrap_or_else(|| "Upload a file".into()),
+                "sizeInBytes": request.max_size.unwrap_or_default(),
+                "acceptContext": context,
+                "declineContext": context
+            }
+        }));
+    }
+    Some(request)
+}
+
+/// Validates the files an `UploadComplete` interaction reports for the input named by
+/// `action_id`. `raw_inputs` is one file object, a list, or either keyed by the input id;
+/// `name`/`fileName`, `size`/`fileSize`, `contentType`/`mimeType`, and
+/// `contentRef`/`contentUrl`/`url`/`uniqueId` are recognized. Files that are malformed, larger
+/// than the input's `maxSize` or the policy's `max_upload_bytes`, or of a type outside the
+/// input's `accept` or the policy's `allowed_upload_types` are dropped with an issue. Returns
+/// the accepted file (or list when `multiple`), `None` when nothing was accepted.
+pub fn complete_upload(
+    interaction: &CardInteraction,
+    card: &Value,
+    policy: Option<&SecurityPolicy>,
+) -> Result<(Option<Value>, Vec<ValidationIssue>), ComponentError> {
+    let input_id = interaction.action_id.as_str();
+    let request = find_file_input(card, input_id).ok_or_else(|| {
+        ComponentError::InteractionInvalid(format!("no file input {input_id} on the card"))
+    })?;
+    let raw = match &interaction.raw_inputs {
+        Value::Object(map) if map.contains_key(input_id) => map[input_id].clone(),
+        other => other.clone(),
+    };
+    let raw_files = match raw {
+        Value::Array(items) => items,
+        Value::Null => Vec::new(),
+        other => vec![other],
+    };
+    let path = format!("/inputs/{input_id}");
+    let mut issues = Vec::new();
+    let issue = |code: &str, message: String| ValidationIssue {
+        code: code.into(),
+        message,
+        path: path.clone(),
+        severity: None,
+    };
+    let max_size = match (request.max_size, policy.and_then(|p| p.max_upload_bytes)) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    let mut files = Vec::new();
+    for raw in &raw_files {
+        let Some(file) = parse_file(raw) else {
+            issues.push(issue(
+                "upload-invalid",
+                format!("upload for {input_id} needs a name and a size, got {raw}"),
+            ));
+            continue;
+        };
+        if let Some(max) = max_size
+            && file.size > max
+        {
+            issues.push(issue(
+                "upload-too-large",
+                format!(
+                    "{} is {}, above the {} limit",
+                    file.name,
+                    format_size(file.size),
+                    format_size(max)
+                ),
+            ));
+            continue;
+        }
+        let allowed = [
+            request.accept.as_slice(),
+            policy
+                .map(|p| p.allowed_upload_types.as_slice())
+                .unwrap_or_default(),
+        ];
+        if let Some(list) = allowed
+            .iter()
+            .find(|list| !list.is_empty() && !list.iter().any(|pattern| accepts(pattern, &file)))
+        {
+            issues.push(issue(
+                "upload-type-not-allowed",
+                format!("{} is not one of {}", file.name, list.join(", ")),
+            ));
+            continue;
+        }
+        files.push(file);
+    }
+    if !request.multiple && files.len() > 1 {
+        issues.push(issue(
+            "upload-too-many-files",
+            format!("{input_id} takes one file; the others were dropped"),
+        ));
+        files.truncate(1);
+    }
+    let value = match files.len() {
+        0 => None,
+        _ if request.multiple => Some(serde_json::to_value(&files)?),
+        _ => Some(serde_json::to_value(&files[0])?),
+    };
+    Ok((value, issues))
+}
+
+/// The upload settings of the file input `input_id`, from its upload button or its native
+/// `Input.File` element.
+fn find_file_input(card: &Value, input_id: &str) -> Option<UploadRequest> {
+    let from_button = actions::collect_actions(card)
+        .into_iter()
+        .find_map(|action| {
+            let request: UploadRequest =
+                serde_json::from_value(action.data()?.get(UPLOAD_KEY)?.clone()).ok()?;
+            (request.input_id == input_id).then_some(request)
+        });
+    from_button.or_else(|| find_element(card, input_id).map(request_for))
+}
+
+fn find_element<'a>(value: &'a Value, input_id: &str) -> Option<&'a Map<String, Value>> {
+    match value {
+        Value::Object(map) => {
+            if map.get("type").and_then(Value::as_str) == Some(FILE_INPUT_TYPE)
+                && map.get("id").and_then(Value::as_str) == Some(input_id)
+            {
+                return Some(map);
+            }
+            map.values().find_map(|child| find_element(child, input_id))
+        }
+        Value::Array(items) => items.iter().find_map(|item| find_element(item, input_id)),
+        _ => None,
+    }
+}
+
+fn request_for(map: &Map<String, Value>) -> UploadRequest {
+    let accept = match map.get("accept") {
+        Some(Value::String(list)) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    UploadRequest {
+        input_id: map
+            .get("id")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        accept,
+        max_size: map.get("maxSize").and_then(Value::as_u64),
+        multiple: map
+            .get("multiple")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        consent_card: None,
+    }
+}
+
+fn walk(value: &mut Value, uploaded: &Value) {
+    match value {
+        Value::Object(map) => {
+            if map.get("type").and_then(Value::as_str) == Some(FILE_INPUT_TYPE)
+                && map.get("id").and_then(Value::as_str).is_some()
+            {
+                *value = upload_control(map, uploaded);
+                return;
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    walk(child, uploaded);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| walk(item, uploaded)),
+        _ => {}
+    }
+}
+
+fn upload_control(map: &Map<String, Value>, uploaded: &Value) -> Value {
+    let request = request_for(map);
+    let mut items = Vec::new();
+    if let Some(label) = map.get("label").and_then(Value::as_str) {
+        let required = map.get("isRequired").and_then(Value::as_bool) == Some(true);
+        items.push(json!({
+            "type": "TextBlock",
+            "text": if required { format!("{label} *") } else { label.to_string() },
+            "wrap": true
+        }));
+    }
+    let files: Vec<UploadedFile> = match uploaded.get(&request.input_id) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| serde_json::from_value(item.clone()).ok())
+            .collect(),
+        Some(file) => serde_json::from_value(file.clone()).into_iter().collect(),
+        None => Vec::new(),
+    };
+    if !files.is_empty() {
+        let facts: Vec<Value> = files
+            .iter()
+            .map(|file| json!({ "title": file.name, "value": format_size(file.size) }))
+            .collect();
+        items.push(json!({ "type": "FactSet", "facts": facts }));
+    }
+    if let Some(hint) = hint(&request) {
+        items.push(json!({
+            "type": "TextBlock",
+            "text": hint,
+            "isSubtle": true,
+            "size": "Small",
+            "spacing": "None",
+            "wrap": true
+        }));
+    }
+    let title = match map.get("uploadTitle").and_then(Value::as_str) {
+        Some(title) => title.to_string(),
+        None if files.is_empty() => "Upload file".into(),
+        None => "Replace file".into(),
+    };
+    items.push(json!({
+        "type": "ActionSet",
+        "actions": [{
+            "type": "Action.Submit",
+            "id": format!("{}-upload", request.input_id),
+            "title": title,
+            "data": { UPLOAD_KEY: request }
+        }]
+    }));
+    let mut control = Map::new();
+    control.insert("type".into(), json!("Container"));
+    control.insert("id".into(), json!(request.input_id));
+    for key in ["isVisible", "separator", "spacing", "visibleToRoles"] {
+        if let Some(value) = map.get(key) {
+            control.insert(key.into(), value.clone());
+        }
+    }
+    control.insert("items".into(), Value::Array(items));
+    Value::Object(control)
+}
+
+/// "Accepted: .pdf, image/*. Up to 5 MB." for the request's limits.
+fn hint(request: &UploadRequest) -> Option<String> {
+    let mut parts = Vec::new();
+    if !request.accept.is_empty() {
+        parts.push(format!("Accepted: {}.", request.accept.join(", ")));
+    }
+    if let Some(max) = request.max_size {
+        parts.push(format!("Up to {}.", format_size(max)));
+    }
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+fn parse_file(raw: &Value) -> Option<UploadedFile> {
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| raw.get(*key).and_then(Value::as_str))
+            .map(str::to_string)
+    };
+    let size = ["size", "fileSize"]
+        .iter()
+        .find_map(|key| match raw.get(*key)? {
+            Value::Number(n) => n.as_u64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        })?;
+    Some(UploadedFile {
+        name: text(&["name", "fileName"]).filter(|name| !name.is_empty())?,
+        size,
+        content_type: text(&["contentType", "mimeType"]),
+        content_ref: text(&["contentRef", "contentUrl", "url", "uniqueId"]),
+    })
+}
+
+/// Matches `.ext` against the file name and `type/*` or `type/subtype` against its content
+/// type, case-insensitively.
+fn accepts(pattern: &str, file: &UploadedFile) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    if pattern.starts_with('.') {
+        return file.name.to_ascii_lowercase().ends_with(&pattern);
+    }
+    let Some(content_type) = file.content_type.as_deref().map(str::to_ascii_lowercase) else {
+        return false;
+    };
+    match pattern.strip_suffix("/*") {
+        Some(family) => content_type
+            .split_once('/')
+            .is_some_and(|(kind, _)| kind == family),
+        None => content_type == pattern,
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 || size.fract() == 0.0 {
+        format!("{size:.0} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
@@ -0,0 +1,413 @@
+use serde_json::{Map, Value, json};
+
+use crate::expression::stringify_value;
+use crate::model::{HostPlatform, IssueSeverity, SecurityPolicy, TargetProfile, ValidationIssue};
+
+/// OpenUrl schemes accepted when the security policy does not list its own.
+pub const DEFAULT_URL_SCHEMES: &[&str] = &["https", "http", "mailto", "tel", "msteams"];
+
+/// Action property holding structured deep link parameters, replaced by the built `url`.
+pub const DEEP_LINK_KEY: &str = "deepLink";
+
+const TEAMS_LINK_BASE: &str = "https://teams.microsoft.com/l";
+
+/// Schemes browsers parse with the WHATWG URL standard's "special" rules.
+const SPECIAL_SCHEMES: &[&str] = &["http", "https", "ws", "wss", "ftp", "file"];
+
+/// Percent-encodes everything but RFC 3986 unreserved characters, for query values and
+/// path segments.
+pub fn url_encode(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for byte in raw.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// Decodes `%XX` escapes; malformed escapes are kept as is.
+pub fn url_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%'
+            && let Some(byte) = raw
+                .get(idx + 1..idx + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            out.push(byte);
+            idx += 3;
+        } else {
+            out.push(bytes[idx]);
+            idx += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The lowercased host an absolute URL points to, read the way browsers read it (WHATWG URL
+/// parsing): for http(s) and the other special schemes `\` counts as `/` and any run of
+/// slashes after the scheme opens the authority; userinfo and port are dropped. Other schemes
+/// without an authority yield the domain of their address (`mailto:ada@example.com`). Fails
+/// when the host holds whitespace, control characters, or code points browsers reject in a
+/// host, such as `\` or `%`.
+pub fn url_host(url: &str) -> Result<String, String> {
+    let url = url.trim_matches(|c: char| c <= ' ');
+    let (scheme, rest) = url
+        .split_once(':')
+        .ok_or_else(|| format!("'{url}' is not an absolute URL"))?;
+    let special = SPECIAL_SCHEMES
+        .iter()
+        .any(|special| special.eq_ignore_ascii_case(scheme));
+    let authority = if special {
+        rest.trim_start_matches(['/', '\\'])
+            .split(['/', '\\', '?', '#'])
+            .next()
+    } else if let Some(rest) = rest.strip_prefix("//") {
+        rest.split(['/', '?', '#']).next()
+    } else {
+        rest.split(['?', '#']).next()
+    }
+    .unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if let Some(literal) = host.strip_prefix('[') {
+        return match literal.split_once(']') {
+            Some((address, _))
+                if address
+                    .chars()
+                    .all(|c| c.is_ascii_hexdigit() || matches!(c, ':' | '.')) =>
+            {
+                Ok(format!("[{}]", address.to_ascii_lowercase()))
+            }
+            _ => Err(format!("'{url}' has a malformed IPv6 host")),
+        };
+    }
+    let host = host.split(':').next().unwrap_or_default();
+    if let Some(c) = host.chars().find(|c| {
+        c.is_whitespace()
+            || c.is_control()
+            || matches!(c, '\\' | '<' | '>' | '^' | '|' | '%' | '[' | ']')
+    }) {
+        return Err(format!("'{url}' has the character {c:?} in its host"));
+    }
+    Ok(host.to_ascii_lowercase())
+}
+
+/// Checks every bound `Action.OpenUrl` target: it must be absolute, use a scheme from the
+/// policy's `allowed_url_schemes` (or [`DEFAULT_URL_SCHEMES`]), name a well-formed host for
+/// http(s), and contain no whitespace, malformed `%` escapes, or (in http(s) URLs) `\`.
+pub fn check_open_urls(card: &Value, policy: Option<&SecurityPolicy>) -> Vec<ValidationIssue> {
+    let schemes: Vec<&str> = match policy {
+        Some(policy) if !policy.allowed_url_schemes.is_empty() => policy
+            .allowed_url_schemes
+            .iter()
+            .map(String::as_str)
+            .collect(),
+        _ => DEFAULT_URL_SCHEMES.to_vec(),
+    };
+    let mut issues = Vec::new();
+    visit(card, "", &schemes, &mut issues);
+    issues
+}
+
+fn visit(value: &Value, path: &str, schemes: &[&str], issues: &mut Vec<ValidationIssue>) {
+    match value {
+        Value::Object(map) => {
+            if map.get("type").and_then(Value::as_str) == Some("Action.OpenUrl")
+                && let Some(url) = map.get("url").and_then(Value::as_str)
+                && !url.is_empty()
+                && let Some((code, message)) = url_problem(url, schemes)
+            {
+                issues.push(ValidationIssue {
+                    code: code.into(),
+                    message,
+                    path: format!("{path}/url"),
+                    severity: Some(IssueSeverity::Warning),
+                });
+            }
+            for (key, child) in map {
+                if key != "data" {
+                    visit(child, &format!("{path}/{key}"), schemes, issues);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                visit(item, &format!("{path}/{idx}"), schemes, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn url_problem(url: &str, schemes: &[&str]) -> Option<(&'static str, String)> {
+    let scheme = url.split_once(':').map(|(scheme, _)| scheme).filter(|s| {
+        s.starts_with(|c: char| c.is_ascii_alphabetic())
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    });
+    let Some(scheme) = scheme else {
+        return Some(("invalid-url", format!("'{url}' is not an absolute URL")));
+    };
+    if !schemes
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(scheme))
+    {
+        return Some((
+            "disallowed-url-scheme",
+            format!("'{url}' uses the {scheme}: scheme, which is not allowed"),
+        ));
+    }
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Some((
+            "invalid-url",
+            format!("'{url}' contains unencoded whitespace; wrap bound values in urlEncode()"),
+        ));
+    }
+    let bytes = url.as_bytes();
+    let malformed_escape = bytes.iter().enumerate().any(|(idx, byte)| {
+        *byte == b'%'
+            && !url
+                .get(idx + 1..idx + 3)
+                .is_some_and(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+    });
+    if malformed_escape {
+        return Some((
+            "invalid-url",
+            format!("'{url}' contains a malformed % escape"),
+        ));
+    }
+    if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") {
+        if url.contains('\\') {
+            return Some((
+                "invalid-url",
+                format!("'{url}' contains '\\', which browsers read as '/'; percent-encode it"),
+            ));
+        }
+        let authority = url[scheme.len() + 1..]
+            .strip_prefix("//")
+            .filter(|rest| !rest.starts_with('/'));
+        match url_host(url) {
+            Err(message) => return Some(("invalid-url", message)),
+            Ok(host) if host.is_empty() || authority.is_none() => {
+                return Some(("invalid-url", format!("'{url}' has no host")));
+            }
+            Ok(_) => {}
+        }
+    }
+    None
+}
+
+/// Builds a deep link of `kind` from `params` in the format of the profile's host.
+///
+/// - `chat` (`users`, `topicName`, `message`), `call` (`users`, `withVideo`), and `meeting`
+///   (`subject`, `attendees`, `startTime`, `endTime`, `content`) are Teams-only.
+/// - `dialog` (`url`, `title`, `height`, `width`, `completionBotId`) opens a Teams dialog of
+///   the profile's `app_id`.
+/// - `tab` (`entityId`, `webUrl`, `label`, `subEntityId`) opens a tab of the profile's app.
+/// - `route` (`path`, `query`) is `base_url` + path on the web, and on Teams the app tab
+///   `entityId` (default `home`) with the route as its `subEntityId`.
+///
+/// List values (e.g. several `users`) are comma-joined.
+pub fn deep_link(
+    profile: Option<&TargetProfile>,
+    kind: &str,
+    params: &Map<String, Value>,
+) -> Result<String, String> {
+    let host = profile.and_then(|p| p.host).unwrap_or_default();
+    let app_id = || {
+        profile
+            .and_then(|p| p.app_id.as_deref())
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| format!("{kind} deep links need target_profile.app_id"))
+    };
+    match (host, kind) {
+        (HostPlatform::Teams, "chat") => Ok(with_query(
+            format!("{TEAMS_LINK_BASE}/chat/0/0"),
+            params,
+            &["users", "topicName", "message"],
+        )),
+        (HostPlatform::Teams, "call") => Ok(with_query(
+            format!("{TEAMS_LINK_BASE}/call/0/0"),
+            params,
+            &["users", "withVideo"],
+        )),
+        (HostPlatform::Teams, "meeting") => Ok(with_query(
+            format!("{TEAMS_LINK_BASE}/meeting/new"),
+            params,
+            &["subject", "attendees", "startTime", "endTime", "content"],
+        )),
+        (HostPlatform::Teams, "dialog") => Ok(with_query(
+            format!("{TEAMS_LINK_BASE}/task/{}", url_encode(app_id()?)),
+            params,
+            &["url", "title", "height", "width", "completionBotId"],
+        )),
+        (HostPlatform::Teams, "tab") => {
+            let entity = required(params, "entityId", kind)?;
+            Ok(tab_link(
+                app_id()?,
+                &entity,
+                params,
+                params.get("subEntityId"),
+            ))
+        }
+        (HostPlatform::Teams, "route") => {
+            let route = route_path(params);
+            let mut tab = params.clone();
+            if let Some(base) = profile.and_then(|p| p.base_url.as_deref()) {
+                tab.entry("webUrl")
+                    .or_insert_with(|| json!(join_base(base, &route)));
+            }
+            let entity = params
+                .get("entityId")
+                .map(stringify_value)
+                .unwrap_or_else(|| "home".into());
+            Ok(tab_link(app_id()?, &entity, &tab, Some(&json!(route))))
+        }
+        (HostPlatform::Web, "route") => {
+            let base = profile
+                .and_then(|p| p.base_url.as_deref())
+                .ok_or_else(|| "route deep links need target_profile.base_url".to_string())?;
+            Ok(join_base(base, &route_path(params)))
+        }
+        (host, _) => Err(format!(
+            "{} hosts have no {kind} deep links",
+            match host {
+                HostPlatform::Teams => "teams",
+                HostPlatform::Web => "web",
+                HostPlatform::Outlook => "outlook",
+            }
+        )),
+    }
+}
+
+/// Replaces the `deepLink` object of every action with the `url` it builds for the target
+/// host; actions whose link cannot be built keep no `url` and get an `invalid-deep-link`
+/// warning.
+pub fn apply_deep_links(card: &mut Value, profile: Option<&TargetProfile>) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    resolve_links(card, "", profile, &mut issues);
+    issues
+}
+
+fn resolve_links(
+    value: &mut Value,
+    path: &str,
+    profile: Option<&TargetProfile>,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    match value {
+        Value::Object(map) => {
+            if let Some(link) = map.remove(DEEP_LINK_KEY) {
+                let built = match &link {
+                    Value::Object(params) => params
+                        .get("kind")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| "deepLink needs a kind".to_string())
+                        .and_then(|kind| deep_link(profile, kind, params)),
+                    _ => Err("deepLink must be an object".to_string()),
+                };
+                match built {
+                    Ok(url) => {
+                        map.insert("url".into(), Value::String(url));
+                    }
+                    Err(message) => issues.push(ValidationIssue {
+                        code: "invalid-deep-link".into(),
+                        message,
+                        path: format!("{path}/{DEEP_LINK_KEY}"),
+                        severity: Some(IssueSeverity::Warning),
+                    }),
+                }
+            }
+            for (key, child) in map.iter_mut() {
+                if key != "data" {
+                    resolve_links(child, &format!("{path}/{key}"), profile, issues);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                resolve_links(item, &format!("{path}/{idx}"), profile, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn tab_link(
+    app_id: &str,
+    entity: &str,
+    params: &Map<String, Value>,
+    sub_entity: Option<&Value>,
+) -> String {
+    let mut url = with_query(
+        format!(
+            "{TEAMS_LINK_BASE}/entity/{}/{}",
+            url_encode(app_id),
+            url_encode(entity)
+        ),
+        params,
+        &["webUrl", "label"],
+    );
+    if let Some(sub_entity) = sub_entity {
+        let context = json!({ "subEntityId": stringify_value(sub_entity) }).to_string();
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&format!("context={}", url_encode(&context)));
+    }
+    url
+}
+
+/// `path` plus the `query` object (keys in order) as a query string.
+fn route_path(params: &Map<String, Value>) -> String {
+    let path = params.get("path").map(stringify_value).unwrap_or_default();
+    let path: Vec<String> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(url_encode)
+        .collect();
+    let mut route = format!("/{}", path.join("/"));
+    if let Some(Value::Object(query)) = params.get("query") {
+        let keys: Vec<&str> = query.keys().map(String::as_str).collect();
+        route = with_query(route, query, &keys);
+    }
+    route
+}
+
+fn join_base(base: &str, route: &str) -> String {
+    format!("{}{route}", base.trim_end_matches('/'))
+}
+
+fn required(params: &Map<String, Value>, key: &str, kind: &str) -> Result<String, String> {
+    params
+        .get(key)
+        .map(stringify_value)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| format!("{kind} deep links need {key}"))
+}
+
+/// Appends the listed, non-null `params` as query parameters.
+fn with_query(mut url: String, params: &Map<String, Value>, keys: &[&str]) -> String {
+    let mut separator = '?';
+    for key in keys {
+        let value = match params.get(*key) {
+            None | Some(Value::Null) => continue,
+            Some(Value::Array(items)) => items
+                .iter()
+                .map(|item| url_encode(&stringify_value(item)))
+                .collect::<Vec<_>>()
+                .join(","),
+            Some(value) => url_encode(&stringify_value(value)),
+        };
+        url.push(separator);
+        url.push_str(&format!("{}={value}", url_encode(key)));
+        separator = '&';
+    }
+    url
+}
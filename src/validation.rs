@@ -16,23 +16,30 @@ static INVOCATION_SCHEMA: Lazy<Validator> = Lazy::new(|| {
         .expect("invocation schema must compile")
 });
 
-pub fn locate_invocation_candidate(value: &Value) -> Option<Value> {
+pub fn locate_invocation_candidate(value: &Value) -> Option<&Value> {
     if let Some(inv) = find_invocation_value(value) {
         return Some(inv);
     }
     if let Some(payload) = value.get("payload")
         && payload.is_object()
     {
-        return Some(payload.clone());
+        return Some(payload);
     }
     if let Some(config) = value.get("config")
         && config.is_object()
     {
-        return Some(config.clone());
+        return Some(config);
     }
     None
 }
 
+/// Whether the message is the invocation itself rather than an envelope around it.
+pub fn is_bare_invocation(value: &Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|obj| obj.contains_key("card_source") || obj.contains_key("card_spec"))
+}
+
 pub fn validate_invocation_schema(value: &Value) -> Vec<ValidationIssue> {
     INVOCATION_SCHEMA
         .iter_errors(value)
@@ -57,19 +64,20 @@ fn map_schema_error(error: &jsonschema::ValidationError) -> ValidationIssue {
         code: code.to_string(),
         message: error.to_string(),
         path,
+        severity: None,
     }
 }
 
-fn find_invocation_value(value: &Value) -> Option<Value> {
+fn find_invocation_value(value: &Value) -> Option<&Value> {
     let obj = value.as_object()?;
-    if obj.contains_key("card_source") || obj.contains_key("card_spec") {
-        return Some(value.clone());
+    if is_bare_invocation(value) {
+        return Some(value);
     }
     if let Some(inv) = obj.get("invocation") {
-        return Some(inv.clone());
+        return Some(inv);
     }
     if let Some(card) = obj.get("card") {
-        return Some(card.clone());
+        return Some(card);
     }
     if let Some(payload) = obj.get("payload")
         && payload
@@ -77,7 +85,7 @@ fn find_invocation_value(value: &Value) -> Option<Value> {
             .map(|p| p.contains_key("card_source") || p.contains_key("card_spec"))
             .unwrap_or(false)
     {
-        return Some(payload.clone());
+        return Some(payload);
     }
     if let Some(config) = obj.get("config") {
         if config
@@ -85,10 +93,10 @@ fn find_invocation_value(value: &Value) -> Option<Value> {
             .map(|c| c.contains_key("card_source") || c.contains_key("card_spec"))
             .unwrap_or(false)
         {
-            return Some(config.clone());
+            return Some(config);
         }
         if let Some(card) = config.get("card") {
-            return Some(card.clone());
+            return Some(card);
         }
     }
     None
@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::OnceCell;
+use serde_json::Value;
+
+use crate::model::{
+    AdaptiveActionEvent, IssueSeverity, SessionUpdateOp, StateUpdateOp, ValidationIssue,
+};
+
+/// Updates a verb handler adds to those of the interaction it handles.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VerbOutcome {
+    pub state_updates: Vec<StateUpdateOp>,
+    pub session_updates: Vec<SessionUpdateOp>,
+}
+
+/// Handles the event of one verb given the card state after the interaction's own updates;
+/// errors become warnings.
+type VerbHandlerFn =
+    dyn Fn(&AdaptiveActionEvent, &Value) -> Result<VerbOutcome, String> + Send + Sync;
+
+static HOST_VERBS: OnceCell<RwLock<BTreeMap<String, Arc<VerbHandlerFn>>>> = OnceCell::new();
+
+fn verbs_cell() -> &'static RwLock<BTreeMap<String, Arc<VerbHandlerFn>>> {
+    HOST_VERBS.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Registers the handler `handle_interaction` runs for events with `verb`, replacing any
+/// earlier one. Its updates are persisted and rendered with the interaction's own and
+/// returned in `state_updates` and `session_updates`.
+pub fn register_verb_handler(verb: &str, handler: Box<VerbHandlerFn>) -> Result<(), &'static str> {
+    verbs_cell()
+        .write()
+        .map_err(|_| "verb handler registry poisoned")?
+        .insert(verb.to_string(), Arc::from(handler));
+    Ok(())
+}
+
+/// Runs the handler registered for the event's verb, if any. A failing handler contributes
+/// no updates and a `verb-handler-failed` warning.
+pub fn dispatch(event: &AdaptiveActionEvent, state: &Value) -> (VerbOutcome, Vec<ValidationIssue>) {
+    let Some(verb) = event.verb.as_deref() else {
+        return Default::default();
+    };
+    let handler = verbs_cell()
+        .read()
+        .ok()
+        .and_then(|registered| registered.get(verb).cloned());
+    let Some(handler) = handler else {
+        return Default::default();
+    };
+    match handler(event, state) {
+        Ok(outcome) => (outcome, Vec::new()),
+        Err(err) => (
+            VerbOutcome::default(),
+            vec![ValidationIssue {
+                code: "verb-handler-failed".into(),
+                message: format!("handler for verb '{verb}' failed: {err}"),
+                path: "/event/verb".into(),
+                severity: Some(IssueSeverity::Warning),
+            }],
+        ),
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeSet;
+
+use serde_json::{Map, Value};
+
+use crate::model::{AdaptiveCardInvocation, IssueSeverity, ValidationIssue};
+
+/// Most `refresh.userIds` entries hosts accept (Teams refreshes at most 60 users).
+pub const MAX_REFRESH_USER_IDS: usize = 60;
+
+/// The `viewer` binding root: the invocation's `viewer`, or the envelope user when only the
+/// envelope identifies who is looking at the card. `Null` when the viewer is unknown.
+pub fn viewer_value(inv: &AdaptiveCardInvocation) -> Value {
+    if let Some(viewer) = inv.viewer.as_ref() {
+        return serde_json::to_value(viewer).unwrap_or(Value::Null);
+    }
+    let Some(ctx) = inv.envelope.as_ref().map(|env| &env.ctx) else {
+        return Value::Null;
+    };
+    match ctx.user_id.as_ref().or(ctx.user.as_ref()) {
+        Some(user) => {
+            let mut viewer = Map::new();
+            viewer.insert("id".into(), Value::String(user.as_str().to_string()));
+            Value::Object(viewer)
+        }
+        None => Value::Null,
+    }
+}
+
+/// Normalizes the card's `refresh` block so only designated users get refreshed views:
+/// `refresh_user_ids` from the invocation replaces `userIds`; otherwise bound entries are
+/// reduced to id strings (user objects contribute their `id`). Ids are de-duplicated and
+/// capped at [`MAX_REFRESH_USER_IDS`], and the refresh action must be `Action.Execute`.
+pub fn normalize_refresh(card: &mut Value, inv: &AdaptiveCardInvocation) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let Some(refresh) = card.get_mut("refresh").and_then(Value::as_object_mut) else {
+        if inv.refresh_user_ids.is_some() {
+            issues.push(warning(
+                "refresh-user-ids-without-refresh",
+                "refresh_user_ids was given but the card has no refresh block".into(),
+                "/refresh".into(),
+            ));
+        }
+        return issues;
+    };
+    let action_type = refresh
+        .get("action")
+        .and_then(|action| action.get("type"))
+        .and_then(Value::as_str);
+    if action_type != Some("Action.Execute") {
+        issues.push(ValidationIssue {
+            code: "invalid-refresh-action".into(),
+            message: "refresh.action must be an Action.Execute".into(),
+            path: "/refresh/action".into(),
+            severity: None,
+        });
+    }
+
+    let entries = match (inv.refresh_user_ids.as_ref(), refresh.get("userIds")) {
+        (Some(ids), _) => ids.iter().cloned().map(Value::String).collect(),
+        (None, None) => return issues,
+        (None, Some(Value::Array(entries))) => entries.clone(),
+        (None, Some(other)) => {
+            issues.push(warning(
+                "invalid-refresh-user-ids",
+                format!("refresh.userIds must be an array, got {other}"),
+                "/refresh/userIds".into(),
+            ));
+            Vec::new()
+        }
+    };
+    let mut seen = BTreeSet::new();
+    let mut ids = Vec::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        let id = match entry {
+            Value::String(id) => Some(id.trim()),
+            Value::Object(user) => user.get("id").and_then(Value::as_str).map(str::trim),
+            _ => None,
+        };
+        match id {
+            Some("") => {}
+            Some(id) => {
+                if seen.insert(id.to_string()) {
+                    ids.push(Value::String(id.to_string()));
+                }
+            }
+            None => issues.push(warning(
+                "invalid-refresh-user-id",
+                format!("refresh.userIds entry must be a user id, got {entry}"),
+                format!("/refresh/userIds/{idx}"),
+            )),
+        }
+    }
+    if ids.len() > MAX_REFRESH_USER_IDS {
+        issues.push(warning(
+            "refresh-user-ids-truncated",
+            format!(
+                "refresh.userIds lists {} users; only the first {MAX_REFRESH_USER_IDS} are kept",
+                ids.len()
+            ),
+            "/refresh/userIds".into(),
+        ));
+        ids.truncate(MAX_REFRESH_USER_IDS);
+    }
+    refresh.insert("userIds".into(), Value::Array(ids));
+    issues
+}
+
+fn warning(code: &str, message: String, path: String) -> ValidationIssue {
+    ValidationIssue {
+        code: code.to_string(),
+        message,
+        path,
+        severity: Some(IssueSeverity::Warning),
+    }
+}
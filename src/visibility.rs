@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+/// Applies persisted `ui.visibility.<elementId>` flags to the card's `isVisible` properties and
+/// returns the resolved visibility of every toggle target or state-tracked element.
+pub fn apply_visibility(card: &mut Value, state: &Value) -> BTreeMap<String, bool> {
+    let overrides = state
+        .get("ui")
+        .and_then(|ui| ui.get("visibility"))
+        .and_then(|v| v.as_object())
+        .cloned()
+        .unwrap_or_default();
+    let mut tracked: Vec<String> = toggle_target_ids(card);
+    tracked.extend(overrides.keys().cloned());
+
+    let mut resolved = BTreeMap::new();
+    visit(card, &overrides, &tracked, &mut resolved);
+    resolved
+}
+
+fn visit(
+    value: &mut Value,
+    overrides: &Map<String, Value>,
+    tracked: &[String],
+    resolved: &mut BTreeMap<String, bool>,
+) {
+    match value {
+        Value::Object(map) => {
+            let is_element = map
+                .get("type")
+                .and_then(|v| v.as_str())
+                .map(|t| !t.starts_with("Action."))
+                .unwrap_or(false);
+            if is_element && let Some(id) = map.get("id").and_then(|v| v.as_str()) {
+                let id = id.to_string();
+                if let Some(flag) = overrides.get(&id).and_then(|v| v.as_bool()) {
+                    map.insert("isVisible".into(), Value::Bool(flag));
+                }
+                if tracked.contains(&id) {
+                    let visible = map
+                        .get("isVisible")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(true);
+                    resolved.insert(id, visible);
+                }
+            }
+            for child in map.values_mut() {
+                visit(child, overrides, tracked, resolved);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                visit(item, overrides, tracked, resolved);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Element ids referenced by any Action.ToggleVisibility in the card.
+pub fn toggle_target_ids(card: &Value) -> Vec<String> {
+    crate::actions::collect_actions(card)
+        .into_iter()
+        .filter(|a| a.kind == "Action.ToggleVisibility")
+        .flat_map(|a| parse_targets(&a.action))
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Parses `targetElements` entries: either bare ids or `{ elementId, isVisible }` objects.
+pub fn parse_targets(action: &Map<String, Value>) -> Vec<(String, Option<bool>)> {
+    action
+        .get("targetElements")
+        .and_then(|v| v.as_array())
+        .map(|targets| {
+            targets
+                .iter()
+                .filter_map(|target| match target {
+                    Value::String(id) => Some((id.clone(), None)),
+                    Value::Object(obj) => obj.get("elementId").and_then(|v| v.as_str()).map(|id| {
+                        (
+                            id.to_string(),
+                            obj.get("isVisible").and_then(|v| v.as_bool()),
+                        )
+                    }),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Resolves the new visibility for each target of a ToggleVisibility action: explicit
+/// `isVisible` wins, otherwise the element's current visibility is flipped.
+pub fn resolve_toggle(action: &Map<String, Value>, card: &Value) -> Vec<(String, bool)> {
+    parse_targets(action)
+        .into_iter()
+        .map(|(id, explicit)| {
+            let next = explicit.unwrap_or_else(|| !current_visibility(card, &id));
+            (id, next)
+        })
+        .collect()
+}
+
+fn current_visibility(card: &Value, id: &str) -> bool {
+    fn find<'a>(value: &'a Value, id: &str) -> Option<&'a Map<String, Value>> {
+        match value {
+            Value::Object(map) => {
+                if map.get("id").and_then(|v| v.as_str()) == Some(id)
+                    && map
+                        .get("type")
+                        .and_then(|v| v.as_str())
+                        .map(|t| !t.starts_with("Action."))
+                        .unwrap_or(false)
+                {
+                    return Some(map);
+                }
+                map.values().find_map(|child| find(child, id))
+            }
+            Value::Array(items) => items.iter().find_map(|item| find(item, id)),
+            _ => None,
+        }
+    }
+    find(card, id)
+        .and_then(|el| el.get("isVisible"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+}
@@ -0,0 +1,489 @@
+use serde_json::{Map, Number, Value};
+
+/// Parses the YAML subset form definitions are written in: block mappings and sequences
+/// (including `- key: value` items), `|` and `>` block scalars, single-line flow
+/// collections (`[a, b]`, `{a: 1}`), single- and double-quoted strings, `#` comments, and
+/// plain scalars typed as null, booleans, numbers, or strings. Anchors, tags, and multi-line
+/// flow collections are not supported. Collections nested deeper than `max_depth`, block or
+/// flow, are rejected. Errors name the offending line.
+pub fn parse(text: &str, max_depth: usize) -> Result<Value, String> {
+    let lines: Vec<Line<'_>> = text
+        .lines()
+        .enumerate()
+        .map(|(idx, raw)| Line {
+            number: idx + 1,
+            indent: raw.len() - raw.trim_start_matches(' ').len(),
+            raw,
+        })
+        .collect();
+    let mut parser = Parser {
+        lines,
+        pos: 0,
+        depth: 0,
+        max_depth,
+    };
+    parser.skip_insignificant();
+    if parser.pos < parser.lines.len() && parser.content().trim_end() == "---" {
+        parser.pos += 1;
+        parser.skip_insignificant();
+    }
+    let Some(line) = parser.current() else {
+        return Ok(Value::Null);
+    };
+    let value = parser.block(line.indent)?;
+    parser.skip_insignificant();
+    match parser.current() {
+        Some(line) if line.raw.trim() != "..." => {
+            Err(format!("line {}: unexpected indentation", line.number))
+        }
+        _ => Ok(value),
+    }
+}
+
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    raw: &'a str,
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+    /// Collections open around the current line.
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn current(&self) -> Option<&Line<'a>> {
+        self.lines.get(self.pos)
+    }
+
+    /// Current line without its indentation.
+    fn content(&self) -> &'a str {
+        let line = &self.lines[self.pos];
+        &line.raw[line.indent..]
+    }
+
+    /// Skips blank and comment-only lines.
+    fn skip_insignificant(&mut self) {
+        while let Some(line) = self.current() {
+            let trimmed = line.raw.trim();
+            if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        let number = self.current().map_or(self.lines.len(), |line| line.number);
+        format!("line {number}: {message}")
+    }
+
+    /// Opens a block collection, failing past `max_depth`.
+    fn enter(&mut self) -> Result<(), String> {
+        if self.depth >= self.max_depth {
+            return Err(self.error(&too_deep(self.max_depth)));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn flow_value(&self, text: &str) -> Result<Value, String> {
+        flow_value(text, self.depth, self.max_depth).map_err(|message| self.error(&message))
+    }
+
+    /// Sequence or mapping whose entries start at `indent`.
+    fn block(&mut self, indent: usize) -> Result<Value, String> {
+        if self.content().starts_with('\t') {
+            return Err(self.error("tabs cannot indent YAML"));
+        }
+        if is_item(self.content()) {
+            self.sequence(indent)
+        } else {
+            self.mapping(indent)
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Value, String> {
+        self.enter()?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_insignificant();
+            let Some(line) = self.current() else { break };
+            if line.indent != indent || !is_item(self.content()) {
+                if line.indent > indent {
+                    return Err(self.error("unexpected indentation"));
+                }
+                break;
+            }
+            let rest = &self.content()[1..];
+            let offset = rest.len() - rest.trim_start().len();
+            let rest = rest.trim_start();
+            if rest.is_empty() || rest.starts_with('#') {
+                self.pos += 1;
+                items.push(self.nested(indent, false)?);
+            } else if split_key(rest).is_some() {
+                // `- key: value` starts a mapping indented to its first key.
+                let line = &mut self.lines[self.pos];
+                line.indent = indent + 1 + offset;
+                items.push(self.mapping(indent + 1 + offset)?);
+            } else {
+                items.push(self.scalar_or_flow(rest, indent)?);
+            }
+        }
+        self.depth -= 1;
+        Ok(Value::Array(items))
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Value, String> {
+        self.enter()?;
+        let mut map = Map::new();
+        loop {
+            self.skip_insignificant();
+            let Some(line) = self.current() else { break };
+            if line.indent != indent || is_item(self.content()) {
+                if line.indent > indent {
+                    return Err(self.error("unexpected indentation"));
+                }
+                break;
+            }
+            let content = self.content();
+            let Some((key, value)) = split_key(content) else {
+                return Err(self.error("expected `key: value`"));
+            };
+            let key = match key.chars().next() {
+                Some('"' | '\'') => match self.flow_value(key)? {
+                    Value::String(key) => key,
+                    other => other.to_string(),
+                },
+                _ => key.to_string(),
+            };
+            if map.contains_key(&key) {
+                return Err(self.error(&format!("duplicate key `{key}`")));
+            }
+            let value = value.trim();
+            let value = if value.is_empty() || value.starts_with('#') {
+                self.pos += 1;
+                self.nested(indent, true)?
+            } else {
+                self.scalar_or_flow(value, indent)?
+            };
+            map.insert(key, value);
+        }
+        self.depth -= 1;
+        Ok(Value::Object(map))
+    }
+
+    /// Value of an entry whose text continues on the following lines: a block indented
+    /// deeper than `indent` (or, for mapping values, a sequence at the same indentation),
+    /// otherwise null.
+    fn nested(&mut self, indent: usize, sequence_at_indent: bool) -> Result<Value, String> {
+        self.skip_insignificant();
+        match self.current() {
+            Some(line) if line.indent > indent => self.block(line.indent),
+            Some(line)
+                if sequence_at_indent && line.indent == indent && is_item(self.content()) =>
+            {
+                self.sequence(indent)
+            }
+            _ => Ok(Value::Null),
+        }
+    }
+
+    /// Inline value on the current line (a block scalar header reads the lines after it).
+    fn scalar_or_flow(&mut self, text: &str, indent: usize) -> Result<Value, String> {
+        let text = strip_comment(text);
+        if let Some(header) = text.strip_prefix(['|', '>'])
+            && matches!(header, "" | "-" | "+")
+        {
+            let folded = text.starts_with('>');
+            self.pos += 1;
+            return Ok(Value::String(self.block_scalar(indent, folded, header)));
+        }
+        let value = self.flow_value(text)?;
+        self.pos += 1;
+        Ok(value)
+    }
+
+    /// Lines indented deeper than `indent`, joined by newlines (`|`) or spaces (`>`), with
+    /// one trailing newline unless chomped (`-`) or kept (`+`).
+    fn block_scalar(&mut self, indent: usize, folded: bool, chomp: &str) -> String {
+        let mut lines: Vec<&str> = Vec::new();
+        let mut content_indent = None;
+        while let Some(line) = self.current() {
+            if line.raw.trim().is_empty() {
+                lines.push("");
+                self.pos += 1;
+                continue;
+            }
+            if line.indent <= indent {
+                break;
+            }
+            let strip = *content_indent.get_or_insert(line.indent);
+            lines.push(line.raw.get(strip.min(line.indent)..).unwrap_or(""));
+            self.pos += 1;
+        }
+        let trailing = lines
+            .iter()
+            .rev()
+            .take_while(|line| line.is_empty())
+            .count();
+        let body = &lines[..lines.len() - trailing];
+        let mut text = if folded {
+            let mut text = String::new();
+            for (idx, line) in body.iter().enumerate() {
+                if idx > 0 {
+                    text.push(if line.is_empty() || body[idx - 1].is_empty() {
+                        '\n'
+                    } else {
+                        ' '
+                    });
+                }
+                text.push_str(line);
+            }
+            text
+        } else {
+            body.join("\n")
+        };
+        match chomp {
+            "-" => {}
+            "+" => text.push_str(&"\n".repeat(trailing + 1)),
+            _ if !body.is_empty() => text.push('\n'),
+            _ => {}
+        }
+        text
+    }
+}
+
+fn is_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// Splits `key: value` at the first `:` followed by a space or the end of the line that is
+/// outside quotes and brackets.
+fn split_key(content: &str) -> Option<(&str, &str)> {
+    if content.starts_with(['[', '{']) {
+        return None;
+    }
+    let mut quote = None;
+    let bytes = content.as_bytes();
+    for (idx, &byte) in bytes.iter().enumerate() {
+        match (quote, byte) {
+            (Some(open), _) if byte == open => quote = None,
+            (Some(_), _) => {}
+            (None, b'"' | b'\'') if idx == 0 => quote = Some(byte),
+            (None, b'#') if idx > 0 && bytes[idx - 1] == b' ' => return None,
+            (None, b':') if bytes.get(idx + 1).is_none_or(|next| *next == b' ') => {
+                let key = content[..idx].trim_end();
+                return (!key.is_empty()).then(|| (key, &content[idx + 1..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Drops a trailing ` # comment` outside quotes.
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    let bytes = text.as_bytes();
+    for (idx, &byte) in bytes.iter().enumerate() {
+        match (quote, byte) {
+            (Some(open), _) if byte == open => quote = None,
+            (Some(_), _) => {}
+            (None, b'"' | b'\'') => quote = Some(byte),
+            (None, b'#') if idx == 0 || bytes[idx - 1] == b' ' => return text[..idx].trim_end(),
+            _ => {}
+        }
+    }
+    text.trim_end()
+}
+
+fn too_deep(max_depth: usize) -> String {
+    format!("collections nest deeper than {max_depth} levels")
+}
+
+/// A flow collection or scalar spanning the rest of a line, inside `depth` collections.
+fn flow_value(text: &str, depth: usize, max_depth: usize) -> Result<Value, String> {
+    let mut flow = Flow {
+        chars: text.char_indices().peekable(),
+        text,
+        depth,
+        max_depth,
+    };
+    let value = flow.value(false)?;
+    flow.skip_spaces();
+    match flow.chars.peek() {
+        None => Ok(value),
+        Some((_, ch)) => Err(format!("unexpected `{ch}`")),
+    }
+}
+
+struct Flow<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    text: &'a str,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Flow<'_> {
+    fn skip_spaces(&mut self) {
+        while self.chars.next_if(|(_, ch)| *ch == ' ').is_some() {}
+    }
+
+    fn value(&mut self, in_flow: bool) -> Result<Value, String> {
+        self.skip_spaces();
+        match self.chars.peek().map(|(_, ch)| *ch) {
+            Some(open @ ('[' | '{')) => {
+                if self.depth >= self.max_depth {
+                    return Err(too_deep(self.max_depth));
+                }
+                self.chars.next();
+                self.depth += 1;
+                let value = if open == '[' {
+                    self.sequence()
+                } else {
+                    self.mapping()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => self.double_quoted(),
+            Some('\'') => self.single_quoted(),
+            _ => Ok(self.plain(in_flow)),
+        }
+    }
+
+    /// Items after an opening `[`.
+    fn sequence(&mut self) -> Result<Value, String> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_spaces();
+            if self.chars.next_if(|(_, ch)| *ch == ']').is_some() {
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value(true)?);
+            self.skip_spaces();
+            match self.chars.next() {
+                Some((_, ',')) => {}
+                Some((_, ']')) => return Ok(Value::Array(items)),
+                _ => return Err("unterminated `[`".into()),
+            }
+        }
+    }
+
+    /// Entries after an opening `{`.
+    fn mapping(&mut self) -> Result<Value, String> {
+        let mut map = Map::new();
+        loop {
+            self.skip_spaces();
+            if self.chars.next_if(|(_, ch)| *ch == '}').is_some() {
+                return Ok(Value::Object(map));
+            }
+            let key = match self.value(true)? {
+                Value::String(key) => key,
+                other => other.to_string(),
+            };
+            self.skip_spaces();
+            let value = match self.chars.next_if(|(_, ch)| *ch == ':') {
+                Some(_) => self.value(true)?,
+                None => Value::Null,
+            };
+            map.insert(key, value);
+            self.skip_spaces();
+            match self.chars.next() {
+                Some((_, ',')) => {}
+                Some((_, '}')) => return Ok(Value::Object(map)),
+                _ => return Err("unterminated `{`".into()),
+            }
+        }
+    }
+
+    fn double_quoted(&mut self) -> Result<Value, String> {
+        self.chars.next();
+        let mut out = String::new();
+        while let Some((_, ch)) = self.chars.next() {
+            match ch {
+                '"' => return Ok(Value::String(out)),
+                '\\' => match self.chars.next().map(|(_, ch)| ch) {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('0') => out.push('\0'),
+                    Some('u') => {
+                        let hex: String = (0..4)
+                            .filter_map(|_| self.chars.next())
+                            .map(|(_, c)| c)
+                            .collect();
+                        let ch = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("invalid escape `\\u{hex}`"))?;
+                        out.push(ch);
+                    }
+                    Some(other @ ('"' | '\\' | '/' | ' ')) => out.push(other),
+                    Some(other) => return Err(format!("invalid escape `\\{other}`")),
+                    None => break,
+                },
+                other => out.push(other),
+            }
+        }
+        Err("unterminated string".into())
+    }
+
+    fn single_quoted(&mut self) -> Result<Value, String> {
+        self.chars.next();
+        let mut out = String::new();
+        while let Some((_, ch)) = self.chars.next() {
+            if ch == '\'' && self.chars.next_if(|(_, ch)| *ch == '\'').is_none() {
+                return Ok(Value::String(out));
+            }
+            out.push(ch);
+        }
+        Err("unterminated string".into())
+    }
+
+    /// Plain scalar up to the end of the text or, inside a flow collection, the next `,`,
+    /// `]`, `}`, or `: `.
+    fn plain(&mut self, in_flow: bool) -> Value {
+        let start = self.chars.peek().map_or(self.text.len(), |(idx, _)| *idx);
+        let mut end = self.text.len();
+        while let Some(&(idx, ch)) = self.chars.peek() {
+            let ends_key = ch == ':'
+                && self.text[idx + 1..]
+                    .chars()
+                    .next()
+                    .is_none_or(|next| next == ' ' || ",]}".contains(next));
+            if in_flow && (",]}".contains(ch) || ends_key) {
+                end = idx;
+                break;
+            }
+            self.chars.next();
+        }
+        typed(self.text[start..end].trim())
+    }
+}
+
+/// Types a plain scalar: `null`/`~`, booleans, integers, floats, or a string.
+fn typed(text: &str) -> Value {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => return Value::Null,
+        "true" | "True" | "TRUE" => return Value::Bool(true),
+        "false" | "False" | "FALSE" => return Value::Bool(false),
+        _ => {}
+    }
+    let numeric = text
+        .trim_start_matches(['-', '+'])
+        .starts_with(|ch: char| ch.is_ascii_digit() || ch == '.');
+    if numeric {
+        if let Ok(int) = text.parse::<i64>() {
+            return Value::Number(int.into());
+        }
+        if let Some(number) = text.parse::<f64>().ok().and_then(Number::from_f64) {
+            return Value::Number(number);
+        }
+    }
+    Value::String(text.to_string())
+}
@@ -0,0 +1,81 @@
+//! Component configuration is process-wide, so these tests run in their own binary.
+
+use component_adaptive_card::{
+    AdaptiveCardInvocation, CardBudget, CardSource, CardSpec, TargetProfile, configure,
+    current_config, handle_invocation, load_startup_config,
+};
+use serde_json::json;
+
+fn invocation(card: serde_json::Value) -> AdaptiveCardInvocation {
+    AdaptiveCardInvocation {
+        card_source: CardSource::Inline,
+        card_spec: CardSpec {
+            inline_json: Some(card),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn startup_config_provides_defaults_that_invocations_override() {
+    let path =
+        std::env::temp_dir().join(format!("adaptive-card-config-{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        json!({
+            "targetProfile": { "maxVersion": "1.2" },
+            "budget": { "max_actions": 1 }
+        })
+        .to_string(),
+    )
+    .expect("write config");
+    // SAFETY: this binary's only test; nothing else reads the environment concurrently.
+    unsafe { std::env::set_var("ADAPTIVE_CARD_CONFIG", &path) };
+    let config = load_startup_config(Some("fr-CA")).expect("config loads");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(config.locale.as_deref(), Some("fr-CA"));
+    assert_eq!(current_config(), config);
+
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{
+            "type": "Table",
+            "fallback": { "type": "TextBlock", "text": "Table unavailable" }
+        }],
+        "actions": [
+            { "type": "Action.Submit", "id": "a", "title": "A" },
+            { "type": "Action.Submit", "id": "b", "title": "B" }
+        ]
+    });
+    let configured = handle_invocation(invocation(card.clone())).expect("render");
+    let rendered = configured.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["text"], "Table unavailable");
+    assert!(
+        configured
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "budget-actions")
+    );
+
+    let mut overridden = invocation(card.clone());
+    overridden.target_profile = Some(TargetProfile {
+        max_version: Some("1.6".into()),
+        ..Default::default()
+    });
+    overridden.budget = Some(CardBudget::default());
+    let result = handle_invocation(overridden).expect("render");
+    assert_eq!(
+        result.rendered_card.expect("card")["body"][0]["type"],
+        "Table"
+    );
+    assert!(result.validation_issues.is_empty());
+
+    configure(Default::default()).expect("reset");
+    let unconfigured = handle_invocation(invocation(card)).expect("render");
+    assert_eq!(
+        unconfigured.rendered_card.expect("card")["body"][0]["type"],
+        "Table"
+    );
+}
@@ -1,6 +1,14 @@
 use component_adaptive_card::{
-    AdaptiveCardInvocation, CardInteraction, CardInteractionType, CardSource, CardSpec,
-    InvocationMode, ValidationMode, handle_invocation, register_host_asset_callback,
+    AdaptiveActionType, AdaptiveCardInvocation, AdaptiveCardResponse, AuditCheck, CardBudget,
+    CardInteraction, CardInteractionType, CardOutputOptions, CardSource, CardSpec, CardViewer,
+    ColorScheme, DisableActions, FailureCode, HostPlatform, HydrationRequest, InvocationMode,
+    IssueSeverity, MarkdownMode, PreviewFormat, RateLimit, RouteRule, SecurityPolicy,
+    SessionUpdateOp, ShowCardMode, StateUpdateOp, StreamFrame, TargetProfile, TelemetryLevel,
+    TruncationStrategy, ValidationMode, VerbOutcome, handle_invocation, invoke_stream,
+    register_component_macro, register_data_transformer_callback,
+    register_element_renderer_callback, register_expression_callback, register_host_asset_callback,
+    register_post_render_callback, register_state_migration_callback, register_verb_handler,
+    remove_post_render_hook,
 };
 use serde_json::json;
 #[cfg(not(target_arch = "wasm32"))]
@@ -24,6 +32,7 @@ fn base_invocation(card: serde_json::Value) -> AdaptiveCardInvocation {
         mode: InvocationMode::RenderAndValidate,
         validation_mode: ValidationMode::Warn,
         envelope: None,
+        ..Default::default()
     }
 }
 
@@ -126,6 +135,7 @@ fn asset_render_loads_card() {
         mode: InvocationMode::RenderAndValidate,
         validation_mode: ValidationMode::Warn,
         envelope: None,
+        ..Default::default()
     };
 
     let result = handle_invocation(invocation).expect("asset render");
@@ -167,6 +177,7 @@ fn catalog_resolution_uses_env_mapping() {
         mode: InvocationMode::RenderAndValidate,
         validation_mode: ValidationMode::Warn,
         envelope: None,
+        ..Default::default()
     };
 
     let result = handle_invocation(invocation).expect("catalog render");
@@ -250,6 +261,8 @@ fn submit_interaction_emits_event_and_updates_state() {
         raw_inputs: json!({ "comment": "Looks good" }),
         card_instance_id: "card-1".to_string(),
         metadata: json!({ "route": "next" }),
+        idempotency_key: None,
+        dialog_id: None,
     });
 
     let result = handle_invocation(invocation).expect("interaction");
@@ -285,6 +298,8 @@ fn toggle_visibility_sets_state_flag() {
         raw_inputs: json!({}),
         card_instance_id: "card-2".to_string(),
         metadata: json!({ "visible": false }),
+        idempotency_key: None,
+        dialog_id: None,
     });
 
     let result = handle_invocation(invocation).expect("toggle");
@@ -391,9 +406,6496 @@ fn host_asset_registry_resolves_assets() {
         mode: InvocationMode::RenderAndValidate,
         validation_mode: ValidationMode::Warn,
         envelope: None,
+        ..Default::default()
     };
 
     let result = handle_invocation(invocation).expect("host registry");
     let card = result.rendered_card.expect("card should render");
     assert_eq!(card["type"], "AdaptiveCard");
 }
+
+#[test]
+fn extended_inputs_are_coerced_on_submit() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "Input.Rating", "id": "stars", "max": 5 },
+            { "type": "Input.Slider", "id": "volume", "min": 0, "max": 100, "step": 10 },
+            { "type": "Input.Slider", "id": "ratio", "min": 0, "max": 1, "step": 0.1 },
+            { "type": "Input.ColorPicker", "id": "accent" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "submit".to_string(),
+        verb: None,
+        raw_inputs: json!({ "stars": "3.7", "volume": 143, "ratio": 0.31, "accent": "#ABC" }),
+        card_instance_id: "card-extended".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+
+    let result = handle_invocation(invocation).expect("interaction");
+    let inputs = result.event.expect("event").inputs;
+    assert_eq!(inputs["stars"], json!(4));
+    assert_eq!(inputs["volume"], json!(100));
+    assert_eq!(inputs["ratio"], json!(0.3));
+    assert_eq!(inputs["accent"], "#aabbcc");
+}
+
+#[test]
+fn validation_reports_extended_input_rules() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "Input.Rating", "id": "stars", "max": 5, "value": 7 },
+            { "type": "Input.Slider", "id": "volume", "min": 10, "max": 1 },
+            { "type": "Input.ColorPicker", "id": "accent", "value": "blue" }
+        ]
+    });
+    let result = handle_invocation(base_invocation(card)).expect("validation");
+    let codes: Vec<String> = result
+        .validation_issues
+        .iter()
+        .map(|i| i.code.clone())
+        .collect();
+    assert!(codes.iter().any(|c| c == "invalid-value"));
+    assert!(codes.iter().any(|c| c == "invalid-range"));
+    assert!(codes.iter().any(|c| c == "invalid-color"));
+}
+
+#[test]
+fn markdown_normalize_strips_unsupported_constructs() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            {
+                "type": "TextBlock",
+                "text": "# Title\n**Bold** and <script>x</script>[site](https://example.com) [bad](javascript:alert(1))\n* item"
+            }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.markdown = MarkdownMode::Normalize;
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(
+        rendered["body"][0]["text"],
+        "Title\n**Bold** and x[site](https://example.com) bad\n- item"
+    );
+}
+
+#[test]
+fn markdown_normalize_keeps_literal_markers_and_rich_text_runs() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "\\*x\\* and 5 * 3 = 15 with snake_case\n#tag stays\n## Heading" },
+            { "type": "TextBlock", "text": "**a * b** and _half" },
+            {
+                "type": "RichTextBlock",
+                "inlines": ["2 * 3 * 4 and [x](y)", { "type": "TextRun", "text": "**raw**" }]
+            }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.markdown = MarkdownMode::Normalize;
+    let result = handle_invocation(invocation).expect("render");
+    let body = &result.rendered_card.expect("card")["body"];
+    assert_eq!(
+        body[0]["text"],
+        "\\*x\\* and 5 * 3 = 15 with snake_case\n#tag stays\nHeading"
+    );
+    assert_eq!(body[1]["text"], "**a * b** and \\_half");
+    assert_eq!(body[2]["inlines"][0]["text"], "2 * 3 * 4 and [x](y)");
+    assert_eq!(body[2]["inlines"][1]["text"], "**raw**");
+}
+
+#[test]
+fn markdown_text_runs_converts_text_block() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "id": "terms", "text": "Read **the** [terms](https://example.com/t)" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.markdown = MarkdownMode::TextRuns;
+    let result = handle_invocation(invocation).expect("render");
+    let block = &result.rendered_card.expect("card")["body"][0];
+    assert_eq!(block["type"], "RichTextBlock");
+    assert_eq!(block["id"], "terms");
+    let inlines = block["inlines"].as_array().expect("inlines");
+    assert_eq!(inlines[1]["weight"], "Bolder");
+    assert_eq!(inlines[3]["selectAction"]["url"], "https://example.com/t");
+}
+
+#[test]
+fn rich_text_run_actions_route_through_interactions() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            {
+                "type": "RichTextBlock",
+                "id": "terms",
+                "inlines": [
+                    "I accept the ",
+                    {
+                        "type": "TextRun",
+                        "text": "terms",
+                        "selectAction": { "type": "Action.Execute", "verb": "accept", "data": { "accepted": true } }
+                    }
+                ]
+            }
+        ]
+    });
+    let rendered = handle_invocation(base_invocation(card.clone())).expect("render");
+    let block = &rendered.rendered_card.expect("card")["body"][0];
+    assert_eq!(block["inlines"][0]["type"], "TextRun");
+    let run_action = block["inlines"][1]["selectAction"]["id"]
+        .as_str()
+        .expect("generated id")
+        .to_string();
+    assert!(run_action.starts_with("action-execute-"));
+    assert!(rendered.validation_issues.is_empty());
+
+    let mut invocation = base_invocation(card);
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Execute,
+        action_id: run_action,
+        verb: None,
+        raw_inputs: json!({}),
+        card_instance_id: "card-terms".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let result = handle_invocation(invocation).expect("interaction");
+    let event = result.event.expect("event");
+    assert_eq!(event.verb.as_deref(), Some("accept"));
+    assert_eq!(event.inputs["accepted"], true);
+}
+
+#[test]
+fn container_select_actions_get_stable_ids_and_route() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            {
+                "type": "Container",
+                "id": "hero",
+                "items": [ { "type": "TextBlock", "text": "Details" } ],
+                "selectAction": {
+                    "type": "Action.ShowCard",
+                    "card": { "type": "AdaptiveCard", "id": "details", "body": [] }
+                }
+            },
+            {
+                "type": "ColumnSet",
+                "columns": [
+                    { "type": "Column", "items": [], "selectAction": { "type": "Action.Submit" } }
+                ]
+            },
+            { "type": "ActionSet", "actions": [ { "type": "Action.Submit", "title": "Go" } ] }
+        ]
+    });
+    let rendered = handle_invocation(base_invocation(card.clone())).expect("render");
+    let body = &rendered.rendered_card.expect("card")["body"];
+    let hero = body[0]["selectAction"]["id"]
+        .as_str()
+        .expect("generated id")
+        .to_string();
+    assert!(hero.starts_with("action-showcard-"));
+    assert!(
+        body[1]["columns"][0]["selectAction"]["id"]
+            .as_str()
+            .is_some_and(|id| id.starts_with("action-submit-"))
+    );
+    assert!(
+        body[2]["actions"][0]["id"]
+            .as_str()
+            .is_some_and(|id| id.starts_with("action-submit-"))
+    );
+    assert!(
+        body[1].get("id").is_none(),
+        "elements get ids only on request"
+    );
+    let again = handle_invocation(base_invocation(card.clone())).expect("render");
+    assert_eq!(
+        again.rendered_card.expect("card")["body"][0]["selectAction"]["id"],
+        hero.as_str()
+    );
+
+    let mut invocation = base_invocation(card);
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::ShowCard,
+        action_id: hero,
+        verb: None,
+        raw_inputs: json!({}),
+        card_instance_id: "card-hero".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let result = handle_invocation(invocation).expect("interaction");
+    assert!(result.state_updates.iter().any(|op| matches!(
+        op,
+        component_adaptive_card::StateUpdateOp::Set { path, value }
+            if path == "ui.active_show_card.card-hero" && value == &json!("details")
+    )));
+}
+
+#[test]
+fn toggle_visibility_updates_each_target_and_rerenders() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "id": "details", "text": "Details", "isVisible": false },
+            { "type": "TextBlock", "id": "summary", "text": "Summary" },
+            { "type": "TextBlock", "id": "footer", "text": "Footer" }
+        ],
+        "actions": [
+            {
+                "type": "Action.ToggleVisibility",
+                "id": "toggle",
+                "targetElements": ["details", { "elementId": "summary", "isVisible": false }, "footer"]
+            }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.state = json!({ "ui": { "visibility": { "footer": false } } });
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::ToggleVisibility,
+        action_id: "toggle".to_string(),
+        verb: None,
+        raw_inputs: json!({}),
+        card_instance_id: "card-toggle".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+
+    let result = handle_invocation(invocation).expect("toggle");
+    let visibility = &result.card_features.visibility;
+    assert_eq!(visibility.get("details"), Some(&true));
+    assert_eq!(visibility.get("summary"), Some(&false));
+    assert_eq!(visibility.get("footer"), Some(&true));
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["isVisible"], true);
+    assert_eq!(rendered["body"][1]["isVisible"], false);
+}
+
+fn show_card_invocation(mode: ShowCardMode) -> AdaptiveCardInvocation {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "Order" },
+            {
+                "type": "ActionSet",
+                "actions": [
+                    {
+                        "type": "Action.ShowCard",
+                        "id": "details",
+                        "card": {
+                            "type": "AdaptiveCard",
+                            "body": [{ "type": "Input.Text", "id": "note" }],
+                            "actions": [{ "type": "Action.Submit", "id": "save" }]
+                        }
+                    }
+                ]
+            },
+            { "type": "TextBlock", "text": "Footer" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.show_card_mode = mode;
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::ShowCard,
+        action_id: "details".to_string(),
+        verb: None,
+        raw_inputs: json!({}),
+        card_instance_id: "card-order".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    invocation
+}
+
+#[test]
+fn show_card_expands_inline_after_interaction() {
+    let result = handle_invocation(show_card_invocation(ShowCardMode::Inline)).expect("show card");
+    let rendered = result.rendered_card.expect("card");
+    let expanded = &rendered["body"][2];
+    assert_eq!(expanded["id"], "showcard-details");
+    assert_eq!(expanded["items"][0]["id"], "note");
+    assert_eq!(expanded["items"][1]["actions"][0]["id"], "save");
+    assert_eq!(rendered["body"][3]["text"], "Footer");
+    assert!(result.subcard.is_none());
+}
+
+#[test]
+fn show_card_separate_mode_returns_subcard() {
+    let result =
+        handle_invocation(show_card_invocation(ShowCardMode::Separate)).expect("show card");
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["body"].as_array().map(|b| b.len()), Some(3));
+    let subcard = result.subcard.expect("subcard");
+    assert_eq!(subcard["body"][0]["id"], "note");
+}
+
+fn policy_card() -> serde_json::Value {
+    json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [{ "type": "TextBlock", "text": "Partner card" }],
+        "actions": [
+            { "type": "Action.OpenUrl", "id": "docs", "url": "https://docs.example.com/start" },
+            { "type": "Action.OpenUrl", "id": "phish", "url": "https://evil.test/login" },
+            { "type": "Action.OpenUrl", "id": "script", "url": "javascript:alert(1)" },
+            { "type": "Action.Submit", "id": "send" },
+            { "type": "Action.Execute", "id": "run", "verb": "run" }
+        ]
+    })
+}
+
+fn partner_policy() -> SecurityPolicy {
+    SecurityPolicy {
+        allowed_action_types: vec!["Action.OpenUrl".into(), "Action.Submit".into()],
+        allowed_url_schemes: vec!["https".into()],
+        allowed_url_domains: vec!["example.com".into()],
+        max_actions: Some(5),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn security_policy_removes_blocked_actions() {
+    let mut invocation = base_invocation(policy_card());
+    invocation.security = Some(partner_policy());
+
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    let ids: Vec<_> = rendered["actions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["docs", "send"]);
+    let codes: Vec<_> = result
+        .validation_issues
+        .iter()
+        .map(|i| (i.code.as_str(), i.path.as_str()))
+        .collect();
+    assert!(codes.contains(&("policy-blocked-url", "/actions/1")));
+    assert!(codes.contains(&("policy-blocked-url", "/actions/2")));
+    assert!(codes.contains(&("policy-blocked-action", "/actions/4")));
+}
+
+#[test]
+fn security_policy_reads_url_hosts_the_way_browsers_do() {
+    let mut invocation = base_invocation(json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [],
+        "actions": [
+            { "type": "Action.OpenUrl", "id": "backslash", "url": "https://evil.test\\@example.com/" },
+            { "type": "Action.OpenUrl", "id": "slashes", "url": "https:\\\\evil.test/example.com" },
+            { "type": "Action.OpenUrl", "id": "tab", "url": "https://exa\tmple.com/" },
+            { "type": "Action.OpenUrl", "id": "escaped", "url": "https://evil%2Etest/" },
+            { "type": "Action.OpenUrl", "id": "userinfo", "url": "https://evil.test@docs.example.com/" },
+            { "type": "Action.OpenUrl", "id": "path", "url": "https://example.com\\@evil.test" }
+        ]
+    }));
+    invocation.security = Some(partner_policy());
+
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    let ids: Vec<_> = rendered["actions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["userinfo", "path"]);
+    let blocked = result
+        .validation_issues
+        .iter()
+        .filter(|i| i.code == "policy-blocked-url")
+        .count();
+    assert_eq!(blocked, 4);
+    // Kept URLs with a backslash are still flagged: it must be percent-encoded.
+    assert!(result.validation_issues.iter().any(|i| {
+        i.code == "invalid-url" && i.path == "/actions/1/url" && i.message.contains("'\\'")
+    }));
+}
+
+#[test]
+fn security_policy_enforces_max_actions() {
+    let mut invocation = base_invocation(policy_card());
+    invocation.security = Some(SecurityPolicy {
+        max_actions: Some(2),
+        ..Default::default()
+    });
+
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["actions"].as_array().map(|a| a.len()), Some(2));
+    assert_eq!(
+        result
+            .validation_issues
+            .iter()
+            .filter(|i| i.code == "policy-max-actions")
+            .count(),
+        3
+    );
+}
+
+#[test]
+fn security_policy_rejects_blocked_interactions() {
+    let mut invocation = base_invocation(policy_card());
+    invocation.security = Some(partner_policy());
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Execute,
+        action_id: "run".to_string(),
+        verb: Some("run".to_string()),
+        raw_inputs: json!({}),
+        card_instance_id: "card-policy".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+
+    let err = handle_invocation(invocation).expect_err("blocked");
+    assert!(err.to_string().contains("blocked by the security policy"));
+}
+
+#[test]
+fn sanitize_strips_scripts_and_unsafe_urls_from_bound_values() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "@{payload.comment}" },
+            { "type": "Image", "url": "@{payload.avatar}" },
+            {
+                "type": "FactSet",
+                "facts": [{ "title": "Name", "value": "@{payload.name}" }]
+            }
+        ],
+        "actions": [{ "type": "Action.OpenUrl", "url": "@{payload.link}" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.sanitize = true;
+    invocation.payload = json!({
+        "comment": "Hi <script>alert('x')</script><b>there</b>\u{0007} 1 < 2",
+        "avatar": "java\tscript:alert(1)",
+        "name": "Ann<img src=x onerror=alert(1)>\u{0000}",
+        "link": "https://example.com/ok"
+    });
+
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["text"], "Hi there 1 < 2");
+    assert!(rendered["body"][1].get("url").is_none());
+    assert_eq!(rendered["body"][2]["facts"][0]["value"], "Ann");
+    assert_eq!(rendered["actions"][0]["url"], "https://example.com/ok");
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|i| i.code == "unsafe-url" && i.path == "/body/1/url")
+    );
+}
+
+#[test]
+fn accessibility_audit_reports_findings_with_severity() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "Image", "url": "https://example.com/logo.png" },
+            { "type": "Image", "url": "https://example.com/a.png", "altText": "Logo" },
+            { "type": "Input.Text", "id": "name", "placeholder": "Name" },
+            { "type": "Input.Text", "id": "email", "label": "Email" },
+            { "type": "TextBlock", "text": "fine print", "size": "Small", "isSubtle": true }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.audit = vec![AuditCheck::Accessibility];
+
+    let result = handle_invocation(invocation).expect("render");
+    let findings: Vec<_> = result
+        .validation_issues
+        .iter()
+        .filter_map(|i| {
+            i.severity
+                .clone()
+                .map(|s| (i.code.as_str(), i.path.as_str(), s))
+        })
+        .collect();
+    assert_eq!(
+        findings,
+        vec![
+            ("a11y-missing-speak", "/speak", IssueSeverity::Info),
+            ("a11y-missing-alt-text", "/body/0", IssueSeverity::Error),
+            ("a11y-missing-label", "/body/2", IssueSeverity::Error),
+            ("a11y-low-contrast", "/body/4", IssueSeverity::Warning),
+        ]
+    );
+}
+
+fn budget_card() -> serde_json::Value {
+    json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            {
+                "type": "Container",
+                "items": [{
+                    "type": "Container",
+                    "items": [{ "type": "TextBlock", "text": "deep" }]
+                }]
+            },
+            { "type": "TextBlock", "text": "one" },
+            { "type": "TextBlock", "text": "two" },
+            { "type": "TextBlock", "text": "three" }
+        ],
+        "actions": [
+            { "type": "Action.Submit", "id": "a" },
+            { "type": "Action.Submit", "id": "b" }
+        ]
+    })
+}
+
+#[test]
+fn budget_violations_are_reported() {
+    let mut invocation = base_invocation(budget_card());
+    invocation.budget = Some(CardBudget {
+        max_bytes: Some(64),
+        max_elements: Some(4),
+        max_depth: Some(2),
+        max_actions: Some(1),
+        truncation: Vec::new(),
+    });
+
+    let result = handle_invocation(invocation).expect("render");
+    let codes: Vec<_> = result
+        .validation_issues
+        .iter()
+        .map(|i| i.code.as_str())
+        .collect();
+    assert_eq!(
+        codes,
+        vec![
+            "budget-bytes",
+            "budget-elements",
+            "budget-depth",
+            "budget-actions"
+        ]
+    );
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["body"].as_array().map(|b| b.len()), Some(4));
+}
+
+#[test]
+fn budget_truncation_collapses_and_drops_trailing_elements() {
+    let mut invocation = base_invocation(budget_card());
+    invocation.budget = Some(CardBudget {
+        max_elements: Some(4),
+        max_depth: Some(2),
+        max_actions: Some(1),
+        truncation: vec![
+            TruncationStrategy::CollapseContainers,
+            TruncationStrategy::DropTrailing,
+        ],
+        ..Default::default()
+    });
+
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["type"], "Container");
+    assert_eq!(rendered["body"][0]["items"][0]["text"], "deep");
+    assert_eq!(rendered["body"][1]["text"], "one");
+    assert_eq!(rendered["body"][2]["text"], "two");
+    assert!(rendered["body"].get(3).is_none());
+    assert_eq!(rendered["actions"].as_array().map(|a| a.len()), Some(1));
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .all(|i| i.code == "budget-truncated")
+    );
+}
+
+#[test]
+fn localization_resolves_markers_and_function_with_fallbacks() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "@loc(title)" },
+            { "type": "TextBlock", "text": "${localize('greeting')}" },
+            { "type": "TextBlock", "text": "@loc(unknown)" }
+        ],
+        "actions": [{ "type": "Action.Submit", "title": "@loc(submit)" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.locale = Some("fr-CA".into());
+    invocation.strings_asset = Some("tests/assets/strings/greeting.json".into());
+    invocation.string_catalogs = serde_json::from_value(json!({
+        "fr-CA": { "greeting": "Allô" },
+        "en": { "greeting": "Hello" }
+    }))
+    .unwrap();
+
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["text"], "Bienvenue");
+    assert_eq!(rendered["body"][1]["text"], "Allô");
+    assert_eq!(rendered["body"][2]["text"], "unknown");
+    assert_eq!(rendered["actions"][0]["title"], "Envoyer");
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|i| i.code == "missing-translation" && i.path == "/body/2/text")
+    );
+}
+
+#[test]
+fn localization_falls_back_to_english() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [{ "type": "TextBlock", "text": "@loc(title) @{payload.name}" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.locale = Some("de-AT".into());
+    invocation.strings_asset = Some("tests/assets/strings/greeting.json".into());
+    invocation.payload = json!({ "name": "Sam" });
+
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["text"], "Welcome Sam");
+}
+
+#[test]
+fn locale_aware_formatting_functions() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "${formatNumber(payload.count)}" },
+            { "type": "TextBlock", "text": "${formatCurrency(payload.price, 'EUR')}" },
+            { "type": "TextBlock", "text": "${formatDate(payload.due)}" },
+            { "type": "TextBlock", "text": "${formatDate(payload.due, 'long')}" },
+            { "type": "TextBlock", "text": "${formatNumber(payload.ratio, 1)}" }
+        ]
+    });
+    let payload =
+        json!({ "count": 1234567.891, "price": 1234.5, "due": "2026-03-09", "ratio": -0.04 });
+    let render = |locale: &str| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.locale = Some(locale.into());
+        invocation.payload = payload.clone();
+        let rendered = handle_invocation(invocation)
+            .expect("render")
+            .rendered_card
+            .expect("card");
+        (0..5)
+            .map(|idx| rendered["body"][idx]["text"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        render("en-US"),
+        vec![
+            "1,234,567.891",
+            "€1,234.50",
+            "3/9/2026",
+            "March 9, 2026",
+            "0.0"
+        ]
+    );
+    assert_eq!(
+        render("de-DE"),
+        vec![
+            "1.234.567,891",
+            "1.234,50\u{A0}€",
+            "09.03.2026",
+            "9. März 2026",
+            "0,0"
+        ]
+    );
+    assert_eq!(
+        render("fr-FR")[..3],
+        [
+            "1\u{202F}234\u{202F}567,891",
+            "1\u{202F}234,50\u{A0}€",
+            "09/03/2026"
+        ]
+    );
+    assert_eq!(render("fr-CA")[2], "2026-03-09");
+    assert_eq!(render("pt-BR")[3], "9 de março de 2026");
+    assert_eq!(render("it-IT")[3], "9 marzo 2026");
+    assert_eq!(render("nl-NL")[3], "9 maart 2026");
+
+    let date = |due: &str| {
+        let mut invocation = base_invocation(json!({
+            "type": "AdaptiveCard",
+            "version": "1.6",
+            "body": [{ "type": "TextBlock", "text": "Due ${formatDate(payload.due)}" }]
+        }));
+        invocation.payload = json!({ "due": due });
+        handle_invocation(invocation)
+            .map(|result| result.rendered_card.expect("card")["body"][0]["text"].clone())
+    };
+    assert_eq!(date("2024-02-29").expect("leap day"), "Due 2/29/2024");
+    assert!(
+        date("2024-02-31").is_err(),
+        "impossible dates are not dates"
+    );
+    assert!(date("2023-02-29").is_err());
+}
+
+#[test]
+fn theme_tokens_resolve_with_tenant_overrides() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            {
+                "type": "Container",
+                "style": "@theme.containerStyles.brand",
+                "spacing": "@theme.spacing.loose",
+                "items": [
+                    { "type": "TextBlock", "text": "Hi", "color": "@theme.colors.accent", "size": "@theme.fontSizes.title" },
+                    { "type": "Image", "url": "@theme.logo", "altText": "logo" },
+                    { "type": "TextBlock", "text": "x", "color": "@theme.colors.missing" }
+                ]
+            }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.theme = Some(json!({ "logo": "https://cdn.example.com/default.png" }));
+    invocation.tenant_themes = serde_json::from_value(json!({
+        "acme": {
+            "colors": { "accent": "Good" },
+            "logo": "https://cdn.example.com/acme.png"
+        }
+    }))
+    .unwrap();
+    invocation.tenant = Some("acme".into());
+
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    let container = &rendered["body"][0];
+    assert_eq!(container["style"], "accent");
+    assert_eq!(container["spacing"], "Large");
+    assert_eq!(container["items"][0]["color"], "Good");
+    assert_eq!(container["items"][0]["size"], "Large");
+    assert_eq!(
+        container["items"][1]["url"],
+        "https://cdn.example.com/acme.png"
+    );
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|i| i.code == "unknown-theme-token" && i.path == "/body/0/items/2/color")
+    );
+}
+
+#[test]
+fn color_scheme_both_renders_light_and_dark_variants() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "Image", "url": "asset:logo.png", "altText": "logo" },
+            { "type": "TextBlock", "text": "Hi", "color": "@theme.colors.text" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.color_scheme = Some(ColorScheme::Both);
+    invocation.theme = Some(json!({
+        "schemes": { "dark": { "colors": { "text": "Light" } } }
+    }));
+    invocation.card_spec.asset_registry = Some(
+        [
+            ("logo.png", "https://cdn.example.com/logo.png"),
+            ("logo.dark.png", "https://cdn.example.com/logo-dark.png"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect(),
+    );
+
+    let result = handle_invocation(invocation).expect("render");
+    let light = &result.variants["light"];
+    let dark = &result.variants["dark"];
+    assert_eq!(result.rendered_card.as_ref(), Some(light));
+    assert_eq!(light["body"][0]["url"], "https://cdn.example.com/logo.png");
+    assert_eq!(light["body"][1]["color"], "Default");
+    assert_eq!(
+        dark["body"][0]["url"],
+        "https://cdn.example.com/logo-dark.png"
+    );
+    assert_eq!(dark["body"][1]["color"], "Light");
+}
+
+#[test]
+fn telemetry_events_follow_requested_verbosity() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "@{payload.name}" },
+            { "type": "Input.Text" }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "save", "verb": "save" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "name": "Ada" });
+
+    let result = handle_invocation(invocation.clone()).expect("render");
+    let names: Vec<_> = result
+        .telemetry_events
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["adaptive_card.render"]);
+    let render = &result.telemetry_events[0].properties;
+    assert_eq!(render["expression_evaluations"], 1);
+    assert_eq!(render["validation_errors"], 1);
+    assert!(render["duration_ms"].is_number());
+
+    invocation.telemetry = TelemetryLevel::Verbose;
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "save".to_string(),
+        verb: None,
+        raw_inputs: json!({}),
+        card_instance_id: "card-telemetry".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let result = handle_invocation(invocation.clone()).expect("interaction");
+    let names: Vec<_> = result
+        .telemetry_events
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "adaptive_card.render",
+            "adaptive_card.interaction",
+            "adaptive_card.bindings",
+            "adaptive_card.validation",
+            "adaptive_card.cache"
+        ]
+    );
+    assert_eq!(result.telemetry_events[1].properties["verb"], "save");
+    assert_eq!(
+        result.telemetry_events[3].properties["by_code"]["missing-id"],
+        1
+    );
+
+    invocation.telemetry = TelemetryLevel::Off;
+    let result = handle_invocation(invocation).expect("interaction");
+    assert!(result.telemetry_events.is_empty());
+}
+
+#[test]
+fn failures_are_returned_as_typed_responses() {
+    let input = json!({
+        "card_source": "inline",
+        "card_spec": {
+            "inline_json": {
+                "type": "AdaptiveCard",
+                "version": "1.6",
+                "body": [{ "type": "TextBlock", "text": "@{payload.missing}" }]
+            }
+        }
+    })
+    .to_string();
+
+    match component_adaptive_card::process_message("card", &input) {
+        AdaptiveCardResponse::Failure { error } => {
+            assert_eq!(error.code, FailureCode::BindingEvalError);
+            assert_eq!(error.path.as_deref(), Some("/card_spec/inline_json"));
+            assert!(!error.retryable);
+        }
+        other => panic!("expected failure, got {other:?}"),
+    }
+
+    let output = component_adaptive_card::handle_message("card", "not json");
+    let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(parsed["error"]["code"], "AC_SCHEMA_INVALID");
+    assert_eq!(parsed["error"]["retryable"], false);
+    let typed: AdaptiveCardResponse = serde_json::from_str(&output).unwrap();
+    assert!(matches!(typed, AdaptiveCardResponse::Failure { .. }));
+}
+
+#[test]
+fn simulate_mode_does_not_persist_state() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [ { "type": "Input.Text", "id": "name" } ],
+        "actions": [ { "type": "Action.Submit", "id": "submit", "title": "Send" } ]
+    });
+    let simulate = |inputs: serde_json::Value| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.mode = InvocationMode::Simulate;
+        invocation.state = serde_json::Value::Null;
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "submit".to_string(),
+            verb: None,
+            raw_inputs: inputs,
+            card_instance_id: "card-simulate".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("simulate")
+    };
+
+    let first = simulate(json!({ "name": "Ada" }));
+    assert!(first.rendered_card.is_some());
+    assert_eq!(first.state_updates.len(), 1);
+    assert_eq!(
+        first.simulated_state,
+        Some(json!({ "form_data": { "name": "Ada" } }))
+    );
+
+    // Nothing was written, so a second dry run starts from empty state again.
+    let second = simulate(json!({ "email": "ada@example.com" }));
+    assert_eq!(
+        second.simulated_state,
+        Some(json!({ "form_data": { "email": "ada@example.com" } }))
+    );
+}
+
+#[test]
+fn duplicate_idempotency_keys_return_cached_result() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [ { "type": "Input.Text", "id": "name" } ],
+        "actions": [ { "type": "Action.Submit", "id": "submit", "title": "Send" } ]
+    });
+    let submit = |key: &str, inputs: serde_json::Value| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.state = serde_json::Value::Null;
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "submit".to_string(),
+            verb: None,
+            raw_inputs: inputs,
+            card_instance_id: "card-idempotent".to_string(),
+            metadata: json!({}),
+            idempotency_key: Some(key.to_string()),
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("interaction")
+    };
+
+    let first = submit("delivery-1", json!({ "name": "Ada" }));
+    let redelivered = submit("delivery-1", json!({ "name": "Bob" }));
+    assert_eq!(redelivered, first);
+    assert_eq!(redelivered.event.expect("event").inputs["name"], "Ada");
+
+    let next = submit("delivery-2", json!({ "name": "Bob" }));
+    assert_eq!(next.event.expect("event").inputs["name"], "Bob");
+}
+
+#[test]
+fn input_changed_drafts_are_restored_and_cleared_on_submit() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "Input.Text", "id": "story" },
+            { "type": "Input.Rating", "id": "stars", "max": 5 }
+        ],
+        "actions": [ { "type": "Action.Submit", "id": "submit", "title": "Send" } ]
+    });
+    let interact = |kind: CardInteractionType, action_id: &str, inputs: serde_json::Value| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.state = serde_json::Value::Null;
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: kind,
+            action_id: action_id.to_string(),
+            verb: None,
+            raw_inputs: inputs,
+            card_instance_id: "card-drafts".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("interaction")
+    };
+
+    interact(
+        CardInteractionType::InputChanged,
+        "story",
+        json!("Once upon"),
+    );
+    let changed = interact(
+        CardInteractionType::InputChanged,
+        "stars",
+        json!({ "stars": "3.6" }),
+    );
+    assert!(changed.event.is_none());
+    let rendered = changed.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["value"], "Once upon");
+    assert_eq!(rendered["body"][1]["value"], json!(4));
+
+    let submitted = interact(
+        CardInteractionType::Submit,
+        "submit",
+        json!({ "story": "Done" }),
+    );
+    assert!(
+        submitted
+            .state_updates
+            .iter()
+            .any(|op| matches!(op, StateUpdateOp::Delete { path } if path == "drafts.card-drafts"))
+    );
+    assert!(
+        submitted.rendered_card.expect("card")["body"][0]
+            .get("value")
+            .is_none()
+    );
+}
+
+#[test]
+fn submitted_inputs_are_coerced_to_declared_types() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "Input.Number", "id": "qty" },
+            { "type": "Input.Toggle", "id": "agree", "title": "Agree", "valueOn": "yes", "valueOff": "no" },
+            { "type": "Input.Date", "id": "due" },
+            { "type": "Input.Time", "id": "at" },
+            { "type": "Input.ChoiceSet", "id": "tags", "isMultiSelect": true, "choices": [
+                { "title": "A", "value": "a" }, { "title": "B", "value": "b" }
+            ] },
+            { "type": "Input.Number", "id": "budget" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "submit".to_string(),
+        verb: None,
+        raw_inputs: json!({
+            "qty": "2.5",
+            "agree": "yes",
+            "due": "2026-03-04T10:00:00Z",
+            "at": "2:05 PM",
+            "tags": "a,b",
+            "budget": "lots"
+        }),
+        card_instance_id: "card-typed".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+
+    let result = handle_invocation(invocation.clone()).expect("interaction");
+    let inputs = result.event.expect("event").inputs;
+    assert_eq!(inputs["qty"], json!(2.5));
+    assert_eq!(inputs["agree"], json!(true));
+    assert_eq!(inputs["due"], "2026-03-04");
+    assert_eq!(inputs["at"], "14:05");
+    assert_eq!(inputs["tags"], json!(["a", "b"]));
+    assert_eq!(inputs["budget"], "lots");
+    let issue = result
+        .validation_issues
+        .iter()
+        .find(|i| i.code == "input-coercion-failed")
+        .expect("coercion issue");
+    assert_eq!(issue.path, "/inputs/budget");
+
+    invocation.validation_mode = ValidationMode::Error;
+    assert!(handle_invocation(invocation).is_err());
+}
+
+#[test]
+fn choice_labels_are_added_to_submit_events() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "Input.ChoiceSet", "id": "color", "choices": [
+                { "title": "Crimson red", "value": "red" }, { "title": "Ocean blue", "value": "blue" }
+            ] },
+            { "type": "Input.ChoiceSet", "id": "sizes", "isMultiSelect": true, "choices": [
+                { "title": "Small", "value": "s" }, { "title": "Large", "value": "l" }
+            ] },
+            { "type": "Input.Text", "id": "note" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.choice_labels = true;
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "submit".to_string(),
+        verb: None,
+        raw_inputs: json!({ "color": "blue", "sizes": "s,l", "note": "hi" }),
+        card_instance_id: "card-labels".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+
+    let event = handle_invocation(invocation)
+        .expect("interaction")
+        .event
+        .expect("event");
+    assert_eq!(event.inputs["color"], "blue");
+    assert_eq!(
+        event.inputs_display,
+        Some(json!({ "color": "Ocean blue", "sizes": ["Small", "Large"], "note": "hi" }))
+    );
+}
+
+#[test]
+fn routing_table_derives_route_node_and_op() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [ { "type": "Input.Number", "id": "amount" } ],
+        "actions": [
+            { "type": "Action.Execute", "id": "approve", "verb": "approve.invoice" },
+            { "type": "Action.Submit", "id": "pay" }
+        ]
+    });
+    let routed = |action_id: &str, inputs: serde_json::Value| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.routing = vec![
+            RouteRule {
+                verb: Some("approve.*".to_string()),
+                route: Some("approvals".to_string()),
+                node: Some("approver".to_string()),
+                op: Some("approve".to_string()),
+                ..Default::default()
+            },
+            RouteRule {
+                action_id: Some("pay".to_string()),
+                when: Some("inputs.amount == 0".to_string()),
+                route: Some("free-checkout".to_string()),
+                ..Default::default()
+            },
+            RouteRule {
+                action_id: Some("*".to_string()),
+                route: Some("checkout".to_string()),
+                ..Default::default()
+            },
+        ];
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: if action_id == "approve" {
+                CardInteractionType::Execute
+            } else {
+                CardInteractionType::Submit
+            },
+            action_id: action_id.to_string(),
+            verb: None,
+            raw_inputs: inputs,
+            card_instance_id: "card-routing".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("interaction")
+    };
+
+    let approved = routed("approve", json!({}));
+    let event = approved.event.expect("event");
+    assert_eq!(event.route.as_deref(), Some("approvals"));
+    assert_eq!(event.node.as_deref(), Some("approver"));
+    assert_eq!(event.op.as_deref(), Some("approve"));
+    assert!(
+        approved
+            .session_updates
+            .contains(&SessionUpdateOp::SetRoute {
+                route: "approvals".to_string()
+            })
+    );
+
+    let free = routed("pay", json!({ "amount": "0" }))
+        .event
+        .expect("event");
+    assert_eq!(free.route.as_deref(), Some("free-checkout"));
+    let paid = routed("pay", json!({ "amount": "12" }))
+        .event
+        .expect("event");
+    assert_eq!(paid.route.as_deref(), Some("checkout"));
+    assert_eq!(paid.node, None);
+}
+
+#[test]
+fn action_data_session_mutations_become_session_updates() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [],
+        "actions": [ {
+            "type": "Action.Submit",
+            "id": "french",
+            "data": {
+                "__session": { "language": "fr", "beta": null },
+                "__sessionMerge": { "prefs": { "compact": true } }
+            }
+        } ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "french".to_string(),
+        verb: None,
+        raw_inputs: json!({ "__session": { "admin": true } }),
+        card_instance_id: "card-session".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+
+    let result = handle_invocation(invocation).expect("interaction");
+    assert_eq!(
+        result.session_updates,
+        vec![
+            SessionUpdateOp::UnsetVariable {
+                name: "beta".to_string()
+            },
+            SessionUpdateOp::SetVariable {
+                name: "language".to_string(),
+                value: json!("fr")
+            },
+            SessionUpdateOp::MergeVariables {
+                values: json!({ "prefs": { "compact": true } })
+            },
+        ]
+    );
+    assert_eq!(result.event.expect("event").inputs, json!({}));
+}
+
+#[test]
+fn lifecycle_ops_create_and_retire_card_instances() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [ { "type": "TextBlock", "text": "Step ${state.step}" } ]
+    });
+    let input = json!({
+        "card_spec": { "inline_json": card },
+        "state": { "step": 1 }
+    });
+    let created = match component_adaptive_card::process_message("init-card", &input.to_string()) {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("init failed: {error:?}"),
+    };
+    let instance_id = created.card_instance_id.clone().expect("instance id");
+    assert!(instance_id.starts_with("card-"));
+    assert_eq!(
+        created.rendered_card.expect("card")["body"][0]["text"],
+        "Step 1"
+    );
+
+    let mut render = base_invocation(card);
+    render.state = serde_json::Value::Null;
+    render.card_instance_id = Some(instance_id.clone());
+    let resumed = handle_invocation(render.clone()).expect("render");
+    assert_eq!(
+        resumed.rendered_card.expect("card")["body"][0]["text"],
+        "Step 1"
+    );
+
+    let expire = json!({
+        "card_spec": { "inline_json": {} },
+        "card_instance_id": instance_id
+    });
+    let expired = component_adaptive_card::handle_message("expire-card", &expire.to_string());
+    let expired: serde_json::Value = serde_json::from_str(&expired).expect("json");
+    assert_eq!(
+        expired["renderedCard"]["body"][0]["text"],
+        "This form has expired."
+    );
+    assert!(
+        handle_invocation(render).is_err(),
+        "state should be cleared"
+    );
+
+    let missing = component_adaptive_card::process_message(
+        "dismiss-card",
+        &json!({ "card_spec": { "inline_json": {} } }).to_string(),
+    );
+    assert!(matches!(missing, AdaptiveCardResponse::Failure { .. }));
+}
+
+#[test]
+fn submit_can_return_read_only_receipt() {
+    let form = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "Order" },
+            { "type": "Input.Text", "id": "name", "label": "Name" },
+            { "type": "Input.ChoiceSet", "id": "size", "label": "Size", "choices": [
+                { "title": "Large", "value": "l" }
+            ] },
+            { "type": "ActionSet", "actions": [ { "type": "Action.Submit", "id": "alt" } ] }
+        ],
+        "actions": [ { "type": "Action.Submit", "id": "submit" } ]
+    });
+    let submit = |card: serde_json::Value| {
+        let mut invocation = base_invocation(card);
+        invocation.render_receipt = true;
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "submit".to_string(),
+            verb: None,
+            raw_inputs: json!({ "name": "Ada", "size": "l" }),
+            card_instance_id: "card-receipt".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation)
+            .expect("interaction")
+            .rendered_card
+            .expect("card")
+    };
+
+    let derived = submit(form.clone());
+    assert!(derived.get("actions").is_none());
+    assert_eq!(
+        derived["body"].clone(),
+        json!([
+            { "type": "TextBlock", "text": "Order" },
+            { "type": "FactSet", "facts": [
+                { "title": "Name", "value": "Ada" },
+                { "title": "Size", "value": "Large" }
+            ] }
+        ])
+    );
+
+    let mut templated = form;
+    templated["post_submit_view"] = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [ { "type": "TextBlock", "text": "Thanks, ${state.form_data.name}!" } ]
+    });
+    let custom = submit(templated);
+    assert_eq!(custom["body"][0]["text"], "Thanks, Ada!");
+}
+
+#[test]
+fn clicked_actions_stay_disabled_across_renders() {
+    let card = |version: &str| {
+        json!({
+            "type": "AdaptiveCard",
+            "version": version,
+            "body": [],
+            "actions": [
+                { "type": "Action.Submit", "id": "order", "title": "Order" },
+                { "type": "Action.Submit", "id": "cancel", "title": "Cancel" }
+            ]
+        })
+    };
+    let click = |card: serde_json::Value, instance: &str| {
+        let mut invocation = base_invocation(card);
+        invocation.state = serde_json::Value::Null;
+        invocation.disable_after_click = Some(DisableActions::Clicked);
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "order".to_string(),
+            verb: None,
+            raw_inputs: json!({}),
+            card_instance_id: instance.to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation)
+    };
+
+    let first = click(card("1.6"), "card-disable-16").expect("first click");
+    let actions = &first.rendered_card.expect("card")["actions"];
+    assert_eq!(actions[0]["isEnabled"], false);
+    assert!(actions[1].get("isEnabled").is_none());
+    let second = click(card("1.6"), "card-disable-16");
+    assert!(matches!(
+        second,
+        Err(component_adaptive_card::ComponentError::InteractionInvalid(
+            _
+        ))
+    ));
+
+    let legacy = click(card("1.4"), "card-disable-14").expect("legacy click");
+    assert_eq!(
+        legacy.rendered_card.expect("card")["actions"],
+        json!([{ "type": "Action.Submit", "id": "cancel", "title": "Cancel" }])
+    );
+}
+
+#[test]
+fn hydration_rebinds_only_changed_paths() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "CPU ${payload.cpu}%" },
+            { "type": "TextBlock", "text": "Mem @{payload.mem}" },
+            { "type": "TextBlock", "text": "${payload.cpu == 99 ? 'Hot' : 'OK'}" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "cpu": 10, "mem": "1 GB" });
+    let previous = handle_invocation(invocation.clone())
+        .expect("full render")
+        .rendered_card
+        .expect("card");
+
+    invocation.payload = json!({ "cpu": 99, "mem": "2 GB" });
+    invocation.hydrate = Some(HydrationRequest {
+        previous_card: previous,
+        changed_paths: vec!["payload.cpu".to_string()],
+    });
+    let hydrated = handle_invocation(invocation.clone()).expect("hydrate");
+    let body = &hydrated.rendered_card.expect("card")["body"];
+    assert_eq!(body[0]["text"], "CPU 99%");
+    assert_eq!(
+        body[1]["text"], "Mem 1 GB",
+        "unchanged paths are not re-bound"
+    );
+    assert_eq!(body[2]["text"], "Hot");
+
+    invocation.hydrate = Some(HydrationRequest {
+        previous_card: json!({ "type": "AdaptiveCard" }),
+        changed_paths: vec!["payload.cpu".to_string()],
+    });
+    let fallback = handle_invocation(invocation).expect("full render fallback");
+    assert_eq!(
+        fallback.rendered_card.expect("card")["body"][1]["text"],
+        "Mem 2 GB"
+    );
+}
+
+#[test]
+fn hydration_keeps_patches_aligned_and_secured_after_policy_drops() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [{ "type": "TextBlock", "text": "${payload.note}" }],
+        "actions": [
+            { "type": "Action.OpenUrl", "title": "Elsewhere", "url": "https://evil.test/x" },
+            { "type": "Action.Submit", "title": "${payload.label}" },
+            { "type": "Action.Submit", "title": "Static" },
+            { "type": "Action.OpenUrl", "title": "Docs", "url": "${payload.link}" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.security = Some(partner_policy());
+    invocation.sanitize = true;
+    invocation.payload = json!({
+        "note": "Hi",
+        "label": "Approve",
+        "link": "https://example.com/docs"
+    });
+    let previous = handle_invocation(invocation.clone())
+        .expect("full render")
+        .rendered_card
+        .expect("card");
+    assert_eq!(previous["actions"].as_array().map(Vec::len), Some(3));
+
+    invocation.payload["label"] = json!("Approve all");
+    invocation.hydrate = Some(HydrationRequest {
+        previous_card: previous.clone(),
+        changed_paths: vec!["payload.label".to_string()],
+    });
+    let hydrated = handle_invocation(invocation.clone()).expect("hydrate");
+    let actions = &hydrated.rendered_card.expect("card")["actions"];
+    assert_eq!(actions[0]["title"], "Approve all");
+    assert_eq!(actions[1]["title"], "Static");
+
+    invocation.payload["note"] = json!("<script>alert(1)</script>Hello");
+    invocation.payload["link"] = json!("https://evil.test/docs");
+    invocation.hydrate = Some(HydrationRequest {
+        previous_card: previous,
+        changed_paths: vec!["payload.note".to_string(), "payload.link".to_string()],
+    });
+    let hydrated = handle_invocation(invocation).expect("hydrate");
+    let card = hydrated.rendered_card.expect("card");
+    assert!(
+        !card["body"][0]["text"]
+            .as_str()
+            .expect("text")
+            .contains("<script")
+    );
+    assert!(
+        card["actions"]
+            .as_array()
+            .expect("actions")
+            .iter()
+            .all(|action| action["url"] != "https://evil.test/docs")
+    );
+    assert!(
+        hydrated
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "policy-blocked-url")
+    );
+}
+
+#[test]
+fn hydration_reads_the_parsed_template_and_reruns_post_bind_passes() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "${payload.note}" },
+            { "type": "TextBlock", "text": "${payload.other}" },
+            {
+                "type": "Chart.Pie",
+                "data": [{ "legend": "Open", "value": "${payload.open}" }]
+            }
+        ],
+        "actions": [
+            { "type": "Action.Submit", "title": "More", "data": { "pageSize": 5, "sortable": true } }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "note": "Hi", "other": "a", "open": "3" });
+    let previous = handle_invocation(invocation.clone())
+        .expect("full render")
+        .rendered_card
+        .expect("card");
+
+    invocation.payload = json!({ "note": "Bye", "other": "b", "open": "3" });
+    invocation.hydrate = Some(HydrationRequest {
+        previous_card: previous.clone(),
+        changed_paths: vec!["payload.note".to_string()],
+    });
+    let hydrated = handle_invocation(invocation.clone()).expect("hydrate");
+    let body = &hydrated.rendered_card.expect("card")["body"];
+    assert_eq!(body[0]["text"], "Bye");
+    assert_eq!(
+        body[1]["text"], "a",
+        "action data keys do not force a full render"
+    );
+
+    invocation.payload["open"] = json!("7");
+    invocation.hydrate = Some(HydrationRequest {
+        previous_card: previous,
+        changed_paths: vec!["payload.open".to_string()],
+    });
+    let rendered = handle_invocation(invocation.clone())
+        .expect("hydrate")
+        .rendered_card
+        .expect("card");
+    invocation.hydrate = None;
+    let full = handle_invocation(invocation)
+        .expect("full render")
+        .rendered_card
+        .expect("card");
+    assert_eq!(rendered["body"][2]["data"][0]["value"], 7);
+    assert_eq!(rendered["body"][2], full["body"][2]);
+}
+
+#[test]
+fn analyze_template_reports_paths_per_element() {
+    let input = json!({
+        "card_spec": { "inline_json": {
+            "type": "AdaptiveCard",
+            "version": "1.6",
+            "body": [
+                { "type": "TextBlock", "id": "greeting", "text": "Hi @{payload.user.name}" },
+                { "type": "Container", "items": [
+                    { "type": "TextBlock", "text": "${state.count == 0 ? localize('empty') : session.label}" }
+                ] },
+                { "type": "TextBlock", "text": "{{#if payload.vip}}VIP{{/if}}" }
+            ],
+            "actions": [ { "type": "Action.Submit", "title": "Go", "data": { "ref": "${payload.ref}" } } ]
+        } }
+    });
+    let result =
+        match component_adaptive_card::process_message("analyze-template", &input.to_string()) {
+            AdaptiveCardResponse::Success(result) => result,
+            AdaptiveCardResponse::Failure { error } => panic!("analysis failed: {error:?}"),
+        };
+    assert!(result.rendered_card.is_none());
+    let analysis = result.template_analysis.expect("analysis");
+    assert_eq!(
+        analysis.paths,
+        vec![
+            "payload.ref",
+            "payload.user.name",
+            "payload.vip",
+            "session.label",
+            "state.count"
+        ]
+    );
+    let greeting = analysis
+        .elements
+        .iter()
+        .find(|e| e.id.as_deref() == Some("greeting"))
+        .expect("greeting element");
+    assert_eq!(greeting.pointer, "/body/0");
+    assert_eq!(greeting.paths, vec!["payload.user.name"]);
+    let nested = analysis
+        .elements
+        .iter()
+        .find(|e| e.pointer == "/body/1/items/0")
+        .expect("nested element");
+    assert_eq!(nested.paths, vec!["session.label", "state.count"]);
+    let action = analysis
+        .elements
+        .iter()
+        .find(|e| e.pointer == "/actions/0")
+        .expect("action");
+    assert_eq!(action.element_type.as_deref(), Some("Action.Submit"));
+}
+
+#[test]
+fn generate_sample_data_fills_every_referenced_path() {
+    let template = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "Hi @{payload.user.name} (${customer.email})" },
+            { "type": "TextBlock", "text": "${formatCurrency(payload.order.total, 'EUR')}" },
+            { "type": "TextBlock", "text": "${formatDate(payload.order.due, 'short')}" },
+            { "type": "TextBlock", "text": "{{#each payload.items}}{{this}} {{/each}}" },
+            { "type": "TextBlock", "text": "Step ${state.step} of ${params.steps}" },
+            { "type": "Image", "url": "${payload.order.lines.0.thumbnail}" },
+            { "type": "TextBlock", "text": "${payload.order.lines.900000000.sku}" },
+            { "type": "TextBlock", "text": "${inputs.comment}" }
+        ]
+    });
+    let input = json!({ "card_spec": { "inline_json": template.clone() } });
+    let result = match component_adaptive_card::process_message(
+        "generate-sample-data",
+        &input.to_string(),
+    ) {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("generation failed: {error:?}"),
+    };
+    assert!(result.rendered_card.is_none());
+    let data = result.sample_data.expect("sample data");
+    assert_eq!(data["payload"]["user"]["name"], "Jane Doe");
+    assert_eq!(data["payload"]["customer"]["email"], "jane.doe@example.com");
+    assert_eq!(data["payload"]["order"]["total"], json!(42.5));
+    assert_eq!(data["payload"]["order"]["due"], "2024-01-15T09:30:00Z");
+    assert!(data["payload"]["items"].is_array());
+    assert_eq!(
+        data["payload"]["order"]["lines"][0]["thumbnail"],
+        "https://example.com"
+    );
+    // Indexes past the repeater limit are not padded out.
+    assert_eq!(
+        data["payload"]["order"]["lines"].as_array().map(Vec::len),
+        Some(1)
+    );
+    assert!(data["state"]["step"].is_string());
+    assert!(data["params"]["steps"].is_string());
+    assert!(data.get("inputs").is_none() && data.get("session").is_none());
+
+    // The generated data renders the template (minus the submission-only `inputs` binding
+    // and the unpadded index).
+    let mut template = template;
+    template["body"].as_array_mut().expect("body").truncate(6);
+    let mut invocation = base_invocation(template);
+    invocation.payload = data["payload"].clone();
+    invocation.state = data["state"].clone();
+    invocation.card_spec.template_params = Some(data["params"].clone());
+    let card = handle_invocation(invocation)
+        .expect("render")
+        .rendered_card
+        .expect("card");
+    assert_eq!(
+        card["body"][0]["text"],
+        "Hi Jane Doe (jane.doe@example.com)"
+    );
+    assert_eq!(card["body"][4]["text"], "Step Sample step of Sample steps");
+}
+
+#[test]
+fn preview_draws_the_rendered_card() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "Order <42> for ${payload.name}", "size": "Large", "weight": "Bolder" },
+            { "type": "FactSet", "facts": [ { "title": "Total", "value": "12 EUR" } ] },
+            { "type": "Input.Text", "id": "note", "label": "Note", "placeholder": "Add a note" }
+        ],
+        "actions": [ { "type": "Action.Submit", "id": "approve", "title": "Approve", "style": "positive" } ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "name": "Ada" });
+    invocation.preview = Some(PreviewFormat::Svg);
+    let result = handle_invocation(invocation.clone()).expect("render");
+    if !cfg!(feature = "preview") {
+        assert!(result.preview.is_none());
+        assert!(
+            result
+                .validation_issues
+                .iter()
+                .any(|issue| issue.code == "preview-unavailable")
+        );
+        return;
+    }
+    let preview = result.preview.expect("svg preview");
+    assert_eq!(preview.media_type, "image/svg+xml");
+    assert_eq!(preview.width, 400);
+    assert!(preview.height > 100);
+    assert!(preview.data.starts_with("<svg"));
+    assert!(preview.data.contains("Order &lt;42&gt; for Ada"));
+    assert!(preview.data.contains(">Approve</text>"));
+    assert!(preview.data.contains(">Add a note</text>"));
+
+    invocation.color_scheme = Some(ColorScheme::Dark);
+    invocation.preview = Some(PreviewFormat::Png);
+    let result = handle_invocation(invocation).expect("render");
+    if cfg!(feature = "preview-png") {
+        let png = result.preview.expect("png preview");
+        assert_eq!(png.media_type, "image/png");
+        assert!(png.data.starts_with("iVBORw0KGgo"));
+    } else {
+        assert!(
+            result
+                .validation_issues
+                .iter()
+                .any(|issue| issue.code == "preview-unavailable")
+        );
+    }
+}
+
+#[test]
+fn fact_sets_image_sets_and_icons_are_normalized() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.4",
+        "body": [
+            { "type": "FactSet", "facts": [
+                { "title": "Amount", "value": "${payload.amount}" },
+                { "title": "Paid", "value": "${payload.paid}" }
+            ] },
+            { "type": "FactSet", "facts": "${payload.details}" },
+            { "type": "ImageSet", "imageSize": "Small", "images": "${payload.photos}" },
+            { "type": "Icon", "id": "cal", "name": "Calendar", "size": "Large", "style": "Filled", "color": "Accent" },
+            { "type": "Icon", "name": "Clock", "size": 32, "separator": "yes" }
+        ]
+    });
+    let mut invocation = base_invocation(card.clone());
+    invocation.payload = json!({
+        "amount": 12.5,
+        "paid": true,
+        "details": { "Owner": "Ada", "Items": 3 },
+        "photos": [ "https://example.com/a.png", { "type": "Image", "url": "https://example.com/b.png", "size": "Large" } ]
+    });
+    invocation.card_spec.asset_registry = Some(
+        [(
+            "icons/Calendar-filled.png".to_string(),
+            "https://cdn.example.com/calendar-filled.png".to_string(),
+        )]
+        .into(),
+    );
+    let result = handle_invocation(invocation.clone()).expect("render");
+    let body = &result.rendered_card.expect("card")["body"];
+    assert_eq!(
+        body[0]["facts"],
+        json!([ { "title": "Amount", "value": "12.5" }, { "title": "Paid", "value": "true" } ])
+    );
+    assert_eq!(
+        body[1]["facts"],
+        json!([ { "title": "Items", "value": "3" }, { "title": "Owner", "value": "Ada" } ])
+    );
+    assert_eq!(
+        body[2]["images"].clone(),
+        json!([
+            { "type": "Image", "url": "https://example.com/a.png", "size": "Small" },
+            { "type": "Image", "url": "https://example.com/b.png", "size": "Large" }
+        ])
+    );
+    assert_eq!(
+        body[3],
+        json!({
+            "type": "Image",
+            "id": "cal",
+            "url": "https://cdn.example.com/calendar-filled.png",
+            "altText": "Calendar",
+            "width": "32px",
+            "height": "32px"
+        })
+    );
+    // Icons with off-spec properties are still downgraded.
+    assert_eq!(body[4]["type"], "Image");
+    assert_eq!(body[4]["altText"], "Clock");
+    assert_eq!(body[4]["separator"], "yes");
+    assert_eq!(body[4]["width"], "24px");
+
+    // Hosts on 1.5+ render Icon natively.
+    let mut modern = card;
+    modern["version"] = json!("1.5");
+    invocation.card_spec.inline_json = Some(modern);
+    let result = handle_invocation(invocation).expect("render");
+    assert_eq!(
+        result.rendered_card.expect("card")["body"][3]["type"],
+        "Icon"
+    );
+}
+
+#[test]
+fn fallback_replaces_unsupported_elements_for_the_target_profile() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            {
+                "type": "Table",
+                "rows": [],
+                "fallback": { "type": "TextBlock", "text": "Table unavailable" }
+            },
+            { "type": "Contoso.Map", "fallback": "drop" },
+            {
+                "type": "Container",
+                "fallback": { "type": "TextBlock", "text": "Open in the app" },
+                "items": [ { "type": "Contoso.Chart" } ]
+            },
+            { "type": "Contoso.Widget" },
+            { "type": "Contoso.Gauge" }
+        ],
+        "actions": [
+            {
+                "type": "Action.Execute",
+                "id": "run",
+                "title": "Run",
+                "fallback": { "type": "Action.Submit", "id": "run", "title": "Run" }
+            },
+            { "type": "Action.Submit", "id": "typed", "title": "Typed", "data": { "type": "order" } }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.target_profile = Some(TargetProfile {
+        max_version: Some("1.3".into()),
+        unsupported: Vec::new(),
+        custom_elements: vec!["Contoso.Gauge".into()],
+        ..TargetProfile::default()
+    });
+    let result = handle_invocation(invocation).expect("render");
+    let card = result.rendered_card.expect("card");
+    assert_eq!(
+        card["body"].clone(),
+        json!([
+            { "type": "TextBlock", "text": "Table unavailable" },
+            { "type": "TextBlock", "text": "Open in the app" },
+            { "type": "Contoso.Gauge" }
+        ])
+    );
+    assert_eq!(card["actions"][0]["type"], "Action.Submit");
+    assert_eq!(card["actions"][1]["data"], json!({ "type": "order" }));
+
+    let replaced: Vec<_> = result
+        .card_features
+        .fallbacks
+        .iter()
+        .map(|f| {
+            (
+                f.pointer.as_str(),
+                f.element_type.as_str(),
+                f.replaced_with.as_deref(),
+                f.reason.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        replaced,
+        vec![
+            ("/body/0", "Table", Some("TextBlock"), "version"),
+            ("/body/1", "Contoso.Map", None, "unknown"),
+            ("/body/2", "Container", Some("TextBlock"), "descendant"),
+            ("/body/3", "Contoso.Widget", None, "unknown"),
+            (
+                "/actions/0",
+                "Action.Execute",
+                Some("Action.Submit"),
+                "version"
+            ),
+        ]
+    );
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "unsupported-element" && issue.path == "/body/3")
+    );
+}
+
+#[test]
+fn typed_card_model_round_trips_losslessly() {
+    use component_adaptive_card::card::{AdaptiveCard, CardAction, CardElement};
+
+    let raw = json!({
+        "type": "AdaptiveCard",
+        "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "Hello", "wrap": true, "maxLines": 2, "customProp": 1 },
+            {
+                "type": "ColumnSet",
+                "columns": [
+                    { "type": "Column", "width": "auto", "items": [{ "type": "Image", "url": "https://x/a.png" }] },
+                    { "width": 2, "items": [{ "type": "Input.Number", "id": "qty", "min": 0, "max": "${payload.max}" }] }
+                ]
+            },
+            { "type": "RichTextBlock", "inlines": ["plain", { "type": "TextRun", "text": "bold", "weight": "Bolder" }] },
+            { "type": "Input.ChoiceSet", "id": "pick", "choices": [{ "title": "A", "value": "a" }] },
+            { "type": "TextBlock", "text": "nulls stay raw", "size": null },
+            { "type": "Rating", "value": 4 }
+        ],
+        "actions": [
+            { "type": "Action.Submit", "title": "Go", "data": { "type": "payload" } },
+            {
+                "type": "Action.ShowCard",
+                "title": "More",
+                "card": { "type": "AdaptiveCard", "body": [{ "type": "Input.Text", "id": "note" }] }
+            }
+        ]
+    });
+
+    let card = AdaptiveCard::from_value(&raw).expect("card parses");
+    assert_eq!(card.to_value(), raw);
+    assert_eq!(serde_json::to_value(&card.body).unwrap(), raw["body"]);
+
+    let body = card.body.as_ref().unwrap();
+    assert!(matches!(
+        &body[0],
+        CardElement::TextBlock(tb) if tb.text.as_deref() == Some("Hello") && tb.common.extra["customProp"] == 1
+    ));
+    // Values the typed fields cannot hold stay in `extra` instead of losing the element type.
+    assert!(matches!(
+        &body[4],
+        CardElement::TextBlock(tb) if tb.size.is_none() && tb.common.extra["size"].is_null()
+    ));
+    assert!(matches!(&body[5], CardElement::Other(_)));
+    assert_eq!(body[5].type_name(), Some("Rating"));
+    assert!(matches!(
+        &card.actions.as_ref().unwrap()[1],
+        CardAction::ShowCard(show) if show.card.is_some()
+    ));
+
+    let inputs: Vec<_> = card
+        .elements()
+        .into_iter()
+        .filter(|e| e.is_input())
+        .filter_map(|e| e.common().and_then(|c| c.id.clone()))
+        .collect();
+    assert_eq!(inputs, vec!["qty", "pick", "note"]);
+    let qty = card
+        .elements()
+        .into_iter()
+        .find_map(|e| match e {
+            CardElement::InputNumber(number) => Some(number),
+            _ => None,
+        })
+        .expect("typed number input");
+    assert_eq!(qty.min, Some(0.into()));
+    assert_eq!(qty.input.common.extra["max"], "${payload.max}");
+}
+
+#[test]
+fn unmet_requires_take_the_fallback_path() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            {
+                "type": "TextBlock",
+                "text": "Live chart",
+                "requires": { "contoso.charts": "1.2" },
+                "fallback": { "type": "TextBlock", "text": "Charts unavailable" }
+            },
+            { "type": "TextBlock", "text": "Map", "requires": { "contoso.maps": "1.0" } },
+            {
+                "type": "Container",
+                "requires": { "contoso.maps": "1.2", "contoso.pins": "*" },
+                "items": [{ "type": "TextBlock", "text": "Pins" }],
+                "fallback": { "type": "TextBlock", "text": "unused", "requires": { "x": "9" } }
+            },
+            { "type": "TextBlock", "text": "Beta", "requires": { "contoso.beta": "1" } }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.target_profile = Some(TargetProfile {
+        features: [
+            ("contoso.charts", "1.1"),
+            ("contoso.maps", "1.3"),
+            ("contoso.pins", "2"),
+        ]
+        .into_iter()
+        .map(|(name, version)| (name.to_string(), version.to_string()))
+        .collect(),
+        ..TargetProfile::default()
+    });
+    let result = handle_invocation(invocation).expect("render");
+    let card = result.rendered_card.expect("card");
+    let texts: Vec<_> = card["body"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|element| element["text"].as_str().unwrap_or("container"))
+        .collect();
+    assert_eq!(texts, vec!["Charts unavailable", "Map", "container"]);
+
+    let reasons: Vec<_> = result
+        .card_features
+        .fallbacks
+        .iter()
+        .map(|f| (f.pointer.as_str(), f.reason.as_str()))
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![("/body/0", "requires"), ("/body/3", "requires")]
+    );
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "unsupported-element" && issue.path == "/body/3")
+    );
+    assert_eq!(
+        result.card_features.requires_features,
+        json!({ "contoso.maps": "1.2", "contoso.pins": "*" })
+    );
+}
+
+#[test]
+fn speak_text_is_derived_from_the_rendered_card() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "**Order** shipped" },
+            { "type": "TextBlock", "text": "Hidden", "isVisible": false },
+            {
+                "type": "Container",
+                "items": [{ "type": "FactSet", "facts": [{ "title": "Total", "value": "$5 & tax" }] }]
+            },
+            { "type": "Image", "url": "https://example.com/a.png", "speak": "A parcel" }
+        ]
+    });
+    let result = handle_invocation(base_invocation(card.clone())).expect("render");
+    assert_eq!(
+        result.speak.as_deref(),
+        Some("Order shipped. Total: $5 & tax. A parcel.")
+    );
+
+    let mut ssml = card.clone();
+    ssml["body"][3]["speak"] = json!("<speak><emphasis>A parcel</emphasis></speak>");
+    let result = handle_invocation(base_invocation(ssml)).expect("render");
+    assert_eq!(
+        result.speak.as_deref(),
+        Some("<speak>Order shipped. Total: $5 &amp; tax. <emphasis>A parcel</emphasis></speak>")
+    );
+
+    let mut explicit = card;
+    explicit["speak"] = json!("Your order shipped");
+    let result = handle_invocation(base_invocation(explicit)).expect("render");
+    assert_eq!(result.speak.as_deref(), Some("Your order shipped"));
+}
+
+#[test]
+fn viewer_specific_views_and_refresh_user_ids() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "refresh": {
+            "action": { "type": "Action.Execute", "verb": "refresh" },
+            "userIds": "${payload.approvers}"
+        },
+        "body": [
+            {
+                "type": "TextBlock",
+                "text": "${viewer.id == payload.requester ? 'Waiting for approval' : 'Please review'}"
+            },
+            { "type": "TextBlock", "text": "Signed in as ${viewer.name}" }
+        ]
+    });
+    let payload = json!({
+        "requester": "u-1",
+        "approvers": [{ "id": "u-2" }, "u-3", "u-2", 7]
+    });
+    let view_for = |viewer: &str, name: &str| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.payload = payload.clone();
+        invocation.viewer = Some(CardViewer {
+            id: viewer.into(),
+            name: Some(name.into()),
+            ..CardViewer::default()
+        });
+        handle_invocation(invocation).expect("render")
+    };
+
+    let requester = view_for("u-1", "Rita");
+    let card_out = requester.rendered_card.as_ref().expect("card");
+    assert_eq!(card_out["body"][0]["text"], "Waiting for approval");
+    assert_eq!(card_out["body"][1]["text"], "Signed in as Rita");
+    assert_eq!(card_out["refresh"]["userIds"], json!(["u-2", "u-3"]));
+    assert!(
+        requester
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "invalid-refresh-user-id"
+                && issue.path == "/refresh/userIds/3")
+    );
+
+    let approver = view_for("u-2", "Ana");
+    let card_out = approver.rendered_card.expect("card");
+    assert_eq!(card_out["body"][0]["text"], "Please review");
+
+    let mut invocation = base_invocation(card.clone());
+    invocation.payload = payload.clone();
+    invocation.viewer = Some(CardViewer {
+        id: "u-9".into(),
+        name: Some("Max".into()),
+        ..CardViewer::default()
+    });
+    invocation.refresh_user_ids = Some(vec!["u-9".into()]);
+    let result = handle_invocation(invocation).expect("render");
+    assert_eq!(
+        result.rendered_card.expect("card")["refresh"]["userIds"],
+        json!(["u-9"])
+    );
+}
+
+#[test]
+fn visible_to_roles_prunes_elements_and_rejects_forged_clicks() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "Expense report" },
+            {
+                "type": "Container",
+                "visibleToRoles": "approver",
+                "items": [{
+                    "type": "ActionSet",
+                    "actions": [{ "type": "Action.Execute", "id": "approve", "verb": "approve" }]
+                }]
+            },
+            { "type": "TextBlock", "text": "Audit trail", "visibleToRoles": ["auditor", "admin"] },
+            { "type": "Input.Number", "id": "approvedAmount", "visibleToRoles": "approver" }
+        ],
+        "actions": [
+            { "type": "Action.Submit", "id": "comment", "title": "Comment" },
+            { "type": "Action.Submit", "id": "delete", "visibleToRoles": ["admin"], "data": { "visibleToRoles": "kept" } }
+        ]
+    });
+    let invocation_for = |roles: &[&str], action: Option<&str>| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.viewer = Some(CardViewer {
+            id: "u-1".into(),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            ..CardViewer::default()
+        });
+        invocation.interaction = action.map(|id| CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Execute,
+            action_id: id.to_string(),
+            verb: None,
+            raw_inputs: json!({}),
+            card_instance_id: "card-roles".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation)
+    };
+
+    let requester = invocation_for(&[], None).expect("render");
+    let rendered = requester.rendered_card.expect("card");
+    assert_eq!(
+        rendered["body"].clone(),
+        json!([{ "type": "TextBlock", "text": "Expense report" }])
+    );
+    assert_eq!(rendered["actions"].as_array().unwrap().len(), 1);
+
+    let admin = invocation_for(&["admin", "approver"], None).expect("render");
+    let rendered = admin.rendered_card.expect("card");
+    assert_eq!(rendered["body"].as_array().unwrap().len(), 4);
+    assert!(rendered["body"][2].get("visibleToRoles").is_none());
+    assert_eq!(rendered["actions"][1]["data"]["visibleToRoles"], "kept");
+
+    assert!(matches!(
+        invocation_for(&["auditor"], Some("approve")),
+        Err(component_adaptive_card::ComponentError::InteractionInvalid(
+            _
+        ))
+    ));
+    assert!(invocation_for(&["approver"], Some("approve")).is_ok());
+
+    // Values for a hidden input are dropped from a visible action's submit, and drafts of it
+    // are rejected.
+    let forge = |roles: &[&str], interaction_type: CardInteractionType, action_id: &str| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.viewer = Some(CardViewer {
+            id: "u-1".into(),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            ..CardViewer::default()
+        });
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type,
+            action_id: action_id.to_string(),
+            verb: None,
+            raw_inputs: json!({ "approvedAmount": 1000000, "note": "ok" }),
+            card_instance_id: "card-roles".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation)
+    };
+    let employee = forge(&[], CardInteractionType::Submit, "comment").expect("submit");
+    assert_eq!(
+        employee.event.expect("event").inputs,
+        json!({ "note": "ok" })
+    );
+    assert!(
+        !serde_json::to_string(&employee.state_updates)
+            .unwrap()
+            .contains("approvedAmount")
+    );
+    let approver = forge(&["approver"], CardInteractionType::Submit, "comment").expect("submit");
+    assert_eq!(
+        approver.event.expect("event").inputs["approvedAmount"],
+        1000000
+    );
+    assert!(matches!(
+        forge(&[], CardInteractionType::InputChanged, "approvedAmount"),
+        Err(component_adaptive_card::ComponentError::InteractionInvalid(
+            _
+        ))
+    ));
+}
+
+#[test]
+fn interactions_beyond_the_rate_limit_fail_with_a_notice_card() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "Vote" }],
+        "actions": [{ "type": "Action.Submit", "id": "vote", "title": "Vote" }]
+    });
+    let click_in = |notice: Option<&str>, mode: InvocationMode| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.mode = mode;
+        invocation.rate_limit = Some(RateLimit {
+            max_interactions: 2,
+            window_seconds: 60,
+            notice: notice.map(str::to_string),
+        });
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "vote".to_string(),
+            verb: None,
+            raw_inputs: json!({}),
+            card_instance_id: "card-rate-limited".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation)
+    };
+    let click = |notice: Option<&str>| click_in(notice, InvocationMode::RenderAndValidate);
+
+    for _ in 0..3 {
+        assert!(
+            click_in(None, InvocationMode::Simulate).is_ok(),
+            "simulations are not counted"
+        );
+    }
+    assert!(click(None).is_ok());
+    assert!(click(None).is_ok());
+    let Err(err) = click(None) else {
+        panic!("third click should be rate limited");
+    };
+    let failure = err.to_failure();
+    assert_eq!(failure.code, FailureCode::RateLimited);
+    assert!(failure.retryable);
+    let details = failure.details.expect("details");
+    let wait = details["retry_after_seconds"]
+        .as_u64()
+        .expect("retry after");
+    assert!((1..=60).contains(&wait));
+    assert!(details.get("rendered_card").is_none());
+
+    let Err(component_adaptive_card::ComponentError::RateLimited { card, .. }) =
+        click(Some("Slow down, please"))
+    else {
+        panic!("still rate limited");
+    };
+    let card = card.expect("notice card");
+    assert_eq!(card["body"][0]["text"], "Slow down, please");
+    assert_eq!(card["body"][1]["text"], "Vote");
+}
+
+#[test]
+fn paginated_containers_render_one_page_and_page_through_interactions() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{
+            "type": "Container",
+            "id": "orders",
+            "pageSize": 3,
+            "items": "${payload.orders}",
+            "itemTemplate": { "type": "TextBlock", "text": "${item.name}" }
+        }]
+    });
+    let orders: Vec<_> = (1..=7)
+        .map(|n| json!({ "name": format!("Order {n}") }))
+        .collect();
+    let invocation_with = |state: serde_json::Value| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.payload = json!({ "orders": orders });
+        invocation.state = state;
+        invocation
+    };
+    let texts = |card: &serde_json::Value| -> Vec<String> {
+        card["body"][0]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|item| item["text"].as_str().map(str::to_string))
+            .collect()
+    };
+    let pager_ids = |card: &serde_json::Value| -> Vec<String> {
+        let items = card["body"][0]["items"].as_array().unwrap();
+        items.last().unwrap()["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|action| action["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let first = handle_invocation(invocation_with(json!({}))).expect("render");
+    let rendered = first.rendered_card.expect("card");
+    assert_eq!(
+        texts(&rendered),
+        vec!["Order 1", "Order 2", "Order 3", "Page 1 of 3"]
+    );
+    assert_eq!(pager_ids(&rendered), vec!["orders-next"]);
+    assert!(rendered["body"][0].get("pageSize").is_none());
+
+    let mut click = invocation_with(json!({}));
+    click.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "orders-next".to_string(),
+        verb: None,
+        raw_inputs: json!({}),
+        card_instance_id: "card-pages".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let turned = handle_invocation(click).expect("page turn");
+    assert!(turned.event.is_none());
+    assert!(turned.state_updates.contains(&StateUpdateOp::Set {
+        path: "ui.pages.orders".into(),
+        value: json!(1),
+    }));
+    let rendered = turned.rendered_card.expect("card");
+    assert_eq!(texts(&rendered)[0], "Order 4");
+    assert_eq!(pager_ids(&rendered), vec!["orders-prev", "orders-next"]);
+
+    let clamped = handle_invocation(invocation_with(
+        json!({ "ui": { "pages": { "orders": 9 } } }),
+    ))
+    .expect("render");
+    assert_eq!(
+        texts(&clamped.rendered_card.expect("card")),
+        vec!["Order 7", "Page 3 of 3"]
+    );
+}
+
+#[test]
+fn sortable_filterable_tables_persist_sort_and_filter_state() {
+    let cell = |text: &str| json!({ "type": "TableCell", "items": [{ "type": "TextBlock", "text": text }] });
+    let row =
+        |name: &str, qty: &str| json!({ "type": "TableRow", "cells": [cell(name), cell(qty)] });
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{
+            "type": "Table",
+            "id": "stock",
+            "sortable": true,
+            "filterable": true,
+            "columns": [{ "width": 1 }, { "width": 1 }],
+            "rows": [row("Item", "Qty"), row("pear", "10"), row("Apple", "9"), row("banana", "100")]
+        }]
+    });
+    let names = |card: &serde_json::Value| -> Vec<String> {
+        let table = card["body"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|element| element["type"] == "Table")
+            .unwrap();
+        table["rows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| {
+                row["cells"][0]["items"][0]["text"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    };
+    let interact = |state: serde_json::Value, action_id: &str, inputs: serde_json::Value| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.state = state;
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: action_id.to_string(),
+            verb: None,
+            raw_inputs: inputs,
+            card_instance_id: "card-table".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("interaction")
+    };
+
+    let initial = handle_invocation(base_invocation(card.clone())).expect("render");
+    let rendered = initial.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["id"], "stock-filter");
+    assert_eq!(rendered["body"][1]["actions"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        rendered["body"][2]["rows"][0]["cells"][1]["selectAction"]["id"],
+        "stock-sort-1"
+    );
+    assert!(rendered["body"][2].get("sortable").is_none());
+
+    let sorted = interact(json!({}), "stock-sort-1", json!({}));
+    assert!(sorted.event.is_none());
+    assert_eq!(
+        names(sorted.rendered_card.as_ref().unwrap()),
+        vec!["Item", "Apple", "pear", "banana"]
+    );
+
+    let state =
+        json!({ "ui": { "tables": { "stock": { "sort": { "column": 1, "direction": "asc" } } } } });
+    let reversed = interact(state.clone(), "stock-sort-1", json!({}));
+    assert_eq!(
+        names(reversed.rendered_card.as_ref().unwrap()),
+        vec!["Item", "banana", "pear", "Apple"]
+    );
+    assert_eq!(
+        reversed.rendered_card.as_ref().unwrap()["body"][2]["rows"][0]["cells"][1]["items"][0]["text"],
+        "Qty ▼"
+    );
+
+    let filtered = interact(state, "stock-filter", json!({ "stock-filter": " AN " }));
+    assert!(filtered.state_updates.contains(&StateUpdateOp::Set {
+        path: "ui.tables.stock.filter".into(),
+        value: json!("AN"),
+    }));
+    let rendered = filtered.rendered_card.expect("card");
+    assert_eq!(names(&rendered), vec!["Item", "banana"]);
+    assert_eq!(rendered["body"][0]["value"], "AN");
+    assert_eq!(rendered["body"][1]["actions"][1]["id"], "stock-clear");
+}
+
+#[test]
+fn charts_bind_payload_series_and_downgrade_to_summaries() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            {
+                "type": "Chart.VerticalBar",
+                "id": "sales",
+                "title": "Sales",
+                "data": "${payload.sales}",
+                "fields": { "x": "month", "y": "total" }
+            },
+            {
+                "type": "Chart.Line",
+                "data": [
+                    { "legend": "EU", "values": [{ "x": "Q1", "y": 3 }, { "x": "Q2", "y": 5 }] },
+                    { "legend": "US", "values": [{ "x": "Q1", "y": 4 }] }
+                ]
+            }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({
+        "sales": [{ "month": "Jan", "total": "12" }, { "month": "Feb", "total": 7.5 }]
+    });
+
+    let native = handle_invocation(invocation.clone()).expect("render");
+    let rendered = native.rendered_card.expect("card");
+    let bar = &rendered["body"][0];
+    assert_eq!(bar["type"], "Chart.VerticalBar");
+    assert_eq!(
+        bar["data"],
+        json!([{ "x": "Jan", "y": 12 }, { "x": "Feb", "y": 7.5 }])
+    );
+    assert!(bar.get("fields").is_none());
+    assert!(
+        native
+            .card_features
+            .used_elements
+            .contains(&"Chart.Line".to_string())
+    );
+
+    invocation.target_profile = Some(TargetProfile {
+        unsupported: vec!["Chart.Line".into(), "Chart.VerticalBar".into()],
+        ..TargetProfile::default()
+    });
+    let downgraded = handle_invocation(invocation.clone()).expect("render");
+    let rendered = downgraded.rendered_card.expect("card");
+    let summary = &rendered["body"][0];
+    assert_eq!(summary["type"], "Container");
+    assert_eq!(summary["id"], "sales");
+    assert_eq!(summary["items"][0]["text"], "Sales");
+    assert_eq!(
+        summary["items"][1]["facts"],
+        json!([{ "title": "Jan", "value": "12" }, { "title": "Feb", "value": "7.5" }])
+    );
+    let table = &rendered["body"][1];
+    assert_eq!(table["type"], "Table");
+    assert_eq!(table["rows"].as_array().unwrap().len(), 4);
+    assert_eq!(table["rows"][3]["cells"][0]["items"][0]["text"], "US");
+
+    invocation.target_profile = Some(TargetProfile {
+        max_version: Some("1.4".into()),
+        ..TargetProfile::default()
+    });
+    let legacy = handle_invocation(invocation).expect("render");
+    let rendered = legacy.rendered_card.expect("card");
+    assert_eq!(rendered["body"][1]["type"], "FactSet");
+    assert_eq!(
+        rendered["body"][1]["facts"][0],
+        json!({ "title": "EU · Q1", "value": "3" })
+    );
+}
+
+#[test]
+fn progress_elements_bind_state_and_downgrade_to_percentages() {
+    let card = |version: &str| {
+        json!({
+            "type": "AdaptiveCard",
+            "version": version,
+            "body": [
+                {
+                    "type": "ProgressBar",
+                    "id": "upload",
+                    "label": "Upload",
+                    "value": "${state.job.done}",
+                    "max": 40,
+                    "color": "Good"
+                },
+                { "type": "ProgressBar", "value": 140 },
+                { "type": "Spinner", "label": "Indexing" }
+            ]
+        })
+    };
+    let invocation = |version: &str| {
+        let mut invocation = base_invocation(card(version));
+        invocation.state = json!({ "job": { "done": "10" } });
+        invocation
+    };
+
+    let current = handle_invocation(invocation("1.5")).expect("render");
+    let rendered = current.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["type"], "ProgressBar");
+    assert_eq!(rendered["body"][0]["value"], json!(10));
+    assert_eq!(
+        rendered["body"][0]["fallback"],
+        json!({ "type": "TextBlock", "text": "Upload: 25%", "wrap": true, "id": "upload", "color": "Good" })
+    );
+    assert_eq!(rendered["body"][1]["value"], json!(100));
+    assert_eq!(rendered["body"][2]["type"], "Spinner");
+
+    let mut targeted = invocation("1.5");
+    targeted.target_profile = Some(TargetProfile {
+        max_version: Some("1.4".into()),
+        ..TargetProfile::default()
+    });
+    let downgraded = handle_invocation(targeted).expect("render");
+    let rendered = downgraded.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["text"], "Upload: 25%");
+    assert_eq!(rendered["body"][1]["text"], "100%");
+
+    let legacy = handle_invocation(invocation("1.3")).expect("render");
+    let rendered = legacy.rendered_card.expect("card");
+    let texts: Vec<&str> = rendered["body"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|element| element["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(texts, vec!["Upload: 25%", "100%", "Indexing"]);
+    assert!(
+        legacy
+            .validation_issues
+            .iter()
+            .all(|issue| issue.code != "invalid-progress-value")
+    );
+}
+
+#[test]
+fn component_macros_expand_composites_into_standard_elements() {
+    register_component_macro(
+        "Contoso.KeyValue",
+        Box::new(|element| {
+            let key = element["key"].as_str().ok_or("key is required")?;
+            Ok(json!({
+                "type": "ListItem",
+                "title": key,
+                "subtitle": element["value"].clone()
+            }))
+        }),
+    )
+    .expect("register macro");
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            {
+                "type": "ListItem",
+                "id": "order",
+                "icon": "Box",
+                "title": "Order ${payload.order}",
+                "subtitle": "Shipped",
+                "selectAction": { "type": "Action.Submit", "id": "open-order" }
+            },
+            {
+                "type": "CompoundButton",
+                "title": "Approve",
+                "description": "Sign off the request",
+                "isVisible": false
+            },
+            { "type": "Contoso.KeyValue", "key": "Owner", "value": "Ada" },
+            { "type": "Contoso.KeyValue", "value": "orphan" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "order": 42 });
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+
+    let item = &rendered["body"][0];
+    assert_eq!(item["type"], "Container");
+    assert_eq!(item["id"], "order");
+    assert_eq!(item["selectAction"]["id"], "open-order");
+    let columns = &item["items"][0]["columns"];
+    assert_eq!(
+        columns[0]["items"][0].clone(),
+        json!({ "type": "Icon", "name": "Box" })
+    );
+    assert_eq!(columns[1]["items"][0]["text"], "Order 42");
+    assert_eq!(columns[1]["items"][1]["text"], "Shipped");
+
+    let button = &rendered["body"][1];
+    assert_eq!(button["style"], "emphasis");
+    assert_eq!(button["isVisible"], false);
+    assert_eq!(
+        button["items"][0]["columns"][0]["items"][1]["text"],
+        "Sign off the request"
+    );
+
+    let host = &rendered["body"][2];
+    assert_eq!(host["type"], "Container");
+    assert_eq!(host["items"][0]["columns"][0]["items"][0]["text"], "Owner");
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| { issue.code == "component-macro-failed" && issue.path == "/body/3" })
+    );
+    assert!(
+        !result
+            .card_features
+            .used_elements
+            .contains(&"ListItem".to_string())
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn includes_expand_partials_with_local_scope() {
+    let registry: std::collections::BTreeMap<String, String> =
+        ["header", "footer", "legal", "loop"]
+            .into_iter()
+            .map(|name| {
+                (
+                    format!("partials/{name}"),
+                    format!("tests/assets/partials/{name}.json"),
+                )
+            })
+            .collect();
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            {
+                "$include": "partials/header",
+                "$with": { "title": "${payload.team} report", "audience": "leads", "count": 3 }
+            },
+            { "type": "TextBlock", "text": "Body" },
+            { "$include": "partials/footer", "$with": { "contact": "ops@example.com", "year": 2026 } }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.card_spec.asset_registry = Some(registry.clone());
+    invocation.payload = json!({ "team": "Platform" });
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    let body = rendered["body"].as_array().unwrap();
+    assert_eq!(body.len(), 4);
+    assert_eq!(body[0]["items"][0]["text"], "Platform report");
+    assert_eq!(body[0]["items"][1]["text"], "For leads (3 items)");
+    assert_eq!(body[2]["text"], "Questions? ops@example.com");
+    assert_eq!(body[3]["text"], "© 2026 Contoso");
+
+    let mut cyclic = base_invocation(json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "$include": "partials/loop" }]
+    }));
+    cyclic.card_spec.asset_registry = Some(registry);
+    let err = handle_invocation(cyclic).expect_err("cycle must fail");
+    assert!(err.to_string().contains("include cycle"), "{err}");
+
+    let missing = base_invocation(json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "$include": "partials/nope" }]
+    }));
+    assert!(handle_invocation(missing).is_err());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn layouts_wrap_child_templates_through_named_slots() {
+    let registry: std::collections::BTreeMap<String, String> = ["branded", "chrome"]
+        .into_iter()
+        .map(|name| {
+            (
+                format!("layouts/{name}"),
+                format!("tests/assets/layouts/{name}.json"),
+            )
+        })
+        .collect();
+    let card = json!({
+        "type": "AdaptiveCard",
+        "$layout": "layouts/branded",
+        "speak": "Weekly report",
+        "$slots": { "sidebar": [{ "type": "TextBlock", "text": "unused" }] },
+        "body": [
+            { "type": "TextBlock", "text": "Hello ${payload.name}" },
+            { "type": "TextBlock", "text": "Your report is ready" }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "ack", "title": "Got it" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.card_spec.asset_registry = Some(registry);
+    invocation.payload = json!({ "name": "Ada" });
+    let result = handle_invocation(invocation).expect("render");
+    let rendered = result.rendered_card.expect("card");
+
+    assert_eq!(rendered["version"], "1.5");
+    assert_eq!(rendered["speak"], "Weekly report");
+    assert!(rendered.get("$layout").is_none());
+    let chrome = &rendered["body"][0];
+    assert_eq!(chrome["style"], "emphasis");
+    let branded = chrome["items"].as_array().unwrap();
+    assert_eq!(branded.len(), 3);
+    assert_eq!(branded[0]["text"], "Contoso");
+    assert_eq!(branded[1]["text"], "Untitled");
+    assert_eq!(branded[2]["items"][0]["text"], "Hello Ada");
+    assert_eq!(branded[2]["items"][1]["text"], "Your report is ready");
+    assert_eq!(rendered["body"][1]["text"], "Sent by Contoso bot");
+    let action_titles: Vec<&str> = rendered["actions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|action| action["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(action_titles, vec!["Got it", "Help"]);
+    assert!(
+        result.validation_issues.iter().any(|issue| {
+            issue.code == "unknown-layout-slot" && issue.path == "/$slots/sidebar"
+        })
+    );
+}
+
+#[test]
+fn compose_merges_cards_into_one_digest() {
+    let part = |title: &str, version: &str, action: &str| {
+        json!({
+            "card_spec": { "inline_json": {
+                "type": "AdaptiveCard",
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "version": version,
+                "body": [{ "type": "TextBlock", "text": format!("{title}: ${{payload.count}}") }],
+                "actions": [{ "type": "Action.Submit", "id": action, "title": title }]
+            } }
+        })
+    };
+    let mut alerts = part("Alerts", "1.3", "open");
+    alerts["payload"] = json!({ "count": 2 });
+    let input = json!({
+        "card_spec": {},
+        "payload": { "count": 7 },
+        "budget": { "max_actions": 2 },
+        "compose": [alerts, part("Tasks", "1.5", "open"), part("Mentions", "1.4", "mentions")]
+    });
+    let result = match component_adaptive_card::process_message("compose", &input.to_string()) {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("compose failed: {error:?}"),
+    };
+    let card = result.rendered_card.expect("card");
+    assert_eq!(card["version"], "1.5");
+    assert_eq!(
+        card["$schema"],
+        "http://adaptivecards.io/schemas/adaptive-card.json"
+    );
+    let body = card["body"].as_array().unwrap();
+    assert_eq!(body.len(), 3);
+    assert_eq!(body[0]["id"], "compose-0");
+    assert!(body[0].get("separator").is_none());
+    assert_eq!(body[0]["items"][0]["text"], "Alerts: 2");
+    assert_eq!(body[1]["separator"], true);
+    assert_eq!(body[1]["items"][0]["text"], "Tasks: 7");
+    let ids: Vec<&str> = card["actions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|action| action["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["open", "open-1"]);
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "compose-actions-truncated")
+    );
+    assert!(
+        result
+            .card_features
+            .used_actions
+            .contains(&"Action.Submit".to_string())
+    );
+}
+
+#[test]
+fn open_url_bindings_encode_values_and_flag_malformed_urls() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "${urlDecode('a%20b%2Fc')}" }],
+        "actions": [
+            {
+                "type": "Action.OpenUrl",
+                "title": "Search",
+                "url": "https://example.com/search?q=${urlEncode(payload.query)}"
+            },
+            { "type": "Action.OpenUrl", "title": "Raw", "url": "https://example.com/search?q=${payload.query}" },
+            { "type": "Action.OpenUrl", "title": "Relative", "url": "/tickets/${payload.id}" },
+            { "type": "Action.OpenUrl", "title": "Ftp", "url": "ftp://files.example.com/${payload.id}" },
+            { "type": "Action.OpenUrl", "title": "Hostless", "url": "https:///${payload.id}" }
+        ]
+    });
+    let mut inv = base_invocation(card);
+    inv.payload = json!({ "query": "rust & wasm/é", "id": 42 });
+    let result = handle_invocation(inv).expect("render");
+    let card = result.rendered_card.expect("card");
+    assert_eq!(card["body"][0]["text"], "a b/c");
+    assert_eq!(
+        card["actions"][0]["url"],
+        "https://example.com/search?q=rust%20%26%20wasm%2F%C3%A9"
+    );
+    let url_issues: Vec<(&str, &str)> = result
+        .validation_issues
+        .iter()
+        .filter(|issue| issue.code.contains("url"))
+        .map(|issue| (issue.code.as_str(), issue.path.as_str()))
+        .collect();
+    assert_eq!(
+        url_issues,
+        vec![
+            ("invalid-url", "/actions/1/url"),
+            ("invalid-url", "/actions/2/url"),
+            ("disallowed-url-scheme", "/actions/3/url"),
+            ("invalid-url", "/actions/4/url"),
+        ]
+    );
+}
+
+#[test]
+fn deep_links_follow_the_target_host() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [],
+        "actions": [
+            {
+                "type": "Action.OpenUrl",
+                "title": "Chat",
+                "url": "${deepLink('chat', 'users', payload.owners, 'message', payload.message)}"
+            },
+            {
+                "type": "Action.OpenUrl",
+                "title": "Details",
+                "deepLink": {
+                    "kind": "route",
+                    "path": "tickets/${payload.id}",
+                    "query": { "tab": "history" }
+                }
+            },
+            {
+                "type": "Action.OpenUrl",
+                "title": "Edit",
+                "deepLink": { "kind": "dialog", "url": "https://app.example.com/edit", "title": "Edit" }
+            }
+        ]
+    });
+    let mut inv = base_invocation(card.clone());
+    inv.payload =
+        json!({ "id": 42, "message": "About 42", "owners": ["ana@contoso.com", "bo@contoso.com"] });
+    inv.target_profile = Some(TargetProfile {
+        host: Some(HostPlatform::Teams),
+        app_id: Some("app-1".into()),
+        base_url: Some("https://app.example.com/".into()),
+        ..Default::default()
+    });
+    let teams = handle_invocation(inv.clone()).expect("render");
+    let actions = teams.rendered_card.expect("card")["actions"].clone();
+    assert_eq!(
+        actions[0]["url"],
+        "https://teams.microsoft.com/l/chat/0/0?users=ana%40contoso.com,bo%40contoso.com&message=About%2042"
+    );
+    assert_eq!(
+        actions[1]["url"],
+        "https://teams.microsoft.com/l/entity/app-1/home?webUrl=https%3A%2F%2Fapp.example.com%2Ftickets%2F42%3Ftab%3Dhistory&context=%7B%22subEntityId%22%3A%22%2Ftickets%2F42%3Ftab%3Dhistory%22%7D"
+    );
+    assert!(actions[1].get("deepLink").is_none());
+    assert_eq!(
+        actions[2]["url"],
+        "https://teams.microsoft.com/l/task/app-1?url=https%3A%2F%2Fapp.example.com%2Fedit&title=Edit"
+    );
+
+    let mut web = base_invocation(json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [],
+        "actions": [card["actions"][1].clone(), card["actions"][2].clone()]
+    }));
+    web.payload = inv.payload.clone();
+    web.target_profile = Some(TargetProfile {
+        base_url: Some("https://app.example.com".into()),
+        ..Default::default()
+    });
+    let result = handle_invocation(web).expect("render");
+    let actions = result.rendered_card.expect("card")["actions"].clone();
+    assert_eq!(
+        actions[0]["url"],
+        "https://app.example.com/tickets/42?tab=history"
+    );
+    assert!(actions[1].get("url").is_none());
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "invalid-deep-link" && issue.path == "/actions/1/deepLink")
+    );
+}
+
+#[test]
+fn dialogs_open_per_host_and_submit_back_to_the_parent_card() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "Name: @{state.dialogs.rename.name||\"unset\"}" }],
+        "actions": [
+            {
+                "type": "Action.Submit",
+                "id": "edit",
+                "title": "Rename",
+                "openDialog": {
+                    "id": "rename",
+                    "width": "medium",
+                    "card": {
+                        "type": "AdaptiveCard",
+                        "version": "1.5",
+                        "body": [{ "type": "Input.Text", "id": "name", "value": "${payload.name}" }],
+                        "actions": [{ "type": "Action.Submit", "id": "save", "title": "Save" }]
+                    }
+                }
+            },
+            {
+                "type": "Action.Submit",
+                "id": "help",
+                "title": "Help",
+                "openDialog": { "url": "https://app.example.com/help", "height": 400 }
+            }
+        ]
+    });
+    let mut inv = base_invocation(card);
+    inv.payload = json!({ "name": "Ada" });
+
+    let web = handle_invocation(inv.clone()).expect("web render");
+    let actions = web.rendered_card.expect("card")["actions"].clone();
+    assert_eq!(actions[0]["type"], "Action.ShowCard");
+    assert_eq!(actions[0]["card"]["body"][0]["value"], "Ada");
+    assert!(actions[0].get("openDialog").is_none());
+    assert_eq!(actions[1]["type"], "Action.OpenUrl");
+    assert_eq!(actions[1]["url"], "https://app.example.com/help");
+
+    inv.target_profile = Some(TargetProfile {
+        host: Some(HostPlatform::Teams),
+        app_id: Some("app-1".into()),
+        ..Default::default()
+    });
+    let teams = handle_invocation(inv.clone()).expect("teams render");
+    let actions = teams.rendered_card.expect("card")["actions"].clone();
+    assert_eq!(actions[0]["type"], "Action.Submit");
+    assert_eq!(actions[0]["data"]["msteams"]["type"], "task/fetch");
+    assert_eq!(actions[0]["data"]["dialogId"], "rename");
+    assert!(actions[0].get("card").is_none());
+    assert_eq!(
+        actions[1]["url"],
+        "https://teams.microsoft.com/l/task/app-1?url=https%3A%2F%2Fapp.example.com%2Fhelp&title=Help&height=400"
+    );
+
+    // Teams task/fetch: the opener's submit returns the bound dialog without an event.
+    let mut open = inv.clone();
+    open.interaction = Some(CardInteraction {
+        interaction_type: CardInteractionType::Submit,
+        action_id: "edit".into(),
+        card_instance_id: "dialog-card".into(),
+        raw_inputs: json!({ "msteams": { "type": "task/fetch" }, "dialogId": "rename" }),
+        ..Default::default()
+    });
+    let opened = handle_invocation(open).expect("open dialog");
+    assert!(opened.event.is_none());
+    assert!(opened.state_updates.is_empty());
+    let dialog = opened.dialog.expect("dialog");
+    assert_eq!(dialog.id, "rename");
+    assert_eq!(dialog.title.as_deref(), Some("Rename"));
+    assert_eq!(dialog.width, Some(json!("medium")));
+    assert_eq!(dialog.card.expect("dialog card")["body"][0]["value"], "Ada");
+
+    // The dialog's submit carries its id and updates the parent card.
+    let mut submit = inv.clone();
+    submit.interaction = Some(CardInteraction {
+        interaction_type: CardInteractionType::Submit,
+        action_id: "save".into(),
+        card_instance_id: "dialog-card".into(),
+        raw_inputs: json!({ "name": "Grace" }),
+        dialog_id: Some("rename".into()),
+        ..Default::default()
+    });
+    let submitted = handle_invocation(submit).expect("dialog submit");
+    let event = submitted.event.expect("event");
+    assert_eq!(event.dialog_id.as_deref(), Some("rename"));
+    assert_eq!(event.inputs["name"], "Grace");
+    assert_eq!(
+        submitted.rendered_card.expect("parent card")["body"][0]["text"],
+        "Name: Grace"
+    );
+
+    let mut unknown = inv;
+    unknown.interaction = Some(CardInteraction {
+        interaction_type: CardInteractionType::OpenDialog,
+        action_id: "missing".into(),
+        card_instance_id: "dialog-card".into(),
+        ..Default::default()
+    });
+    assert!(handle_invocation(unknown).is_err());
+}
+
+#[test]
+fn file_inputs_render_upload_controls_and_validate_completed_uploads() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{
+            "type": "Input.File",
+            "id": "receipt",
+            "label": "Receipt",
+            "isRequired": true,
+            "accept": [".pdf", "image/*"],
+            "maxSize": 1048576
+        }]
+    });
+    let mut inv = base_invocation(card);
+    inv.target_profile = Some(TargetProfile {
+        host: Some(HostPlatform::Teams),
+        ..Default::default()
+    });
+    let rendered = handle_invocation(inv.clone()).expect("render");
+    let control = rendered.rendered_card.expect("card")["body"][0].clone();
+    assert_eq!(control["type"], "Container");
+    assert_eq!(control["id"], "receipt");
+    assert_eq!(control["items"][0]["text"], "Receipt *");
+    assert_eq!(
+        control["items"][1]["text"],
+        "Accepted: .pdf, image/*. Up to 1 MB."
+    );
+    let button = &control["items"][2]["actions"][0];
+    assert_eq!(button["id"], "receipt-upload");
+    assert_eq!(button["title"], "Upload file");
+
+    let interact = |base: &AdaptiveCardInvocation,
+                    kind: CardInteractionType,
+                    action: &str,
+                    raw: serde_json::Value| {
+        let mut click = base.clone();
+        click.interaction = Some(CardInteraction {
+            interaction_type: kind,
+            action_id: action.into(),
+            card_instance_id: "expense-1".into(),
+            raw_inputs: raw,
+            ..Default::default()
+        });
+        click
+    };
+    let requested = handle_invocation(interact(
+        &inv,
+        CardInteractionType::Submit,
+        "receipt-upload",
+        json!({}),
+    ))
+    .expect("upload request");
+    assert!(requested.event.is_none());
+    let request = requested.upload_request.expect("upload request");
+    assert_eq!(request.input_id, "receipt");
+    assert_eq!(request.max_size, Some(1048576));
+    let consent = request.consent_card.expect("teams consent card");
+    assert_eq!(
+        consent["contentType"],
+        "application/vnd.microsoft.teams.card.file.consent"
+    );
+    assert_eq!(
+        consent["content"]["acceptContext"]["cardInstanceId"],
+        "expense-1"
+    );
+
+    let done = handle_invocation(interact(
+        &inv,
+        CardInteractionType::UploadComplete,
+        "receipt",
+        json!({ "fileName": "taxi.pdf", "fileSize": "524288", "contentUrl": "blob://taxi" }),
+    ))
+    .expect("upload complete");
+    let event = done.event.expect("event");
+    assert_eq!(event.action_type, AdaptiveActionType::UploadComplete);
+    assert_eq!(
+        event.inputs["receipt"],
+        json!({ "name": "taxi.pdf", "size": 524288, "contentRef": "blob://taxi" })
+    );
+    assert!(done.state_updates.iter().any(|op| matches!(
+        op,
+        StateUpdateOp::Set { path, .. } if path == "uploads.expense-1.receipt"
+    )));
+    let control = done.rendered_card.expect("card")["body"][0].clone();
+    assert_eq!(control["items"][1]["facts"][0]["title"], "taxi.pdf");
+    assert_eq!(control["items"][1]["facts"][0]["value"], "512 KB");
+    assert_eq!(control["items"][3]["actions"][0]["title"], "Replace file");
+
+    inv.security = Some(SecurityPolicy {
+        allowed_upload_types: vec!["application/pdf".into()],
+        ..Default::default()
+    });
+    let rejected = handle_invocation(interact(
+        &inv,
+        CardInteractionType::UploadComplete,
+        "receipt",
+        json!([
+            { "name": "scan.png", "size": 2048, "contentType": "image/png" },
+            { "name": "huge.pdf", "size": 5000000, "contentType": "application/pdf" }
+        ]),
+    ))
+    .expect("rejected upload");
+    assert!(rejected.event.is_none());
+    assert!(rejected.state_updates.is_empty());
+    let codes: Vec<&str> = rejected
+        .validation_issues
+        .iter()
+        .map(|issue| issue.code.as_str())
+        .collect();
+    assert_eq!(codes, vec!["upload-type-not-allowed", "upload-too-large"]);
+}
+
+#[test]
+fn results_carry_card_hashes_and_template_data_fingerprints() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "Status: ${payload.status}" }]
+    });
+    let render = |payload: serde_json::Value| {
+        let mut inv = base_invocation(card.clone());
+        inv.payload = payload;
+        handle_invocation(inv).expect("render")
+    };
+    let first = render(json!({ "status": "open" }));
+    let again = render(json!({ "status": "open" }));
+    let card_hash = first.card_hash.clone().expect("card hash");
+    assert!(card_hash.starts_with("blake3:"));
+    assert_eq!(again.card_hash, first.card_hash);
+    assert_eq!(again.fingerprint, first.fingerprint);
+    assert_ne!(first.fingerprint.as_deref(), Some(card_hash.as_str()));
+
+    // Data the template ignores changes the fingerprint but not the rendered card.
+    let unused = render(json!({ "status": "open", "traceId": "abc" }));
+    assert_eq!(unused.card_hash, first.card_hash);
+    assert_ne!(unused.fingerprint, first.fingerprint);
+
+    let changed = render(json!({ "status": "closed" }));
+    assert_ne!(changed.card_hash, first.card_hash);
+    assert_ne!(changed.fingerprint, first.fingerprint);
+}
+
+#[test]
+fn deterministic_ids_are_stable_and_listed_in_card_features() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "${payload.title}" },
+            { "type": "Container", "id": "details", "items": [
+                { "type": "Image", "url": "https://example.com/a.png" },
+                { "type": "Input.Text", "label": "Note" }
+            ] }
+        ],
+        "actions": [{ "type": "Action.Submit", "title": "Send" }]
+    });
+    let render = |title: &str| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.payload = json!({ "title": title });
+        invocation.generate_ids = true;
+        handle_invocation(invocation).expect("render")
+    };
+
+    let first = render("Order 1");
+    let second = render("Order 2");
+    let rendered = first.rendered_card.expect("card");
+    let heading = rendered["body"][0]["id"].as_str().expect("generated id");
+    assert!(heading.starts_with("textblock-"));
+    assert_eq!(
+        second.rendered_card.expect("card")["body"][0]["id"],
+        heading
+    );
+    let image = rendered["body"][1]["items"][0]["id"].as_str().expect("id");
+    assert!(image.starts_with("image-"));
+    assert!(rendered["body"][1]["items"][1].get("id").is_none());
+    let submit = rendered["actions"][0]["id"].as_str().expect("id");
+    assert!(submit.starts_with("action-submit-"));
+
+    let ids = &first.card_features.ids;
+    assert_eq!(ids[heading], "/body/0");
+    assert_eq!(ids["details"], "/body/1");
+    assert_eq!(ids[image], "/body/1/items/0");
+    assert_eq!(ids[submit], "/actions/0");
+    assert_eq!(ids.len(), 4);
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn get_schema_returns_schemas_generated_from_the_model() {
+    let output =
+        component_adaptive_card::handle_message("get-schema", r#"{"schema": "invocation"}"#);
+    let parsed: serde_json::Value = serde_json::from_str(&output).expect("json");
+    let invocation = &parsed["schemas"]["invocation"];
+    assert_eq!(invocation["title"], "AdaptiveCardInvocation");
+    assert!(invocation["properties"]["cardSource"].is_object());
+    assert!(invocation["$defs"]["CardInteraction"].is_object());
+
+    let output = component_adaptive_card::handle_message("get-schema", "{}");
+    let parsed: serde_json::Value = serde_json::from_str(&output).expect("json");
+    let schemas = parsed["schemas"].as_object().expect("schemas");
+    assert_eq!(schemas.keys().map(String::as_str).collect::<Vec<_>>(), {
+        let mut names = component_adaptive_card::SCHEMA_NAMES.to_vec();
+        names.sort();
+        names
+    });
+
+    let output = component_adaptive_card::handle_message("get-schema", r#"{"schema": "card"}"#);
+    let parsed: serde_json::Value = serde_json::from_str(&output).expect("json");
+    assert_eq!(parsed["error"]["code"], "AC_SCHEMA_INVALID");
+
+    // The checked-in copies under schemas/generated must match the model; rerun with
+    // UPDATE_SCHEMAS=1 to regenerate them.
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas/generated");
+    for name in component_adaptive_card::SCHEMA_NAMES {
+        let path = dir.join(format!("{name}.schema.json"));
+        let schema = component_adaptive_card::model_schema(name).expect("known schema");
+        let text = serde_json::to_string_pretty(&schema).expect("json") + "\n";
+        if std::env::var_os("UPDATE_SCHEMAS").is_some() {
+            fs::create_dir_all(&dir).expect("create schema dir");
+            fs::write(&path, text).expect("write schema");
+        } else {
+            let checked_in = fs::read_to_string(&path).unwrap_or_default();
+            assert!(
+                checked_in == text,
+                "{} is out of date; rerun with UPDATE_SCHEMAS=1",
+                path.display()
+            );
+        }
+    }
+}
+
+#[test]
+fn manifest_lists_operations_versions_features_and_functions() {
+    let manifest: serde_json::Value =
+        serde_json::from_str(&component_adaptive_card::describe_payload()).expect("json");
+    let component = &manifest["component"];
+    let operations: Vec<&str> = component["operations"]
+        .as_array()
+        .expect("operations")
+        .iter()
+        .filter_map(|op| op["id"].as_str())
+        .collect();
+    for op in [
+        "card",
+        "continue-stream",
+        "validate",
+        "analyze-template",
+        "compose",
+        "get-schema",
+    ] {
+        assert!(operations.contains(&op), "missing operation {op}");
+    }
+    assert_eq!(component["schema_versions"][0], "1.0");
+    assert_eq!(
+        component["schema_versions"]
+            .as_array()
+            .unwrap()
+            .last()
+            .unwrap(),
+        "1.6"
+    );
+    assert_eq!(component["features"]["markdown"], true);
+    assert_eq!(component["features"]["charts"], true);
+    assert_eq!(
+        component["features"]["preview_svg"],
+        cfg!(feature = "preview")
+    );
+    assert_eq!(
+        component["features"]["asset_store"],
+        cfg!(all(target_arch = "wasm32", feature = "asset-store"))
+    );
+    let functions = component["functions"].as_array().expect("functions");
+    assert!(functions.contains(&json!("formatDate")));
+    assert!(functions.contains(&json!("urlEncode")));
+}
+
+#[test]
+fn health_reports_counters_state_store_and_build_info() {
+    let probe = || {
+        let output = component_adaptive_card::handle_message("health", "");
+        let parsed: serde_json::Value = serde_json::from_str(&output).expect("json");
+        parsed["health"].clone()
+    };
+    let before = probe();
+    let failed = component_adaptive_card::handle_message("card", "not json");
+    assert!(failed.contains("AC_SCHEMA_INVALID"));
+    let after = probe();
+
+    assert!(after["invocations"].as_u64() > before["invocations"].as_u64());
+    assert!(after["failures"].as_u64() > before["failures"].as_u64());
+    assert!(
+        after["recent_errors"]
+            .as_array()
+            .expect("recent errors")
+            .contains(&json!("AC_SCHEMA_INVALID"))
+    );
+    assert_eq!(after["state_store"], "ok");
+    assert_eq!(
+        after["build"]["component"],
+        "ai.greentic.component-adaptive-card"
+    );
+    assert!(after["uptime_ms"].is_u64());
+    assert!(after["caches"].is_object());
+}
+
+#[test]
+fn asset_caches_are_shared_across_threads_and_report_counters() {
+    let invocation = AdaptiveCardInvocation {
+        card_source: CardSource::Asset,
+        card_spec: CardSpec {
+            asset_path: Some("tests/assets/cards/simple.json".to_string()),
+            ..Default::default()
+        },
+        telemetry: TelemetryLevel::Verbose,
+        ..Default::default()
+    };
+    let renders: Vec<_> = (0..8)
+        .map(|_| {
+            let invocation = invocation.clone();
+            std::thread::spawn(move || handle_invocation(invocation).expect("render"))
+        })
+        .collect();
+    for render in renders {
+        let result = render.join().expect("thread");
+        assert_eq!(result.rendered_card.expect("card")["type"], "AdaptiveCard");
+    }
+
+    let result = handle_invocation(invocation).expect("render");
+    let event = result
+        .telemetry_events
+        .iter()
+        .find(|event| event.name == "adaptive_card.cache")
+        .expect("cache event");
+    let templates = &event.properties["templates"];
+    assert!(templates["hits"].as_u64().unwrap() >= 1);
+    assert!(templates["entries"].as_u64().unwrap() >= 1);
+    assert!(templates["evictions"].is_u64());
+
+    let health: serde_json::Value =
+        serde_json::from_str(&component_adaptive_card::handle_message("health", "")).expect("json");
+    assert!(
+        health["health"]["caches"]["templates"]["hits"]
+            .as_u64()
+            .unwrap()
+            >= 1
+    );
+    assert!(health["health"]["caches"]["assets"].is_object());
+}
+
+#[derive(Default)]
+struct MemoryBackend(std::sync::Mutex<std::collections::HashMap<String, serde_json::Value>>);
+
+impl component_adaptive_card::StateBackend for MemoryBackend {
+    async fn load(
+        &self,
+        key: &str,
+    ) -> Result<Option<serde_json::Value>, component_adaptive_card::ComponentError> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    async fn save(
+        &self,
+        key: &str,
+        state: serde_json::Value,
+    ) -> Result<(), component_adaptive_card::ComponentError> {
+        self.0.lock().unwrap().insert(key.to_string(), state);
+        Ok(())
+    }
+}
+
+/// Polls a future that never waits on I/O to completion.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn engine_renders_validates_and_interacts_against_an_async_state_backend() {
+    let engine = component_adaptive_card::AdaptiveCardEngine::with_config(
+        component_adaptive_card::ComponentConfig {
+            locale: Some("fr".into()),
+            ..Default::default()
+        },
+    );
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "${localize('hello')}" },
+            { "type": "TextBlock", "text": "${state.form_data.name || 'Guest'}" },
+            { "type": "Input.Text", "id": "name", "label": "Name" }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "save", "title": "Save" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.state = serde_json::Value::Null;
+    invocation.card_instance_id = Some("engine-card".into());
+    invocation.string_catalogs = [(
+        "fr".to_string(),
+        [("hello".to_string(), "Bonjour".to_string())].into(),
+    )]
+    .into();
+
+    let validated = engine.validate(invocation.clone()).expect("validate");
+    assert!(validated.rendered_card.is_none());
+
+    let backend = MemoryBackend::default();
+    let interaction = CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "save".to_string(),
+        verb: None,
+        raw_inputs: json!({ "name": "Ada" }),
+        card_instance_id: "engine-card".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    };
+    let submitted = block_on(engine.interact_async(invocation.clone(), interaction, &backend))
+        .expect("interact");
+    assert_eq!(submitted.event.expect("event").action_id, "save");
+    let saved = backend.0.lock().unwrap()["adaptive-card:card:engine-card"].clone();
+    assert_eq!(saved["form_data"]["name"], "Ada");
+
+    let rendered = block_on(engine.render_async(invocation, &backend)).expect("render");
+    let card = rendered.rendered_card.expect("card");
+    assert_eq!(card["body"][0]["text"], "Bonjour");
+    assert_eq!(card["body"][1]["text"], "Ada");
+}
+
+#[test]
+fn engine_migrates_backend_state_and_keeps_nothing_in_the_component_store() {
+    let engine = component_adaptive_card::AdaptiveCardEngine::new();
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "$state": {
+            "version": 2,
+            "migrations": {
+                "1": [{ "Rename": { "from": "form_data.amount", "to": "form_data.total" } }]
+            }
+        },
+        "body": [
+            { "type": "TextBlock", "text": "${state.form_data.total}" },
+            { "type": "Input.Text", "id": "note" }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "save", "title": "Save" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.state = serde_json::Value::Null;
+    invocation.card_instance_id = Some("engine-migrated".into());
+    invocation.rate_limit = Some(RateLimit {
+        max_interactions: 1,
+        window_seconds: 60,
+        notice: None,
+    });
+    let backend = MemoryBackend::default();
+    backend.0.lock().unwrap().insert(
+        "adaptive-card:card:engine-migrated".into(),
+        json!({ "form_data": { "amount": "42" } }),
+    );
+    let interaction = |note: &str| CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "save".to_string(),
+        verb: None,
+        raw_inputs: json!({ "note": note }),
+        card_instance_id: "engine-migrated".to_string(),
+        metadata: json!({}),
+        idempotency_key: Some("same-key".into()),
+        dialog_id: None,
+    };
+
+    let rendered = block_on(engine.render_async(invocation.clone(), &backend)).expect("render");
+    assert_eq!(
+        rendered.rendered_card.expect("card")["body"][0]["text"],
+        "42"
+    );
+
+    for note in ["first", "second"] {
+        let result =
+            block_on(engine.interact_async(invocation.clone(), interaction(note), &backend))
+                .expect("interact");
+        assert_eq!(result.rendered_card.expect("card")["body"][0]["text"], "42");
+        let saved = backend.0.lock().unwrap()["adaptive-card:card:engine-migrated"].clone();
+        assert_eq!(saved["form_data"]["total"], "42");
+        assert_eq!(saved["form_data"]["note"], note);
+        assert!(saved["form_data"].get("amount").is_none());
+        assert_eq!(saved["$stateVersion"], 2);
+    }
+
+    // The component's own store holds neither the state nor a replay or rate-limit record.
+    let mut local = invocation;
+    local.interaction = Some(interaction("third"));
+    let Err(component_adaptive_card::ComponentError::Binding(message)) = handle_invocation(local)
+    else {
+        panic!("the state is not in the component store");
+    };
+    assert!(message.contains("state.form_data.total"));
+}
+
+#[test]
+fn value_and_raw_entry_points_match_the_string_interface() {
+    let input = json!({
+        "card_source": "inline",
+        "card_spec": { "inline_json": {
+            "type": "AdaptiveCard",
+            "version": "1.6",
+            "body": [{ "type": "TextBlock", "id": "title", "text": "${payload.title}" }]
+        } },
+        "payload": { "title": "Hello", "rows": vec![json!({ "n": 1 }); 1000] },
+        "telemetry": "off"
+    });
+    let via_string: serde_json::Value = serde_json::from_str(
+        &component_adaptive_card::handle_message("card", &input.to_string()),
+    )
+    .expect("json");
+    let via_value = component_adaptive_card::handle_invocation_value(input.clone());
+    assert_eq!(via_value, via_string);
+    assert_eq!(via_value["renderedCard"]["body"][0]["text"], "Hello");
+
+    let raw = serde_json::value::to_raw_value(&input).expect("raw");
+    let via_raw = component_adaptive_card::handle_message_raw("card", &raw);
+    let via_raw: serde_json::Value = serde_json::from_str(via_raw.get()).expect("json");
+    assert_eq!(via_raw, via_string);
+
+    let failed = component_adaptive_card::handle_message_value("card", json!("not an invocation"));
+    assert_eq!(failed["error"]["code"], "AC_SCHEMA_INVALID");
+}
+
+#[test]
+fn large_payload_subtrees_bind_without_changing_the_output() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            {
+                "type": "Container",
+                "id": "rows",
+                "pageSize": 2,
+                "items": "${payload.rows}",
+                "itemTemplate": { "type": "TextBlock", "text": "${item.name}" }
+            },
+            {
+                "type": "Input.ChoiceSet",
+                "id": "pick",
+                "choices": "${payload.choices}"
+            }
+        ]
+    });
+    let rows: Vec<_> = (0..5000)
+        .map(|n| json!({ "name": format!("Row {n}"), "blob": "x".repeat(64) }))
+        .collect();
+    let choices: Vec<_> = (0..500)
+        .map(|n| json!({ "title": format!("Choice {n}"), "value": n.to_string() }))
+        .collect();
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "rows": rows, "choices": choices });
+
+    let rendered = handle_invocation(invocation.clone())
+        .expect("render")
+        .rendered_card
+        .expect("card");
+    let items = rendered["body"][0]["items"].as_array().unwrap();
+    assert_eq!(items[0]["text"], "Row 0");
+    assert_eq!(items[1]["text"], "Row 1");
+    assert_eq!(
+        rendered["body"][1]["choices"],
+        invocation.payload["choices"]
+    );
+    // The invocation's payload is read, not consumed or rewritten.
+    assert_eq!(invocation.payload["rows"].as_array().unwrap().len(), 5000);
+}
+
+#[test]
+fn limits_fail_deep_nesting_complex_expressions_and_large_repeaters() {
+    let failure = |card: serde_json::Value, extra: serde_json::Value| {
+        let mut input = json!({
+            "card_source": "inline",
+            "card_spec": { "inline_json": card },
+            "payload": { "flag": true, "rows": vec![json!({ "n": 1 }); 20] }
+        });
+        input
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        let response: serde_json::Value = serde_json::from_str(
+            &component_adaptive_card::handle_message("card", &input.to_string()),
+        )
+        .expect("json");
+        response["error"].clone()
+    };
+    let card =
+        |body: serde_json::Value| json!({ "type": "AdaptiveCard", "version": "1.5", "body": body });
+
+    let mut nested = json!({ "type": "TextBlock", "text": "deep" });
+    for _ in 0..40 {
+        nested = json!({ "type": "Container", "items": [nested] });
+    }
+    let error = failure(card(json!([nested.clone()])), json!({}));
+    assert_eq!(error["code"], "AC_LIMIT_EXCEEDED");
+    assert_eq!(error["path"], "/limits/maxNesting");
+    assert_eq!(error["details"]["max"], 32);
+    let allowed = failure(
+        card(json!([nested])),
+        json!({ "limits": { "maxNesting": 64 } }),
+    );
+    assert!(allowed.is_null(), "{allowed}");
+
+    let chain = (0..30).fold("'end'".to_string(), |rest, _| {
+        format!("payload.flag == false ? 'x' : {rest}")
+    });
+    let text = json!([{ "type": "TextBlock", "text": format!("${{{chain}}}") }]);
+    let error = failure(card(text.clone()), json!({}));
+    assert_eq!(error["path"], "/limits/maxExpressionNodes");
+    let error = failure(
+        card(text),
+        json!({ "limits": { "maxExpressionLength": 64 } }),
+    );
+    assert_eq!(error["path"], "/limits/maxExpressionLength");
+
+    let repeater = json!([{
+        "type": "Container",
+        "id": "rows",
+        "pageSize": 20,
+        "items": "${payload.rows}",
+        "itemTemplate": { "type": "TextBlock", "text": "${item.n}" }
+    }]);
+    let error = failure(
+        card(repeater.clone()),
+        json!({ "limits": { "max_repeat": 10 } }),
+    );
+    assert_eq!(error["path"], "/limits/maxRepeat");
+    assert!(failure(card(repeater), json!({})).is_null());
+}
+
+#[test]
+fn time_budget_aborts_or_returns_the_partially_bound_card() {
+    let mut invocation = base_invocation(json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "${payload.title}" },
+            { "type": "TextBlock", "text": "${payload.title}" }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "ok", "title": "OK" }]
+    }));
+    invocation.payload = json!({ "title": "Report" });
+
+    invocation.time_budget = Some(component_adaptive_card::TimeBudget {
+        max_ms: 60_000,
+        on_exceeded: component_adaptive_card::TimeBudgetPolicy::Error,
+    });
+    let rendered = handle_invocation(invocation.clone()).expect("within budget");
+    assert_eq!(
+        rendered.rendered_card.expect("card")["body"][1]["text"],
+        "Report"
+    );
+
+    invocation.time_budget = Some(component_adaptive_card::TimeBudget {
+        max_ms: 0,
+        on_exceeded: component_adaptive_card::TimeBudgetPolicy::Error,
+    });
+    let error = handle_invocation(invocation.clone()).expect_err("out of time");
+    let failure = error.to_failure();
+    assert_eq!(
+        serde_json::to_value(failure.code).unwrap(),
+        json!("AC_TIMEOUT")
+    );
+    assert!(failure.retryable);
+
+    invocation.time_budget = Some(component_adaptive_card::TimeBudget {
+        max_ms: 0,
+        on_exceeded: component_adaptive_card::TimeBudgetPolicy::Partial,
+    });
+    let partial = handle_invocation(invocation).expect("partial");
+    let card = partial.rendered_card.expect("card");
+    assert_eq!(card["body"], json!([]));
+    assert!(
+        card.get("actions")
+            .is_none_or(|actions| actions == &json!([]))
+    );
+    let issue = partial
+        .validation_issues
+        .iter()
+        .find(|issue| issue.code == "time-budget-exceeded")
+        .expect("warning");
+    assert!(issue.message.contains("3 unbound"), "{}", issue.message);
+}
+
+#[test]
+fn associated_inputs_scope_submitted_inputs_to_the_action_card() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "Input.Text", "id": "name" }],
+        "actions": [
+            { "type": "Action.Submit", "id": "save", "title": "Save" },
+            {
+                "type": "Action.Submit",
+                "id": "cancel",
+                "title": "Cancel",
+                "associatedInputs": "none",
+                "data": { "reason": "cancelled" }
+            },
+            {
+                "type": "Action.ShowCard",
+                "id": "comment",
+                "title": "Comment",
+                "card": {
+                    "type": "AdaptiveCard",
+                    "body": [
+                        { "type": "Input.Text", "id": "comment" },
+                        { "type": "Input.Number", "id": "score" }
+                    ],
+                    "actions": [{ "type": "Action.Submit", "id": "send", "title": "Send" }]
+                }
+            }
+        ]
+    });
+    let submit = |action_id: &str| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: action_id.to_string(),
+            verb: None,
+            raw_inputs: json!({ "name": "Ada", "comment": "Nice", "score": "lots" }),
+            card_instance_id: "scoped".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("interaction")
+    };
+
+    let saved = submit("save");
+    assert_eq!(saved.event.expect("event").inputs, json!({ "name": "Ada" }));
+    assert!(
+        !saved
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "input-coercion-failed")
+    );
+
+    let cancelled = submit("cancel");
+    assert_eq!(cancelled.event.expect("event").inputs, json!({}));
+
+    let sent = submit("send");
+    assert_eq!(
+        sent.event.expect("event").inputs,
+        json!({ "name": "Ada", "comment": "Nice", "score": "lots" })
+    );
+    assert!(
+        sent.validation_issues
+            .iter()
+            .any(|issue| issue.code == "input-coercion-failed" && issue.path == "/inputs/score")
+    );
+}
+
+#[test]
+fn namespaced_item_inputs_avoid_collisions_and_regroup_on_submit() {
+    let rows = |namespace: Option<&str>| {
+        let mut container = json!({
+            "type": "Container",
+            "id": "lines",
+            "pageSize": 2,
+            "items": "${payload.lines}",
+            "itemTemplate": {
+                "type": "ColumnSet",
+                "columns": [
+                    { "type": "Column", "items": [{ "type": "TextBlock", "text": "${item.sku}" }] },
+                    { "type": "Column", "items": [{ "type": "Input.Number", "id": "qty", "value": "${item.qty}" }] }
+                ]
+            }
+        });
+        if let Some(namespace) = namespace {
+            container["inputNamespace"] = json!(namespace);
+        }
+        json!({
+            "type": "AdaptiveCard",
+            "version": "1.5",
+            "body": [container],
+            "actions": [{ "type": "Action.Submit", "id": "order", "title": "Order" }]
+        })
+    };
+    let invocation_with = |card: serde_json::Value, state: serde_json::Value| {
+        let mut invocation = base_invocation(card);
+        invocation.payload = json!({ "lines": [
+            { "sku": "A", "qty": 1 }, { "sku": "B", "qty": 2 }, { "sku": "C", "qty": 3 }
+        ] });
+        invocation.state = state;
+        invocation
+    };
+
+    let colliding = handle_invocation(invocation_with(rows(None), json!({}))).expect("render");
+    let duplicate = colliding
+        .validation_issues
+        .iter()
+        .find(|issue| issue.code == "duplicate-id")
+        .expect("collision reported");
+    assert!(
+        duplicate
+            .message
+            .contains("Input id qty is already used at /body/0/items/0")
+    );
+
+    let namespaced =
+        handle_invocation(invocation_with(rows(Some("lines")), json!({}))).expect("render");
+    assert!(
+        !namespaced
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "duplicate-id")
+    );
+    let card = namespaced.rendered_card.expect("card");
+    assert_eq!(
+        card["body"][0]["items"][1]["columns"][1]["items"][0]["id"],
+        "lines[1].qty"
+    );
+
+    let mut submit = invocation_with(
+        rows(Some("lines")),
+        json!({ "ui": { "pages": { "lines": 1 } } }),
+    );
+    submit.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "order".to_string(),
+        verb: None,
+        raw_inputs: json!({ "lines[2].qty": "5", "note": "rush" }),
+        card_instance_id: "order-card".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let event = handle_invocation(submit)
+        .expect("submit")
+        .event
+        .expect("event");
+    assert_eq!(
+        event.inputs,
+        json!({ "lines": [null, null, { "qty": 5 }], "note": "rush" })
+    );
+}
+
+#[test]
+fn derived_state_is_computed_after_interaction_updates() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "$derived": {
+            "state.totals.sum": "sum(state.form_data.items[].price)",
+            "totals.count": "${count(state.form_data.items)}",
+            "totals.label": "state.totals.count == 2 ? 'pair' : 'other'",
+            "totals.broken": "unknownFunction(state.form_data)"
+        },
+        "body": [{ "type": "TextBlock", "text": "Order" }],
+        "actions": [{ "type": "Action.Submit", "id": "save", "title": "Save" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.state = json!({ "totals": { "count": 2 } });
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "save".to_string(),
+        verb: None,
+        raw_inputs: json!({ "items": [{ "price": 2.5 }, { "price": 4 }] }),
+        card_instance_id: "derived-card".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let result = handle_invocation(invocation).expect("interaction");
+
+    let sets: Vec<_> = result
+        .state_updates
+        .iter()
+        .filter_map(|op| match op {
+            component_adaptive_card::StateUpdateOp::Set { path, value } => {
+                Some((path.as_str(), value.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    // `totals.count` is unchanged, so only the sum and label are emitted.
+    assert_eq!(
+        sets,
+        vec![("totals.label", json!("pair")), ("totals.sum", json!(6.5))]
+    );
+    assert!(result.validation_issues.iter().any(
+        |issue| issue.code == "derived-state-failed" && issue.path == "/$derived/totals.broken"
+    ));
+    assert!(
+        result
+            .rendered_card
+            .expect("card")
+            .get("$derived")
+            .is_none()
+    );
+}
+
+#[test]
+fn is_enabled_expressions_disable_actions_and_reject_their_submits() {
+    let card = |version: &str| {
+        json!({
+            "type": "AdaptiveCard",
+            "version": version,
+            "body": [{ "type": "Input.Text", "id": "comment", "label": "Comment" }],
+            "actions": [
+                {
+                    "type": "Action.Submit",
+                    "id": "approve",
+                    "title": "Approve",
+                    "isEnabled": "${state.form_data.comment || false}"
+                },
+                { "type": "Action.Submit", "id": "reject", "title": "Reject" }
+            ]
+        })
+    };
+    let submit = |card: serde_json::Value, state: serde_json::Value| {
+        let mut invocation = base_invocation(card);
+        invocation.state = state;
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "approve".to_string(),
+            verb: None,
+            raw_inputs: json!({ "comment": "Looks good" }),
+            card_instance_id: "enablement-card".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation)
+    };
+
+    let blank = json!({ "form_data": { "comment": "" } });
+    let rendered = handle_invocation(base_invocation(card("1.6")))
+        .expect("render")
+        .rendered_card
+        .expect("card");
+    assert_eq!(rendered["actions"][0]["isEnabled"], false);
+    assert!(rendered["actions"][1].get("isEnabled").is_none());
+    assert!(matches!(
+        submit(card("1.6"), blank.clone()),
+        Err(component_adaptive_card::ComponentError::InteractionInvalid(message))
+            if message == "action approve is disabled"
+    ));
+
+    let commented = json!({ "form_data": { "comment": "Ship it" } });
+    let result = submit(card("1.6"), commented).expect("enabled submit");
+    assert_eq!(
+        result.rendered_card.expect("card")["actions"][0]["isEnabled"],
+        true
+    );
+
+    let mut legacy = base_invocation(card("1.4"));
+    legacy.state = blank;
+    let actions = &handle_invocation(legacy)
+        .expect("legacy render")
+        .rendered_card
+        .expect("card")["actions"];
+    assert_eq!(actions.as_array().map(Vec::len), Some(1));
+    assert_eq!(actions[0]["id"], "reject");
+}
+
+#[test]
+fn cross_input_rules_hold_back_submits_and_render_inline_errors() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "$rules": [
+            {
+                "rule": "${inputs.endDate >= inputs.startDate}",
+                "message": "The end date must not be before the start date.",
+                "inputs": ["endDate"]
+            },
+            { "rule": "inputs.nights != 0", "message": "Book at least one night.", "inputs": ["nights"] }
+        ],
+        "body": [
+            { "type": "Input.Date", "id": "startDate", "label": "From" },
+            { "type": "Input.Date", "id": "endDate", "label": "To" },
+            { "type": "Input.Number", "id": "nights", "label": "Nights" }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "book", "title": "Book" }]
+    });
+    let submit = |state: serde_json::Value, inputs: serde_json::Value| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.state = state;
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "book".to_string(),
+            verb: None,
+            raw_inputs: inputs,
+            card_instance_id: "rules-card".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        invocation
+    };
+
+    let invalid = json!({ "startDate": "2024-05-10", "endDate": "2024-05-08", "nights": "2" });
+    let rejected = handle_invocation(submit(json!({}), invalid.clone())).expect("rejected");
+    assert!(rejected.event.is_none());
+    assert!(rejected.validation_issues.iter().any(|issue| {
+        issue.code == "input-rule-failed"
+            && issue.path == "/inputs/endDate"
+            && issue.message == "The end date must not be before the start date."
+    }));
+    let body = &rejected.rendered_card.expect("card")["body"];
+    assert_eq!(body[1]["value"], "2024-05-08");
+    assert_eq!(body[2]["id"], "endDate-error");
+    assert_eq!(body[2]["color"], "attention");
+    assert_eq!(
+        body[2]["text"],
+        "The end date must not be before the start date."
+    );
+    assert_eq!(body[3]["id"], "nights");
+    let mut state = json!({});
+    for op in &rejected.state_updates {
+        if let StateUpdateOp::Set { path, value } = op {
+            assert_eq!(path, "ui.input_errors.rules-card");
+            state["ui"] = json!({ "input_errors": { "rules-card": value } });
+        }
+    }
+
+    let valid = json!({ "startDate": "2024-05-10", "endDate": "2024-05-12", "nights": "2" });
+    let accepted = handle_invocation(submit(state, valid)).expect("accepted");
+    assert!(accepted.event.is_some());
+    assert!(accepted.state_updates.iter().any(|op| matches!(
+        op,
+        StateUpdateOp::Delete { path } if path == "ui.input_errors.rules-card"
+    )));
+    let body = &accepted.rendered_card.expect("card")["body"];
+    assert_eq!(body.as_array().map(Vec::len), Some(3));
+
+    let mut strict = submit(json!({}), invalid);
+    strict.validation_mode = ValidationMode::Error;
+    assert!(matches!(
+        handle_invocation(strict),
+        Err(component_adaptive_card::ComponentError::CardValidation(issues)) if issues.len() == 1
+    ));
+}
+
+#[test]
+fn cross_input_rules_read_bare_ids_combine_clauses_and_fail_closed() {
+    let check = |rule: &str, inputs: serde_json::Value| {
+        let mut invocation = base_invocation(json!({
+            "type": "AdaptiveCard",
+            "version": "1.5",
+            "$rules": [{ "rule": rule, "message": "Check the dates.", "inputs": ["endDate"] }],
+            "body": [
+                { "type": "Input.Date", "id": "startDate" },
+                { "type": "Input.Date", "id": "endDate" },
+                { "type": "Input.Toggle", "id": "agree", "title": "Agree" }
+            ],
+            "actions": [{ "type": "Action.Submit", "id": "book", "title": "Book" }]
+        }));
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "book".to_string(),
+            verb: None,
+            raw_inputs: inputs,
+            card_instance_id: "rules-clauses".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        let result = handle_invocation(invocation).expect("submit");
+        result
+            .validation_issues
+            .iter()
+            .find(|issue| issue.code.starts_with("input-rule"))
+            .map(|issue| issue.code.clone())
+    };
+    let backwards = json!({ "startDate": "2024-05-10", "endDate": "2024-05-08", "agree": "true" });
+    let forwards = json!({ "startDate": "2024-05-10", "endDate": "2024-05-12", "agree": "true" });
+    let broken = Some("input-rule-failed".to_string());
+
+    assert_eq!(check("endDate >= startDate", backwards.clone()), broken);
+    assert_eq!(check("endDate >= startDate", forwards.clone()), None);
+    let both = "${inputs.agree == true && inputs.endDate >= inputs.startDate}";
+    assert_eq!(check(both, backwards.clone()), broken);
+    assert_eq!(check(both, forwards.clone()), None);
+    let either = "(agree == false) || (endDate >= startDate)";
+    assert_eq!(check(either, backwards.clone()), broken);
+    assert_eq!(check(either, forwards.clone()), None);
+
+    // A rule reading an input left empty holds; one that cannot be evaluated otherwise fails.
+    assert_eq!(
+        check("endDate >= startDate", json!({ "startDate": "2024-05-10" })),
+        None
+    );
+    assert_eq!(
+        check("endDate >= startdate", forwards.clone()),
+        Some("input-rule-invalid".to_string())
+    );
+    assert_eq!(
+        check("endDate >= lenght(startDate)", forwards),
+        Some("input-rule-invalid".to_string())
+    );
+}
+
+#[test]
+fn submitted_values_prefill_the_re_rendered_inputs() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "Input.Text", "id": "name", "value": "Template default" },
+            { "type": "Input.Toggle", "id": "subscribe", "title": "News", "valueOn": "yes", "valueOff": "no" },
+            {
+                "type": "Input.ChoiceSet",
+                "id": "topics",
+                "isMultiSelect": true,
+                "choices": [
+                    { "title": "Rust", "value": "rust" },
+                    { "title": "Wasm", "value": "wasm" }
+                ]
+            },
+            { "type": "Input.Number", "id": "seats" },
+            { "type": "Input.Text", "id": "notes", "value": "Cleared" }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "save", "title": "Save" }]
+    });
+    let submit = |inputs: serde_json::Value, preserve: bool| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.preserve_inputs = preserve;
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "save".to_string(),
+            verb: None,
+            raw_inputs: inputs,
+            card_instance_id: "prefill-card".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation)
+            .expect("submit")
+            .rendered_card
+            .expect("card")
+    };
+    let entries = |seats: &str| {
+        json!({
+            "name": "Ada",
+            "subscribe": "yes",
+            "topics": "rust,wasm",
+            "seats": seats,
+            "notes": ""
+        })
+    };
+
+    // A value that fails coercion keeps every entry as submitted.
+    let invalid = submit(entries("lots"), false);
+    assert_eq!(invalid["body"][0]["value"], "Ada");
+    assert_eq!(invalid["body"][1]["value"], "yes");
+    assert_eq!(invalid["body"][2]["value"], "rust,wasm");
+    assert_eq!(invalid["body"][3]["value"], "lots");
+    assert_eq!(invalid["body"][4]["value"], "");
+
+    let preserved = submit(entries("3"), true);
+    assert_eq!(preserved["body"][0]["value"], "Ada");
+    assert_eq!(preserved["body"][3]["value"], 3);
+
+    let plain = submit(entries("3"), false);
+    assert_eq!(plain["body"][0]["value"], "Template default");
+    assert!(plain["body"][3].get("value").is_none());
+}
+
+#[test]
+fn audit_log_records_hashed_inputs_and_the_outcome() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "Input.Text", "id": "email" },
+            { "type": "Input.Text", "id": "passcode" }
+        ],
+        "actions": [
+            { "type": "Action.Execute", "id": "register", "title": "Register", "verb": "signup" }
+        ]
+    });
+    let interact = |action: &str, kind: CardInteractionType, audit: bool| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.viewer = Some(CardViewer {
+            id: "user-7".to_string(),
+            ..Default::default()
+        });
+        invocation.audit_log = audit.then(|| component_adaptive_card::AuditLogConfig {
+            redact_inputs: vec!["pass*".to_string()],
+        });
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: kind,
+            action_id: action.to_string(),
+            verb: None,
+            raw_inputs: json!({ "email": "ada@example.com", "passcode": "1234" }),
+            card_instance_id: "audit-card".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("interaction")
+    };
+
+    let record = interact("register", CardInteractionType::Execute, true)
+        .audit
+        .expect("audit record");
+    assert_eq!(record.actor.as_deref(), Some("user-7"));
+    assert_eq!(record.card_instance_id, "audit-card");
+    assert_eq!(record.action_id, "register");
+    assert_eq!(record.verb.as_deref(), Some("signup"));
+    assert_eq!(
+        record.outcome,
+        component_adaptive_card::AuditOutcome::Accepted
+    );
+    assert!(record.timestamp_ms > 0);
+    assert!(record.inputs["email"].starts_with("blake3:"));
+    assert!(!record.inputs["email"].contains("ada@example.com"));
+    assert_eq!(record.inputs["passcode"], "redacted");
+
+    let drafted = interact("email", CardInteractionType::InputChanged, true)
+        .audit
+        .expect("audit record");
+    assert_eq!(
+        drafted.outcome,
+        component_adaptive_card::AuditOutcome::Handled
+    );
+
+    assert!(
+        interact("register", CardInteractionType::Execute, false)
+            .audit
+            .is_none()
+    );
+}
+
+#[test]
+fn redaction_policy_hashes_or_masks_inputs_before_state_and_events() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "Input.Text", "id": "name" },
+            { "type": "Input.Text", "id": "email" }
+        ],
+        "actions": [{
+            "type": "Action.Submit",
+            "id": "send",
+            "title": "Send"
+        }]
+    });
+    let submit = |mode: component_adaptive_card::RedactionMode, key: Option<&str>| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.redaction = Some(component_adaptive_card::RedactionPolicy {
+            inputs: vec!["e*".to_string()],
+            paths: vec!["contact.phone".to_string()],
+            mode,
+            key: key.map(str::to_string),
+        });
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "send".to_string(),
+            verb: None,
+            raw_inputs: json!({
+                "name": "Ada",
+                "email": "ada@example.com",
+                "contact": { "phone": "555-0100", "channel": "sms" }
+            }),
+            card_instance_id: "redaction-card".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("submit")
+    };
+
+    let hashed = submit(
+        component_adaptive_card::RedactionMode::Hash,
+        Some("host-secret"),
+    );
+    let inputs = hashed.event.expect("event").inputs;
+    assert_eq!(inputs["name"], "Ada");
+    assert_eq!(inputs["contact"]["channel"], "sms");
+    assert!(
+        inputs["email"]
+            .as_str()
+            .is_some_and(|email| email.starts_with("blake3:"))
+    );
+    assert!(
+        inputs["contact"]["phone"]
+            .as_str()
+            .is_some_and(|phone| phone.starts_with("blake3:"))
+    );
+    let stored = hashed
+        .state_updates
+        .iter()
+        .find_map(|op| match op {
+            StateUpdateOp::Merge { path, value } if path == "form_data" => Some(value.clone()),
+            _ => None,
+        })
+        .expect("form_data merge");
+    assert_eq!(stored, inputs);
+    // The hash is keyed: a plain hash of a guessed address does not match it.
+    assert_ne!(
+        inputs["email"],
+        format!(
+            "blake3:{}",
+            blake3::hash(&serde_json::to_vec(&json!("ada@example.com")).unwrap()).to_hex()
+        )
+    );
+    let rekeyed = submit(
+        component_adaptive_card::RedactionMode::Hash,
+        Some("other-secret"),
+    );
+    assert_ne!(
+        rekeyed.event.expect("event").inputs["email"],
+        inputs["email"]
+    );
+    let unkeyed = submit(component_adaptive_card::RedactionMode::Hash, None);
+    assert_eq!(unkeyed.event.expect("event").inputs["email"], "redacted");
+
+    let masked = submit(component_adaptive_card::RedactionMode::Mask, None)
+        .event
+        .expect("event")
+        .inputs;
+    assert_eq!(masked["email"], "***********.com");
+    assert_eq!(masked["contact"]["phone"], "********");
+}
+
+#[test]
+fn redacted_inputs_stay_out_of_receipts_and_recorded_results() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "Input.Text", "id": "name", "label": "Name" },
+            { "type": "Input.Text", "id": "email", "label": "Email" }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "send", "title": "Send" }]
+    });
+    let submit = |inputs: serde_json::Value, render_receipt: bool| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.render_receipt = render_receipt;
+        invocation.preserve_inputs = !render_receipt;
+        invocation.redaction = Some(component_adaptive_card::RedactionPolicy {
+            inputs: vec!["email".to_string()],
+            key: Some("host-secret".to_string()),
+            ..Default::default()
+        });
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Submit,
+            action_id: "send".to_string(),
+            verb: None,
+            raw_inputs: inputs,
+            card_instance_id: "redaction-receipt".to_string(),
+            metadata: json!({}),
+            idempotency_key: Some(format!("redaction-{render_receipt}")),
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("submit")
+    };
+    let raw = json!({ "name": "Alice", "email": "alice@example.com" });
+
+    for render_receipt in [true, false] {
+        let first = submit(raw.clone(), render_receipt);
+        let card = serde_json::to_string(&first.rendered_card).unwrap();
+        assert!(card.contains("Alice"));
+        assert!(!card.contains("alice@example.com"));
+
+        let replay = submit(json!({ "name": "Bob" }), render_receipt);
+        let replayed = serde_json::to_string(&replay).unwrap();
+        assert!(replayed.contains("Alice"));
+        assert!(!replayed.contains("alice@example.com"));
+    }
+}
+
+#[test]
+fn data_classifications_flow_into_event_metadata() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "Input.Text", "id": "name", "dataClassification": "internal" },
+            { "type": "Input.Text", "id": "email", "dataClassification": "pii" },
+            { "type": "Input.Text", "id": "token", "dataClassification": "secret" },
+            { "type": "Input.Text", "id": "note", "dataClassification": "confidential" },
+            { "type": "Input.Text", "id": "topic" }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "send", "title": "Send" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "send".to_string(),
+        verb: None,
+        raw_inputs: json!({ "name": "Ada", "email": "ada@example.com", "topic": "rust" }),
+        card_instance_id: "classified-card".to_string(),
+        metadata: json!({ "source": "teams" }),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let result = handle_invocation(invocation).expect("submit");
+
+    let metadata = result.event.expect("event").metadata;
+    assert_eq!(metadata["source"], "teams");
+    assert_eq!(
+        metadata["dataClassification"],
+        json!({ "name": "internal", "email": "pii" })
+    );
+    assert!(result.validation_issues.iter().any(|issue| {
+        issue.code == "invalid-data-classification" && issue.path == "/body/3/dataClassification"
+    }));
+    let body = &result.rendered_card.expect("card")["body"];
+    assert!(
+        body.as_array()
+            .expect("body")
+            .iter()
+            .all(|input| input.get("dataClassification").is_none())
+    );
+}
+
+#[test]
+fn outlook_profile_emits_actionable_messages() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "TextBlock", "text": "Expense report" },
+            { "type": "Input.Text", "id": "comment" },
+            { "type": "Table", "columns": [{ "width": 1 }], "rows": [], "fallback": "drop" }
+        ],
+        "actions": [
+            { "type": "Action.Execute", "id": "approve", "title": "Approve", "verb": "approve", "data": { "report": 42 } },
+            { "type": "Action.OpenUrl", "id": "open", "title": "Open", "url": "https://example.com/r/42" }
+        ]
+    });
+    let render = |profile: TargetProfile| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.target_profile = Some(profile);
+        handle_invocation(invocation).expect("render")
+    };
+
+    let result = render(TargetProfile {
+        host: Some(HostPlatform::Outlook),
+        originator: Some("8a1b6a0e-provider".to_string()),
+        action_url: Some("https://flows.example.com/outlook".to_string()),
+        ..Default::default()
+    });
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["version"], "1.4");
+    assert_eq!(rendered["originator"], "8a1b6a0e-provider");
+    assert_eq!(rendered["body"].as_array().map(Vec::len), Some(2));
+    let approve = &rendered["actions"][0];
+    assert_eq!(approve["type"], "Action.Http");
+    assert_eq!(approve["method"], "POST");
+    assert_eq!(approve["url"], "https://flows.example.com/outlook");
+    assert!(approve.get("verb").is_none());
+    let body: serde_json::Value =
+        serde_json::from_str(approve["body"].as_str().expect("body")).expect("json body");
+    assert_eq!(
+        body,
+        json!({
+            "actionId": "approve",
+            "verb": "approve",
+            "data": { "report": 42 },
+            "inputs": { "comment": "{{comment.value}}" }
+        })
+    );
+    assert_eq!(rendered["actions"][1]["type"], "Action.OpenUrl");
+    assert!(
+        !result
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code.starts_with("outlook-"))
+    );
+
+    let unconfigured = render(TargetProfile {
+        host: Some(HostPlatform::Outlook),
+        ..Default::default()
+    });
+    let codes: Vec<_> = unconfigured
+        .validation_issues
+        .iter()
+        .map(|issue| issue.code.as_str())
+        .collect();
+    assert!(codes.contains(&"outlook-originator-missing"));
+    assert!(codes.contains(&"outlook-action-url-missing"));
+}
+
+#[test]
+fn bot_activity_wraps_the_rendered_card_in_an_attachment() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "Build ${payload.build} passed" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "build": 118 });
+    invocation.bot_activity = Some(component_adaptive_card::BotActivityOptions {
+        summary: Some("Build passed".to_string()),
+        fallback_text: None,
+    });
+    let result = handle_invocation(invocation.clone()).expect("render");
+    let activity = serde_json::to_value(result.activity.expect("activity")).expect("json");
+    assert_eq!(
+        activity,
+        json!({
+            "type": "message",
+            "summary": "Build passed",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "content": {
+                    "type": "AdaptiveCard",
+                    "version": "1.5",
+                    "body": [{ "type": "TextBlock", "text": "Build 118 passed" }],
+                    "fallbackText": "Build 118 passed."
+                }
+            }]
+        })
+    );
+    assert!(
+        result
+            .rendered_card
+            .expect("card")
+            .get("fallbackText")
+            .is_none()
+    );
+
+    invocation.bot_activity = Some(component_adaptive_card::BotActivityOptions {
+        summary: None,
+        fallback_text: Some("Open Teams to see the build".to_string()),
+    });
+    let activity = handle_invocation(invocation).expect("render").activity;
+    assert_eq!(
+        activity.expect("activity").attachments[0].content["fallbackText"],
+        "Open Teams to see the build"
+    );
+}
+
+#[test]
+fn whatsapp_target_transcodes_choice_sets_into_list_messages() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "Lunch order", "style": "heading" },
+            { "type": "TextBlock", "text": "Pick a **main** for ${payload.day}" },
+            { "type": "Input.Text", "id": "notes" },
+            {
+                "type": "Input.ChoiceSet",
+                "id": "main",
+                "label": "Mains",
+                "choices": [
+                    { "title": "Pasta", "value": "pasta" },
+                    { "title": "Soup of the day", "value": "soup" }
+                ]
+            }
+        ],
+        "actions": [{ "type": "Action.Submit", "id": "order", "title": "Order" }]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "day": "Friday" });
+    invocation.target = Some(component_adaptive_card::OutputTarget::WhatsApp);
+    let result = handle_invocation(invocation.clone()).expect("render");
+    let transcoded = serde_json::to_value(result.transcoded.expect("transcoded")).expect("json");
+    assert_eq!(
+        transcoded,
+        json!({
+            "target": "whatsapp",
+            "content": {
+                "messaging_product": "whatsapp",
+                "recipient_type": "individual",
+                "type": "interactive",
+                "interactive": {
+                    "type": "list",
+                    "header": { "type": "text", "text": "Lunch order" },
+                    "body": { "text": "Pick a main for Friday" },
+                    "action": {
+                        "button": "Mains",
+                        "sections": [{
+                            "title": "Mains",
+                            "rows": [
+                                { "id": "submit:order?main=pasta", "title": "Pasta" },
+                                { "id": "submit:order?main=soup", "title": "Soup of the day" }
+                            ]
+                        }]
+                    }
+                }
+            }
+        })
+    );
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| { issue.code == "transcode-unsupported" && issue.path == "/body/2" })
+    );
+
+    let reply = component_adaptive_card::reply_interaction("submit:order?main=soup", "lunch-1")
+        .expect("reply id");
+    assert_eq!(reply.interaction_type, CardInteractionType::Submit);
+    assert_eq!(reply.action_id, "order");
+    invocation.target = None;
+    invocation.interaction = Some(reply);
+    let result = handle_invocation(invocation).expect("interaction");
+    assert_eq!(result.event.expect("event").inputs["main"], "soup");
+    assert!(component_adaptive_card::reply_interaction("order", "lunch-1").is_none());
+}
+
+#[test]
+fn whatsapp_target_gives_id_less_actions_unique_routable_reply_ids() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "Approve the change?" }],
+        "actions": [
+            { "type": "Action.Execute", "title": "Approve", "verb": "approve" },
+            { "type": "Action.Execute", "title": "Reject", "verb": "reject" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.target = Some(component_adaptive_card::OutputTarget::WhatsApp);
+    let result = handle_invocation(invocation.clone()).expect("render");
+    let content = result.transcoded.expect("transcoded").content;
+    let buttons = content["interactive"]["action"]["buttons"]
+        .as_array()
+        .expect("buttons");
+    let reply_ids: Vec<&str> = buttons
+        .iter()
+        .map(|button| button["reply"]["id"].as_str().expect("reply id"))
+        .collect();
+    assert_eq!(reply_ids.len(), 2);
+    assert_ne!(reply_ids[0], reply_ids[1]);
+
+    invocation.target = None;
+    for (reply_id, verb) in reply_ids.iter().zip(["approve", "reject"]) {
+        let reply =
+            component_adaptive_card::reply_interaction(reply_id, "change-7").expect("reply");
+        assert!(reply.action_id.starts_with("action-execute-"));
+        invocation.interaction = Some(reply);
+        let event = handle_invocation(invocation.clone())
+            .expect("interaction")
+            .event
+            .expect("event");
+        assert_eq!(event.verb.as_deref(), Some(verb));
+    }
+}
+
+#[test]
+fn discord_target_maps_actions_to_components_routed_back_by_custom_id() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "Incident ${payload.id}", "size": "large" },
+            { "type": "TextBlock", "text": "Database latency is _elevated_" },
+            { "type": "FactSet", "facts": [{ "title": "Severity", "value": "2" }] },
+            {
+                "type": "Input.ChoiceSet",
+                "id": "owner",
+                "placeholder": "Assign to",
+                "value": "db",
+                "choices": [
+                    { "title": "Database team", "value": "db" },
+                    { "title": "Network team", "value": "net" }
+                ]
+            }
+        ],
+        "actions": [
+            { "type": "Action.Submit", "id": "assign", "title": "Assign" },
+            { "type": "Action.Submit", "id": "resolve", "title": "Resolve", "style": "positive" },
+            { "type": "Action.OpenUrl", "title": "Runbook", "url": "https://example.com/runbook" },
+            { "type": "Action.ToggleVisibility", "id": "details", "title": "Details", "targetElements": [] }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "id": "INC-7" });
+    invocation.target = Some(component_adaptive_card::OutputTarget::Discord);
+    let result = handle_invocation(invocation.clone()).expect("render");
+    let transcoded = result.transcoded.expect("transcoded");
+    assert_eq!(
+        transcoded.target,
+        component_adaptive_card::OutputTarget::Discord
+    );
+    assert_eq!(
+        transcoded.content,
+        json!({
+            "embeds": [{
+                "title": "Incident INC-7",
+                "description": "Database latency is elevated",
+                "fields": [{ "name": "Severity", "value": "2", "inline": true }]
+            }],
+            "components": [
+                {
+                    "type": 1,
+                    "components": [{
+                        "type": 3,
+                        "custom_id": "submit:assign?owner=",
+                        "placeholder": "Assign to",
+                        "min_values": 1,
+                        "max_values": 1,
+                        "options": [
+                            { "label": "Database team", "value": "db", "default": true },
+                            { "label": "Network team", "value": "net" }
+                        ]
+                    }]
+                },
+                {
+                    "type": 1,
+                    "components": [
+                        { "type": 2, "style": 3, "label": "Resolve", "custom_id": "submit:resolve" },
+                        { "type": 2, "style": 5, "label": "Runbook", "url": "https://example.com/runbook" }
+                    ]
+                }
+            ]
+        })
+    );
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| { issue.code == "transcode-unsupported" && issue.path == "/actions/3" })
+    );
+
+    let selected = component_adaptive_card::select_interaction(
+        "submit:assign?owner=",
+        &["net".to_string()],
+        "incident-7",
+    )
+    .expect("custom_id");
+    assert_eq!(selected.action_id, "assign");
+    invocation.target = None;
+    invocation.interaction = Some(selected);
+    let result = handle_invocation(invocation).expect("interaction");
+    assert_eq!(result.event.expect("event").inputs["owner"], "net");
+}
+
+#[test]
+fn discord_target_gives_id_less_actions_unique_routable_custom_ids() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "Approve the change?" }],
+        "actions": [
+            { "type": "Action.Execute", "title": "Approve", "verb": "approve" },
+            { "type": "Action.Execute", "title": "Reject", "verb": "reject" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.target = Some(component_adaptive_card::OutputTarget::Discord);
+    let result = handle_invocation(invocation.clone()).expect("render");
+    let content = result.transcoded.expect("transcoded").content;
+    let buttons = content["components"][0]["components"]
+        .as_array()
+        .expect("buttons");
+    let custom_ids: Vec<&str> = buttons
+        .iter()
+        .map(|button| button["custom_id"].as_str().expect("custom_id"))
+        .collect();
+    assert_eq!(custom_ids.len(), 2);
+    assert_ne!(custom_ids[0], custom_ids[1]);
+
+    invocation.target = None;
+    for (custom_id, verb) in custom_ids.iter().zip(["approve", "reject"]) {
+        let reply =
+            component_adaptive_card::reply_interaction(custom_id, "incident-7").expect("reply");
+        assert!(reply.action_id.starts_with("action-execute-"));
+        invocation.interaction = Some(reply);
+        let event = handle_invocation(invocation.clone())
+            .expect("interaction")
+            .event
+            .expect("event");
+        assert_eq!(event.verb.as_deref(), Some(verb));
+    }
+}
+
+#[test]
+fn email_target_renders_themed_html_with_tracked_action_links() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "Expense ${payload.id}", "style": "heading" },
+            { "type": "TextBlock", "text": "Approve **$42** for <lunch>?" },
+            { "type": "FactSet", "facts": [{ "title": "Submitter", "value": "Ana" }] },
+            { "type": "Input.Text", "id": "comment" }
+        ],
+        "actions": [
+            { "type": "Action.Execute", "id": "approve", "title": "Approve", "verb": "approve" },
+            { "type": "Action.OpenUrl", "id": "receipt", "title": "Receipt", "url": "https://example.com/r/7" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "id": "EXP-7" });
+    invocation.card_instance_id = Some("expense-7".to_string());
+    invocation.theme = Some(json!({
+        "email": { "colors": { "accent": "#5b2d90" }, "logoUrl": "https://example.com/logo.png" }
+    }));
+    invocation.target = Some(component_adaptive_card::OutputTarget::Email);
+    invocation.email = Some(component_adaptive_card::EmailOptions {
+        action_url: Some("https://hooks.example.com/card".to_string()),
+        tracking_url: Some("https://t.example.com/click?src=mail".to_string()),
+        subject: None,
+    });
+    let result = handle_invocation(invocation.clone()).expect("render");
+    let content = result.transcoded.expect("transcoded").content;
+    assert_eq!(content["subject"], "Expense EXP-7");
+    assert_eq!(
+        content["text"],
+        "Expense EXP-7\n\nApprove $42 for ?\n\nSubmitter: Ana\n\n\
+         Approve: https://hooks.example.com/card?reply=execute%3Aapprove&card=expense-7\n\n\
+         Receipt: https://t.example.com/click?src=mail&url=https%3A%2F%2Fexample.com%2Fr%2F7&action=receipt&card=expense-7"
+    );
+    let html = content["html"].as_str().expect("html");
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Approve <strong>$42</strong> for ?"));
+    assert!(html.contains(r#"<img src="https://example.com/logo.png""#));
+    assert!(html.contains(
+        r#"<a href="https://hooks.example.com/card?reply=execute%3Aapprove&amp;card=expense-7" style="display:inline-block;margin:0 8px 8px 0;padding:10px 16px;border-radius:4px;background:#5b2d90;"#
+    ));
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| { issue.code == "transcode-unsupported" && issue.path == "/body/3" })
+    );
+
+    let reply = component_adaptive_card::reply_interaction("execute:approve", "expense-7")
+        .expect("reply id");
+    assert_eq!(reply.interaction_type, CardInteractionType::Execute);
+
+    invocation.email = None;
+    let result = handle_invocation(invocation).expect("render");
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "email-action-url-missing")
+    );
+}
+
+#[test]
+fn email_target_drops_malformed_pixel_widths_and_reads_enums_in_any_case() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "Quarterly report", "size": "ExtraLarge", "weight": "Bolder",
+              "horizontalAlignment": "Center" },
+            { "type": "ColumnSet", "columns": [
+                { "type": "Column", "width": "120px", "items": [{ "type": "TextBlock", "text": "a" }] },
+                { "type": "Column", "width": "1px\" onmouseover=\"alert(1)\" x=\"px",
+                  "items": [{ "type": "TextBlock", "text": "b" }] }
+            ] }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.target = Some(component_adaptive_card::OutputTarget::Email);
+    let result = handle_invocation(invocation).expect("render");
+    let content = result.transcoded.expect("transcoded").content;
+    assert_eq!(content["subject"], "Quarterly report");
+    let html = content["html"].as_str().expect("html");
+    assert!(html.contains("font-size:26px;font-weight:bold;color:"));
+    assert!(html.contains("text-align:center\">Quarterly report"));
+    assert!(html.contains(r#"<td width="120" valign="top""#));
+    assert!(!html.contains("onmouseover"));
+}
+
+#[test]
+fn generate_card_from_schema_builds_inputs_matching_the_schema() {
+    let input = json!({
+        "card_spec": {},
+        "source_schema": {
+            "title": "Ticket",
+            "type": "object",
+            "required": ["summary", "priority"],
+            "properties": {
+                "summary": { "type": "string", "maxLength": 80 },
+                "priority": { "$ref": "#/$defs/priority" },
+                "dueDate": { "type": "string", "format": "date" },
+                "estimate": { "type": "integer", "minimum": 1, "default": 3 },
+                "reporter": {
+                    "type": "object",
+                    "properties": { "email": { "type": "string", "format": "email" } }
+                },
+                "attachments": { "type": "array", "items": { "type": "object" } }
+            },
+            "$defs": { "priority": { "enum": ["low", "high"] } }
+        }
+    });
+    let result = match component_adaptive_card::process_message(
+        "generate-card-from-schema",
+        &input.to_string(),
+    ) {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("generation failed: {error:?}"),
+    };
+    let card = result.rendered_card.expect("card");
+    assert_eq!(
+        card["body"],
+        json!([
+            { "type": "TextBlock", "text": "Ticket", "style": "heading", "wrap": true },
+            { "type": "Input.Date", "id": "dueDate", "label": "Due date" },
+            { "type": "Input.Number", "id": "estimate", "label": "Estimate", "min": 1, "value": "3" },
+            { "type": "Input.ChoiceSet", "id": "priority", "label": "Priority", "isRequired": true,
+              "errorMessage": "Priority is required",
+              "choices": [{ "title": "low", "value": "low" }, { "title": "high", "value": "high" }] },
+            { "type": "TextBlock", "text": "Reporter", "weight": "bolder", "separator": true, "wrap": true },
+            { "type": "Input.Text", "id": "reporter.email", "label": "Email", "style": "email" },
+            { "type": "Input.Text", "id": "summary", "label": "Summary", "maxLength": 80,
+              "isRequired": true, "errorMessage": "Summary is required" }
+        ])
+    );
+    assert_eq!(card["actions"][0]["id"], "submit");
+    assert!(result.validation_issues.iter().any(|issue| {
+        issue.code == "schema-import-unsupported" && issue.path == "/attachments"
+    }));
+
+    let mut invocation = base_invocation(card);
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "submit".to_string(),
+        verb: None,
+        raw_inputs: json!({ "summary": "Printer jam", "priority": "high", "estimate": "5" }),
+        card_instance_id: "ticket-1".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let event = handle_invocation(invocation)
+        .expect("submit")
+        .event
+        .expect("event");
+    assert_eq!(event.inputs["estimate"], json!(5));
+    assert_eq!(event.inputs["priority"], "high");
+
+    let form = json!({
+        "card_spec": {},
+        "source_schema": {
+            "title": "Feedback",
+            "submit": "Send",
+            "fields": [
+                { "id": "rating", "type": "select", "options": ["1", "2", "3"], "required": true },
+                { "name": "comments", "type": "textarea", "label": "Anything else?" }
+            ]
+        }
+    });
+    let result = match component_adaptive_card::process_message(
+        "generate-card-from-schema",
+        &form.to_string(),
+    ) {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("generation failed: {error:?}"),
+    };
+    let card = result.rendered_card.expect("card");
+    assert_eq!(card["body"][1]["type"], "Input.ChoiceSet");
+    assert_eq!(card["body"][1]["isRequired"], true);
+    assert_eq!(card["body"][2]["isMultiline"], true);
+    assert_eq!(card["body"][2]["label"], "Anything else?");
+    assert_eq!(card["actions"][0]["title"], "Send");
+}
+
+#[test]
+fn generate_card_from_openapi_submits_the_operations_request_body() {
+    let input = json!({
+        "card_spec": {},
+        "openapi": {
+            "operation": {
+                "operationId": "createComment",
+                "parameters": [
+                    { "name": "issueId", "in": "path", "schema": { "type": "integer" } },
+                    { "name": "notify", "in": "query", "schema": { "type": "boolean" } },
+                    { "name": "session", "in": "cookie", "schema": { "type": "string" } }
+                ],
+                "requestBody": {
+                    "content": {
+                        "application/json": { "schema": { "$ref": "#/components/schemas/Comment" } }
+                    }
+                }
+            },
+            "document": {
+                "components": {
+                    "schemas": {
+                        "Comment": {
+                            "type": "object",
+                            "required": ["text"],
+                            "properties": {
+                                "text": { "type": "string" },
+                                "author": {
+                                    "type": "object",
+                                    "properties": { "name": { "type": "string" } }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    });
+    let result = match component_adaptive_card::process_message(
+        "generate-card-from-openapi",
+        &input.to_string(),
+    ) {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("generation failed: {error:?}"),
+    };
+    let card = result.rendered_card.expect("card");
+    assert_eq!(card["body"][0]["text"], "Create comment");
+    assert_eq!(card["body"][1]["id"], "path.issueId");
+    assert_eq!(card["body"][1]["isRequired"], true);
+    assert_eq!(card["actions"][0]["type"], "Action.Execute");
+    assert_eq!(card["actions"][0]["verb"], "createComment");
+    assert!(result.validation_issues.iter().any(|issue| {
+        issue.code == "schema-import-unsupported" && issue.path == "/cookie/session"
+    }));
+
+    let mut invocation = base_invocation(card);
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Execute,
+        action_id: "submit".to_string(),
+        verb: Some("createComment".to_string()),
+        raw_inputs: json!({
+            "path.issueId": "42",
+            "query.notify": "true",
+            "body.text": "Looks good",
+            "body.author.name": "Sam"
+        }),
+        card_instance_id: "comment-1".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let event = handle_invocation(invocation)
+        .expect("execute")
+        .event
+        .expect("event");
+    assert_eq!(event.inputs["path"], json!({ "issueId": 42 }));
+    assert_eq!(event.inputs["query"], json!({ "notify": true }));
+    assert_eq!(
+        event.inputs["body"],
+        json!({ "text": "Looks good", "author": { "name": "Sam" } })
+    );
+}
+
+#[test]
+fn compile_form_expands_the_yaml_dsl_into_a_house_styled_card() {
+    let yaml = r#"
+# Expense claim form
+title: Expense claim
+description: >
+  Claims over 500 need
+  a manager's approval.
+fields:
+  - id: amount
+    type: number
+    required: true
+    min: 1
+    max: 5000
+    message: "Enter an amount between 1 and 5000"
+  - section: Details
+  - id: category
+    type: select
+    options: [travel, meals, 'office supplies']
+  - name: notes   # optional
+    type: textarea
+actions:
+  - title: Send claim
+    verb: submitClaim
+  - title: Cancel
+    style: destructive
+    validate: false
+"#;
+    let input = json!({ "card_spec": {}, "form": yaml });
+    let result = match component_adaptive_card::process_message("compile-form", &input.to_string())
+    {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("compile failed: {error:?}"),
+    };
+    let card = result.rendered_card.expect("card");
+    let header = &card["body"][0];
+    assert_eq!(header["style"], "@theme.containerStyles.highlight");
+    assert_eq!(header["items"][0]["text"], "Expense claim");
+    assert_eq!(
+        header["items"][1]["text"],
+        "Claims over 500 need a manager's approval.\n"
+    );
+    assert_eq!(card["body"][1]["type"], "Input.Number");
+    assert_eq!(card["body"][1]["max"], 5000);
+    assert_eq!(
+        card["body"][1]["errorMessage"],
+        "Enter an amount between 1 and 5000"
+    );
+    assert_eq!(card["body"][2]["text"], "Details");
+    assert_eq!(card["body"][2]["color"], "@theme.colors.accent");
+    assert_eq!(card["body"][3]["choices"][2]["value"], "office supplies");
+    assert_eq!(card["body"][4]["label"], "Notes");
+    assert_eq!(
+        card["actions"],
+        json!([
+            { "type": "Action.Execute", "id": "send-claim", "title": "Send claim",
+              "verb": "submitClaim", "style": "positive" },
+            { "type": "Action.Submit", "id": "cancel", "title": "Cancel",
+              "associatedInputs": "none", "style": "destructive" }
+        ])
+    );
+
+    let rendered = handle_invocation(base_invocation(card))
+        .expect("render")
+        .rendered_card
+        .expect("rendered card");
+    assert_eq!(rendered["body"][0]["style"], "emphasis");
+    assert_eq!(rendered["body"][0]["items"][0]["size"], "Large");
+
+    let broken = json!({ "card_spec": {}, "form": "fields:\n  - id: a\n   type: text\n" });
+    match component_adaptive_card::process_message("compile-form", &broken.to_string()) {
+        AdaptiveCardResponse::Failure { error } => assert_eq!(
+            error.details.expect("details")["validation_issues"][0]["message"],
+            "form: line 3: unexpected indentation"
+        ),
+        AdaptiveCardResponse::Success(_) => panic!("malformed YAML compiled"),
+    }
+
+    let nesting_error = |form: String, limits: serde_json::Value| {
+        let input = json!({ "card_spec": {}, "form": form, "limits": limits });
+        match component_adaptive_card::process_message("compile-form", &input.to_string()) {
+            AdaptiveCardResponse::Failure { error } => {
+                error.details.expect("details")["validation_issues"][0]["message"].clone()
+            }
+            AdaptiveCardResponse::Success(_) => panic!("over-nested YAML compiled"),
+        }
+    };
+    assert_eq!(
+        nesting_error(format!("fields: {}", "[".repeat(100_000)), json!(null)),
+        "form: line 1: collections nest deeper than 32 levels"
+    );
+    let block: String = (0..10)
+        .map(|level| format!("{}a:\n", "  ".repeat(level)))
+        .collect();
+    assert_eq!(
+        nesting_error(format!("fields: []\n{block}"), json!({ "maxNesting": 8 })),
+        "form: line 10: collections nest deeper than 8 levels"
+    );
+}
+
+#[test]
+fn introspect_card_describes_inputs_and_actions() {
+    let input = json!({
+        "card_spec": { "inline_json": {
+            "type": "AdaptiveCard",
+            "version": "1.6",
+            "body": [
+                { "type": "Input.Number", "id": "qty", "label": "Quantity", "isRequired": true,
+                  "min": 1, "max": 10, "value": "2", "errorMessage": "1 to 10" },
+                { "type": "Input.ChoiceSet", "id": "size", "isMultiSelect": true, "value": "s,m",
+                  "choices": [{ "title": "Small", "value": "s" }, { "title": "Medium", "value": "m" }] },
+                { "type": "ActionSet", "actions": [
+                    { "type": "Action.ShowCard", "id": "more", "title": "More", "card": {
+                        "type": "AdaptiveCard",
+                        "body": [{ "type": "Input.Text", "id": "note", "style": "email", "maxLength": 40 }],
+                        "actions": [{ "type": "Action.Submit", "id": "send-note", "title": "Send" }]
+                    } }
+                ] }
+            ],
+            "actions": [
+                { "type": "Action.Execute", "id": "order", "verb": "placeOrder", "data": { "sku": "A1" } },
+                { "type": "Action.Submit", "title": "Cancel", "associatedInputs": "none" },
+                { "type": "Action.OpenUrl", "id": "help", "url": "https://example.com/help" }
+            ]
+        } }
+    });
+    let result =
+        match component_adaptive_card::process_message("introspect-card", &input.to_string()) {
+            AdaptiveCardResponse::Success(result) => result,
+            AdaptiveCardResponse::Failure { error } => panic!("introspection failed: {error:?}"),
+        };
+    let introspection =
+        serde_json::to_value(result.introspection.expect("introspection")).expect("serializes");
+    let inputs = &introspection["inputs"];
+    assert_eq!(
+        inputs[0],
+        json!({
+            "id": "qty", "type": "Input.Number", "pointer": "/body/0", "label": "Quantity",
+            "required": true, "default": 2,
+            "constraints": { "min": 1, "max": 10, "multiSelect": false, "errorMessage": "1 to 10" }
+        })
+    );
+    assert_eq!(inputs[1]["default"], json!(["s", "m"]));
+    assert_eq!(inputs[1]["constraints"]["choices"][1]["title"], "Medium");
+    assert_eq!(inputs[2]["constraints"]["style"], "email");
+    assert_eq!(inputs[2]["constraints"]["maxLength"], 40);
+
+    let actions = introspection["actions"].as_array().expect("actions");
+    let action = |id: &str| {
+        actions
+            .iter()
+            .find(|action| action["id"] == id)
+            .unwrap_or_else(|| panic!("action {id}"))
+    };
+    assert_eq!(action("order")["verb"], "placeOrder");
+    assert_eq!(action("order")["data"], json!({ "sku": "A1" }));
+    assert_eq!(action("order")["submits"], json!(["qty", "size"]));
+    assert_eq!(
+        action("send-note")["submits"],
+        json!(["qty", "size", "note"])
+    );
+    assert_eq!(action("help")["url"], "https://example.com/help");
+    let cancel = actions
+        .iter()
+        .find(|action| action["title"] == "Cancel")
+        .expect("cancel");
+    assert!(cancel.get("submits").is_none());
+}
+
+#[test]
+fn verb_handlers_add_state_and_session_updates() {
+    register_verb_handler(
+        "approveExpense",
+        Box::new(|event, state| {
+            let approvals = state["approvals"].as_u64().unwrap_or(0);
+            Ok(VerbOutcome {
+                state_updates: vec![
+                    StateUpdateOp::Set {
+                        path: "approvals".into(),
+                        value: json!(approvals + 1),
+                    },
+                    StateUpdateOp::Set {
+                        path: "approvedAmount".into(),
+                        value: event.inputs["amount"].clone(),
+                    },
+                ],
+                session_updates: vec![SessionUpdateOp::SetRoute {
+                    route: "expenses/approved".into(),
+                }],
+            })
+        }),
+    )
+    .expect("register handler");
+    register_verb_handler(
+        "rejectExpense",
+        Box::new(|_, _| Err("ledger unavailable".into())),
+    )
+    .expect("register handler");
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "Input.Number", "id": "amount" },
+            { "type": "TextBlock", "text": "Approvals: ${state.approvals}" }
+        ],
+        "actions": [
+            { "type": "Action.Execute", "id": "approve", "verb": "approveExpense" },
+            { "type": "Action.Execute", "id": "reject", "verb": "rejectExpense" }
+        ]
+    });
+    let interact = |action_id: &str, verb: &str| {
+        let mut invocation = base_invocation(card.clone());
+        invocation.state = json!({ "approvals": 2 });
+        invocation.interaction = Some(CardInteraction {
+            enabled: None,
+            interaction_type: CardInteractionType::Execute,
+            action_id: action_id.to_string(),
+            verb: Some(verb.to_string()),
+            raw_inputs: json!({ "amount": "120" }),
+            card_instance_id: "expense-1".to_string(),
+            metadata: json!({}),
+            idempotency_key: None,
+            dialog_id: None,
+        });
+        handle_invocation(invocation).expect("interaction")
+    };
+
+    let approved = interact("approve", "approveExpense");
+    assert!(approved.state_updates.contains(&StateUpdateOp::Set {
+        path: "approvals".into(),
+        value: json!(3),
+    }));
+    assert!(approved.state_updates.contains(&StateUpdateOp::Set {
+        path: "approvedAmount".into(),
+        value: json!(120),
+    }));
+    assert!(
+        approved
+            .session_updates
+            .contains(&SessionUpdateOp::SetRoute {
+                route: "expenses/approved".into(),
+            })
+    );
+    assert_eq!(
+        approved.rendered_card.expect("card")["body"][1]["text"],
+        "Approvals: 3"
+    );
+
+    let rejected = interact("reject", "rejectExpense");
+    assert!(
+        rejected
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "verb-handler-failed")
+    );
+    assert!(
+        !rejected
+            .state_updates
+            .iter()
+            .any(|op| matches!(op, StateUpdateOp::Set { path, .. } if path == "approvals"))
+    );
+}
+
+#[test]
+fn post_render_hooks_rewrite_the_card_in_order() {
+    fn scoped(inv: &AdaptiveCardInvocation) -> bool {
+        inv.card_instance_id.as_deref() == Some("hooked-card")
+    }
+    register_post_render_callback(
+        "tracking",
+        20,
+        Box::new(|card, inv| {
+            if scoped(inv) {
+                let count = card["body"].as_array().map_or(0, Vec::len);
+                card["body"][count - 1]["id"] = json!(format!("tracked-{count}"));
+            }
+            Ok(())
+        }),
+    )
+    .expect("register hook");
+    register_post_render_callback(
+        "footer",
+        10,
+        Box::new(|card, inv| {
+            if scoped(inv) {
+                card["body"]
+                    .as_array_mut()
+                    .ok_or("card has no body")?
+                    .push(json!({ "type": "TextBlock", "text": "Contoso Ltd.", "isSubtle": true }));
+            }
+            Ok(())
+        }),
+    )
+    .expect("register hook");
+    register_post_render_callback(
+        "broken",
+        15,
+        Box::new(|card, inv| {
+            if !scoped(inv) {
+                return Ok(());
+            }
+            card["body"] = json!([]);
+            Err("tracking service down".into())
+        }),
+    )
+    .expect("register hook");
+
+    let mut invocation = base_invocation(json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "Hello" }]
+    }));
+    invocation.card_instance_id = Some("hooked-card".into());
+    let result = handle_invocation(invocation.clone()).expect("render");
+    for name in ["tracking", "footer", "broken"] {
+        assert!(remove_post_render_hook(name).expect("remove hook"));
+    }
+    let card = result.rendered_card.expect("card");
+    assert_eq!(card["body"][1]["text"], "Contoso Ltd.");
+    assert_eq!(card["body"][1]["id"], "tracked-2");
+    assert!(result.validation_issues.iter().any(|issue| {
+        issue.code == "post-render-hook-failed" && issue.message.contains("'broken'")
+    }));
+
+    let unhooked = handle_invocation(invocation).expect("render");
+    assert_eq!(
+        unhooked.rendered_card.expect("card")["body"]
+            .as_array()
+            .map(Vec::len),
+        Some(1)
+    );
+}
+
+#[test]
+fn post_render_hook_output_stays_within_the_budget() {
+    register_post_render_callback(
+        "budgeted-footer",
+        10,
+        Box::new(|card, inv| {
+            if inv.card_instance_id.as_deref() == Some("budgeted-card") {
+                let body = card["body"].as_array_mut().ok_or("card has no body")?;
+                body.push(json!({ "type": "TextBlock", "text": "Contoso Ltd." }));
+                body.push(json!({ "type": "TextBlock", "text": "Unsubscribe" }));
+            }
+            Ok(())
+        }),
+    )
+    .expect("register hook");
+
+    let mut invocation = base_invocation(json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "Hello" }]
+    }));
+    invocation.card_instance_id = Some("budgeted-card".into());
+    invocation.budget = Some(CardBudget {
+        max_elements: Some(2),
+        truncation: vec![TruncationStrategy::DropTrailing],
+        ..Default::default()
+    });
+    let result = handle_invocation(invocation).expect("render");
+    assert!(remove_post_render_hook("budgeted-footer").expect("remove hook"));
+
+    let card = result.rendered_card.expect("card");
+    let texts: Vec<_> = card["body"]
+        .as_array()
+        .expect("body")
+        .iter()
+        .map(|element| element["text"].clone())
+        .collect();
+    assert_eq!(texts, vec![json!("Hello"), json!("Contoso Ltd.")]);
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "budget-truncated")
+    );
+}
+
+#[test]
+fn data_transformers_reshape_data_before_binding() {
+    register_data_transformer_callback(
+        "flattenCustomer",
+        Box::new(|data| {
+            let customer = data.payload["response"]["data"]["customer"].take();
+            data.payload["customer"] = customer;
+            Ok(())
+        }),
+    )
+    .expect("register transformer");
+    register_data_transformer_callback(
+        "isoDueDate",
+        Box::new(|data| {
+            let due = data.state["due"].as_str().ok_or("state.due is missing")?;
+            let (day, rest) = due.split_once('/').ok_or("expected dd/mm/yyyy")?;
+            let (month, year) = rest.split_once('/').ok_or("expected dd/mm/yyyy")?;
+            data.state["due"] = json!(format!("{year}-{month}-{day}"));
+            Ok(())
+        }),
+    )
+    .expect("register transformer");
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "$transform": ["flattenCustomer", "isoDueDate", "unknownStep"],
+        "body": [
+            { "type": "TextBlock", "text": "${payload.customer.name}" },
+            { "type": "TextBlock", "text": "Due ${state.due}" }
+        ]
+    });
+    let mut invocation = base_invocation(card.clone());
+    invocation.payload = json!({ "response": { "data": { "customer": { "name": "Ada" } } } });
+    invocation.state = json!({ "due": "31/01/2027" });
+    let result = handle_invocation(invocation.clone()).expect("render");
+    let rendered = result.rendered_card.expect("card");
+    assert_eq!(rendered["body"][0]["text"], "Ada");
+    assert_eq!(rendered["body"][1]["text"], "Due 2027-01-31");
+    assert!(rendered.get("$transform").is_none());
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "unknown-data-transformer"
+                && issue.message.contains("unknownStep"))
+    );
+
+    invocation.state = json!({ "due": "soon" });
+    let result = handle_invocation(invocation).expect("render");
+    assert_eq!(
+        result.rendered_card.expect("card")["body"][1]["text"],
+        "Due soon"
+    );
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| issue.code == "data-transformer-failed")
+    );
+}
+
+#[test]
+fn custom_element_renderers_expand_before_binding() {
+    register_element_renderer_callback(
+        "X.CustomerTimeline",
+        Box::new(|element, ctx| {
+            let source = element["source"].as_str().ok_or("source is required")?;
+            let events = ctx.payload[source].as_array().ok_or("source is not a list")?;
+            let mut rendered = vec![json!({
+                "type": "TextBlock",
+                "text": "Timeline of ${payload.customer}",
+                "weight": "Bolder"
+            })];
+            rendered.extend(events.iter().enumerate().map(|(idx, event)| {
+                json!({
+                    "type": "TextBlock",
+                    "text": format!("{}: ${{payload.{source}[{idx}].what}}", event["at"].as_str().unwrap_or("?"))
+                })
+            }));
+            Ok(json!(rendered))
+        }),
+    )
+    .expect("register renderer");
+    register_element_renderer_callback(
+        "X.Badge",
+        Box::new(|element, _| {
+            Ok(json!({ "type": "TextBlock", "text": element["label"].clone(), "color": "Accent" }))
+        }),
+    )
+    .expect("register renderer");
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "X.Badge", "id": "vip", "label": "VIP ${payload.customer}" },
+            { "type": "X.CustomerTimeline", "source": "events" },
+            { "type": "X.CustomerTimeline" },
+            { "type": "TextBlock", "text": "End" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({
+        "customer": "Ada",
+        "events": [
+            { "at": "09:00", "what": "Signed up" },
+            { "at": "10:30", "what": "First order" }
+        ]
+    });
+    let result = handle_invocation(invocation).expect("render");
+    let body = result.rendered_card.expect("card")["body"].clone();
+    assert_eq!(body[0]["id"], "vip");
+    assert_eq!(body[0]["text"], "VIP Ada");
+    assert_eq!(body[1]["text"], "Timeline of Ada");
+    assert_eq!(body[2]["text"], "09:00: Signed up");
+    assert_eq!(body[3]["text"], "10:30: First order");
+    assert_eq!(body[4]["text"], "End");
+    assert!(
+        result
+            .validation_issues
+            .iter()
+            .any(|issue| { issue.code == "custom-element-failed" && issue.path == "/body/2" })
+    );
+}
+
+#[test]
+fn host_expression_functions_are_called_with_arity_checks() {
+    register_expression_callback(
+        "riskScore",
+        2..=2,
+        Box::new(|args| {
+            let amount = args[0].as_f64().ok_or("amount must be a number")?;
+            let factor = if args[1] == "high" { 3.0 } else { 1.0 };
+            Ok(json!(amount * factor / 100.0))
+        }),
+    )
+    .expect("register function");
+    assert!(register_expression_callback("sum", 1..=1, Box::new(|_| Ok(json!(0)))).is_err());
+
+    let render = |text: &str| {
+        let mut invocation = base_invocation(json!({
+            "type": "AdaptiveCard",
+            "version": "1.5",
+            "body": [{ "type": "TextBlock", "text": text }]
+        }));
+        invocation.payload = json!({ "amount": 250, "country": "high", "label": "n/a" });
+        handle_invocation(invocation)
+    };
+    let card = render("${riskScore(payload.amount, payload.country) > 5 ? 'review' : 'ok'}")
+        .expect("render")
+        .rendered_card
+        .expect("card");
+    assert_eq!(card["body"][0]["text"], "review");
+
+    let arity = render("${riskScore(payload.amount)}").expect_err("arity mismatch");
+    assert!(
+        arity
+            .to_string()
+            .contains("riskScore expects 2 argument(s), got 1")
+    );
+    let failed = render("${riskScore(payload.label, payload.country)}").expect_err("failure");
+    assert!(
+        failed
+            .to_string()
+            .contains("riskScore failed: amount must be a number")
+    );
+    let unknown = render("${churnScore(payload.amount)}").expect_err("unknown function");
+    assert!(
+        unknown
+            .to_string()
+            .contains("unknown function 'churnScore'")
+    );
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&component_adaptive_card::describe_payload()).expect("manifest");
+    assert!(
+        manifest["component"]["functions"]
+            .as_array()
+            .expect("functions")
+            .contains(&json!("riskScore"))
+    );
+}
+
+#[test]
+fn invoke_stream_sends_processing_card_before_execute_result() {
+    use std::sync::Mutex;
+    static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    register_verb_handler(
+        "buildReport",
+        Box::new(|_, _| {
+            LOG.lock().unwrap().push("handler".into());
+            Ok(VerbOutcome {
+                state_updates: vec![StateUpdateOp::Set {
+                    path: "report".into(),
+                    value: json!("ready"),
+                }],
+                session_updates: Vec::new(),
+            })
+        }),
+    )
+    .expect("register handler");
+    let input = json!({
+        "card_source": "inline",
+        "card_spec": {
+            "inline_json": {
+                "type": "AdaptiveCard",
+                "version": "1.5",
+                "body": [{ "type": "TextBlock", "text": "Report: ${state.report || 'none'}" }],
+                "actions": [{ "type": "Action.Execute", "id": "build", "verb": "buildReport" }]
+            }
+        },
+        "processing": {
+            "type": "AdaptiveCard",
+            "version": "1.5",
+            "body": [{ "type": "TextBlock", "text": "Building your report…" }]
+        },
+        "interaction": {
+            "interaction_type": "Execute",
+            "action_id": "build",
+            "verb": "buildReport",
+            "raw_inputs": {},
+            "card_instance_id": "report-1"
+        }
+    })
+    .to_string();
+
+    let stream = |operation: &str| {
+        let mut frames = Vec::new();
+        invoke_stream(operation, &input, &mut |frame| {
+            if let StreamFrame::Data(json) = &frame {
+                let data: serde_json::Value = serde_json::from_str(json).expect("frame");
+                LOG.lock()
+                    .unwrap()
+                    .push(data["phase"].as_str().unwrap().into());
+            }
+            frames.push(frame);
+        });
+        assert_eq!(frames.first(), Some(&StreamFrame::Progress(0)));
+        assert_eq!(frames.last(), Some(&StreamFrame::Done));
+        let data: Vec<serde_json::Value> = frames
+            .iter()
+            .filter_map(|frame| match frame {
+                StreamFrame::Data(json) => serde_json::from_str(json).ok(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(data.len(), 1);
+        data[0].clone()
+    };
+
+    let processing = stream("card");
+    assert_eq!(
+        *LOG.lock().unwrap(),
+        ["processing"],
+        "the interaction waits for the continuation"
+    );
+    assert_eq!(processing["sequence"], 0);
+    assert_eq!(processing["continuation"], "continue-stream");
+    assert_eq!(
+        processing["result"]["renderedCard"]["body"][0]["text"],
+        "Building your report…"
+    );
+    assert_eq!(processing["result"]["cardInstanceId"], "report-1");
+
+    let last = stream("continue-stream");
+    assert_eq!(*LOG.lock().unwrap(), ["processing", "handler", "final"]);
+    assert_eq!(last["sequence"], 1);
+    assert_eq!(last["phase"], "final");
+    assert_eq!(
+        last["result"]["renderedCard"]["body"][0]["text"],
+        "Report: ready"
+    );
+
+    let mut plain = Vec::new();
+    invoke_stream("health", "", &mut |frame| plain.push(frame));
+    assert_eq!(plain.len(), 3);
+    assert!(matches!(&plain[1], StreamFrame::Data(json) if json.contains("uptime_ms")));
+}
+
+#[test]
+fn schedule_metadata_and_on_expire_view() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "$schedule": {
+            "remindAfter": ["24h", 3600, "96h", "soon"],
+            "expireAfter": "3d",
+            "onExpire": {
+                "type": "AdaptiveCard",
+                "version": "1.5",
+                "body": [{ "type": "TextBlock", "text": "Approval for ${state.request} expired" }]
+            }
+        },
+        "body": [{ "type": "TextBlock", "text": "Approve ${state.request}?" }]
+    });
+    let input = json!({
+        "card_spec": { "inline_json": card },
+        "state": { "request": "PO-7" }
+    });
+    let created = match component_adaptive_card::process_message("init-card", &input.to_string()) {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("init failed: {error:?}"),
+    };
+    let rendered = created.rendered_card.expect("card");
+    assert!(rendered.get("$schedule").is_none());
+    let schedule = created.schedule.expect("schedule");
+    assert_eq!(schedule.expire_after_seconds, Some(259_200));
+    assert_eq!(schedule.remind_after_seconds, vec![3_600, 86_400]);
+    assert!(schedule.on_expire);
+    let warnings: Vec<_> = created
+        .validation_issues
+        .iter()
+        .filter(|issue| issue.code == "invalid-schedule")
+        .map(|issue| issue.path.as_str())
+        .collect();
+    assert_eq!(
+        warnings,
+        ["/$schedule/remindAfter/3", "/$schedule/remindAfter"]
+    );
+
+    let expire = json!({
+        "card_spec": { "inline_json": card },
+        "card_instance_id": created.card_instance_id.expect("instance id")
+    });
+    let expired = component_adaptive_card::handle_message("expire-card", &expire.to_string());
+    let expired: serde_json::Value = serde_json::from_str(&expired).expect("json");
+    assert_eq!(
+        expired["renderedCard"]["body"][0]["text"],
+        "Approval for PO-7 expired"
+    );
+}
+
+#[test]
+fn actions_declare_the_continuation_of_a_card_flow() {
+    let step_two = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "TextBlock", "text": "Thanks ${state.form_data.name}, step ${params.step}" }]
+    });
+    let step_one = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "Input.Text", "id": "name" }],
+        "actions": [{
+            "type": "Action.Submit",
+            "id": "next",
+            "data": {
+                "__next": {
+                    "card_spec": { "inline_json": step_two, "template_params": { "step": 2 } }
+                }
+            }
+        }]
+    });
+    let mut invocation = base_invocation(step_one);
+    invocation.state = serde_json::Value::Null;
+    invocation.locale = Some("en-GB".into());
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "next".to_string(),
+        verb: None,
+        raw_inputs: json!({ "name": "Ada" }),
+        card_instance_id: "flow-1".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    let submitted = handle_invocation(invocation).expect("interaction");
+    assert_eq!(
+        submitted.event.expect("event").inputs,
+        json!({ "name": "Ada" })
+    );
+    let continuation = submitted.continuation.expect("continuation");
+    assert_eq!(continuation.op, "card");
+    assert_eq!(continuation.input["card_instance_id"], "flow-1");
+    assert_eq!(continuation.input["locale"], "en-GB");
+
+    let next = match component_adaptive_card::process_message(
+        &continuation.op,
+        &continuation.input.to_string(),
+    ) {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("continuation failed: {error:?}"),
+    };
+    assert_eq!(
+        next.rendered_card.expect("card")["body"][0]["text"],
+        "Thanks Ada, step 2"
+    );
+    assert!(next.continuation.is_none());
+}
+
+#[test]
+fn persisted_state_migrates_to_the_template_state_version() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static HOST_RUNS: AtomicUsize = AtomicUsize::new(0);
+    register_state_migration_callback(
+        "invoice-migration-test",
+        2,
+        Box::new(|state| {
+            HOST_RUNS.fetch_add(1, Ordering::SeqCst);
+            state["currency"] = json!("EUR");
+            Ok(())
+        }),
+    )
+    .expect("register");
+
+    let v1 = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [{ "type": "Input.Text", "id": "amount" }],
+        "actions": [{ "type": "Action.Submit", "id": "save" }]
+    });
+    let mut invocation = base_invocation(v1);
+    invocation.state = serde_json::Value::Null;
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::Submit,
+        action_id: "save".to_string(),
+        verb: None,
+        raw_inputs: json!({ "amount": "42" }),
+        card_instance_id: "invoice-migration-1".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    handle_invocation(invocation).expect("v1 submit");
+
+    let v3 = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "$state": {
+            "name": "invoice-migration-test",
+            "version": 3,
+            "migrations": {
+                "1": [{ "Rename": { "from": "form_data.amount", "to": "form_data.total" } }]
+            }
+        },
+        "body": [
+            { "type": "TextBlock", "text": "${state.form_data.total}" },
+            { "type": "TextBlock", "text": "${state.currency}" }
+        ]
+    });
+    for _ in 0..2 {
+        let result = component_adaptive_card::process_message(
+            "card",
+            &json!({
+                "card_source": "inline",
+                "card_spec": { "inline_json": v3 },
+                "card_instance_id": "invoice-migration-1"
+            })
+            .to_string(),
+        );
+        let card = match result {
+            AdaptiveCardResponse::Success(result) => result.rendered_card.expect("card"),
+            AdaptiveCardResponse::Failure { error } => panic!("render failed: {error:?}"),
+        };
+        assert_eq!(card["body"][0]["text"], "42");
+        assert_eq!(card["body"][1]["text"], "EUR");
+        assert!(card.get("$state").is_none());
+    }
+    assert_eq!(HOST_RUNS.load(Ordering::SeqCst), 1);
+
+    // Only versions with a migration are visited, so a huge jump finishes at once.
+    let mut jump = v3.clone();
+    jump["$state"] = json!({
+        "name": "invoice-migration-test",
+        "version": u64::MAX,
+        "migrations": {
+            "2": [{ "Set": { "path": "currency", "value": "GBP" } }],
+            "4000000000": [{ "Set": { "path": "currency", "value": "USD" } }]
+        }
+    });
+    let result = component_adaptive_card::process_message(
+        "card",
+        &json!({
+            "card_source": "inline",
+            "card_spec": { "inline_json": jump },
+            "card_instance_id": "invoice-migration-1"
+        })
+        .to_string(),
+    );
+    let card = match result {
+        AdaptiveCardResponse::Success(result) => result.rendered_card.expect("card"),
+        AdaptiveCardResponse::Failure { error } => panic!("render failed: {error:?}"),
+    };
+    assert_eq!(card["body"][1]["text"], "USD");
+    assert_eq!(HOST_RUNS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn read_only_renders_do_not_write_migrated_state_back() {
+    let created = component_adaptive_card::process_message(
+        "init-card",
+        &json!({
+            "card_spec": { "inline_json": { "type": "AdaptiveCard", "version": "1.5", "body": [] } },
+            "card_instance_id": "migration-dry-run",
+            "state": { "form_data": { "amount": "42" } }
+        })
+        .to_string(),
+    );
+    assert!(matches!(created, AdaptiveCardResponse::Success(_)));
+    let v2 = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "$state": {
+            "version": 2,
+            "migrations": {
+                "1": [{ "Rename": { "from": "form_data.amount", "to": "form_data.total" } }]
+            }
+        },
+        "body": [{ "type": "TextBlock", "text": "${state.form_data.total}" }]
+    });
+    let render = |mode: &str| match component_adaptive_card::process_message(
+        "card",
+        &json!({
+            "card_spec": { "inline_json": v2 },
+            "card_instance_id": "migration-dry-run",
+            "mode": mode
+        })
+        .to_string(),
+    ) {
+        AdaptiveCardResponse::Success(result) => result,
+        AdaptiveCardResponse::Failure { error } => panic!("render failed: {error:?}"),
+    };
+    let stored = || {
+        let exported = component_adaptive_card::handle_message(
+            "export-state",
+            &json!({ "card_spec": { "inline_json": {} }, "card_instance_id": "migration-dry-run" })
+                .to_string(),
+        );
+        let exported: serde_json::Value = serde_json::from_str(&exported).expect("json");
+        exported["stateSnapshot"]["state"].clone()
+    };
+
+    for mode in ["simulate", "validate"] {
+        render(mode);
+        assert_eq!(stored(), json!({ "form_data": { "amount": "42" } }));
+    }
+    let simulated = render("simulate").rendered_card.expect("card");
+    assert_eq!(simulated["body"][0]["text"], "42");
+
+    render("render");
+    assert_eq!(
+        stored(),
+        json!({ "$stateVersion": 2, "form_data": { "total": "42" } })
+    );
+}
+
+#[test]
+fn card_instance_state_exports_and_imports_across_stores() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [ { "type": "TextBlock", "text": "Step ${state.step}" } ]
+    });
+    let created = component_adaptive_card::process_message(
+        "init-card",
+        &json!({
+            "card_spec": { "inline_json": card },
+            "card_instance_id": "export-source",
+            "state": { "step": 2 }
+        })
+        .to_string(),
+    );
+    assert!(matches!(created, AdaptiveCardResponse::Success(_)));
+
+    let exported = component_adaptive_card::handle_message(
+        "export-state",
+        &json!({ "card_spec": { "inline_json": {} }, "card_instance_id": "export-source" })
+            .to_string(),
+    );
+    let exported: serde_json::Value = serde_json::from_str(&exported).expect("json");
+    let snapshot = exported["stateSnapshot"].clone();
+    assert_eq!(snapshot["cardInstanceId"], "export-source");
+    assert_eq!(snapshot["version"], 1);
+    assert_eq!(snapshot["state"], json!({ "step": 2 }));
+    assert_eq!(
+        snapshot["metadata"]["hash"].as_str().map(str::len),
+        Some(64)
+    );
+
+    let imported = component_adaptive_card::process_message(
+        "import-state",
+        &json!({
+            "card_spec": { "inline_json": {} },
+            "card_instance_id": "export-target",
+            "state_snapshot": snapshot
+        })
+        .to_string(),
+    );
+    match imported {
+        AdaptiveCardResponse::Success(result) => {
+            assert_eq!(result.card_instance_id.as_deref(), Some("export-target"))
+        }
+        AdaptiveCardResponse::Failure { error } => panic!("import failed: {error:?}"),
+    }
+    let mut render = base_invocation(card);
+    render.state = serde_json::Value::Null;
+    render.card_instance_id = Some("export-target".into());
+    assert_eq!(
+        handle_invocation(render)
+            .expect("render")
+            .rendered_card
+            .expect("card")["body"][0]["text"],
+        "Step 2"
+    );
+
+    let mut tampered = snapshot;
+    tampered["state"]["step"] = json!(9);
+    let rejected = component_adaptive_card::process_message(
+        "import-state",
+        &json!({ "card_spec": { "inline_json": {} }, "state_snapshot": tampered }).to_string(),
+    );
+    assert!(matches!(rejected, AdaptiveCardResponse::Failure { .. }));
+}
+
+#[test]
+fn orphaned_ui_state_is_collected_during_interactions_and_compaction() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [ { "type": "Container", "id": "details", "items": [] } ],
+        "actions": [
+            { "type": "Action.ToggleVisibility", "id": "toggle", "targetElements": ["details"] }
+        ]
+    });
+    let stale = json!({
+        "ui": {
+            "visibility": { "details": false, "removed-section": true },
+            "active_show_card": { "gc-1": "old-subcard" },
+            "disabled_actions": { "*": false, "old-action": true }
+        }
+    });
+    let mut invocation = base_invocation(card.clone());
+    invocation.state = stale.clone();
+    invocation.interaction = Some(CardInteraction {
+        enabled: None,
+        interaction_type: CardInteractionType::ToggleVisibility,
+        action_id: "toggle".to_string(),
+        verb: None,
+        raw_inputs: json!({}),
+        card_instance_id: "gc-1".to_string(),
+        metadata: json!({}),
+        idempotency_key: None,
+        dialog_id: None,
+    });
+    invocation.mode = InvocationMode::Simulate;
+    let toggled = handle_invocation(invocation).expect("interaction");
+    assert_eq!(
+        toggled.simulated_state.expect("state")["ui"],
+        json!({
+            "visibility": { "details": true },
+            "active_show_card": {},
+            "disabled_actions": { "*": false }
+        })
+    );
+
+    let created = component_adaptive_card::process_message(
+        "init-card",
+        &json!({
+            "card_spec": { "inline_json": card },
+            "card_instance_id": "gc-2",
+            "state": stale
+        })
+        .to_string(),
+    );
+    assert!(matches!(created, AdaptiveCardResponse::Success(_)));
+    let compact = json!({ "card_spec": { "inline_json": card }, "card_instance_id": "gc-2" });
+    let compacted =
+        match component_adaptive_card::process_message("compact-state", &compact.to_string()) {
+            AdaptiveCardResponse::Success(result) => result,
+            AdaptiveCardResponse::Failure { error } => panic!("compact failed: {error:?}"),
+        };
+    assert_eq!(compacted.state_updates.len(), 3);
+    let again = component_adaptive_card::process_message("compact-state", &compact.to_string());
+    match again {
+        AdaptiveCardResponse::Success(result) => assert!(result.state_updates.is_empty()),
+        AdaptiveCardResponse::Failure { error } => panic!("compact failed: {error:?}"),
+    }
+}
+
+#[test]
+fn templates_bind_runtime_roots_without_payload_copies() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "${env.region}" },
+            { "type": "TextBlock", "text": "@{user.name}" },
+            { "type": "TextBlock", "text": "${today}" },
+            { "type": "TextBlock", "text": "${now}" },
+            { "type": "TextBlock", "text": "${card.instanceId}" },
+            { "type": "TextBlock", "text": "${card.title}" }
+        ]
+    });
+    let mut invocation = base_invocation(card);
+    invocation.env = Some(json!({ "region": "eu-west" }));
+    invocation.viewer = Some(CardViewer {
+        id: "u-1".into(),
+        name: Some("Ada".into()),
+        ..Default::default()
+    });
+    invocation.now = Some("2026-03-09T08:30:00Z".into());
+    invocation.card_instance_id = Some("runtime-1".into());
+    invocation.payload = json!({ "card": { "title": "From payload" } });
+    let rendered = handle_invocation(invocation)
+        .expect("render")
+        .rendered_card
+        .expect("card");
+    let texts: Vec<_> = rendered["body"]
+        .as_array()
+        .expect("body")
+        .iter()
+        .map(|block| block["text"].clone())
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            json!("eu-west"),
+            json!("Ada"),
+            json!("2026-03-09"),
+            json!("2026-03-09T08:30:00Z"),
+            json!("runtime-1"),
+            json!("From payload")
+        ]
+    );
+}
+
+#[test]
+fn expressions_fall_back_on_missing_optional_fields() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "${payload.customer?.name ?? 'Unknown customer'}" },
+            { "type": "TextBlock", "text": "${default(payload.order.note, 'No note')}" },
+            { "type": "TextBlock", "text": "${payload.order.total ?? 0}" },
+            { "type": "TextBlock", "text": "${payload.missing ?? payload.order.note ?? 'n/a'}" },
+            { "type": "TextBlock", "text": "${payload.order?.total > 10 ? 'large' : 'small'}" },
+            { "type": "TextBlock", "text": "${payload.flag ?? 'unset' == 'unset' ? 'a' : 'b'}" }
+        ]
+    });
+    let mut invocation = base_invocation(card.clone());
+    invocation.payload = json!({ "order": { "total": 42, "note": null }, "flag": null });
+    let rendered = handle_invocation(invocation)
+        .expect("render")
+        .rendered_card
+        .expect("card");
+    let texts: Vec<_> = rendered["body"]
+        .as_array()
+        .expect("body")
+        .iter()
+        .map(|block| block["text"].clone())
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            json!("Unknown customer"),
+            json!("No note"),
+            json!("42"),
+            json!("n/a"),
+            json!("large"),
+            json!("a")
+        ]
+    );
+
+    let analysis = component_adaptive_card::process_message(
+        "analyze-template",
+        &json!({ "card_spec": { "inline_json": card } }).to_string(),
+    );
+    let AdaptiveCardResponse::Success(analysis) = analysis else {
+        panic!("analysis failed");
+    };
+    let paths = analysis.template_analysis.expect("analysis").paths;
+    assert!(paths.contains(&"payload.customer.name".to_string()));
+    assert!(!paths.contains(&"name".to_string()));
+}
+
+#[test]
+fn string_properties_interpolate_literals_and_expressions() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            {
+                "type": "TextBlock",
+                "text": "Hello ${payload.user.name}, you have ${length(payload.items)} items"
+            },
+            { "type": "TextBlock", "text": "${payload.user.name} ${payload.items[0]}" },
+            { "type": "TextBlock", "text": "${payload.user.nickname ?? '{none}'} / ${count(payload.items) > 1 ? 'many' : 'one'}" }
+        ],
+        "actions": [
+            { "type": "Action.OpenUrl", "title": "Open ${payload.items[1]}", "url": "https://example.com/${urlEncode(payload.user.name)}" }
+        ]
+    });
+    let mut invocation = base_invocation(card.clone());
+    invocation.payload = json!({ "user": { "name": "Ada L" }, "items": ["pen", "ink"] });
+    let rendered = handle_invocation(invocation)
+        .expect("render")
+        .rendered_card
+        .expect("card");
+    assert_eq!(rendered["body"][0]["text"], "Hello Ada L, you have 2 items");
+    assert_eq!(rendered["body"][1]["text"], "Ada L pen");
+    assert_eq!(rendered["body"][2]["text"], "{none} / many");
+    assert_eq!(rendered["actions"][0]["title"], "Open ink");
+    assert_eq!(rendered["actions"][0]["url"], "https://example.com/Ada%20L");
+
+    let analysis = component_adaptive_card::process_message(
+        "analyze-template",
+        &json!({ "card_spec": { "inline_json": card } }).to_string(),
+    );
+    let AdaptiveCardResponse::Success(analysis) = analysis else {
+        panic!("analysis failed");
+    };
+    let paths = analysis.template_analysis.expect("analysis").paths;
+    assert!(paths.contains(&"payload.items".to_string()));
+    assert!(!paths.iter().any(|path| path.contains('(')));
+}
+
+#[test]
+fn escaped_placeholders_pass_through_unevaluated() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "\\${payload.code}" },
+            { "type": "TextBlock", "text": "Use $${payload.name} or @@{payload.name} for ${payload.name}" },
+            { "type": "TextBlock", "text": "\\@{missing.path} stays" }
+        ]
+    });
+    let mut invocation = base_invocation(card.clone());
+    invocation.payload = json!({ "name": "Ada" });
+    let rendered = handle_invocation(invocation)
+        .expect("render")
+        .rendered_card
+        .expect("card");
+    assert_eq!(rendered["body"][0]["text"], "${payload.code}");
+    assert_eq!(
+        rendered["body"][1]["text"],
+        "Use ${payload.name} or @{payload.name} for Ada"
+    );
+    assert_eq!(rendered["body"][2]["text"], "@{missing.path} stays");
+
+    let analysis = component_adaptive_card::process_message(
+        "analyze-template",
+        &json!({ "card_spec": { "inline_json": card } }).to_string(),
+    );
+    let AdaptiveCardResponse::Success(analysis) = analysis else {
+        panic!("analysis failed");
+    };
+    assert_eq!(
+        analysis.template_analysis.expect("analysis").paths,
+        vec!["payload.name".to_string()]
+    );
+}
+
+#[test]
+fn output_options_control_rendered_card_serialization() {
+    let card = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "${payload.title}", "subtitle": null, "spacing": "${payload.spacing}" },
+            { "type": "Container", "items": [], "style": {} }
+        ],
+        "actions": []
+    });
+    let mut invocation = base_invocation(card);
+    invocation.payload = json!({ "title": "Report", "spacing": null });
+    invocation.output = Some(CardOutputOptions {
+        strip_empty: true,
+        sort_keys: true,
+        pretty: false,
+    });
+    let compact = handle_invocation(invocation.clone()).expect("render");
+    let expected = json!({
+        "type": "AdaptiveCard",
+        "version": "1.5",
+        "body": [
+            { "type": "TextBlock", "text": "Report" },
+            { "type": "Container" }
+        ]
+    });
+    assert_eq!(compact.rendered_card.clone().expect("card"), expected);
+    let text = compact.rendered_card_json.expect("json");
+    assert!(!text.contains('\n'));
+    assert!(text.find("\"body\"") < text.find("\"type\""));
+
+    invocation.output.as_mut().expect("output").pretty = true;
+    let pretty = handle_invocation(invocation).expect("render");
+    let text = pretty.rendered_card_json.expect("json");
+    assert!(text.starts_with("{\n  \"body\": ["));
+    let reparsed: serde_json::Value = serde_json::from_str(&text).expect("json");
+    assert_eq!(Some(reparsed), pretty.rendered_card);
+    assert_eq!(pretty.card_hash, compact.card_hash);
+}
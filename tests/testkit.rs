@@ -0,0 +1,87 @@
+#![cfg(feature = "testkit")]
+
+use std::path::{Path, PathBuf};
+
+use component_adaptive_card::testkit::{self, DiffOptions, strategies};
+use serde_json::json;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/golden")
+}
+
+#[test]
+fn rendered_fixture_matches_golden() {
+    let dir = golden_dir();
+    let card = testkit::render_fixture(
+        dir.join("order.template.json"),
+        Some(&dir.join("order.data.json")),
+    );
+    testkit::assert_golden(&card, dir.join("order.golden.json"));
+}
+
+#[test]
+fn semantic_diff_is_readable_and_order_insensitive_where_allowed() {
+    let expected = json!({
+        "type": "AdaptiveCard",
+        "body": [ { "type": "TextBlock", "text": "Total 1" } ],
+        "actions": [ { "type": "Action.ToggleVisibility", "targetElements": ["a", "b"] } ]
+    });
+    let reordered = json!({
+        "body": [ { "text": "Total 1", "type": "TextBlock" } ],
+        "actions": [ { "type": "Action.ToggleVisibility", "targetElements": ["b", "a"] } ],
+        "type": "AdaptiveCard"
+    });
+    assert!(testkit::semantic_diff(&expected, &reordered, &DiffOptions::default()).is_empty());
+
+    let changed = json!({
+        "type": "AdaptiveCard",
+        "body": [ { "type": "TextBlock", "text": "Total 2", "wrap": true } ],
+        "actions": []
+    });
+    let diffs = testkit::semantic_diff(&expected, &changed, &DiffOptions::default());
+    assert_eq!(
+        diffs,
+        vec![
+            "/actions: expected 1 item(s), got 0",
+            "/body/0/text: expected \"Total 1\", got \"Total 2\"",
+            "/body/0/wrap: unexpected true",
+        ]
+    );
+}
+
+fn form_card() -> serde_json::Value {
+    json!({
+        "type": "AdaptiveCard",
+        "version": "1.6",
+        "body": [
+            { "type": "Input.Text", "id": "name", "isRequired": true },
+            { "type": "Input.Number", "id": "qty", "min": 1, "max": 10 },
+            { "type": "Input.Toggle", "id": "agree", "title": "Agree", "valueOn": "yes", "valueOff": "no" },
+            { "type": "Input.Date", "id": "when" },
+            { "type": "Input.Time", "id": "at" },
+            {
+                "type": "Input.ChoiceSet", "id": "tags", "isMultiSelect": true,
+                "choices": [ { "title": "A", "value": "a" }, { "title": "B", "value": "b" } ]
+            },
+            { "type": "TextBlock", "id": "details", "text": "Hello ${name}", "isVisible": false }
+        ],
+        "actions": [
+            { "type": "Action.Submit", "id": "send", "data": { "kind": "order" } },
+            { "type": "Action.Execute", "id": "approve", "verb": "approve" },
+            { "type": "Action.ToggleVisibility", "id": "more", "targetElements": ["details"] },
+            {
+                "type": "Action.ShowCard", "id": "extra",
+                "card": { "type": "AdaptiveCard", "body": [ { "type": "Input.Text", "id": "note" } ] }
+            }
+        ]
+    })
+}
+
+proptest::proptest! {
+    #[test]
+    fn generated_interactions_never_panic_and_stay_schema_valid(
+        invocation in strategies::arb_interaction_invocation(form_card())
+    ) {
+        let _ = strategies::check_invocation(invocation);
+    }
+}